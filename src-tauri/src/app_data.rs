@@ -0,0 +1,149 @@
+use std::path::{Path, PathBuf};
+
+// Files older versions kept in the data directory, with the directory each belongs in now
+const LEGACY_ENTRIES: [(&str, fn() -> PathBuf); 1] = [("failed_downloads.json", AppData::get_failed_downloads_path)];
+
+pub struct AppData;
+
+impl AppData {
+    /// Database and the bundled yt-dlp: `$XDG_DATA_HOME/ytaudiobar` on Linux
+    pub fn get_data_dir() -> PathBuf {
+        let mut path = dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."));
+        path.push("ytaudiobar");
+        path
+    }
+
+    /// Bookkeeping that should survive restarts but isn't worth backing up:
+    /// `$XDG_STATE_HOME/ytaudiobar` on Linux, the data directory elsewhere
+    pub fn get_state_dir() -> PathBuf {
+        match dirs::state_dir() {
+            Some(mut path) => {
+                path.push("ytaudiobar");
+                path
+            }
+            None => Self::get_data_dir(),
+        }
+    }
+
+    /// Anything that can be rebuilt from the network: `$XDG_CACHE_HOME/ytaudiobar` on Linux
+    pub fn get_cache_dir() -> PathBuf {
+        let mut path = dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from("."));
+        path.push("ytaudiobar");
+        path
+    }
+
+    pub fn get_search_cache_dir() -> PathBuf {
+        Self::get_cache_dir().join("search")
+    }
+
+    pub fn get_thumbnails_dir() -> PathBuf {
+        Self::get_cache_dir().join("thumbnails")
+    }
+
+    /// Compressed audio copied from streams, kept until it becomes a download or the next track
+    pub fn get_stream_cache_dir() -> PathBuf {
+        Self::get_cache_dir().join("streams")
+    }
+
+    pub fn get_failed_downloads_path() -> PathBuf {
+        Self::get_state_dir().join("failed_downloads.json")
+    }
+
+    /// Move caches and state that older versions kept in the data directory to where they
+    /// belong now. Entries already present at the new location are left alone.
+    pub fn migrate_legacy_layout() {
+        let data_dir = Self::get_data_dir();
+        for (name, target) in LEGACY_ENTRIES {
+            let from = data_dir.join(name);
+            let to = target();
+            if from == to || !from.exists() || to.exists() {
+                continue;
+            }
+
+            match move_path(&from, &to) {
+                Ok(()) => println!("📁 Moved {} to {}", from.display(), to.display()),
+                Err(e) => eprintln!("⚠️ Failed to move {} to {}: {}", from.display(), to.display(), e),
+            }
+        }
+    }
+
+    /// Total size in bytes of the files inside `dir` and its subfolders
+    pub fn directory_size(dir: &Path) -> i64 {
+        let mut total = 0i64;
+
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                // Entry metadata doesn't follow symlinks, so linked folders aren't counted twice
+                if let Ok(metadata) = entry.metadata() {
+                    if metadata.is_file() {
+                        total += metadata.len() as i64;
+                    } else if metadata.is_dir() {
+                        total += Self::directory_size(&entry.path());
+                    }
+                }
+            }
+        }
+
+        total
+    }
+
+    /// `directory_size` on a blocking thread, for large folders walked from async code
+    pub async fn directory_size_async(dir: PathBuf) -> i64 {
+        tokio::task::spawn_blocking(move || Self::directory_size(&dir))
+            .await
+            .unwrap_or(0)
+    }
+
+    /// Remove everything inside `dir` but keep the directory itself
+    pub fn clear_directory(dir: &Path) -> Result<(), String> {
+        if !dir.exists() {
+            return Ok(());
+        }
+
+        let entries = std::fs::read_dir(dir).map_err(|e| e.to_string())?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let result = if path.is_dir() {
+                std::fs::remove_dir_all(&path)
+            } else {
+                std::fs::remove_file(&path)
+            };
+            result.map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Renames when possible and copies otherwise, since the cache and state directories can be
+// on a different filesystem than the data directory
+pub fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    copy_path(from, to)?;
+    if from.is_dir() {
+        std::fs::remove_dir_all(from)
+    } else {
+        std::fs::remove_file(from)
+    }
+}
+
+fn copy_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !from.is_dir() {
+        return std::fs::copy(from, to).map(|_| ());
+    }
+
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        copy_path(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
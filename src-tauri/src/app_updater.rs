@@ -0,0 +1,81 @@
+use crate::models::{AppUpdate, AppUpdateProgress};
+use tauri::AppHandle;
+use tauri_plugin_updater::{Update, UpdaterExt};
+use tokio::sync::Mutex;
+
+/// Checks GitHub releases for a newer app version and keeps the found update, and once
+/// downloaded its installer, until it is installed
+pub struct AppUpdater {
+    pending: Mutex<Option<Update>>,
+    downloaded: Mutex<Option<Vec<u8>>>,
+    installed: Mutex<Option<String>>, // version waiting for a restart to apply
+}
+
+impl AppUpdater {
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(None),
+            downloaded: Mutex::new(None),
+            installed: Mutex::new(None),
+        }
+    }
+
+    /// Look for a newer release, returning it with its release notes
+    pub async fn check(&self, app: &AppHandle) -> Result<Option<AppUpdate>, String> {
+        let updater = app.updater().map_err(|e| format!("Updater not available: {}", e))?;
+        let update = updater.check().await.map_err(|e| format!("Failed to check for updates: {}", e))?;
+
+        let info = update.as_ref().map(|update| AppUpdate {
+            version: update.version.clone(),
+            current_version: update.current_version.clone(),
+            notes: update.body.clone(),
+            published_at: update.date.map(|date| date.unix_timestamp()),
+        });
+
+        // A different release replaces whatever was downloaded for the previous one
+        let mut pending = self.pending.lock().await;
+        if pending.as_ref().map(|p| &p.version) != update.as_ref().map(|u| &u.version) {
+            *self.downloaded.lock().await = None;
+        }
+        *pending = update;
+        Ok(info)
+    }
+
+    /// Download the update found by the last check, reporting progress as it arrives
+    pub async fn download(&self, on_progress: impl Fn(AppUpdateProgress)) -> Result<(), String> {
+        let update = self.pending.lock().await.clone().ok_or("No update available, check for updates first")?;
+        if self.downloaded.lock().await.is_some() {
+            return Ok(());
+        }
+
+        let mut downloaded = 0u64;
+        let bytes = update
+            .download(
+                |chunk, total| {
+                    downloaded += chunk as u64;
+                    on_progress(AppUpdateProgress { downloaded, total });
+                },
+                || {},
+            )
+            .await
+            .map_err(|e| format!("Failed to download update: {}", e))?;
+
+        *self.downloaded.lock().await = Some(bytes);
+        Ok(())
+    }
+
+    /// Install the downloaded update; it takes effect when the app restarts
+    pub async fn install(&self) -> Result<(), String> {
+        let update = self.pending.lock().await.clone().ok_or("No update available, check for updates first")?;
+        let bytes = self.downloaded.lock().await.take().ok_or("The update hasn't been downloaded yet")?;
+
+        update.install(bytes).map_err(|e| format!("Failed to install update: {}", e))?;
+        *self.pending.lock().await = None;
+        *self.installed.lock().await = Some(update.version);
+        Ok(())
+    }
+
+    pub async fn installed_version(&self) -> Option<String> {
+        self.installed.lock().await.clone()
+    }
+}
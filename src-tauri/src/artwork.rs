@@ -0,0 +1,247 @@
+use crate::app_data::AppData;
+use crate::models::Track;
+use base64::Engine;
+use image::imageops::FilterType;
+use image::{DynamicImage, RgbImage};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+// Playlist covers are a 2x2 grid of these
+const TILE_SIZE: u32 = 160;
+const COVER_TILES: usize = 4;
+const COVER_JPEG_QUALITY: u8 = 85;
+// Thumbnails are shrunk to this before extracting colors; the palette barely changes and it's far faster
+const PALETTE_SAMPLE_SIZE: u32 = 64;
+
+/// Builds playlist covers from the cached track thumbnails
+pub struct ArtworkManager {
+    generating: Mutex<HashSet<String>>, // playlists whose cover is being built
+}
+
+impl ArtworkManager {
+    pub fn new() -> Self {
+        Self {
+            generating: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The cover on disk for the playlist's current tracks, if it has been built
+    pub fn cached_cover(playlist_id: &str, tracks: &[Track]) -> Option<String> {
+        let sources = cover_sources(tracks);
+        if sources.is_empty() {
+            return None;
+        }
+        let bytes = std::fs::read(cover_path(playlist_id, &sources)).ok()?;
+        Some(data_url(&bytes))
+    }
+
+    /// Builds any missing covers, returning the ids of the playlists that got one
+    pub async fn generate_covers(&self, playlists: Vec<(String, Vec<Track>)>) -> Vec<String> {
+        let mut updated = Vec::new();
+
+        for (playlist_id, tracks) in playlists {
+            if !self.generating.lock().await.insert(playlist_id.clone()) {
+                continue;
+            }
+
+            match build_cover(&playlist_id, &tracks).await {
+                Ok(true) => updated.push(playlist_id.clone()),
+                Ok(false) => {}
+                Err(e) => eprintln!("⚠️ Failed to build cover for playlist {}: {}", playlist_id, e),
+            }
+
+            self.generating.lock().await.remove(&playlist_id);
+        }
+
+        updated
+    }
+}
+
+/// A track's thumbnail, downloaded into the thumbnail cache the first time it's needed
+pub async fn cached_thumbnail(video_id: &str, url: &str) -> Result<Vec<u8>, String> {
+    let path = AppData::get_thumbnails_dir().join(format!("{}.thumb", video_id));
+    if let Ok(bytes) = tokio::fs::read(&path).await {
+        return Ok(bytes);
+    }
+
+    let response = reqwest::get(url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to download thumbnail: {}", e))?;
+    let bytes = response.bytes().await.map_err(|e| e.to_string())?.to_vec();
+
+    tokio::fs::create_dir_all(AppData::get_thumbnails_dir())
+        .await
+        .map_err(|e| e.to_string())?;
+    tokio::fs::write(&path, &bytes).await.map_err(|e| e.to_string())?;
+
+    Ok(bytes)
+}
+
+/// The most common colors of an image as "#rrggbb", most common first, found by median cut
+pub fn dominant_colors(image_bytes: &[u8], count: usize) -> Result<Vec<String>, String> {
+    let image = image::load_from_memory(image_bytes).map_err(|e| format!("Failed to decode thumbnail: {}", e))?;
+    let pixels: Vec<[u8; 3]> = image
+        .resize_exact(PALETTE_SAMPLE_SIZE, PALETTE_SAMPLE_SIZE, FilterType::Triangle)
+        .to_rgb8()
+        .pixels()
+        .map(|p| p.0)
+        .collect();
+
+    // Keep splitting the box with the widest channel range at its median
+    let mut boxes = vec![pixels];
+    while boxes.len() < count {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .map(|(index, pixels)| {
+                let (channel, range) = widest_channel(pixels);
+                (index, channel, range)
+            })
+            .filter(|(_, _, range)| *range > 0)
+            .max_by_key(|(_, _, range)| *range);
+        let Some((index, channel, _)) = widest else {
+            break; // every box is a single color
+        };
+
+        let mut pixels = boxes.swap_remove(index);
+        pixels.sort_unstable_by_key(|p| p[channel]);
+        let upper = pixels.split_off(pixels.len() / 2);
+        boxes.push(pixels);
+        boxes.push(upper);
+    }
+
+    boxes.sort_by_key(|pixels| std::cmp::Reverse(pixels.len()));
+    Ok(boxes
+        .iter()
+        .map(|pixels| {
+            let mut sum = [0u64; 3];
+            for pixel in pixels {
+                for channel in 0..3 {
+                    sum[channel] += pixel[channel] as u64;
+                }
+            }
+            let n = pixels.len().max(1) as u64;
+            format!("#{:02x}{:02x}{:02x}", sum[0] / n, sum[1] / n, sum[2] / n)
+        })
+        .collect())
+}
+
+// (channel index, max - min) of the channel that varies most
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let min = pixels.iter().map(|p| p[channel]).min().unwrap_or(0);
+            let max = pixels.iter().map(|p| p[channel]).max().unwrap_or(0);
+            (channel, max - min)
+        })
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or((0, 0))
+}
+
+// Newest tracks with a thumbnail, as (video id, thumbnail url)
+fn cover_sources(tracks: &[Track]) -> Vec<(String, String)> {
+    let mut seen = HashSet::new();
+    tracks
+        .iter()
+        .filter_map(|t| t.thumbnail_url.as_ref().map(|url| (t.id.clone(), url.clone())))
+        .filter(|(id, _)| seen.insert(id.clone()))
+        .take(COVER_TILES)
+        .collect()
+}
+
+// Named after the tracks it shows, so adding or removing one invalidates the cache
+fn cover_path(playlist_id: &str, sources: &[(String, String)]) -> PathBuf {
+    use sha2::{Digest, Sha256};
+
+    let ids: Vec<&str> = sources.iter().map(|(id, _)| id.as_str()).collect();
+    let digest = format!("{:x}", Sha256::digest(ids.join(",").as_bytes()));
+    AppData::get_thumbnails_dir().join(format!("playlist-{}-{}.jpg", playlist_id, &digest[..16]))
+}
+
+// Writes the cover if it isn't cached yet; Ok(false) when there was nothing to build
+async fn build_cover(playlist_id: &str, tracks: &[Track]) -> Result<bool, String> {
+    let sources = cover_sources(tracks);
+    if sources.is_empty() {
+        return Ok(false);
+    }
+
+    let path = cover_path(playlist_id, &sources);
+    if path.exists() {
+        return Ok(false);
+    }
+
+    // Tracks whose thumbnail is gone from YouTube are left out rather than failing the cover
+    let mut thumbnails = Vec::new();
+    for (video_id, url) in &sources {
+        match cached_thumbnail(video_id, url).await {
+            Ok(bytes) => thumbnails.push(bytes),
+            Err(e) => eprintln!("⚠️ Skipping thumbnail of {}: {}", video_id, e),
+        }
+    }
+    if thumbnails.is_empty() {
+        return Ok(false);
+    }
+
+    let cover = tokio::task::spawn_blocking(move || compose_mosaic(&thumbnails))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    remove_covers(playlist_id);
+    tokio::fs::write(&path, cover).await.map_err(|e| e.to_string())?;
+    println!("🖼️ Built cover for playlist {}", playlist_id);
+
+    Ok(true)
+}
+
+// Lays out up to four thumbnails as a JPEG grid, repeating them diagonally when there are fewer
+fn compose_mosaic(thumbnails: &[Vec<u8>]) -> Result<Vec<u8>, String> {
+    let images = thumbnails
+        .iter()
+        .filter_map(|bytes| image::load_from_memory(bytes).ok())
+        .collect::<Vec<DynamicImage>>();
+
+    let cover = match images.len() {
+        0 => return Err("No thumbnail could be decoded".to_string()),
+        1 => images[0].resize_to_fill(TILE_SIZE * 2, TILE_SIZE * 2, FilterType::Triangle).to_rgb8(),
+        count => {
+            let order: [usize; COVER_TILES] = match count {
+                2 => [0, 1, 1, 0],
+                3 => [0, 1, 2, 0],
+                _ => [0, 1, 2, 3],
+            };
+            let mut cover = RgbImage::new(TILE_SIZE * 2, TILE_SIZE * 2);
+            for (slot, index) in order.iter().enumerate() {
+                let tile = images[*index].resize_to_fill(TILE_SIZE, TILE_SIZE, FilterType::Triangle).to_rgb8();
+                let x = (slot as u32 % 2) * TILE_SIZE;
+                let y = (slot as u32 / 2) * TILE_SIZE;
+                image::imageops::replace(&mut cover, &tile, x as i64, y as i64);
+            }
+            cover
+        }
+    };
+
+    let mut bytes = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut bytes, COVER_JPEG_QUALITY)
+        .encode_image(&cover)
+        .map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+/// Deletes every cover built for the playlist
+pub fn remove_covers(playlist_id: &str) {
+    let prefix = format!("playlist-{}-", playlist_id);
+    if let Ok(entries) = std::fs::read_dir(AppData::get_thumbnails_dir()) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+/// An image the webview can show without fetching anything
+pub fn data_url(jpeg: &[u8]) -> String {
+    format!("data:image/jpeg;base64,{}", base64::engine::general_purpose::STANDARD.encode(jpeg))
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use symphonia::core::codecs::{
+    CodecType, CODEC_TYPE_AAC, CODEC_TYPE_FLAC, CODEC_TYPE_MP3, CODEC_TYPE_OPUS, CODEC_TYPE_PCM_S16LE, CODEC_TYPE_VORBIS,
+};
+
+// Extensions the player may be asked to open, with the codec they usually carry
+const KNOWN_FORMATS: [(&str, &str, CodecType); 8] = [
+    ("m4a", "aac", CODEC_TYPE_AAC),
+    ("aac", "aac", CODEC_TYPE_AAC),
+    ("mp3", "mp3", CODEC_TYPE_MP3),
+    ("ogg", "vorbis", CODEC_TYPE_VORBIS),
+    ("flac", "flac", CODEC_TYPE_FLAC),
+    ("wav", "pcm", CODEC_TYPE_PCM_S16LE),
+    ("webm", "opus", CODEC_TYPE_OPUS),
+    ("opus", "opus", CODEC_TYPE_OPUS),
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DecodeStrategy {
+    Ffmpeg,      // external ffmpeg binary
+    Symphonia,   // built-in decoder, used when ffmpeg is missing or preferred in settings
+    Unsupported, // nothing on this system can decode it
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormatSupport {
+    pub extension: String,
+    pub codec: String,
+    pub strategy: DecodeStrategy,
+    #[serde(default)]
+    pub builtin: bool, // the built-in decoder can play it
+}
+
+/// Which decoders this system has, probed once at startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioCapabilities {
+    pub ffmpeg_version: Option<String>, // None when ffmpeg isn't on PATH
+    pub ffprobe_available: bool,        // needed to check downloads for truncation
+    pub streaming_available: bool,      // YouTube streams need ffmpeg or built-in AAC support
+    pub symphonia_codecs: Vec<String>,
+    pub formats: Vec<FormatSupport>,
+}
+
+impl AudioCapabilities {
+    pub fn detect() -> Self {
+        let ffmpeg_version = Command::new("ffmpeg")
+            .arg("-version")
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8_lossy(&output.stdout).lines().next().map(|l| l.to_string()));
+
+        let ffprobe_available = Command::new("ffprobe")
+            .arg("-version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false);
+
+        let codecs = symphonia::default::get_codecs();
+        let mut symphonia_codecs: Vec<String> = KNOWN_FORMATS
+            .iter()
+            .filter(|(_, _, codec_type)| codecs.get_codec(*codec_type).is_some())
+            .map(|(_, codec, _)| codec.to_string())
+            .collect();
+        symphonia_codecs.dedup();
+
+        let formats = KNOWN_FORMATS
+            .iter()
+            .map(|(extension, codec, codec_type)| {
+                let builtin = codecs.get_codec(*codec_type).is_some();
+                let strategy = if ffmpeg_version.is_some() {
+                    DecodeStrategy::Ffmpeg
+                } else if builtin {
+                    DecodeStrategy::Symphonia
+                } else {
+                    DecodeStrategy::Unsupported
+                };
+                FormatSupport {
+                    extension: extension.to_string(),
+                    codec: codec.to_string(),
+                    strategy,
+                    builtin,
+                }
+            })
+            .collect();
+
+        // Without ffmpeg, streams are fetched as AAC in MP4 for the built-in decoder
+        let builtin_streaming = codecs.get_codec(CODEC_TYPE_AAC).is_some();
+
+        Self {
+            streaming_available: ffmpeg_version.is_some() || builtin_streaming,
+            ffmpeg_version,
+            ffprobe_available,
+            symphonia_codecs,
+            formats,
+        }
+    }
+
+    /// How a local file will be decoded, judged by its extension. With `prefer_builtin` the
+    /// built-in decoder takes every format it supports and ffmpeg is left the rest.
+    pub fn strategy_for(&self, path: &Path, prefer_builtin: bool) -> DecodeStrategy {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+        match self.formats.iter().find(|f| f.extension == extension) {
+            Some(format) if prefer_builtin && format.builtin => DecodeStrategy::Symphonia,
+            Some(format) => format.strategy,
+            // Unknown extensions are left to ffmpeg, which can probe almost anything
+            None if self.ffmpeg_version.is_some() => DecodeStrategy::Ffmpeg,
+            None => DecodeStrategy::Unsupported,
+        }
+    }
+
+    /// How YouTube streams will be decoded
+    pub fn stream_strategy(&self, prefer_builtin: bool) -> DecodeStrategy {
+        let builtin = self.symphonia_codecs.iter().any(|codec| codec == "aac");
+        match (self.ffmpeg_version.is_some(), builtin) {
+            (true, false) => DecodeStrategy::Ffmpeg,
+            (true, true) if !prefer_builtin => DecodeStrategy::Ffmpeg,
+            (_, true) => DecodeStrategy::Symphonia,
+            (false, false) => DecodeStrategy::Unsupported,
+        }
+    }
+
+    /// Explains why a file can't be played and what to install to fix it
+    pub fn unsupported_message(&self, path: &Path) -> String {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+        let codec = self
+            .formats
+            .iter()
+            .find(|f| f.extension == extension)
+            .map(|f| f.codec.clone())
+            .unwrap_or_else(|| "this format".to_string());
+        format!(
+            "Can't play .{} files: the built-in decoder doesn't support {}. Install ffmpeg and add it to PATH to play them.",
+            extension, codec
+        )
+    }
+}
@@ -0,0 +1,346 @@
+use crate::models::{AudioEffects, LimiterSettings};
+use rodio::Source;
+use std::f32::consts::PI;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// EQ band centre frequencies (Hz)
+const BASS_FREQUENCY: f32 = 100.0;
+const MID_FREQUENCY: f32 = 1000.0;
+const TREBLE_FREQUENCY: f32 = 8000.0;
+
+// Crossfeed only bleeds the low end across channels, like sound from speakers reaching both ears
+const CROSSFEED_CUTOFF: f32 = 700.0;
+const CROSSFEED_MAX_LEVEL: f32 = 0.45;
+
+// How many frames pass between checks for updated settings
+const SETTINGS_CHECK_INTERVAL: usize = 1024;
+
+// Gain reduction is instant so no peak gets through, then eases back over this long
+const LIMITER_RELEASE_SECONDS: f32 = 0.25;
+
+#[derive(Clone, Copy)]
+enum FilterKind {
+    LowShelf,
+    Peaking,
+    HighShelf,
+}
+
+// Second-order IIR filter using the RBJ audio EQ cookbook formulas
+#[derive(Clone, Copy, Default)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    fn new(kind: FilterKind, frequency: f32, gain_db: f32, sample_rate: u32) -> Self {
+        let a = 10f32.powf(gain_db / 40.0);
+        let w0 = 2.0 * PI * frequency / sample_rate as f32;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let shelf = 2.0 * a.sqrt() * alpha;
+
+        let (b0, b1, b2, a0, a1, a2) = match kind {
+            FilterKind::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos + shelf),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - shelf),
+                (a + 1.0) + (a - 1.0) * cos + shelf,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - shelf,
+            ),
+            FilterKind::Peaking => (
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+            FilterKind::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos + shelf),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - shelf),
+                (a + 1.0) - (a - 1.0) * cos + shelf,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - shelf,
+            ),
+        };
+
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            ..Default::default()
+        }
+    }
+
+    // Swap in new coefficients while keeping the filter history, so changes don't click
+    fn retune(&mut self, other: Biquad) {
+        self.b0 = other.b0;
+        self.b1 = other.b1;
+        self.b2 = other.b2;
+        self.a1 = other.a1;
+        self.a2 = other.a2;
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+fn eq_filters(effects: &AudioEffects, sample_rate: u32) -> [Biquad; 3] {
+    [
+        Biquad::new(FilterKind::LowShelf, BASS_FREQUENCY, effects.bass_db, sample_rate),
+        Biquad::new(FilterKind::Peaking, MID_FREQUENCY, effects.mid_db, sample_rate),
+        Biquad::new(FilterKind::HighShelf, TREBLE_FREQUENCY, effects.treble_db, sample_rate),
+    ]
+}
+
+/// Applies the 3-band EQ and crossfeed to a stereo source, picking up setting changes while playing
+pub struct EffectsSource<S> {
+    input: S,
+    settings: Arc<Mutex<AudioEffects>>,
+    current: AudioEffects,
+    filters: [[Biquad; 3]; 2], // per channel
+    crossfeed_lowpass: [f32; 2],
+    crossfeed_alpha: f32,
+    pending_right: Option<f32>,
+    frames_until_check: usize,
+}
+
+impl<S> EffectsSource<S>
+where
+    S: Source<Item = f32>,
+{
+    pub fn new(input: S, settings: Arc<Mutex<AudioEffects>>) -> Self {
+        let current = settings.lock().map(|s| *s).unwrap_or_default();
+        let sample_rate = input.sample_rate();
+        let filters = eq_filters(&current, sample_rate);
+        let dt = 1.0 / sample_rate as f32;
+        let rc = 1.0 / (2.0 * PI * CROSSFEED_CUTOFF);
+
+        Self {
+            input,
+            settings,
+            current,
+            filters: [filters, filters],
+            crossfeed_lowpass: [0.0; 2],
+            crossfeed_alpha: dt / (rc + dt),
+            pending_right: None,
+            frames_until_check: SETTINGS_CHECK_INTERVAL,
+        }
+    }
+
+    fn refresh_settings(&mut self) {
+        let Ok(settings) = self.settings.try_lock().map(|s| *s) else {
+            return;
+        };
+        if settings == self.current {
+            return;
+        }
+
+        let filters = eq_filters(&settings, self.input.sample_rate());
+        for channel in self.filters.iter_mut() {
+            for (filter, updated) in channel.iter_mut().zip(filters) {
+                filter.retune(updated);
+            }
+        }
+        self.current = settings;
+    }
+
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let mut frame = [left, right];
+        for (sample, filters) in frame.iter_mut().zip(self.filters.iter_mut()) {
+            for filter in filters.iter_mut() {
+                *sample = filter.process(*sample);
+            }
+        }
+
+        if self.current.crossfeed > 0.0 {
+            for (lowpass, sample) in self.crossfeed_lowpass.iter_mut().zip(frame) {
+                *lowpass += self.crossfeed_alpha * (sample - *lowpass);
+            }
+            let level = self.current.crossfeed.clamp(0.0, 1.0) * CROSSFEED_MAX_LEVEL;
+            let [l, r] = frame;
+            frame = [
+                (l + level * self.crossfeed_lowpass[1]) / (1.0 + level),
+                (r + level * self.crossfeed_lowpass[0]) / (1.0 + level),
+            ];
+        }
+
+        (frame[0], frame[1])
+    }
+}
+
+impl<S> Iterator for EffectsSource<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if let Some(right) = self.pending_right.take() {
+            return Some(right);
+        }
+
+        // Mono or multichannel input passes through untouched
+        if self.input.channels() != 2 {
+            return self.input.next();
+        }
+
+        let left = self.input.next()?;
+        let right = self.input.next().unwrap_or(0.0);
+
+        self.frames_until_check -= 1;
+        if self.frames_until_check == 0 {
+            self.frames_until_check = SETTINGS_CHECK_INTERVAL;
+            self.refresh_settings();
+        }
+
+        let (left, right) = self.process_frame(left, right);
+        self.pending_right = Some(right);
+        Some(left)
+    }
+}
+
+impl<S> Source for EffectsSource<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+/// Holds peaks under a ceiling with instant attack and a smooth release, so a screaming intro
+/// or a loud ad is turned down without clipping or pumping the rest of the track. Output gain
+/// above unity is applied here, before limiting, so a boost can't push peaks past the ceiling.
+pub struct LimiterSource<S> {
+    input: S,
+    settings: Arc<Mutex<LimiterSettings>>,
+    current: LimiterSettings,
+    boost_setting: Arc<Mutex<f32>>,
+    boost: f32,
+    ceiling: f32, // linear
+    gain: f32,
+    release: f32, // per-sample step back towards unity gain
+    samples_until_check: usize,
+}
+
+impl<S> LimiterSource<S>
+where
+    S: Source<Item = f32>,
+{
+    pub fn new(input: S, settings: Arc<Mutex<LimiterSettings>>, boost_setting: Arc<Mutex<f32>>) -> Self {
+        let current = settings.lock().map(|s| *s).unwrap_or_default();
+        let boost = boost_setting.lock().map(|b| *b).unwrap_or(1.0);
+        let samples_per_second = input.sample_rate() as f32 * input.channels() as f32;
+
+        Self {
+            input,
+            settings,
+            current,
+            boost_setting,
+            boost,
+            ceiling: db_to_linear(current.ceiling_db),
+            gain: 1.0,
+            release: 1.0 - (-1.0 / (LIMITER_RELEASE_SECONDS * samples_per_second)).exp(),
+            samples_until_check: SETTINGS_CHECK_INTERVAL,
+        }
+    }
+
+    fn refresh_settings(&mut self) {
+        if let Ok(settings) = self.settings.try_lock().map(|s| *s) {
+            self.current = settings;
+            self.ceiling = db_to_linear(settings.ceiling_db);
+        }
+        if let Ok(boost) = self.boost_setting.try_lock().map(|b| *b) {
+            self.boost = boost;
+        }
+    }
+}
+
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+impl<S> Iterator for LimiterSource<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.input.next()? * self.boost;
+
+        self.samples_until_check -= 1;
+        if self.samples_until_check == 0 {
+            self.samples_until_check = SETTINGS_CHECK_INTERVAL;
+            self.refresh_settings();
+        }
+
+        if !self.current.enabled {
+            self.gain = 1.0;
+            return Some(sample);
+        }
+
+        // Channels share one gain so the stereo image doesn't shift while limiting
+        let target = if sample.abs() > self.ceiling { self.ceiling / sample.abs() } else { 1.0 };
+        if target < self.gain {
+            self.gain = target;
+        } else {
+            self.gain += (target - self.gain) * self.release;
+        }
+
+        Some(sample * self.gain)
+    }
+}
+
+impl<S> Source for LimiterSource<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
@@ -1,23 +1,49 @@
-use crate::models::{AudioState, YTVideoInfo};
+use crate::audio_capabilities::{AudioCapabilities, DecodeStrategy};
+use crate::audio_effects::{EffectsSource, LimiterSource};
+use crate::app_data::AppData;
+use crate::audio_stream::{SourceClock, StreamBuffer};
+use crate::content_classifier;
+use crate::models::{
+    AudioEffects, AudioState, ContentType, LimiterSettings, PlaybackError, PlayerEvent, PlayerEventKind, QuietHours, QuietHoursMode,
+    SpeedSettings, StreamedAudio, TrackChanged, TrackTrim, YTVideoInfo, YtdlpOperation, YtdlpOptions,
+};
+use crate::stream_cache::StreamCache;
 use crate::ytdlp_installer::YTDLPInstaller;
-use rodio::{buffer::SamplesBuffer, OutputStream, Sink, Source};
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::Arc;
-use std::time::Instant;
-use tokio::sync::{mpsc, Mutex};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, Mutex, Semaphore};
 use tauri::{AppHandle, Emitter};
 use std::sync::mpsc as std_mpsc;
 
 // Commands that can be sent to the audio thread
 enum AudioCommand {
-    Play(YTVideoInfo),
-    PlayFromFile(YTVideoInfo, String), // track, file_path
+    Play(YTVideoInfo, Option<String>, f64), // track, resolved stream URL if there is one, start position
+    Warm(String, String), // video ID, resolved stream URL to buffer the start of
+    PlayFromFile(YTVideoInfo, String, f64), // track, file_path, start position
     TogglePlayPause,
     Pause,
-    Stop,
+    Stop,   // back to the start, keeping the track loaded
+    Unload, // stop and free the track's audio
     Seek(f64), // position in seconds
-    SetVolume(f32),
+    Rewind(f64), // seconds back from the current position
+    SetVolume(f32), // output gain
     SetPlaybackRate(f32),
+    SetEffects(AudioEffects),
+    SetLimiter(LimiterSettings),
+    SetOutputDevice(Option<String>), // None follows the system default
+    SetBufferTarget(f64), // seconds buffered ahead before playing
+    SetNetworkOffline(bool),
+    SetDataSaver(bool),
+    SetYtdlpOptions(YtdlpOptions),
+    SetPreferBuiltinDecoder(bool),
+    SetKeepStreamedAudio(bool),
+    PlayJingle(String, oneshot::Sender<Result<bool, String>>), // file_path, told whether it played to the end
 }
 
 pub struct AudioManager {
@@ -26,6 +52,21 @@ pub struct AudioManager {
     app_handle: Arc<Mutex<Option<AppHandle>>>,
     state_change_rx: Arc<Mutex<std_mpsc::Receiver<()>>>,
     track_ended_rx: Arc<Mutex<std_mpsc::Receiver<()>>>,
+    playback_error_rx: Arc<Mutex<std_mpsc::Receiver<PlaybackError>>>,
+    device_change_rx: Arc<Mutex<std_mpsc::Receiver<String>>>,
+    player_event_rx: Arc<Mutex<std_mpsc::Receiver<PlayerEvent>>>,
+    stream_url_refused_rx: Arc<Mutex<std_mpsc::Receiver<String>>>, // video IDs
+    streamed_audio_rx: Arc<Mutex<std_mpsc::Receiver<StreamedAudio>>>,
+    player_events: Arc<Mutex<VecDeque<PlayerEvent>>>, // most recent last
+    speed_settings: Arc<Mutex<SpeedSettings>>,
+    capabilities: Arc<Mutex<AudioCapabilities>>, // filled in by the audio thread at startup
+    prefer_builtin_decoder: Arc<Mutex<bool>>,
+    keep_streamed_audio: Arc<Mutex<bool>>,
+    stream_cache: Arc<StdMutex<Option<StreamCacheSlot>>>, // the current stream's copy, shared with the audio thread
+    quiet_hours: Arc<Mutex<QuietHours>>,
+    volume_range: Arc<Mutex<Option<(f32, f32)>>>, // slider limits while party mode is on
+    trims: Arc<Mutex<HashMap<String, TrackTrim>>>,  // by video ID
+    warming: Semaphore, // stream URLs being resolved for warm()
 }
 
 impl AudioManager {
@@ -33,12 +74,33 @@ impl AudioManager {
         let (command_tx, command_rx) = mpsc::unbounded_channel();
         let (state_change_tx, state_change_rx) = std_mpsc::channel();
         let (track_ended_tx, track_ended_rx) = std_mpsc::channel();
+        let (playback_error_tx, playback_error_rx) = std_mpsc::channel();
+        let (device_change_tx, device_change_rx) = std_mpsc::channel();
+        let (player_event_tx, player_event_rx) = std_mpsc::channel();
+        let (stream_url_refused_tx, stream_url_refused_rx) = std_mpsc::channel();
+        let (streamed_audio_tx, streamed_audio_rx) = std_mpsc::channel();
         let state = Arc::new(Mutex::new(AudioState::default()));
+        let capabilities = Arc::new(Mutex::new(AudioCapabilities::default()));
+        let stream_cache = Arc::new(StdMutex::new(None));
 
         // Spawn dedicated audio thread
         let state_clone = Arc::clone(&state);
+        let capabilities_clone = Arc::clone(&capabilities);
+        let stream_cache_clone = Arc::clone(&stream_cache);
         std::thread::spawn(move || {
-            audio_thread(command_rx, state_clone, state_change_tx, track_ended_tx);
+            audio_thread(
+                command_rx,
+                state_clone,
+                capabilities_clone,
+                state_change_tx,
+                track_ended_tx,
+                playback_error_tx,
+                device_change_tx,
+                player_event_tx,
+                stream_url_refused_tx,
+                stream_cache_clone,
+                streamed_audio_tx,
+            );
         });
 
         Self {
@@ -47,6 +109,21 @@ impl AudioManager {
             app_handle: Arc::new(Mutex::new(None)),
             state_change_rx: Arc::new(Mutex::new(state_change_rx)),
             track_ended_rx: Arc::new(Mutex::new(track_ended_rx)),
+            playback_error_rx: Arc::new(Mutex::new(playback_error_rx)),
+            device_change_rx: Arc::new(Mutex::new(device_change_rx)),
+            player_event_rx: Arc::new(Mutex::new(player_event_rx)),
+            stream_url_refused_rx: Arc::new(Mutex::new(stream_url_refused_rx)),
+            streamed_audio_rx: Arc::new(Mutex::new(streamed_audio_rx)),
+            player_events: Arc::new(Mutex::new(VecDeque::with_capacity(PLAYER_EVENT_LIMIT))),
+            speed_settings: Arc::new(Mutex::new(SpeedSettings::default())),
+            capabilities,
+            prefer_builtin_decoder: Arc::new(Mutex::new(false)),
+            keep_streamed_audio: Arc::new(Mutex::new(false)),
+            stream_cache,
+            quiet_hours: Arc::new(Mutex::new(QuietHours::default())),
+            volume_range: Arc::new(Mutex::new(None)),
+            trims: Arc::new(Mutex::new(HashMap::new())),
+            warming: Semaphore::new(WARM_CONCURRENCY),
         }
     }
 
@@ -57,6 +134,12 @@ impl AudioManager {
         let state = Arc::clone(&self.state);
         let state_change_rx = Arc::clone(&self.state_change_rx);
         let track_ended_rx = Arc::clone(&self.track_ended_rx);
+        let playback_error_rx = Arc::clone(&self.playback_error_rx);
+        let device_change_rx = Arc::clone(&self.device_change_rx);
+        let player_event_rx = Arc::clone(&self.player_event_rx);
+        let stream_url_refused_rx = Arc::clone(&self.stream_url_refused_rx);
+        let streamed_audio_rx = Arc::clone(&self.streamed_audio_rx);
+        let player_events = Arc::clone(&self.player_events);
         let handle_clone = handle.clone();
 
         tokio::spawn(async move {
@@ -83,58 +166,178 @@ impl AudioManager {
                     let _ = handle_clone.emit("track-ended", ());
                 }
 
+                // Check for playback failures reported by the audio thread
+                let playback_error = {
+                    let rx = playback_error_rx.lock().await;
+                    rx.try_recv().ok()
+                };
+
+                if let Some(error) = playback_error {
+                    println!("🔔 Emitting playback-error event for {}", error.track.title);
+                    let _ = handle_clone.emit("playback-error", error);
+                }
+
+                // Check for output device switches (explicit or a new system default)
+                let device_change = {
+                    let rx = device_change_rx.lock().await;
+                    rx.try_recv().ok()
+                };
+
+                if let Some(device) = device_change {
+                    println!("🔔 Emitting output-device-changed event for {}", device);
+                    let _ = handle_clone.emit("output-device-changed", device);
+                }
+
+                // Check for resolved stream URLs YouTube no longer accepts
+                let refused = {
+                    let rx = stream_url_refused_rx.lock().await;
+                    rx.try_recv().ok()
+                };
+
+                if let Some(video_id) = refused {
+                    println!("🔔 Emitting stream-url-refused event for {}", video_id);
+                    let _ = handle_clone.emit("stream-url-refused", video_id);
+                }
+
+                // Check for stream copies a download was waiting on
+                let streamed_audio = {
+                    let rx = streamed_audio_rx.lock().await;
+                    rx.try_recv().ok()
+                };
+
+                if let Some(streamed) = streamed_audio {
+                    println!("🔔 Emitting streamed-audio-finished event for {}", streamed.video_id);
+                    let _ = handle_clone.emit("streamed-audio-finished", streamed);
+                }
+
+                // Keep a rolling log of player events and forward them to the UI
+                let events: Vec<PlayerEvent> = {
+                    let rx = player_event_rx.lock().await;
+                    rx.try_iter().collect()
+                };
+
+                if !events.is_empty() {
+                    let mut log = player_events.lock().await;
+                    for event in events {
+                        let _ = handle_clone.emit("player-event", &event);
+                        if log.len() == PLAYER_EVENT_LIMIT {
+                            log.pop_front();
+                        }
+                        log.push_back(event);
+                    }
+                }
+
                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
             }
         });
     }
 
-    pub async fn play(&self, track: YTVideoInfo) -> Result<(), String> {
+    /// Play a YouTube track. `stream_url` resolves to a direct audio URL to read instead of
+    /// going through yt-dlp, and is awaited only after the player shows the track loading.
+    /// `start_at` starts partway in, e.g. a restored session, instead of at the trimmed start.
+    pub async fn play(
+        &self,
+        track: YTVideoInfo,
+        stream_url: impl Future<Output = Option<String>>,
+        start_at: Option<f64>,
+    ) -> Result<(), String> {
+        self.ensure_playback_allowed().await?;
         println!("🎵 Playing track: {}", track.title);
 
+        let default_rate = self.default_rate_for(&track).await;
+        let trim = self.trims.lock().await.get(&track.id).copied();
+
         // Update state immediately for UI feedback
-        {
+        let position = {
             let mut state = self.state.lock().await;
+            if let Some(rate) = default_rate {
+                state.playback_rate = rate;
+            }
             state.current_track = Some(track.clone());
             state.is_loading = true;
             state.is_playing = false;
-            state.current_position = 0.0;
-            state.duration = track.duration as f64;
-        }
+            apply_trim(&mut state, track.duration as f64, trim);
+            state.current_position = start_position(&state, start_at);
+            state.current_position
+        };
 
         self.emit_state_change().await;
 
+        // Only ffmpeg reading the URL itself has a use for it; the built-in decoder and copies
+        // kept for downloads go through yt-dlp. Another track may have started while it resolved.
+        let use_url = self.streams_with_ffmpeg().await && !*self.keep_streamed_audio.lock().await;
+        let stream_url = if use_url { stream_url.await } else { None };
+        let superseded = self.state.lock().await.current_track.as_ref().map(|t| &t.id) != Some(&track.id);
+        if superseded {
+            return Ok(());
+        }
+
         // Send play command to audio thread
         self.command_tx
-            .send(AudioCommand::Play(track))
+            .send(AudioCommand::Play(track, stream_url, position))
             .map_err(|_| "Audio thread disconnected".to_string())?;
 
         Ok(())
     }
 
-    pub async fn play_from_file(&self, track: YTVideoInfo, file_path: String) -> Result<(), String> {
+    /// Buffer the first few seconds of a track that's likely to be played next, e.g. one the
+    /// pointer is resting on, so `play` can start from them. Only streams ffmpeg reads from a
+    /// resolved URL can be warmed; lookups past WARM_CONCURRENCY are dropped, not queued.
+    pub async fn warm(&self, video_id: String, stream_url: impl Future<Output = Option<String>>) -> Result<(), String> {
+        if !self.streams_with_ffmpeg().await || *self.keep_streamed_audio.lock().await {
+            return Ok(());
+        }
+        if self.state.lock().await.current_track.as_ref().is_some_and(|t| t.id == video_id) {
+            return Ok(());
+        }
+        let Ok(_permit) = self.warming.try_acquire() else {
+            return Ok(());
+        };
+        let Some(stream_url) = stream_url.await else {
+            return Ok(());
+        };
+
+        self.command_tx
+            .send(AudioCommand::Warm(video_id, stream_url))
+            .map_err(|_| "Audio thread disconnected".to_string())
+    }
+
+    pub async fn play_from_file(&self, track: YTVideoInfo, file_path: String, start_at: Option<f64>) -> Result<(), String> {
+        self.ensure_playback_allowed().await?;
         println!("🎵 Playing track from file: {} ({})", track.title, file_path);
 
+        let default_rate = self.default_rate_for(&track).await;
+        let trim = self.trims.lock().await.get(&track.id).copied();
+
         // Update state immediately for UI feedback
-        {
+        let position = {
             let mut state = self.state.lock().await;
+            if let Some(rate) = default_rate {
+                state.playback_rate = rate;
+            }
             state.current_track = Some(track.clone());
             state.is_loading = true;
             state.is_playing = false;
-            state.current_position = 0.0;
-            state.duration = track.duration as f64;
-        }
+            apply_trim(&mut state, track.duration as f64, trim);
+            state.current_position = start_position(&state, start_at);
+            state.current_position
+        };
 
         self.emit_state_change().await;
 
         // Send play from file command to audio thread
         self.command_tx
-            .send(AudioCommand::PlayFromFile(track, file_path))
+            .send(AudioCommand::PlayFromFile(track, file_path, position))
             .map_err(|_| "Audio thread disconnected".to_string())?;
 
         Ok(())
     }
 
     pub async fn toggle_play_pause(&self) -> Result<(), String> {
+        // Pausing is always allowed, resuming isn't during blocking quiet hours
+        if !self.state.lock().await.is_playing {
+            self.ensure_playback_allowed().await?;
+        }
         self.command_tx
             .send(AudioCommand::TogglePlayPause)
             .map_err(|_| "Audio thread disconnected".to_string())?;
@@ -148,6 +351,7 @@ impl AudioManager {
         Ok(())
     }
 
+    /// Stop and rewind, keeping the track's audio so playing again starts instantly
     pub async fn stop(&self) -> Result<(), String> {
         self.command_tx
             .send(AudioCommand::Stop)
@@ -155,9 +359,71 @@ impl AudioManager {
         Ok(())
     }
 
+    /// Stop and free the track's audio, leaving nothing loaded
+    pub async fn unload(&self) -> Result<(), String> {
+        self.command_tx
+            .send(AudioCommand::Unload)
+            .map_err(|_| "Audio thread disconnected".to_string())?;
+        Ok(())
+    }
+
+    /// Lower the output while something else (like a spoken announcement) is heard, or restore it
+    pub async fn set_ducked(&self, ducked: bool) -> Result<(), String> {
+        let gain = self.state.lock().await.output_gain;
+        let gain = if ducked { gain * DUCK_LEVEL } else { gain };
+        self.command_tx
+            .send(AudioCommand::SetVolume(gain))
+            .map_err(|_| "Audio thread disconnected".to_string())?;
+        Ok(())
+    }
+
+    /// Play a short local clip between tracks, returning once it's over; false when playing
+    /// something else cut it short
+    pub async fn play_jingle(&self, file_path: String) -> Result<bool, String> {
+        let (done_tx, done_rx) = oneshot::channel();
+        self.command_tx
+            .send(AudioCommand::PlayJingle(file_path, done_tx))
+            .map_err(|_| "Audio thread disconnected".to_string())?;
+        done_rx.await.map_err(|_| "Audio thread disconnected".to_string())?
+    }
+
+    /// Ramp the output down to silence over `duration`, then stop playback
+    pub async fn fade_out(&self, duration: Duration) -> Result<(), String> {
+        // Only the sink is ramped, so the next track starts at the normal volume again
+        self.ramp_down(duration).await?;
+        self.stop().await
+    }
+
+    /// Ramp the output down to silence over `duration`, then pause so it can be resumed later
+    pub async fn fade_out_and_pause(&self, duration: Duration) -> Result<(), String> {
+        self.ramp_down(duration).await?;
+        self.pause().await?;
+
+        // The paused sink keeps its volume, so put it back for when playback resumes
+        let gain = self.state.lock().await.output_gain;
+        self.command_tx
+            .send(AudioCommand::SetVolume(gain))
+            .map_err(|_| "Audio thread disconnected".to_string())?;
+        Ok(())
+    }
+
+    async fn ramp_down(&self, duration: Duration) -> Result<(), String> {
+        let gain = self.state.lock().await.output_gain;
+        for step in (0..FADE_STEPS).rev() {
+            self.command_tx
+                .send(AudioCommand::SetVolume(gain * step as f32 / FADE_STEPS as f32))
+                .map_err(|_| "Audio thread disconnected".to_string())?;
+            tokio::time::sleep(duration / FADE_STEPS).await;
+        }
+        Ok(())
+    }
+
     pub async fn seek(&self, position: f64) -> Result<(), String> {
-        let duration = self.state.lock().await.duration;
-        let position = position.min(duration).max(0.0);
+        let (start, duration) = {
+            let state = self.state.lock().await;
+            (state.trim_start, state.duration)
+        };
+        let position = position.min(duration).max(start);
 
         // Send seek command to audio thread
         self.command_tx
@@ -167,21 +433,145 @@ impl AudioManager {
         Ok(())
     }
 
+    /// Jump back `seconds` from the live playback position
+    pub async fn replay(&self, seconds: f64) -> Result<(), String> {
+        self.command_tx
+            .send(AudioCommand::Rewind(seconds.max(0.0)))
+            .map_err(|_| "Audio thread disconnected".to_string())?;
+        Ok(())
+    }
+
     pub async fn set_volume(&self, volume: f32) -> Result<(), String> {
-        let volume = volume.max(0.0).min(1.0);
+        let volume = match *self.volume_range.lock().await {
+            Some((min, max)) => volume.clamp(min, max),
+            None => volume.max(0.0).min(1.0),
+        };
+
+        // Quiet hours cap what's played, not the slider itself
+        let ceiling = self.volume_ceiling().await;
 
         // Update state
-        self.state.lock().await.volume = volume;
+        let gain = {
+            let mut state = self.state.lock().await;
+            state.volume = volume;
+            state.output_gain = perceptual_gain(volume.min(ceiling), state.max_gain);
+            state.output_gain
+        };
 
         // Send to audio thread
         self.command_tx
-            .send(AudioCommand::SetVolume(volume))
+            .send(AudioCommand::SetVolume(gain))
+            .map_err(|_| "Audio thread disconnected".to_string())?;
+
+        self.emit_state_change().await;
+        Ok(())
+    }
+
+    /// Set the gain reached at full volume; values above 1.0 boost quiet sources
+    pub async fn set_max_gain(&self, max_gain: f32) -> Result<(), String> {
+        let max_gain = max_gain.clamp(MIN_GAIN_LIMIT, MAX_GAIN_LIMIT);
+        self.state.lock().await.max_gain = max_gain;
+
+        // Re-apply the current slider position against the new ceiling
+        let volume = self.state.lock().await.volume;
+        self.set_volume(volume).await
+    }
+
+    pub async fn get_quiet_hours(&self) -> QuietHours {
+        self.quiet_hours.lock().await.clone()
+    }
+
+    pub async fn set_quiet_hours(&self, quiet_hours: QuietHours) -> Result<(), String> {
+        if quiet_hours.window().is_none() {
+            return Err("Quiet hours need start and end times as HH:MM".to_string());
+        }
+        *self.quiet_hours.lock().await = quiet_hours;
+        self.refresh_quiet_hours().await
+    }
+
+    /// Re-evaluate quiet hours against the clock, capping the volume or pausing as they start and end
+    pub async fn refresh_quiet_hours(&self) -> Result<(), String> {
+        let quiet_hours = self.quiet_hours.lock().await.clone();
+        let active = quiet_hours.is_active_at(chrono::Local::now().time());
+
+        let (was_active, is_playing, volume) = {
+            let mut state = self.state.lock().await;
+            let was_active = state.quiet_hours_active;
+            state.quiet_hours_active = active;
+            (was_active, state.is_playing, state.volume)
+        };
+
+        if active != was_active {
+            println!("{}", if active { "🌙 Quiet hours started" } else { "☀️ Quiet hours ended" });
+        }
+
+        if active && is_playing && quiet_hours.mode == QuietHoursMode::Block {
+            self.pause().await?;
+        }
+
+        // Also picks up a changed cap while quiet hours are already running
+        self.set_volume(volume).await
+    }
+
+    async fn ensure_playback_allowed(&self) -> Result<(), String> {
+        let quiet_hours = self.quiet_hours.lock().await.clone();
+        if quiet_hours.mode == QuietHoursMode::Block && quiet_hours.is_active_at(chrono::Local::now().time()) {
+            return Err(format!("Playback is blocked during quiet hours ({} to {})", quiet_hours.start, quiet_hours.end));
+        }
+        Ok(())
+    }
+
+    // Highest slider position that's actually played right now
+    async fn volume_ceiling(&self) -> f32 {
+        let quiet_cap = {
+            let quiet_hours = self.quiet_hours.lock().await;
+            match quiet_hours.mode {
+                QuietHoursMode::CapVolume(cap) if quiet_hours.is_active_at(chrono::Local::now().time()) => cap.clamp(0.0, 1.0),
+                _ => 1.0,
+            }
+        };
+        quiet_cap.min(self.state.lock().await.max_volume)
+    }
+
+    /// Never play louder than this slider position, whatever the slider says
+    pub async fn set_max_volume(&self, limit: f32) -> Result<(), String> {
+        let volume = {
+            let mut state = self.state.lock().await;
+            state.max_volume = limit.clamp(MIN_VOLUME_LIMIT, 1.0);
+            state.volume
+        };
+        self.set_volume(volume).await
+    }
+
+    pub async fn set_limiter(&self, limiter: LimiterSettings) -> Result<(), String> {
+        let limiter = LimiterSettings {
+            ceiling_db: limiter.ceiling_db.clamp(MIN_LIMITER_CEILING_DB, 0.0),
+            ..limiter
+        };
+        self.state.lock().await.limiter = limiter;
+
+        self.command_tx
+            .send(AudioCommand::SetLimiter(limiter))
             .map_err(|_| "Audio thread disconnected".to_string())?;
 
         self.emit_state_change().await;
         Ok(())
     }
 
+    /// Hold the slider within `range`, or free it again with `None`
+    pub async fn set_volume_range(&self, range: Option<(f32, f32)>) -> Result<(), String> {
+        *self.volume_range.lock().await = range;
+        let volume = self.state.lock().await.volume;
+        self.set_volume(volume).await
+    }
+
+    /// Nudge the volume by `delta` (e.g. 0.05 for +5%), returning the new volume
+    pub async fn adjust_volume(&self, delta: f32) -> Result<f32, String> {
+        let volume = (self.state.lock().await.volume + delta).clamp(0.0, 1.0);
+        self.set_volume(volume).await?;
+        Ok(self.state.lock().await.volume)
+    }
+
     pub async fn set_playback_rate(&self, rate: f32) -> Result<(), String> {
         let rate = rate.max(0.25).min(2.0);
 
@@ -197,85 +587,653 @@ impl AudioManager {
         Ok(())
     }
 
-    pub async fn get_state(&self) -> AudioState {
-        self.state.lock().await.clone()
+    pub async fn set_effects(&self, effects: AudioEffects) -> Result<(), String> {
+        let effects = AudioEffects {
+            bass_db: effects.bass_db.clamp(-12.0, 12.0),
+            mid_db: effects.mid_db.clamp(-12.0, 12.0),
+            treble_db: effects.treble_db.clamp(-12.0, 12.0),
+            crossfeed: effects.crossfeed.clamp(0.0, 1.0),
+        };
+
+        self.state.lock().await.effects = effects;
+
+        self.command_tx
+            .send(AudioCommand::SetEffects(effects))
+            .map_err(|_| "Audio thread disconnected".to_string())?;
+
+        self.emit_state_change().await;
+        Ok(())
+    }
+
+    pub async fn set_buffer_target(&self, seconds: f64) -> Result<(), String> {
+        let seconds = seconds.clamp(MIN_BUFFER_TARGET, MAX_BUFFER_TARGET);
+        self.state.lock().await.buffer_target = seconds;
+
+        self.command_tx
+            .send(AudioCommand::SetBufferTarget(seconds))
+            .map_err(|_| "Audio thread disconnected".to_string())?;
+
+        self.emit_state_change().await;
+        Ok(())
+    }
+
+    /// Told by the connectivity monitor; streams cut off while offline reconnect once back online
+    pub async fn set_network_offline(&self, offline: bool) -> Result<(), String> {
+        self.state.lock().await.network_offline = offline;
+
+        self.command_tx
+            .send(AudioCommand::SetNetworkOffline(offline))
+            .map_err(|_| "Audio thread disconnected".to_string())?;
+
+        self.emit_state_change().await;
+        Ok(())
+    }
+
+    /// Streams started while data saver is on fetch a low bitrate format
+    pub async fn set_data_saver(&self, enabled: bool) -> Result<(), String> {
+        self.state.lock().await.data_saver = enabled;
+
+        self.command_tx
+            .send(AudioCommand::SetDataSaver(enabled))
+            .map_err(|_| "Audio thread disconnected".to_string())?;
+
+        self.emit_state_change().await;
+        Ok(())
+    }
+
+    /// Decode with symphonia wherever it can, keeping ffmpeg for formats it can't handle
+    pub async fn set_prefer_builtin_decoder(&self, enabled: bool) -> Result<(), String> {
+        *self.prefer_builtin_decoder.lock().await = enabled;
+
+        self.command_tx
+            .send(AudioCommand::SetPreferBuiltinDecoder(enabled))
+            .map_err(|_| "Audio thread disconnected".to_string())
+    }
+
+    /// Keep a copy of each streamed track's audio so downloading it doesn't fetch it again
+    pub async fn set_keep_streamed_audio(&self, enabled: bool) -> Result<(), String> {
+        *self.keep_streamed_audio.lock().await = enabled;
+
+        self.command_tx
+            .send(AudioCommand::SetKeepStreamedAudio(enabled))
+            .map_err(|_| "Audio thread disconnected".to_string())
+    }
+
+    /// Hand the current stream's copy of `video_id` to a download, if there is one. A copy
+    /// still downloading is reported with a "streamed-audio-finished" event once it's done.
+    pub fn claim_streamed_audio(&self, video_id: &str) -> Option<StreamedAudioClaim> {
+        let mut slot = self.stream_cache.lock().unwrap();
+        let entry = slot.as_mut().filter(|entry| entry.video_id == video_id && !entry.claimed)?;
+        match entry.path.take() {
+            Some(path) => {
+                *slot = None;
+                Some(StreamedAudioClaim::Ready(path))
+            }
+            None => {
+                entry.claimed = true;
+                Some(StreamedAudioClaim::Pending)
+            }
+        }
+    }
+
+    async fn streams_with_ffmpeg(&self) -> bool {
+        let prefer_builtin = *self.prefer_builtin_decoder.lock().await;
+        self.capabilities.lock().await.stream_strategy(prefer_builtin) == DecodeStrategy::Ffmpeg
+    }
+
+    /// Advanced yt-dlp options used by streams started from now on
+    pub fn set_ytdlp_options(&self, options: YtdlpOptions) -> Result<(), String> {
+        self.command_tx
+            .send(AudioCommand::SetYtdlpOptions(options))
+            .map_err(|_| "Audio thread disconnected".to_string())
+    }
+
+    pub async fn set_output_device(&self, device: Option<String>) -> Result<(), String> {
+        self.command_tx
+            .send(AudioCommand::SetOutputDevice(device))
+            .map_err(|_| "Audio thread disconnected".to_string())?;
+        Ok(())
+    }
+
+    pub fn list_output_devices() -> Vec<String> {
+        rodio::cpal::default_host()
+            .output_devices()
+            .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+            .unwrap_or_default()
+    }
+
+    pub async fn set_speed_settings(&self, settings: SpeedSettings) {
+        *self.speed_settings.lock().await = settings;
+    }
+
+    pub async fn get_speed_settings(&self) -> SpeedSettings {
+        self.speed_settings.lock().await.clone()
+    }
+
+    // Speed a track should start at, or None to keep the current speed
+    async fn default_rate_for(&self, track: &YTVideoInfo) -> Option<f32> {
+        let settings = self.speed_settings.lock().await;
+        if !settings.auto_speed {
+            return None;
+        }
+
+        match content_classifier::classify(track) {
+            ContentType::Music => Some(1.0),
+            ContentType::Speech => Some(settings.speech_rate.clamp(0.25, 2.0)),
+        }
+    }
+
+    pub async fn set_trims(&self, trims: HashMap<String, TrackTrim>) {
+        *self.trims.lock().await = trims;
+    }
+
+    /// Trim a track's intro and outro, or clear its trim with None. Takes effect right away
+    /// when it's the current track.
+    pub async fn set_trim(&self, video_id: &str, trim: Option<TrackTrim>) {
+        match trim {
+            Some(trim) => self.trims.lock().await.insert(video_id.to_string(), trim),
+            None => self.trims.lock().await.remove(video_id),
+        };
+
+        let changed = {
+            let mut state = self.state.lock().await;
+            let duration = state.current_track.as_ref().filter(|t| t.id == video_id).map(|t| t.duration as f64);
+            if let Some(duration) = duration {
+                apply_trim(&mut state, duration, trim);
+            }
+            duration.is_some()
+        };
+        if changed {
+            self.emit_state_change().await;
+        }
+    }
+
+    /// Recent player events, oldest first
+    pub async fn get_player_events(&self) -> Vec<PlayerEvent> {
+        self.player_events.lock().await.iter().cloned().collect()
+    }
+
+    pub async fn get_capabilities(&self) -> AudioCapabilities {
+        self.capabilities.lock().await.clone()
+    }
+
+    pub async fn get_state(&self) -> AudioState {
+        self.state.lock().await.clone()
+    }
+
+    /// Emit `track-changed` for a track that just started, with what started it
+    pub async fn announce_track_change(&self, change: TrackChanged) {
+        if let Some(handle) = self.app_handle.lock().await.as_ref() {
+            let _ = handle.emit("track-changed", change);
+        }
+    }
+
+    async fn emit_state_change(&self) {
+        let app_guard = self.app_handle.lock().await;
+        if let Some(handle) = app_guard.as_ref() {
+            let state = self.state.lock().await;
+            let _ = handle.emit("playback-state-changed", state.clone());
+        }
+    }
+}
+
+// Audio playback constants
+const SAMPLE_RATE: u32 = 44100;
+const CHANNELS: u16 = 2;
+
+// The volume slider spans this many dB, so equal slider steps sound like equal loudness steps
+const VOLUME_RANGE_DB: f32 = 50.0;
+// Below this slider position the gain fades linearly down to true silence
+const VOLUME_FADE_THRESHOLD: f32 = 0.05;
+const MIN_GAIN_LIMIT: f32 = 0.1;
+const MAX_GAIN_LIMIT: f32 = 2.0;
+// A volume cap below this would make the player unusably quiet
+const MIN_VOLUME_LIMIT: f32 = 0.1;
+// Lowest limiter ceiling, in dBFS
+const MIN_LIMITER_CEILING_DB: f32 = -30.0;
+
+// Volume steps used when fading out
+const FADE_STEPS: u32 = 40;
+// Fraction of the normal gain kept while ducked
+const DUCK_LEVEL: f32 = 0.25;
+
+fn perceptual_gain(volume: f32, max_gain: f32) -> f32 {
+    if volume <= 0.0 {
+        return 0.0;
+    }
+
+    let gain = 10f32.powf(VOLUME_RANGE_DB * (volume - 1.0) / 20.0);
+    let fade = (volume / VOLUME_FADE_THRESHOLD).min(1.0);
+    gain * fade * max_gain
+}
+
+// How often the system default output device is checked for changes
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+// Streams start once this much audio is buffered, and resume after an underrun once it has refilled
+const MIN_BUFFER_TARGET: f64 = 1.0;
+const MAX_BUFFER_TARGET: f64 = 60.0;
+// Playback pauses to rebuffer when it gets this close to the end of the downloaded audio
+const UNDERRUN_MARGIN: f64 = 0.5;
+// Minimum time between attempts to refetch a stream cut off by a dropped connection
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(15);
+// Lowest-bitrate audio that still sounds acceptable (~48-70 kbps opus) for data saver
+const DATA_SAVER_FORMAT: &str = "bestaudio[abr<=70]/worstaudio";
+
+// The built-in decoder has no opus, so its streams prefer AAC in MP4 over the usual webm
+const BUILTIN_STREAM_FORMAT: &str = "bestaudio[ext=m4a]/bestaudio";
+const BUILTIN_DATA_SAVER_FORMAT: &str = "worstaudio[ext=m4a]/bestaudio[abr<=70]/worstaudio";
+
+/// yt-dlp format selector for streamed tracks
+pub fn stream_format(data_saver: bool) -> &'static str {
+    if data_saver { DATA_SAVER_FORMAT } else { "bestaudio" }
+}
+
+// How many player events are kept for get_player_events
+const PLAYER_EVENT_LIMIT: usize = 500;
+
+// Warmed streams stop after this much audio, and are dropped when unplayed for WARM_STREAM_TTL
+const WARM_SECONDS: f64 = 5.0;
+const WARM_STREAM_LIMIT: usize = 3;
+const WARM_STREAM_TTL: Duration = Duration::from_secs(120);
+// Stream URLs resolved at once for warming
+const WARM_CONCURRENCY: usize = 2;
+
+fn default_output_name() -> Option<String> {
+    rodio::cpal::default_host()
+        .default_output_device()
+        .and_then(|device| device.name().ok())
+}
+
+// Opens whichever output device works, for when there's none at all. Devices held exclusively
+// by another app (ASIO, WASAPI exclusive mode) fail to open and are skipped.
+fn open_any_output() -> Option<(OutputStream, OutputStreamHandle, String)> {
+    open_output(None).or_else(|| {
+        rodio::cpal::default_host().output_devices().ok()?.find_map(|device| {
+            let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+            let (stream, handle) = OutputStream::try_from_device(&device).ok()?;
+            Some((stream, handle, device_name))
+        })
+    })
+}
+
+// Turning down happens on the sink, boosting inside the source chain before the limiter
+fn set_output_gain(sink: &Sink, boost: &StdMutex<f32>, gain: f32) {
+    sink.set_volume(gain.min(1.0));
+    if let Ok(mut boost) = boost.lock() {
+        *boost = gain.max(1.0);
+    }
+}
+
+fn new_sink(handle: &Option<OutputStreamHandle>) -> Result<Sink, String> {
+    let handle = handle.as_ref().ok_or("No audio output available")?;
+    Sink::try_new(handle).map_err(|e| e.to_string())
+}
+
+// Opens the named output device, or the system default when no name is given
+fn open_output(name: Option<&str>) -> Option<(OutputStream, OutputStreamHandle, String)> {
+    let host = rodio::cpal::default_host();
+    let device = match name {
+        Some(name) => host
+            .output_devices()
+            .ok()?
+            .find(|d| d.name().map(|n| n == name).unwrap_or(false)),
+        None => host.default_output_device(),
+    }?;
+
+    let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+    let (stream, handle) = OutputStream::try_from_device(&device).ok()?;
+    Some((stream, handle, device_name))
+}
+
+// Decodes a local file the way the system can, retrying with ffmpeg if the built-in decoder fails
+fn decode_local_file(capabilities: &AudioCapabilities, file_path: &str, prefer_builtin: bool) -> Result<StreamBuffer, String> {
+    let path = Path::new(file_path);
+    match capabilities.strategy_for(path, prefer_builtin) {
+        DecodeStrategy::Ffmpeg => decode_file_with_ffmpeg(file_path),
+        DecodeStrategy::Symphonia => {
+            println!("🦀 Decoding with the built-in decoder");
+            StreamBuffer::decode_file(path).or_else(|e| match capabilities.ffmpeg_version {
+                Some(_) => {
+                    eprintln!("⚠️ Built-in decoder failed ({}), falling back to ffmpeg", e);
+                    decode_file_with_ffmpeg(file_path)
+                }
+                None => Err(e),
+            })
+        }
+        DecodeStrategy::Unsupported => Err(capabilities.unsupported_message(path)),
+    }
+}
+
+// Converts a local file to raw PCM with ffmpeg
+fn decode_file_with_ffmpeg(file_path: &str) -> Result<StreamBuffer, String> {
+    let ffmpeg_child = Command::new("ffmpeg")
+        .args([
+            "-i", file_path,
+            "-f", "s16le",
+            "-acodec", "pcm_s16le",
+            "-ar", &SAMPLE_RATE.to_string(),
+            "-ac", &CHANNELS.to_string(),
+            "-loglevel", "error",
+            "pipe:1",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg on local file: {}", e))?;
+
+    StreamBuffer::spawn(ffmpeg_child, None, None, CHANNELS, SAMPLE_RATE)
+}
+
+// A jingle playing between tracks, with whoever waits for it to end
+type PlayingJingle = (Sink, StreamBuffer, oneshot::Sender<Result<bool, String>>);
+
+// Stops the jingle, if any, telling its waiter whether it had played to the end
+fn finish_jingle(jingle: &mut Option<PlayingJingle>, completed: bool) {
+    if let Some((sink, stream, done)) = jingle.take() {
+        sink.stop();
+        stream.cancel();
+        let _ = done.send(Ok(completed));
+    }
+}
+
+// Starts decoding a YouTube track to raw PCM. With ffmpeg it reads an already resolved URL when
+// there is one and goes through the yt-dlp + ffmpeg pipeline otherwise. A `cache` gets a copy of
+// what yt-dlp downloads, which ffmpeg reading the URL itself can't give.
+fn spawn_stream(
+    track: &YTVideoInfo,
+    strategy: DecodeStrategy,
+    stream_url: Option<&str>,
+    cache: Option<StreamCache>,
+    data_saver: bool,
+    options: &YtdlpOptions,
+) -> Result<StreamBuffer, String> {
+    if strategy == DecodeStrategy::Symphonia {
+        return spawn_builtin_stream(track, cache, data_saver, options);
+    }
+    if let (Some(stream_url), None) = (stream_url, &cache) {
+        return spawn_url_stream(stream_url, options);
+    }
+
+    let video_url = format!("https://www.youtube.com/watch?v={}", track.id);
+    println!("📥 Fetching audio via yt-dlp + ffmpeg pipeline...");
+
+    // Get yt-dlp path
+    let ytdlp_path = YTDLPInstaller::get_ytdlp_path();
+
+    // Use yt-dlp to pipe audio through ffmpeg to get raw PCM
+    let format = stream_format(data_saver);
+    let mut ytdlp_child = Command::new(&ytdlp_path)
+        .args([
+            "-f", format,
+            "-o", "-",
+            "--no-warnings",
+            "--quiet",
+            &video_url,
+        ])
+        .args(options.args(YtdlpOperation::Stream))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+
+    let mut ytdlp_stdout = Some(
+        ytdlp_child
+            .stdout
+            .take()
+            .ok_or_else(|| "Failed to capture yt-dlp stdout".to_string())?,
+    );
+
+    // Pipe yt-dlp output through ffmpeg to convert to raw PCM, copying it on the way when caching
+    let ffmpeg_input = match &cache {
+        Some(_) => Stdio::piped(),
+        None => Stdio::from(ytdlp_stdout.take()),
+    };
+    let mut ffmpeg_child = match Command::new("ffmpeg")
+        .args([
+            "-i", "pipe:0",
+            "-f", "s16le",
+            "-acodec", "pcm_s16le",
+            "-ar", &SAMPLE_RATE.to_string(),
+            "-ac", &CHANNELS.to_string(),
+            "-loglevel", "error",
+            "pipe:1",
+        ])
+        .stdin(ffmpeg_input)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            let _ = ytdlp_child.kill();
+            eprintln!("Make sure ffmpeg is installed and in PATH");
+            return Err(format!("Failed to run ffmpeg: {}", e));
+        }
+    };
+
+    if let (Some(cache), Some(ytdlp_stdout), Some(mut ffmpeg_stdin)) = (&cache, ytdlp_stdout, ffmpeg_child.stdin.take()) {
+        let mut source = cache.tee(ytdlp_stdout);
+        std::thread::spawn(move || {
+            // Ends when yt-dlp finishes or ffmpeg goes away; dropping stdin lets ffmpeg finish
+            let _ = std::io::copy(&mut source, &mut ffmpeg_stdin);
+        });
+    }
+
+    StreamBuffer::spawn(ffmpeg_child, Some(ytdlp_child), cache, CHANNELS, SAMPLE_RATE)
+}
+
+// Pipes yt-dlp's download straight into the built-in decoder, no ffmpeg involved
+fn spawn_builtin_stream(
+    track: &YTVideoInfo,
+    cache: Option<StreamCache>,
+    data_saver: bool,
+    options: &YtdlpOptions,
+) -> Result<StreamBuffer, String> {
+    let video_url = format!("https://www.youtube.com/watch?v={}", track.id);
+    println!("📥 Fetching audio via yt-dlp for the built-in decoder...");
+
+    let format = if data_saver { BUILTIN_DATA_SAVER_FORMAT } else { BUILTIN_STREAM_FORMAT };
+    let ytdlp_child = Command::new(YTDLPInstaller::get_ytdlp_path())
+        .args([
+            "-f", format,
+            "-o", "-",
+            "--no-warnings",
+            "--quiet",
+            &video_url,
+        ])
+        .args(options.args(YtdlpOperation::Stream))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+
+    StreamBuffer::decode_pipe(ytdlp_child, None, cache)
+}
+
+// Has ffmpeg fetch a resolved googlevideo URL itself, skipping yt-dlp's extraction.
+// stderr is kept so a 403 from an expired URL can be told apart from other failures.
+fn spawn_url_stream(stream_url: &str, options: &YtdlpOptions) -> Result<StreamBuffer, String> {
+    println!("📥 Fetching audio from cached stream URL...");
+
+    let mut command = Command::new("ffmpeg");
+    if let Some(user_agent) = &options.user_agent {
+        command.args(["-user_agent", user_agent]);
+    }
+    let ffmpeg_child = command
+        .args([
+            "-i", stream_url,
+            "-f", "s16le",
+            "-acodec", "pcm_s16le",
+            "-ar", &SAMPLE_RATE.to_string(),
+            "-ac", &CHANNELS.to_string(),
+            "-loglevel", "error",
+            "pipe:1",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+
+    StreamBuffer::spawn(ffmpeg_child, None, None, CHANNELS, SAMPLE_RATE)
+}
+
+// Starts of tracks buffered ahead of being played, oldest first
+#[derive(Default)]
+struct WarmStreams {
+    streams: VecDeque<(String, StreamBuffer, Instant)>, // video ID, held stream, when it was started
+}
+
+impl WarmStreams {
+    fn contains(&self, video_id: &str) -> bool {
+        self.streams.iter().any(|(id, _, _)| id == video_id)
+    }
+
+    fn insert(&mut self, video_id: String, stream: StreamBuffer) {
+        self.streams.push_back((video_id, stream, Instant::now()));
+        while self.streams.len() > WARM_STREAM_LIMIT {
+            if let Some((_, stream, _)) = self.streams.pop_front() {
+                stream.cancel();
+            }
+        }
+    }
+
+    // A warmed stream that has failed since is dropped, the track is streamed afresh instead
+    fn take(&mut self, video_id: &str) -> Option<StreamBuffer> {
+        let index = self.streams.iter().position(|(id, _, _)| id == video_id)?;
+        let (_, stream, _) = self.streams.remove(index)?;
+        if stream.error().is_some() {
+            stream.cancel();
+            return None;
+        }
+        Some(stream)
+    }
+
+    // Held streams keep a connection open, so ones nobody played don't linger
+    fn expire(&mut self) {
+        self.streams.retain(|(_, stream, started)| {
+            let expired = started.elapsed() >= WARM_STREAM_TTL;
+            if expired {
+                stream.cancel();
+            }
+            !expired
+        });
+    }
+
+    // For when the settings they were started with changed
+    fn clear(&mut self) {
+        for (_, stream, _) in self.streams.drain(..) {
+            stream.cancel();
+        }
+    }
+}
+
+// Copy of the current stream's audio, with its path once all of it is on disk
+struct StreamCacheSlot {
+    video_id: String,
+    path: Option<PathBuf>,
+    claimed: bool, // a download is waiting for it
+}
+
+/// A stream copy handed to a download
+pub enum StreamedAudioClaim {
+    Ready(PathBuf),
+    Pending, // still downloading
+}
+
+// Starts a copy of the stream about to be fetched for `video_id`, when copies are kept
+fn open_stream_cache(enabled: bool, video_id: &str) -> Option<StreamCache> {
+    if !enabled {
+        return None;
     }
+    StreamCache::create(&AppData::get_stream_cache_dir(), video_id)
+        .map_err(|e| eprintln!("⚠️ Can't keep a copy of the stream: {}", e))
+        .ok()
+}
 
-    async fn emit_state_change(&self) {
-        let app_guard = self.app_handle.lock().await;
-        if let Some(handle) = app_guard.as_ref() {
-            let state = self.state.lock().await;
-            let _ = handle.emit("playback-state-changed", state.clone());
+// Lets go of the previous stream's copy: an unclaimed file is deleted, and a download still
+// waiting on an unfinished one is told it won't arrive
+fn release_stream_cache(slot: &StdMutex<Option<StreamCacheSlot>>, streamed_audio_tx: &std_mpsc::Sender<StreamedAudio>) {
+    let Some(entry) = slot.lock().unwrap().take() else {
+        return;
+    };
+    match (entry.path, entry.claimed) {
+        (Some(path), false) => {
+            let _ = std::fs::remove_file(path);
         }
+        (None, true) => {
+            let _ = streamed_audio_tx.send(StreamedAudio { video_id: entry.video_id, path: None });
+        }
+        _ => {}
     }
 }
 
-// Audio playback constants
-const SAMPLE_RATE: u32 = 44100;
-const CHANNELS: u16 = 2;
+// A track waiting for enough audio to start playing at `position`
+struct PendingStart {
+    track: YTVideoInfo,
+    requested: Instant,
+    position: f64,
+}
 
-// Tracks playback position using elapsed time
-struct PlaybackTimer {
-    start_instant: Option<Instant>,
-    start_position: f64,
-    playback_rate: f32,
+// The reported duration ends at a trimmed outro, so progress, seeking and auto-advance stop there
+fn apply_trim(state: &mut AudioState, duration: f64, trim: Option<TrackTrim>) {
+    state.trim_start = trim.map_or(0.0, |trim| trim.start);
+    state.trim_end = trim.and_then(|trim| trim.end);
+    state.duration = state.trim_end.unwrap_or(duration);
 }
 
-impl PlaybackTimer {
-    fn new() -> Self {
-        Self {
-            start_instant: None,
-            start_position: 0.0,
-            playback_rate: 1.0,
-        }
-    }
+// Where a new track starts: the requested position kept within its trim, or the trimmed start
+fn start_position(state: &AudioState, start_at: Option<f64>) -> f64 {
+    start_at.map_or(state.trim_start, |position| position.min(state.duration).max(state.trim_start))
+}
+
+// A new track starts out buffering with nothing downloaded yet
+fn set_buffering_start(state: &Mutex<AudioState>) {
+    let mut state_guard = state.blocking_lock();
+    state_guard.is_loaded = false;
+    state_guard.is_buffering = true;
+    state_guard.buffered_ahead = 0.0;
+    state_guard.buffered_until = 0.0;
+}
 
-    fn start(&mut self, position: f64, rate: f32) {
-        self.start_instant = Some(Instant::now());
-        self.start_position = position;
-        self.playback_rate = rate;
+// Tracks playback position from the samples the output has taken from the playing source,
+// so underruns, device buffering and speed changes can't pull it away from what's heard
+struct PlaybackClock {
+    source: Option<SourceClock>,
+    playing: bool,
+}
+
+impl PlaybackClock {
+    fn new() -> Self {
+        Self { source: None, playing: false }
     }
 
-    fn pause(&mut self) -> f64 {
-        let position = self.current_position();
-        self.start_position = position; // Save current position so resume works correctly
-        self.start_instant = None;
-        position
+    // Follow a newly appended source; it starts counting from where it was opened
+    fn start(&mut self, source: SourceClock) {
+        self.source = Some(source);
+        self.playing = true;
     }
 
-    fn seek(&mut self, position: f64) {
-        self.start_position = position;
-        if self.start_instant.is_some() {
-            self.start_instant = Some(Instant::now());
-        }
+    fn resume(&mut self) {
+        self.playing = self.source.is_some();
     }
 
-    fn set_rate(&mut self, rate: f32) {
-        // Update position before changing rate
-        if self.start_instant.is_some() {
-            self.start_position = self.current_position();
-            self.start_instant = Some(Instant::now());
-        }
-        self.playback_rate = rate;
+    fn pause(&mut self) -> f64 {
+        self.playing = false;
+        self.current_position()
     }
 
     fn current_position(&self) -> f64 {
-        match self.start_instant {
-            Some(start) => {
-                let elapsed = start.elapsed().as_secs_f64();
-                self.start_position + (elapsed * self.playback_rate as f64)
-            }
-            None => self.start_position,
-        }
+        self.source.as_ref().map_or(0.0, |source| source.position())
     }
 
     fn is_playing(&self) -> bool {
-        self.start_instant.is_some()
+        self.playing
     }
 
     fn stop(&mut self) {
-        self.start_instant = None;
-        self.start_position = 0.0;
+        self.source = None;
+        self.playing = false;
     }
 }
 
@@ -283,19 +1241,96 @@ impl PlaybackTimer {
 fn audio_thread(
     mut command_rx: mpsc::UnboundedReceiver<AudioCommand>,
     state: Arc<Mutex<AudioState>>,
+    capabilities_slot: Arc<Mutex<AudioCapabilities>>,
     state_change_tx: std_mpsc::Sender<()>,
     track_ended_tx: std_mpsc::Sender<()>,
+    playback_error_tx: std_mpsc::Sender<PlaybackError>,
+    device_change_tx: std_mpsc::Sender<String>,
+    player_event_tx: std_mpsc::Sender<PlayerEvent>,
+    stream_url_refused_tx: std_mpsc::Sender<String>,
+    stream_cache: Arc<StdMutex<Option<StreamCacheSlot>>>,
+    streamed_audio_tx: std_mpsc::Sender<StreamedAudio>,
 ) {
-    // Create audio output stream once for this thread
-    let Ok((_stream, stream_handle)) = OutputStream::try_default() else {
-        eprintln!("❌ Failed to create audio output");
-        return;
+    // Logs a player event against the current track; never call while holding the state lock
+    let record = |kind: PlayerEventKind| {
+        let track_id = state.blocking_lock().current_track.as_ref().map(|t| t.id.clone());
+        let _ = player_event_tx.send(PlayerEvent::new(track_id, kind));
+    };
+
+    // Clears the loading state and reports the failure so the queue can retry or skip
+    let report_error = |track: &YTVideoInfo, message: String| {
+        eprintln!("❌ {}", message);
+        {
+            let mut state_guard = state.blocking_lock();
+            state_guard.is_loading = false;
+            state_guard.is_loaded = false;
+            state_guard.is_buffering = false;
+            state_guard.is_playing = false;
+        }
+        let _ = state_change_tx.send(());
+        record(PlayerEventKind::Error { message: message.clone() });
+        let _ = playback_error_tx.send(PlaybackError {
+            track: track.clone(),
+            message,
+        });
+    };
+
+    // Find out which decoders exist before the first track is requested
+    let capabilities = AudioCapabilities::detect();
+    match &capabilities.ffmpeg_version {
+        Some(version) => println!("✅ Decoding with {}", version),
+        None => println!("⚠️ ffmpeg not found; decoding with the built-in decoder ({})", capabilities.symphonia_codecs.join(", ")),
+    }
+    *capabilities_slot.blocking_lock() = capabilities.clone();
+
+    // Create audio output stream for this thread, reopened when the output device changes. With
+    // no device free the thread carries on without one, retrying until one can be opened.
+    let (mut _stream, mut stream_handle, mut device_name) = match open_any_output() {
+        Some((stream, handle, name)) => {
+            println!("✅ Audio output stream created on {}", name);
+            let _ = device_change_tx.send(name.clone());
+            (Some(stream), Some(handle), name)
+        }
+        None => {
+            eprintln!("⚠️ No audio output available, waiting for a device to free up");
+            (None, None, String::new())
+        }
     };
-    println!("✅ Audio output stream created");
+    {
+        let mut state_guard = state.blocking_lock();
+        state_guard.output_device = stream_handle.as_ref().map(|_| device_name.clone());
+        state_guard.output_unavailable = stream_handle.is_none();
+    }
+
+    let effects = Arc::new(StdMutex::new(AudioEffects::default()));
+    let limiter = Arc::new(StdMutex::new(LimiterSettings::default()));
+    // Output gain above unity, applied ahead of the limiter so boosted audio is still held under its ceiling
+    let boost = Arc::new(StdMutex::new(1.0f32));
+    let mut selected_device: Option<String> = None; // None follows the system default
+    let mut pending_device_switch: Option<Option<String>> = None;
+    let mut retrying_output = false; // the pending switch is a background retry, failing is expected
+    let mut last_device_check = Instant::now();
 
     let mut current_sink: Option<Sink> = None;
-    let mut current_samples: Option<Vec<i16>> = None; // Store samples for seeking
-    let mut position_timer = PlaybackTimer::new(); // Track playback position
+    let mut current_stream: Option<StreamBuffer> = None; // Decoded audio, kept for seeking and restarts
+    let mut pending_start: Option<PendingStart> = None; // Track waiting for its initial buffer
+    let mut buffer_target = state.blocking_lock().buffer_target;
+    let mut stalled = false; // Paused by an underrun rather than the user
+    let mut streamed_track: Option<YTVideoInfo> = None; // Current track when it comes from YouTube rather than a file
+    let mut streamed_url: Option<String> = None; // Resolved URL the track is read from, reused to reconnect
+    let mut stream_strategy = DecodeStrategy::Ffmpeg; // Decoder the current stream goes through
+    let mut prefer_builtin_decoder = false;
+    let mut keep_streamed_audio = false;
+    // Copies left over from the last run were never claimed
+    let _ = std::fs::remove_dir_all(AppData::get_stream_cache_dir());
+    let mut interrupted_at: Option<f64> = None; // Where a stream cut off by a dropped connection stopped
+    let mut last_reconnect: Option<Instant> = None;
+    let mut network_offline = false;
+    let mut data_saver = false;
+    let mut ytdlp_options = YtdlpOptions::default();
+    let mut jingle: Option<PlayingJingle> = None;
+    let mut warm_streams = WarmStreams::default();
+    let mut position_clock = PlaybackClock::new(); // Track playback position
     let mut last_position_update = Instant::now();
 
     // Process commands with polling to allow periodic position updates
@@ -303,293 +1338,452 @@ fn audio_thread(
         // Try to receive a command (non-blocking)
         let command = command_rx.try_recv().ok();
 
-        // Check if track has ended (sink is empty)
+        if jingle.as_ref().is_some_and(|(sink, _, _)| sink.empty()) {
+            finish_jingle(&mut jingle, true);
+        }
+        warm_streams.expire();
+
+        // Check if track has ended (sink is empty, or it reached a trimmed outro)
         if let Some(sink) = &current_sink {
-            if sink.empty() && position_timer.is_playing() {
+            let (duration, trim_end) = {
+                let state_guard = state.blocking_lock();
+                (state_guard.duration, state_guard.trim_end)
+            };
+            if trim_end.is_some_and(|end| position_clock.is_playing() && position_clock.current_position() >= end) {
+                sink.stop();
+            }
+            let cut_short = streamed_track.is_some()
+                && current_stream.as_ref().is_some_and(|stream| stream.error().is_some())
+                && position_clock.current_position() < duration - 1.0;
+
+            if sink.empty() && position_clock.is_playing() && cut_short {
+                // The download died mid-track, usually a dropped connection; wait to reconnect rather than skip ahead
+                let position = position_clock.pause();
+                println!("📡 Stream cut off at {:.1}s, waiting to reconnect", position);
+                interrupted_at = Some(position);
+                last_reconnect = None;
+                {
+                    let mut state_guard = state.blocking_lock();
+                    state_guard.is_buffering = true;
+                    state_guard.current_position = position;
+                }
+                let _ = state_change_tx.send(());
+                record(PlayerEventKind::Stall { position });
+                current_sink = None;
+            } else if sink.empty() && position_clock.is_playing() {
                 println!("🏁 Track ended (sink empty)");
-                position_timer.stop();
-                // Keep current_samples so we can restart the track if user presses play
+                let ended_at = position_clock.current_position();
+                position_clock.stop();
+                // Keep current_stream so we can restart the track if user presses play
 
                 let mut state_guard = state.blocking_lock();
                 let duration = state_guard.duration;
                 state_guard.is_playing = false;
                 state_guard.current_position = duration; // Set to exact duration
                 drop(state_guard);
+                record(PlayerEventKind::Ended { position: ended_at });
 
                 // Emit both state change and track-ended event
                 let _ = state_change_tx.send(());
                 let _ = track_ended_tx.send(()); // Notify that track ended for auto-play
 
-                current_sink = None; // Clear sink to stop the empty check, but samples remain
+                current_sink = None; // Clear sink to stop the empty check, but the stream remains
             }
         }
 
-        // Periodically update position in state while playing (every 500ms)
-        if position_timer.is_playing() && last_position_update.elapsed() > std::time::Duration::from_millis(500) {
-            let current_pos = position_timer.current_position();
-            let duration = state.blocking_lock().duration;
-
-            // Don't exceed duration
-            let clamped_pos = current_pos.min(duration);
-            {
-                let mut state_guard = state.blocking_lock();
-                state_guard.current_position = clamped_pos;
+        // Follow the system default output when no device was picked explicitly, and go back to
+        // the picked one (or any at all) once it's free again
+        if last_device_check.elapsed() > DEVICE_CHECK_INTERVAL && pending_device_switch.is_none() {
+            last_device_check = Instant::now();
+            let retry = match &selected_device {
+                _ if stream_handle.is_none() => true,
+                Some(selected) => *selected != device_name,
+                None => default_output_name().is_some_and(|name| name != device_name),
+            };
+            if retry {
+                pending_device_switch = Some(selected_device.clone());
+                retrying_output = true;
             }
-            let _ = state_change_tx.send(());
-            last_position_update = Instant::now();
         }
 
-        let Some(command) = command else {
-            // No command, sleep briefly and continue loop for position updates
-            std::thread::sleep(std::time::Duration::from_millis(50));
-            continue;
-        };
-
-        match command {
-            AudioCommand::Play(track) => {
-                // Stop current playback
-                if let Some(sink) = current_sink.take() {
-                    sink.stop();
-                }
-                current_samples = None;
-
-                let video_url = format!("https://www.youtube.com/watch?v={}", track.id);
-                println!("📥 Fetching audio via yt-dlp + ffmpeg pipeline...");
-
-                // Get yt-dlp path
-                let ytdlp_path = YTDLPInstaller::get_ytdlp_path();
-
-                // Use yt-dlp to pipe audio through ffmpeg to get raw PCM
-                let ytdlp_child = match Command::new(&ytdlp_path)
-                    .args(&[
-                        "-f", "bestaudio",
-                        "-o", "-",
-                        "--no-warnings",
-                        "--quiet",
-                        &video_url,
-                    ])
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::null())
-                    .spawn()
-                {
-                    Ok(child) => child,
-                    Err(e) => {
-                        eprintln!("❌ Failed to spawn yt-dlp: {}", e);
-                        continue;
+        if let Some(target) = pending_device_switch.take() {
+            let opened = match open_output(target.as_deref()) {
+                None if stream_handle.is_none() => open_any_output(),
+                opened => opened,
+            };
+            let retried = std::mem::take(&mut retrying_output);
+            match opened {
+                Some((stream, handle, name)) => {
+                    let position = position_clock.current_position();
+                    let was_playing = position_clock.is_playing();
+                    let had_sink = current_sink.is_some();
+                    if let Some(sink) = current_sink.take() {
+                        sink.stop();
                     }
-                };
 
-                let ytdlp_stdout = match ytdlp_child.stdout {
-                    Some(stdout) => stdout,
-                    None => {
-                        eprintln!("❌ Failed to capture yt-dlp stdout");
-                        continue;
+                    _stream = Some(stream);
+                    stream_handle = Some(handle);
+                    device_name = name;
+
+                    // Carry on from the same position on the new device
+                    if let (true, Some(stream)) = (had_sink, &current_stream) {
+                        let (volume, rate) = {
+                            let state_guard = state.blocking_lock();
+                            (state_guard.output_gain, state_guard.playback_rate)
+                        };
+
+                        if let Ok(sink) = new_sink(&stream_handle) {
+                            let source = stream.source_from(position);
+                            position_clock.start(source.clock());
+                            set_output_gain(&sink, &boost, volume);
+                            sink.set_speed(rate);
+                            sink.append(LimiterSource::new(
+                                EffectsSource::new(source.convert_samples::<f32>(), Arc::clone(&effects)),
+                                Arc::clone(&limiter),
+                                Arc::clone(&boost),
+                            ));
+                            if !was_playing {
+                                sink.pause();
+                                position_clock.pause();
+                            }
+                            current_sink = Some(sink);
+                        }
                     }
-                };
 
-                // Pipe yt-dlp output through ffmpeg to convert to raw PCM
-                let ffmpeg_output = match Command::new("ffmpeg")
-                    .args(&[
-                        "-i", "pipe:0",
-                        "-f", "s16le",
-                        "-acodec", "pcm_s16le",
-                        "-ar", &SAMPLE_RATE.to_string(),
-                        "-ac", &CHANNELS.to_string(),
-                        "-loglevel", "error",
-                        "pipe:1",
-                    ])
-                    .stdin(ytdlp_stdout)
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::null())
-                    .output()
-                {
-                    Ok(output) => output,
-                    Err(e) => {
-                        eprintln!("❌ Failed to run ffmpeg: {}", e);
-                        eprintln!("Make sure ffmpeg is installed and in PATH");
-                        continue;
+                    {
+                        let mut state_guard = state.blocking_lock();
+                        state_guard.output_device = Some(device_name.clone());
+                        state_guard.output_unavailable = false;
                     }
-                };
-
-                if !ffmpeg_output.status.success() {
-                    eprintln!("❌ ffmpeg conversion failed");
-                    continue;
+                    let _ = state_change_tx.send(());
+                    let _ = device_change_tx.send(device_name.clone());
+                    record(PlayerEventKind::OutputChanged { device: device_name.clone() });
+                    println!("🔈 Output switched to {}", device_name);
                 }
+                None if retried => {}
+                None => eprintln!("❌ Failed to open output device {:?}", target),
+            }
+        }
 
-                let pcm_bytes = ffmpeg_output.stdout;
-                println!("✅ Got {} bytes of raw PCM audio", pcm_bytes.len());
-
-                if pcm_bytes.is_empty() {
-                    eprintln!("❌ No audio data received");
-                    continue;
+        // Offer the stream's copy once all of it is on disk, straight to a download waiting for it
+        if let Some(path) = current_stream.as_ref().and_then(|stream| stream.cached_file()) {
+            let mut slot = stream_cache.lock().unwrap();
+            if let Some(entry) = slot.as_mut().filter(|entry| entry.path.is_none()) {
+                if entry.claimed {
+                    let video_id = entry.video_id.clone();
+                    *slot = None;
+                    let _ = streamed_audio_tx.send(StreamedAudio {
+                        video_id,
+                        path: Some(path.to_string_lossy().to_string()),
+                    });
+                } else {
+                    entry.path = Some(path);
                 }
+            }
+        }
 
-                // Convert bytes to i16 samples
-                let samples: Vec<i16> = pcm_bytes
-                    .chunks_exact(2)
-                    .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
-                    .collect();
-
-                println!("✅ Converted to {} samples", samples.len());
-
-                // Store samples for seeking
-                current_samples = Some(samples.clone());
-
-                // Create source and sink
-                let source = SamplesBuffer::new(CHANNELS, SAMPLE_RATE, samples);
+        // Refetch a stream cut off mid-track once the network looks usable again
+        let reconnect_due = last_reconnect.map_or(true, |at| at.elapsed() > RECONNECT_INTERVAL);
+        if let (Some(position), Some(track), None, false, true) =
+            (interrupted_at, &streamed_track, &pending_start, network_offline, reconnect_due)
+        {
+            last_reconnect = Some(Instant::now());
+            println!("🔌 Reconnecting {} at {:.1}s", track.title, position);
+            if current_stream.as_ref().is_some_and(|stream| stream.was_refused()) && streamed_url.take().is_some() {
+                let _ = stream_url_refused_tx.send(track.id.clone());
+            }
+            let cache = open_stream_cache(keep_streamed_audio, &track.id);
+            match spawn_stream(track, stream_strategy, streamed_url.as_deref(), cache, data_saver, &ytdlp_options) {
+                Ok(stream) => {
+                    if let Some(old) = current_stream.replace(stream) {
+                        old.cancel();
+                    }
+                    pending_start = Some(PendingStart { track: track.clone(), requested: Instant::now(), position });
+                }
+                Err(e) => eprintln!("❌ Reconnect failed: {}", e),
+            }
+        }
 
-                println!("🔊 Creating audio sink...");
-                let Ok(sink) = Sink::try_new(&stream_handle) else {
-                    eprintln!("❌ Failed to create sink");
-                    continue;
-                };
+        // Start a track once enough of it has been buffered, from the beginning or where a stream was
+        // cut off. Without an output it stays buffered until one frees up.
+        let ready = stream_handle.is_some() && match (&pending_start, &current_stream) {
+            (Some(pending), Some(stream)) => {
+                stream.is_finished() || stream.buffered_seconds() >= pending.position + buffer_target
+            }
+            _ => false,
+        };
+        let starting = if ready { pending_start.take().zip(current_stream.clone()) } else { None };
+        if let Some((PendingStart { track, requested, position }, stream)) = starting {
+            let refused_url = stream.was_refused() && streamed_url.is_some();
+            let ffmpeg_fallback = stream.is_unsupported()
+                && stream_strategy == DecodeStrategy::Symphonia
+                && capabilities.ffmpeg_version.is_some();
+            if refused_url || ffmpeg_fallback {
+                if refused_url {
+                    // The resolved URL has gone stale, fetch the track through yt-dlp instead
+                    println!("🔁 Stream URL for {} was refused, resolving it again", track.title);
+                    streamed_url = None;
+                    let _ = stream_url_refused_tx.send(track.id.clone());
+                } else {
+                    println!("🔁 Built-in decoder can't play {}, falling back to ffmpeg", track.title);
+                    stream_strategy = DecodeStrategy::Ffmpeg;
+                }
+                let cache = open_stream_cache(keep_streamed_audio, &track.id);
+                match spawn_stream(&track, stream_strategy, streamed_url.as_deref(), cache, data_saver, &ytdlp_options) {
+                    Ok(stream) => {
+                        current_stream = Some(stream);
+                        pending_start = Some(PendingStart { track, requested, position });
+                    }
+                    Err(e) => {
+                        current_stream = None;
+                        report_error(&track, e);
+                    }
+                }
+            } else if interrupted_at.is_some() && stream.buffered_seconds() <= position {
+                // Still can't get past the cut, try again later
+                eprintln!("⚠️ Reconnect for {} didn't get past {:.1}s", track.title, position);
+            } else if stream.is_empty() {
+                current_stream = None;
+                report_error(&track, stream.error().unwrap_or_else(|| "No audio data received".to_string()));
+            } else if let Ok(sink) = new_sink(&stream_handle) {
+                let load_ms = requested.elapsed().as_millis() as u64;
+                println!("✅ Buffered {:.1}s of audio in {}ms", stream.buffered_seconds(), load_ms);
 
                 // Get current settings from state
                 let (volume, rate) = {
                     let state_guard = state.blocking_lock();
-                    (state_guard.volume, state_guard.playback_rate)
+                    (state_guard.output_gain, state_guard.playback_rate)
                 };
 
-                sink.set_volume(volume);
+                let source = stream.source_from(position);
+                position_clock.start(source.clock());
+                set_output_gain(&sink, &boost, volume);
                 sink.set_speed(rate);
-                sink.append(source.convert_samples::<f32>());
+                sink.append(LimiterSource::new(
+                    EffectsSource::new(source.convert_samples::<f32>(), Arc::clone(&effects)),
+                    Arc::clone(&limiter),
+                    Arc::clone(&boost),
+                ));
                 sink.play();
 
                 current_sink = Some(sink);
-
-                // Start position timer
-                position_timer.start(0.0, rate);
                 last_position_update = Instant::now();
 
                 // Update state
                 {
                     let mut state_guard = state.blocking_lock();
                     state_guard.is_loading = false;
+                    state_guard.is_loaded = true;
+                    state_guard.is_buffering = false;
                     state_guard.is_playing = true;
-                    state_guard.current_position = 0.0;
+                    state_guard.current_position = position;
                 }
                 let _ = state_change_tx.send(());
+                record(PlayerEventKind::Buffered { load_ms });
 
-                println!("▶️ Playing: {} (position timer started at 0.0s)", track.title);
-            }
-            AudioCommand::PlayFromFile(track, file_path) => {
-                // Stop current playback
-                if let Some(sink) = current_sink.take() {
-                    sink.stop();
+                if interrupted_at.take().is_some() {
+                    record(PlayerEventKind::Resumed);
+                    println!("▶️ Reconnected: {} (resumed at {:.1}s)", track.title, position);
+                } else {
+                    record(PlayerEventKind::PlayStarted);
+                    println!("▶️ Playing: {} (from {:.1}s)", track.title, position);
                 }
-                current_samples = None;
-
-                println!("📥 Loading audio from local file: {}", file_path);
-
-                // Use ffmpeg to convert local file to raw PCM
-                let ffmpeg_output = match Command::new("ffmpeg")
-                    .args(&[
-                        "-i", &file_path,
-                        "-f", "s16le",
-                        "-acodec", "pcm_s16le",
-                        "-ar", &SAMPLE_RATE.to_string(),
-                        "-ac", &CHANNELS.to_string(),
-                        "-loglevel", "error",
-                        "pipe:1",
-                    ])
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::null())
-                    .output()
-                {
-                    Ok(output) => output,
-                    Err(e) => {
-                        eprintln!("❌ Failed to run ffmpeg on local file: {}", e);
-                        eprintln!("Make sure ffmpeg is installed and in PATH");
-                        continue;
-                    }
-                };
+            } else {
+                report_error(&track, "Failed to create sink".to_string());
+            }
+        }
 
-                if !ffmpeg_output.status.success() {
-                    eprintln!("❌ ffmpeg conversion failed for local file");
-                    continue;
+        // Pause on an underrun and pick back up once the buffer target has refilled
+        if let (Some(sink), Some(stream)) = (&current_sink, &current_stream) {
+            let position = position_clock.current_position();
+            let ahead = stream.buffered_seconds() - position;
+            if stalled {
+                if stream.is_finished() || ahead >= buffer_target {
+                    stalled = false;
+                    sink.play();
+                    position_clock.resume();
+                    last_position_update = Instant::now();
+                    state.blocking_lock().is_buffering = false;
+                    let _ = state_change_tx.send(());
+                    record(PlayerEventKind::Resumed);
+                    println!("▶️ Buffer refilled, resuming at {:.1}s", position);
                 }
+            } else if position_clock.is_playing() && !stream.is_finished() && ahead < UNDERRUN_MARGIN {
+                stalled = true;
+                sink.pause();
+                position_clock.pause();
+                state.blocking_lock().is_buffering = true;
+                let _ = state_change_tx.send(());
+                record(PlayerEventKind::Stall { position });
+                println!("⏳ Buffer ran dry at {:.1}s, waiting for more audio", position);
+            }
+        }
 
-                let pcm_bytes = ffmpeg_output.stdout;
-                println!("✅ Got {} bytes of raw PCM audio from local file", pcm_bytes.len());
+        // Periodically update position and buffer progress in state (every 500ms)
+        let downloading = current_stream.as_ref().is_some_and(|stream| !stream.is_finished());
+        if (position_clock.is_playing() || downloading) && last_position_update.elapsed() > std::time::Duration::from_millis(500) {
+            let current_pos = position_clock.current_position();
+            let buffered = current_stream.as_ref().map_or(0.0, |stream| stream.buffered_seconds());
+            {
+                let mut state_guard = state.blocking_lock();
+                // Don't exceed duration
+                state_guard.current_position = current_pos.min(state_guard.duration);
+                state_guard.buffered_ahead = (buffered - current_pos).max(0.0);
+                state_guard.buffered_until = buffered;
+            }
+            let _ = state_change_tx.send(());
+            last_position_update = Instant::now();
+        }
 
-                if pcm_bytes.is_empty() {
-                    eprintln!("❌ No audio data received from local file");
-                    continue;
-                }
+        let Some(command) = command else {
+            // No command, sleep briefly and continue loop for position updates
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            continue;
+        };
 
-                // Convert bytes to i16 samples
-                let samples: Vec<i16> = pcm_bytes
-                    .chunks_exact(2)
-                    .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
-                    .collect();
+        // Rewinds are resolved against the live position clock, then handled like a seek
+        let command = match command {
+            AudioCommand::Rewind(seconds) => {
+                AudioCommand::Seek((position_clock.current_position() - seconds).max(0.0))
+            }
+            command => command,
+        };
 
-                println!("✅ Converted to {} samples", samples.len());
+        match command {
+            AudioCommand::Play(track, stream_url, position) => {
+                let load_started = Instant::now();
+                finish_jingle(&mut jingle, false);
 
-                // Store samples for seeking
-                current_samples = Some(samples.clone());
+                // Stop current playback
+                if let Some(sink) = current_sink.take() {
+                    sink.stop();
+                }
+                if let Some(stream) = current_stream.take() {
+                    stream.cancel();
+                }
+                position_clock.stop();
+                stalled = false;
 
-                // Create source and sink
-                let source = SamplesBuffer::new(CHANNELS, SAMPLE_RATE, samples);
+                streamed_track = Some(track.clone());
+                streamed_url = stream_url;
+                interrupted_at = None;
 
-                println!("🔊 Creating audio sink...");
-                let Ok(sink) = Sink::try_new(&stream_handle) else {
-                    eprintln!("❌ Failed to create sink");
+                if !capabilities.streaming_available {
+                    report_error(&track, "Streaming needs ffmpeg. Install it and add it to PATH, or download the track to play it offline.".to_string());
                     continue;
-                };
+                }
 
-                // Get current settings from state
-                let (volume, rate) = {
-                    let state_guard = state.blocking_lock();
-                    (state_guard.volume, state_guard.playback_rate)
+                // Playback starts from the main loop once the buffer target is reached
+                release_stream_cache(&stream_cache, &streamed_audio_tx);
+                if keep_streamed_audio {
+                    *stream_cache.lock().unwrap() = Some(StreamCacheSlot {
+                        video_id: track.id.clone(),
+                        path: None,
+                        claimed: false,
+                    });
+                }
+                stream_strategy = capabilities.stream_strategy(prefer_builtin_decoder);
+                let spawned = match warm_streams.take(&track.id) {
+                    Some(stream) => {
+                        println!("⚡ Starting {} from its warmed stream", track.title);
+                        stream.release();
+                        Ok(stream)
+                    }
+                    None => {
+                        let cache = open_stream_cache(keep_streamed_audio, &track.id);
+                        spawn_stream(&track, stream_strategy, streamed_url.as_deref(), cache, data_saver, &ytdlp_options)
+                    }
                 };
+                match spawned {
+                    Ok(stream) => {
+                        println!("⏳ Buffering {:.1}s of {}...", buffer_target, track.title);
+                        set_buffering_start(&state);
+                        let _ = state_change_tx.send(());
+                        current_stream = Some(stream);
+                        pending_start = Some(PendingStart { track, requested: load_started, position });
+                    }
+                    Err(e) => report_error(&track, e),
+                }
+            }
+            AudioCommand::Warm(video_id, stream_url) => {
+                // Same conditions as AudioManager::warm checked, as of this thread's settings
+                let warmable = !network_offline
+                    && !keep_streamed_audio
+                    && capabilities.stream_strategy(prefer_builtin_decoder) == DecodeStrategy::Ffmpeg
+                    && !streamed_track.as_ref().is_some_and(|t| t.id == video_id)
+                    && !warm_streams.contains(&video_id);
+                if warmable {
+                    match spawn_url_stream(&stream_url, &ytdlp_options) {
+                        Ok(stream) => {
+                            stream.hold_at(WARM_SECONDS);
+                            warm_streams.insert(video_id, stream);
+                        }
+                        Err(e) => eprintln!("⚠️ Failed to warm {}: {}", video_id, e),
+                    }
+                }
+            }
+            AudioCommand::PlayFromFile(track, file_path, position) => {
+                let load_started = Instant::now();
+                finish_jingle(&mut jingle, false);
 
-                sink.set_volume(volume);
-                sink.set_speed(rate);
-                sink.append(source.convert_samples::<f32>());
-                sink.play();
+                // Stop current playback
+                if let Some(sink) = current_sink.take() {
+                    sink.stop();
+                }
+                if let Some(stream) = current_stream.take() {
+                    stream.cancel();
+                }
+                position_clock.stop();
+                stalled = false;
 
-                current_sink = Some(sink);
+                streamed_track = None;
+                streamed_url = None;
+                release_stream_cache(&stream_cache, &streamed_audio_tx);
+                interrupted_at = None;
 
-                // Start position timer
-                position_timer.start(0.0, rate);
-                last_position_update = Instant::now();
+                println!("📥 Loading audio from local file: {}", file_path);
 
-                // Update state
-                {
-                    let mut state_guard = state.blocking_lock();
-                    state_guard.is_loading = false;
-                    state_guard.is_playing = true;
-                    state_guard.current_position = 0.0;
-                }
-                let _ = state_change_tx.send(());
+                let decoded = decode_local_file(&capabilities, &file_path, prefer_builtin_decoder);
 
-                println!("▶️ Playing from local file: {} (position timer started at 0.0s)", track.title);
+                match decoded {
+                    Ok(stream) => {
+                        set_buffering_start(&state);
+                        let _ = state_change_tx.send(());
+                        current_stream = Some(stream);
+                        pending_start = Some(PendingStart { track, requested: load_started, position });
+                    }
+                    Err(e) => report_error(&track, e),
+                }
             }
             AudioCommand::Seek(position) => {
-                if let Some(samples) = &current_samples {
+                let seek_from = position_clock.current_position();
+                // A track still buffering its start begins from the new position instead
+                if let Some(pending) = pending_start.as_mut() {
+                    pending.position = position;
+                    state.blocking_lock().current_position = position;
+                    let _ = state_change_tx.send(());
+                    println!("⏩ Will start at {:.1}s", position);
+                    continue;
+                }
+                if let Some(stream) = &current_stream {
+                    if stream.is_finished() && position >= stream.buffered_seconds() {
+                        println!("⏩ Seek position at end of track");
+                        continue;
+                    }
+
                     // Stop current playback
                     if let Some(sink) = current_sink.take() {
                         sink.stop();
                     }
+                    stalled = false;
+                    interrupted_at = None;
 
-                    // Calculate sample index from position
-                    // position_secs * sample_rate * channels = sample index
-                    let sample_index = (position * SAMPLE_RATE as f64 * CHANNELS as f64) as usize;
-                    let sample_index = sample_index.min(samples.len());
-
-                    // Get samples from position onwards
-                    let remaining_samples: Vec<i16> = samples[sample_index..].to_vec();
-
-                    if remaining_samples.is_empty() {
-                        println!("⏩ Seek position at end of track");
-                        continue;
-                    }
-
-                    // Create source from remaining samples
-                    let source = SamplesBuffer::new(CHANNELS, SAMPLE_RATE, remaining_samples);
+                    // Seeking past the buffered audio rebuffers from the main loop
+                    let source = stream.source_from(position);
+                    position_clock.start(source.clock());
 
                     // Create new sink
-                    let Ok(sink) = Sink::try_new(&stream_handle) else {
+                    let Ok(sink) = new_sink(&stream_handle) else {
                         eprintln!("❌ Failed to create sink for seek");
                         continue;
                     };
@@ -597,18 +1791,19 @@ fn audio_thread(
                     // Get current settings from state
                     let (volume, rate) = {
                         let state_guard = state.blocking_lock();
-                        (state_guard.volume, state_guard.playback_rate)
+                        (state_guard.output_gain, state_guard.playback_rate)
                     };
 
-                    sink.set_volume(volume);
+                    set_output_gain(&sink, &boost, volume);
                     sink.set_speed(rate);
-                    sink.append(source.convert_samples::<f32>());
+                    sink.append(LimiterSource::new(
+                        EffectsSource::new(source.convert_samples::<f32>(), Arc::clone(&effects)),
+                        Arc::clone(&limiter),
+                        Arc::clone(&boost),
+                    ));
                     sink.play();
 
                     current_sink = Some(sink);
-
-                    // Update position timer - always restart from seek position
-                    position_timer.start(position, rate);
                     last_position_update = Instant::now();
 
                     // Update state with actual position
@@ -618,61 +1813,86 @@ fn audio_thread(
                         state_guard.is_playing = true;
                     }
                     let _ = state_change_tx.send(());
+                    record(PlayerEventKind::Seek { from: seek_from, to: position });
 
-                    println!("⏩ Seeked to {:.1}s (timer reset to {:.1}s)", position, position);
+                    println!("⏩ Seeked to {:.1}s", position);
                 }
             }
             AudioCommand::TogglePlayPause => {
+                // The track has ended and a jingle plays before the next one
+                if let Some((sink, _, _)) = &jingle {
+                    if sink.is_paused() {
+                        sink.play();
+                        println!("🔔 Resumed jingle");
+                    } else {
+                        sink.pause();
+                        println!("🔔 Paused jingle");
+                    }
+                    continue;
+                }
+
+                // Still buffering the start of a track, or waiting to reconnect
+                if pending_start.is_some() || interrupted_at.is_some() {
+                    continue;
+                }
+
                 let state_guard = state.blocking_lock();
                 let is_playing = state_guard.is_playing;
                 let duration = state_guard.duration;
-                let current_pos = position_timer.current_position();
+                let current_pos = position_clock.current_position();
                 let rate = state_guard.playback_rate;
-                let volume = state_guard.volume;
+                let volume = state_guard.output_gain;
                 drop(state_guard);
 
                 // Check if track ended (at or near duration, or sink is gone) - need to restart
                 let track_ended = (current_pos >= duration - 0.5 && duration > 0.0) ||
-                                  (current_samples.is_some() && current_sink.is_none());
+                                  (current_stream.is_some() && current_sink.is_none());
 
                 if is_playing {
                     // Pause
                     if let Some(sink) = &current_sink {
                         sink.pause();
-                        let paused_pos = position_timer.pause();
+                        stalled = false;
+                        let paused_pos = position_clock.pause();
                         let mut state_guard = state.blocking_lock();
                         state_guard.is_playing = false;
+                        state_guard.is_buffering = false;
                         state_guard.current_position = paused_pos;
                         println!("⏸️ Paused at {:.1}s", paused_pos);
                         drop(state_guard);
                         let _ = state_change_tx.send(());
+                        record(PlayerEventKind::Paused);
                     }
                 } else if track_ended {
                     // Track ended, restart from beginning
-                    if let Some(samples) = &current_samples {
+                    if let Some(stream) = &current_stream {
                         // Stop current sink if exists
                         if let Some(sink) = current_sink.take() {
                             sink.stop();
                         }
 
-                        // Create new sink from the beginning
-                        let source = SamplesBuffer::new(CHANNELS, SAMPLE_RATE, samples.clone());
-                        if let Ok(sink) = Sink::try_new(&stream_handle) {
-                            sink.set_volume(volume);
+                        // Create new sink from the beginning, past any trimmed intro
+                        let start = state.blocking_lock().trim_start;
+                        let source = stream.source_from(start);
+                        if let Ok(sink) = new_sink(&stream_handle) {
+                            position_clock.start(source.clock());
+                            set_output_gain(&sink, &boost, volume);
                             sink.set_speed(rate);
-                            sink.append(source.convert_samples::<f32>());
+                            sink.append(LimiterSource::new(
+                                EffectsSource::new(source.convert_samples::<f32>(), Arc::clone(&effects)),
+                                Arc::clone(&limiter),
+                                Arc::clone(&boost),
+                            ));
                             sink.play();
                             current_sink = Some(sink);
-
-                            // Reset position timer to 0
-                            position_timer.start(0.0, rate);
                             last_position_update = Instant::now();
 
                             let mut state_guard = state.blocking_lock();
                             state_guard.is_playing = true;
-                            state_guard.current_position = 0.0;
+                            state_guard.current_position = start;
                             drop(state_guard);
                             let _ = state_change_tx.send(());
+                            record(PlayerEventKind::PlayStarted);
                             println!("🔄 Restarted track from beginning");
                         }
                     }
@@ -680,7 +1900,7 @@ fn audio_thread(
                     // Normal resume
                     if let Some(sink) = &current_sink {
                         sink.play();
-                        position_timer.start(current_pos, rate);
+                        position_clock.resume();
                         let mut state_guard = state.blocking_lock();
                         state_guard.is_playing = true;
                         state_guard.current_position = current_pos;
@@ -688,45 +1908,164 @@ fn audio_thread(
                         drop(state_guard);
                         last_position_update = Instant::now();
                         let _ = state_change_tx.send(());
+                        record(PlayerEventKind::Resumed);
                     }
                 }
             }
             AudioCommand::Pause => {
+                if let Some((sink, _, _)) = &jingle {
+                    sink.pause();
+                    println!("🔔 Paused jingle");
+                }
                 if let Some(sink) = &current_sink {
                     sink.pause();
-                    // Pause timer and get current position
-                    let current_pos = position_timer.pause();
+                    stalled = false;
+                    // Stop the clock and get the current position
+                    let current_pos = position_clock.pause();
                     let mut state_guard = state.blocking_lock();
                     state_guard.is_playing = false;
+                    state_guard.is_buffering = false;
                     state_guard.current_position = current_pos;
                     println!("⏸️ Explicit pause at {:.1}s", current_pos);
                     drop(state_guard);
                     let _ = state_change_tx.send(());
+                    record(PlayerEventKind::Paused);
                 }
             }
             AudioCommand::Stop => {
+                finish_jingle(&mut jingle, false);
                 if let Some(sink) = current_sink.take() {
                     sink.stop();
                 }
-                current_samples = None;
-                position_timer.stop();
+                // A track still buffering its start has nothing worth keeping
+                if pending_start.take().is_some() {
+                    if let Some(stream) = current_stream.take() {
+                        stream.cancel();
+                    }
+                    release_stream_cache(&stream_cache, &streamed_audio_tx);
+                }
+                stalled = false;
+                interrupted_at = None;
+                position_clock.stop();
+                // The stream stays, so playing again restarts from the buffer without refetching
+                let buffered = current_stream.as_ref().map_or(0.0, |stream| stream.buffered_seconds());
                 let mut state_guard = state.blocking_lock();
                 state_guard.is_playing = false;
-                state_guard.current_position = 0.0;
+                state_guard.is_loading = false;
+                state_guard.is_loaded = current_stream.is_some();
+                state_guard.is_buffering = false;
+                state_guard.current_position = state_guard.trim_start;
+                state_guard.buffered_ahead = (buffered - state_guard.trim_start).max(0.0);
+                state_guard.buffered_until = buffered;
                 drop(state_guard);
                 let _ = state_change_tx.send(());
+                record(PlayerEventKind::Stopped);
                 println!("⏹️ Stopped");
             }
+            AudioCommand::Unload => {
+                finish_jingle(&mut jingle, false);
+                if let Some(sink) = current_sink.take() {
+                    sink.stop();
+                }
+                if let Some(stream) = current_stream.take() {
+                    stream.cancel();
+                }
+                release_stream_cache(&stream_cache, &streamed_audio_tx);
+                pending_start = None;
+                stalled = false;
+                interrupted_at = None;
+                streamed_track = None;
+                streamed_url = None;
+                position_clock.stop();
+                let mut state_guard = state.blocking_lock();
+                state_guard.current_track = None;
+                state_guard.is_playing = false;
+                state_guard.is_loading = false;
+                state_guard.is_loaded = false;
+                state_guard.is_buffering = false;
+                state_guard.current_position = 0.0;
+                apply_trim(&mut state_guard, 0.0, None);
+                state_guard.buffered_ahead = 0.0;
+                state_guard.buffered_until = 0.0;
+                drop(state_guard);
+                let _ = state_change_tx.send(());
+                record(PlayerEventKind::Stopped);
+                println!("⏏️ Unloaded");
+            }
             AudioCommand::SetVolume(volume) => {
                 if let Some(sink) = &current_sink {
+                    set_output_gain(sink, &boost, volume);
+                }
+                if let Some((sink, _, _)) = &jingle {
                     sink.set_volume(volume);
                 }
             }
+            AudioCommand::Rewind(_) => {}
+            AudioCommand::SetEffects(updated) => {
+                // Picked up by the playing source within a few milliseconds
+                if let Ok(mut effects) = effects.lock() {
+                    *effects = updated;
+                }
+            }
+            AudioCommand::SetLimiter(updated) => {
+                if let Ok(mut limiter) = limiter.lock() {
+                    *limiter = updated;
+                }
+            }
+            AudioCommand::SetOutputDevice(device) => {
+                selected_device = device.clone();
+                pending_device_switch = Some(device);
+            }
+            AudioCommand::SetBufferTarget(seconds) => {
+                buffer_target = seconds;
+            }
+            AudioCommand::SetDataSaver(enabled) => {
+                data_saver = enabled;
+                warm_streams.clear();
+            }
+            AudioCommand::SetYtdlpOptions(options) => {
+                ytdlp_options = options;
+                warm_streams.clear();
+            }
+            AudioCommand::SetPreferBuiltinDecoder(enabled) => {
+                prefer_builtin_decoder = enabled;
+                warm_streams.clear();
+            }
+            AudioCommand::SetKeepStreamedAudio(enabled) => {
+                keep_streamed_audio = enabled;
+                warm_streams.clear();
+            }
+            AudioCommand::PlayJingle(file_path, done) => {
+                finish_jingle(&mut jingle, false);
+                let decoded = decode_local_file(&capabilities, &file_path, prefer_builtin_decoder);
+                let started = decoded.and_then(|stream| {
+                    let sink = new_sink(&stream_handle).map_err(|e| format!("Failed to create sink for jingle: {}", e))?;
+                    sink.set_volume(state.blocking_lock().output_gain);
+                    sink.append(stream.source_from(0.0));
+                    Ok((sink, stream))
+                });
+                match started {
+                    Ok((sink, stream)) => {
+                        println!("🔔 Playing jingle: {}", file_path);
+                        jingle = Some((sink, stream, done));
+                    }
+                    Err(e) => {
+                        let _ = done.send(Err(e));
+                    }
+                }
+            }
+            AudioCommand::SetNetworkOffline(offline) => {
+                network_offline = offline;
+                warm_streams.clear();
+                if !offline {
+                    // Reconnect right away instead of waiting out the retry interval
+                    last_reconnect = None;
+                }
+            }
             AudioCommand::SetPlaybackRate(rate) => {
                 if let Some(sink) = &current_sink {
+                    // The position clock follows the samples played, so it keeps up without being told
                     sink.set_speed(rate);
-                    // Update position timer with new rate
-                    position_timer.set_rate(rate);
                 }
             }
         }
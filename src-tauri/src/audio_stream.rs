@@ -0,0 +1,478 @@
+use crate::recent_log;
+use crate::stream_cache::StreamCache;
+use rodio::Source;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::{MediaSource, MediaSourceStream, ReadOnlySource};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+// Bytes read from the decoder at a time (~0.1s of 44.1kHz stereo PCM)
+const READ_CHUNK_BYTES: usize = 16 * 1024;
+// Samples copied out of the shared buffer per lock
+const SOURCE_CHUNK_SAMPLES: usize = 4096;
+// Assumed for a piped stream until its header has been read; nothing plays before then
+const PLACEHOLDER_CHANNELS: u16 = 2;
+const PLACEHOLDER_SAMPLE_RATE: u32 = 44100;
+// How often a held reader checks whether it was released
+const HOLD_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Default)]
+struct StreamData {
+    samples: Vec<i16>,
+    channels: u16,
+    sample_rate: u32,
+    finished: bool,
+    cancelled: bool,
+    error: Option<String>,
+    refused: bool,     // the server answered the decoder's request with 403 Forbidden
+    unsupported: bool, // the built-in decoder can't read this format or codec
+    cached_file: Option<PathBuf>, // complete copy of the compressed stream, when one was kept
+    hold_at: Option<usize>,       // samples to stop reading at until released
+}
+
+impl StreamData {
+    fn is_held(&self) -> bool {
+        !self.cancelled && self.hold_at.is_some_and(|limit| self.samples.len() >= limit)
+    }
+}
+
+// A demuxed audio track with the decoder for its codec
+struct OpenedTrack {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    channels: u16,
+    sample_rate: u32,
+}
+
+// Why symphonia couldn't open a source: a format or codec it lacks, or anything else
+enum OpenError {
+    Unsupported(String),
+    Failed(String),
+}
+
+impl OpenError {
+    fn message(self) -> String {
+        match self {
+            OpenError::Unsupported(message) | OpenError::Failed(message) => message,
+        }
+    }
+}
+
+/// Raw PCM that keeps growing while the decoder is still producing it
+#[derive(Clone)]
+pub struct StreamBuffer {
+    data: Arc<Mutex<StreamData>>,
+}
+
+impl StreamBuffer {
+    fn new(channels: u16, sample_rate: u32) -> Self {
+        Self {
+            data: Arc::new(Mutex::new(StreamData {
+                channels,
+                sample_rate,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Reads s16le PCM from the decoder's stdout on a background thread.
+    /// `upstream` feeds the decoder (e.g. yt-dlp) and is killed along with it on cancel.
+    /// If the decoder's stderr is piped it's checked for an HTTP 403 once the decoder exits.
+    /// `cache` is what the decoder's input is being copied to, finished along with the stream.
+    pub fn spawn(
+        mut decoder: Child,
+        mut upstream: Option<Child>,
+        cache: Option<StreamCache>,
+        channels: u16,
+        sample_rate: u32,
+    ) -> Result<Self, String> {
+        let mut stdout = decoder.stdout.take().ok_or("Failed to capture decoder stdout")?;
+        let buffer = Self::new(channels, sample_rate);
+
+        // Drained on its own thread so a chatty decoder can't block on a full pipe
+        let decoder_log = decoder.stderr.take().map(|mut stderr| {
+            std::thread::spawn(move || {
+                let mut log = String::new();
+                let _ = stderr.read_to_string(&mut log);
+                log
+            })
+        });
+
+        let data = Arc::clone(&buffer.data);
+        std::thread::spawn(move || {
+            let mut chunk = vec![0u8; READ_CHUNK_BYTES];
+            let mut leftover: Option<u8> = None;
+
+            loop {
+                // Not reading leaves the decoder blocked on a full pipe, and its download with it
+                while data.lock().unwrap().is_held() {
+                    std::thread::sleep(HOLD_POLL_INTERVAL);
+                }
+
+                let read = match stdout.read(&mut chunk) {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => read,
+                };
+
+                let mut bytes = Vec::with_capacity(read + 1);
+                bytes.extend(leftover.take());
+                bytes.extend_from_slice(&chunk[..read]);
+                if bytes.len() % 2 == 1 {
+                    leftover = bytes.pop();
+                }
+
+                let mut data = data.lock().unwrap();
+                if data.cancelled {
+                    break;
+                }
+                data.samples.extend(bytes.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])));
+            }
+
+            let cancelled = data.lock().unwrap().cancelled;
+            if cancelled {
+                let _ = decoder.kill();
+                if let Some(child) = upstream.as_mut() {
+                    let _ = child.kill();
+                }
+            }
+
+            let decoded = decoder.wait().map(|s| s.success()).unwrap_or(false);
+            let fetched = upstream
+                .as_mut()
+                .map(|child| child.wait().map(|s| s.success()).unwrap_or(false))
+                .unwrap_or(true);
+
+            let decoder_log = decoder_log.and_then(|log| log.join().ok());
+            if let Some(log) = &decoder_log {
+                recent_log::record("decoder", log);
+            }
+            let refused = decoder_log.is_some_and(|log| log.contains("403 Forbidden"));
+
+            let complete = !cancelled && !refused && decoded && fetched;
+            let cached_file = cache.and_then(|cache| cache.finish(complete));
+
+            let mut data = data.lock().unwrap();
+            data.cached_file = cached_file;
+            if !cancelled && refused {
+                data.refused = true;
+                data.error = Some("The audio server refused the stream URL".to_string());
+            } else if !cancelled && !decoded {
+                data.error = Some("ffmpeg conversion failed".to_string());
+            } else if !cancelled && !fetched {
+                data.error = Some("Audio download was interrupted".to_string());
+            }
+            data.finished = true;
+        });
+
+        Ok(buffer)
+    }
+
+    /// Decodes a local file with symphonia on a background thread.
+    /// Audio keeps the file's own sample rate and channel count; rodio converts on output.
+    pub fn decode_file(path: &Path) -> Result<Self, String> {
+        let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+        let source = MediaSourceStream::new(Box::new(file), Default::default());
+        let extension = path.extension().and_then(|e| e.to_str());
+        let mut track = open_track(source, extension).map_err(OpenError::message)?;
+
+        let buffer = Self::new(track.channels, track.sample_rate);
+
+        let data = Arc::clone(&buffer.data);
+        std::thread::spawn(move || {
+            let error = decode_packets(&mut track, &data);
+
+            let mut data = data.lock().unwrap();
+            if !data.cancelled {
+                data.error = error;
+            }
+            data.finished = true;
+        });
+
+        Ok(buffer)
+    }
+
+    /// Decodes compressed audio piped from `upstream`'s stdout (yt-dlp) with symphonia on a
+    /// background thread. The format is only known once the first bytes arrive, so a stream
+    /// symphonia can't read finishes with `is_unsupported` set instead of failing here.
+    /// With a `cache`, everything read from yt-dlp is copied to it as well.
+    pub fn decode_pipe(mut upstream: Child, extension: Option<&str>, cache: Option<StreamCache>) -> Result<Self, String> {
+        let stdout = upstream.stdout.take().ok_or("Failed to capture yt-dlp stdout")?;
+        let extension = extension.map(|e| e.to_string());
+        let buffer = Self::new(PLACEHOLDER_CHANNELS, PLACEHOLDER_SAMPLE_RATE);
+
+        let data = Arc::clone(&buffer.data);
+        std::thread::spawn(move || {
+            let source: Box<dyn MediaSource> = match &cache {
+                Some(cache) => Box::new(ReadOnlySource::new(cache.tee(stdout))),
+                None => Box::new(ReadOnlySource::new(stdout)),
+            };
+            let source = MediaSourceStream::new(source, Default::default());
+            let (error, unsupported) = match open_track(source, extension.as_deref()) {
+                Ok(mut track) => {
+                    {
+                        let mut data = data.lock().unwrap();
+                        data.channels = track.channels;
+                        data.sample_rate = track.sample_rate;
+                    }
+                    let error = decode_packets(&mut track, &data);
+                    // The container may carry bytes after the last packet, which the copy needs too
+                    if error.is_none() && cache.is_some() {
+                        let _ = std::io::copy(&mut track.format.into_inner(), &mut std::io::sink());
+                    }
+                    (error, false)
+                }
+                Err(OpenError::Unsupported(message)) => (Some(message), true),
+                Err(OpenError::Failed(message)) => (Some(message), false),
+            };
+
+            // yt-dlp would otherwise keep downloading into a pipe nobody reads
+            if error.is_some() || data.lock().unwrap().cancelled {
+                let _ = upstream.kill();
+            }
+            let fetched = upstream.wait().map(|s| s.success()).unwrap_or(false);
+
+            let cancelled = data.lock().unwrap().cancelled;
+            let cached_file = cache.and_then(|cache| cache.finish(!cancelled && error.is_none() && fetched));
+
+            let mut data = data.lock().unwrap();
+            data.cached_file = cached_file;
+            if !data.cancelled {
+                data.unsupported = unsupported;
+                data.error = error.or_else(|| (!fetched).then(|| "Audio download was interrupted".to_string()));
+            }
+            data.finished = true;
+        });
+
+        Ok(buffer)
+    }
+
+    /// Stops the decoder; sources already playing keep what was buffered
+    pub fn cancel(&self) {
+        self.data.lock().unwrap().cancelled = true;
+    }
+
+    /// Stop decoding once `seconds` are buffered, until `release`. Only decoders started with
+    /// `spawn` can be held.
+    pub fn hold_at(&self, seconds: f64) {
+        let mut data = self.data.lock().unwrap();
+        data.hold_at = Some((seconds * data.sample_rate as f64 * data.channels as f64) as usize);
+    }
+
+    pub fn release(&self) {
+        self.data.lock().unwrap().hold_at = None;
+    }
+
+    /// Seconds of audio decoded so far
+    pub fn buffered_seconds(&self) -> f64 {
+        let data = self.data.lock().unwrap();
+        data.samples.len() as f64 / (data.sample_rate as f64 * data.channels as f64)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.data.lock().unwrap().finished
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.lock().unwrap().samples.is_empty()
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.data.lock().unwrap().error.clone()
+    }
+
+    /// Whether the decoder was turned away with a 403, which for a resolved URL means it expired
+    pub fn was_refused(&self) -> bool {
+        self.data.lock().unwrap().refused
+    }
+
+    /// The kept copy of the compressed stream, once all of it has been written
+    pub fn cached_file(&self) -> Option<PathBuf> {
+        self.data.lock().unwrap().cached_file.clone()
+    }
+
+    /// Whether the built-in decoder gave up on the stream's format or codec, so ffmpeg might play it
+    pub fn is_unsupported(&self) -> bool {
+        self.data.lock().unwrap().unsupported
+    }
+
+    /// A playable source starting `position` seconds in
+    pub fn source_from(&self, position: f64) -> StreamSource {
+        let (channels, sample_rate) = {
+            let data = self.data.lock().unwrap();
+            (data.channels, data.sample_rate)
+        };
+        let frame = (position.max(0.0) * sample_rate as f64) as usize;
+        let index = frame * channels as usize;
+        StreamSource {
+            buffer: self.clone(),
+            index,
+            chunk: Vec::new(),
+            chunk_pos: 0,
+            underrun: false,
+            clock: SourceClock {
+                played: Arc::new(AtomicUsize::new(index)),
+                channels,
+                sample_rate,
+            },
+        }
+    }
+}
+
+/// How far into the track a StreamSource's output has got, counted in samples the sink
+/// has actually taken. Stands still while the sink is paused or playing underrun silence.
+#[derive(Clone)]
+pub struct SourceClock {
+    played: Arc<AtomicUsize>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl SourceClock {
+    pub fn position(&self) -> f64 {
+        let frames = self.played.load(Ordering::Relaxed) / self.channels.max(1) as usize;
+        frames as f64 / self.sample_rate.max(1) as f64
+    }
+}
+
+/// Plays a StreamBuffer, emitting silence if it catches up with the decoder
+pub struct StreamSource {
+    buffer: StreamBuffer,
+    index: usize,
+    chunk: Vec<i16>,
+    chunk_pos: usize,
+    underrun: bool, // the chunk is silence standing in for audio not decoded yet
+    clock: SourceClock,
+}
+
+impl StreamSource {
+    pub fn clock(&self) -> SourceClock {
+        self.clock.clone()
+    }
+}
+
+impl Iterator for StreamSource {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if self.chunk_pos == self.chunk.len() {
+            let data = self.buffer.data.lock().unwrap();
+            let channels = data.channels as usize;
+            let start = self.index.min(data.samples.len());
+            // Whole frames only, so silence inserted on an underrun can't swap the channels
+            let available = (data.samples.len() - start) / channels * channels;
+            let end = start + available.min(SOURCE_CHUNK_SAMPLES);
+
+            self.chunk.clear();
+            self.chunk_pos = 0;
+
+            if start == end {
+                if data.finished {
+                    return None;
+                }
+                // Underrun: play a silent frame while the audio thread pauses the sink to refill
+                self.chunk.resize(channels, 0);
+                self.underrun = true;
+            } else {
+                self.chunk.extend_from_slice(&data.samples[start..end]);
+                self.index = end;
+                self.underrun = false;
+            }
+        }
+
+        let sample = self.chunk[self.chunk_pos];
+        self.chunk_pos += 1;
+        if !self.underrun {
+            self.clock.played.fetch_add(1, Ordering::Relaxed);
+        }
+        Some(sample)
+    }
+}
+
+impl Source for StreamSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.buffer.data.lock().unwrap().channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.buffer.data.lock().unwrap().sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// Probes the container and sets up a decoder for its default track
+fn open_track(source: MediaSourceStream, extension: Option<&str>) -> Result<OpenedTrack, OpenError> {
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(&hint, source, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| match e {
+            SymphoniaError::Unsupported(_) => OpenError::Unsupported(format!("Unsupported audio format: {}", e)),
+            e => OpenError::Failed(format!("Couldn't read audio: {}", e)),
+        })?;
+    let format = probed.format;
+
+    let track = format.default_track().ok_or_else(|| OpenError::Failed("No audio track found".to_string()))?;
+    let track_id = track.id;
+    let params = track.codec_params.clone();
+    let sample_rate = params.sample_rate.ok_or_else(|| OpenError::Failed("Unknown sample rate".to_string()))?;
+    let channels = params
+        .channels
+        .map(|c| c.count() as u16)
+        .ok_or_else(|| OpenError::Failed("Unknown channel layout".to_string()))?;
+    let decoder = symphonia::default::get_codecs()
+        .make(&params, &DecoderOptions::default())
+        .map_err(|e| OpenError::Unsupported(format!("No built-in decoder for this codec: {}", e)))?;
+
+    Ok(OpenedTrack { format, decoder, track_id, channels, sample_rate })
+}
+
+// Decodes packets into the buffer until the track ends, it's cancelled or decoding fails
+fn decode_packets(track: &mut OpenedTrack, data: &Mutex<StreamData>) -> Option<String> {
+    loop {
+        let packet = match track.format.next_packet() {
+            Ok(packet) => packet,
+            // symphonia signals the end of the stream as an unexpected EOF
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => return None,
+            Err(e) => return Some(format!("Decoding failed: {}", e)),
+        };
+        if packet.track_id() != track.track_id {
+            continue;
+        }
+
+        let decoded = match track.decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupt packet costs a few milliseconds of audio, not the whole track
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Some(format!("Decoding failed: {}", e)),
+        };
+
+        let mut samples = SampleBuffer::<i16>::new(decoded.capacity() as u64, *decoded.spec());
+        samples.copy_interleaved_ref(decoded);
+
+        let mut data = data.lock().unwrap();
+        if data.cancelled {
+            return None;
+        }
+        data.samples.extend_from_slice(samples.samples());
+    }
+}
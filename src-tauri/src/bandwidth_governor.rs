@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+
+// Playback has to keep streaming (or stay done) this long before downloads are throttled (or
+// released), so a short pause or a track played from a file doesn't restart them
+const SETTLE_DELAY: Duration = Duration::from_secs(5);
+
+/// A running download only restarts at its new rate once playback has stayed the other way this
+/// long; every restart costs a new yt-dlp run and its extraction requests
+pub const RESTART_HOLD: Duration = Duration::from_secs(60);
+
+/// yt-dlp `--limit-rate` for each throttled download
+pub const THROTTLED_RATE: &str = "256K";
+
+#[derive(Debug, Clone, Default)]
+pub struct BandwidthState {
+    pub playback_streaming: bool,
+    prioritized: HashSet<String>, // downloads kept at full speed regardless, by video ID
+}
+
+impl BandwidthState {
+    pub fn throttles(&self, video_id: &str) -> bool {
+        self.playback_streaming && !self.prioritized.contains(video_id)
+    }
+
+    pub fn is_prioritized(&self, video_id: &str) -> bool {
+        self.prioritized.contains(video_id)
+    }
+}
+
+/// Shares the connection between playback and downloads: while the current track is still
+/// being fetched, downloads run rate-limited unless they were given priority
+pub struct BandwidthGovernor {
+    state: watch::Sender<BandwidthState>,
+    pending: Mutex<Option<(bool, JoinHandle<()>)>>, // streaming change waiting out SETTLE_DELAY
+}
+
+impl BandwidthGovernor {
+    pub fn new() -> Self {
+        let (state, _) = watch::channel(BandwidthState::default());
+        Self {
+            state,
+            pending: Mutex::new(None),
+        }
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<BandwidthState> {
+        self.state.subscribe()
+    }
+
+    /// Report whether playback is fetching audio it doesn't have yet
+    pub async fn set_playback_streaming(self: &Arc<Self>, streaming: bool) {
+        let mut pending = self.pending.lock().await;
+        if let Some((value, handle)) = pending.take() {
+            if value == streaming && !handle.is_finished() {
+                *pending = Some((value, handle));
+                return;
+            }
+            handle.abort();
+        }
+        if self.state.borrow().playback_streaming == streaming {
+            return;
+        }
+
+        let governor = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            tokio::time::sleep(SETTLE_DELAY).await;
+            governor.state.send_modify(|state| state.playback_streaming = streaming);
+            if streaming {
+                println!("🐢 Throttling downloads while playback streams");
+            } else {
+                println!("🚀 Downloads back to full speed");
+            }
+        });
+        *pending = Some((streaming, handle));
+    }
+
+    /// Keep a download at full speed even while playback streams, or hand it back to the governor
+    pub fn set_prioritized(&self, video_id: &str, prioritized: bool) {
+        self.state.send_if_modified(|state| {
+            if prioritized {
+                state.prioritized.insert(video_id.to_string())
+            } else {
+                state.prioritized.remove(video_id)
+            }
+        });
+    }
+}
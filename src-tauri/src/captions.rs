@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptionLine {
+    pub start: f64, // seconds
+    pub end: f64,
+    pub text: String,
+}
+
+/// Parse a WebVTT file into caption lines.
+/// Auto-generated captions repeat rolling lines, so consecutive duplicates are collapsed.
+pub fn parse_vtt(content: &str) -> Vec<CaptionLine> {
+    let mut lines = Vec::new();
+    let mut current: Option<(f64, f64, Vec<String>)> = None;
+
+    for raw_line in content.lines() {
+        let line = raw_line.trim();
+
+        if let Some((start, end)) = parse_cue_timing(line) {
+            if let Some(cue) = current.take() {
+                push_cue(&mut lines, cue);
+            }
+            current = Some((start, end, Vec::new()));
+        } else if line.is_empty() {
+            if let Some(cue) = current.take() {
+                push_cue(&mut lines, cue);
+            }
+        } else if let Some((_, _, text)) = current.as_mut() {
+            let cleaned = strip_tags(line);
+            if !cleaned.is_empty() {
+                text.push(cleaned);
+            }
+        }
+    }
+
+    if let Some(cue) = current.take() {
+        push_cue(&mut lines, cue);
+    }
+
+    lines
+}
+
+/// Case-insensitive search over caption lines
+pub fn search(lines: &[CaptionLine], query: &str) -> Vec<CaptionLine> {
+    let query = query.to_lowercase();
+    lines
+        .iter()
+        .filter(|line| line.text.to_lowercase().contains(&query))
+        .cloned()
+        .collect()
+}
+
+fn push_cue(lines: &mut Vec<CaptionLine>, (start, end, text): (f64, f64, Vec<String>)) {
+    // Rolling captions carry the previous line over, keep only the newest one
+    let Some(text) = text.last().cloned() else {
+        return;
+    };
+
+    if let Some(previous) = lines.last_mut() {
+        if previous.text == text {
+            previous.end = end;
+            return;
+        }
+    }
+
+    lines.push(CaptionLine { start, end, text });
+}
+
+// "00:01:02.345 --> 00:01:05.000 align:start position:0%"
+fn parse_cue_timing(line: &str) -> Option<(f64, f64)> {
+    let (start, rest) = line.split_once("-->")?;
+    let end = rest.split_whitespace().next()?;
+    Some((parse_timestamp(start.trim())?, parse_timestamp(end)?))
+}
+
+fn parse_timestamp(value: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    for part in value.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(seconds)
+}
+
+fn strip_tags(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut in_tag = false;
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => result.push(c),
+            _ => {}
+        }
+    }
+    result.trim().to_string()
+}
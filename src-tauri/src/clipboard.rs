@@ -0,0 +1,66 @@
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Read plain text from the system clipboard using the platform's clipboard tools
+pub async fn read_text() -> Result<String, String> {
+    #[cfg(target_os = "windows")]
+    let candidates: &[(&str, &[&str])] = &[("powershell", &["-NoProfile", "-Command", "Get-Clipboard -Raw"])];
+
+    #[cfg(target_os = "macos")]
+    let candidates: &[(&str, &[&str])] = &[("pbpaste", &[])];
+
+    // Wayland first, then the common X11 tools
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let candidates: &[(&str, &[&str])] = &[
+        ("wl-paste", &["--no-newline"]),
+        ("xclip", &["-selection", "clipboard", "-o"]),
+        ("xsel", &["--clipboard", "--output"]),
+    ];
+
+    for (program, args) in candidates {
+        if let Ok(output) = Command::new(program).args(*args).output().await {
+            if output.status.success() {
+                return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+            }
+        }
+    }
+
+    Err("No clipboard tool available".to_string())
+}
+
+/// Put plain text on the system clipboard using the platform's clipboard tools
+pub async fn write_text(text: &str) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let candidates: &[(&str, &[&str])] = &[("powershell", &["-NoProfile", "-Command", "$input | Set-Clipboard"])];
+
+    #[cfg(target_os = "macos")]
+    let candidates: &[(&str, &[&str])] = &[("pbcopy", &[])];
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let candidates: &[(&str, &[&str])] = &[
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard", "-i"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ];
+
+    for (program, args) in candidates {
+        let Ok(mut child) = Command::new(program).args(*args).stdin(Stdio::piped()).spawn() else {
+            continue;
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            if stdin.write_all(text.as_bytes()).await.is_err() {
+                continue;
+            }
+            // Closing stdin lets the tool know the text is complete
+            drop(stdin);
+        }
+
+        if child.wait().await.map(|status| status.success()).unwrap_or(false) {
+            return Ok(());
+        }
+    }
+
+    Err("No clipboard tool available".to_string())
+}
@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+// Reaching YouTube is what matters, not just having a network interface
+const PROBE_ADDRESS: &str = "www.youtube.com:443";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Tracks whether YouTube is reachable so streaming and downloads can wait out outages
+pub struct ConnectivityMonitor {
+    online: AtomicBool,
+    metered: AtomicBool,
+}
+
+impl ConnectivityMonitor {
+    pub fn new() -> Self {
+        Self {
+            online: AtomicBool::new(true),
+            metered: AtomicBool::new(false),
+        }
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.online.load(Ordering::SeqCst)
+    }
+
+    /// Probe the connection, returning the new status when it changed since the last check
+    pub async fn check(&self) -> Option<bool> {
+        let online = probe().await;
+        let was_online = self.online.swap(online, Ordering::SeqCst);
+        (online != was_online).then_some(online)
+    }
+
+    pub fn is_metered(&self) -> bool {
+        self.metered.load(Ordering::SeqCst)
+    }
+
+    /// Ask the OS whether the active connection is metered, returning the new value when it changed
+    pub async fn check_metered(&self) -> Option<bool> {
+        let metered = detect_metered().await;
+        let was_metered = self.metered.swap(metered, Ordering::SeqCst);
+        (metered != was_metered).then_some(metered)
+    }
+}
+
+async fn probe() -> bool {
+    let connect = tokio::net::TcpStream::connect(PROBE_ADDRESS);
+    matches!(tokio::time::timeout(PROBE_TIMEOUT, connect).await, Ok(Ok(_)))
+}
+
+// Connections the OS can't classify are treated as unmetered
+async fn detect_metered() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        let script = "[void][Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]; \
+            $p = [Windows.Networking.Connectivity.NetworkInformation]::GetInternetConnectionProfile(); \
+            if ($p) { $p.GetConnectionCost().NetworkCostType }";
+        match tokio::process::Command::new("powershell").args(["-NoProfile", "-Command", script]).output().await {
+            // Fixed and Variable cost plans are metered; Unrestricted and Unknown are not
+            Ok(output) => matches!(String::from_utf8_lossy(&output.stdout).trim(), "Fixed" | "Variable"),
+            Err(_) => false,
+        }
+    }
+
+    // NetworkManager reports "yes" or "yes (guessed)" for metered devices
+    #[cfg(target_os = "linux")]
+    {
+        match tokio::process::Command::new("nmcli").args(["-t", "-f", "GENERAL.METERED", "device", "show"]).output().await {
+            Ok(output) => String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .any(|line| line.trim_start_matches("GENERAL.METERED:").starts_with("yes")),
+            Err(_) => false,
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        false
+    }
+}
@@ -0,0 +1,47 @@
+use crate::models::{ContentType, YTVideoInfo};
+
+// Music rarely runs this long; mixes are the exception and usually say so in the title
+const SPEECH_MIN_DURATION: i64 = 20 * 60;
+// Videos in talk-heavy categories shorter than this are more often clips than full talks
+const SPEECH_CATEGORY_MIN_DURATION: i64 = 5 * 60;
+
+const SPEECH_KEYWORDS: &[&str] = &["podcast", "episode", "ep.", "lecture", "interview", "ted talk", "audiobook", "sermon"];
+const MUSIC_KEYWORDS: &[&str] = &["official audio", "official video", "lyrics", "remix", "mix", "album", "feat.", "ft."];
+
+// YouTube categories whose long-form videos are almost always spoken word
+const SPEECH_CATEGORIES: &[&str] = &[
+    "Education",
+    "Science & Technology",
+    "News & Politics",
+    "Howto & Style",
+    "People & Blogs",
+    "Nonprofits & Activism",
+];
+
+/// Classify from yt-dlp's category metadata, or None when the categories don't settle it
+pub fn classify_metadata(categories: &[&str], duration: i64) -> Option<ContentType> {
+    if categories.contains(&"Music") {
+        return Some(ContentType::Music);
+    }
+    if duration >= SPEECH_CATEGORY_MIN_DURATION && categories.iter().any(|c| SPEECH_CATEGORIES.contains(c)) {
+        return Some(ContentType::Speech);
+    }
+    None
+}
+
+/// Whether a track is music or speech, preferring its stored classification over title and duration hints
+pub fn classify(track: &YTVideoInfo) -> ContentType {
+    if let Some(content_type) = track.content_type {
+        return content_type;
+    }
+
+    let title = track.title.to_lowercase();
+
+    if MUSIC_KEYWORDS.iter().any(|k| title.contains(k)) {
+        return ContentType::Music;
+    }
+    if SPEECH_KEYWORDS.iter().any(|k| title.contains(k)) || track.duration >= SPEECH_MIN_DURATION {
+        return ContentType::Speech;
+    }
+    ContentType::Music
+}
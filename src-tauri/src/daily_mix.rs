@@ -0,0 +1,128 @@
+use crate::database::DatabaseManager;
+use crate::models::{ContentType, Track};
+use rand::seq::SliceRandom;
+use std::collections::{HashMap, HashSet};
+
+// Plays older than this don't shape the mixes any more
+const HISTORY_WINDOW_DAYS: i64 = 30;
+// A favorite counts as this many plays
+const FAVORITE_WEIGHT: i64 = 3;
+const MAX_MIXES: usize = 5;
+const MIX_SIZE: usize = 25;
+// An artist needs this many tracks left to anchor a mix; at most this share of a mix is theirs
+const MIN_SEED_TRACKS: usize = 3;
+const SEED_SHARE: f64 = 0.5;
+
+struct Candidate {
+    track: Track,
+    score: i64,
+}
+
+/// Rebuild the daily mixes from recent plays and favorites, returning the new playlist ids
+pub async fn generate_daily_mixes(db: &DatabaseManager) -> Result<Vec<String>, String> {
+    let since = chrono::Utc::now().timestamp() - HISTORY_WINDOW_DAYS * 24 * 60 * 60;
+    let candidates = db
+        .get_mix_candidates(since)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(track, plays, favorite)| Candidate {
+            score: plays + if favorite { FAVORITE_WEIGHT } else { 0 },
+            track,
+        })
+        .collect();
+
+    let playlists: Vec<(String, String, Vec<String>)> = build_mixes(candidates)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (artist, track_ids))| {
+            (format!("daily-mix-{}", index + 1), format!("Daily Mix {} · {}", index + 1, artist), track_ids)
+        })
+        .collect();
+
+    db.replace_generated_playlists(&playlists).await.map_err(|e| e.to_string())?;
+    println!("🎛️ Generated {} daily mix(es)", playlists.len());
+
+    Ok(playlists.into_iter().map(|(id, _, _)| id).collect())
+}
+
+// One mix per top artist: their best tracks, filled up with the best tracks of other
+// artists sharing their content type. No track appears in two mixes.
+fn build_mixes(mut candidates: Vec<Candidate>) -> Vec<(String, Vec<String>)> {
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.score));
+
+    let mut artist_scores: HashMap<String, (String, i64)> = HashMap::new();
+    for candidate in &candidates {
+        if let Some(artist) = artist_name(&candidate.track) {
+            let entry = artist_scores.entry(artist.to_lowercase()).or_insert((artist.to_string(), 0));
+            entry.1 += candidate.score;
+        }
+    }
+    let mut artists: Vec<(String, String, i64)> =
+        artist_scores.into_iter().map(|(key, (name, score))| (key, name, score)).collect();
+    artists.sort_by_key(|(_, _, score)| std::cmp::Reverse(*score));
+
+    let seed_limit = (MIX_SIZE as f64 * SEED_SHARE) as usize;
+    let mut used: HashSet<String> = HashSet::new();
+    let mut mixes = Vec::new();
+    let mut rng = rand::thread_rng();
+
+    for (key, name, _) in artists {
+        if mixes.len() >= MAX_MIXES {
+            break;
+        }
+
+        let seed: Vec<&Candidate> = candidates
+            .iter()
+            .filter(|c| artist_key(&c.track).as_deref() == Some(key.as_str()) && !used.contains(&c.track.id))
+            .take(seed_limit)
+            .collect();
+        if seed.len() < MIN_SEED_TRACKS {
+            continue;
+        }
+
+        let tag = most_common_type(&seed);
+        let mut track_ids: Vec<String> = seed.iter().map(|c| c.track.id.clone()).collect();
+        let fill: Vec<String> = candidates
+            .iter()
+            .filter(|c| {
+                artist_key(&c.track).as_deref() != Some(key.as_str())
+                    && c.track.content_type == tag
+                    && !used.contains(&c.track.id)
+            })
+            .take(MIX_SIZE - track_ids.len())
+            .map(|c| c.track.id.clone())
+            .collect();
+        track_ids.extend(fill);
+
+        track_ids.shuffle(&mut rng);
+        used.extend(track_ids.iter().cloned());
+        mixes.push((name, track_ids));
+    }
+
+    mixes
+}
+
+// The artist the user sees, their own override first
+fn artist_name(track: &Track) -> Option<&str> {
+    track
+        .custom_author
+        .as_deref()
+        .or(track.author.as_deref())
+        .filter(|a| !a.is_empty() && *a != "Unknown")
+}
+
+fn artist_key(track: &Track) -> Option<String> {
+    artist_name(track).map(|a| a.to_lowercase())
+}
+
+fn most_common_type(tracks: &[&Candidate]) -> Option<ContentType> {
+    let mut counts: Vec<(Option<ContentType>, usize)> = Vec::new();
+    for candidate in tracks {
+        match counts.iter_mut().find(|(t, _)| *t == candidate.track.content_type) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((candidate.track.content_type, 1)),
+        }
+    }
+    counts.into_iter().max_by_key(|(_, count)| *count).and_then(|(t, _)| t)
+}
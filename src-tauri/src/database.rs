@@ -1,9 +1,27 @@
-use sqlx::{sqlite::SqlitePool, Row};
-use std::path::PathBuf;
-use crate::models::{AppSettings, Playlist, Track};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteSynchronous};
+use sqlx::sqlite::{SqliteConnection, SqliteRow};
+use sqlx::{Connection, Row};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use crate::app_data::AppData;
+use crate::models::{AppSettings, AudioFingerprint, Bookmark, ChannelRule, ContentType, MaintenanceProgress, MaintenanceReport, PlayRecord, Playlist, PlaylistSummary, Track, TrackSkipStats, WeeklyRecap};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// WAL lets readers run alongside a writer, so a small pool is enough
+const MAX_CONNECTIONS: u32 = 5;
+// How long a connection waits on a locked database before failing with "database is locked"
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+// Tables holding user data, parents before the tables that reference them
+const USER_TABLES: [&str; 10] = ["tracks", "playlists", "playlist_memberships", "plays", "bookmarks", "channel_rules", "weekly_recaps", "fingerprints", "app_settings", "preferences"];
+// Maintenance steps in the order they run; reindexing first repairs broken indexes before the vacuum copies them
+const MAINTENANCE_STEPS: [&str; 3] = ["integrity_check", "reindex", "vacuum"];
 
 pub struct DatabaseManager {
     pool: SqlitePool,
+    maintenance: tokio::sync::Mutex<()>,
 }
 
 impl DatabaseManager {
@@ -15,21 +33,31 @@ impl DatabaseManager {
             std::fs::create_dir_all(parent).ok();
         }
 
-        let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
-        let pool = SqlitePool::connect(&db_url).await?;
+        let db_url = format!("sqlite:{}", db_path.display());
+        let options = SqliteConnectOptions::from_str(&db_url)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(BUSY_TIMEOUT)
+            .foreign_keys(true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(MAX_CONNECTIONS)
+            .acquire_timeout(BUSY_TIMEOUT)
+            .connect_with(options)
+            .await?;
 
-        let manager = Self { pool };
+        let manager = Self {
+            pool,
+            maintenance: tokio::sync::Mutex::new(()),
+        };
         manager.init_database().await?;
 
         Ok(manager)
     }
 
-    fn get_db_path() -> PathBuf {
-        let mut path = dirs::data_local_dir()
-            .unwrap_or_else(|| PathBuf::from("."));
-        path.push("ytaudiobar");
-        path.push("ytaudiobar.db");
-        path
+    pub fn get_db_path() -> PathBuf {
+        AppData::get_data_dir().join("ytaudiobar.db")
     }
 
     async fn init_database(&self) -> Result<(), sqlx::Error> {
@@ -50,6 +78,13 @@ impl DatabaseManager {
         .execute(&self.pool)
         .await?;
 
+        // Columns added after the initial schema
+        self.add_column_if_missing("tracks", "custom_title", "TEXT").await?;
+        self.add_column_if_missing("tracks", "custom_author", "TEXT").await?;
+        self.add_column_if_missing("tracks", "note", "TEXT").await?;
+        self.add_column_if_missing("tracks", "content_type", "TEXT").await?;
+        self.add_column_if_missing("tracks", "duration_lookup_failed_at", "INTEGER").await?;
+
         // Create playlists table
         sqlx::query(
             r#"
@@ -64,6 +99,8 @@ impl DatabaseManager {
         .execute(&self.pool)
         .await?;
 
+        self.add_column_if_missing("playlists", "is_generated", "BOOLEAN DEFAULT 0").await?;
+
         // Create playlist_memberships table
         sqlx::query(
             r#"
@@ -95,15 +132,270 @@ impl DatabaseManager {
         .execute(&self.pool)
         .await?;
 
+        // Create plays table (one row per playback start)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS plays (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                track_id TEXT NOT NULL,
+                played_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_plays_track_id ON plays(track_id)")
+            .execute(&self.pool)
+            .await?;
+
+        self.add_column_if_missing("plays", "skipped", "BOOLEAN DEFAULT 0").await?;
+        // Kept so plays of tracks outside the library can still be named in recaps
+        self.add_column_if_missing("plays", "title", "TEXT").await?;
+        self.add_column_if_missing("plays", "author", "TEXT").await?;
+        self.add_column_if_missing("plays", "duration", "INTEGER").await?;
+
+        // Create bookmarks table
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS bookmarks (
+                id TEXT PRIMARY KEY,
+                track_id TEXT NOT NULL,
+                position REAL NOT NULL,
+                note TEXT,
+                created_date INTEGER
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create channel_rules table (per-uploader defaults)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS channel_rules (
+                uploader TEXT PRIMARY KEY,
+                playback_rate REAL,
+                auto_download BOOLEAN DEFAULT 0,
+                playlist_id TEXT,
+                FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE SET NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create weekly_recaps table (one JSON recap per week)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS weekly_recaps (
+                week_start TEXT PRIMARY KEY,
+                recap TEXT NOT NULL,
+                generated_at INTEGER
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create fingerprints table (downloads grouped by recording)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS fingerprints (
+                track_id TEXT PRIMARY KEY,
+                group_id TEXT NOT NULL,
+                duration REAL NOT NULL,
+                fingerprint TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_fingerprints_group_id ON fingerprints(group_id)")
+            .execute(&self.pool)
+            .await?;
+
+        // Create preferences table (JSON values keyed by name)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS preferences (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         // Create system "All Favorites" playlist if not exists
         self.create_system_playlist().await?;
 
         Ok(())
     }
 
+    /// Size of the database file including its WAL and shared-memory files
+    pub fn get_database_size() -> i64 {
+        let db_path = Self::get_db_path();
+        ["", "-wal", "-shm"]
+            .iter()
+            .filter_map(|suffix| {
+                let mut path = db_path.clone().into_os_string();
+                path.push(suffix);
+                std::fs::metadata(path).ok()
+            })
+            .map(|m| m.len() as i64)
+            .sum()
+    }
+
+    /// Check integrity, rebuild indexes and vacuum, reporting each step as it starts.
+    /// Returns None when maintenance is already running.
+    pub async fn run_maintenance(
+        &self,
+        on_progress: impl Fn(MaintenanceProgress),
+    ) -> Result<Option<MaintenanceReport>, sqlx::Error> {
+        let Ok(_guard) = self.maintenance.try_lock() else {
+            return Ok(None);
+        };
+        let size_before = Self::get_database_size();
+        let mut problems = Vec::new();
+
+        for (index, step) in MAINTENANCE_STEPS.iter().enumerate() {
+            on_progress(MaintenanceProgress {
+                step: step.to_string(),
+                progress: index as f64 / MAINTENANCE_STEPS.len() as f64,
+            });
+
+            match *step {
+                "integrity_check" => {
+                    let rows = sqlx::query("PRAGMA integrity_check").fetch_all(&self.pool).await?;
+                    problems = rows
+                        .iter()
+                        .map(|row| row.get::<String, _>(0))
+                        .filter(|message| message != "ok")
+                        .collect();
+                }
+                "reindex" => {
+                    sqlx::query("REINDEX").execute(&self.pool).await?;
+                }
+                _ => {
+                    sqlx::query("VACUUM").execute(&self.pool).await?;
+                    // Fold the WAL back in so the reclaimed space shows up on disk
+                    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&self.pool).await?;
+                }
+            }
+        }
+
+        on_progress(MaintenanceProgress {
+            step: "done".to_string(),
+            progress: 1.0,
+        });
+
+        Ok(Some(MaintenanceReport {
+            integrity_ok: problems.is_empty(),
+            problems,
+            bytes_reclaimed: (size_before - Self::get_database_size()).max(0),
+            completed_at: chrono::Utc::now().timestamp(),
+        }))
+    }
+
+    /// Delete all user data, leaving the schema and the system playlist in place
+    pub async fn reset(&self) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        for table in USER_TABLES.iter().rev() {
+            sqlx::query(&format!("DELETE FROM {}", table))
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        self.create_system_playlist().await?;
+        sqlx::query("VACUUM").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Write a consistent copy of the whole database to `path`
+    pub async fn snapshot_to(&self, path: &Path) -> Result<(), sqlx::Error> {
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Replace all user data with the contents of another ytaudiobar database.
+    /// Only columns both schemas have are copied, so exports from older versions still import.
+    pub async fn restore_from(&self, path: &Path) -> Result<(), sqlx::Error> {
+        // ATTACH is per connection, so the whole copy runs on one
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("ATTACH DATABASE ? AS profile")
+            .bind(path.to_string_lossy().to_string())
+            .execute(&mut *conn)
+            .await?;
+
+        let result = Self::copy_attached_profile(&mut conn).await;
+
+        sqlx::query("DETACH DATABASE profile").execute(&mut *conn).await?;
+        result?;
+
+        self.create_system_playlist().await?;
+        Ok(())
+    }
+
+    async fn copy_attached_profile(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+        let mut tx = conn.begin().await?;
+
+        for table in USER_TABLES.iter().rev() {
+            sqlx::query(&format!("DELETE FROM main.{}", table))
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for table in USER_TABLES {
+            let ours: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?, 'main')")
+                .bind(table)
+                .fetch_all(&mut *tx)
+                .await?;
+            let theirs: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?, 'profile')")
+                .bind(table)
+                .fetch_all(&mut *tx)
+                .await?;
+
+            let columns: Vec<&str> = ours.iter().filter(|c| theirs.contains(c)).map(|c| c.as_str()).collect();
+            if columns.is_empty() {
+                continue; // table didn't exist in the exporting version
+            }
+
+            let columns = columns.join(", ");
+            sqlx::query(&format!(
+                "INSERT INTO main.{table} ({columns}) SELECT {columns} FROM profile.{table}"
+            ))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    async fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<(), sqlx::Error> {
+        let columns: Vec<String> = sqlx::query_scalar(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(&self.pool)
+            .await?;
+
+        if !columns.iter().any(|c| c == column) {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     async fn create_system_playlist(&self) -> Result<(), sqlx::Error> {
         let exists: bool = sqlx::query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM playlists WHERE is_system_playlist = 1 LIMIT 1)",
+            "SELECT EXISTS(SELECT 1 FROM playlists WHERE id = 'favorites' LIMIT 1)",
         )
         .fetch_one(&self.pool)
         .await?;
@@ -125,12 +417,13 @@ impl DatabaseManager {
     }
 
     pub async fn save_track(&self, track: &Track) -> Result<(), sqlx::Error> {
-        // Use INSERT OR IGNORE instead of REPLACE to avoid triggering ON DELETE CASCADE
-        // which would delete all playlist memberships when track already exists
+        // Upsert instead of REPLACE to avoid triggering ON DELETE CASCADE, which would delete
+        // all playlist memberships when the track already exists. Only a missing content type is filled in.
         sqlx::query(
             r#"
-            INSERT OR IGNORE INTO tracks (id, title, author, duration, thumbnail_url, added_date, file_path)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO tracks (id, title, author, duration, thumbnail_url, added_date, file_path, content_type)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET content_type = COALESCE(tracks.content_type, excluded.content_type)
             "#,
         )
         .bind(&track.id)
@@ -140,166 +433,1120 @@ impl DatabaseManager {
         .bind(&track.thumbnail_url)
         .bind(track.added_date)
         .bind(&track.file_path)
+        .bind(track.content_type.as_ref().map(|c| c.as_str()))
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    pub async fn get_track(&self, id: &str) -> Result<Option<Track>, sqlx::Error> {
-        let row = sqlx::query(
-            "SELECT id, title, author, duration, thumbnail_url, added_date, file_path FROM tracks WHERE id = ?"
+    /// Record where a downloaded track's audio lives, saving the track first if it isn't known yet.
+    /// Custom names from the download's metadata fill in ones the database doesn't have.
+    pub async fn set_download_path(&self, track: &Track, file_path: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO tracks (id, title, author, duration, thumbnail_url, added_date, file_path, content_type, custom_title, custom_author)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                file_path = excluded.file_path,
+                custom_title = COALESCE(tracks.custom_title, excluded.custom_title),
+                custom_author = COALESCE(tracks.custom_author, excluded.custom_author)
+            "#,
         )
-        .bind(id)
-        .fetch_optional(&self.pool)
+        .bind(&track.id)
+        .bind(&track.title)
+        .bind(&track.author)
+        .bind(track.duration)
+        .bind(&track.thumbnail_url)
+        .bind(track.added_date)
+        .bind(file_path)
+        .bind(track.content_type.as_ref().map(|c| c.as_str()))
+        .bind(&track.custom_title)
+        .bind(&track.custom_author)
+        .execute(&self.pool)
         .await?;
 
-        Ok(row.map(|r| Track {
-            id: r.get("id"),
-            title: r.get("title"),
-            author: r.get("author"),
-            duration: r.get("duration"),
-            thumbnail_url: r.get("thumbnail_url"),
-            added_date: r.get("added_date"),
-            file_path: r.get("file_path"),
-        }))
+        Ok(())
     }
 
-    pub async fn delete_track(&self, id: &str) -> Result<(), sqlx::Error> {
-        sqlx::query("DELETE FROM tracks WHERE id = ?")
-            .bind(id)
+    /// Point a known track at its downloaded file, or mark it as not downloaded with None
+    pub async fn update_download_path(&self, track_id: &str, file_path: Option<&str>) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE tracks SET file_path = ? WHERE id = ?")
+            .bind(file_path)
+            .bind(track_id)
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
-    pub async fn create_playlist(&self, name: &str) -> Result<String, sqlx::Error> {
-        let id = uuid::Uuid::new_v4().to_string();
-        let now = chrono::Utc::now().timestamp();
+    /// Make `tracks.file_path` match the downloads on disk, given as (track id, file path) pairs
+    pub async fn sync_download_paths(&self, downloads: &[(String, String)]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
 
-        sqlx::query(
-            "INSERT INTO playlists (id, name, created_date, is_system_playlist) VALUES (?, ?, ?, 0)"
+        sqlx::query("UPDATE tracks SET file_path = NULL WHERE file_path IS NOT NULL")
+            .execute(&mut *tx)
+            .await?;
+
+        for (track_id, file_path) in downloads {
+            sqlx::query("UPDATE tracks SET file_path = ? WHERE id = ?")
+                .bind(file_path)
+                .bind(track_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await
+    }
+
+    pub async fn get_track(&self, id: &str) -> Result<Option<Track>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT id, title, author, duration, thumbnail_url, added_date, file_path, custom_title, custom_author, note, content_type FROM tracks WHERE id = ?"
         )
-        .bind(&id)
-        .bind(name)
-        .bind(now)
-        .execute(&self.pool)
+        .bind(id)
+        .fetch_optional(&self.pool)
         .await?;
 
-        Ok(id)
+        Ok(row.as_ref().map(track_from_row))
     }
 
-    pub async fn delete_playlist(&self, id: &str) -> Result<(), sqlx::Error> {
-        sqlx::query("DELETE FROM playlists WHERE id = ? AND is_system_playlist = 0")
-            .bind(id)
+    /// Set or clear (with None) the user's display overrides and note for a saved track
+    pub async fn update_track_display(
+        &self,
+        track_id: &str,
+        title: Option<&str>,
+        author: Option<&str>,
+        note: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let result = sqlx::query("UPDATE tracks SET custom_title = ?, custom_author = ?, note = ? WHERE id = ?")
+            .bind(title)
+            .bind(author)
+            .bind(note)
+            .bind(track_id)
             .execute(&self.pool)
             .await?;
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
         Ok(())
     }
 
-    pub async fn add_track_to_playlist(&self, track_id: &str, playlist_id: &str) -> Result<(), sqlx::Error> {
-        let id = uuid::Uuid::new_v4().to_string();
-        let now = chrono::Utc::now().timestamp();
+    pub async fn update_track_duration(&self, track_id: &str, duration: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE tracks SET duration = ? WHERE id = ?")
+            .bind(duration)
+            .bind(track_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
 
-        sqlx::query(
-            "INSERT INTO playlist_memberships (id, playlist_id, track_id, added_date, is_favorite) VALUES (?, ?, ?, ?, 0)"
+    /// Saved tracks without a known duration, newest first, leaving out ones whose lookup failed
+    /// after `failed_before`
+    pub async fn get_track_ids_missing_duration(&self, limit: i64, failed_before: i64) -> Result<Vec<String>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id FROM tracks WHERE duration <= 0 AND (duration_lookup_failed_at IS NULL OR duration_lookup_failed_at < ?) ORDER BY added_date DESC LIMIT ?",
         )
-        .bind(&id)
-        .bind(playlist_id)
-        .bind(track_id)
-        .bind(now)
-        .execute(&self.pool)
+        .bind(failed_before)
+        .bind(limit)
+        .fetch_all(&self.pool)
         .await?;
+        Ok(rows.iter().map(|row| row.get("id")).collect())
+    }
 
+    pub async fn set_duration_lookup_failed(&self, track_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE tracks SET duration_lookup_failed_at = ? WHERE id = ?")
+            .bind(chrono::Utc::now().timestamp())
+            .bind(track_id)
+            .execute(&self.pool)
+            .await?;
         Ok(())
     }
 
-    pub async fn remove_track_from_playlist(&self, track_id: &str, playlist_id: &str) -> Result<(), sqlx::Error> {
-        sqlx::query("DELETE FROM playlist_memberships WHERE track_id = ? AND playlist_id = ?")
-            .bind(track_id)
-            .bind(playlist_id)
+    pub async fn set_track_content_type(&self, id: &str, content_type: Option<ContentType>) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE tracks SET content_type = ? WHERE id = ?")
+            .bind(content_type.as_ref().map(|c| c.as_str()))
+            .bind(id)
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
-    pub async fn get_playlist_tracks(&self, playlist_id: &str) -> Result<Vec<Track>, sqlx::Error> {
+    pub async fn get_tracks_by_content_type(&self, content_type: ContentType) -> Result<Vec<Track>, sqlx::Error> {
         let rows = sqlx::query(
-            r#"
-            SELECT t.id, t.title, t.author, t.duration, t.thumbnail_url, t.added_date, t.file_path
-            FROM tracks t
-            INNER JOIN playlist_memberships pm ON t.id = pm.track_id
-            WHERE pm.playlist_id = ?
-            ORDER BY pm.added_date DESC
-            "#
+            "SELECT id, title, author, duration, thumbnail_url, added_date, file_path, custom_title, custom_author, note, content_type FROM tracks WHERE content_type = ? ORDER BY added_date DESC"
         )
-        .bind(playlist_id)
+        .bind(content_type.as_str())
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(rows
-            .into_iter()
-            .map(|r| Track {
-                id: r.get("id"),
-                title: r.get("title"),
-                author: r.get("author"),
-                duration: r.get("duration"),
-                thumbnail_url: r.get("thumbnail_url"),
-                added_date: r.get("added_date"),
-                file_path: r.get("file_path"),
-            })
-            .collect())
+        Ok(rows.iter().map(track_from_row).collect())
     }
 
-    pub async fn add_to_favorites(&self, track_id: &str) -> Result<(), sqlx::Error> {
-        self.add_track_to_playlist(track_id, "favorites").await
-    }
-
-    pub async fn remove_from_favorites(&self, track_id: &str) -> Result<(), sqlx::Error> {
-        self.remove_track_from_playlist(track_id, "favorites").await
+    pub async fn delete_track(&self, id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM tracks WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
     }
 
-    pub async fn get_favorites(&self) -> Result<Vec<Track>, sqlx::Error> {
-        self.get_playlist_tracks("favorites").await
-    }
+    pub async fn create_playlist(&self, name: &str) -> Result<String, sqlx::Error> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().timestamp();
 
-    pub async fn get_all_playlists(&self) -> Result<Vec<Playlist>, sqlx::Error> {
-        let rows = sqlx::query(
-            "SELECT id, name, created_date, is_system_playlist FROM playlists ORDER BY is_system_playlist DESC, created_date ASC"
+        sqlx::query(
+            "INSERT INTO playlists (id, name, created_date, is_system_playlist) VALUES (?, ?, ?, 0)"
         )
-        .fetch_all(&self.pool)
+        .bind(&id)
+        .bind(name)
+        .bind(now)
+        .execute(&self.pool)
         .await?;
 
-        Ok(rows
-            .into_iter()
-            .map(|r| Playlist {
-                id: r.get("id"),
-                name: r.get("name"),
-                created_date: r.get("created_date"),
-                is_system_playlist: r.get("is_system_playlist"),
-            })
-            .collect())
+        Ok(id)
     }
 
-    pub async fn save_settings(&self, settings: &AppSettings) -> Result<(), sqlx::Error> {
+    /// Create a system playlist with a fixed id unless it already exists. One created before it was
+    /// marked as a system playlist is marked now.
+    pub async fn ensure_playlist(&self, id: &str, name: &str) -> Result<(), sqlx::Error> {
         sqlx::query(
-            r#"
-            INSERT OR REPLACE INTO app_settings (id, default_download_path, preferred_audio_quality, auto_update_ytdlp)
-            VALUES ('default', ?, ?, ?)
-            "#
+            "INSERT INTO playlists (id, name, created_date, is_system_playlist) VALUES (?, ?, ?, 1)
+             ON CONFLICT(id) DO UPDATE SET is_system_playlist = 1"
         )
-        .bind(&settings.default_download_path)
-        .bind(&settings.preferred_audio_quality)
-        .bind(settings.auto_update_ytdlp)
+        .bind(id)
+        .bind(name)
+        .bind(chrono::Utc::now().timestamp())
         .execute(&self.pool)
         .await?;
+        Ok(())
+    }
 
+    pub async fn delete_playlist(&self, id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM playlists WHERE id = ? AND is_system_playlist = 0")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
         Ok(())
     }
 
-    pub async fn load_settings(&self) -> Result<AppSettings, sqlx::Error> {
+    pub async fn get_playlist(&self, id: &str) -> Result<Option<Playlist>, sqlx::Error> {
         let row = sqlx::query(
-            "SELECT default_download_path, preferred_audio_quality, auto_update_ytdlp FROM app_settings WHERE id = 'default'"
+            "SELECT id, name, created_date, is_system_playlist, is_generated FROM playlists WHERE id = ?"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| Playlist {
+            id: r.get("id"),
+            name: r.get("name"),
+            created_date: r.get("created_date"),
+            is_system_playlist: r.get("is_system_playlist"),
+            is_generated: r.get("is_generated"),
+            cover: None,
+        }))
+    }
+
+    /// (track_id, added_date) pairs for a playlist, used to snapshot memberships before removal
+    pub async fn get_playlist_memberships(&self, playlist_id: &str) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        sqlx::query_as("SELECT track_id, added_date FROM playlist_memberships WHERE playlist_id = ?")
+            .bind(playlist_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Re-create memberships captured by `get_playlist_memberships`, keeping their original dates
+    pub async fn restore_memberships(&self, playlist_id: &str, memberships: &[(String, i64)]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        for (track_id, added_date) in memberships {
+            sqlx::query(
+                "INSERT INTO playlist_memberships (id, playlist_id, track_id, added_date, is_favorite) VALUES (?, ?, ?, ?, 0)"
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(playlist_id)
+            .bind(track_id)
+            .bind(added_date)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    /// Re-create a deleted playlist together with its memberships
+    pub async fn restore_playlist(&self, playlist: &Playlist, memberships: &[(String, i64)]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO playlists (id, name, created_date, is_system_playlist, is_generated) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(&playlist.id)
+        .bind(&playlist.name)
+        .bind(playlist.created_date)
+        .bind(playlist.is_system_playlist)
+        .bind(playlist.is_generated)
+        .execute(&mut *tx)
+        .await?;
+
+        for (track_id, added_date) in memberships {
+            sqlx::query(
+                "INSERT INTO playlist_memberships (id, playlist_id, track_id, added_date, is_favorite) VALUES (?, ?, ?, ?, 0)"
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(&playlist.id)
+            .bind(track_id)
+            .bind(added_date)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    /// Copy a playlist and all its memberships under a new name, returning the new ID
+    pub async fn duplicate_playlist(&self, id: &str, new_name: &str) -> Result<String, sqlx::Error> {
+        let new_id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().timestamp();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO playlists (id, name, created_date, is_system_playlist) VALUES (?, ?, ?, 0)"
+        )
+        .bind(&new_id)
+        .bind(new_name)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        let memberships: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT track_id, added_date FROM playlist_memberships WHERE playlist_id = ?"
+        )
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for (track_id, added_date) in memberships {
+            sqlx::query(
+                "INSERT INTO playlist_memberships (id, playlist_id, track_id, added_date, is_favorite) VALUES (?, ?, ?, ?, 0)"
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(&new_id)
+            .bind(track_id)
+            .bind(added_date)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(new_id)
+    }
+
+    /// Copy tracks from every source playlist into the target.
+    /// With `dedupe`, tracks already in the target (or copied earlier in the merge) are skipped.
+    pub async fn merge_playlists(&self, source_ids: &[String], target_id: &str, dedupe: bool) -> Result<(), sqlx::Error> {
+        let now = chrono::Utc::now().timestamp();
+        let mut tx = self.pool.begin().await?;
+
+        let mut existing: Vec<String> =
+            sqlx::query_scalar("SELECT track_id FROM playlist_memberships WHERE playlist_id = ?")
+                .bind(target_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        for source_id in source_ids.iter().filter(|id| id.as_str() != target_id) {
+            let track_ids: Vec<String> = sqlx::query_scalar(
+                "SELECT track_id FROM playlist_memberships WHERE playlist_id = ? ORDER BY added_date ASC"
+            )
+            .bind(source_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            for track_id in track_ids {
+                if dedupe && existing.contains(&track_id) {
+                    continue;
+                }
+
+                sqlx::query(
+                    "INSERT INTO playlist_memberships (id, playlist_id, track_id, added_date, is_favorite) VALUES (?, ?, ?, ?, 0)"
+                )
+                .bind(uuid::Uuid::new_v4().to_string())
+                .bind(target_id)
+                .bind(&track_id)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+
+                existing.push(track_id);
+            }
+        }
+
+        tx.commit().await
+    }
+
+    pub async fn add_track_to_playlist(&self, track_id: &str, playlist_id: &str) -> Result<(), sqlx::Error> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().timestamp();
+
+        sqlx::query(
+            "INSERT INTO playlist_memberships (id, playlist_id, track_id, added_date, is_favorite) VALUES (?, ?, ?, ?, 0)"
+        )
+        .bind(&id)
+        .bind(playlist_id)
+        .bind(track_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Save the track (if new) and add it to the playlist in a single transaction
+    pub async fn save_track_to_playlist(&self, track: &Track, playlist_id: &str) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO tracks (id, title, author, duration, thumbnail_url, added_date, file_path, content_type)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET content_type = COALESCE(tracks.content_type, excluded.content_type)
+            "#,
+        )
+        .bind(&track.id)
+        .bind(&track.title)
+        .bind(&track.author)
+        .bind(track.duration)
+        .bind(&track.thumbnail_url)
+        .bind(track.added_date)
+        .bind(&track.file_path)
+        .bind(track.content_type.as_ref().map(|c| c.as_str()))
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO playlist_memberships (id, playlist_id, track_id, added_date, is_favorite) VALUES (?, ?, ?, ?, 0)"
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(playlist_id)
+        .bind(&track.id)
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    pub async fn remove_track_from_playlist(&self, track_id: &str, playlist_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM playlist_memberships WHERE track_id = ? AND playlist_id = ?")
+            .bind(track_id)
+            .bind(playlist_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn add_tracks_to_playlist(&self, track_ids: &[String], playlist_id: &str) -> Result<(), sqlx::Error> {
+        let now = chrono::Utc::now().timestamp();
+        let mut tx = self.pool.begin().await?;
+
+        for track_id in track_ids {
+            // Skip tracks that are already in the playlist
+            sqlx::query(
+                r#"
+                INSERT INTO playlist_memberships (id, playlist_id, track_id, added_date, is_favorite)
+                SELECT ?, ?, ?, ?, 0
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM playlist_memberships WHERE playlist_id = ? AND track_id = ?
+                )
+                "#
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(playlist_id)
+            .bind(track_id)
+            .bind(now)
+            .bind(playlist_id)
+            .bind(track_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    pub async fn remove_tracks_from_playlist(&self, track_ids: &[String], playlist_id: &str) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        for track_id in track_ids {
+            sqlx::query("DELETE FROM playlist_memberships WHERE track_id = ? AND playlist_id = ?")
+                .bind(track_id)
+                .bind(playlist_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await
+    }
+
+    pub async fn get_track_playlist_ids(&self, track_id: &str) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT DISTINCT playlist_id FROM playlist_memberships WHERE track_id = ?")
+            .bind(track_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Make `playlist_ids` the exact set of playlists containing the track
+    pub async fn set_track_playlists(&self, track_id: &str, playlist_ids: &[String]) -> Result<(), sqlx::Error> {
+        let now = chrono::Utc::now().timestamp();
+        let mut tx = self.pool.begin().await?;
+
+        let current: Vec<String> =
+            sqlx::query_scalar("SELECT DISTINCT playlist_id FROM playlist_memberships WHERE track_id = ?")
+                .bind(track_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        for playlist_id in current.iter().filter(|id| !playlist_ids.contains(id)) {
+            sqlx::query("DELETE FROM playlist_memberships WHERE track_id = ? AND playlist_id = ?")
+                .bind(track_id)
+                .bind(playlist_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for playlist_id in playlist_ids.iter().filter(|id| !current.contains(id)) {
+            sqlx::query(
+                "INSERT INTO playlist_memberships (id, playlist_id, track_id, added_date, is_favorite) VALUES (?, ?, ?, ?, 0)"
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(playlist_id)
+            .bind(track_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    pub async fn get_playlist_tracks(&self, playlist_id: &str) -> Result<Vec<Track>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.title, t.author, t.duration, t.thumbnail_url, t.added_date, t.file_path,
+                   t.custom_title, t.custom_author, t.note, t.content_type
+            FROM tracks t
+            INNER JOIN playlist_memberships pm ON t.id = pm.track_id
+            WHERE pm.playlist_id = ?
+            ORDER BY pm.added_date DESC
+            "#
+        )
+        .bind(playlist_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(track_from_row).collect())
+    }
+
+    /// A playlist's tracks whose title, artist or note contains `query`, ignoring case
+    pub async fn filter_playlist_tracks(&self, playlist_id: &str, query: &str) -> Result<Vec<Track>, sqlx::Error> {
+        let pattern = format!("%{}%", query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.title, t.author, t.duration, t.thumbnail_url, t.added_date, t.file_path,
+                   t.custom_title, t.custom_author, t.note, t.content_type
+            FROM tracks t
+            INNER JOIN playlist_memberships pm ON t.id = pm.track_id
+            WHERE pm.playlist_id = ?1
+              AND (t.title LIKE ?2 ESCAPE '\' OR t.author LIKE ?2 ESCAPE '\'
+                   OR t.custom_title LIKE ?2 ESCAPE '\' OR t.custom_author LIKE ?2 ESCAPE '\'
+                   OR t.note LIKE ?2 ESCAPE '\')
+            ORDER BY pm.added_date DESC
+            "#
+        )
+        .bind(playlist_id)
+        .bind(pattern)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(track_from_row).collect())
+    }
+
+    pub async fn get_playlist_track_ids(&self, playlist_id: &str) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT track_id FROM playlist_memberships WHERE playlist_id = ?")
+            .bind(playlist_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    pub async fn get_all_track_ids(&self) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT id FROM tracks")
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Fetch tracks by ID, preserving the order of `ids` and skipping unknown IDs
+    pub async fn get_tracks_by_ids(&self, ids: &[String]) -> Result<Vec<Track>, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!(
+            "SELECT id, title, author, duration, thumbnail_url, added_date, file_path, custom_title, custom_author, note, content_type FROM tracks WHERE id IN ({})",
+            placeholders
+        );
+
+        let mut query = sqlx::query(&sql);
+        for id in ids {
+            query = query.bind(id);
+        }
+        let rows = query.fetch_all(&self.pool).await?;
+
+        let mut tracks: std::collections::HashMap<String, Track> = rows
+            .into_iter()
+            .map(|r| {
+                let track = track_from_row(&r);
+                (track.id.clone(), track)
+            })
+            .collect();
+
+        Ok(ids.iter().filter_map(|id| tracks.remove(id)).collect())
+    }
+
+    pub async fn add_to_favorites(&self, track_id: &str) -> Result<(), sqlx::Error> {
+        self.add_track_to_playlist(track_id, "favorites").await
+    }
+
+    pub async fn remove_from_favorites(&self, track_id: &str) -> Result<(), sqlx::Error> {
+        self.remove_track_from_playlist(track_id, "favorites").await
+    }
+
+    pub async fn get_favorites(&self) -> Result<Vec<Track>, sqlx::Error> {
+        self.get_playlist_tracks("favorites").await
+    }
+
+    pub async fn get_all_playlists(&self) -> Result<Vec<Playlist>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, name, created_date, is_system_playlist, is_generated FROM playlists ORDER BY is_system_playlist DESC, created_date ASC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| Playlist {
+                id: r.get("id"),
+                name: r.get("name"),
+                created_date: r.get("created_date"),
+                is_system_playlist: r.get("is_system_playlist"),
+                is_generated: r.get("is_generated"),
+                cover: None,
+            })
+            .collect())
+    }
+
+    /// Tracks played since `since` or in the favorites, with their play count in that period
+    pub async fn get_mix_candidates(&self, since: i64) -> Result<Vec<(Track, i64, bool)>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.title, t.author, t.duration, t.thumbnail_url, t.added_date, t.file_path,
+                   t.custom_title, t.custom_author, t.note, t.content_type,
+                   (SELECT COUNT(*) FROM plays p LEFT JOIN fingerprints pf ON pf.track_id = p.track_id
+                    WHERE COALESCE(pf.group_id, p.track_id) = COALESCE((SELECT group_id FROM fingerprints WHERE track_id = t.id), t.id)
+                      AND p.played_at >= ? AND p.skipped = 0) AS play_count,
+                   EXISTS(SELECT 1 FROM playlist_memberships pm WHERE pm.track_id = t.id AND pm.playlist_id = 'favorites') AS favorite
+            FROM tracks t
+            WHERE play_count > 0 OR favorite
+            "#
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| (track_from_row(r), r.get("play_count"), r.get("favorite")))
+            .collect())
+    }
+
+    /// Swap the generated playlists for new ones, given as (id, name, track ids)
+    pub async fn replace_generated_playlists(&self, playlists: &[(String, String, Vec<String>)]) -> Result<(), sqlx::Error> {
+        let now = chrono::Utc::now().timestamp();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM playlist_memberships WHERE playlist_id IN (SELECT id FROM playlists WHERE is_generated = 1)")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM playlists WHERE is_generated = 1")
+            .execute(&mut *tx)
+            .await?;
+
+        for (id, name, track_ids) in playlists {
+            sqlx::query(
+                "INSERT INTO playlists (id, name, created_date, is_system_playlist, is_generated) VALUES (?, ?, ?, 0, 1)"
+            )
+            .bind(id)
+            .bind(name)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+            // Dated one second apart so the playlist keeps the mix's order (newest first)
+            for (position, track_id) in track_ids.iter().enumerate() {
+                sqlx::query(
+                    "INSERT INTO playlist_memberships (id, playlist_id, track_id, added_date, is_favorite) VALUES (?, ?, ?, ?, 0)"
+                )
+                .bind(uuid::Uuid::new_v4().to_string())
+                .bind(id)
+                .bind(track_id)
+                .bind(now - position as i64)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await
+    }
+
+    pub async fn record_play(&self, track: &Track) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT INTO plays (track_id, played_at, title, author, duration) VALUES (?, ?, ?, ?, ?)")
+            .bind(&track.id)
+            .bind(chrono::Utc::now().timestamp())
+            .bind(&track.title)
+            .bind(&track.author)
+            .bind(track.duration)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Plays from `since` up to (not including) `until`, oldest first
+    pub async fn get_plays_between(&self, since: i64, until: i64) -> Result<Vec<PlayRecord>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT p.track_id, p.played_at, p.skipped,
+                   COALESCE(f.group_id, p.track_id) AS recording_id,
+                   COALESCE(t.custom_title, t.title, p.title) AS title,
+                   COALESCE(t.custom_author, t.author, p.author) AS author,
+                   COALESCE(t.duration, p.duration, 0) AS duration
+            FROM plays p
+            LEFT JOIN tracks t ON t.id = p.track_id
+            LEFT JOIN fingerprints f ON f.track_id = p.track_id
+            WHERE p.played_at >= ? AND p.played_at < ?
+            ORDER BY p.played_at ASC
+            "#
+        )
+        .bind(since)
+        .bind(until)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| PlayRecord {
+                track_id: r.get("track_id"),
+                recording_id: r.get("recording_id"),
+                title: r.get("title"),
+                author: r.get("author"),
+                duration: r.get("duration"),
+                played_at: r.get("played_at"),
+                skipped: r.get("skipped"),
+            })
+            .collect())
+    }
+
+    /// Times of every play from `since` up to `until`
+    pub async fn get_play_times(&self, since: i64, until: i64) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar("SELECT played_at FROM plays WHERE played_at >= ? AND played_at < ?")
+            .bind(since)
+            .bind(until)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Recordings whose first ever play, of any upload, falls between `since` and `until`
+    pub async fn get_first_played_recording_ids(&self, since: i64, until: i64) -> Result<HashSet<String>, sqlx::Error> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(f.group_id, p.track_id) AS recording_id
+            FROM plays p
+            LEFT JOIN fingerprints f ON f.track_id = p.track_id
+            GROUP BY recording_id
+            HAVING MIN(p.played_at) >= ? AND MIN(p.played_at) < ?
+            "#
+        )
+        .bind(since)
+        .bind(until)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids.into_iter().collect())
+    }
+
+    /// Fingerprints within `tolerance` seconds of `duration`, leaving out `exclude_id`
+    pub async fn get_fingerprints_near(&self, duration: f64, tolerance: f64, exclude_id: &str) -> Result<Vec<AudioFingerprint>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT track_id, group_id, duration, fingerprint FROM fingerprints WHERE duration BETWEEN ? AND ? AND track_id != ?"
+        )
+        .bind(duration - tolerance)
+        .bind(duration + tolerance)
+        .bind(exclude_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|r| {
+                Some(AudioFingerprint {
+                    track_id: r.get("track_id"),
+                    group_id: r.get("group_id"),
+                    duration: r.get("duration"),
+                    fingerprint: serde_json::from_str(&r.get::<String, _>("fingerprint")).ok()?,
+                })
+            })
+            .collect())
+    }
+
+    pub async fn save_fingerprint(&self, fingerprint: &AudioFingerprint) -> Result<(), sqlx::Error> {
+        let value = serde_json::to_string(&fingerprint.fingerprint).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        sqlx::query("INSERT OR REPLACE INTO fingerprints (track_id, group_id, duration, fingerprint) VALUES (?, ?, ?, ?)")
+            .bind(&fingerprint.track_id)
+            .bind(&fingerprint.group_id)
+            .bind(fingerprint.duration)
+            .bind(value)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_fingerprinted_ids(&self) -> Result<HashSet<String>, sqlx::Error> {
+        let ids: Vec<String> = sqlx::query_scalar("SELECT track_id FROM fingerprints")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(ids.into_iter().collect())
+    }
+
+    /// Recording each matched upload belongs to, keyed by the upload's id. Uploads that are
+    /// the first of their recording aren't listed.
+    pub async fn get_recording_groups(&self) -> Result<HashMap<String, String>, sqlx::Error> {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT track_id, group_id FROM fingerprints WHERE track_id != group_id")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    pub async fn save_weekly_recap(&self, recap: &WeeklyRecap) -> Result<(), sqlx::Error> {
+        let value = serde_json::to_string(recap).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        sqlx::query("INSERT OR REPLACE INTO weekly_recaps (week_start, recap, generated_at) VALUES (?, ?, ?)")
+            .bind(&recap.week_start)
+            .bind(value)
+            .bind(recap.generated_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_weekly_recap(&self, week_start: &str) -> Result<Option<WeeklyRecap>, sqlx::Error> {
+        let value: Option<String> = sqlx::query_scalar("SELECT recap FROM weekly_recaps WHERE week_start = ?")
+            .bind(week_start)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(value.and_then(|v| serde_json::from_str(&v).ok()))
+    }
+
+    /// The most recent recaps, newest week first
+    pub async fn get_weekly_recaps(&self, limit: i64) -> Result<Vec<WeeklyRecap>, sqlx::Error> {
+        let values: Vec<String> = sqlx::query_scalar("SELECT recap FROM weekly_recaps ORDER BY week_start DESC LIMIT ?")
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(values.iter().filter_map(|v| serde_json::from_str(v).ok()).collect())
+    }
+
+    /// Tracks by when they were last played, most recent first. Tracks outside the library are
+    /// named from what their latest play recorded.
+    pub async fn get_recently_played(&self, limit: i64) -> Result<Vec<Track>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT p.track_id AS id,
+                   COALESCE(t.title, p.title, p.track_id) AS title,
+                   COALESCE(t.author, p.author) AS author,
+                   COALESCE(t.duration, p.duration, 0) AS duration,
+                   t.thumbnail_url,
+                   COALESCE(t.added_date, p.played_at) AS added_date,
+                   t.file_path, t.custom_title, t.custom_author, t.note, t.content_type
+            FROM plays p
+            LEFT JOIN tracks t ON t.id = p.track_id
+            WHERE p.id IN (SELECT MAX(id) FROM plays GROUP BY track_id)
+            ORDER BY p.played_at DESC
+            LIMIT ?
+            "#
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(track_from_row).collect())
+    }
+
+    /// Mark the latest play of a track as skipped
+    pub async fn mark_last_play_skipped(&self, track_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE plays SET skipped = 1 WHERE id = (SELECT MAX(id) FROM plays WHERE track_id = ?)")
+            .bind(track_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Tracks played through (not skipped) since `since`
+    pub async fn get_heard_track_ids(&self, since: i64) -> Result<HashSet<String>, sqlx::Error> {
+        let ids: Vec<String> = sqlx::query_scalar("SELECT DISTINCT track_id FROM plays WHERE played_at >= ? AND skipped = 0")
+            .bind(since)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(ids.into_iter().collect())
+    }
+
+    /// Share of plays that were skipped, for every track skipped at least once. Plays of every
+    /// upload of a recording count together.
+    pub async fn get_skip_rates(&self) -> Result<HashMap<String, f64>, sqlx::Error> {
+        let rows: Vec<(String, i64, i64)> = sqlx::query_as(
+            r#"
+            WITH recordings AS (
+                SELECT COALESCE(f.group_id, p.track_id) AS recording_id, COUNT(*) AS plays, SUM(p.skipped) AS skips
+                FROM plays p
+                LEFT JOIN fingerprints f ON f.track_id = p.track_id
+                GROUP BY recording_id
+                HAVING SUM(p.skipped) > 0
+            )
+            SELECT recording_id, plays, skips FROM recordings
+            UNION
+            SELECT f.track_id, r.plays, r.skips FROM recordings r JOIN fingerprints f ON f.group_id = r.recording_id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(track_id, plays, skips)| (track_id, skips as f64 / plays as f64))
+            .collect())
+    }
+
+    /// Tracks skipped most often, then by the share of their plays that were skipped
+    pub async fn get_most_skipped(&self, limit: i64) -> Result<Vec<TrackSkipStats>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.title, t.author, t.duration, t.thumbnail_url, t.added_date, t.file_path,
+                   t.custom_title, t.custom_author, t.note, t.content_type,
+                   COUNT(p.id) AS play_count, SUM(p.skipped) AS skip_count
+            FROM tracks t
+            INNER JOIN plays p ON p.track_id = t.id
+            GROUP BY t.id
+            HAVING skip_count > 0
+            ORDER BY skip_count DESC, CAST(skip_count AS REAL) / play_count DESC
+            LIMIT ?
+            "#
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| {
+                let plays: i64 = r.get("play_count");
+                let skips: i64 = r.get("skip_count");
+                TrackSkipStats {
+                    track: track_from_row(r),
+                    plays,
+                    skips,
+                    skip_rate: skips as f64 / plays as f64,
+                }
+            })
+            .collect())
+    }
+
+    /// Aggregate track count, duration and last play time for a playlist.
+    /// Download counts are left at zero for the caller to fill in from the download manager.
+    pub async fn get_playlist_summary(&self, playlist_id: &str) -> Result<PlaylistSummary, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                COUNT(t.id) AS track_count,
+                COALESCE(SUM(t.duration), 0) AS total_duration,
+                (
+                    SELECT MAX(p.played_at)
+                    FROM plays p
+                    INNER JOIN playlist_memberships m ON p.track_id = m.track_id
+                    WHERE m.playlist_id = ?
+                ) AS last_played
+            FROM tracks t
+            INNER JOIN playlist_memberships pm ON t.id = pm.track_id
+            WHERE pm.playlist_id = ?
+            "#
+        )
+        .bind(playlist_id)
+        .bind(playlist_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(PlaylistSummary {
+            playlist_id: playlist_id.to_string(),
+            track_count: row.get("track_count"),
+            total_duration: row.get("total_duration"),
+            downloaded_count: 0,
+            downloaded_size: 0,
+            last_played: row.get("last_played"),
+        })
+    }
+
+    pub async fn add_bookmark(&self, track_id: &str, position: f64, note: Option<&str>) -> Result<Bookmark, sqlx::Error> {
+        let bookmark = Bookmark {
+            id: uuid::Uuid::new_v4().to_string(),
+            track_id: track_id.to_string(),
+            position,
+            note: note.map(|n| n.to_string()),
+            created_date: chrono::Utc::now().timestamp(),
+        };
+
+        sqlx::query(
+            "INSERT INTO bookmarks (id, track_id, position, note, created_date) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(&bookmark.id)
+        .bind(&bookmark.track_id)
+        .bind(bookmark.position)
+        .bind(&bookmark.note)
+        .bind(bookmark.created_date)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(bookmark)
+    }
+
+    pub async fn get_bookmarks(&self, track_id: &str) -> Result<Vec<Bookmark>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, track_id, position, note, created_date FROM bookmarks WHERE track_id = ? ORDER BY position ASC"
+        )
+        .bind(track_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| Bookmark {
+                id: r.get("id"),
+                track_id: r.get("track_id"),
+                position: r.get("position"),
+                note: r.get("note"),
+                created_date: r.get("created_date"),
+            })
+            .collect())
+    }
+
+    pub async fn get_bookmark(&self, id: &str) -> Result<Option<Bookmark>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT id, track_id, position, note, created_date FROM bookmarks WHERE id = ?"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| Bookmark {
+            id: r.get("id"),
+            track_id: r.get("track_id"),
+            position: r.get("position"),
+            note: r.get("note"),
+            created_date: r.get("created_date"),
+        }))
+    }
+
+    pub async fn delete_bookmark(&self, id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM bookmarks WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_channel_rules(&self) -> Result<Vec<ChannelRule>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT uploader, playback_rate, auto_download, playlist_id FROM channel_rules ORDER BY uploader COLLATE NOCASE"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(channel_rule_from_row).collect())
+    }
+
+    pub async fn get_channel_rule(&self, uploader: &str) -> Result<Option<ChannelRule>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT uploader, playback_rate, auto_download, playlist_id FROM channel_rules WHERE uploader = ?"
+        )
+        .bind(uploader)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(channel_rule_from_row))
+    }
+
+    pub async fn save_channel_rule(&self, rule: &ChannelRule) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO channel_rules (uploader, playback_rate, auto_download, playlist_id) VALUES (?, ?, ?, ?)"
+        )
+        .bind(&rule.uploader)
+        .bind(rule.playback_rate.map(|rate| rate as f64))
+        .bind(rule.auto_download)
+        .bind(&rule.playlist_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_channel_rule(&self, uploader: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM channel_rules WHERE uploader = ?")
+            .bind(uploader)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn save_settings(&self, settings: &AppSettings) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO app_settings (id, default_download_path, preferred_audio_quality, auto_update_ytdlp)
+            VALUES ('default', ?, ?, ?)
+            "#
+        )
+        .bind(&settings.default_download_path)
+        .bind(&settings.preferred_audio_quality)
+        .bind(settings.auto_update_ytdlp)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_preference<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, sqlx::Error> {
+        let value: Option<String> = sqlx::query_scalar("SELECT value FROM preferences WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        // A value that no longer parses is treated as unset
+        Ok(value.and_then(|v| serde_json::from_str(&v).ok()))
+    }
+
+    pub async fn set_preference<T: Serialize>(&self, key: &str, value: &T) -> Result<(), sqlx::Error> {
+        let value = serde_json::to_string(value).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        sqlx::query("INSERT OR REPLACE INTO preferences (key, value) VALUES (?, ?)")
+            .bind(key)
+            .bind(value)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn load_settings(&self) -> Result<AppSettings, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT default_download_path, preferred_audio_quality, auto_update_ytdlp FROM app_settings WHERE id = 'default'"
         )
         .fetch_optional(&self.pool)
         .await?;
@@ -311,3 +1558,28 @@ impl DatabaseManager {
         }).unwrap_or_default())
     }
 }
+
+fn track_from_row(r: &SqliteRow) -> Track {
+    Track {
+        id: r.get("id"),
+        title: r.get("title"),
+        author: r.get("author"),
+        duration: r.get("duration"),
+        thumbnail_url: r.get("thumbnail_url"),
+        added_date: r.get("added_date"),
+        file_path: r.get("file_path"),
+        custom_title: r.get("custom_title"),
+        custom_author: r.get("custom_author"),
+        note: r.get("note"),
+        content_type: r.get::<Option<String>, _>("content_type").as_deref().and_then(ContentType::parse),
+    }
+}
+
+fn channel_rule_from_row(r: &SqliteRow) -> ChannelRule {
+    ChannelRule {
+        uploader: r.get("uploader"),
+        playback_rate: r.get::<Option<f64>, _>("playback_rate").map(|rate| rate as f32),
+        auto_download: r.get("auto_download"),
+        playlist_id: r.get("playlist_id"),
+    }
+}
@@ -0,0 +1,145 @@
+use crate::audio_capabilities::AudioCapabilities;
+use crate::database::DatabaseManager;
+use crate::download_manager::FailedDownload;
+use crate::models::{PlayerEvent, StorageProblem};
+use crate::recent_log::LogLine;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const HEALTH_ENTRY: &str = "health.json";
+const SETTINGS_ENTRY: &str = "settings.json";
+const EVENTS_ENTRY: &str = "player_events.json";
+const ERRORS_ENTRY: &str = "errors.json";
+const LOGS_ENTRY: &str = "recent_log.json";
+
+// Only the most recent player events go into the bundle
+const EVENT_LIMIT: usize = 200;
+
+// Preferences that help explain a bug report; anything personal (cookies, sessions, keywords) stays out
+const SHARED_PREFERENCES: [&str; 12] = [
+    "data_saver_mode",
+    "buffer_target",
+    "speed_settings",
+    "limiter",
+    "max_volume",
+    "max_gain",
+    "end_of_queue_action",
+    "announce_tracks",
+    "auto_check_updates",
+    "minimize_to_background",
+    "onboarding_completed_steps",
+    "ytdlp_release",
+];
+
+/// Versions and availability of everything the app depends on at runtime
+#[derive(Debug, Serialize)]
+pub struct DependencyHealth {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub ytdlp_version: Option<String>, // None when yt-dlp isn't installed or doesn't run
+    pub audio: AudioCapabilities,
+    pub online: bool,
+    pub metered: bool,
+    pub storage_problem: Option<StorageProblem>,
+    pub database_size: i64,
+    pub generated_at: i64,
+}
+
+// A failed download without the track's title, which could say more about the user than the bug
+#[derive(Debug, Serialize)]
+struct ReportedError {
+    video_id: String,
+    error: String,
+    kind: String,
+    failed_at: i64,
+}
+
+pub struct DiagnosticsBundle {
+    pub health: DependencyHealth,
+    pub settings: serde_json::Value,
+    pub events: Vec<PlayerEvent>,
+    pub failed_downloads: Vec<FailedDownload>,
+    pub logs: Vec<LogLine>,
+}
+
+/// App settings and the shared preferences, with paths in the home directory shortened to `~`
+pub async fn collect_settings(db: &DatabaseManager, downloads_dir: &Path) -> Result<serde_json::Value, String> {
+    let mut settings = db.load_settings().await.map_err(|e| e.to_string())?;
+    settings.default_download_path = anonymize_path(&settings.default_download_path);
+
+    let mut preferences = serde_json::Map::new();
+    for key in SHARED_PREFERENCES {
+        if let Some(value) = db.get_preference::<serde_json::Value>(key).await.map_err(|e| e.to_string())? {
+            preferences.insert(key.to_string(), value);
+        }
+    }
+
+    Ok(serde_json::json!({
+        "app_settings": settings,
+        "downloads_dir": anonymize_path(&downloads_dir.to_string_lossy()),
+        "preferences": preferences,
+    }))
+}
+
+/// Zip the bundle into one file users can attach to a GitHub issue
+pub async fn write_bundle(bundle: DiagnosticsBundle, path: &Path) -> Result<(), String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || write_archive(&bundle, &path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn write_archive(bundle: &DiagnosticsBundle, path: &Path) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let events = &bundle.events[bundle.events.len().saturating_sub(EVENT_LIMIT)..];
+    let errors: Vec<ReportedError> = bundle
+        .failed_downloads
+        .iter()
+        .map(|failed| ReportedError {
+            video_id: failed.track.id.clone(),
+            error: anonymize_path(&failed.error),
+            kind: format!("{:?}", failed.kind),
+            failed_at: failed.failed_at,
+        })
+        .collect();
+    let logs: Vec<LogLine> = bundle
+        .logs
+        .iter()
+        .map(|log| LogLine {
+            line: anonymize_path(&log.line),
+            ..log.clone()
+        })
+        .collect();
+
+    let entries = [
+        (HEALTH_ENTRY, serde_json::to_vec_pretty(&bundle.health)),
+        (SETTINGS_ENTRY, serde_json::to_vec_pretty(&bundle.settings)),
+        (EVENTS_ENTRY, serde_json::to_vec_pretty(events)),
+        (ERRORS_ENTRY, serde_json::to_vec_pretty(&errors)),
+        (LOGS_ENTRY, serde_json::to_vec_pretty(&logs)),
+    ];
+    for (name, json) in entries {
+        let json = json.map_err(|e| e.to_string())?;
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(&json).map_err(|e| e.to_string())?;
+    }
+
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Home directories usually contain the user's name
+fn anonymize_path(text: &str) -> String {
+    match dirs::home_dir() {
+        Some(home) => text.replace(&*home.to_string_lossy(), "~"),
+        None => text.to_string(),
+    }
+}
@@ -1,12 +1,27 @@
-use crate::models::YTVideoInfo;
+use crate::app_data::AppData;
+use crate::bandwidth_governor::{BandwidthGovernor, BandwidthState, RESTART_HOLD, THROTTLED_RATE};
+use crate::database::DatabaseManager;
+use crate::models::{ContentType, Track, YTVideoInfo, YtdlpOperation, YtdlpOptions};
+use crate::rate_limiter::RateLimiter;
+use crate::recent_log;
+use crate::storage_monitor::StorageMonitor;
 use crate::ytdlp_installer::YTDLPInstaller;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
-use tokio::sync::Mutex;
+use tokio::sync::{watch, Mutex};
+
+// Normal downloads give way to a streaming track, high priority ones keep full speed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum DownloadPriority {
+    #[default]
+    Normal,
+    High,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadProgress {
@@ -16,7 +31,80 @@ pub struct DownloadProgress {
     pub eta: String,
     pub file_size: String,
     pub is_completed: bool,
-    pub error: Option<String>,
+    pub priority: DownloadPriority,
+    pub throttled: bool, // rate-limited while playback streams
+}
+
+// Changes found when reconciling the downloads directory with the known downloads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadsChange {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelinkReport {
+    pub relinked: Vec<String>, // video IDs whose audio file was found again
+    pub unmatched: usize,      // audio files in the folder that matched no download
+}
+
+// Progress of a multi-track download, summed over its tracks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchProgress {
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub progress: f64, // 0.0 to 1.0 across the whole batch
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CleanupReport {
+    pub metadata_removed: usize, // metadata files whose audio is gone
+    pub partial_removed: usize,  // leftovers of interrupted yt-dlp downloads
+    pub bytes_reclaimed: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DownloadErrorKind {
+    Network,     // dropped or timed-out connection, retried automatically after reconnecting
+    Unavailable, // removed, private or region-locked video
+    Storage,     // the file couldn't be written, retried automatically once storage is writable
+    Other,
+}
+
+impl DownloadErrorKind {
+    fn classify(message: &str) -> Self {
+        let message = message.to_lowercase();
+        let matches = |patterns: &[&str]| patterns.iter().any(|p| message.contains(p));
+
+        if matches(&["no space left", "permission denied", "read-only file system", "disk full"]) {
+            Self::Storage
+        } else if matches(&["video unavailable", "private video", "has been removed", "not available in your country", "sign in to confirm your age"]) {
+            Self::Unavailable
+        } else if matches(&[
+            "timed out",
+            "connection reset",
+            "connection refused",
+            "connection aborted",
+            "network is unreachable",
+            "getaddrinfo",
+            "name resolution",
+            "http error 5",
+        ]) {
+            Self::Network
+        } else {
+            Self::Other
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedDownload {
+    pub track: YTVideoInfo,
+    pub quality: Option<String>, // per-download quality override, reused on retry
+    pub error: String,
+    pub kind: DownloadErrorKind,
+    pub failed_at: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,18 +113,36 @@ pub struct DownloadedTrack {
     pub file_path: String,
     pub file_size: i64,
     pub download_date: i64,
+    pub actual_duration: Option<f64>, // probed from the file, None if ffprobe couldn't read it
+    pub is_truncated: bool,
 }
 
 pub struct DownloadManager {
     active_downloads: Arc<Mutex<HashMap<String, DownloadProgress>>>,
     completed_downloads: Arc<Mutex<Vec<String>>>, // video IDs
+    failed_downloads: Arc<Mutex<Vec<FailedDownload>>>,
     downloads_dir: Arc<Mutex<PathBuf>>,
     audio_quality: Arc<Mutex<String>>, // Audio quality preference
     app_handle: Arc<Mutex<Option<AppHandle>>>,
+    watcher: Arc<Mutex<Option<RecommendedWatcher>>>,
+    watcher_enabled: Arc<Mutex<bool>>,
+    db: Arc<DatabaseManager>, // tracks.file_path mirrors the completed downloads
+    storage_used: Arc<Mutex<Option<i64>>>, // cached size of the downloads directory
+    batch: Arc<Mutex<Vec<String>>>,        // video IDs of the running multi-track download
+    storage: Arc<StorageMonitor>,          // downloads pause while storage has a problem
+    limiter: Arc<RateLimiter>,             // shared with searches so YouTube sees one paced client
+    ytdlp_options: Arc<Mutex<YtdlpOptions>>,
+    awaiting_stream: Arc<Mutex<HashMap<String, YTVideoInfo>>>, // downloads waiting for a stream's copy, by video ID
+    bandwidth: Arc<BandwidthGovernor>,
 }
 
 impl DownloadManager {
-    pub fn new() -> Self {
+    pub fn new(
+        db: Arc<DatabaseManager>,
+        storage: Arc<StorageMonitor>,
+        limiter: Arc<RateLimiter>,
+        bandwidth: Arc<BandwidthGovernor>,
+    ) -> Self {
         // Default downloads directory
         let downloads_dir = dirs::download_dir()
             .unwrap_or_else(|| PathBuf::from("."))
@@ -48,9 +154,20 @@ impl DownloadManager {
         Self {
             active_downloads: Arc::new(Mutex::new(HashMap::new())),
             completed_downloads: Arc::new(Mutex::new(Vec::new())),
+            failed_downloads: Arc::new(Mutex::new(Vec::new())),
             downloads_dir: Arc::new(Mutex::new(downloads_dir)),
             audio_quality: Arc::new(Mutex::new("best".to_string())), // Default to best quality
             app_handle: Arc::new(Mutex::new(None)),
+            watcher: Arc::new(Mutex::new(None)),
+            watcher_enabled: Arc::new(Mutex::new(true)),
+            db,
+            storage_used: Arc::new(Mutex::new(None)),
+            batch: Arc::new(Mutex::new(Vec::new())),
+            storage,
+            limiter,
+            ytdlp_options: Arc::new(Mutex::new(YtdlpOptions::default())),
+            awaiting_stream: Arc::new(Mutex::new(HashMap::new())),
+            bandwidth,
         }
     }
 
@@ -63,26 +180,132 @@ impl DownloadManager {
         let downloads_dir = self.downloads_dir.lock().await.clone();
         let mut completed = self.completed_downloads.lock().await;
 
-        // Scan downloads directory for metadata files
-        if let Ok(entries) = std::fs::read_dir(&downloads_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if let Some(file_name) = path.file_name() {
-                    let name = file_name.to_string_lossy();
-                    // Look for metadata files
-                    if name.ends_with("_metadata.json") {
-                        // Extract video ID from filename
-                        let video_id = name.trim_end_matches("_metadata.json").to_string();
-                        // Check if corresponding audio file exists
-                        if find_audio_file(&downloads_dir, &video_id).is_some() {
-                            completed.push(video_id);
-                        }
-                    }
-                }
-            }
+        // Deletions staged for undo in a previous session are final now
+        let staging_dir = downloads_dir.join(".undo");
+        if staging_dir.exists() {
+            std::fs::remove_dir_all(&staging_dir).ok();
         }
 
+        // Scan downloads directory for existing downloads
+        completed.extend(scan_downloads(&downloads_dir));
+
         println!("Initialized download manager with {} existing downloads", completed.len());
+        drop(completed);
+
+        // Failures from previous sessions stay listed until retried
+        if let Some(failed) = std::fs::read_to_string(AppData::get_failed_downloads_path())
+            .ok()
+            .and_then(|json| serde_json::from_str::<Vec<FailedDownload>>(&json).ok())
+        {
+            *self.failed_downloads.lock().await = failed;
+        }
+
+        self.sync_track_paths().await;
+        self.refresh_storage_used().await;
+        self.start_watching().await;
+    }
+
+    /// Watch the downloads directory so files removed or added outside the app are picked up
+    pub async fn start_watching(&self) {
+        self.stop_watching().await;
+
+        if !*self.watcher_enabled.lock().await {
+            return;
+        }
+
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let mut watcher = match notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if res.is_ok() {
+                let _ = event_tx.send(());
+            }
+        }) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                eprintln!("⚠️ Failed to create downloads watcher: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = watcher.watch(&downloads_dir, RecursiveMode::NonRecursive) {
+            eprintln!("⚠️ Failed to watch {}: {}", downloads_dir.display(), e);
+            return;
+        }
+
+        *self.watcher.lock().await = Some(watcher);
+        println!("👀 Watching downloads directory: {}", downloads_dir.display());
+
+        let self_clone = self.clone_for_task();
+        tokio::spawn(async move {
+            // Ends when the watcher (and with it the sender) is dropped
+            while event_rx.recv().await.is_some() {
+                // Debounce bursts of filesystem events into a single reconcile
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                while event_rx.try_recv().is_ok() {}
+                self_clone.reconcile().await;
+            }
+        });
+    }
+
+    pub async fn stop_watching(&self) {
+        self.watcher.lock().await.take();
+    }
+
+    pub async fn set_watcher_enabled(&self, enabled: bool) {
+        *self.watcher_enabled.lock().await = enabled;
+        if enabled {
+            self.start_watching().await;
+        } else {
+            self.stop_watching().await;
+        }
+    }
+
+    pub async fn is_watcher_enabled(&self) -> bool {
+        *self.watcher_enabled.lock().await
+    }
+
+    /// Re-scan the downloads directory and sync the completed list with what is on disk
+    pub async fn reconcile(&self) -> DownloadsChange {
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        let on_disk = scan_downloads(&downloads_dir);
+        let active: Vec<String> = self.active_downloads.lock().await.keys().cloned().collect();
+
+        let change = {
+            let mut completed = self.completed_downloads.lock().await;
+
+            // Downloads in progress are still writing files, leave them alone
+            let removed: Vec<String> = completed
+                .iter()
+                .filter(|id| !on_disk.contains(id) && !active.contains(id))
+                .cloned()
+                .collect();
+            let added: Vec<String> = on_disk
+                .iter()
+                .filter(|id| !completed.contains(id))
+                .cloned()
+                .collect();
+
+            completed.retain(|id| !removed.contains(id));
+            completed.extend(added.iter().cloned());
+
+            DownloadsChange { added, removed }
+        };
+
+        if !change.added.is_empty() || !change.removed.is_empty() {
+            println!(
+                "🔄 Downloads reconciled: {} added, {} removed",
+                change.added.len(),
+                change.removed.len()
+            );
+            self.sync_track_paths().await;
+            self.refresh_storage_used().await;
+            if let Some(handle) = self.app_handle.lock().await.as_ref() {
+                let _ = handle.emit("downloads-updated", change.clone());
+            }
+        }
+
+        change
     }
 
     pub async fn get_downloads_dir(&self) -> PathBuf {
@@ -118,6 +341,10 @@ impl DownloadManager {
         // Update the directory
         *self.downloads_dir.lock().await = path;
 
+        self.sync_track_paths().await;
+        self.refresh_storage_used().await;
+        self.start_watching().await;
+
         Ok(())
     }
 
@@ -150,7 +377,7 @@ impl DownloadManager {
         let mut error_count = 0;
 
         if let Ok(entries) = std::fs::read_dir(from) {
-            let audio_extensions = ["m4a", "webm", "mp3", "aac", "ogg", "json"];
+            let audio_extensions = ["m4a", "webm", "mp3", "aac", "ogg", "json", "vtt"];
 
             for entry in entries.flatten() {
                 let path = entry.path();
@@ -189,7 +416,8 @@ impl DownloadManager {
         }
     }
 
-    pub async fn download_track(&self, track: YTVideoInfo) -> Result<(), String> {
+    /// Download a track; `quality` overrides the global audio quality for this file only
+    pub async fn download_track(&self, track: YTVideoInfo, quality: Option<String>) -> Result<(), String> {
         let video_id = track.id.clone();
 
         // Check if already downloading
@@ -208,6 +436,16 @@ impl DownloadManager {
             }
         }
 
+        // Park with the storage failures until the disk can be written again
+        if let Some(problem) = self.storage.problem() {
+            self.park_download(track, quality, problem.cause, DownloadErrorKind::Storage).await;
+            return Ok(());
+        }
+
+        // A retried download is no longer failed
+        self.failed_downloads.lock().await.retain(|failed| failed.track.id != video_id);
+        self.save_failed_downloads().await;
+
         // Initialize progress
         {
             let mut active = self.active_downloads.lock().await;
@@ -220,7 +458,8 @@ impl DownloadManager {
                     eta: "Calculating...".to_string(),
                     file_size: "Unknown".to_string(),
                     is_completed: false,
-                    error: None,
+                    priority: DownloadPriority::Normal,
+                    throttled: false,
                 },
             );
         }
@@ -232,36 +471,143 @@ impl DownloadManager {
         let track_clone = track.clone();
 
         tokio::spawn(async move {
-            if let Err(e) = self_clone.download_with_ytdlp(track_clone).await {
+            if let Err(e) = self_clone.download_with_ytdlp(track_clone.clone(), quality.clone()).await {
                 println!("❌ Download failed: {}", e);
-                self_clone
-                    .update_download_error(&video_id, &e.to_string())
-                    .await;
+                self_clone.record_failure(track_clone.clone(), quality, e).await;
             }
+            self_clone.bandwidth.set_prioritized(&track_clone.id, false);
         });
 
         Ok(())
     }
 
+    /// Save the audio kept from streaming a track as its download, instead of fetching it again
+    pub async fn save_streamed_audio(&self, track: YTVideoInfo, path: PathBuf) -> Result<(), String> {
+        let busy = if self.completed_downloads.lock().await.contains(&track.id) {
+            Some("Track already downloaded")
+        } else if self.active_downloads.lock().await.contains_key(&track.id) {
+            Some("Download already in progress")
+        } else {
+            None
+        };
+        if let Some(reason) = busy {
+            let _ = std::fs::remove_file(&path);
+            return Err(reason.to_string());
+        }
+
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("m4a");
+        let filename = format!("{}.{}", download_file_stem(&track.id, &track.title, &track.uploader), extension);
+        let source = path.clone();
+        let destination = downloads_dir.join(filename);
+        tokio::task::spawn_blocking(move || crate::app_data::move_path(&source, &destination))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Failed to save streamed audio: {}", e))?;
+
+        println!("💾 Saved the streamed audio of {} as a download", track.title);
+        self.mark_download_completed(&track).await
+    }
+
+    /// Show a download that will be saved from the stream still fetching it. If the stream
+    /// ends without a complete copy, `finish_streamed_audio` downloads the track as usual.
+    pub async fn await_streamed_audio(&self, track: YTVideoInfo) -> Result<(), String> {
+        {
+            let mut active = self.active_downloads.lock().await;
+            if active.contains_key(&track.id) {
+                return Err("Download already in progress".to_string());
+            }
+            active.insert(
+                track.id.clone(),
+                DownloadProgress {
+                    video_id: track.id.clone(),
+                    progress: 0.0,
+                    speed: "Saving from stream".to_string(),
+                    eta: String::new(),
+                    file_size: "Unknown".to_string(),
+                    is_completed: false,
+                    priority: DownloadPriority::Normal,
+                    throttled: false,
+                },
+            );
+        }
+        self.awaiting_stream.lock().await.insert(track.id.clone(), track);
+        self.emit_downloads_update().await;
+        Ok(())
+    }
+
+    /// Complete a download that was waiting on a stream, from its copy or with yt-dlp if there's none
+    pub async fn finish_streamed_audio(&self, video_id: &str, path: Option<PathBuf>) -> Result<(), String> {
+        let Some(track) = self.awaiting_stream.lock().await.remove(video_id) else {
+            // Cancelled while waiting
+            if let Some(path) = path {
+                let _ = std::fs::remove_file(path);
+            }
+            return Ok(());
+        };
+        self.active_downloads.lock().await.remove(video_id);
+
+        match path {
+            Some(path) => self.save_streamed_audio(track, path).await,
+            None => {
+                println!("📥 Stream of {} ended before it was all fetched, downloading it", track.title);
+                self.download_track(track, None).await
+            }
+        }
+    }
+
     fn clone_for_task(&self) -> Self {
         Self {
             active_downloads: Arc::clone(&self.active_downloads),
             completed_downloads: Arc::clone(&self.completed_downloads),
+            failed_downloads: Arc::clone(&self.failed_downloads),
             downloads_dir: Arc::clone(&self.downloads_dir),
             audio_quality: Arc::clone(&self.audio_quality),
             app_handle: Arc::clone(&self.app_handle),
+            watcher: Arc::clone(&self.watcher),
+            watcher_enabled: Arc::clone(&self.watcher_enabled),
+            db: Arc::clone(&self.db),
+            storage_used: Arc::clone(&self.storage_used),
+            batch: Arc::clone(&self.batch),
+            storage: Arc::clone(&self.storage),
+            limiter: Arc::clone(&self.limiter),
+            ytdlp_options: Arc::clone(&self.ytdlp_options),
+            awaiting_stream: Arc::clone(&self.awaiting_stream),
+            bandwidth: Arc::clone(&self.bandwidth),
+        }
+    }
+
+    async fn unlink_track(&self, video_id: &str) {
+        if let Err(e) = self.db.update_download_path(video_id, None).await {
+            eprintln!("⚠️ Failed to unlink track {}: {}", video_id, e);
         }
     }
 
-    async fn download_with_ytdlp(&self, track: YTVideoInfo) -> Result<(), String> {
+    // Rewrites every track's file path from the completed downloads, after bulk changes on disk
+    async fn sync_track_paths(&self) {
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        let completed = self.completed_downloads.lock().await.clone();
+        let paths: Vec<(String, String)> = completed
+            .into_iter()
+            .filter_map(|video_id| {
+                find_audio_file(&downloads_dir, &video_id).map(|path| (video_id, path.to_string_lossy().to_string()))
+            })
+            .collect();
+
+        if let Err(e) = self.db.sync_download_paths(&paths).await {
+            eprintln!("⚠️ Failed to sync download paths: {}", e);
+        }
+    }
+
+    async fn download_with_ytdlp(&self, track: YTVideoInfo, quality: Option<String>) -> Result<(), String> {
         let ytdlp_path = YTDLPInstaller::get_ytdlp_path();
         let downloads_dir = self.downloads_dir.lock().await.clone();
-        let quality = self.audio_quality.lock().await.clone();
+        let quality = match quality {
+            Some(quality) => quality,
+            None => self.audio_quality.lock().await.clone(),
+        };
 
-        let safe_title = sanitize_filename(&track.title);
-        let safe_uploader = sanitize_filename(&track.uploader);
-        // Include video_id in filename to uniquely identify downloads
-        let filename = format!("[{}] {} - {}", track.id, safe_title, safe_uploader);
+        let filename = download_file_stem(&track.id, &track.title, &track.uploader);
 
         let output_template = downloads_dir
             .join(format!("{}.%(ext)s", filename))
@@ -279,24 +625,61 @@ impl DownloadManager {
             _ => "bestaudio[ext=m4a]/bestaudio", // "best" or default
         };
 
-        // Use tokio::process::Command for proper async I/O
-        let mut child = tokio::process::Command::new(&ytdlp_path)
-            .args(&[
-                "--format",
-                format_string,
-                "--output",
-                &output_template,
-                "--no-playlist",
-                "--newline", // Force yt-dlp to output progress on new lines
-                "--progress",
-                &video_url,
-            ])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+        let option_args = self.ytdlp_options.lock().await.args(YtdlpOperation::Download);
+
+        // yt-dlp can't change its rate limit while running, so a download whose throttling changes
+        // for good is stopped and started again, resuming from its partial file. The same happens
+        // when the binary is about to be swapped; the restart waits for the swap
+        let mut bandwidth = self.bandwidth.subscribe();
+        loop {
+            let throttled = bandwidth.borrow_and_update().throttles(&track.id);
+            self.set_throttled(&track.id, throttled).await;
+            let rate_args: &[&str] = if throttled { &["--limit-rate", THROTTLED_RATE] } else { &[] };
+
+            // Downloads queue up behind a cooldown instead of failing
+            self.limiter.acquire_patiently().await;
+
+            // Held until this run ends or a yt-dlp update asks for it
+            let _binary = YTDLPInstaller::hold_binary().await;
+            // Use tokio::process::Command for proper async I/O
+            let child = tokio::process::Command::new(&ytdlp_path)
+                .args(&[
+                    "--format",
+                    format_string,
+                    "--output",
+                    &output_template,
+                    "--no-playlist",
+                    "--newline", // Force yt-dlp to output progress on new lines
+                    "--progress",
+                    &video_url,
+                ])
+                .args(rate_args)
+                .args(&option_args)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+
+            if let Some(result) = self.run_download(&track, child, &mut bandwidth, throttled).await {
+                return result;
+            }
+            println!("🔁 Restarting download of {}", track.title);
+        }
+    }
+
+    // Waits for a yt-dlp download, or stops it and returns None once its throttling changes or the
+    // binary is about to be swapped
+    async fn run_download(
+        &self,
+        track: &YTVideoInfo,
+        mut child: tokio::process::Child,
+        bandwidth: &mut watch::Receiver<BandwidthState>,
+        throttled: bool,
+    ) -> Option<Result<(), String>> {
+        let Some((stdout, stderr)) = child.stdout.take().zip(child.stderr.take()) else {
+            return Some(Err("Failed to capture yt-dlp output".to_string()));
+        };
 
-        let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
         let video_id = track.id.clone();
         let self_for_parse = self.clone_for_task();
 
@@ -311,17 +694,98 @@ impl DownloadManager {
             }
         });
 
-        let status = child.wait().await.map_err(|e| format!("Wait failed: {}", e))?;
+        // Keep yt-dlp's last error line to explain a failure
+        let error_handle = tokio::spawn(async move {
+            use tokio::io::{AsyncBufReadExt, BufReader};
+            let mut lines = BufReader::new(stderr).lines();
+            let mut last_error = None;
+            while let Ok(Some(line)) = lines.next_line().await {
+                recent_log::record("yt-dlp download", &line);
+                if line.starts_with("ERROR") {
+                    last_error = Some(line);
+                }
+            }
+            last_error
+        });
+
+        let throttling_changed = async {
+            loop {
+                if bandwidth.wait_for(|state| state.throttles(&track.id) != throttled).await.is_err() {
+                    return false;
+                }
+                // Giving a download priority applies right away, playback flips have to hold
+                if throttled && bandwidth.borrow().is_prioritized(&track.id) {
+                    return true;
+                }
+                tokio::time::sleep(RESTART_HOLD).await;
+                let still_changed = bandwidth.borrow_and_update().throttles(&track.id) != throttled;
+                if still_changed && !self.is_nearly_done(&track.id).await {
+                    return true;
+                }
+            }
+        };
+        let status = tokio::select! {
+            status = child.wait() => status,
+            true = throttling_changed => {
+                println!("🐢 Throttling of {} changed", track.title);
+                let _ = child.kill().await;
+                let _ = parse_handle.await;
+                let _ = error_handle.await;
+                return None;
+            }
+            _ = YTDLPInstaller::swap_requested() => {
+                println!("⏸️ Pausing download of {} for the yt-dlp update", track.title);
+                let _ = child.kill().await;
+                let _ = parse_handle.await;
+                let _ = error_handle.await;
+                return None;
+            }
+        };
+        let status = match status {
+            Ok(status) => status,
+            Err(e) => return Some(Err(format!("Wait failed: {}", e))),
+        };
 
         // Wait for parsing to complete
         let _ = parse_handle.await;
+        let last_error = error_handle.await.ok().flatten();
+        self.limiter
+            .report(status.success(), last_error.as_deref().unwrap_or_default())
+            .await;
 
         if status.success() {
-            self.mark_download_completed(&track).await?;
-            Ok(())
+            Some(self.mark_download_completed(track).await)
         } else {
-            Err(format!("Download failed with status: {:?}", status))
+            Some(Err(last_error.unwrap_or_else(|| format!("Download failed with status: {:?}", status))))
+        }
+    }
+
+    // A download this far along finishes sooner at its current rate than by restarting
+    async fn is_nearly_done(&self, video_id: &str) -> bool {
+        self.active_downloads
+            .lock()
+            .await
+            .get(video_id)
+            .is_some_and(|download| download.progress >= NEARLY_DONE)
+    }
+
+    async fn set_throttled(&self, video_id: &str, throttled: bool) {
+        if let Some(download) = self.active_downloads.lock().await.get_mut(video_id) {
+            download.throttled = throttled;
         }
+        self.emit_downloads_update().await;
+    }
+
+    /// Let a download keep full speed while playback streams, or have it give way again
+    pub async fn set_download_priority(&self, video_id: &str, priority: DownloadPriority) -> Result<(), String> {
+        {
+            let mut active = self.active_downloads.lock().await;
+            let download = active.get_mut(video_id).ok_or("Download isn't running")?;
+            download.priority = priority;
+        }
+        self.bandwidth.set_prioritized(video_id, priority == DownloadPriority::High);
+        self.emit_downloads_update().await;
+        Ok(())
     }
 
     async fn parse_ytdlp_progress(&self, line: &str, video_id: &str) {
@@ -363,6 +827,9 @@ impl DownloadManager {
     }
 
     async fn mark_download_completed(&self, track: &YTVideoInfo) -> Result<(), String> {
+        // Save metadata first so the directory watcher sees a complete download
+        self.save_track_metadata(track).await?;
+
         // Remove from active
         {
             let mut active = self.active_downloads.lock().await;
@@ -377,26 +844,234 @@ impl DownloadManager {
             }
         }
 
-        // Save metadata
-        self.save_track_metadata(track).await?;
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        if let Some(file_path) = find_audio_file(&downloads_dir, &track.id) {
+            let db_track = Track::from(track.clone());
+            if let Err(e) = self.db.set_download_path(&db_track, &file_path.to_string_lossy()).await {
+                eprintln!("⚠️ Failed to link download of {} to its track: {}", track.id, e);
+            }
+        }
+
+        let (_, truncated) = read_integrity(&downloads_dir, &track.id);
+        if truncated {
+            println!("⚠️ Download of {} is shorter than expected", track.title);
+            if let Some(handle) = self.app_handle.lock().await.as_ref() {
+                let _ = handle.emit("download-truncated", track);
+            }
+        }
+
+        // Tracks of a batch are announced together once the whole batch is done
+        if !self.batch.lock().await.contains(&track.id) {
+            if let Some(handle) = self.app_handle.lock().await.as_ref() {
+                let _ = handle.emit("download-completed", track);
+            }
+        }
 
+        self.refresh_storage_used().await;
         self.emit_downloads_update().await;
         Ok(())
     }
 
-    async fn update_download_error(&self, video_id: &str, error: &str) {
-        let mut active = self.active_downloads.lock().await;
-        if let Some(dl) = active.get_mut(video_id) {
-            dl.error = Some(error.to_string());
+    // Moves a failed download out of the active list into the persisted failed list
+    async fn record_failure(&self, track: YTVideoInfo, quality: Option<String>, error: String) {
+        // Cancelled downloads aren't failures
+        if self.active_downloads.lock().await.remove(&track.id).is_none() {
+            return;
         }
-        drop(active);
+
+        let kind = DownloadErrorKind::classify(&error);
+        let mut error = error;
+        if kind == DownloadErrorKind::Storage {
+            let downloads_dir = self.downloads_dir.lock().await.clone();
+            if let Some(problem) = self.storage.report(&downloads_dir, &error) {
+                println!("💾 {}, pausing downloads", problem.cause);
+                if let Some(handle) = self.app_handle.lock().await.as_ref() {
+                    let _ = handle.emit("storage-problem", &problem);
+                }
+            }
+            if let Some(problem) = self.storage.problem() {
+                error = problem.cause;
+            }
+        }
+
+        self.park_download(track, quality, error, kind).await;
+    }
+
+    /// Park a download so it starts with the next network retry, e.g. one requested while offline
+    pub async fn defer_download(&self, track: YTVideoInfo, quality: Option<String>, reason: &str) -> Result<(), String> {
+        self.park_download(track, quality, reason.to_string(), DownloadErrorKind::Network).await;
+        Ok(())
+    }
+
+    async fn park_download(&self, track: YTVideoInfo, quality: Option<String>, error: String, kind: DownloadErrorKind) {
+        {
+            let mut failed = self.failed_downloads.lock().await;
+            failed.retain(|f| f.track.id != track.id);
+            failed.push(FailedDownload {
+                track,
+                quality,
+                error,
+                kind,
+                failed_at: chrono::Utc::now().timestamp(),
+            });
+        }
+
+        self.save_failed_downloads().await;
         self.emit_downloads_update().await;
     }
 
+    async fn save_failed_downloads(&self) {
+        let failed = self.failed_downloads.lock().await;
+        let path = AppData::get_failed_downloads_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).ok();
+        }
+        if let Ok(json) = serde_json::to_string_pretty(&*failed) {
+            if let Err(e) = std::fs::write(&path, json) {
+                eprintln!("⚠️ Failed to save failed downloads: {}", e);
+            }
+        }
+    }
+
+    pub async fn get_failed_downloads(&self) -> Vec<FailedDownload> {
+        self.failed_downloads.lock().await.clone()
+    }
+
+    pub async fn has_network_failures(&self) -> bool {
+        self.failed_downloads
+            .lock()
+            .await
+            .iter()
+            .any(|f| f.kind == DownloadErrorKind::Network)
+    }
+
+    /// Download many tracks as one batch whose combined progress is reported through
+    /// `download-batch-progress`. Returns how many downloads were started.
+    pub async fn download_tracks(&self, tracks: Vec<YTVideoInfo>, quality: Option<String>) -> usize {
+        let mut started = 0;
+        for track in tracks {
+            let video_id = track.id.clone();
+            match self.download_track(track, quality.clone()).await {
+                Ok(()) => {
+                    started += 1;
+                    let mut batch = self.batch.lock().await;
+                    if !batch.contains(&video_id) {
+                        batch.push(video_id);
+                    }
+                }
+                Err(e) => eprintln!("⚠️ Skipping {} in batch download: {}", video_id, e),
+            }
+        }
+
+        self.emit_batch_progress().await;
+        started
+    }
+
+    // Reports the running batch, forgetting it once every track has finished or failed.
+    // A finished batch also sends `download-batch-finished` with the video IDs that completed.
+    async fn emit_batch_progress(&self) {
+        let mut batch = self.batch.lock().await;
+        if batch.is_empty() {
+            return;
+        }
+
+        let mut completed_ids = Vec::new();
+        let progress = {
+            let active = self.active_downloads.lock().await;
+            let completed = self.completed_downloads.lock().await;
+            let failed = self.failed_downloads.lock().await;
+
+            let mut summary = BatchProgress {
+                total: batch.len(),
+                completed: 0,
+                failed: 0,
+                progress: 0.0,
+            };
+            let mut done = 0.0;
+            for video_id in batch.iter() {
+                if let Some(download) = active.get(video_id) {
+                    done += download.progress;
+                } else if completed.contains(video_id) {
+                    summary.completed += 1;
+                    completed_ids.push(video_id.clone());
+                    done += 1.0;
+                } else if failed.iter().any(|f| &f.track.id == video_id) {
+                    summary.failed += 1;
+                    done += 1.0;
+                }
+            }
+            summary.progress = done / batch.len() as f64;
+            summary
+        };
+
+        let finished = progress.completed + progress.failed == progress.total;
+        if finished {
+            println!("📦 Batch download finished: {} completed, {} failed", progress.completed, progress.failed);
+            batch.clear();
+        }
+        drop(batch);
+
+        if let Some(handle) = self.app_handle.lock().await.as_ref() {
+            let _ = handle.emit("download-batch-progress", progress);
+            if finished && !completed_ids.is_empty() {
+                let _ = handle.emit("download-batch-finished", completed_ids);
+            }
+        }
+    }
+
+    /// Restart every failed download, returning how many were restarted
+    pub async fn retry_all_failed(&self) -> usize {
+        self.retry_failed(|_| true).await
+    }
+
+    /// Restart downloads that failed on a network error
+    pub async fn retry_network_failures(&self) -> usize {
+        self.retry_failed(|f| f.kind == DownloadErrorKind::Network).await
+    }
+
+    /// Restart downloads that failed because the disk was full or read-only
+    pub async fn retry_storage_failures(&self) -> usize {
+        self.retry_failed(|f| f.kind == DownloadErrorKind::Storage).await
+    }
+
+    async fn retry_failed(&self, filter: impl Fn(&FailedDownload) -> bool) -> usize {
+        let to_retry: Vec<FailedDownload> = self
+            .failed_downloads
+            .lock()
+            .await
+            .iter()
+            .filter(|f| filter(f))
+            .cloned()
+            .collect();
+
+        let mut retried = 0;
+        for failed in to_retry {
+            match self.download_track(failed.track.clone(), failed.quality).await {
+                Ok(()) => retried += 1,
+                Err(e) => eprintln!("⚠️ Couldn't retry {}: {}", failed.track.title, e),
+            }
+        }
+        retried
+    }
+
     async fn save_track_metadata(&self, track: &YTVideoInfo) -> Result<(), String> {
         let downloads_dir = self.downloads_dir.lock().await.clone();
         let metadata_path = downloads_dir.join(format!("{}_metadata.json", track.id));
 
+        // Hash the audio so the file can be re-matched later even if it gets renamed
+        let audio_path = find_audio_file(&downloads_dir, &track.id);
+        let content_hash = match &audio_path {
+            Some(path) => hash_audio_file(path.clone()).await,
+            None => None,
+        };
+
+        // Compare the real length against the listed one to catch cut-off downloads
+        let actual_duration = match &audio_path {
+            Some(path) => probe_duration(path).await,
+            None => None,
+        };
+        let truncated = is_truncated(track.duration, actual_duration);
+
         let metadata = serde_json::json!({
             "id": track.id,
             "title": track.title,
@@ -405,6 +1080,12 @@ impl DownloadManager {
             "thumbnail_url": track.thumbnail_url,
             "description": track.description,
             "download_date": chrono::Utc::now().timestamp(),
+            "content_hash": content_hash,
+            "content_type": track.content_type,
+            "custom_title": track.custom_title,
+            "custom_author": track.custom_author,
+            "actual_duration": actual_duration,
+            "truncated": truncated,
         });
 
         let json = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
@@ -429,32 +1110,21 @@ impl DownloadManager {
         let mut tracks = Vec::new();
 
         for video_id in completed.iter() {
-            let metadata_path = downloads_dir.join(format!("{}_metadata.json", video_id));
-
-            if let Ok(json) = std::fs::read_to_string(&metadata_path) {
-                if let Ok(metadata) = serde_json::from_str::<serde_json::Value>(&json) {
-                    let video_info = YTVideoInfo {
-                        id: metadata["id"].as_str().unwrap_or("").to_string(),
-                        title: metadata["title"].as_str().unwrap_or("").to_string(),
-                        uploader: metadata["uploader"].as_str().unwrap_or("").to_string(),
-                        duration: metadata["duration"].as_i64().unwrap_or(0),
-                        thumbnail_url: metadata["thumbnail_url"].as_str().map(|s| s.to_string()),
-                        audio_url: None,
-                        description: metadata["description"].as_str().map(|s| s.to_string()),
-                    };
-
-                    if let Some(file_path) = find_audio_file(&downloads_dir, video_id) {
-                        let file_size = std::fs::metadata(&file_path)
-                            .map(|m| m.len() as i64)
-                            .unwrap_or(0);
-
-                        tracks.push(DownloadedTrack {
-                            video_info,
-                            file_path: file_path.to_string_lossy().to_string(),
-                            file_size,
-                            download_date: metadata["download_date"].as_i64().unwrap_or(0),
-                        });
-                    }
+            if let Some((video_info, download_date)) = read_track_metadata(&downloads_dir, video_id) {
+                if let Some(file_path) = find_audio_file(&downloads_dir, video_id) {
+                    let file_size = std::fs::metadata(&file_path)
+                        .map(|m| m.len() as i64)
+                        .unwrap_or(0);
+                    let (actual_duration, is_truncated) = read_integrity(&downloads_dir, video_id);
+
+                    tracks.push(DownloadedTrack {
+                        video_info,
+                        file_path: file_path.to_string_lossy().to_string(),
+                        file_size,
+                        download_date,
+                        actual_duration,
+                        is_truncated,
+                    });
                 }
             }
         }
@@ -462,9 +1132,52 @@ impl DownloadManager {
         tracks
     }
 
+    /// Number of downloaded tracks among `video_ids` and their combined size in bytes
+    pub async fn get_downloaded_totals(&self, video_ids: &[String]) -> (i64, i64) {
+        let completed = self.completed_downloads.lock().await.clone();
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+
+        let mut count = 0;
+        let mut size = 0;
+        for video_id in video_ids.iter().filter(|id| completed.contains(id)) {
+            if let Some(file_path) = find_audio_file(&downloads_dir, video_id) {
+                count += 1;
+                size += std::fs::metadata(&file_path).map(|m| m.len() as i64).unwrap_or(0);
+            }
+        }
+
+        (count, size)
+    }
+
+    pub async fn get_downloaded_ids(&self) -> Vec<String> {
+        self.completed_downloads.lock().await.clone()
+    }
+
+    pub async fn get_downloaded_video_info(&self, video_id: &str) -> Option<YTVideoInfo> {
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        read_track_metadata(&downloads_dir, video_id).map(|(video_info, _)| video_info)
+    }
+
     pub async fn get_storage_used(&self) -> i64 {
+        if let Some(size) = *self.storage_used.lock().await {
+            return size;
+        }
+        self.refresh_storage_used().await
+    }
+
+    // Re-measures the downloads directory, telling the UI when its size changed
+    async fn refresh_storage_used(&self) -> i64 {
         let downloads_dir = self.downloads_dir.lock().await.clone();
-        calculate_directory_size(&downloads_dir)
+        let size = AppData::directory_size_async(downloads_dir).await;
+
+        let previous = self.storage_used.lock().await.replace(size);
+        if previous != Some(size) {
+            if let Some(handle) = self.app_handle.lock().await.as_ref() {
+                let _ = handle.emit("storage-changed", size);
+            }
+        }
+
+        size
     }
 
     pub async fn is_downloaded(&self, video_id: &str) -> bool {
@@ -497,6 +1210,10 @@ impl DownloadManager {
         self.audio_quality.lock().await.clone()
     }
 
+    pub async fn set_ytdlp_options(&self, options: YtdlpOptions) {
+        *self.ytdlp_options.lock().await = options;
+    }
+
     pub async fn delete_download(&self, video_id: &str) -> Result<(), String> {
         let downloads_dir = self.downloads_dir.lock().await.clone();
 
@@ -511,20 +1228,335 @@ impl DownloadManager {
             std::fs::remove_file(&metadata_path).map_err(|e| e.to_string())?;
         }
 
+        // Delete captions
+        if let Some(captions_path) = find_captions_file(&downloads_dir, video_id) {
+            std::fs::remove_file(&captions_path).map_err(|e| e.to_string())?;
+        }
+
         // Remove from completed list
         {
             let mut completed = self.completed_downloads.lock().await;
             completed.retain(|id| id != video_id);
         }
 
+        self.unlink_track(video_id).await;
+        self.refresh_storage_used().await;
         self.emit_downloads_update().await;
         Ok(())
     }
 
+    /// Keep a download's custom names in its metadata, so relinking or importing it restores them
+    pub async fn set_display_names(&self, video_id: &str, title: Option<&str>, author: Option<&str>) {
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        let metadata_path = downloads_dir.join(format!("{}_metadata.json", video_id));
+        let Some(mut metadata) = std::fs::read_to_string(&metadata_path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        else {
+            return;
+        };
+
+        metadata["custom_title"] = serde_json::json!(title);
+        metadata["custom_author"] = serde_json::json!(author);
+        if let Ok(json) = serde_json::to_string_pretty(&metadata) {
+            if let Err(e) = std::fs::write(&metadata_path, json) {
+                eprintln!("⚠️ Failed to save custom names for {}: {}", video_id, e);
+            }
+        }
+        self.emit_downloads_update().await;
+    }
+
+    /// Probe every download's real duration, updating its metadata, and return the IDs of truncated ones
+    pub async fn verify_downloads(&self) -> Vec<String> {
+        let completed = self.completed_downloads.lock().await.clone();
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+
+        let mut truncated_ids = Vec::new();
+        for video_id in completed {
+            let Some((video_info, _)) = read_track_metadata(&downloads_dir, &video_id) else {
+                continue;
+            };
+            let actual_duration = match find_audio_file(&downloads_dir, &video_id) {
+                Some(path) => probe_duration(&path).await,
+                None => None,
+            };
+            let truncated = is_truncated(video_info.duration, actual_duration);
+
+            let metadata_path = downloads_dir.join(format!("{}_metadata.json", video_id));
+            if let Some(mut metadata) = std::fs::read_to_string(&metadata_path)
+                .ok()
+                .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+            {
+                metadata["actual_duration"] = serde_json::json!(actual_duration);
+                metadata["truncated"] = serde_json::json!(truncated);
+                if let Ok(json) = serde_json::to_string_pretty(&metadata) {
+                    std::fs::write(&metadata_path, json).ok();
+                }
+            }
+
+            if truncated {
+                truncated_ids.push(video_id);
+            }
+        }
+
+        self.emit_downloads_update().await;
+        truncated_ids
+    }
+
+    /// Delete a download and fetch it again, e.g. after it was found to be truncated
+    pub async fn redownload(&self, video_id: &str) -> Result<(), String> {
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        let (video_info, _) = read_track_metadata(&downloads_dir, video_id)
+            .ok_or_else(|| "Track is not downloaded".to_string())?;
+
+        self.delete_download(video_id).await?;
+        self.download_track(video_info, None).await
+    }
+
+    /// Re-match audio files in `folder` to downloads whose audio is missing, by video ID
+    /// in the filename or by content hash, and move them back under their canonical name.
+    pub async fn relink_downloads(&self, folder: PathBuf) -> Result<RelinkReport, String> {
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+
+        // Metadata entries whose audio file can no longer be found
+        let mut missing: Vec<(YTVideoInfo, Option<String>)> = Vec::new();
+        let entries = std::fs::read_dir(&downloads_dir).map_err(|e| e.to_string())?;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(video_id) = name.strip_suffix("_metadata.json") else {
+                continue;
+            };
+            if find_audio_file(&downloads_dir, video_id).is_some() {
+                continue;
+            }
+            if let Some((video_info, _)) = read_track_metadata(&downloads_dir, video_id) {
+                let content_hash = read_content_hash(&downloads_dir, video_id);
+                missing.push((video_info, content_hash));
+            }
+        }
+
+        let mut report = RelinkReport {
+            relinked: Vec::new(),
+            unmatched: 0,
+        };
+
+        let entries = std::fs::read_dir(&folder).map_err(|e| e.to_string())?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(ext) = path.extension().and_then(|e| e.to_str()).map(|e| e.to_string()) else {
+                continue;
+            };
+            if !AUDIO_EXTENSIONS.contains(&ext.as_str()) {
+                continue;
+            }
+
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let file_id = file_video_id(&path);
+            let mut matched = missing.iter().position(|(info, _)| file_id == Some(info.id.as_str()));
+            if matched.is_none() {
+                // Filename no longer carries the ID, fall back to the content hash
+                if let Some(hash) = hash_audio_file(path.clone()).await {
+                    matched = missing
+                        .iter()
+                        .position(|(_, content_hash)| content_hash.as_deref() == Some(hash.as_str()));
+                }
+            }
+
+            let Some(index) = matched else {
+                report.unmatched += 1;
+                continue;
+            };
+
+            let (video_info, _) = missing.remove(index);
+            let stem = download_file_stem(&video_info.id, &video_info.title, &video_info.uploader);
+            let destination = downloads_dir.join(format!("{}.{}", stem, ext));
+
+            if std::fs::rename(&path, &destination).is_err() {
+                // Different filesystem, fall back to copy + remove
+                std::fs::copy(&path, &destination).map_err(|e| e.to_string())?;
+                std::fs::remove_file(&path).ok();
+            }
+
+            println!("🔗 Relinked {} -> {}", file_name, destination.display());
+            report.relinked.push(video_info.id);
+        }
+
+        {
+            let mut completed = self.completed_downloads.lock().await;
+            for video_id in &report.relinked {
+                if !completed.contains(video_id) {
+                    completed.push(video_id.clone());
+                }
+            }
+        }
+
+        self.sync_track_paths().await;
+        self.refresh_storage_used().await;
+        self.emit_downloads_update().await;
+        Ok(report)
+    }
+
+    /// Delete metadata left behind by downloads whose audio is gone, and partial files of
+    /// interrupted downloads. Downloads in progress are left alone.
+    pub async fn clean_up_orphans(&self) -> CleanupReport {
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        let mut keep: HashSet<String> = self.active_downloads.lock().await.keys().cloned().collect();
+        // A failed download resumes from its partial file when it's retried
+        keep.extend(self.failed_downloads.lock().await.iter().map(|f| f.track.id.clone()));
+        // Partial files are only ours if they carry the ID of a track the app knows
+        let mut known: HashSet<String> = self.completed_downloads.lock().await.iter().cloned().collect();
+        match self.db.get_all_track_ids().await {
+            Ok(ids) => known.extend(ids),
+            Err(e) => eprintln!("⚠️ Failed to list tracks for cleanup: {}", e),
+        }
+
+        // Walking the folder and deleting files blocks, so it runs off the async runtime
+        let report = tokio::task::spawn_blocking(move || remove_orphans(&downloads_dir, &keep, &known))
+            .await
+            .unwrap_or_default();
+
+        if report.metadata_removed > 0 || report.partial_removed > 0 {
+            println!(
+                "🧹 Removed {} orphaned metadata and {} partial files, reclaimed {} bytes",
+                report.metadata_removed, report.partial_removed, report.bytes_reclaimed
+            );
+            if let Some(handle) = self.app_handle.lock().await.as_ref() {
+                let _ = handle.emit("downloads-cleaned", report.clone());
+            }
+            self.refresh_storage_used().await;
+        }
+
+        report
+    }
+
+    /// Path to the captions of a downloaded track, fetching them with yt-dlp on first use
+    pub async fn get_captions_path(&self, video_id: &str) -> Result<PathBuf, String> {
+        if !self.is_downloaded(video_id).await {
+            return Err("Track is not downloaded".to_string());
+        }
+
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        if let Some(path) = find_captions_file(&downloads_dir, video_id) {
+            return Ok(path);
+        }
+
+        let ytdlp_path = YTDLPInstaller::get_ytdlp_path();
+        let output_template = downloads_dir
+            .join(format!("{}_captions", video_id))
+            .to_string_lossy()
+            .to_string();
+        let video_url = format!("https://www.youtube.com/watch?v={}", video_id);
+
+        let option_args = self.ytdlp_options.lock().await.args(YtdlpOperation::Lookup);
+
+        // yt-dlp appends the language, e.g. "<id>_captions.en.vtt"
+        let _binary = YTDLPInstaller::hold_binary().await;
+        let output = tokio::process::Command::new(&ytdlp_path)
+            .args([
+                "--skip-download",
+                "--write-subs",
+                "--write-auto-subs",
+                "--sub-langs",
+                "en.*,en",
+                "--sub-format",
+                "vtt",
+                "--no-warnings",
+                "--output",
+                &output_template,
+                &video_url,
+            ])
+            .args(&option_args)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+
+        if !output.status.success() {
+            return Err("Failed to fetch captions".to_string());
+        }
+
+        find_captions_file(&downloads_dir, video_id)
+            .ok_or_else(|| "No captions available for this track".to_string())
+    }
+
+    /// Move a download's files aside so the deletion can still be undone.
+    /// Returns (staged path, original path) pairs.
+    pub async fn stage_delete(&self, video_id: &str) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        let staging_dir = downloads_dir.join(".undo");
+        std::fs::create_dir_all(&staging_dir).map_err(|e| e.to_string())?;
+
+        let mut originals = Vec::new();
+        if let Some(file_path) = find_audio_file(&downloads_dir, video_id) {
+            originals.push(file_path);
+        }
+        let metadata_path = downloads_dir.join(format!("{}_metadata.json", video_id));
+        if metadata_path.exists() {
+            originals.push(metadata_path);
+        }
+        if let Some(captions_path) = find_captions_file(&downloads_dir, video_id) {
+            originals.push(captions_path);
+        }
+
+        let mut staged_files = Vec::new();
+        for original in originals {
+            let staged = staging_dir.join(original.file_name().unwrap_or_default());
+            std::fs::rename(&original, &staged).map_err(|e| e.to_string())?;
+            staged_files.push((staged, original));
+        }
+
+        {
+            let mut completed = self.completed_downloads.lock().await;
+            completed.retain(|id| id != video_id);
+        }
+
+        self.unlink_track(video_id).await;
+        self.refresh_storage_used().await;
+        self.emit_downloads_update().await;
+        Ok(staged_files)
+    }
+
+    pub async fn restore_staged(&self, video_id: &str, staged_files: &[(PathBuf, PathBuf)]) -> Result<(), String> {
+        for (staged, original) in staged_files {
+            std::fs::rename(staged, original).map_err(|e| e.to_string())?;
+        }
+
+        {
+            let mut completed = self.completed_downloads.lock().await;
+            if !completed.iter().any(|id| id == video_id) {
+                completed.push(video_id.to_string());
+            }
+        }
+
+        let downloads_dir = self.downloads_dir.lock().await.clone();
+        if let Some(file_path) = find_audio_file(&downloads_dir, video_id) {
+            if let Err(e) = self.db.update_download_path(video_id, Some(&file_path.to_string_lossy())).await {
+                eprintln!("⚠️ Failed to relink track {}: {}", video_id, e);
+            }
+        }
+
+        self.refresh_storage_used().await;
+        self.emit_downloads_update().await;
+        Ok(())
+    }
+
+    /// Delete every downloaded track and its metadata
+    pub async fn delete_all_downloads(&self) -> Result<(), String> {
+        let video_ids = self.completed_downloads.lock().await.clone();
+        for video_id in video_ids {
+            self.delete_download(&video_id).await?;
+        }
+        Ok(())
+    }
+
     pub async fn cancel_download(&self, video_id: &str) -> Result<(), String> {
         let mut active = self.active_downloads.lock().await;
         active.remove(video_id);
         drop(active);
+        self.awaiting_stream.lock().await.remove(video_id);
+        self.bandwidth.set_prioritized(video_id, false);
+
+        // A cancelled track no longer counts towards its batch
+        self.batch.lock().await.retain(|id| id != video_id);
 
         self.emit_downloads_update().await;
         Ok(())
@@ -534,7 +1566,177 @@ impl DownloadManager {
         if let Some(handle) = self.app_handle.lock().await.as_ref() {
             let _ = handle.emit("downloads-updated", ());
         }
+        self.emit_batch_progress().await;
+    }
+}
+
+const AUDIO_EXTENSIONS: [&str; 5] = ["m4a", "webm", "mp3", "aac", "ogg"];
+
+// Progress past which a download keeps its rate instead of restarting at a new one
+const NEARLY_DONE: f64 = 0.9;
+
+// Temporary files yt-dlp writes while downloading
+const PARTIAL_EXTENSIONS: [&str; 3] = ["part", "ytdl", "temp"];
+
+// Partial files this recent may belong to a download started moments ago, e.g. by another window
+const ORPHAN_PARTIAL_GRACE: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+// Orphaned metadata is kept this long so `relink_downloads` can still match audio that was moved away
+const ORPHAN_METADATA_GRACE: std::time::Duration = std::time::Duration::from_secs(7 * 24 * 60 * 60);
+
+// Downloads shorter than this fraction of the listed duration (minus the slack) are truncated
+const TRUNCATED_RATIO: f64 = 0.97;
+const DURATION_SLACK: f64 = 2.0;
+
+// Include video_id in filename to uniquely identify downloads
+fn download_file_stem(video_id: &str, title: &str, uploader: &str) -> String {
+    format!("[{}] {} - {}", video_id, sanitize_filename(title), sanitize_filename(uploader))
+}
+
+// The ID a file was saved under, from a "[<id>] title - uploader" name or a bare "<id>" one
+fn file_video_id(path: &Path) -> Option<&str> {
+    let stem = path.file_stem()?.to_str()?;
+    match stem.strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map(|(id, _)| id),
+        None => Some(stem),
+    }
+}
+
+// Hashing reads the whole file, so it runs off the async runtime
+async fn hash_audio_file(path: PathBuf) -> Option<String> {
+    tokio::task::spawn_blocking(move || hash_file(&path)).await.ok()?.ok()
+}
+
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
     }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Removes metadata whose audio is gone and partial files of downloads that aren't running or
+// waiting to be retried. Files of IDs in `keep` are left alone.
+fn remove_orphans(downloads_dir: &Path, keep: &HashSet<String>, known: &HashSet<String>) -> CleanupReport {
+    let mut report = CleanupReport::default();
+    let Ok(entries) = std::fs::read_dir(downloads_dir) else {
+        return report;
+    };
+
+    for entry in entries.flatten() {
+        let Ok(file_info) = entry.metadata() else {
+            continue;
+        };
+        if !file_info.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if keep.iter().any(|video_id| name.contains(video_id.as_str())) {
+            continue;
+        }
+        let age = file_info.modified().ok().and_then(|t| t.elapsed().ok()).unwrap_or_default();
+
+        if let Some(video_id) = name.strip_suffix("_metadata.json") {
+            if find_audio_file(downloads_dir, video_id).is_some() || age < ORPHAN_METADATA_GRACE {
+                continue;
+            }
+            if std::fs::remove_file(entry.path()).is_ok() {
+                report.metadata_removed += 1;
+                report.bytes_reclaimed += file_info.len();
+            }
+            // Captions are useless without the download they belong to
+            if let Some(captions_path) = find_captions_file(downloads_dir, video_id) {
+                let size = std::fs::metadata(&captions_path).map(|m| m.len()).unwrap_or(0);
+                if std::fs::remove_file(&captions_path).is_ok() {
+                    report.bytes_reclaimed += size;
+                }
+            }
+        } else if is_partial_file(&name)
+            && age >= ORPHAN_PARTIAL_GRACE
+            && partial_video_id(&name).is_some_and(|video_id| known.contains(video_id))
+            && std::fs::remove_file(entry.path()).is_ok()
+        {
+            report.partial_removed += 1;
+            report.bytes_reclaimed += file_info.len();
+        }
+    }
+
+    report
+}
+
+// The ID in a "[<id>] title - uploader.m4a.part" name; partial files without one aren't ours
+fn partial_video_id(name: &str) -> Option<&str> {
+    name.strip_prefix('[')?.split_once(']').map(|(id, _)| id)
+}
+
+// Fragmented downloads leave "<name>.part-Frag<n>" files next to the ".part" file
+fn is_partial_file(name: &str) -> bool {
+    name.contains(".part-Frag")
+        || Path::new(name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| PARTIAL_EXTENSIONS.contains(&ext))
+}
+
+fn find_captions_file(dir: &Path, video_id: &str) -> Option<PathBuf> {
+    let prefix = format!("{}_captions", video_id);
+    std::fs::read_dir(dir).ok()?.flatten().map(|entry| entry.path()).find(|path| {
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.starts_with(&prefix) && n.ends_with(".vtt"))
+            .unwrap_or(false)
+    })
+}
+
+// Probed duration and truncation flag recorded in a download's metadata
+fn read_integrity(dir: &Path, video_id: &str) -> (Option<f64>, bool) {
+    let metadata_path = dir.join(format!("{}_metadata.json", video_id));
+    let Some(metadata) = std::fs::read_to_string(&metadata_path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+    else {
+        return (None, false);
+    };
+    (metadata["actual_duration"].as_f64(), metadata["truncated"].as_bool().unwrap_or(false))
+}
+
+// Real length of an audio file in seconds, read with ffprobe
+async fn probe_duration(path: &Path) -> Option<f64> {
+    let output = tokio::process::Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries", "format=duration", "-of", "default=noprint_wrappers=1:nokey=1"])
+        .arg(path)
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+// Listed durations are rounded, so allow some slack before calling a file truncated
+fn is_truncated(expected: i64, actual: Option<f64>) -> bool {
+    match actual {
+        Some(actual) if expected > 0 => actual < expected as f64 * TRUNCATED_RATIO - DURATION_SLACK,
+        _ => false,
+    }
+}
+
+fn read_content_hash(dir: &Path, video_id: &str) -> Option<String> {
+    let metadata_path = dir.join(format!("{}_metadata.json", video_id));
+    let json = std::fs::read_to_string(&metadata_path).ok()?;
+    let metadata = serde_json::from_str::<serde_json::Value>(&json).ok()?;
+    metadata["content_hash"].as_str().map(|s| s.to_string())
 }
 
 fn sanitize_filename(name: &str) -> String {
@@ -543,41 +1745,70 @@ fn sanitize_filename(name: &str) -> String {
         .collect()
 }
 
-fn find_audio_file(dir: &PathBuf, video_id: &str) -> Option<PathBuf> {
-    let extensions = ["m4a", "webm", "mp3", "aac", "ogg"];
+// IDs of downloads in `dir` that have both a metadata file and an audio file
+fn scan_downloads(dir: &Path) -> Vec<String> {
+    let mut video_ids = Vec::new();
 
     if let Ok(entries) = std::fs::read_dir(dir) {
         for entry in entries.flatten() {
             let path = entry.path();
-            if let Some(ext) = path.extension() {
-                if extensions.contains(&ext.to_str().unwrap_or(""))
-                    && path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .map(|n| n.contains(video_id))
-                        .unwrap_or(false)
-                {
-                    return Some(path);
+            if let Some(file_name) = path.file_name() {
+                let name = file_name.to_string_lossy();
+                // Look for metadata files
+                if name.ends_with("_metadata.json") {
+                    // Extract video ID from filename
+                    let video_id = name.trim_end_matches("_metadata.json").to_string();
+                    // Check if corresponding audio file exists
+                    if find_audio_file(dir, &video_id).is_some() {
+                        video_ids.push(video_id);
+                    }
                 }
             }
         }
     }
 
-    None
+    video_ids
 }
 
-fn calculate_directory_size(dir: &PathBuf) -> i64 {
-    let mut total = 0i64;
+// Reads the saved metadata JSON for a download, returning the video info and download date
+fn read_track_metadata(dir: &Path, video_id: &str) -> Option<(YTVideoInfo, i64)> {
+    let metadata_path = dir.join(format!("{}_metadata.json", video_id));
+    let json = std::fs::read_to_string(&metadata_path).ok()?;
+    let metadata = serde_json::from_str::<serde_json::Value>(&json).ok()?;
+
+    let video_info = YTVideoInfo {
+        id: metadata["id"].as_str().unwrap_or("").to_string(),
+        title: metadata["title"].as_str().unwrap_or("").to_string(),
+        uploader: metadata["uploader"].as_str().unwrap_or("").to_string(),
+        duration: metadata["duration"].as_i64().unwrap_or(0),
+        thumbnail_url: metadata["thumbnail_url"].as_str().map(|s| s.to_string()),
+        audio_url: None,
+        description: metadata["description"].as_str().map(|s| s.to_string()),
+        content_type: metadata["content_type"].as_str().and_then(ContentType::parse),
+        custom_title: metadata["custom_title"].as_str().map(|s| s.to_string()),
+        custom_author: metadata["custom_author"].as_str().map(|s| s.to_string()),
+    };
+
+    Some((video_info, metadata["download_date"].as_i64().unwrap_or(0)))
+}
 
+fn find_audio_file(dir: &Path, video_id: &str) -> Option<PathBuf> {
     if let Ok(entries) = std::fs::read_dir(dir) {
         for entry in entries.flatten() {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_file() {
-                    total += metadata.len() as i64;
+            let path = entry.path();
+            if let Some(ext) = path.extension() {
+                if AUDIO_EXTENSIONS.contains(&ext.to_str().unwrap_or(""))
+                    && path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .map(|n| n.contains(video_id))
+                        .unwrap_or(false)
+                {
+                    return Some(path);
                 }
             }
         }
     }
 
-    total
+    None
 }
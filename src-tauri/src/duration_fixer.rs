@@ -0,0 +1,175 @@
+use crate::database::DatabaseManager;
+use crate::models::{TrackDuration, YTVideoInfo};
+use crate::queue_manager::QueueManager;
+use crate::ytdlp_manager::YTDLPManager;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+// Pause between lookups, the fixer is never in a hurry
+const LOOKUP_INTERVAL: Duration = Duration::from_secs(1);
+
+// Most lookups one list queues, so a long search doesn't turn into dozens of yt-dlp requests
+const MAX_LOOKUPS_PER_LIST: usize = 10;
+
+// A track whose duration couldn't be found isn't looked up again for this long
+pub const FAILED_RETRY_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Default)]
+struct Pending {
+    ids: VecDeque<String>,
+    queued: HashSet<String>,
+    running: bool,
+}
+
+/// Looks up durations for tracks that arrived without one (flat-playlist searches report 0),
+/// one at a time in the background, and fixes the saved track and queue as each comes in.
+/// The only place durations are looked up; lookups wait while offline or in data saver.
+pub struct DurationFixer {
+    db: Arc<DatabaseManager>,
+    ytdlp: Arc<YTDLPManager>,
+    queue: Arc<QueueManager>,
+    app_handle: Mutex<Option<AppHandle>>,
+    resolved: Mutex<HashMap<String, i64>>, // found this session, so repeat searches come back fixed
+    failed: Mutex<HashMap<String, Instant>>, // lookups that found nothing, skipped for a while
+    pending: Mutex<Pending>,
+    paused: AtomicBool,
+}
+
+impl DurationFixer {
+    pub fn new(db: Arc<DatabaseManager>, ytdlp: Arc<YTDLPManager>, queue: Arc<QueueManager>) -> Self {
+        Self {
+            db,
+            ytdlp,
+            queue,
+            app_handle: Mutex::new(None),
+            resolved: Mutex::new(HashMap::new()),
+            failed: Mutex::new(HashMap::new()),
+            pending: Mutex::new(Pending::default()),
+            paused: AtomicBool::new(false),
+        }
+    }
+
+    pub async fn set_app_handle(&self, handle: AppHandle) {
+        *self.app_handle.lock().await = Some(handle);
+    }
+
+    /// Hold off lookups, e.g. while offline or in data saver; queued tracks wait until resumed
+    pub async fn set_paused(self: &Arc<Self>, paused: bool) {
+        if self.paused.swap(paused, Ordering::SeqCst) && !paused {
+            self.enqueue(Vec::new()).await;
+        }
+    }
+
+    /// Fill in durations already found, and queue lookups for the first tracks still missing one
+    pub async fn fix(self: &Arc<Self>, tracks: &mut [YTVideoInfo]) {
+        let resolved = self.resolved.lock().await;
+        let mut missing = Vec::new();
+        for track in tracks.iter_mut().filter(|t| t.duration <= 0) {
+            match resolved.get(&track.id) {
+                Some(duration) => track.duration = *duration,
+                None => missing.push(track.id.clone()),
+            }
+        }
+        drop(resolved);
+        missing.truncate(MAX_LOOKUPS_PER_LIST);
+        self.enqueue(missing).await;
+    }
+
+    /// A duration found this session, queueing a lookup when there's none yet
+    pub async fn duration_or_enqueue(self: &Arc<Self>, video_id: &str) -> Option<i64> {
+        let known = self.resolved.lock().await.get(video_id).copied();
+        if known.is_none() {
+            self.enqueue(vec![video_id.to_string()]).await;
+        }
+        known
+    }
+
+    /// Look these tracks up once the ones already waiting are done; `track-duration-fixed` is
+    /// emitted for each. Tracks whose lookup recently found nothing are skipped.
+    pub async fn enqueue(self: &Arc<Self>, video_ids: Vec<String>) {
+        let failed = self.failed.lock().await;
+        let mut pending = self.pending.lock().await;
+        for video_id in video_ids {
+            let recently_failed = failed.get(&video_id).is_some_and(|at| at.elapsed() < FAILED_RETRY_AFTER);
+            if !recently_failed && pending.queued.insert(video_id.clone()) {
+                pending.ids.push_back(video_id);
+            }
+        }
+        drop(failed);
+        if pending.running || pending.ids.is_empty() || self.paused.load(Ordering::SeqCst) {
+            return;
+        }
+        pending.running = true;
+
+        let fixer = Arc::clone(self);
+        tokio::spawn(async move {
+            fixer.run().await;
+        });
+    }
+
+    async fn run(&self) {
+        loop {
+            let video_id = {
+                let mut pending = self.pending.lock().await;
+                if self.paused.load(Ordering::SeqCst) {
+                    pending.running = false;
+                    return;
+                }
+                match pending.ids.pop_front() {
+                    Some(video_id) => {
+                        pending.queued.remove(&video_id);
+                        video_id
+                    }
+                    None => {
+                        pending.running = false;
+                        return;
+                    }
+                }
+            };
+
+            let known = self.resolved.lock().await.get(&video_id).copied();
+            let duration = match known {
+                Some(duration) => duration,
+                None => {
+                    let looked_up = self.ytdlp.get_duration(&video_id).await;
+                    tokio::time::sleep(LOOKUP_INTERVAL).await;
+                    match looked_up {
+                        Ok(duration) if duration > 0 => duration,
+                        Ok(_) => {
+                            self.record_failure(&video_id).await;
+                            continue;
+                        }
+                        Err(e) => {
+                            eprintln!("⚠️ {}", e);
+                            self.record_failure(&video_id).await;
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            self.resolved.lock().await.insert(video_id.clone(), duration);
+            if let Err(e) = self.db.update_track_duration(&video_id, duration).await {
+                eprintln!("⚠️ Failed to save duration for {}: {}", video_id, e);
+            }
+            self.queue.set_duration(&video_id, duration).await;
+            println!("⏱️ Fixed duration of {}: {}s", video_id, duration);
+            if let Some(handle) = self.app_handle.lock().await.as_ref() {
+                let _ = handle.emit("track-duration-fixed", TrackDuration { video_id, duration });
+            }
+        }
+    }
+
+    // Remembered here for search results and in the database for saved tracks, so neither a
+    // repeat search nor the next launch looks it up again right away
+    async fn record_failure(&self, video_id: &str) {
+        self.failed.lock().await.insert(video_id.to_string(), Instant::now());
+        if let Err(e) = self.db.set_duration_lookup_failed(video_id).await {
+            eprintln!("⚠️ Failed to save duration lookup failure for {}: {}", video_id, e);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::models::AudioFingerprint;
+use serde::Deserialize;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// Only the start of a track is fingerprinted, that's plenty to tell recordings apart
+const FINGERPRINT_SECONDS: u32 = 120;
+// Chromaprint emits about eight items per second; re-uploads may start up to ten seconds apart
+const MAX_OFFSET: usize = 80;
+// Overlap needed before a comparison means anything, about twenty seconds
+const MIN_OVERLAP: usize = 160;
+// Share of matching bits that makes two fingerprints the same recording; unrelated audio sits near half
+const MATCH_THRESHOLD: f64 = 0.85;
+/// Recordings further apart in length than this aren't compared at all
+pub const MAX_DURATION_DIFFERENCE: f64 = 15.0;
+
+#[derive(Deserialize)]
+struct FpcalcOutput {
+    duration: f64,
+    fingerprint: Vec<u32>,
+}
+
+/// Whether Chromaprint's fpcalc tool is on PATH
+pub fn is_available() -> bool {
+    Command::new("fpcalc")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// Raw Chromaprint fingerprint and duration of an audio file. Blocking.
+pub fn compute(path: &Path) -> Result<(Vec<u32>, f64), String> {
+    let output = Command::new("fpcalc")
+        .args(["-raw", "-json", "-length", &FINGERPRINT_SECONDS.to_string()])
+        .arg(path)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run fpcalc: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("fpcalc couldn't read {}", path.display()));
+    }
+
+    let parsed: FpcalcOutput =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("Unexpected fpcalc output: {}", e))?;
+    if parsed.fingerprint.len() < MIN_OVERLAP {
+        return Err("Track is too short to fingerprint".to_string());
+    }
+    Ok((parsed.fingerprint, parsed.duration))
+}
+
+/// The best matching recording among `known`, which should already be close in duration
+pub fn find_match<'a>(fingerprint: &[u32], known: &'a [AudioFingerprint]) -> Option<&'a AudioFingerprint> {
+    known
+        .iter()
+        .map(|candidate| (candidate, similarity(fingerprint, &candidate.fingerprint)))
+        .filter(|(_, score)| *score >= MATCH_THRESHOLD)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(candidate, _)| candidate)
+}
+
+// Share of equal bits at the best alignment of the two fingerprints
+fn similarity(a: &[u32], b: &[u32]) -> f64 {
+    let mut best = 0.0;
+    for shift in -(MAX_OFFSET as isize)..=MAX_OFFSET as isize {
+        let (a, b) = if shift >= 0 {
+            (a.get(shift as usize..).unwrap_or_default(), b)
+        } else {
+            (a, b.get((-shift) as usize..).unwrap_or_default())
+        };
+        let overlap = a.len().min(b.len());
+        if overlap < MIN_OVERLAP {
+            continue;
+        }
+
+        let differing: u32 = a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum();
+        let score = 1.0 - differing as f64 / (overlap as f64 * 32.0);
+        if score > best {
+            best = score;
+        }
+    }
+    best
+}
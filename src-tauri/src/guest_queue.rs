@@ -0,0 +1,454 @@
+use crate::models::{GuestQueueSettings, GuestQueueStatus, GuestRequest, YTVideoInfo};
+use crate::queue_manager::QueueManager;
+use crate::ytdlp_manager::YTDLPManager;
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+// How many searches and added tracks each guest gets within the window
+const SEARCH_LIMIT: (usize, Duration) = (10, Duration::from_secs(60));
+const ADD_LIMIT: (usize, Duration) = (5, Duration::from_secs(10 * 60));
+// Search results handed to guests, so they can only add tracks they found without another lookup
+const KNOWN_TRACKS_LIMIT: usize = 500;
+// Guests only send a search or a video ID, anything bigger or slower is dropped
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Served at `/`; reads the token from its own link and talks to the JSON endpoints
+const GUEST_PAGE: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>YTAudioBar Guest Queue</title>
+<style>
+body { font-family: system-ui, sans-serif; margin: 0 auto; padding: 16px; max-width: 560px; background: #111; color: #eee; }
+form { display: flex; gap: 8px; }
+input { flex: 1; padding: 10px; border-radius: 8px; border: none; background: #222; color: #eee; }
+button { padding: 10px 14px; border-radius: 8px; border: none; background: #e33; color: #fff; }
+li { display: flex; justify-content: space-between; align-items: center; gap: 8px; padding: 8px 0; border-bottom: 1px solid #222; }
+ul { list-style: none; padding: 0; }
+small { color: #999; }
+</style>
+</head>
+<body>
+<h2>Add to the queue</h2>
+<form id="search"><input id="query" placeholder="Search YouTube"><button>Search</button></form>
+<p id="message"></p>
+<ul id="results"></ul>
+<script>
+const token = new URLSearchParams(location.search).get('token') || '';
+const message = document.getElementById('message');
+const results = document.getElementById('results');
+async function call(path, options) {
+    const response = await fetch(path, { ...options, headers: { 'Authorization': 'Bearer ' + token, 'Content-Type': 'application/json' } });
+    const body = await response.json();
+    if (!response.ok) throw new Error(body.error);
+    return body;
+}
+document.getElementById('search').onsubmit = async (event) => {
+    event.preventDefault();
+    message.textContent = 'Searching...';
+    results.replaceChildren();
+    try {
+        const tracks = await call('/api/search?q=' + encodeURIComponent(document.getElementById('query').value));
+        message.textContent = '';
+        for (const track of tracks) {
+            const item = document.createElement('li');
+            const label = document.createElement('span');
+            label.textContent = track.title + ' ';
+            const uploader = document.createElement('small');
+            uploader.textContent = track.uploader;
+            label.appendChild(uploader);
+            const add = document.createElement('button');
+            add.textContent = 'Add';
+            add.onclick = async () => {
+                try {
+                    const result = await call('/api/queue', { method: 'POST', body: JSON.stringify({ video_id: track.id }) });
+                    message.textContent = result.status === 'pending' ? 'Sent to the host for approval' : 'Added to the queue';
+                } catch (error) {
+                    message.textContent = error.message;
+                }
+            };
+            item.append(label, add);
+            results.appendChild(item);
+        }
+    } catch (error) {
+        message.textContent = error.message;
+    }
+};
+</script>
+</body>
+</html>
+"#;
+
+#[derive(Default)]
+struct GuestActivity {
+    searches: VecDeque<Instant>,
+    adds: VecDeque<Instant>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>, // names lowercased
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, serde_json::json!({ "error": message }))
+    }
+}
+
+/// LAN "guest queue": other devices search and add tracks through token-protected HTTP
+/// endpoints, but can't control playback
+pub struct GuestQueue {
+    ytdlp: Arc<YTDLPManager>,
+    queue: Arc<QueueManager>,
+    settings: Mutex<GuestQueueSettings>,
+    requests: Mutex<Vec<GuestRequest>>, // awaiting the host, oldest first
+    activity: Mutex<HashMap<IpAddr, GuestActivity>>,
+    known_tracks: Mutex<HashMap<String, YTVideoInfo>>,
+    server: Mutex<Option<JoinHandle<()>>>,
+    app_handle: Mutex<Option<AppHandle>>,
+}
+
+impl GuestQueue {
+    pub fn new(ytdlp: Arc<YTDLPManager>, queue: Arc<QueueManager>) -> Self {
+        Self {
+            ytdlp,
+            queue,
+            settings: Mutex::new(GuestQueueSettings::default()),
+            requests: Mutex::new(Vec::new()),
+            activity: Mutex::new(HashMap::new()),
+            known_tracks: Mutex::new(HashMap::new()),
+            server: Mutex::new(None),
+            app_handle: Mutex::new(None),
+        }
+    }
+
+    pub async fn set_app_handle(&self, handle: AppHandle) {
+        *self.app_handle.lock().await = Some(handle);
+    }
+
+    pub async fn get_settings(&self) -> GuestQueueSettings {
+        self.settings.lock().await.clone()
+    }
+
+    /// Apply new settings, (re)starting or stopping the server; returns them with the token filled in
+    pub async fn set_settings(self: &Arc<Self>, mut settings: GuestQueueSettings) -> Result<GuestQueueSettings, String> {
+        if settings.port < 1024 {
+            return Err("Pick a port from 1024 up".to_string());
+        }
+        if settings.token.is_empty() {
+            settings.token = new_token();
+        }
+
+        self.stop().await;
+        *self.settings.lock().await = settings.clone();
+        if settings.enabled {
+            self.start(settings.port).await?;
+        }
+        Ok(settings)
+    }
+
+    /// Replace the token, so guests with the old link lose access
+    pub async fn regenerate_token(self: &Arc<Self>) -> Result<GuestQueueSettings, String> {
+        let settings = GuestQueueSettings {
+            token: new_token(),
+            ..self.get_settings().await
+        };
+        self.set_settings(settings).await
+    }
+
+    pub async fn status(&self) -> GuestQueueStatus {
+        let settings = self.get_settings().await;
+        let running = self.server.lock().await.is_some();
+        let url = running
+            .then(local_ip)
+            .flatten()
+            .map(|ip| format!("http://{}/?token={}", SocketAddr::new(ip, settings.port), settings.token));
+        GuestQueueStatus { settings, running, url }
+    }
+
+    async fn start(self: &Arc<Self>, port: u16) -> Result<(), String> {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .await
+            .map_err(|e| format!("Failed to open port {}: {}", port, e))?;
+        println!("🎉 Guest queue listening on port {}", port);
+
+        let guests = Arc::clone(self);
+        let server = tokio::spawn(async move {
+            loop {
+                let Ok((stream, address)) = listener.accept().await else {
+                    // Out of file handles or similar, give it a moment rather than spinning
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                };
+                let guests = Arc::clone(&guests);
+                tokio::spawn(async move {
+                    let _ = tokio::time::timeout(REQUEST_TIMEOUT, guests.serve(stream, address.ip())).await;
+                });
+            }
+        });
+        *self.server.lock().await = Some(server);
+        Ok(())
+    }
+
+    async fn stop(&self) {
+        if let Some(server) = self.server.lock().await.take() {
+            server.abort();
+            println!("🎉 Guest queue stopped");
+        }
+    }
+
+    async fn serve(&self, mut stream: TcpStream, guest: IpAddr) {
+        let response = match read_request(&mut stream).await {
+            Some(request) => self.route(request, guest).await,
+            None => Response::error(400, "Bad request"),
+        };
+
+        let head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+            response.status,
+            reason(response.status),
+            response.content_type,
+            response.body.len()
+        );
+        let _ = stream.write_all(head.as_bytes()).await;
+        let _ = stream.write_all(response.body.as_bytes()).await;
+        let _ = stream.shutdown().await;
+    }
+
+    async fn route(&self, request: Request, guest: IpAddr) -> Response {
+        if request.method == "GET" && request.path == "/" {
+            return Response {
+                status: 200,
+                content_type: "text/html; charset=utf-8",
+                body: GUEST_PAGE.to_string(),
+            };
+        }
+
+        let token = request
+            .headers
+            .get("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or(request.query.get("token").map(String::as_str))
+            .unwrap_or_default();
+        if token.is_empty() || token != self.settings.lock().await.token {
+            return Response::error(401, "This link has expired, ask the host for a new one");
+        }
+
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/api/search") => self.search(request, guest).await,
+            ("POST", "/api/queue") => self.add(request, guest).await,
+            _ => Response::error(404, "Not found"),
+        }
+    }
+
+    async fn search(&self, request: Request, guest: IpAddr) -> Response {
+        let query = request.query.get("q").map(|q| q.trim()).unwrap_or_default();
+        if query.is_empty() {
+            return Response::error(400, "Type something to search for");
+        }
+        if !self.allow(guest, |activity| &mut activity.searches, SEARCH_LIMIT).await {
+            return Response::error(429, "Too many searches, wait a minute and try again");
+        }
+
+        match self.ytdlp.search(query.to_string(), true).await {
+            Ok(tracks) => {
+                let mut known_tracks = self.known_tracks.lock().await;
+                if known_tracks.len() + tracks.len() > KNOWN_TRACKS_LIMIT {
+                    known_tracks.clear();
+                }
+                for track in &tracks {
+                    known_tracks.insert(track.id.clone(), track.clone());
+                }
+                Response::json(200, serde_json::json!(tracks))
+            }
+            Err(e) => Response::error(502, &e),
+        }
+    }
+
+    async fn add(&self, request: Request, guest: IpAddr) -> Response {
+        let video_id = serde_json::from_slice::<serde_json::Value>(&request.body)
+            .ok()
+            .and_then(|body| body.get("video_id")?.as_str().map(str::to_string));
+        let Some(video_id) = video_id else {
+            return Response::error(400, "Missing video_id");
+        };
+        let Some(track) = self.known_tracks.lock().await.get(&video_id).cloned() else {
+            return Response::error(404, "Search for the track first");
+        };
+        if !self.allow(guest, |activity| &mut activity.adds, ADD_LIMIT).await {
+            return Response::error(429, "You've added a lot of tracks, give the others a turn");
+        }
+
+        if !self.settings.lock().await.require_approval {
+            println!("🎉 Guest {} added {}", guest, track.title);
+            self.queue.add_to_queue(track).await;
+            return Response::json(200, serde_json::json!({ "status": "queued" }));
+        }
+
+        self.requests.lock().await.push(GuestRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            guest: guest.to_string(),
+            track,
+            requested_at: chrono::Utc::now().timestamp(),
+        });
+        self.emit_requests().await;
+        Response::json(200, serde_json::json!({ "status": "pending" }))
+    }
+
+    // Record the guest's action if it's within the limit
+    async fn allow(
+        &self,
+        guest: IpAddr,
+        history: impl FnOnce(&mut GuestActivity) -> &mut VecDeque<Instant>,
+        (limit, window): (usize, Duration),
+    ) -> bool {
+        let mut activity = self.activity.lock().await;
+        let history = history(activity.entry(guest).or_default());
+        let now = Instant::now();
+        while history.front().is_some_and(|at| now.duration_since(*at) > window) {
+            history.pop_front();
+        }
+        if history.len() >= limit {
+            return false;
+        }
+        history.push_back(now);
+        true
+    }
+
+    pub async fn get_requests(&self) -> Vec<GuestRequest> {
+        self.requests.lock().await.clone()
+    }
+
+    /// Add a guest's track to the queue
+    pub async fn approve(&self, id: &str) -> Result<(), String> {
+        let request = self.take_request(id).await?;
+        println!("🎉 Approved {} from guest {}", request.track.title, request.guest);
+        self.queue.add_to_queue(request.track).await;
+        Ok(())
+    }
+
+    pub async fn reject(&self, id: &str) -> Result<(), String> {
+        self.take_request(id).await.map(|_| ())
+    }
+
+    async fn take_request(&self, id: &str) -> Result<GuestRequest, String> {
+        let request = {
+            let mut requests = self.requests.lock().await;
+            let index = requests
+                .iter()
+                .position(|request| request.id == id)
+                .ok_or_else(|| "That request was already handled".to_string())?;
+            requests.remove(index)
+        };
+        self.emit_requests().await;
+        Ok(request)
+    }
+
+    async fn emit_requests(&self) {
+        let requests = self.get_requests().await;
+        if let Some(handle) = self.app_handle.lock().await.as_ref() {
+            let _ = handle.emit("guest-requests-changed", requests);
+        }
+    }
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position;
+        }
+        if buffer.len() > MAX_REQUEST_BYTES {
+            return None;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = std::str::from_utf8(&buffer[..header_end]).ok()?;
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let content_length: usize = headers.get("content-length").and_then(|len| len.parse().ok()).unwrap_or(0);
+    if content_length > MAX_REQUEST_BYTES {
+        return None;
+    }
+    let mut body = buffer[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    let url = reqwest::Url::parse(&format!("http://guest{}", target)).ok()?;
+    Some(Request {
+        method,
+        path: url.path().to_string(),
+        query: url.query_pairs().into_owned().collect(),
+        headers,
+        body,
+    })
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        _ => "Bad Gateway",
+    }
+}
+
+fn new_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+// The address other devices on the network reach this one at; connecting a UDP socket sends nothing
+fn local_ip() -> Option<IpAddr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// A seek counts as skipping the intro when it leaves from early in the track, but not
+// right at the start, which is where restored sessions and bookmarks jump from
+const MIN_SEEK_FROM: f64 = 1.0;
+const MAX_SEEK_FROM: f64 = 15.0;
+
+// Intros this long are worth skipping; further jumps are looking for something else
+const MIN_INTRO: f64 = 5.0;
+const MAX_INTRO: f64 = 180.0;
+
+// Plays that have to skip to about the same spot before a skip is suggested
+const MIN_SKIPS: usize = 3;
+const SKIP_TOLERANCE: f64 = 5.0;
+
+// Skips remembered per track, and tracks remembered overall
+const SKIPS_KEPT: usize = 5;
+const TRACKS_KEPT: usize = 500;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntroSeeks {
+    targets: Vec<f64>, // where each play skipped to, oldest first
+    dismissed: bool,   // the user turned the suggestion down, so it isn't offered again
+    updated_at: i64,
+}
+
+/// Where plays of each track habitually skip to, persisted as the `intro_seeks` preference
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntroSkipLog(HashMap<String, IntroSeeks>);
+
+impl IntroSkipLog {
+    /// Note a seek made during a play; returns false when it doesn't look like an intro skip
+    pub fn record(&mut self, video_id: &str, from: f64, to: f64) -> bool {
+        if !(MIN_SEEK_FROM..=MAX_SEEK_FROM).contains(&from) || !(MIN_INTRO..=MAX_INTRO).contains(&to) || to <= from {
+            return false;
+        }
+
+        let seeks = self.0.entry(video_id.to_string()).or_default();
+        seeks.targets.push(to);
+        if seeks.targets.len() > SKIPS_KEPT {
+            seeks.targets.remove(0);
+        }
+        seeks.updated_at = chrono::Utc::now().timestamp();
+
+        if self.0.len() > TRACKS_KEPT {
+            if let Some(oldest) = self.0.iter().min_by_key(|(_, seeks)| seeks.updated_at).map(|(id, _)| id.clone()) {
+                self.0.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    /// Where to start the track, once enough plays skipped to about the same spot
+    pub fn suggestion(&self, video_id: &str) -> Option<f64> {
+        let seeks = self.0.get(video_id)?;
+        if seeks.dismissed || seeks.targets.len() < MIN_SKIPS {
+            return None;
+        }
+
+        let mut targets = seeks.targets.clone();
+        targets.sort_by(|a, b| a.total_cmp(b));
+        let median = targets[targets.len() / 2];
+        let agreeing = targets.iter().filter(|t| (*t - median).abs() <= SKIP_TOLERANCE).count();
+        // Start at the earliest agreeing skip, so none of what the user listens to gets cut
+        let start = targets.iter().copied().find(|t| (t - median).abs() <= SKIP_TOLERANCE)?;
+        (agreeing >= MIN_SKIPS).then_some(start.floor())
+    }
+
+    pub fn dismiss(&mut self, video_id: &str) {
+        let seeks = self.0.entry(video_id.to_string()).or_default();
+        seeks.dismissed = true;
+        seeks.updated_at = chrono::Utc::now().timestamp();
+    }
+
+    /// Forget a track once its skip became a trim
+    pub fn forget(&mut self, video_id: &str) {
+        self.0.remove(video_id);
+    }
+}
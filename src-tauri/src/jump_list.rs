@@ -0,0 +1,76 @@
+// Taskbar jump list entries relaunch the app with these; the single-instance plugin hands
+// them to the running instance
+pub const PLAY_TRACK_ARG: &str = "--play-track=";
+pub const PLAY_PLAYLIST_ARG: &str = "--play-playlist=";
+
+/// Replace the jump list with recent tracks and pinned playlists, each as (id, title);
+/// does nothing outside Windows
+#[cfg(not(windows))]
+pub fn update(_recent_tracks: &[(String, String)], _pinned_playlists: &[(String, String)]) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn update(recent_tracks: &[(String, String)], pinned_playlists: &[(String, String)]) -> Result<(), String> {
+    use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_APARTMENTTHREADED};
+
+    // Runs on a blocking pool thread, which may or may not have COM set up already
+    let initialized = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) }.is_ok();
+    let result = build(recent_tracks, pinned_playlists).map_err(|e| format!("Failed to update the jump list: {}", e));
+    if initialized {
+        unsafe { CoUninitialize() };
+    }
+    result
+}
+
+#[cfg(windows)]
+fn build(recent_tracks: &[(String, String)], pinned_playlists: &[(String, String)]) -> windows::core::Result<()> {
+    use windows::core::HSTRING;
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+    use windows::Win32::UI::Shell::Common::IObjectArray;
+    use windows::Win32::UI::Shell::{DestinationList, ICustomDestinationList};
+
+    unsafe {
+        let list: ICustomDestinationList = CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+        let mut max_slots = 0u32;
+        let _removed: IObjectArray = list.BeginList(&mut max_slots)?;
+        for (category, entries, arg) in [
+            ("Pinned Playlists", pinned_playlists, PLAY_PLAYLIST_ARG),
+            ("Recent Tracks", recent_tracks, PLAY_TRACK_ARG),
+        ] {
+            if entries.is_empty() {
+                continue;
+            }
+            let links = links(entries, arg)?;
+            list.AppendCategory(&HSTRING::from(category), &links)?;
+        }
+        list.CommitList()
+    }
+}
+
+// Shortcuts back to this executable, one per entry
+#[cfg(windows)]
+unsafe fn links(entries: &[(String, String)], arg: &str) -> windows::core::Result<windows::Win32::UI::Shell::Common::IObjectArray> {
+    use windows::core::{Interface, HSTRING, PROPVARIANT};
+    use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+    use windows::Win32::UI::Shell::Common::IObjectCollection;
+    use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+    use windows::Win32::UI::Shell::{EnumerableObjectCollection, IShellLinkW, ShellLink};
+
+    let exe = std::env::current_exe().map_err(|_| windows::core::Error::from_win32())?;
+    let exe = HSTRING::from(exe.as_os_str());
+    let collection: IObjectCollection = CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+    for (id, title) in entries {
+        let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+        link.SetPath(&exe)?;
+        link.SetArguments(&HSTRING::from(format!("{}{}", arg, id)))?;
+        link.SetIconLocation(&exe, 0)?;
+        link.SetDescription(&HSTRING::from(title.as_str()))?;
+        let properties: IPropertyStore = link.cast()?;
+        properties.SetValue(&PKEY_Title, &PROPVARIANT::from(title.as_str()))?;
+        properties.Commit()?;
+        collection.AddObject(&link)?;
+    }
+    collection.cast()
+}
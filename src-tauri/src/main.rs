@@ -2,28 +2,190 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod models;
+mod notifications;
+mod now_playing;
+mod onboarding;
+mod profile;
+mod app_data;
+mod app_updater;
+mod artwork;
+mod captions;
+mod content_classifier;
+mod daily_mix;
+mod fingerprint;
+mod clipboard;
+mod connectivity;
 mod database;
+mod diagnostics;
+mod duration_fixer;
 mod ytdlp_manager;
 mod ytdlp_installer;
+mod audio_capabilities;
+mod audio_effects;
 mod audio_manager;
+mod audio_stream;
+mod bandwidth_governor;
 mod queue_manager;
 mod download_manager;
+mod guest_queue;
+mod intro_skip;
+mod jump_list;
+mod party_mode;
+mod player_action;
+mod rate_limiter;
+mod recent_log;
+mod settings_sync;
+mod storage_monitor;
+mod stream_cache;
+mod system_theme;
+mod telemetry;
 mod media_key_manager;
+mod metadata_prefetch;
+#[cfg(target_os = "linux")]
+mod mpris;
+mod undo_manager;
+mod window_placement;
+mod timer_manager;
+mod tray_manager;
+mod tts;
+mod weekly_recap;
 
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use tauri::{
     Manager, State, WindowEvent, tray::{TrayIconBuilder, TrayIconEvent, MouseButton, MouseButtonState},
     menu::{Menu, MenuItem}
 };
 
+use crate::app_data::AppData;
+use crate::app_updater::AppUpdater;
+use crate::artwork::ArtworkManager;
+use crate::bandwidth_governor::BandwidthGovernor;
 use crate::database::DatabaseManager;
-use crate::models::{AudioState, Playlist, RepeatMode, Track, YTVideoInfo};
-use crate::ytdlp_manager::YTDLPManager;
+use crate::models::{AppUpdate, AudioEffects, AudioFingerprint, AudioProfile, AudioState, Bookmark, ChannelRule, ContentType, CooldownStatus, CookieSource, DataSaverMode, DataScope, DataUsageBreakdown, EndOfQueueAction, FingerprintStatus, GuestQueueSettings, GuestQueueStatus, GuestRequest, IntroSkipSuggestion, JingleSettings, LibraryImport, LimiterSettings, ListFormat, MaintenanceReport, NotificationPolicy, NowPlayingOutput, OnboardingStatus, OnboardingStep, PartyModeStatus, PlaybackError, PlayerEvent, PlayerEventKind, Playlist, PlaylistSummary, PrefetchPriority, ProfileImport, QueueMatch, QueueSession, QueueSessionSummary, QuietHours, RecordingDuplicate, RepeatMode, SavedSession, ScheduledTimer, SessionRestoreMode, ShuffleScope, SkippedTrack, SpeedSettings, StorageProblem, StreamedAudio, SystemTheme, TelemetryEvent, TelemetryStatus, TimerKind, Track, TrackChangeReason, TrackChanged, TrackSkipStats, TrackTrim, VolumeKeySettings, WeeklyRecap, YTVideoInfo, YouTubeLibrary, YtdlpOptions, YtdlpRelease, YtdlpVersions};
+use crate::ytdlp_manager::{extract_video_id, parse_extra_args, YTDLPManager};
 use crate::ytdlp_installer::YTDLPInstaller;
-use crate::audio_manager::AudioManager;
+use crate::audio_manager::{AudioManager, StreamedAudioClaim};
+use crate::connectivity::ConnectivityMonitor;
 use crate::queue_manager::QueueManager;
-use crate::download_manager::DownloadManager;
+use crate::download_manager::{DownloadManager, DownloadPriority};
+use crate::guest_queue::GuestQueue;
+use crate::intro_skip::IntroSkipLog;
+use crate::party_mode::{PartyLock, PartyMode};
+use crate::player_action::PlayerAction;
+use crate::rate_limiter::RateLimiter;
+use crate::settings_sync::SettingsSync;
+use crate::storage_monitor::StorageMonitor;
+use crate::telemetry::TelemetryManager;
 use crate::media_key_manager::MediaKeyManager;
+use crate::metadata_prefetch::MetadataPrefetcher;
+use crate::duration_fixer::{DurationFixer, FAILED_RETRY_AFTER};
+use crate::timer_manager::TimerManager;
+use crate::undo_manager::{UndoAction, UndoManager};
+use crate::tray_manager::{is_tray_available, PlaybackIndicator, TrayManager};
+
+// How many times a failing track is retried before the queue skips past it
+const MAX_PLAYBACK_RETRIES: u32 = 1;
+// Pause before retrying, so a network blip has a moment to pass
+const PLAYBACK_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Lazy shuffle materializes this many tracks at a time, refilling when fewer remain queued
+const SHUFFLE_CHUNK_SIZE: usize = 25;
+const SHUFFLE_REFILL_THRESHOLD: usize = 5;
+
+// Artist radio adds up to this many tracks whenever fewer than the threshold remain queued
+const RADIO_BATCH_SIZE: usize = 15;
+const RADIO_REFILL_THRESHOLD: usize = 3;
+
+// Pasted URL lists are looked up this many links at a time, each link costs a yt-dlp call
+const URL_LIST_CHUNK_SIZE: usize = 3;
+const URL_LIST_REFILL_THRESHOLD: usize = 2;
+
+// Tracks the user moves on from before this share of their duration count as skipped
+const SKIP_THRESHOLD: f64 = 0.3;
+
+// Share of a track that has to play before it goes into the month's listening log
+const LISTENING_LOG_THRESHOLD: f64 = 0.8;
+// How many tracks `get_most_skipped` lists when no limit is given
+const MOST_SKIPPED_LIMIT: i64 = 20;
+// Recently played tracks listed in the Windows jump list
+const JUMP_LIST_RECENT_LIMIT: i64 = 8;
+// Saved tracks without a duration looked up at launch, newest first
+const DURATION_FIX_STARTUP_LIMIT: i64 = 200;
+// Search results queued by a "play artist" action
+const ARTIST_ACTION_TRACKS: usize = 20;
+
+// Global shortcut for playing whatever is on the clipboard
+const PASTE_AND_PLAY_SHORTCUT: &str = "CommandOrControl+Alt+V";
+
+// How often libraries enabled for sync are re-imported to pick up newly liked tracks
+const LIBRARY_SYNC_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+// How far `replay` rewinds when no amount is given
+const DEFAULT_REPLAY_SECONDS: f64 = 10.0;
+
+// How long the final track fades out before the app quits at the end of the queue
+const END_OF_QUEUE_FADE: std::time::Duration = std::time::Duration::from_secs(8);
+
+// How often YouTube's reachability is checked
+const CONNECTIVITY_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+// Shortest a trimmed track can be, in seconds
+const MIN_TRIMMED_LENGTH: f64 = 1.0;
+
+// Upper bound for `set_repeat_count`, enough for any practice session
+const MAX_REPEAT_COUNT: u32 = 99;
+
+// How often the OS high contrast setting is re-read; dark mode changes come as window events
+const HIGH_CONTRAST_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// How often the downloads folder and database directory are probed for a full or read-only disk
+const STORAGE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// Automatic update checks run shortly after launch and then this often
+const APP_UPDATE_CHECK_DELAY: std::time::Duration = std::time::Duration::from_secs(3);
+const APP_UPDATE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(12 * 60 * 60);
+
+// Opted-in usage counts are sent at most this often
+const TELEMETRY_UPLOAD_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+// How often usage counts are written to disk, instead of on every search or download
+const TELEMETRY_SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// How often the OS is asked whether the connection is metered
+const METERED_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// Volume change per tray volume step
+const TRAY_VOLUME_STEP: f32 = 0.05;
+
+// Sleep and stop-at timers fade out over this long, reaching silence at the set time
+const TIMER_FADE_SECONDS: f64 = 10.0;
+const TIMER_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+// How often orphaned download metadata and partial files are cleaned up, starting at launch
+const DOWNLOAD_CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+// Daily mixes are rebuilt once this old; the age is checked hourly so sleep doesn't skip a refresh
+const DAILY_MIX_REFRESH: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+const DAILY_MIX_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+// How often to check whether last week's recap still needs writing
+const WEEKLY_RECAP_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+// Recaps kept for the "Your Week" screen
+const WEEKLY_RECAPS_SHOWN: i64 = 12;
+
+// The database is checked and vacuumed once this long after the last run, checked daily
+const DB_MAINTENANCE_PERIOD: std::time::Duration = std::time::Duration::from_secs(30 * 24 * 60 * 60);
+const DB_MAINTENANCE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+// How often the queue is saved while playing, for `restore_session`
+const SESSION_SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+// How often quiet hours are checked against the clock
+const QUIET_HOURS_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+// Colors extracted per thumbnail for the player's accent theming
+const PALETTE_SIZE: usize = 5;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -33,6 +195,28 @@ pub struct AppState {
     ytdlp: Arc<YTDLPManager>,
     downloads: Arc<DownloadManager>,
     media_keys: Arc<MediaKeyManager>,
+    undo: Arc<UndoManager>,
+    tray: Arc<TrayManager>,
+    connectivity: Arc<ConnectivityMonitor>,
+    storage: Arc<StorageMonitor>,
+    bandwidth: Arc<BandwidthGovernor>,
+    artwork: Arc<ArtworkManager>,
+    prefetcher: Arc<MetadataPrefetcher>,
+    durations: Arc<DurationFixer>,
+    timers: Arc<TimerManager>,
+    updater: Arc<AppUpdater>,
+    telemetry: Arc<TelemetryManager>,
+    rate_limiter: Arc<RateLimiter>,
+    party: Arc<PartyMode>,
+    guests: Arc<GuestQueue>,
+    settings: Arc<SettingsSync>,
+}
+
+// Saves a setting and lets every window know through `settings-changed` if its value changed
+async fn save_setting<T: serde::Serialize>(state: &AppState, key: &str, value: &T) -> Result<(), String> {
+    state.db.set_preference(key, value).await.map_err(|e| e.to_string())?;
+    state.settings.record(key, value).await;
+    Ok(())
 }
 
 #[tauri::command]
@@ -41,7 +225,16 @@ async fn search_youtube(
     music_mode: bool,
     state: State<'_, AppState>,
 ) -> Result<Vec<YTVideoInfo>, String> {
-    state.ytdlp.search(query, music_mode).await
+    state.telemetry.record(TelemetryEvent::Search, 1);
+    let mut results = state.ytdlp.search(query, music_mode).await?;
+    state.durations.fix(&mut results).await;
+    Ok(results)
+}
+
+/// Whether YouTube requests are paused after being throttled, and until when
+#[tauri::command]
+async fn get_youtube_cooldown(state: State<'_, AppState>) -> Result<CooldownStatus, String> {
+    Ok(state.rate_limiter.status().await)
 }
 
 #[tauri::command]
@@ -49,337 +242,3089 @@ async fn check_ytdlp_installed() -> Result<bool, String> {
     Ok(YTDLPInstaller::is_installed().await)
 }
 
-#[tauri::command]
-async fn install_ytdlp() -> Result<(), String> {
-    YTDLPInstaller::install().await
+// The yt-dlp channel and pinned version chosen in settings
+async fn saved_ytdlp_release(state: &AppState) -> Result<YtdlpRelease, String> {
+    let release = state.db.get_preference("ytdlp_release").await.map_err(|e| e.to_string())?;
+    Ok(release.unwrap_or_default())
 }
 
 #[tauri::command]
-async fn get_ytdlp_version() -> Result<String, String> {
-    YTDLPInstaller::get_version().await
+async fn install_ytdlp(state: State<'_, AppState>) -> Result<(), String> {
+    let release = saved_ytdlp_release(&state).await?;
+    YTDLPInstaller::install(&release).await
 }
 
-// Audio playback commands
+/// The chosen channel and pin, with the installed version and the one a rollback would restore
 #[tauri::command]
-async fn play_track(track: YTVideoInfo, state: State<'_, AppState>) -> Result<(), String> {
-    // Check if track is downloaded and use local file if available
-    if let Some(file_path) = state.downloads.get_downloaded_file_path(&track.id).await {
-        println!("🎵 Playing from local file: {}", file_path);
-        state.audio.play_from_file(track, file_path).await
-    } else {
-        // Play track directly WITHOUT adding to queue
-        // Queue is only populated via "Play All" playlist action
-        state.audio.play(track).await
-    }
+async fn get_ytdlp_versions(state: State<'_, AppState>) -> Result<YtdlpVersions, String> {
+    Ok(YtdlpVersions {
+        release: saved_ytdlp_release(&state).await?,
+        installed: YTDLPInstaller::get_version().await.ok(),
+        previous: YTDLPInstaller::get_previous_version().await.ok(),
+    })
 }
 
+/// Switch channel or pin a version, installing it right away; the replaced build is kept for rollback
 #[tauri::command]
-async fn toggle_play_pause(state: State<'_, AppState>) -> Result<(), String> {
-    state.audio.toggle_play_pause().await
+async fn set_ytdlp_release(release: YtdlpRelease, state: State<'_, AppState>) -> Result<YtdlpVersions, String> {
+    let version = release.version.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    // Release tags are dates, e.g. "2024.08.06" or "2024.08.07.232724" for nightly builds
+    if version.as_ref().is_some_and(|v| !v.chars().all(|c| c.is_ascii_digit() || c == '.')) {
+        return Err("Versions look like 2024.08.06".to_string());
+    }
+    let release = YtdlpRelease { version, ..release };
+
+    YTDLPInstaller::install(&release).await?;
+    save_setting(&state, "ytdlp_release", &release).await?;
+    get_ytdlp_versions(state).await
 }
 
+/// Go back to the yt-dlp build the last install replaced
 #[tauri::command]
-async fn pause_playback(state: State<'_, AppState>) -> Result<(), String> {
-    state.audio.pause().await
+async fn rollback_ytdlp(state: State<'_, AppState>) -> Result<YtdlpVersions, String> {
+    YTDLPInstaller::rollback().await?;
+    get_ytdlp_versions(state).await
 }
 
+/// Where first-run setup stands, so the frontend can resume the setup wizard at the right step
 #[tauri::command]
-async fn stop_playback(state: State<'_, AppState>) -> Result<(), String> {
-    state.audio.stop().await
+async fn get_onboarding_status(state: State<'_, AppState>) -> Result<OnboardingStatus, String> {
+    let capabilities = state.audio.get_capabilities().await;
+    let downloads_dir = state.downloads.get_downloads_dir().await;
+    onboarding::get_status(&state.db, &capabilities, downloads_dir).await
 }
 
+/// Carry out or confirm one setup step, returning the updated status
 #[tauri::command]
-async fn seek_to(position: f64, state: State<'_, AppState>) -> Result<(), String> {
-    state.audio.seek(position).await
+async fn complete_onboarding_step(step: OnboardingStep, state: State<'_, AppState>) -> Result<OnboardingStatus, String> {
+    let downloads_dir = state.downloads.get_downloads_dir().await;
+    onboarding::complete_step(&state.db, step, downloads_dir.clone()).await?;
+    let capabilities = state.audio.get_capabilities().await;
+    onboarding::get_status(&state.db, &capabilities, downloads_dir).await
 }
 
 #[tauri::command]
-async fn set_volume(volume: f32, state: State<'_, AppState>) -> Result<(), String> {
-    state.audio.set_volume(volume).await
+async fn get_ytdlp_version() -> Result<String, String> {
+    YTDLPInstaller::get_version().await
 }
 
-#[tauri::command]
-async fn set_playback_speed(rate: f32, state: State<'_, AppState>) -> Result<(), String> {
-    state.audio.set_playback_rate(rate).await
+// Plays a track from its downloaded file when available, streaming otherwise
+async fn play_resolved(state: &AppState, track: YTVideoInfo, reason: TrackChangeReason) -> Result<(), String> {
+    play_resolved_at(state, track, reason, None).await
 }
 
-#[tauri::command]
-async fn play_next(state: State<'_, AppState>) -> Result<Option<YTVideoInfo>, String> {
-    if let Some(track) = state.queue.play_next().await {
-        state.audio.play(track.clone()).await?;
-        Ok(Some(track))
+// `play_resolved` starting at `start_at` seconds, which a seek right after playing can't do reliably
+async fn play_resolved_at(
+    state: &AppState,
+    mut track: YTVideoInfo,
+    reason: TrackChangeReason,
+    start_at: Option<f64>,
+) -> Result<(), String> {
+    // Tracks coming back from the frontend may have lost their saved classification
+    if track.content_type.is_none() {
+        if let Ok(Some(saved)) = state.db.get_track(&track.id).await {
+            track.content_type = saved.content_type;
+        }
+    }
+
+    let previous = state.audio.get_state().await.current_track;
+    state.queue.record_played(&track.id).await;
+    if let Err(e) = state.db.record_play(&Track::from(track.clone())).await {
+        eprintln!("⚠️ Failed to record play: {}", e);
+    }
+    let jump_list_state = state.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = refresh_jump_list(&jump_list_state).await {
+            eprintln!("⚠️ {}", e);
+        }
+    });
+
+    let rule_track = track.clone();
+    let played = if let Some(file_path) = state.downloads.get_downloaded_file_path(&track.id).await {
+        println!("🎵 Playing from local file: {}", file_path);
+        state.audio.play_from_file(track, file_path, start_at).await
+    } else if !state.connectivity.is_online() {
+        Err("No network connection, only downloaded tracks can be played".to_string())
     } else {
-        Ok(None)
+        // Custom stream arguments may change how audio is fetched, so those plays always go through yt-dlp
+        let direct = state.ytdlp.get_options().await.stream_args.is_none();
+        let format = audio_manager::stream_format(state.audio.get_state().await.data_saver);
+        let video_id = track.id.clone();
+        let ytdlp = Arc::clone(&state.ytdlp);
+        let stream_url = async move {
+            if !direct {
+                return None;
+            }
+            ytdlp
+                .get_stream_url(&video_id, format)
+                .await
+                .map_err(|e| eprintln!("⚠️ Failed to resolve stream URL, streaming through yt-dlp: {}", e))
+                .ok()
+        };
+        state.audio.play(track, stream_url, start_at).await
+    };
+
+    if played.is_ok() {
+        state
+            .audio
+            .announce_track_change(TrackChanged { track: rule_track.clone(), previous, reason })
+            .await;
+        // Rules apply once playback has started, so their speed wins over the per-content default
+        let rule_state = state.clone();
+        tauri::async_runtime::spawn(async move {
+            apply_channel_rule(&rule_state, &rule_track, true).await;
+        });
     }
+    played
 }
 
-#[tauri::command]
-async fn play_previous(state: State<'_, AppState>) -> Result<Option<YTVideoInfo>, String> {
-    if let Some(track) = state.queue.play_previous().await {
-        state.audio.play(track.clone()).await?;
-        Ok(Some(track))
-    } else {
-        Ok(None)
+/// Apply the uploader's saved defaults to a track being played, or queued when `playing` is
+/// false. Speed and downloads only apply to plays, the playlist to both.
+async fn apply_channel_rule(state: &AppState, track: &YTVideoInfo, playing: bool) {
+    let rule = match state.db.get_channel_rule(&track.uploader).await {
+        Ok(Some(rule)) => rule,
+        Ok(None) => return,
+        Err(e) => {
+            eprintln!("⚠️ Failed to load channel rule: {}", e);
+            return;
+        }
+    };
+
+    if let Some(playlist_id) = &rule.playlist_id {
+        let already_added = state
+            .db
+            .get_track_playlist_ids(&track.id)
+            .await
+            .is_ok_and(|ids| ids.contains(playlist_id));
+        if !already_added {
+            if let Err(e) = state.db.save_track_to_playlist(&Track::from(track.clone()), playlist_id).await {
+                eprintln!("⚠️ Failed to add track to the channel rule's playlist: {}", e);
+            }
+        }
+    }
+
+    if !playing {
+        return;
+    }
+
+    if let Some(rate) = rule.playback_rate {
+        if let Err(e) = state.audio.set_playback_rate(rate).await {
+            eprintln!("⚠️ Failed to apply channel rule speed: {}", e);
+        }
+    }
+
+    // Data saver holds back automatic downloads, the same as duration lookups
+    if rule.auto_download
+        && state.connectivity.is_online()
+        && !state.audio.get_state().await.data_saver
+        && !state.downloads.is_downloaded(&track.id).await
+    {
+        state.telemetry.record(TelemetryEvent::Download, 1);
+        // Fails harmlessly when the track is already downloading
+        if let Err(e) = state.downloads.download_track(track.clone(), None).await {
+            println!("📥 Channel rule download skipped: {}", e);
+        }
     }
 }
 
+// Audio playback commands
 #[tauri::command]
-async fn get_audio_state(state: State<'_, AppState>) -> Result<AudioState, String> {
-    Ok(state.audio.get_state().await)
+async fn play_track(track: YTVideoInfo, state: State<'_, AppState>) -> Result<(), String> {
+    // Play track directly WITHOUT adding to queue
+    // Queue is only populated via "Play All" playlist action
+    play_resolved(&state, track, TrackChangeReason::User).await
 }
 
-// Queue commands
+/// Buffer the start of a track the user is hovering or has selected, so playing it starts
+/// almost at once. Tracks that play from a file or through custom stream arguments are skipped,
+/// and nothing is warmed while data saver is on.
 #[tauri::command]
-async fn add_to_queue(track: YTVideoInfo, state: State<'_, AppState>) -> Result<(), String> {
-    state.queue.add_to_queue(track).await;
-    Ok(())
+async fn warm_track(video_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    if !state.connectivity.is_online() || state.ytdlp.get_options().await.stream_args.is_some() {
+        return Ok(());
+    }
+    if state.audio.get_state().await.data_saver {
+        return Ok(());
+    }
+    if state.downloads.get_downloaded_file_path(&video_id).await.is_some() {
+        return Ok(());
+    }
+
+    let format = audio_manager::stream_format(false);
+    let ytdlp = Arc::clone(&state.ytdlp);
+    let id = video_id.clone();
+    state
+        .audio
+        .warm(video_id, async move {
+            ytdlp
+                .get_stream_url(&id, format)
+                .await
+                .map_err(|e| eprintln!("⚠️ Failed to resolve stream URL for warming: {}", e))
+                .ok()
+        })
+        .await
 }
 
-#[tauri::command]
-async fn get_queue(state: State<'_, AppState>) -> Result<Vec<YTVideoInfo>, String> {
-    Ok(state.queue.get_queue().await)
+// Plays a YouTube URL from the clipboard, or the top search result for any other text
+async fn play_clipboard(state: &AppState) -> Result<YTVideoInfo, String> {
+    let text = clipboard::read_text().await?;
+    if text.is_empty() {
+        return Err("Clipboard is empty".to_string());
+    }
+
+    let track = match extract_video_id(&text) {
+        Some(video_id) => state.ytdlp.get_video_info(&video_id).await?,
+        None => {
+            println!("🔍 Searching clipboard text: {}", text);
+            state
+                .ytdlp
+                .search(text, true)
+                .await?
+                .into_iter()
+                .next()
+                .ok_or("No results for clipboard text")?
+        }
+    };
+
+    play_resolved(state, track.clone(), TrackChangeReason::User).await?;
+    Ok(track)
 }
 
 #[tauri::command]
-async fn clear_queue(state: State<'_, AppState>) -> Result<(), String> {
-    state.queue.clear_queue().await;
-    Ok(())
+async fn play_from_clipboard(state: State<'_, AppState>) -> Result<YTVideoInfo, String> {
+    play_clipboard(&state).await
 }
 
-#[tauri::command]
-async fn toggle_shuffle(state: State<'_, AppState>) -> Result<bool, String> {
-    Ok(state.queue.toggle_shuffle().await)
+// Numbered list of tracks with their YouTube links, for sharing setlists
+fn format_track_list(tracks: &[YTVideoInfo], format: ListFormat) -> String {
+    tracks
+        .iter()
+        .enumerate()
+        .map(|(i, track)| {
+            let url = format!("https://www.youtube.com/watch?v={}", track.id);
+            match format {
+                ListFormat::Text => format!("{}. {} - {}\n   {}", i + 1, track.display_title(), track.display_uploader(), url),
+                ListFormat::Markdown => format!("{}. [{}]({}) - {}", i + 1, track.display_title().replace(']', "\\]"), url, track.display_uploader()),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
+/// Copies the queue, or a playlist when one is given, as a track list and returns the copied text
 #[tauri::command]
-async fn cycle_repeat_mode(state: State<'_, AppState>) -> Result<RepeatMode, String> {
-    Ok(state.queue.cycle_repeat_mode().await)
+async fn copy_queue_to_clipboard(
+    format: ListFormat,
+    playlist_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let tracks = match playlist_id {
+        Some(playlist_id) => state
+            .db
+            .get_playlist_tracks(&playlist_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(YTVideoInfo::from)
+            .collect(),
+        None => state.queue.get_queue().await,
+    };
+
+    if tracks.is_empty() {
+        return Err("Nothing to copy".to_string());
+    }
+
+    let text = format_track_list(&tracks, format);
+    clipboard::write_text(&text).await?;
+    Ok(text)
 }
 
 #[tauri::command]
-async fn get_queue_info(state: State<'_, AppState>) -> Result<String, String> {
-    Ok(state.queue.get_queue_info().await)
+async fn copy_current_track_url(state: State<'_, AppState>) -> Result<String, String> {
+    let track = state.audio.get_state().await.current_track.ok_or("Nothing is playing")?;
+    let url = format!("https://www.youtube.com/watch?v={}", track.id);
+    clipboard::write_text(&url).await?;
+    Ok(url)
 }
 
+/// youtu.be link to the playing track, optionally starting at the current position
 #[tauri::command]
-async fn reorder_queue(new_queue: Vec<YTVideoInfo>, state: State<'_, AppState>) -> Result<(), String> {
-    state.queue.reorder_queue(new_queue).await
-}
+async fn get_share_link(with_timestamp: bool, state: State<'_, AppState>) -> Result<String, String> {
+    let audio_state = state.audio.get_state().await;
+    let track = audio_state.current_track.ok_or("Nothing is playing")?;
 
-// ===== PLAYLIST COMMANDS =====
+    let seconds = audio_state.current_position.floor() as i64;
+    if with_timestamp && seconds > 0 {
+        Ok(format!("https://youtu.be/{}?t={}", track.id, seconds))
+    } else {
+        Ok(format!("https://youtu.be/{}", track.id))
+    }
+}
 
 #[tauri::command]
-async fn get_all_playlists(state: State<'_, AppState>) -> Result<Vec<Playlist>, String> {
-    state
-        .db
-        .get_all_playlists()
-        .await
-        .map_err(|e| e.to_string())
+async fn toggle_play_pause(state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.toggle_play_pause().await
 }
 
 #[tauri::command]
-async fn create_playlist(name: String, state: State<'_, AppState>) -> Result<String, String> {
-    state.db.create_playlist(&name).await.map_err(|e| e.to_string())
+async fn pause_playback(state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.pause().await
 }
 
 #[tauri::command]
-async fn delete_playlist(id: String, state: State<'_, AppState>) -> Result<(), String> {
-    state.db.delete_playlist(&id).await.map_err(|e| e.to_string())
+async fn stop_playback(state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Stopping playback").await?;
+    state.audio.stop().await
 }
 
+/// Stop and forget the current track, freeing its audio
 #[tauri::command]
-async fn get_playlist_tracks(playlist_id: String, state: State<'_, AppState>) -> Result<Vec<Track>, String> {
-    state
-        .db
-        .get_playlist_tracks(&playlist_id)
-        .await
-        .map_err(|e| e.to_string())
+async fn unload_playback(state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Stopping playback").await?;
+    state.audio.unload().await
 }
 
 #[tauri::command]
-async fn add_track_to_playlist(
-    track: YTVideoInfo,
-    playlist_id: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    // First save the track to database
-    let db_track = Track {
-        id: track.id.clone(),
-        title: track.title,
-        author: Some(track.uploader),
-        duration: track.duration,
-        thumbnail_url: track.thumbnail_url,
-        added_date: chrono::Utc::now().timestamp(),
-        file_path: None,
-    };
-
-    state.db.save_track(&db_track).await.map_err(|e| e.to_string())?;
-
-    // Then add to playlist
-    state
-        .db
-        .add_track_to_playlist(&track.id, &playlist_id)
-        .await
-        .map_err(|e| e.to_string())
+async fn seek_to(position: f64, state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.seek(position).await
 }
 
 #[tauri::command]
-async fn remove_track_from_playlist(
-    track_id: String,
-    playlist_id: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    state
-        .db
-        .remove_track_from_playlist(&track_id, &playlist_id)
-        .await
-        .map_err(|e| e.to_string())
+async fn replay(seconds: Option<f64>, state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.replay(seconds.unwrap_or(DEFAULT_REPLAY_SECONDS)).await
 }
 
 #[tauri::command]
-async fn add_to_favorites(track: YTVideoInfo, state: State<'_, AppState>) -> Result<(), String> {
-    // Save track first
-    let db_track = Track {
-        id: track.id.clone(),
-        title: track.title,
-        author: Some(track.uploader),
-        duration: track.duration,
-        thumbnail_url: track.thumbnail_url,
-        added_date: chrono::Utc::now().timestamp(),
-        file_path: None,
-    };
-
-    state.db.save_track(&db_track).await.map_err(|e| e.to_string())?;
-
-    // Add to favorites
-    state
-        .db
-        .add_to_favorites(&track.id)
-        .await
-        .map_err(|e| e.to_string())
+async fn set_volume(volume: f32, state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.set_volume(volume).await?;
+    persist_volume(&state).await
 }
 
+/// Lock out stop, clear-queue and delete operations, and hold the volume near where it is,
+/// until `disable_party_mode` is given the same PIN
 #[tauri::command]
-async fn remove_from_favorites(track_id: String, state: State<'_, AppState>) -> Result<(), String> {
-    state
-        .db
-        .remove_from_favorites(&track_id)
-        .await
-        .map_err(|e| e.to_string())
+async fn enable_party_mode(pin: String, app: tauri::AppHandle, state: State<'_, AppState>) -> Result<PartyModeStatus, String> {
+    let volume = state.audio.get_state().await.volume;
+    let lock = state.party.enable(&pin, volume).await?;
+    save_setting(&state, "party_mode", &Some(lock)).await?;
+    apply_party_mode(&app, &state).await
 }
 
 #[tauri::command]
-async fn play_playlist(playlist_id: String, state: State<'_, AppState>) -> Result<(), String> {
-    // Get all tracks from playlist
-    let tracks = state
-        .db
-        .get_playlist_tracks(&playlist_id)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if tracks.is_empty() {
-        return Err("Playlist is empty".to_string());
-    }
+async fn disable_party_mode(pin: String, app: tauri::AppHandle, state: State<'_, AppState>) -> Result<PartyModeStatus, String> {
+    state.party.disable(&pin).await?;
+    save_setting(&state, "party_mode", &None::<PartyLock>).await?;
+    apply_party_mode(&app, &state).await
+}
 
-    // Convert to YTVideoInfo
-    let video_tracks: Vec<YTVideoInfo> = tracks
-        .into_iter()
-        .map(|t| YTVideoInfo {
-            id: t.id,
-            title: t.title,
-            uploader: t.author.unwrap_or_else(|| "Unknown".to_string()),
-            duration: t.duration,
-            thumbnail_url: t.thumbnail_url,
-            audio_url: None,
-            description: None,
-        })
-        .collect();
+#[tauri::command]
+async fn get_party_mode(state: State<'_, AppState>) -> Result<PartyModeStatus, String> {
+    Ok(state.party.status().await)
+}
 
-    // Clear queue and add all playlist tracks
-    state.queue.clear_queue().await;
-    state.queue.add_to_queue_batch(video_tracks.clone()).await;
+async fn apply_party_mode(app: &tauri::AppHandle, state: &AppState) -> Result<PartyModeStatus, String> {
+    use tauri::Emitter;
 
-    // Set current index to first track
-    state.queue.set_current_index(0).await;
+    state.audio.set_volume_range(state.party.volume_range().await).await?;
+    let status = state.party.status().await;
+    let _ = app.emit("party-mode-changed", &status);
+    Ok(status)
+}
 
-    // Play first track
-    if let Some(first_track) = video_tracks.first() {
-        state.audio.play(first_track.clone()).await?;
-    }
+#[tauri::command]
+async fn get_guest_queue(state: State<'_, AppState>) -> Result<GuestQueueStatus, String> {
+    Ok(state.guests.status().await)
+}
 
-    Ok(())
+/// Turn the LAN guest queue on or off; other devices open the returned link to search and add tracks
+#[tauri::command]
+async fn set_guest_queue(settings: GuestQueueSettings, state: State<'_, AppState>) -> Result<GuestQueueStatus, String> {
+    let settings = state.guests.set_settings(settings).await?;
+    save_setting(&state, "guest_queue", &settings).await?;
+    Ok(state.guests.status().await)
 }
 
-// ===== DOWNLOAD COMMANDS =====
+/// New link for guests; the old one stops working
+#[tauri::command]
+async fn regenerate_guest_token(state: State<'_, AppState>) -> Result<GuestQueueStatus, String> {
+    let settings = state.guests.regenerate_token().await?;
+    save_setting(&state, "guest_queue", &settings).await?;
+    Ok(state.guests.status().await)
+}
 
 #[tauri::command]
-async fn download_track(track: YTVideoInfo, state: State<'_, AppState>) -> Result<(), String> {
-    state.downloads.download_track(track).await
+async fn get_guest_requests(state: State<'_, AppState>) -> Result<Vec<GuestRequest>, String> {
+    Ok(state.guests.get_requests().await)
 }
 
 #[tauri::command]
-async fn get_active_downloads(state: State<'_, AppState>) -> Result<Vec<crate::download_manager::DownloadProgress>, String> {
-    Ok(state.downloads.get_active_downloads().await)
+async fn approve_guest_request(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.guests.approve(&id).await
 }
 
 #[tauri::command]
-async fn get_downloaded_tracks(state: State<'_, AppState>) -> Result<Vec<crate::download_manager::DownloadedTrack>, String> {
-    Ok(state.downloads.get_downloaded_tracks().await)
+async fn reject_guest_request(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.guests.reject(&id).await
 }
 
 #[tauri::command]
-async fn get_storage_used(state: State<'_, AppState>) -> Result<i64, String> {
-    Ok(state.downloads.get_storage_used().await)
+async fn set_max_volume_gain(max_gain: f32, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Changing the volume limit").await?;
+    state.audio.set_max_gain(max_gain).await?;
+    persist_volume(&state).await
 }
 
+/// Hearing protection: the slider can't play louder than `limit` (0.1 to 1.0)
 #[tauri::command]
-async fn is_track_downloaded(video_id: String, state: State<'_, AppState>) -> Result<bool, String> {
-    Ok(state.downloads.is_downloaded(&video_id).await)
+async fn set_max_volume(limit: f32, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Changing the volume limit").await?;
+    state.audio.set_max_volume(limit).await?;
+    let max_volume = state.audio.get_state().await.max_volume;
+    save_setting(&state, "max_volume", &max_volume).await
 }
 
 #[tauri::command]
-async fn delete_download(video_id: String, state: State<'_, AppState>) -> Result<(), String> {
-    state.downloads.delete_download(&video_id).await
+async fn set_limiter(limiter: LimiterSettings, state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.set_limiter(limiter).await?;
+    let limiter = state.audio.get_state().await.limiter;
+    save_setting(&state, "limiter", &limiter).await
 }
 
+/// Seconds of audio to fetch ahead of the playhead; higher values ride out flaky connections
 #[tauri::command]
-async fn cancel_download(video_id: String, state: State<'_, AppState>) -> Result<(), String> {
-    state.downloads.cancel_download(&video_id).await
+async fn set_buffer_target(seconds: f64, state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.set_buffer_target(seconds).await?;
+    let buffer_target = state.audio.get_state().await.buffer_target;
+    save_setting(&state, "buffer_target", &buffer_target).await
 }
 
-// ===== SETTINGS COMMANDS =====
+// Turns data saver on or off from the user's mode and whether the connection is metered
+async fn apply_data_saver(state: &AppState) -> Result<bool, String> {
+    let mode: Option<DataSaverMode> = state.db.get_preference("data_saver_mode").await.map_err(|e| e.to_string())?;
+    let active = mode.unwrap_or_default().is_active(state.connectivity.is_metered());
+    state.audio.set_data_saver(active).await?;
+    state.durations.set_paused(active || !state.connectivity.is_online()).await;
+    Ok(active)
+}
 
 #[tauri::command]
-async fn get_downloads_directory(state: State<'_, AppState>) -> Result<String, String> {
-    Ok(state.downloads.get_downloads_directory().await)
+async fn get_audio_capabilities(state: State<'_, AppState>) -> Result<crate::audio_capabilities::AudioCapabilities, String> {
+    Ok(state.audio.get_capabilities().await)
 }
 
 #[tauri::command]
-async fn set_downloads_directory(path: String, state: State<'_, AppState>) -> Result<(), String> {
-    use std::path::PathBuf;
-    let path_buf = PathBuf::from(path);
-    state.downloads.set_downloads_dir(path_buf).await
+async fn get_prefer_builtin_decoder(state: State<'_, AppState>) -> Result<bool, String> {
+    let enabled = state.db.get_preference("prefer_builtin_decoder").await.map_err(|e| e.to_string())?;
+    Ok(enabled.unwrap_or(false))
 }
 
+/// Decode with the built-in decoder wherever it can, keeping ffmpeg for what it can't play
 #[tauri::command]
-async fn get_audio_quality(state: State<'_, AppState>) -> Result<String, String> {
-    Ok(state.downloads.get_audio_quality().await)
+async fn set_prefer_builtin_decoder(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.set_prefer_builtin_decoder(enabled).await?;
+    save_setting(&state, "prefer_builtin_decoder", &enabled).await
 }
 
 #[tauri::command]
-async fn set_audio_quality(quality: String, state: State<'_, AppState>) -> Result<(), String> {
-    state.downloads.set_audio_quality(quality).await
+async fn get_keep_streamed_audio(state: State<'_, AppState>) -> Result<bool, String> {
+    let enabled = state.db.get_preference("keep_streamed_audio").await.map_err(|e| e.to_string())?;
+    Ok(enabled.unwrap_or(false))
 }
 
+/// Keep what streaming fetched so downloading the playing track doesn't fetch it twice
 #[tauri::command]
-async fn get_app_version() -> Result<String, String> {
-    Ok(env!("CARGO_PKG_VERSION").to_string())
+async fn set_keep_streamed_audio(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.set_keep_streamed_audio(enabled).await?;
+    save_setting(&state, "keep_streamed_audio", &enabled).await
 }
 
 #[tauri::command]
-async fn check_for_updates_manual(app: tauri::AppHandle) -> Result<bool, String> {
-    check_for_updates_silently(app).await;
-    Ok(true)
+async fn set_sleep_timer(minutes: f64, state: State<'_, AppState>) -> Result<ScheduledTimer, String> {
+    state.party.ensure_unlocked("Setting a sleep timer").await?;
+    if minutes <= 0.0 {
+        return Err("Sleep timer needs a positive number of minutes".to_string());
+    }
+    let fires_at = chrono::Utc::now().timestamp() + (minutes * 60.0).round() as i64;
+    Ok(state.timers.set(TimerKind::Sleep, fires_at, TIMER_FADE_SECONDS).await)
+}
+
+/// Fade out and pause at the next occurrence of `time` ("HH:MM", local time)
+#[tauri::command]
+async fn set_stop_at(time: String, state: State<'_, AppState>) -> Result<ScheduledTimer, String> {
+    use chrono::TimeZone;
+
+    state.party.ensure_unlocked("Setting a stop time").await?;
+    let target = chrono::NaiveTime::parse_from_str(&time, "%H:%M").map_err(|_| "Time must be HH:MM".to_string())?;
+    let now = chrono::Local::now();
+    let mut date = now.date_naive();
+    if now.time() >= target {
+        date = date.succ_opt().ok_or("Invalid date")?;
+    }
+    // On DST changes a local time can be missing or repeated; take the earliest valid one
+    let fires_at = chrono::Local
+        .from_local_datetime(&date.and_time(target))
+        .earliest()
+        .ok_or_else(|| format!("{} doesn't exist today in the local time zone", time))?
+        .timestamp();
+
+    Ok(state.timers.set(TimerKind::StopAt, fires_at, TIMER_FADE_SECONDS).await)
+}
+
+#[tauri::command]
+async fn cancel_timer(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    if state.timers.cancel(&id).await {
+        Ok(())
+    } else {
+        Err("Timer not found".to_string())
+    }
+}
+
+#[tauri::command]
+async fn get_timers(state: State<'_, AppState>) -> Result<Vec<ScheduledTimer>, String> {
+    Ok(state.timers.get_all().await)
+}
+
+#[tauri::command]
+async fn get_quiet_hours(state: State<'_, AppState>) -> Result<QuietHours, String> {
+    Ok(state.audio.get_quiet_hours().await)
+}
+
+#[tauri::command]
+async fn set_quiet_hours(quiet_hours: QuietHours, state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.set_quiet_hours(quiet_hours.clone()).await?;
+    save_setting(&state, "quiet_hours", &quiet_hours).await
+}
+
+async fn get_trims(state: &AppState) -> Result<HashMap<String, TrackTrim>, String> {
+    let trims = state.db.get_preference("track_trims").await.map_err(|e| e.to_string())?;
+    Ok(trims.unwrap_or_default())
+}
+
+#[tauri::command]
+async fn get_track_trim(video_id: String, state: State<'_, AppState>) -> Result<Option<TrackTrim>, String> {
+    Ok(get_trims(&state).await?.remove(&video_id))
+}
+
+/// Skip a track's silent intro and outro: it starts at `start` seconds and ends at `end`,
+/// or plays to the end without one. A zero start and no end clear the trim.
+#[tauri::command]
+async fn set_track_trim(video_id: String, start: f64, end: Option<f64>, state: State<'_, AppState>) -> Result<(), String> {
+    if !start.is_finite() || start < 0.0 {
+        return Err("The start has to be zero or later".to_string());
+    }
+    if let Some(end) = end {
+        if !end.is_finite() || end <= start + MIN_TRIMMED_LENGTH {
+            return Err(format!("The end has to be more than {} seconds after the start", MIN_TRIMMED_LENGTH));
+        }
+    }
+    if let Ok(Some(track)) = state.db.get_track(&video_id).await {
+        let duration = track.duration as f64;
+        if duration > 0.0 && (start >= duration || end.is_some_and(|end| end > duration)) {
+            return Err("The trim goes past the end of the track".to_string());
+        }
+    }
+
+    let trim = (start > 0.0 || end.is_some()).then_some(TrackTrim { start, end });
+    save_trim(&state, &video_id, trim).await
+}
+
+async fn save_trim(state: &AppState, video_id: &str, trim: Option<TrackTrim>) -> Result<(), String> {
+    let mut trims = get_trims(state).await?;
+    match trim {
+        Some(trim) => trims.insert(video_id.to_string(), trim),
+        None => trims.remove(video_id),
+    };
+    state.db.set_preference("track_trims", &trims).await.map_err(|e| e.to_string())?;
+    state.audio.set_trim(video_id, trim).await;
+    Ok(())
+}
+
+async fn get_intro_skips(state: &AppState) -> Result<IntroSkipLog, String> {
+    let log = state.db.get_preference("intro_seeks").await.map_err(|e| e.to_string())?;
+    Ok(log.unwrap_or_default())
+}
+
+// Learns intro skips from early seeks, and suggests one when a track starts that plays keep skipping
+async fn handle_intro_skip_event(handle: &tauri::AppHandle, state: &AppState, event: PlayerEvent, seeked: &std::sync::Mutex<Option<String>>) {
+    let Some(video_id) = event.track_id else {
+        return;
+    };
+    match event.kind {
+        PlayerEventKind::PlayStarted => {
+            if let Ok(mut seeked) = seeked.lock() {
+                *seeked = None;
+            }
+            if get_trims(state).await.is_ok_and(|trims| trims.contains_key(&video_id)) {
+                return;
+            }
+            let Some(start) = get_intro_skips(state).await.ok().and_then(|log| log.suggestion(&video_id)) else {
+                return;
+            };
+            let title = state.audio.get_state().await.current_track.map(|t| t.title).unwrap_or_default();
+            use tauri::Emitter;
+            let _ = handle.emit("intro-skip-suggested", IntroSkipSuggestion { video_id, title, start });
+        }
+        PlayerEventKind::Seek { from, to } => {
+            // Only the first seek of each play says where the user wanted to start
+            let first = seeked
+                .lock()
+                .is_ok_and(|mut seeked| seeked.replace(video_id.clone()).as_ref() != Some(&video_id));
+            if !first {
+                return;
+            }
+            let Ok(mut log) = get_intro_skips(state).await else {
+                return;
+            };
+            if log.record(&video_id, from, to) {
+                if let Err(e) = state.db.set_preference("intro_seeks", &log).await {
+                    eprintln!("⚠️ Failed to save intro skips: {}", e);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Turn a suggested intro skip into a trim, jumping past the intro if the track is playing
+#[tauri::command]
+async fn accept_intro_skip(video_id: String, start: f64, state: State<'_, AppState>) -> Result<(), String> {
+    let end = get_trims(&state).await?.get(&video_id).and_then(|trim| trim.end);
+    save_trim(&state, &video_id, Some(TrackTrim { start: start.max(0.0), end })).await?;
+
+    let mut log = get_intro_skips(&state).await?;
+    log.forget(&video_id);
+    state.db.set_preference("intro_seeks", &log).await.map_err(|e| e.to_string())?;
+
+    let audio_state = state.audio.get_state().await;
+    if audio_state.current_track.is_some_and(|t| t.id == video_id) && audio_state.current_position < start {
+        state.audio.seek(start).await?;
+    }
+    Ok(())
+}
+
+/// Stop suggesting an intro skip for this track
+#[tauri::command]
+async fn dismiss_intro_skip(video_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let mut log = get_intro_skips(&state).await?;
+    log.dismiss(&video_id);
+    state.db.set_preference("intro_seeks", &log).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_blocked_keywords(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.ytdlp.get_blocked_keywords().await)
+}
+
+#[tauri::command]
+async fn set_blocked_keywords(keywords: Vec<String>, state: State<'_, AppState>) -> Result<(), String> {
+    state.ytdlp.set_blocked_keywords(keywords).await;
+    let keywords = state.ytdlp.get_blocked_keywords().await;
+    save_setting(&state, "blocked_keywords", &keywords).await
+}
+
+#[tauri::command]
+async fn get_data_saver_mode(state: State<'_, AppState>) -> Result<DataSaverMode, String> {
+    let mode = state.db.get_preference("data_saver_mode").await.map_err(|e| e.to_string())?;
+    Ok(mode.unwrap_or_default())
+}
+
+#[tauri::command]
+async fn set_data_saver_mode(mode: DataSaverMode, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "data_saver_mode", &mode).await?;
+    apply_data_saver(&state).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn is_connection_metered(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.connectivity.is_metered())
+}
+
+// Saves the slider position and gain ceiling so they survive restarts. Not announced through
+// `settings-changed`: a drag saves many times, and windows already follow `playback-state-changed`
+async fn persist_volume(state: &AppState) -> Result<(), String> {
+    let audio_state = state.audio.get_state().await;
+    state.db.set_preference("volume", &audio_state.volume).await.map_err(|e| e.to_string())?;
+    state.db.set_preference("max_gain", &audio_state.max_gain).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_playback_speed(rate: f32, state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.set_playback_rate(rate).await
+}
+
+#[tauri::command]
+async fn set_audio_effects(effects: AudioEffects, state: State<'_, AppState>) -> Result<(), String> {
+    state.audio.set_effects(effects).await
+}
+
+#[tauri::command]
+async fn list_output_devices() -> Result<Vec<String>, String> {
+    Ok(AudioManager::list_output_devices())
+}
+
+#[tauri::command]
+async fn set_output_device(device: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "output_device", &device).await?;
+    state.audio.set_output_device(device).await
+}
+
+async fn get_profiles(state: &AppState) -> Result<HashMap<String, AudioProfile>, String> {
+    let profiles = state.db.get_preference("audio_profiles").await.map_err(|e| e.to_string())?;
+    Ok(profiles.unwrap_or_default())
+}
+
+// Restores the volume and effects saved for an output device, if any
+async fn apply_device_profile(state: &AppState, device: &str) -> Result<(), String> {
+    let Some(profile) = get_profiles(state).await?.remove(device) else {
+        return Ok(());
+    };
+    println!("🎚️ Applying audio profile for {}", device);
+    state.audio.set_volume(profile.volume).await?;
+    state.audio.set_effects(profile.effects).await
+}
+
+#[tauri::command]
+async fn get_audio_profiles(state: State<'_, AppState>) -> Result<HashMap<String, AudioProfile>, String> {
+    get_profiles(&state).await
+}
+
+#[tauri::command]
+async fn save_audio_profile(device: String, profile: AudioProfile, state: State<'_, AppState>) -> Result<(), String> {
+    let mut profiles = get_profiles(&state).await?;
+    profiles.insert(device, profile);
+    save_setting(&state, "audio_profiles", &profiles).await
+}
+
+#[tauri::command]
+async fn delete_audio_profile(device: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Deleting audio profiles").await?;
+    let mut profiles = get_profiles(&state).await?;
+    profiles.remove(&device);
+    save_setting(&state, "audio_profiles", &profiles).await
+}
+
+#[tauri::command]
+async fn get_track_announcements(state: State<'_, AppState>) -> Result<bool, String> {
+    let enabled = state.db.get_preference("announce_tracks").await.map_err(|e| e.to_string())?;
+    Ok(enabled.unwrap_or(false))
+}
+
+#[tauri::command]
+async fn set_track_announcements(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "announce_tracks", &enabled).await
+}
+
+#[tauri::command]
+async fn get_listening_log(state: State<'_, AppState>) -> Result<bool, String> {
+    let enabled = state.db.get_preference("listening_log").await.map_err(|e| e.to_string())?;
+    Ok(enabled.unwrap_or(false))
+}
+
+#[tauri::command]
+async fn set_listening_log(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "listening_log", &enabled).await
+}
+
+// Adds a track to this month's listening log playlist, creating the playlist on the month's first listen
+async fn add_to_listening_log(state: &AppState, track: YTVideoInfo) -> Result<(), String> {
+    let now = chrono::Local::now();
+    let playlist_id = format!("listening-log-{}", now.format("%Y-%m"));
+    let name = format!("Listening Log – {}", now.format("%B %Y"));
+    state.db.ensure_playlist(&playlist_id, &name).await.map_err(|e| e.to_string())?;
+
+    let playlist_ids = state.db.get_track_playlist_ids(&track.id).await.map_err(|e| e.to_string())?;
+    if playlist_ids.contains(&playlist_id) {
+        return Ok(());
+    }
+    state
+        .db
+        .save_track_to_playlist(&Track::from(track), &playlist_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_volume_keys(state: State<'_, AppState>) -> Result<VolumeKeySettings, String> {
+    let settings = state.db.get_preference("volume_keys").await.map_err(|e| e.to_string())?;
+    Ok(settings.unwrap_or_default())
+}
+
+/// Let the hardware volume keys change the app's volume instead of the system's while it plays
+#[tauri::command]
+async fn set_volume_keys(settings: VolumeKeySettings, state: State<'_, AppState>) -> Result<(), String> {
+    if !(0.01..=0.25).contains(&settings.step) {
+        return Err("Volume step must be between 1% and 25%".to_string());
+    }
+    save_setting(&state, "volume_keys", &settings).await?;
+    let is_playing = state.audio.get_state().await.is_playing;
+    state.media_keys.set_volume_keys(settings, is_playing).await;
+    Ok(())
+}
+
+async fn get_jingles(state: &AppState) -> Result<JingleSettings, String> {
+    let settings = state.db.get_preference("jingle_settings").await.map_err(|e| e.to_string())?;
+    Ok(settings.unwrap_or_default())
+}
+
+#[tauri::command]
+async fn get_jingle_settings(state: State<'_, AppState>) -> Result<JingleSettings, String> {
+    get_jingles(&state).await
+}
+
+#[tauri::command]
+async fn set_jingle_settings(settings: JingleSettings, state: State<'_, AppState>) -> Result<(), String> {
+    if settings.every == 0 {
+        return Err("Jingles need at least one track between them".to_string());
+    }
+    if settings.enabled {
+        let path = settings.file_path.as_deref().ok_or("Choose an audio file for the jingle")?;
+        if !std::path::Path::new(path).is_file() {
+            return Err(format!("{} doesn't exist", path));
+        }
+    }
+    save_setting(&state, "jingle_settings", &settings).await
+}
+
+async fn get_now_playing(state: &AppState) -> Result<NowPlayingOutput, String> {
+    let output = state.db.get_preference("now_playing_output").await.map_err(|e| e.to_string())?;
+    Ok(output.unwrap_or_default())
+}
+
+#[tauri::command]
+async fn get_now_playing_output(state: State<'_, AppState>) -> Result<NowPlayingOutput, String> {
+    get_now_playing(&state).await
+}
+
+/// Keep "Artist – Title" of the current track in a text file and/or named pipe for streaming tools
+#[tauri::command]
+async fn set_now_playing_output(output: NowPlayingOutput, state: State<'_, AppState>) -> Result<(), String> {
+    if output.enabled {
+        if output.file_path.is_none() && output.pipe_path.is_none() {
+            return Err("Choose a text file or a named pipe to write to".to_string());
+        }
+        // Writing the current track right away checks the paths work
+        let track = state.audio.get_state().await.current_track;
+        now_playing::write(&output, &now_playing::line(track.as_ref())).await?;
+    }
+    save_setting(&state, "now_playing_output", &output).await
+}
+
+#[tauri::command]
+async fn get_notification_policy(state: State<'_, AppState>) -> Result<NotificationPolicy, String> {
+    let policy = state.db.get_preference("notification_policy").await.map_err(|e| e.to_string())?;
+    Ok(policy.unwrap_or_default())
+}
+
+/// When track change notifications are shown: always, only while the popup is hidden,
+/// never, or while it's hidden and the OS isn't in Do Not Disturb
+#[tauri::command]
+async fn set_notification_policy(policy: NotificationPolicy, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "notification_policy", &policy).await
+}
+
+// Plays the jingle when enough tracks have ended since the last one, returning false when
+// the user started something else while it played
+async fn play_jingle_if_due(state: &AppState, tracks_since_jingle: &AtomicU32) -> bool {
+    let settings = match get_jingles(state).await {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("⚠️ Failed to load jingle settings: {}", e);
+            return true;
+        }
+    };
+    let Some(file_path) = settings.file_path.filter(|_| settings.enabled) else {
+        return true;
+    };
+    if tracks_since_jingle.fetch_add(1, Ordering::SeqCst) + 1 < settings.every {
+        return true;
+    }
+    tracks_since_jingle.store(0, Ordering::SeqCst);
+
+    match state.audio.play_jingle(file_path).await {
+        Ok(completed) => completed,
+        Err(e) => {
+            eprintln!("⚠️ Jingle failed: {}", e);
+            true
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_speed_presets(state: State<'_, AppState>) -> Result<SpeedSettings, String> {
+    Ok(state.audio.get_speed_settings().await)
+}
+
+#[tauri::command]
+async fn set_speed_presets(settings: SpeedSettings, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "speed_settings", &settings).await?;
+    state.audio.set_speed_settings(settings).await;
+    Ok(())
+}
+
+// Append the next chunk of a lazy shuffle to the queue when it is running low
+async fn refill_shuffle_queue(state: &AppState) -> Result<(), String> {
+    if !state.queue.needs_refill(SHUFFLE_REFILL_THRESHOLD).await {
+        return Ok(());
+    }
+
+    let Some(scope) = state.queue.get_shuffle_scope().await else {
+        return Ok(());
+    };
+
+    let ids = state.queue.take_pending_chunk(SHUFFLE_CHUNK_SIZE).await;
+    let tracks: Vec<YTVideoInfo> = match scope {
+        ShuffleScope::Downloads => {
+            let mut tracks = Vec::with_capacity(ids.len());
+            for id in &ids {
+                if let Some(info) = state.downloads.get_downloaded_video_info(id).await {
+                    tracks.push(info);
+                }
+            }
+            tracks
+        }
+        _ => state
+            .db
+            .get_tracks_by_ids(&ids)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(YTVideoInfo::from)
+            .collect(),
+    };
+
+    state.queue.add_to_queue_batch(tracks).await;
+    Ok(())
+}
+
+// Looks up the next pasted links when the queue is running low, moving past links that can't be played
+async fn refill_url_list(state: &AppState) {
+    // Pending IDs without a shuffle scope come from a pasted URL list
+    if state.queue.get_shuffle_scope().await.is_some() || !state.queue.needs_refill(URL_LIST_REFILL_THRESHOLD).await {
+        return;
+    }
+
+    loop {
+        let ids = state.queue.take_pending_chunk(URL_LIST_CHUNK_SIZE).await;
+        if ids.is_empty() {
+            return;
+        }
+
+        let mut tracks = Vec::with_capacity(ids.len());
+        for id in &ids {
+            match state.ytdlp.get_video_info(id).await {
+                Ok(track) => tracks.push(track),
+                Err(e) => eprintln!("⚠️ Skipping link {}: {}", id, e),
+            }
+        }
+        if !tracks.is_empty() {
+            state.queue.add_to_queue_batch(tracks).await;
+            return;
+        }
+    }
+}
+
+// Tops up the queue of a lazy shuffle, a pasted URL list or an artist radio station
+async fn refill_queue(state: &AppState) -> Result<(), String> {
+    refill_shuffle_queue(state).await?;
+
+    if state.queue.has_next().await {
+        // Looking up links and fetching a station's tracks take a while, don't hold up the next track for them
+        let state = state.clone();
+        tauri::async_runtime::spawn(async move {
+            refill_url_list(&state).await;
+            if let Err(e) = refill_artist_radio(&state).await {
+                eprintln!("⚠️ Radio refill failed: {}", e);
+            }
+        });
+        Ok(())
+    } else {
+        refill_url_list(state).await;
+        refill_artist_radio(state).await
+    }
+}
+
+// Queues more of the station's artist plus the YouTube mix of the last queued track
+async fn refill_artist_radio(state: &AppState) -> Result<(), String> {
+    let Some(artist) = state.queue.radio_needs_refill(RADIO_REFILL_THRESHOLD).await else {
+        return Ok(());
+    };
+    let Some(seed) = state.queue.get_queue().await.last().cloned() else {
+        return Ok(());
+    };
+
+    let mut artist_tracks = state.ytdlp.fetch_artist_tracks(&artist).await.unwrap_or_default();
+    let mut related = state.ytdlp.fetch_mix(&seed.id).await?;
+    demote_skipped(state, &mut artist_tracks).await;
+    demote_skipped(state, &mut related).await;
+
+    // Checked after fetching, another refill may have added tracks meanwhile
+    let snapshot = state.queue.snapshot().await;
+    let mut heard: HashSet<String> = snapshot.queue.iter().map(|t| t.id.clone()).collect();
+    heard.extend(snapshot.play_history);
+
+    let tracks = station_batch(artist_tracks, related, &mut heard);
+    if tracks.is_empty() {
+        println!("📻 Nothing new to add to the {} station", artist);
+        return Ok(());
+    }
+
+    println!("📻 Added {} track(s) to the {} station", tracks.len(), artist);
+    state.queue.add_to_queue_batch(tracks).await;
+    Ok(())
+}
+
+// Alternates the artist's own tracks with related ones, skipping anything already heard or queued
+fn station_batch(artist_tracks: Vec<YTVideoInfo>, related: Vec<YTVideoInfo>, heard: &mut HashSet<String>) -> Vec<YTVideoInfo> {
+    let mut artist_tracks = artist_tracks.into_iter();
+    let mut related = related.into_iter();
+    let mut batch = Vec::new();
+
+    while batch.len() < RADIO_BATCH_SIZE {
+        let next_pair = [artist_tracks.next(), related.next()];
+        if next_pair.iter().all(|t| t.is_none()) {
+            break;
+        }
+        for track in next_pair.into_iter().flatten() {
+            if batch.len() < RADIO_BATCH_SIZE && heard.insert(track.id.clone()) {
+                batch.push(track);
+            }
+        }
+    }
+
+    batch
+}
+
+/// Replace the queue with a station of the artist's tracks and related artists that keeps itself topped up
+#[tauri::command]
+async fn start_artist_radio(uploader_or_channel: String, state: State<'_, AppState>) -> Result<YTVideoInfo, String> {
+    state.party.ensure_unlocked("Replacing the queue").await?;
+    let mut artist_tracks = state.ytdlp.fetch_artist_tracks(&uploader_or_channel).await?;
+    demote_skipped(&state, &mut artist_tracks).await;
+    let seed = artist_tracks
+        .first()
+        .cloned()
+        .ok_or_else(|| format!("No tracks found for {}", uploader_or_channel))?;
+    let mut related = state.ytdlp.fetch_mix(&seed.id).await.unwrap_or_default();
+    demote_skipped(&state, &mut related).await;
+
+    let mut heard: HashSet<String> = state.queue.snapshot().await.play_history.into_iter().collect();
+    let mut tracks = station_batch(artist_tracks.clone(), related.clone(), &mut heard);
+    if tracks.is_empty() {
+        // Everything was heard recently, a station still has to start somewhere
+        tracks = station_batch(artist_tracks, related, &mut HashSet::new());
+    }
+
+    state.queue.clear_queue().await;
+    state.queue.start_radio_station(uploader_or_channel.clone()).await;
+    state.queue.add_to_queue_batch(tracks).await;
+    println!("📻 Started {} radio", uploader_or_channel);
+
+    let track = state.queue.play_next().await.ok_or("Radio station is empty")?;
+    play_resolved(&state, track.clone(), TrackChangeReason::User).await?;
+    Ok(track)
+}
+
+/// Play a pasted list of YouTube links, one per line, without creating a playlist. Titles are
+/// looked up a few links at a time as the queue plays.
+#[tauri::command]
+async fn play_url_list(text: String, state: State<'_, AppState>) -> Result<YTVideoInfo, String> {
+    state.party.ensure_unlocked("Replacing the queue").await?;
+    let mut seen = HashSet::new();
+    let ids: Vec<String> = text
+        .lines()
+        .filter_map(extract_video_id)
+        .filter(|id| seen.insert(id.clone()))
+        .collect();
+    if ids.is_empty() {
+        return Err("No YouTube links found".to_string());
+    }
+
+    state.queue.start_url_list(ids).await;
+    refill_url_list(&state).await;
+
+    let track = state.queue.play_next().await.ok_or("None of the links could be played")?;
+    play_resolved(&state, track.clone(), TrackChangeReason::User).await?;
+    Ok(track)
+}
+
+#[tauri::command]
+async fn play_all_shuffled(scope: ShuffleScope, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Replacing the queue").await?;
+    let ids = match &scope {
+        ShuffleScope::Playlist(playlist_id) => state.db.get_playlist_track_ids(playlist_id).await,
+        ShuffleScope::Favorites => state.db.get_playlist_track_ids("favorites").await,
+        ShuffleScope::Library => state.db.get_all_track_ids().await,
+        ShuffleScope::Downloads => Ok(state.downloads.get_downloaded_ids().await),
+    }
+    .map_err(|e| e.to_string())?;
+    let ids = dedupe_recordings(&state, ids).await;
+
+    if ids.is_empty() {
+        return Err("Nothing to shuffle".to_string());
+    }
+
+    let skip_rates = state.db.get_skip_rates().await.unwrap_or_default();
+    state.queue.start_lazy_shuffle(scope, ids, &skip_rates).await;
+    refill_shuffle_queue(&state).await?;
+
+    if let Some(track) = state.queue.play_next().await {
+        play_resolved(&state, track, TrackChangeReason::User).await?;
+    }
+
+    Ok(())
+}
+
+// Counts the current track as skipped when it's left before SKIP_THRESHOLD of it played
+async fn record_skip(state: &AppState) {
+    let audio = state.audio.get_state().await;
+    let Some(track) = audio.current_track else {
+        return;
+    };
+    if audio.duration <= 0.0 || audio.current_position / audio.duration >= SKIP_THRESHOLD {
+        return;
+    }
+    if let Err(e) = state.db.mark_last_play_skipped(&track.id).await {
+        eprintln!("⚠️ Failed to record skip: {}", e);
+    }
+}
+
+// Moves often skipped tracks to the back, keeping the order otherwise
+async fn demote_skipped(state: &AppState, tracks: &mut [YTVideoInfo]) {
+    let skip_rates = state.db.get_skip_rates().await.unwrap_or_default();
+    if skip_rates.is_empty() {
+        return;
+    }
+    tracks.sort_by(|a, b| {
+        let rate = |t: &YTVideoInfo| skip_rates.get(&t.id).copied().unwrap_or(0.0);
+        rate(a).total_cmp(&rate(b))
+    });
+}
+
+/// The tracks skipped most often, with how many of their plays were skips
+#[tauri::command]
+async fn get_most_skipped(limit: Option<i64>, state: State<'_, AppState>) -> Result<Vec<TrackSkipStats>, String> {
+    state
+        .db
+        .get_most_skipped(limit.unwrap_or(MOST_SKIPPED_LIMIT))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn skip_to_next(state: &AppState, reason: TrackChangeReason) -> Result<Option<YTVideoInfo>, String> {
+    record_skip(state).await;
+    refill_queue(state).await?;
+    if let Some(track) = state.queue.play_next().await {
+        play_resolved(state, track.clone(), reason).await?;
+        Ok(Some(track))
+    } else {
+        Ok(None)
+    }
+}
+
+async fn skip_to_previous(state: &AppState, reason: TrackChangeReason) -> Result<Option<YTVideoInfo>, String> {
+    if let Some(track) = state.queue.play_previous().await {
+        play_resolved(state, track.clone(), reason).await?;
+        Ok(Some(track))
+    } else {
+        Ok(None)
+    }
+}
+
+#[tauri::command]
+async fn play_next(state: State<'_, AppState>) -> Result<Option<YTVideoInfo>, String> {
+    skip_to_next(&state, TrackChangeReason::User).await
+}
+
+#[tauri::command]
+async fn play_previous(state: State<'_, AppState>) -> Result<Option<YTVideoInfo>, String> {
+    skip_to_previous(&state, TrackChangeReason::User).await
+}
+
+#[tauri::command]
+async fn get_audio_state(state: State<'_, AppState>) -> Result<AudioState, String> {
+    Ok(state.audio.get_state().await)
+}
+
+/// Recent playback transitions (play, seek, pause, errors...) for diagnosing playback issues
+#[tauri::command]
+async fn get_player_events(state: State<'_, AppState>) -> Result<Vec<PlayerEvent>, String> {
+    Ok(state.audio.get_player_events().await)
+}
+
+// Queue commands
+#[tauri::command]
+async fn add_to_queue(track: YTVideoInfo, state: State<'_, AppState>) -> Result<(), String> {
+    apply_channel_rule(&state, &track, false).await;
+    if track.duration <= 0 {
+        state.durations.enqueue(vec![track.id.clone()]).await;
+    }
+    state.queue.add_to_queue(track).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_queue(state: State<'_, AppState>) -> Result<Vec<YTVideoInfo>, String> {
+    Ok(state.queue.get_queue().await)
+}
+
+#[tauri::command]
+async fn filter_queue(query: String, state: State<'_, AppState>) -> Result<Vec<QueueMatch>, String> {
+    Ok(state.queue.filter_queue(&query).await)
+}
+
+#[tauri::command]
+async fn clear_queue(state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Clearing the queue").await?;
+    let snapshot = state.queue.snapshot().await;
+    state.queue.clear_queue().await;
+    state.undo.push(UndoAction::ClearQueue { snapshot }).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn toggle_shuffle(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.queue.toggle_shuffle().await)
+}
+
+#[tauri::command]
+async fn cycle_repeat_mode(state: State<'_, AppState>) -> Result<RepeatMode, String> {
+    Ok(state.queue.cycle_repeat_mode().await)
+}
+
+/// Repeat the current track `count` more times before going back to the previous repeat mode
+#[tauri::command]
+async fn set_repeat_count(count: u32, state: State<'_, AppState>) -> Result<RepeatMode, String> {
+    if count > MAX_REPEAT_COUNT {
+        return Err(format!("A track can be repeated at most {} times", MAX_REPEAT_COUNT));
+    }
+    Ok(state.queue.set_repeat_count(count).await)
+}
+
+#[tauri::command]
+async fn get_queue_info(state: State<'_, AppState>) -> Result<String, String> {
+    Ok(state.queue.get_queue_info().await)
+}
+
+#[tauri::command]
+async fn reorder_queue(new_queue: Vec<YTVideoInfo>, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Reordering the queue").await?;
+    state.queue.reorder_queue(new_queue).await
+}
+
+// ===== PLAYLIST COMMANDS =====
+
+#[tauri::command]
+async fn get_all_playlists(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<Vec<Playlist>, String> {
+    let mut playlists = state.db.get_all_playlists().await.map_err(|e| e.to_string())?;
+    state
+        .media_keys
+        .update_playlists(playlists.iter().map(|p| (p.id.clone(), p.name.clone())).collect())
+        .await;
+
+    // Covers that aren't cached yet are built in the background and announced when ready
+    let mut missing = Vec::new();
+    for playlist in &mut playlists {
+        let tracks = state.db.get_playlist_tracks(&playlist.id).await.map_err(|e| e.to_string())?;
+        playlist.cover = ArtworkManager::cached_cover(&playlist.id, &tracks);
+        if playlist.cover.is_none() && tracks.iter().any(|t| t.thumbnail_url.is_some()) {
+            missing.push((playlist.id.clone(), tracks));
+        }
+    }
+
+    if !missing.is_empty() {
+        let artwork = Arc::clone(&state.artwork);
+        tauri::async_runtime::spawn(async move {
+            use tauri::Emitter;
+            let updated = artwork.generate_covers(missing).await;
+            if !updated.is_empty() {
+                let _ = app.emit("playlist-covers-updated", updated);
+            }
+        });
+    }
+
+    Ok(playlists)
+}
+
+#[tauri::command]
+async fn get_dominant_colors(video_id: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let track = state.db.get_track(&video_id).await.map_err(|e| e.to_string())?;
+    let url = track
+        .and_then(|t| t.thumbnail_url)
+        .unwrap_or_else(|| format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", video_id));
+
+    let thumbnail = crate::artwork::cached_thumbnail(&video_id, &url).await?;
+    tokio::task::spawn_blocking(move || crate::artwork::dominant_colors(&thumbnail, PALETTE_SIZE))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Fetch thumbnails and missing durations for listed tracks a few at a time, replacing the
+/// batch for a list that's no longer shown
+#[tauri::command]
+async fn prefetch_metadata(
+    track_ids: Vec<String>,
+    priority: PrefetchPriority,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.prefetcher.prefetch(track_ids, priority).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn cancel_metadata_prefetch(state: State<'_, AppState>) -> Result<(), String> {
+    state.prefetcher.cancel().await;
+    Ok(())
+}
+
+/// A playlist's tracks matching `query`, filtered in the database rather than the webview
+#[tauri::command]
+async fn filter_playlist_tracks(playlist_id: String, query: String, state: State<'_, AppState>) -> Result<Vec<Track>, String> {
+    if query.trim().is_empty() {
+        return state.db.get_playlist_tracks(&playlist_id).await.map_err(|e| e.to_string());
+    }
+    state.db.filter_playlist_tracks(&playlist_id, query.trim()).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn create_playlist(name: String, state: State<'_, AppState>) -> Result<String, String> {
+    state.db.create_playlist(&name).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn delete_playlist(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Deleting playlists").await?;
+    let playlist = state.db.get_playlist(&id).await.map_err(|e| e.to_string())?;
+    let memberships = state
+        .db
+        .get_playlist_memberships(&id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    state.db.delete_playlist(&id).await.map_err(|e| e.to_string())?;
+    crate::artwork::remove_covers(&id);
+
+    if let Some(playlist) = playlist.filter(|p| !p.is_system_playlist) {
+        state.undo.push(UndoAction::DeletePlaylist { playlist, memberships }).await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn duplicate_playlist(id: String, new_name: String, state: State<'_, AppState>) -> Result<String, String> {
+    state
+        .db
+        .duplicate_playlist(&id, &new_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn merge_playlists(
+    source_ids: Vec<String>,
+    target_id: String,
+    dedupe: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state
+        .db
+        .merge_playlists(&source_ids, &target_id, dedupe)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_playlist_tracks(playlist_id: String, state: State<'_, AppState>) -> Result<Vec<Track>, String> {
+    state
+        .db
+        .get_playlist_tracks(&playlist_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_playlist_summary(playlist_id: String, state: State<'_, AppState>) -> Result<PlaylistSummary, String> {
+    let mut summary = state
+        .db
+        .get_playlist_summary(&playlist_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let track_ids = state
+        .db
+        .get_playlist_track_ids(&playlist_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (downloaded_count, downloaded_size) = state.downloads.get_downloaded_totals(&track_ids).await;
+    summary.downloaded_count = downloaded_count;
+    summary.downloaded_size = downloaded_size;
+
+    Ok(summary)
+}
+
+#[tauri::command]
+async fn add_track_to_playlist(
+    track: YTVideoInfo,
+    playlist_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    // Save the track to database along with the membership
+    let db_track = Track::from(track);
+
+    // Add to playlist
+    state
+        .db
+        .save_track_to_playlist(&db_track, &playlist_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn remove_track_from_playlist(
+    track_id: String,
+    playlist_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.party.ensure_unlocked("Removing tracks from playlists").await?;
+    let memberships: Vec<(String, i64)> = state
+        .db
+        .get_playlist_memberships(&playlist_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(id, _)| *id == track_id)
+        .collect();
+
+    state
+        .db
+        .remove_track_from_playlist(&track_id, &playlist_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    state.undo.push(UndoAction::RemoveTracks { playlist_id, memberships }).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn add_tracks_to_playlist(
+    track_ids: Vec<String>,
+    playlist_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state
+        .db
+        .add_tracks_to_playlist(&track_ids, &playlist_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn remove_tracks_from_playlist(
+    track_ids: Vec<String>,
+    playlist_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.party.ensure_unlocked("Removing tracks from playlists").await?;
+    let memberships: Vec<(String, i64)> = state
+        .db
+        .get_playlist_memberships(&playlist_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(id, _)| track_ids.contains(id))
+        .collect();
+
+    state
+        .db
+        .remove_tracks_from_playlist(&track_ids, &playlist_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    state.undo.push(UndoAction::RemoveTracks { playlist_id, memberships }).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_track_playlists(track_id: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    state
+        .db
+        .get_track_playlist_ids(&track_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_track_playlists(
+    track: YTVideoInfo,
+    playlist_ids: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    // Make sure the track exists before assigning memberships
+    let db_track = Track::from(track);
+
+    state.db.save_track(&db_track).await.map_err(|e| e.to_string())?;
+
+    state
+        .db
+        .set_track_playlists(&db_track.id, &playlist_ids)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Rename a track for display and attach a note, saving the track first if it isn't yet
+#[tauri::command]
+async fn update_track_display(
+    track: YTVideoInfo,
+    title: Option<String>,
+    artist: Option<String>,
+    note: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    // Blank values clear the override so the original YouTube metadata shows again
+    let clean = |value: &Option<String>| value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+    let (title, artist, note) = (clean(&title), clean(&artist), clean(&note));
+
+    state.db.save_track(&Track::from(track.clone())).await.map_err(|e| e.to_string())?;
+    state
+        .db
+        .update_track_display(&track.id, title.as_deref(), artist.as_deref(), note.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+    state.downloads.set_display_names(&track.id, title.as_deref(), artist.as_deref()).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_track_content_type(
+    track_id: String,
+    content_type: Option<ContentType>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state
+        .db
+        .set_track_content_type(&track_id, content_type)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_tracks_by_content_type(content_type: ContentType, state: State<'_, AppState>) -> Result<Vec<Track>, String> {
+    state
+        .db
+        .get_tracks_by_content_type(content_type)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn add_to_favorites(track: YTVideoInfo, state: State<'_, AppState>) -> Result<(), String> {
+    // Save track and favorite it together
+    let db_track = Track::from(track);
+
+    // Add to favorites
+    state
+        .db
+        .save_track_to_playlist(&db_track, "favorites")
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn remove_from_favorites(track_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Removing favorites").await?;
+    state
+        .db
+        .remove_from_favorites(&track_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn get_pinned(state: &AppState) -> Result<Vec<String>, String> {
+    let pinned = state.db.get_preference("pinned_playlists").await.map_err(|e| e.to_string())?;
+    Ok(pinned.unwrap_or_default())
+}
+
+#[tauri::command]
+async fn get_pinned_playlists(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    get_pinned(&state).await
+}
+
+/// Pin a playlist to the Windows jump list, or unpin it
+#[tauri::command]
+async fn set_playlist_pinned(playlist_id: String, pinned: bool, state: State<'_, AppState>) -> Result<(), String> {
+    let mut ids = get_pinned(&state).await?;
+    ids.retain(|id| *id != playlist_id);
+    if pinned {
+        ids.push(playlist_id);
+    }
+    save_setting(&state, "pinned_playlists", &ids).await?;
+    refresh_jump_list(&state).await
+}
+
+// Rebuild the Windows jump list from recent plays and pinned playlists
+async fn refresh_jump_list(state: &AppState) -> Result<(), String> {
+    if !cfg!(windows) {
+        return Ok(());
+    }
+
+    let recent_tracks: Vec<(String, String)> = state
+        .db
+        .get_recently_played(JUMP_LIST_RECENT_LIMIT)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|track| (track.id, track.custom_title.unwrap_or(track.title)))
+        .collect();
+    let pinned = get_pinned(state).await?;
+    let playlists = state.db.get_all_playlists().await.map_err(|e| e.to_string())?;
+    // Listed in the order they were pinned; deleted playlists drop out
+    let pinned_playlists: Vec<(String, String)> = pinned
+        .iter()
+        .filter_map(|id| playlists.iter().find(|p| p.id == *id))
+        .map(|p| (p.id.clone(), p.name.clone()))
+        .collect();
+
+    tauri::async_runtime::spawn_blocking(move || jump_list::update(&recent_tracks, &pinned_playlists))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// Starts what a jump list entry asked for, when the app is launched with its arguments
+async fn handle_launch_args(state: &AppState, args: &[String]) -> Result<(), String> {
+    for arg in args {
+        if let Some(track_id) = arg.strip_prefix(jump_list::PLAY_TRACK_ARG) {
+            let track = match state.db.get_track(track_id).await.map_err(|e| e.to_string())? {
+                Some(track) => YTVideoInfo::from(track),
+                None => state.ytdlp.get_video_info(track_id).await?,
+            };
+            return play_resolved(state, track, TrackChangeReason::User).await;
+        }
+        if let Some(playlist_id) = arg.strip_prefix(jump_list::PLAY_PLAYLIST_ARG) {
+            state.party.ensure_unlocked("Replacing the queue").await?;
+            return start_playlist(state, playlist_id, TrackChangeReason::User).await;
+        }
+        if let Some(action) = arg.strip_prefix(player_action::ACTION_ARG) {
+            return run_player_action(state, player_action::parse(action)?).await;
+        }
+    }
+    Ok(())
+}
+
+/// Single entry point for scripts, assistants and speech tools, taking an action as JSON or a
+/// short phrase like "play artist Daft Punk", "volume 50" or "queue playlist Focus"
+#[tauri::command]
+async fn execute_player_action(action: String, state: State<'_, AppState>) -> Result<(), String> {
+    run_player_action(&state, player_action::parse(&action)?).await
+}
+
+async fn run_player_action(state: &AppState, action: PlayerAction) -> Result<(), String> {
+    println!("🗣️ Player action: {:?}", action);
+    match action {
+        PlayerAction::Play => {
+            if !state.audio.get_state().await.is_playing {
+                state.audio.toggle_play_pause().await?;
+            }
+            Ok(())
+        }
+        PlayerAction::Pause => state.audio.pause().await,
+        PlayerAction::Toggle => state.audio.toggle_play_pause().await,
+        PlayerAction::Stop => {
+            state.party.ensure_unlocked("Stopping playback").await?;
+            state.audio.stop().await
+        }
+        PlayerAction::Next => skip_to_next(state, TrackChangeReason::Remote).await.map(|_| ()),
+        PlayerAction::Previous => skip_to_previous(state, TrackChangeReason::Remote).await.map(|_| ()),
+        PlayerAction::PlaySearch { query } => {
+            let track = top_search_result(state, query).await?;
+            play_resolved(state, track, TrackChangeReason::Remote).await
+        }
+        PlayerAction::PlayArtist { artist } => {
+            state.party.ensure_unlocked("Replacing the queue").await?;
+            let results = state.ytdlp.search(artist.clone(), true).await?;
+            // Prefer the artist's own uploads, but anything the search found beats nothing
+            let needle = artist.to_lowercase();
+            let (mut tracks, others): (Vec<YTVideoInfo>, Vec<YTVideoInfo>) =
+                results.into_iter().partition(|t| t.uploader.to_lowercase().contains(&needle));
+            if tracks.is_empty() {
+                tracks = others;
+            }
+            tracks.truncate(ARTIST_ACTION_TRACKS);
+            let Some(first) = tracks.first().cloned() else {
+                return Err(format!("No tracks found for {}", artist));
+            };
+
+            state.queue.clear_queue().await;
+            state.queue.add_to_queue_batch(tracks).await;
+            state.queue.set_source_playlist(None, chrono::Utc::now().timestamp()).await;
+            state.queue.set_current_index(0).await;
+            play_resolved(state, first, TrackChangeReason::Remote).await
+        }
+        PlayerAction::PlayPlaylist { playlist } => {
+            state.party.ensure_unlocked("Replacing the queue").await?;
+            let playlist_id = find_playlist_by_name(state, &playlist).await?;
+            start_playlist(state, &playlist_id, TrackChangeReason::Remote).await
+        }
+        PlayerAction::QueueSearch { query } => {
+            let track = top_search_result(state, query).await?;
+            apply_channel_rule(state, &track, false).await;
+            state.queue.add_to_queue(track).await;
+            Ok(())
+        }
+        PlayerAction::QueuePlaylist { playlist } => {
+            let playlist_id = find_playlist_by_name(state, &playlist).await?;
+            let tracks = state.db.get_playlist_tracks(&playlist_id).await.map_err(|e| e.to_string())?;
+            if tracks.is_empty() {
+                return Err("Playlist is empty".to_string());
+            }
+            state.queue.add_to_queue_batch(tracks.into_iter().map(YTVideoInfo::from).collect()).await;
+            Ok(())
+        }
+        PlayerAction::Volume { percent } => {
+            state.audio.set_volume(percent as f32 / 100.0).await?;
+            persist_volume(state).await
+        }
+        PlayerAction::Seek { seconds } => state.audio.seek(seconds).await,
+    }
+}
+
+async fn top_search_result(state: &AppState, query: String) -> Result<YTVideoInfo, String> {
+    state.telemetry.record(TelemetryEvent::Search, 1);
+    state
+        .ytdlp
+        .search(query.clone(), true)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No results for {}", query))
+}
+
+// Playlists are named by people, so match the name without caring about case
+async fn find_playlist_by_name(state: &AppState, name: &str) -> Result<String, String> {
+    let playlists = state.db.get_all_playlists().await.map_err(|e| e.to_string())?;
+    playlists
+        .into_iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+        .map(|p| p.id)
+        .ok_or_else(|| format!("No playlist named {}", name))
+}
+
+#[tauri::command]
+async fn play_playlist(playlist_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Replacing the queue").await?;
+    start_playlist(&state, &playlist_id, TrackChangeReason::User).await
+}
+
+/// Play a track from a playlist with the rest of the playlist queued around it, so next and
+/// previous move through the playlist
+#[tauri::command]
+async fn play_track_in_context(track_id: String, playlist_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Replacing the queue").await?;
+    start_playlist_at(&state, &playlist_id, Some(&track_id), TrackChangeReason::User).await
+}
+
+// Saves the queue and position so the next launch can pick up where this one left off
+async fn save_last_session(state: &AppState) {
+    let snapshot = state.queue.snapshot().await;
+    if snapshot.queue.is_empty() {
+        return;
+    }
+
+    let session = SavedSession {
+        queue: snapshot.queue,
+        current_index: snapshot.current_index,
+        position: state.audio.get_state().await.current_position,
+        source_playlist: snapshot.source_playlist,
+        started_at: snapshot.started_at,
+        saved_at: chrono::Utc::now().timestamp(),
+    };
+    if let Err(e) = state.db.set_preference("last_session", &session).await {
+        eprintln!("⚠️ Failed to save session: {}", e);
+    }
+}
+
+#[tauri::command]
+async fn get_saved_session(state: State<'_, AppState>) -> Result<Option<SavedSession>, String> {
+    state.db.get_preference("last_session").await.map_err(|e| e.to_string())
+}
+
+/// Rebuild the last session's queue and play on, either exactly where it stopped or from
+/// the first track of its playlist that wasn't heard yet
+#[tauri::command]
+async fn restore_session(mode: SessionRestoreMode, state: State<'_, AppState>) -> Result<Option<YTVideoInfo>, String> {
+    state.party.ensure_unlocked("Replacing the queue").await?;
+    let Some(session) = state
+        .db
+        .get_preference::<SavedSession>("last_session")
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+
+    let (queue, index, position) = match mode {
+        SessionRestoreMode::Exact => (session.queue, session.current_index.max(0) as usize, session.position),
+        SessionRestoreMode::NextUnplayed => {
+            // The playlist may have changed since, so it is read again rather than taken from the save
+            let mut queue = session.queue;
+            if let Some(playlist_id) = &session.source_playlist {
+                let tracks = state.db.get_playlist_tracks(playlist_id).await.map_err(|e| e.to_string())?;
+                if !tracks.is_empty() {
+                    queue = tracks.into_iter().map(YTVideoInfo::from).collect();
+                }
+            }
+            let heard = state
+                .db
+                .get_heard_track_ids(session.started_at)
+                .await
+                .map_err(|e| e.to_string())?;
+            let index = queue.iter().position(|t| !heard.contains(&t.id)).unwrap_or(0);
+            (queue, index, 0.0)
+        }
+    };
+
+    if queue.is_empty() {
+        return Ok(None);
+    }
+
+    state.queue.clear_queue().await;
+    state.queue.add_to_queue_batch(queue).await;
+    state.queue.set_source_playlist(session.source_playlist, session.started_at).await;
+
+    let track = state.queue.play_track_at(index).await.ok_or("Saved session no longer fits its queue")?;
+    let start_at = Some(position).filter(|p| *p > 0.0);
+    play_resolved_at(&state, track.clone(), TrackChangeReason::User, start_at).await?;
+
+    println!("⏯️ Restored session at {} ({:?})", track.title, mode);
+    Ok(Some(track))
+}
+
+async fn get_queue_session_map(state: &AppState) -> Result<HashMap<String, QueueSession>, String> {
+    let sessions = state.db.get_preference("queue_sessions").await.map_err(|e| e.to_string())?;
+    Ok(sessions.unwrap_or_default())
+}
+
+/// Saved queue sessions, most recently saved first
+#[tauri::command]
+async fn get_sessions(state: State<'_, AppState>) -> Result<Vec<QueueSessionSummary>, String> {
+    let mut sessions: Vec<QueueSessionSummary> = get_queue_session_map(&state)
+        .await?
+        .into_iter()
+        .map(|(name, session)| QueueSessionSummary {
+            name,
+            track_count: session.queue.queue.len(),
+            current_track: usize::try_from(session.queue.current_index)
+                .ok()
+                .and_then(|index| session.queue.queue.get(index).cloned()),
+            shuffle_mode: session.queue.shuffle_mode,
+            repeat_mode: session.queue.repeat_mode,
+            saved_at: session.saved_at,
+        })
+        .collect();
+    sessions.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+    Ok(sessions)
+}
+
+/// Save the queue, current track and position, shuffle and repeat modes under a name,
+/// replacing any session already saved under it
+#[tauri::command]
+async fn save_session(name: String, state: State<'_, AppState>) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Give the session a name".to_string());
+    }
+
+    let queue = state.queue.snapshot().await;
+    if queue.queue.is_empty() {
+        return Err("The queue is empty".to_string());
+    }
+
+    let mut sessions = get_queue_session_map(&state).await?;
+    sessions.insert(
+        name.clone(),
+        QueueSession {
+            queue,
+            position: state.audio.get_state().await.current_position,
+            saved_at: chrono::Utc::now().timestamp(),
+        },
+    );
+    state.db.set_preference("queue_sessions", &sessions).await.map_err(|e| e.to_string())?;
+    println!("💾 Saved session {}", name);
+    Ok(())
+}
+
+/// Swap the queue for a saved session and play on from where it was saved
+#[tauri::command]
+async fn load_session(name: String, state: State<'_, AppState>) -> Result<Option<YTVideoInfo>, String> {
+    state.party.ensure_unlocked("Replacing the queue").await?;
+    let session = get_queue_session_map(&state)
+        .await?
+        .remove(&name)
+        .ok_or_else(|| format!("No session named {}", name))?;
+
+    let index = usize::try_from(session.queue.current_index).ok();
+    state.queue.restore(session.queue).await;
+    // Saved before anything played, so there's nothing to pick up from yet
+    let Some(index) = index else {
+        let _ = state.audio.unload().await;
+        return Ok(None);
+    };
+    let Some(track) = state.queue.play_track_at(index).await else {
+        return Ok(None);
+    };
+
+    let start_at = Some(session.position).filter(|p| *p > 0.0);
+    play_resolved_at(&state, track.clone(), TrackChangeReason::User, start_at).await?;
+
+    println!("🔄 Switched to session {}", name);
+    Ok(Some(track))
+}
+
+#[tauri::command]
+async fn delete_session(name: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Deleting sessions").await?;
+    let mut sessions = get_queue_session_map(&state).await?;
+    sessions.remove(&name);
+    state.db.set_preference("queue_sessions", &sessions).await.map_err(|e| e.to_string())
+}
+
+// Replaces the queue with a playlist's tracks and plays the first one
+async fn start_playlist(state: &AppState, playlist_id: &str, reason: TrackChangeReason) -> Result<(), String> {
+    start_playlist_at(state, playlist_id, None, reason).await
+}
+
+// Queues the whole playlist and plays from `track_id`, or from the top without one
+async fn start_playlist_at(state: &AppState, playlist_id: &str, track_id: Option<&str>, reason: TrackChangeReason) -> Result<(), String> {
+    // Get all tracks from playlist
+    let tracks = state
+        .db
+        .get_playlist_tracks(playlist_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if tracks.is_empty() {
+        return Err("Playlist is empty".to_string());
+    }
+
+    // Convert to YTVideoInfo
+    let video_tracks: Vec<YTVideoInfo> = tracks.into_iter().map(YTVideoInfo::from).collect();
+    let index = match track_id {
+        Some(track_id) => video_tracks
+            .iter()
+            .position(|t| t.id == track_id)
+            .ok_or("Track isn't in this playlist")?,
+        None => 0,
+    };
+
+    // Clear queue and add all playlist tracks
+    state.queue.clear_queue().await;
+    state.queue.add_to_queue_batch(video_tracks.clone()).await;
+    state
+        .queue
+        .set_source_playlist(Some(playlist_id.to_string()), chrono::Utc::now().timestamp())
+        .await;
+
+    // Position the queue on the track being played
+    state.queue.set_current_index(index as i32).await;
+    play_resolved(state, video_tracks[index].clone(), reason).await
+}
+
+// Queues tracks related to the seed (by the same uploader) that weren't played recently, then plays on
+async fn start_radio(state: &AppState, seed: &YTVideoInfo) -> Result<(), String> {
+    let recent = state.queue.snapshot().await.play_history;
+    let mut tracks: Vec<YTVideoInfo> = state
+        .ytdlp
+        .search(format!("{} {}", seed.uploader, seed.title), true)
+        .await?
+        .into_iter()
+        .filter(|t| t.id != seed.id && !recent.contains(&t.id))
+        .collect();
+    demote_skipped(state, &mut tracks).await;
+
+    if tracks.is_empty() {
+        return Err("No related tracks found".to_string());
+    }
+
+    println!("📻 Radio queued {} track(s) after {}", tracks.len(), seed.title);
+    state.queue.add_to_queue_batch(tracks).await;
+    match state.queue.play_next().await {
+        Some(track) => play_resolved(state, track, TrackChangeReason::Auto).await,
+        None => Ok(()),
+    }
+}
+
+// Runs the configured end-of-queue action once auto-advance has no next track
+async fn handle_end_of_queue(app: &tauri::AppHandle, state: &AppState) -> Result<(), String> {
+    let last_track = state.audio.get_state().await.current_track;
+
+    match state.queue.get_end_of_queue_action().await {
+        EndOfQueueAction::Stop => {
+            println!("⏹️ No more tracks in queue");
+            Ok(())
+        }
+        EndOfQueueAction::Radio => match last_track {
+            Some(seed) => start_radio(state, &seed).await,
+            None => Ok(()),
+        },
+        EndOfQueueAction::ReplayQueue => match state.queue.play_track_at(0).await {
+            Some(track) => play_resolved(state, track, TrackChangeReason::Auto).await,
+            None => Ok(()),
+        },
+        EndOfQueueAction::FadeOutAndQuit => {
+            // Normally the fade already ran before the track ended, this covers tracks too short for it
+            println!("👋 Queue finished, quitting");
+            app.exit(0);
+            Ok(())
+        }
+        EndOfQueueAction::FallbackPlaylist(playlist_id) => start_playlist(state, &playlist_id, TrackChangeReason::Auto).await,
+    }
+}
+
+// Rebuilds the daily mixes and remembers when, returning the ids of the new playlists
+async fn refresh_daily_mixes(state: &AppState) -> Result<Vec<String>, String> {
+    let playlist_ids = daily_mix::generate_daily_mixes(&state.db).await?;
+    state
+        .db
+        .set_preference("daily_mixes_generated_at", &chrono::Utc::now().timestamp())
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(playlist_ids)
+}
+
+/// Rebuild the daily mix playlists now instead of waiting for the daily refresh
+#[tauri::command]
+async fn generate_daily_mixes(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    use tauri::Emitter;
+    let playlist_ids = refresh_daily_mixes(&state).await?;
+    let _ = app.emit("daily-mixes-updated", &playlist_ids);
+    Ok(playlist_ids)
+}
+
+// Writes the recap for the week that ended most recently, replacing any earlier one for it
+async fn refresh_weekly_recap(state: &AppState) -> Result<WeeklyRecap, String> {
+    let recap = weekly_recap::generate_weekly_recap(&state.db, weekly_recap::last_full_week()).await?;
+    state.db.save_weekly_recap(&recap).await.map_err(|e| e.to_string())?;
+    println!("📊 Generated the weekly recap for {}", recap.week_start);
+    Ok(recap)
+}
+
+/// Rebuild last week's recap now instead of waiting for the Monday job
+#[tauri::command]
+async fn generate_weekly_recap(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<WeeklyRecap, String> {
+    use tauri::Emitter;
+    let recap = refresh_weekly_recap(&state).await?;
+    let _ = app.emit("weekly-recap-ready", &recap);
+    Ok(recap)
+}
+
+#[tauri::command]
+async fn get_weekly_recaps(state: State<'_, AppState>) -> Result<Vec<WeeklyRecap>, String> {
+    state.db.get_weekly_recaps(WEEKLY_RECAPS_SHOWN).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_end_of_queue_action(state: State<'_, AppState>) -> Result<EndOfQueueAction, String> {
+    Ok(state.queue.get_end_of_queue_action().await)
+}
+
+#[tauri::command]
+async fn set_end_of_queue_action(action: EndOfQueueAction, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Changing what happens after the queue").await?;
+    save_setting(&state, "end_of_queue_action", &action).await?;
+    state.queue.set_end_of_queue_action(action).await;
+    Ok(())
+}
+
+/// Zip the dependency health report, anonymized settings, recent player events, download errors
+/// and yt-dlp and decoder output into one file to attach to a GitHub issue
+#[tauri::command]
+async fn generate_diagnostics_bundle(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    let health = diagnostics::DependencyHealth {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        ytdlp_version: YTDLPInstaller::get_version().await.ok(),
+        audio: state.audio.get_capabilities().await,
+        online: state.connectivity.is_online(),
+        metered: state.connectivity.is_metered(),
+        storage_problem: state.storage.problem(),
+        database_size: DatabaseManager::get_database_size(),
+        generated_at: chrono::Utc::now().timestamp(),
+    };
+    let downloads_dir = state.downloads.get_downloads_dir().await;
+    let bundle = diagnostics::DiagnosticsBundle {
+        health,
+        settings: diagnostics::collect_settings(&state.db, &downloads_dir).await?,
+        events: state.audio.get_player_events().await,
+        failed_downloads: state.downloads.get_failed_downloads().await,
+        logs: recent_log::recent(),
+    };
+
+    diagnostics::write_bundle(bundle, std::path::Path::new(&path)).await?;
+    println!("🩺 Wrote diagnostics bundle to {}", path);
+    Ok(())
+}
+
+#[tauri::command]
+async fn export_profile(path: String, include_downloads: bool, state: State<'_, AppState>) -> Result<(), String> {
+    let downloads = if include_downloads {
+        Some(state.downloads.get_downloaded_tracks().await)
+    } else {
+        None
+    };
+    crate::profile::export(&state.db, downloads, std::path::Path::new(&path)).await?;
+    println!("📦 Exported profile to {}", path);
+    Ok(())
+}
+
+// Applies saved settings to the running managers, at startup and after a profile import
+async fn restore_settings(state: &AppState) {
+    if let Ok(Some(max_gain)) = state.db.get_preference::<f32>("max_gain").await {
+        let _ = state.audio.set_max_gain(max_gain).await;
+    }
+    if let Ok(Some(limit)) = state.db.get_preference::<f32>("max_volume").await {
+        let _ = state.audio.set_max_volume(limit).await;
+    }
+    if let Ok(Some(limiter)) = state.db.get_preference::<LimiterSettings>("limiter").await {
+        let _ = state.audio.set_limiter(limiter).await;
+    }
+    if let Ok(Some(volume)) = state.db.get_preference::<f32>("volume").await {
+        let _ = state.audio.set_volume(volume).await;
+    }
+    if let Ok(Some(Some(lock))) = state.db.get_preference::<Option<PartyLock>>("party_mode").await {
+        state.party.restore(lock).await;
+        let _ = state.audio.set_volume_range(state.party.volume_range().await).await;
+    }
+    if let Ok(Some(quiet_hours)) = state.db.get_preference::<QuietHours>("quiet_hours").await {
+        let _ = state.audio.set_quiet_hours(quiet_hours).await;
+    }
+    if let Ok(trims) = get_trims(state).await {
+        state.audio.set_trims(trims).await;
+    }
+    if let Ok(Some(keywords)) = state.db.get_preference::<Vec<String>>("blocked_keywords").await {
+        state.ytdlp.set_blocked_keywords(keywords).await;
+    }
+    if let Ok(Some(seconds)) = state.db.get_preference::<f64>("buffer_target").await {
+        let _ = state.audio.set_buffer_target(seconds).await;
+    }
+    if let Ok(Some(enabled)) = state.db.get_preference::<bool>("prefer_builtin_decoder").await {
+        let _ = state.audio.set_prefer_builtin_decoder(enabled).await;
+    }
+    if let Ok(Some(enabled)) = state.db.get_preference::<bool>("keep_streamed_audio").await {
+        let _ = state.audio.set_keep_streamed_audio(enabled).await;
+    }
+    if let Ok(Some(settings)) = state.db.get_preference::<SpeedSettings>("speed_settings").await {
+        state.audio.set_speed_settings(settings).await;
+    }
+    if let Ok(Some(action)) = state.db.get_preference::<EndOfQueueAction>("end_of_queue_action").await {
+        state.queue.set_end_of_queue_action(action).await;
+    }
+    if let Ok(Some(cookies)) = state.db.get_preference::<CookieSource>("cookie_source").await {
+        state.ytdlp.set_cookies(Some(cookies)).await;
+    }
+    if let Ok(Some(options)) = state.db.get_preference::<YtdlpOptions>("ytdlp_options").await {
+        let _ = apply_ytdlp_options(state, options).await;
+    }
+
+    // The chosen output device, or the default one's audio profile
+    if let Ok(Some(device)) = state.db.get_preference::<Option<String>>("output_device").await {
+        let _ = state.audio.set_output_device(device).await;
+    } else if let Some(device) = state.audio.get_state().await.output_device {
+        let _ = apply_device_profile(state, &device).await;
+    }
+}
+
+// Saved settings are applied right away; downloads missing here start as one batch, or wait for a
+// connection when offline
+#[tauri::command]
+async fn import_profile(path: String, restore_downloads: bool, state: State<'_, AppState>) -> Result<ProfileImport, String> {
+    state.party.ensure_unlocked("Importing a profile").await?;
+    let (manifest, downloads) = crate::profile::import(&state.db, std::path::Path::new(&path)).await?;
+    println!("📦 Imported profile exported by YTAudioBar {}", manifest.app_version);
+
+    restore_settings(&state).await;
+    if let Ok(Some(settings)) = state.db.get_preference::<VolumeKeySettings>("volume_keys").await {
+        let is_playing = state.audio.get_state().await.is_playing;
+        state.media_keys.set_volume_keys(settings, is_playing).await;
+    }
+    if let Ok(Some(enabled)) = state.db.get_preference::<bool>("minimize_to_background").await {
+        state.tray.set_minimize_to_background(enabled);
+    }
+    if let Err(e) = load_telemetry_settings(&state).await {
+        eprintln!("⚠️ Failed to load telemetry settings: {}", e);
+    }
+    state.settings.reset().await;
+
+    let mut downloads_queued = 0;
+    if restore_downloads {
+        let mut missing = Vec::new();
+        for download in downloads {
+            if !state.downloads.is_downloaded(&download.video_info.id).await {
+                missing.push(download.video_info);
+            }
+        }
+        downloads_queued = start_downloads(&state, missing, None).await?;
+    }
+
+    Ok(ProfileImport {
+        app_version: manifest.app_version,
+        exported_at: manifest.exported_at,
+        downloads_queued,
+    })
+}
+
+// ===== DOWNLOAD COMMANDS =====
+
+// Imports a YouTube library playlist into its local playlist, adding only tracks not already there
+async fn import_library(state: &AppState, library: YouTubeLibrary) -> Result<LibraryImport, String> {
+    let videos = state.ytdlp.fetch_library(library).await?;
+
+    // Reuse the playlist from earlier imports unless the user has deleted it
+    let key = format!("library_playlist_{}", library.list_id());
+    let existing: Option<String> = state.db.get_preference(&key).await.map_err(|e| e.to_string())?;
+    let playlist_id = match existing {
+        Some(id) if state.db.get_playlist(&id).await.map_err(|e| e.to_string())?.is_some() => id,
+        _ => {
+            let id = state.db.create_playlist(library.playlist_name()).await.map_err(|e| e.to_string())?;
+            state.db.set_preference(&key, &id).await.map_err(|e| e.to_string())?;
+            id
+        }
+    };
+
+    let existing_ids = state.db.get_playlist_track_ids(&playlist_id).await.map_err(|e| e.to_string())?;
+    let mut new_ids = Vec::new();
+    for video in &videos {
+        if existing_ids.contains(&video.id) {
+            continue;
+        }
+        state.db.save_track(&Track::from(video.clone())).await.map_err(|e| e.to_string())?;
+        new_ids.push(video.id.clone());
+    }
+    state.db.add_tracks_to_playlist(&new_ids, &playlist_id).await.map_err(|e| e.to_string())?;
+
+    println!("📥 Imported {} new track(s) into {}", new_ids.len(), library.playlist_name());
+    Ok(LibraryImport {
+        library,
+        playlist_id,
+        added: new_ids.len(),
+        total: videos.len(),
+    })
+}
+
+#[tauri::command]
+async fn get_cookie_source(state: State<'_, AppState>) -> Result<Option<CookieSource>, String> {
+    Ok(state.ytdlp.get_cookies().await)
+}
+
+#[tauri::command]
+async fn set_cookie_source(source: Option<CookieSource>, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "cookie_source", &source).await?;
+    state.ytdlp.set_cookies(source).await;
+    Ok(())
+}
+
+// Hands the advanced yt-dlp options to everything that runs yt-dlp
+async fn apply_ytdlp_options(state: &AppState, options: YtdlpOptions) -> Result<(), String> {
+    state.audio.set_ytdlp_options(options.clone())?;
+    state.downloads.set_ytdlp_options(options.clone()).await;
+    state.ytdlp.set_options(options).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_ytdlp_options(state: State<'_, AppState>) -> Result<YtdlpOptions, String> {
+    Ok(state.ytdlp.get_options().await)
+}
+
+/// Save the advanced yt-dlp options; blank fields fall back to yt-dlp's own defaults, and extra
+/// arguments with flags that could run programs or move files are refused
+#[tauri::command]
+async fn set_ytdlp_options(options: YtdlpOptions, state: State<'_, AppState>) -> Result<(), String> {
+    let clean = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let options = YtdlpOptions {
+        user_agent: clean(options.user_agent),
+        player_client: clean(options.player_client),
+        po_token: clean(options.po_token),
+        search_args: clean(options.search_args),
+        stream_args: clean(options.stream_args),
+        download_args: clean(options.download_args),
+    };
+
+    if options.user_agent.as_ref().is_some_and(|ua| ua.contains(['\n', '\r'])) {
+        return Err("The user agent must be a single line".to_string());
+    }
+    // Separators inside these would inject further extractor args
+    let is_extractor_value = |value: &String| !value.contains([';', ' ', '\n', '\r']);
+    if !options.player_client.iter().all(is_extractor_value) {
+        return Err("Player clients are a comma-separated list like \"web,mweb\"".to_string());
+    }
+    if !options.po_token.iter().all(is_extractor_value) {
+        return Err("The PO token must look like \"web.gvs+TOKEN\"".to_string());
+    }
+    for extra in [&options.search_args, &options.stream_args, &options.download_args].into_iter().flatten() {
+        parse_extra_args(extra)?;
+    }
+
+    save_setting(&state, "ytdlp_options", &options).await?;
+    apply_ytdlp_options(&state, options).await
+}
+
+#[tauri::command]
+async fn import_youtube_library(library: YouTubeLibrary, state: State<'_, AppState>) -> Result<LibraryImport, String> {
+    import_library(&state, library).await
+}
+
+#[tauri::command]
+async fn get_library_sync(state: State<'_, AppState>) -> Result<Vec<YouTubeLibrary>, String> {
+    let libraries = state.db.get_preference("library_sync").await.map_err(|e| e.to_string())?;
+    Ok(libraries.unwrap_or_default())
+}
+
+#[tauri::command]
+async fn set_library_sync(libraries: Vec<YouTubeLibrary>, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "library_sync", &libraries).await
+}
+
+#[tauri::command]
+async fn download_track(
+    track: YTVideoInfo,
+    quality: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.telemetry.record(TelemetryEvent::Download, 1);
+    // The playing track may already be on disk from streaming it, unless a specific quality was asked for
+    if quality.is_none() {
+        match state.audio.claim_streamed_audio(&track.id) {
+            Some(StreamedAudioClaim::Ready(path)) => return state.downloads.save_streamed_audio(track, path).await,
+            Some(StreamedAudioClaim::Pending) => return state.downloads.await_streamed_audio(track).await,
+            None => {}
+        }
+    }
+    // Offline downloads wait in the failed list and start automatically once back online
+    if !state.connectivity.is_online() {
+        return state.downloads.defer_download(track, quality, "Waiting for a network connection").await;
+    }
+    state.downloads.download_track(track, quality).await
+}
+
+/// Start downloading many tracks at once, returning how many were started or queued
+#[tauri::command]
+async fn download_tracks(
+    tracks: Vec<YTVideoInfo>,
+    quality: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    state.telemetry.record(TelemetryEvent::Download, tracks.len() as u64);
+    start_downloads(&state, tracks, quality).await
+}
+
+// Starts tracks as one download batch, or waits for a connection with them when offline
+async fn start_downloads(state: &AppState, tracks: Vec<YTVideoInfo>, quality: Option<String>) -> Result<usize, String> {
+    if !state.connectivity.is_online() {
+        let count = tracks.len();
+        for track in tracks {
+            state
+                .downloads
+                .defer_download(track, quality.clone(), "Waiting for a network connection")
+                .await?;
+        }
+        return Ok(count);
+    }
+    Ok(state.downloads.download_tracks(tracks, quality).await)
+}
+
+#[tauri::command]
+async fn get_download_notifications(state: State<'_, AppState>) -> Result<bool, String> {
+    let enabled = state.db.get_preference("download_notifications").await.map_err(|e| e.to_string())?;
+    Ok(enabled.unwrap_or(true))
+}
+
+/// Whether finished downloads and playlist batches are announced, with a "Play now" button on Linux
+#[tauri::command]
+async fn set_download_notifications(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "download_notifications", &enabled).await
+}
+
+#[tauri::command]
+async fn get_fingerprinting(state: State<'_, AppState>) -> Result<FingerprintStatus, String> {
+    let enabled = state.db.get_preference("fingerprinting").await.map_err(|e| e.to_string())?;
+    let available = tokio::task::spawn_blocking(fingerprint::is_available).await.unwrap_or(false);
+    Ok(FingerprintStatus {
+        enabled: enabled.unwrap_or(false),
+        available,
+    })
+}
+
+#[tauri::command]
+async fn set_fingerprinting(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    if enabled && !tokio::task::spawn_blocking(fingerprint::is_available).await.unwrap_or(false) {
+        return Err("Fingerprinting needs fpcalc from Chromaprint. Install it and add it to PATH.".to_string());
+    }
+    save_setting(&state, "fingerprinting", &enabled).await
+}
+
+// Fingerprints a downloaded file and files it under the recording it matches, returning the
+// duplicate when it's another upload of something already in the library
+async fn fingerprint_download(state: &AppState, video_id: &str) -> Result<Option<RecordingDuplicate>, String> {
+    let Some(file_path) = state.downloads.get_downloaded_file_path(video_id).await else {
+        return Ok(None);
+    };
+    let (fingerprint, duration) = tokio::task::spawn_blocking(move || fingerprint::compute(std::path::Path::new(&file_path)))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let known = state
+        .db
+        .get_fingerprints_near(duration, fingerprint::MAX_DURATION_DIFFERENCE, video_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (group_id, fingerprint) = tokio::task::spawn_blocking(move || {
+        let group_id = fingerprint::find_match(&fingerprint, &known).map(|m| m.group_id.clone());
+        (group_id, fingerprint)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    state
+        .db
+        .save_fingerprint(&AudioFingerprint {
+            track_id: video_id.to_string(),
+            group_id: group_id.clone().unwrap_or_else(|| video_id.to_string()),
+            duration,
+            fingerprint,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match group_id {
+        Some(original_id) => Ok(Some(describe_duplicate(state, video_id, &original_id).await)),
+        None => Ok(None),
+    }
+}
+
+async fn describe_duplicate(state: &AppState, video_id: &str, original_id: &str) -> RecordingDuplicate {
+    let title_of = |id: String| async move {
+        if let Some(info) = state.downloads.get_downloaded_video_info(&id).await {
+            return info.title;
+        }
+        match state.db.get_track(&id).await {
+            Ok(Some(track)) => track.custom_title.unwrap_or(track.title),
+            _ => id,
+        }
+    };
+    RecordingDuplicate {
+        video_id: video_id.to_string(),
+        title: title_of(video_id.to_string()).await,
+        original_id: original_id.to_string(),
+        original_title: title_of(original_id.to_string()).await,
+    }
+}
+
+// Fingerprints freshly downloaded tracks when enabled, warning about re-uploads of known recordings
+async fn fingerprint_new_downloads(handle: tauri::AppHandle, state: AppState, video_ids: Vec<String>) {
+    use tauri::Emitter;
+    let enabled: bool = state.db.get_preference("fingerprinting").await.ok().flatten().unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    for video_id in video_ids {
+        match fingerprint_download(&state, &video_id).await {
+            Ok(Some(duplicate)) => {
+                println!("🧬 {} is the same recording as {}", duplicate.title, duplicate.original_title);
+                let _ = handle.emit("recording-duplicate-found", &duplicate);
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("⚠️ Failed to fingerprint {}: {}", video_id, e),
+        }
+    }
+}
+
+/// Fingerprint every download that hasn't been yet, returning the re-uploads found
+#[tauri::command]
+async fn fingerprint_downloads(state: State<'_, AppState>) -> Result<Vec<RecordingDuplicate>, String> {
+    let done = state.db.get_fingerprinted_ids().await.map_err(|e| e.to_string())?;
+    let mut duplicates = Vec::new();
+    for video_id in state.downloads.get_downloaded_ids().await {
+        if done.contains(&video_id) {
+            continue;
+        }
+        match fingerprint_download(&state, &video_id).await {
+            Ok(Some(duplicate)) => duplicates.push(duplicate),
+            Ok(None) => {}
+            Err(e) => eprintln!("⚠️ Failed to fingerprint {}: {}", video_id, e),
+        }
+    }
+    Ok(duplicates)
+}
+
+/// Downloads that are another upload of a recording already in the library
+#[tauri::command]
+async fn get_recording_duplicates(state: State<'_, AppState>) -> Result<Vec<RecordingDuplicate>, String> {
+    let groups = state.db.get_recording_groups().await.map_err(|e| e.to_string())?;
+    let mut duplicates = Vec::new();
+    for (video_id, original_id) in groups {
+        if state.downloads.is_downloaded(&video_id).await {
+            duplicates.push(describe_duplicate(&state, &video_id, &original_id).await);
+        }
+    }
+    duplicates.sort_by(|a, b| a.original_title.cmp(&b.original_title));
+    Ok(duplicates)
+}
+
+// Keeps one upload per recording so re-uploads don't weigh a recording twice in a shuffle
+async fn dedupe_recordings(state: &AppState, ids: Vec<String>) -> Vec<String> {
+    let groups = state.db.get_recording_groups().await.unwrap_or_default();
+    if groups.is_empty() {
+        return ids;
+    }
+    let mut seen = HashSet::new();
+    ids.into_iter()
+        .filter(|id| seen.insert(groups.get(id).unwrap_or(id).clone()))
+        .collect()
+}
+
+// Notifies that downloads finished, playing them from the "Play now" action on Linux: a single
+// track plays on its own, a batch replaces the queue in download order. Other platforms don't
+// report notification clicks back, so they get a plain notification
+async fn notify_downloads_finished(handle: tauri::AppHandle, state: AppState, video_ids: Vec<String>) {
+    if !state.db.get_preference::<bool>("download_notifications").await.ok().flatten().unwrap_or(true) {
+        return;
+    }
+
+    let mut tracks = Vec::new();
+    for video_id in &video_ids {
+        if let Ok(Some(track)) = state.db.get_track(video_id).await {
+            tracks.push(YTVideoInfo::from(track));
+        }
+    }
+    let (title, body) = match tracks.as_slice() {
+        [] => return,
+        [track] => ("Download finished".to_string(), format!("{} – {}", track.display_uploader(), track.display_title())),
+        tracks => (
+            format!("{} downloads finished", tracks.len()),
+            tracks.iter().map(|track| track.display_title()).collect::<Vec<_>>().join(", "),
+        ),
+    };
+
+    #[cfg(target_os = "linux")]
+    let shown = {
+        let _ = handle;
+        let on_play = move || {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = play_downloaded(&state, tracks).await {
+                    eprintln!("⚠️ Failed to play the downloaded tracks: {}", e);
+                }
+            });
+        };
+        tokio::task::spawn_blocking(move || notifications::show_with_play_action(&title, &body, on_play)).await
+    };
+    #[cfg(not(target_os = "linux"))]
+    let shown = tokio::task::spawn_blocking(move || notifications::show(&handle, &title, &body)).await;
+    if let Ok(Err(e)) = shown {
+        eprintln!("⚠️ {}", e);
+    }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+async fn play_downloaded(state: &AppState, mut tracks: Vec<YTVideoInfo>) -> Result<(), String> {
+    if tracks.len() == 1 {
+        return play_resolved(state, tracks.remove(0), TrackChangeReason::User).await;
+    }
+
+    state.party.ensure_unlocked("Replacing the queue").await?;
+    state.queue.clear_queue().await;
+    state.queue.add_to_queue_batch(tracks.clone()).await;
+    state.queue.set_current_index(0).await;
+    match tracks.into_iter().next() {
+        Some(first) => play_resolved(state, first, TrackChangeReason::User).await,
+        None => Ok(()),
+    }
+}
+
+#[tauri::command]
+async fn get_active_downloads(state: State<'_, AppState>) -> Result<Vec<crate::download_manager::DownloadProgress>, String> {
+    Ok(state.downloads.get_active_downloads().await)
+}
+
+#[tauri::command]
+async fn get_downloaded_tracks(state: State<'_, AppState>) -> Result<Vec<crate::download_manager::DownloadedTrack>, String> {
+    Ok(state.downloads.get_downloaded_tracks().await)
+}
+
+#[tauri::command]
+async fn get_storage_used(state: State<'_, AppState>) -> Result<i64, String> {
+    Ok(state.downloads.get_storage_used().await)
+}
+
+#[tauri::command]
+async fn is_track_downloaded(video_id: String, state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.downloads.is_downloaded(&video_id).await)
+}
+
+#[tauri::command]
+async fn delete_download(video_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Deleting downloads").await?;
+    // Files are staged rather than removed so the deletion can be undone
+    let staged_files = state.downloads.stage_delete(&video_id).await?;
+    state
+        .undo
+        .push(UndoAction::DeleteDownloads {
+            downloads: vec![(video_id, staged_files)],
+        })
+        .await;
+    Ok(())
+}
+
+/// Delete many downloads as one action, undone together. Returns how many were deleted.
+#[tauri::command]
+async fn delete_downloads(video_ids: Vec<String>, state: State<'_, AppState>) -> Result<usize, String> {
+    state.party.ensure_unlocked("Deleting downloads").await?;
+    let mut downloads = Vec::new();
+    let mut errors = Vec::new();
+    for video_id in video_ids {
+        match state.downloads.stage_delete(&video_id).await {
+            Ok(staged_files) => downloads.push((video_id, staged_files)),
+            Err(e) => errors.push(format!("{}: {}", video_id, e)),
+        }
+    }
+
+    let deleted = downloads.len();
+    if deleted > 0 {
+        state.undo.push(UndoAction::DeleteDownloads { downloads }).await;
+    }
+
+    if errors.is_empty() {
+        Ok(deleted)
+    } else {
+        Err(format!("Deleted {} downloads, {} failed: {}", deleted, errors.len(), errors.join("; ")))
+    }
+}
+
+/// Re-probe downloaded files and return the IDs of those cut short
+#[tauri::command]
+async fn verify_downloads(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.downloads.verify_downloads().await)
+}
+
+#[tauri::command]
+async fn redownload_track(video_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.downloads.redownload(&video_id).await
+}
+
+#[tauri::command]
+async fn get_failed_downloads(
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::download_manager::FailedDownload>, String> {
+    Ok(state.downloads.get_failed_downloads().await)
+}
+
+/// Restart every failed download, returning how many were restarted
+#[tauri::command]
+async fn retry_all_failed(state: State<'_, AppState>) -> Result<usize, String> {
+    Ok(state.downloads.retry_all_failed().await)
+}
+
+#[tauri::command]
+async fn cancel_download(video_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.downloads.cancel_download(&video_id).await
+}
+
+/// High priority keeps a download at full speed while playback streams; normal ones give way
+#[tauri::command]
+async fn set_download_priority(
+    video_id: String,
+    priority: DownloadPriority,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.downloads.set_download_priority(&video_id, priority).await
+}
+
+// Find caption lines in a downloaded track containing `text`; use `seek_to` with a line's start to jump there
+#[tauri::command]
+async fn search_in_track(
+    video_id: String,
+    text: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::captions::CaptionLine>, String> {
+    let captions_path = state.downloads.get_captions_path(&video_id).await?;
+    let content = std::fs::read_to_string(&captions_path).map_err(|e| e.to_string())?;
+    let lines = crate::captions::parse_vtt(&content);
+    Ok(crate::captions::search(&lines, &text))
+}
+
+// ===== BOOKMARK COMMANDS =====
+
+#[tauri::command]
+async fn add_bookmark(
+    video_id: String,
+    position: f64,
+    note: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Bookmark, String> {
+    // Bookmarks are usually made on the playing track; save it so the bookmark can be played later
+    let known = state.db.get_track(&video_id).await.map_err(|e| e.to_string())?.is_some();
+    if !known {
+        if let Some(track) = state.audio.get_state().await.current_track.filter(|t| t.id == video_id) {
+            state.db.save_track(&Track::from(track)).await.map_err(|e| e.to_string())?;
+        }
+    }
+
+    state
+        .db
+        .add_bookmark(&video_id, position.max(0.0), note.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_bookmarks(video_id: String, state: State<'_, AppState>) -> Result<Vec<Bookmark>, String> {
+    state.db.get_bookmarks(&video_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn delete_bookmark(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Deleting bookmarks").await?;
+    state.db.delete_bookmark(&id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn play_bookmark(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let bookmark = state
+        .db
+        .get_bookmark(&id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Bookmark not found".to_string())?;
+
+    let current_track = state.audio.get_state().await.current_track;
+    if current_track.is_some_and(|t| t.id == bookmark.track_id) {
+        return state.audio.seek(bookmark.position).await;
+    }
+
+    let track = match state.db.get_track(&bookmark.track_id).await.map_err(|e| e.to_string())? {
+        Some(track) => YTVideoInfo::from(track),
+        None => state
+            .downloads
+            .get_downloaded_video_info(&bookmark.track_id)
+            .await
+            .ok_or_else(|| "Track not found".to_string())?,
+    };
+
+    play_resolved_at(&state, track, TrackChangeReason::User, Some(bookmark.position)).await
+}
+
+// ===== CHANNEL RULE COMMANDS =====
+
+#[tauri::command]
+async fn get_channel_rules(state: State<'_, AppState>) -> Result<Vec<ChannelRule>, String> {
+    state.db.get_channel_rules().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_channel_rule(uploader: String, state: State<'_, AppState>) -> Result<Option<ChannelRule>, String> {
+    state.db.get_channel_rule(&uploader).await.map_err(|e| e.to_string())
+}
+
+/// Save the defaults for one uploader, replacing any earlier rule. A rule that changes
+/// nothing is deleted instead.
+#[tauri::command]
+async fn save_channel_rule(rule: ChannelRule, state: State<'_, AppState>) -> Result<(), String> {
+    if rule.uploader.trim().is_empty() {
+        return Err("Channel rules need an uploader".to_string());
+    }
+    if rule.playback_rate.is_some_and(|rate| !(0.25..=2.0).contains(&rate)) {
+        return Err("Speed must be between 0.25x and 2x".to_string());
+    }
+
+    if rule.playback_rate.is_none() && !rule.auto_download && rule.playlist_id.is_none() {
+        return state.db.delete_channel_rule(&rule.uploader).await.map_err(|e| e.to_string());
+    }
+    state.db.save_channel_rule(&rule).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn delete_channel_rule(uploader: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.db.delete_channel_rule(&uploader).await.map_err(|e| e.to_string())
+}
+
+// ===== UNDO COMMANDS =====
+
+#[tauri::command]
+async fn undo_last_action(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    let Some(action) = state.undo.take().await else {
+        return Ok(None);
+    };
+    let description = action.describe();
+
+    match action {
+        UndoAction::DeletePlaylist { playlist, memberships } => {
+            state
+                .db
+                .restore_playlist(&playlist, &memberships)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        UndoAction::RemoveTracks { playlist_id, memberships } => {
+            state
+                .db
+                .restore_memberships(&playlist_id, &memberships)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        UndoAction::ClearQueue { snapshot } => {
+            state.queue.restore(snapshot).await;
+        }
+        UndoAction::DeleteDownloads { downloads } => {
+            for (video_id, staged_files) in downloads {
+                state.downloads.restore_staged(&video_id, &staged_files).await?;
+            }
+        }
+    }
+
+    println!("↩️ {}", description);
+    Ok(Some(description))
+}
+
+// ===== SETTINGS COMMANDS =====
+
+#[tauri::command]
+async fn get_downloads_directory(state: State<'_, AppState>) -> Result<String, String> {
+    Ok(state.downloads.get_downloads_directory().await)
+}
+
+#[tauri::command]
+async fn set_downloads_directory(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    use std::path::PathBuf;
+    let path_buf = PathBuf::from(path);
+    state.downloads.set_downloads_dir(path_buf.clone()).await?;
+    state.settings.record("downloads_directory", &path_buf).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_downloads_watcher_enabled(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.downloads.is_watcher_enabled().await)
+}
+
+#[tauri::command]
+async fn set_downloads_watcher_enabled(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "downloads_watcher", &enabled).await?;
+    state.downloads.set_watcher_enabled(enabled).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn reconcile_downloads(
+    state: State<'_, AppState>,
+) -> Result<crate::download_manager::DownloadsChange, String> {
+    Ok(state.downloads.reconcile().await)
+}
+
+#[tauri::command]
+async fn relink_downloads(
+    folder: String,
+    state: State<'_, AppState>,
+) -> Result<crate::download_manager::RelinkReport, String> {
+    use std::path::PathBuf;
+    state.downloads.relink_downloads(PathBuf::from(folder)).await
+}
+
+#[tauri::command]
+async fn clean_up_downloads(state: State<'_, AppState>) -> Result<crate::download_manager::CleanupReport, String> {
+    Ok(state.downloads.clean_up_orphans().await)
+}
+
+#[tauri::command]
+async fn get_audio_quality(state: State<'_, AppState>) -> Result<String, String> {
+    Ok(state.downloads.get_audio_quality().await)
+}
+
+#[tauri::command]
+async fn set_audio_quality(quality: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.downloads.set_audio_quality(quality.clone()).await?;
+    state.settings.record("audio_quality", &quality).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_data_usage_breakdown(state: State<'_, AppState>) -> Result<DataUsageBreakdown, String> {
+    Ok(DataUsageBreakdown {
+        search_cache: AppData::directory_size_async(AppData::get_search_cache_dir()).await,
+        thumbnails: AppData::directory_size_async(AppData::get_thumbnails_dir()).await,
+        downloads: state.downloads.get_storage_used().await,
+        database: DatabaseManager::get_database_size(),
+    })
+}
+
+// Runs database maintenance, streaming progress and remembering when it last completed
+async fn maintain_database(app: &tauri::AppHandle, state: &AppState) -> Result<Option<MaintenanceReport>, String> {
+    use tauri::Emitter;
+    let report = state
+        .db
+        .run_maintenance(|progress| {
+            let _ = app.emit("db-maintenance-progress", &progress);
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(report) = &report {
+        state
+            .db
+            .set_preference("db_maintained_at", &report.completed_at)
+            .await
+            .map_err(|e| e.to_string())?;
+        if !report.integrity_ok {
+            eprintln!("⚠️ Database integrity check found problems: {:?}", report.problems);
+        }
+    }
+    Ok(report)
+}
+
+/// Check database integrity, rebuild indexes and vacuum, emitting `db-maintenance-progress`
+#[tauri::command]
+async fn run_db_maintenance(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<MaintenanceReport, String> {
+    maintain_database(&app, &state)
+        .await?
+        .ok_or_else(|| "Database maintenance is already running".to_string())
+}
+
+/// True when running as a normal window, either from `--window` or because no tray is available
+#[tauri::command]
+async fn is_window_mode(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.tray.is_window_mode())
+}
+
+#[tauri::command]
+async fn get_minimize_to_background(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.tray.minimizes_to_background())
+}
+
+/// In window mode, whether closing the window keeps the app playing in the background
+#[tauri::command]
+async fn set_minimize_to_background(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "minimize_to_background", &enabled).await?;
+    state.tray.set_minimize_to_background(enabled);
+    Ok(())
+}
+
+/// The current full or read-only storage problem, None while everything can be written
+#[tauri::command]
+async fn get_storage_problem(state: State<'_, AppState>) -> Result<Option<StorageProblem>, String> {
+    Ok(state.storage.problem())
+}
+
+/// The OS dark mode and high contrast settings, also sent as `system-theme-changed` when they change
+#[tauri::command]
+async fn get_system_theme() -> Result<SystemTheme, String> {
+    tokio::task::spawn_blocking(system_theme::detect)
+        .await
+        .map_err(|e| format!("Failed to read the system theme: {}", e))
+}
+
+#[tauri::command]
+async fn reset_app_data(scope: DataScope, state: State<'_, AppState>) -> Result<(), String> {
+    state.party.ensure_unlocked("Resetting app data").await?;
+    let everything = scope == DataScope::Everything;
+
+    if everything || scope == DataScope::SearchCache {
+        AppData::clear_directory(&AppData::get_search_cache_dir())?;
+    }
+    if everything || scope == DataScope::Thumbnails {
+        AppData::clear_directory(&AppData::get_thumbnails_dir())?;
+    }
+    if everything || scope == DataScope::Downloads {
+        state.downloads.delete_all_downloads().await?;
+    }
+    if everything || scope == DataScope::Database {
+        state.db.reset().await.map_err(|e| e.to_string())?;
+        state.ytdlp.set_cookies(None).await;
+        state.queue.clear_queue().await;
+        state.settings.reset().await;
+    }
+
+    println!("🧹 Reset app data: {:?}", scope);
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_app_version() -> Result<String, String> {
+    Ok(env!("CARGO_PKG_VERSION").to_string())
+}
+
+// Loads the saved opt-in and endpoint into the telemetry manager
+async fn load_telemetry_settings(state: &AppState) -> Result<(), String> {
+    let enabled = state.db.get_preference::<bool>("telemetry_enabled").await.map_err(|e| e.to_string())?;
+    let endpoint = state.db.get_preference::<String>("telemetry_endpoint").await.map_err(|e| e.to_string())?;
+    state.telemetry.set_enabled(enabled.unwrap_or(false));
+    state.telemetry.set_endpoint(endpoint);
+    Ok(())
+}
+
+/// Whether usage metrics are on, where they go, and a preview of the next upload
+#[tauri::command]
+async fn get_telemetry_status(state: State<'_, AppState>) -> Result<TelemetryStatus, String> {
+    Ok(TelemetryStatus {
+        enabled: state.telemetry.is_enabled(),
+        endpoint: state.telemetry.get_endpoint(),
+        preview: state.telemetry.preview(),
+    })
+}
+
+#[tauri::command]
+async fn set_telemetry_enabled(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "telemetry_enabled", &enabled).await?;
+    state.telemetry.set_enabled(enabled);
+    Ok(())
+}
+
+/// Where opted-in usage counts are posted; an empty endpoint keeps them on this computer
+#[tauri::command]
+async fn set_telemetry_endpoint(endpoint: String, state: State<'_, AppState>) -> Result<(), String> {
+    let endpoint = Some(endpoint.trim().to_string()).filter(|e| !e.is_empty());
+    if let Some(url) = &endpoint {
+        if !url.starts_with("https://") {
+            return Err("The telemetry endpoint must be an https:// URL".to_string());
+        }
+    }
+    save_setting(&state, "telemetry_endpoint", &endpoint).await?;
+    state.telemetry.set_endpoint(endpoint);
+    Ok(())
+}
+
+/// Look for a newer release on GitHub, returning its version and release notes
+#[tauri::command]
+async fn check_app_update(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<Option<AppUpdate>, String> {
+    state.updater.check(&app).await
+}
+
+/// Download the update found by `check_app_update`, emitting `app-update-progress`
+#[tauri::command]
+async fn download_app_update(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    use tauri::Emitter;
+    state
+        .updater
+        .download(|progress| {
+            let _ = app.emit("app-update-progress", &progress);
+        })
+        .await
+}
+
+/// Install the downloaded update; it applies on the next restart
+#[tauri::command]
+async fn install_app_update(state: State<'_, AppState>) -> Result<(), String> {
+    state.updater.install().await?;
+    println!("✅ Update installed! Will apply on next restart.");
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_auto_check_updates(state: State<'_, AppState>) -> Result<bool, String> {
+    let enabled = state.db.get_preference("auto_check_updates").await.map_err(|e| e.to_string())?;
+    Ok(enabled.unwrap_or(true))
+}
+
+#[tauri::command]
+async fn set_auto_check_updates(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    save_setting(&state, "auto_check_updates", &enabled).await
 }
 
 // ===== MEDIA KEY COMMANDS =====
@@ -412,136 +3357,810 @@ async fn clear_media_info(state: State<'_, AppState>) -> Result<(), String> {
     Ok(())
 }
 
-// Silent auto-update function (like macOS Sparkle)
-async fn check_for_updates_silently(app: tauri::AppHandle) {
-    use tauri_plugin_updater::UpdaterExt;
+// `--headless` runs without the webview; the app is driven by the tray menu, media keys, and
+// `--action=` launches, which single-instance forwards to the running app
+fn is_headless() -> bool {
+    std::env::args().any(|arg| arg == "--headless")
+}
+
+// `--window` shows a regular window instead of the tray popup, also used when no tray is available
+fn is_window_flag() -> bool {
+    std::env::args().any(|arg| arg == "--window")
+}
+
+// Without a frontend, media keys and the OS media overlay have to be handled in the backend
+fn setup_headless_controls(app: &tauri::AppHandle, state: AppState) {
+    use tauri::Listener;
+
+    let media_key_events = [
+        "media-key-play",
+        "media-key-pause",
+        "media-key-toggle",
+        "media-key-next",
+        "media-key-previous",
+        "media-key-stop",
+        "media-key-seek",
+        "media-key-seek-to",
+        "media-key-replay",
+    ];
+
+    for event_name in media_key_events {
+        let state = state.clone();
+        app.listen(event_name, move |event| {
+            let state = state.clone();
+            let payload = event.payload().to_string();
+            tauri::async_runtime::spawn(async move {
+                let is_playing = state.audio.get_state().await.is_playing;
+                let result = match event_name {
+                    "media-key-play" if !is_playing => state.audio.toggle_play_pause().await,
+                    "media-key-pause" => state.audio.pause().await,
+                    "media-key-toggle" => state.audio.toggle_play_pause().await,
+                    "media-key-next" => skip_to_next(&state, TrackChangeReason::MediaKey).await.map(|_| ()),
+                    "media-key-previous" => skip_to_previous(&state, TrackChangeReason::MediaKey).await.map(|_| ()),
+                    "media-key-stop" => match state.party.ensure_unlocked("Stopping playback").await {
+                        Ok(()) => state.audio.stop().await,
+                        Err(e) => Err(e),
+                    },
+                    "media-key-seek" => {
+                        let offset: f64 = serde_json::from_str(&payload).unwrap_or(0.0);
+                        let position = state.audio.get_state().await.current_position;
+                        state.audio.seek(position + offset).await
+                    }
+                    "media-key-seek-to" => {
+                        let position: f64 = serde_json::from_str(&payload).unwrap_or(0.0);
+                        state.audio.seek(position).await
+                    }
+                    "media-key-replay" => state.audio.replay(DEFAULT_REPLAY_SECONDS).await,
+                    _ => Ok(()),
+                };
+                if let Err(e) = result {
+                    eprintln!("⚠️ Failed to handle {}: {}", event_name, e);
+                }
+            });
+        });
+    }
+
+    // Keep the OS media overlay in sync, the frontend normally does this
+    let last_track_id: Arc<tokio::sync::Mutex<Option<String>>> = Arc::new(tokio::sync::Mutex::new(None));
+    app.listen("playback-state-changed", move |event| {
+        let Ok(audio_state) = serde_json::from_str::<AudioState>(event.payload()) else {
+            return;
+        };
+        let state = state.clone();
+        let last_track_id = Arc::clone(&last_track_id);
+        tauri::async_runtime::spawn(async move {
+            let Some(track) = audio_state.current_track else {
+                state.media_keys.clear().await;
+                return;
+            };
+
+            let mut last_track_id = last_track_id.lock().await;
+            if last_track_id.as_deref() != Some(track.id.as_str()) {
+                state
+                    .media_keys
+                    .update_metadata(track.display_title().to_string(), track.display_uploader().to_string(), audio_state.duration)
+                    .await;
+                *last_track_id = Some(track.id);
+            }
+            state
+                .media_keys
+                .update_playback_state(audio_state.is_playing, audio_state.current_position, audio_state.duration)
+                .await;
+        });
+    });
+}
+
+// Silent auto-update (like macOS Sparkle): announce the release, then download and install it
+async fn update_silently(app: &tauri::AppHandle, state: &AppState) -> Result<(), String> {
+    use tauri::Emitter;
+    let Some(update) = state.updater.check(app).await? else {
+        println!("✅ App is up to date");
+        return Ok(());
+    };
+    if state.updater.installed_version().await.as_ref() == Some(&update.version) {
+        return Ok(());
+    }
+
+    println!("🔄 Update available: {} -> {}", update.current_version, update.version);
+    let _ = app.emit("app-update-available", &update);
+
+    println!("📥 Downloading update...");
+    state.updater.download(|_| {}).await?;
+    state.updater.install().await?;
+    println!("✅ Update installed! Will apply on next restart.");
+    let _ = app.emit("app-update-installed", &update);
+    Ok(())
+}
+
+// Opens the database and creates every manager. Runs once the single-instance check has passed,
+// so a second launch never touches the data directory or opens the audio device
+async fn create_app_state() -> AppState {
+    // Before anything opens files at the new locations
+    AppData::migrate_legacy_layout();
+
+    // Initialize database
+    let db = DatabaseManager::new()
+        .await
+        .expect("Failed to initialize database");
+
+    // Create app state
+    let db = Arc::new(db);
+    let audio_manager = Arc::new(AudioManager::new());
+    let storage_monitor = Arc::new(StorageMonitor::new());
+    let rate_limiter = Arc::new(RateLimiter::new());
+    let bandwidth_governor = Arc::new(BandwidthGovernor::new());
+    let download_manager = Arc::new(DownloadManager::new(
+        Arc::clone(&db),
+        Arc::clone(&storage_monitor),
+        Arc::clone(&rate_limiter),
+        Arc::clone(&bandwidth_governor),
+    ));
+    let media_key_manager = Arc::new(MediaKeyManager::new());
+    let queue_manager = Arc::new(QueueManager::new());
+    let ytdlp_manager = Arc::new(YTDLPManager::new(Arc::clone(&rate_limiter)));
+    let guest_queue = Arc::new(GuestQueue::new(Arc::clone(&ytdlp_manager), Arc::clone(&queue_manager)));
+    let duration_fixer = Arc::new(DurationFixer::new(
+        Arc::clone(&db),
+        Arc::clone(&ytdlp_manager),
+        Arc::clone(&queue_manager),
+    ));
+    let prefetcher = Arc::new(MetadataPrefetcher::new(Arc::clone(&db), Arc::clone(&duration_fixer)));
+    AppState {
+        audio: audio_manager,
+        queue: queue_manager,
+        db,
+        ytdlp: ytdlp_manager,
+        downloads: download_manager,
+        media_keys: media_key_manager,
+        undo: Arc::new(UndoManager::new()),
+        tray: Arc::new(TrayManager::new()),
+        connectivity: Arc::new(ConnectivityMonitor::new()),
+        storage: storage_monitor,
+        bandwidth: bandwidth_governor,
+        artwork: Arc::new(ArtworkManager::new()),
+        prefetcher,
+        durations: duration_fixer,
+        timers: Arc::new(TimerManager::new()),
+        updater: Arc::new(AppUpdater::new()),
+        telemetry: Arc::new(TelemetryManager::new()),
+        rate_limiter,
+        party: Arc::new(PartyMode::new()),
+        guests: guest_queue,
+        settings: Arc::new(SettingsSync::new()),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    tauri::Builder::default()
+        // Must come first: a second launch exits here, before any state is created, and the running
+        // instance shows its window
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            // Jump list entries and player actions do something; a plain launch brings the window back
+            let handled = [jump_list::PLAY_TRACK_ARG, jump_list::PLAY_PLAYLIST_ARG, player_action::ACTION_ARG];
+            if args.iter().any(|arg| handled.iter().any(|prefix| arg.starts_with(prefix))) {
+                let state = app.state::<AppState>().inner().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = handle_launch_args(&state, &args).await {
+                        eprintln!("⚠️ Failed to handle launch arguments: {}", e);
+                    }
+                });
+                return;
+            }
+            println!("🪟 Another launch detected, showing the existing window");
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.show().and_then(|_| window.set_focus());
+            } else {
+                println!("🖥️ Running headless, launch with --action=\"<action>\" to control playback");
+            }
+        }))
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .setup(move |app| {
+            // Blocks launch until the database is open; commands need the state from the start
+            let app_state = tokio::task::block_in_place(|| tauri::async_runtime::block_on(create_app_state()));
+            // Count crashes for opted-in users; the count is saved before the panic aborts the app
+            let telemetry = Arc::clone(&app_state.telemetry);
+            let default_hook = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                telemetry.record_crash();
+                default_hook(info);
+            }));
+
+            let audio_manager = Arc::clone(&app_state.audio);
+            let rate_limiter = Arc::clone(&app_state.rate_limiter);
+            let prefetcher = Arc::clone(&app_state.prefetcher);
+            let download_manager = Arc::clone(&app_state.downloads);
+            app.manage(app_state);
+
+            // Set app handle in audio manager for events
+            let handle = app.handle().clone();
+            let audio_clone = Arc::clone(&audio_manager);
+            tauri::async_runtime::spawn(async move {
+                audio_clone.set_app_handle(handle).await;
+            });
+
+            // Set app handle in the rate limiter so the UI hears about cooldowns
+            let handle = app.handle().clone();
+            let limiter_clone = Arc::clone(&rate_limiter);
+            tauri::async_runtime::spawn(async move {
+                limiter_clone.set_app_handle(handle).await;
+            });
+
+            // Set app handle in settings sync so every window hears about changed settings
+            let handle = app.handle().clone();
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                state.settings.set_app_handle(handle).await;
+            });
+
+            // Set app handle in the metadata prefetcher so lists hear about fetched thumbnails
+            let handle = app.handle().clone();
+            let prefetcher_clone = Arc::clone(&prefetcher);
+            tauri::async_runtime::spawn(async move {
+                prefetcher_clone.set_app_handle(handle).await;
+            });
+
+            // Set app handle in the duration fixer, then look up saved tracks still showing 0:00
+            let handle = app.handle().clone();
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                state.durations.set_app_handle(handle).await;
+                let failed_before = chrono::Utc::now().timestamp() - FAILED_RETRY_AFTER.as_secs() as i64;
+                match state.db.get_track_ids_missing_duration(DURATION_FIX_STARTUP_LIMIT, failed_before).await {
+                    Ok(ids) => state.durations.enqueue(ids).await,
+                    Err(e) => eprintln!("⚠️ Failed to list tracks missing a duration: {}", e),
+                }
+            });
+
+            // Let the host hear about guest requests, and reopen the guest queue if it was on
+            let handle = app.handle().clone();
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                state.guests.set_app_handle(handle).await;
+                if let Ok(Some(settings)) = state.db.get_preference::<GuestQueueSettings>("guest_queue").await {
+                    if let Err(e) = state.guests.set_settings(settings).await {
+                        eprintln!("⚠️ Failed to start the guest queue: {}", e);
+                    }
+                }
+            });
+
+            // Set app handle in download manager, initialize existing downloads and keep the folder tidy
+            let handle = app.handle().clone();
+            let download_clone = Arc::clone(&download_manager);
+            let db = app.state::<AppState>().db.clone();
+            tauri::async_runtime::spawn(async move {
+                download_clone.set_app_handle(handle).await;
+                // The watcher is on unless it was turned off in an earlier session
+                if let Ok(Some(false)) = db.get_preference::<bool>("downloads_watcher").await {
+                    download_clone.set_watcher_enabled(false).await;
+                }
+                download_clone.initialize().await;
+
+                let mut interval = tokio::time::interval(DOWNLOAD_CLEANUP_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    download_clone.clean_up_orphans().await;
+                }
+            });
+
+            // Restore saved playback settings, the output device and yt-dlp's cookies and options
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                restore_settings(&state).await;
+            });
+
+            // Periodically sync YouTube libraries into local playlists
+            let handle = app.handle().clone();
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(LIBRARY_SYNC_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let libraries: Vec<YouTubeLibrary> = state
+                        .db
+                        .get_preference("library_sync")
+                        .await
+                        .ok()
+                        .flatten()
+                        .unwrap_or_default();
+
+                    for library in libraries {
+                        match import_library(&state, library).await {
+                            Ok(import) if import.added > 0 => {
+                                use tauri::Emitter;
+                                let _ = handle.emit("library-synced", &import);
+                            }
+                            Ok(_) => {}
+                            Err(e) => eprintln!("⚠️ Library sync failed for {}: {}", library.playlist_name(), e),
+                        }
+                    }
+                }
+            });
+
+            // Rebuild the daily mixes once a day
+            let handle = app.handle().clone();
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(DAILY_MIX_CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let generated_at = state
+                        .db
+                        .get_preference::<i64>("daily_mixes_generated_at")
+                        .await
+                        .ok()
+                        .flatten()
+                        .unwrap_or(0);
+                    if chrono::Utc::now().timestamp() - generated_at < DAILY_MIX_REFRESH.as_secs() as i64 {
+                        continue;
+                    }
+
+                    match refresh_daily_mixes(&state).await {
+                        Ok(playlist_ids) => {
+                            use tauri::Emitter;
+                            let _ = handle.emit("daily-mixes-updated", &playlist_ids);
+                        }
+                        Err(e) => eprintln!("⚠️ Failed to generate daily mixes: {}", e),
+                    }
+                }
+            });
+
+            // Recap the past week once it's over, announcing it on Monday
+            let handle = app.handle().clone();
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(WEEKLY_RECAP_CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let week_start = weekly_recap::last_full_week().format("%Y-%m-%d").to_string();
+                    match state.db.get_weekly_recap(&week_start).await {
+                        Ok(None) => {}
+                        Ok(Some(_)) => continue,
+                        Err(e) => {
+                            eprintln!("⚠️ Failed to check the weekly recap: {}", e);
+                            continue;
+                        }
+                    }
+
+                    let recap = match refresh_weekly_recap(&state).await {
+                        Ok(recap) => recap,
+                        Err(e) => {
+                            eprintln!("⚠️ Failed to generate the weekly recap: {}", e);
+                            continue;
+                        }
+                    };
+                    {
+                        use tauri::Emitter;
+                        let _ = handle.emit("weekly-recap-ready", &recap);
+                    }
+                    if recap.total_plays == 0 {
+                        continue;
+                    }
+                    let body = format!(
+                        "{:.1} hours across {} plays, {} new discoveries",
+                        recap.total_hours,
+                        recap.total_plays,
+                        recap.new_discoveries.len()
+                    );
+                    let notify_handle = handle.clone();
+                    let shown = tokio::task::spawn_blocking(move || {
+                        notifications::show(&notify_handle, "Your week in music", &body)
+                    })
+                    .await;
+                    if let Ok(Err(e)) = shown {
+                        eprintln!("⚠️ {}", e);
+                    }
+                }
+            });
+
+            // Check and vacuum the database once a month
+            let handle = app.handle().clone();
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(DB_MAINTENANCE_CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let maintained_at = match state.db.get_preference::<i64>("db_maintained_at").await {
+                        Ok(Some(maintained_at)) => maintained_at,
+                        // Count a fresh install as just maintained instead of vacuuming an empty database
+                        Ok(None) => {
+                            let now = chrono::Utc::now().timestamp();
+                            let _ = state.db.set_preference("db_maintained_at", &now).await;
+                            now
+                        }
+                        Err(_) => continue,
+                    };
+                    if chrono::Utc::now().timestamp() - maintained_at < DB_MAINTENANCE_PERIOD.as_secs() as i64 {
+                        continue;
+                    }
 
-    match app.updater() {
-        Ok(updater) => {
-            match updater.check().await {
-                Ok(Some(update)) => {
-                    println!("🔄 Update available: {} -> {}",
-                        update.current_version, update.version);
+                    if let Err(e) = maintain_database(&handle, &state).await {
+                        eprintln!("⚠️ Database maintenance failed: {}", e);
+                    }
+                }
+            });
 
-                    // Download and install silently
-                    println!("📥 Downloading update...");
-                    match update.download_and_install(|_, _| {}, || {}).await {
-                        Ok(_) => {
-                            println!("✅ Update installed! Will apply on next restart.");
-                        }
-                        Err(e) => {
-                            eprintln!("❌ Failed to download/install update: {}", e);
+            // Fire sleep and stop-at timers, fading out so playback is silent by the set time
+            let handle = app.handle().clone();
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(TIMER_CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let now = chrono::Utc::now().timestamp();
+                    for timer in state.timers.take_due(now).await {
+                        use tauri::Emitter;
+                        let _ = handle.emit("timer-fired", &timer);
+                        if !state.audio.get_state().await.is_playing {
+                            continue;
                         }
+
+                        println!("⏰ Timer reached, fading out");
+                        let fade = (timer.fires_at - now) as f64;
+                        let audio = Arc::clone(&state.audio);
+                        tauri::async_runtime::spawn(async move {
+                            let _ = audio.fade_out_and_pause(std::time::Duration::from_secs_f64(fade.max(0.5))).await;
+                        });
                     }
                 }
-                Ok(None) => {
-                    println!("✅ App is up to date");
-                }
-                Err(e) => {
-                    eprintln!("⚠️ Failed to check for updates: {}", e);
+            });
+
+            // Start and end quiet hours on time
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(QUIET_HOURS_CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let _ = state.audio.refresh_quiet_hours().await;
                 }
-            }
-        }
-        Err(e) => {
-            eprintln!("⚠️ Updater not available: {}", e);
-        }
-    }
-}
+            });
 
-#[tokio::main]
-async fn main() {
-    // Initialize database
-    let db = DatabaseManager::new()
-        .await
-        .expect("Failed to initialize database");
+            // Save the session while playing so it can be restored after a restart
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(SESSION_SAVE_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    if state.audio.get_state().await.is_playing {
+                        save_last_session(&state).await;
+                    }
+                }
+            });
 
-    // Create app state
-    let audio_manager = Arc::new(AudioManager::new());
-    let download_manager = Arc::new(DownloadManager::new());
-    let media_key_manager = Arc::new(MediaKeyManager::new());
-    let app_state = AppState {
-        audio: Arc::clone(&audio_manager),
-        queue: Arc::new(QueueManager::new()),
-        db: Arc::new(db),
-        ytdlp: Arc::new(YTDLPManager::new()),
-        downloads: Arc::clone(&download_manager),
-        media_keys: Arc::clone(&media_key_manager),
-    };
+            // Follow the OS high contrast setting, which the webview can't reliably see and no event
+            // announces. Dark mode follows the window's ThemeChanged event instead.
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(HIGH_CONTRAST_CHECK_INTERVAL);
+                let mut current = None;
+                loop {
+                    interval.tick().await;
+                    let Ok(high_contrast) = tokio::task::spawn_blocking(system_theme::detect_high_contrast).await else {
+                        continue;
+                    };
+                    if current.replace(high_contrast).is_some_and(|previous| previous != high_contrast) {
+                        let Ok(theme) = tokio::task::spawn_blocking(system_theme::detect).await else {
+                            continue;
+                        };
+                        use tauri::Emitter;
+                        let _ = handle.emit("system-theme-changed", theme);
+                    }
+                }
+            });
 
-    tauri::Builder::default()
-        .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .plugin(tauri_plugin_updater::Builder::new().build())
-        .manage(app_state)
-        .setup(move |app| {
-            // Set app handle in audio manager for events
+            // Watch for a full or read-only disk: pause downloads while it lasts, then resume them
             let handle = app.handle().clone();
-            let audio_clone = Arc::clone(&audio_manager);
+            let state = app.state::<AppState>().inner().clone();
             tauri::async_runtime::spawn(async move {
-                audio_clone.set_app_handle(handle).await;
+                let mut interval = tokio::time::interval(STORAGE_CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let mut dirs = vec![state.downloads.get_downloads_dir().await];
+                    dirs.extend(DatabaseManager::get_db_path().parent().map(|dir| dir.to_path_buf()));
+
+                    use tauri::Emitter;
+                    match state.storage.check(dirs).await {
+                        Some(true) => {
+                            let _ = handle.emit("storage-problem-resolved", ());
+                            let resumed = state.downloads.retry_storage_failures().await;
+                            println!("💾 Storage is writable again, resumed {} downloads", resumed);
+                        }
+                        Some(false) => {
+                            if let Some(problem) = state.storage.problem() {
+                                println!("💾 {}, pausing downloads", problem.cause);
+                                let _ = handle.emit("storage-problem", &problem);
+                            }
+                        }
+                        None => {}
+                    }
+                }
             });
 
-            // Set app handle in download manager and initialize existing downloads
+            // Watch connectivity: flag playback as offline, then reconnect streams and retry downloads once it returns
             let handle = app.handle().clone();
-            let download_clone = Arc::clone(&download_manager);
+            let state = app.state::<AppState>().inner().clone();
             tauri::async_runtime::spawn(async move {
-                download_clone.set_app_handle(handle).await;
-                download_clone.initialize().await;
+                let mut interval = tokio::time::interval(CONNECTIVITY_CHECK_INTERVAL);
+                let mut last_metered_check: Option<std::time::Instant> = None;
+                loop {
+                    interval.tick().await;
+                    let change = state.connectivity.check().await;
+                    if let Some(online) = change {
+                        use tauri::Emitter;
+                        println!("{}", if online { "🌐 Back online" } else { "📴 Network offline" });
+                        let _ = state.audio.set_network_offline(!online).await;
+                        let data_saver = state.audio.get_state().await.data_saver;
+                        state.durations.set_paused(data_saver || !online).await;
+                        let _ = handle.emit("network-status-changed", online);
+                    }
+
+                    // Metered connections switch on data saver unless the user overrode it
+                    let metered_due = last_metered_check.is_none_or(|t| t.elapsed() > METERED_CHECK_INTERVAL) || change == Some(true);
+                    if metered_due {
+                        let first_check = last_metered_check.is_none();
+                        last_metered_check = Some(std::time::Instant::now());
+                        if let Some(metered) = state.connectivity.check_metered().await {
+                            use tauri::Emitter;
+                            println!("{}", if metered { "💸 Connection is metered" } else { "📶 Connection is unmetered" });
+                            let _ = handle.emit("metered-status-changed", metered);
+                            let _ = apply_data_saver(&state).await;
+                        } else if first_check {
+                            let _ = apply_data_saver(&state).await;
+                        }
+                    }
+
+                    // Network failures are retried once per reconnect, so a download that keeps failing
+                    // while online isn't restarted forever. Data saver holds off automatic downloads;
+                    // failed ones wait for a manual retry
+                    let data_saver = state.audio.get_state().await.data_saver;
+                    if change == Some(true) && !data_saver && state.downloads.has_network_failures().await {
+                        let retried = state.downloads.retry_network_failures().await;
+                        println!("🔁 Retried {} downloads that failed on a network error", retried);
+                    }
+                }
+            });
+
+            // Fill the jump list, and play whatever entry launched the app
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = refresh_jump_list(&state).await {
+                    eprintln!("⚠️ {}", e);
+                }
+                let args: Vec<String> = std::env::args().collect();
+                if let Err(e) = handle_launch_args(&state, &args).await {
+                    eprintln!("⚠️ Failed to handle launch arguments: {}", e);
+                }
             });
 
-            // Initialize media key manager
+            // Initialize media key manager, then list the saved playlists for MPRIS applets
             let handle = app.handle().clone();
-            let media_key_clone = Arc::clone(&media_key_manager);
+            let state = app.state::<AppState>().inner().clone();
             tauri::async_runtime::spawn(async move {
-                if let Err(e) = media_key_clone.initialize(handle).await {
+                let initialized = state.media_keys.initialize(handle).await;
+                if let Ok(Some(settings)) = state.db.get_preference::<VolumeKeySettings>("volume_keys").await {
+                    let is_playing = state.audio.get_state().await.is_playing;
+                    state.media_keys.set_volume_keys(settings, is_playing).await;
+                }
+                if let Err(e) = initialized {
                     eprintln!("Failed to initialize media keys: {}", e);
+                    return;
+                }
+                if let Ok(playlists) = state.db.get_all_playlists().await {
+                    state
+                        .media_keys
+                        .update_playlists(playlists.into_iter().map(|p| (p.id, p.name)).collect())
+                        .await;
+                }
+            });
+
+            // Send opted-in usage counts once a day
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = load_telemetry_settings(&state).await {
+                    eprintln!("⚠️ Failed to load telemetry settings: {}", e);
+                }
+                let mut interval = tokio::time::interval(TELEMETRY_UPLOAD_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    match state.telemetry.upload().await {
+                        Ok(true) => println!("📊 Sent usage metrics"),
+                        Ok(false) => {}
+                        Err(e) => eprintln!("⚠️ {}", e),
+                    }
+                }
+            });
+
+            // Save usage counts now and then, off the async runtime
+            let telemetry = Arc::clone(&app.state::<AppState>().telemetry);
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(TELEMETRY_SAVE_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let telemetry = Arc::clone(&telemetry);
+                    let _ = tokio::task::spawn_blocking(move || telemetry.flush()).await;
                 }
             });
 
-            // Check for updates silently in background (like macOS Sparkle)
+            // Check for updates silently in background (like macOS Sparkle), unless turned off in settings
             let handle = app.handle().clone();
+            let state = app.state::<AppState>().inner().clone();
             tauri::async_runtime::spawn(async move {
-                use std::time::Duration;
-                tokio::time::sleep(Duration::from_secs(3)).await;
-                println!("🔍 Checking for updates in background...");
-                check_for_updates_silently(handle).await;
+                tokio::time::sleep(APP_UPDATE_CHECK_DELAY).await;
+                let mut interval = tokio::time::interval(APP_UPDATE_CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let enabled = state.db.get_preference::<bool>("auto_check_updates").await.ok().flatten();
+                    if !enabled.unwrap_or(true) {
+                        continue;
+                    }
+
+                    println!("🔍 Checking for updates in background...");
+                    if let Err(e) = update_silently(&handle, &state).await {
+                        eprintln!("⚠️ {}", e);
+                    }
+                }
             });
 
-            // Listen for track-ended events and auto-play next track
+            // Listen for track-ended events and auto-play next track, with a jingle in between when one is due
             let handle_clone = app.handle().clone();
             let state_clone = app.state::<AppState>().inner().clone();
+            let tracks_since_jingle = Arc::new(AtomicU32::new(0));
             tauri::async_runtime::spawn(async move {
                 use tauri::Listener;
+                let app_handle = handle_clone.clone();
                 handle_clone.listen("track-ended", move |_event| {
                     let state = state_clone.clone();
+                    let app_handle = app_handle.clone();
+                    let tracks_since_jingle = Arc::clone(&tracks_since_jingle);
                     tauri::async_runtime::spawn(async move {
                         println!("🎵 Track ended, attempting to play next...");
+                        let _ = refill_queue(&state).await;
+                        // The jingle plays before the queue moves on, so starting something else
+                        // meanwhile leaves the queue where it was
+                        if state.queue.has_next().await && !play_jingle_if_due(&state, &tracks_since_jingle).await {
+                            return;
+                        }
                         if let Some(track) = state.queue.play_next().await {
                             println!("▶️ Auto-playing next track: {}", track.title);
-                            let _ = state.audio.play(track).await;
+                            let _ = play_resolved(&state, track, TrackChangeReason::Auto).await;
+                        } else if let Err(e) = handle_end_of_queue(&app_handle, &state).await {
+                            eprintln!("⚠️ End-of-queue action failed: {}", e);
+                        }
+                    });
+                });
+            });
+
+            // Listen for playback errors: retry the failing track, then skip to the next one
+            let handle_clone = app.handle().clone();
+            let state_clone = app.state::<AppState>().inner().clone();
+            let failed_attempts: Arc<tokio::sync::Mutex<HashMap<String, u32>>> =
+                Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+            let cleared_attempts = Arc::clone(&failed_attempts);
+            tauri::async_runtime::spawn(async move {
+                use tauri::{Emitter, Listener};
+                let emit_handle = handle_clone.clone();
+                handle_clone.listen("playback-error", move |event| {
+                    let Ok(error) = serde_json::from_str::<PlaybackError>(event.payload()) else {
+                        return;
+                    };
+                    let state = state_clone.clone();
+                    let handle = emit_handle.clone();
+                    let failed_attempts = Arc::clone(&failed_attempts);
+                    tauri::async_runtime::spawn(async move {
+                        let attempts = {
+                            let mut failed = failed_attempts.lock().await;
+                            let attempts = failed.entry(error.track.id.clone()).or_insert(0);
+                            *attempts += 1;
+                            *attempts
+                        };
+
+                        if attempts <= MAX_PLAYBACK_RETRIES {
+                            tokio::time::sleep(PLAYBACK_RETRY_DELAY).await;
+                            // Something else was played meanwhile
+                            let current = state.audio.get_state().await.current_track;
+                            if !current.is_some_and(|t| t.id == error.track.id) {
+                                return;
+                            }
+                            println!("🔁 Retrying {} (attempt {}/{})", error.track.title, attempts, MAX_PLAYBACK_RETRIES);
+                            let _ = play_resolved(&state, error.track, TrackChangeReason::Auto).await;
+                            return;
+                        }
+
+                        failed_attempts.lock().await.remove(&error.track.id);
+                        println!("⏭️ Skipping unplayable track: {} ({})", error.track.title, error.message);
+
+                        // A track played on its own isn't in the queue's way, so the queue stays put
+                        let next_track = if state.queue.is_current(&error.track.id).await {
+                            let _ = refill_queue(&state).await;
+                            state.queue.play_next().await
                         } else {
-                            println!("⏹️ No more tracks in queue");
+                            None
+                        };
+                        let _ = handle.emit("track-skipped", SkippedTrack {
+                            track: error.track,
+                            reason: error.message,
+                            attempts,
+                            next_track: next_track.clone(),
+                        });
+
+                        if let Some(track) = next_track {
+                            println!("▶️ Auto-playing next track: {}", track.title);
+                            let _ = play_resolved(&state, track, TrackChangeReason::Auto).await;
                         }
                     });
                 });
+
+                // A track that got going again starts over with a fresh retry
+                handle_clone.listen("playback-state-changed", move |event| {
+                    let Ok(audio_state) = serde_json::from_str::<AudioState>(event.payload()) else {
+                        return;
+                    };
+                    let Some(track) = audio_state.current_track.filter(|_| audio_state.is_playing) else {
+                        return;
+                    };
+                    let failed_attempts = Arc::clone(&cleared_attempts);
+                    tauri::async_runtime::spawn(async move {
+                        failed_attempts.lock().await.remove(&track.id);
+                    });
+                });
             });
 
-            let app = app;
+            let headless = is_headless();
+
             // Create tray menu
+            let play_pause_item = MenuItem::with_id(app, "play_pause", "Play/Pause", true, None::<&str>)?;
+            let next_item = MenuItem::with_id(app, "next", "Next", true, None::<&str>)?;
+            let previous_item = MenuItem::with_id(app, "previous", "Previous", true, None::<&str>)?;
+            let paste_play_item = MenuItem::with_id(app, "paste_play", "Play from Clipboard", true, None::<&str>)?;
+            let volume_up_item = MenuItem::with_id(app, "volume_up", "Volume +5%", true, None::<&str>)?;
+            let volume_down_item = MenuItem::with_id(app, "volume_down", "Volume -5%", true, None::<&str>)?;
             let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let show_item = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_item, &quit_item])?;
+            let show_item = MenuItem::with_id(app, "show", "Show", !headless, None::<&str>)?;
+            let menu = Menu::with_items(
+                app,
+                &[
+                    &show_item,
+                    &play_pause_item,
+                    &next_item,
+                    &previous_item,
+                    &paste_play_item,
+                    &volume_up_item,
+                    &volume_down_item,
+                    &quit_item,
+                ],
+            )?;
 
             // Create tray icon
-            let _tray = TrayIconBuilder::new()
+            let tray = TrayIconBuilder::new()
                 .icon(app.default_window_icon().unwrap().clone())
                 .menu(&menu)
-                .show_menu_on_left_click(false)
+                .show_menu_on_left_click(headless)
                 .on_tray_icon_event(|tray, event| {
+                    // Middle click toggles playback. Scrolling can't change the volume: Tauri's tray
+                    // events stop at clicks and hovers on every platform, and the Linux indicator's
+                    // scroll signal isn't forwarded. Volume steps are in the tray menu instead.
+                    if let TrayIconEvent::Click {
+                        button: MouseButton::Middle,
+                        button_state: MouseButtonState::Up,
+                        ..
+                    } = event
+                    {
+                        let state = tray.app_handle().state::<AppState>().inner().clone();
+                        tauri::async_runtime::spawn(async move {
+                            let _ = state.audio.toggle_play_pause().await;
+                        });
+                        return;
+                    }
+
                     if let TrayIconEvent::Click {
                         button: MouseButton::Left,
                         button_state: MouseButtonState::Up,
+                        rect,
                         ..
                     } = event
                     {
@@ -550,19 +4169,8 @@ async fn main() {
                             if window.is_visible().unwrap_or(false) {
                                 let _ = window.hide();
                             } else {
-                                // Position window in bottom-right before showing
-                                #[cfg(target_os = "windows")]
-                                {
-                                    use tauri::PhysicalPosition;
-                                    if let Ok(Some(monitor)) = window.current_monitor() {
-                                        let screen_size = monitor.size();
-                                        if let Ok(window_size) = window.outer_size() {
-                                            let x = screen_size.width as i32 - window_size.width as i32 - 5;
-                                            let y = screen_size.height as i32 - window_size.height as i32 - 80;
-                                            let _ = window.set_position(PhysicalPosition::new(x, y));
-                                        }
-                                    }
-                                }
+                                // Anchor the popup to the icon that was clicked before showing
+                                let _ = window_placement::place_near_tray(&window, Some(rect));
                                 let _ = window.show().and_then(|_| window.set_focus());
                             }
                         }
@@ -577,110 +4185,749 @@ async fn main() {
                             let _ = window.show().and_then(|_| window.set_focus());
                         }
                     }
+                    "volume_up" | "volume_down" => {
+                        let state = app.state::<AppState>().inner().clone();
+                        let delta = if event.id.as_ref() == "volume_up" {
+                            TRAY_VOLUME_STEP
+                        } else {
+                            -TRAY_VOLUME_STEP
+                        };
+                        tauri::async_runtime::spawn(async move {
+                            if let Ok(volume) = state.audio.adjust_volume(delta).await {
+                                println!("🔊 Volume: {:.0}%", volume * 100.0);
+                                let _ = persist_volume(&state).await;
+                            }
+                        });
+                    }
+                    "play_pause" | "next" | "previous" | "paste_play" => {
+                        let state = app.state::<AppState>().inner().clone();
+                        let action = event.id.as_ref().to_string();
+                        tauri::async_runtime::spawn(async move {
+                            let result = match action.as_str() {
+                                "play_pause" => state.audio.toggle_play_pause().await,
+                                "paste_play" => play_clipboard(&state).await.map(|_| ()),
+                                "next" => skip_to_next(&state, TrackChangeReason::User).await.map(|_| ()),
+                                _ => skip_to_previous(&state, TrackChangeReason::User).await.map(|_| ()),
+                            };
+                            if let Err(e) = result {
+                                eprintln!("⚠️ Tray action {} failed: {}", action, e);
+                            }
+                        });
+                    }
                     _ => {}
                 })
                 .build(app)?;
 
-            // Get the main window
-            let window = app.get_webview_window("main").unwrap();
-
-            // Position window near system tray (bottom-right on Windows, top-right on Linux)
-            #[cfg(target_os = "windows")]
+            // Register the paste-and-play global shortcut
             {
-                use tauri::PhysicalPosition;
-                if let Some(monitor) = window.current_monitor()? {
-                    let screen_size = monitor.size();
-                    if let Ok(window_size) = window.outer_size() {
-                        // Position in bottom-right corner - close to right edge, more space from taskbar
-                        let x = screen_size.width as i32 - window_size.width as i32 - 5;
-                        let y = screen_size.height as i32 - window_size.height as i32 - 80;
+                use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
 
-                        let _ = window.set_position(PhysicalPosition::new(x, y));
+                let state = app.state::<AppState>().inner().clone();
+                let registered = app.global_shortcut().on_shortcut(PASTE_AND_PLAY_SHORTCUT, move |_app, _shortcut, event| {
+                    if event.state != ShortcutState::Pressed {
+                        return;
                     }
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = play_clipboard(&state).await {
+                            eprintln!("⚠️ Paste and play failed: {}", e);
+                        }
+                    });
+                });
+                if let Err(e) = registered {
+                    eprintln!("⚠️ Failed to register {} shortcut: {}", PASTE_AND_PLAY_SHORTCUT, e);
                 }
             }
 
-            #[cfg(target_os = "linux")]
+            // Hand the tray to the tray manager and keep its icon in sync with playback, downloads and buffering
+            let state = app.state::<AppState>().inner().clone();
+            let base_icon = app.default_window_icon().unwrap().clone().to_owned();
+            tauri::async_runtime::spawn(async move {
+                state.tray.set_tray(tray, &base_icon).await;
+            });
+
+            {
+                use tauri::Listener;
+
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("downloads-updated", move |_event| {
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        // Failed downloads move to their own list, so everything active is in progress
+                        let active = state.downloads.get_active_downloads().await;
+                        let progress = if active.is_empty() {
+                            None
+                        } else {
+                            Some(active.iter().map(|d| d.progress).sum::<f64>() / active.len() as f64)
+                        };
+                        state.tray.set_download_progress(progress).await;
+                    });
+                });
+
+                // Downloads give way while the playing track is still being fetched
+                let bandwidth = app.state::<AppState>().inner().bandwidth.clone();
+                app.listen("playback-state-changed", move |event| {
+                    let Ok(audio_state) = serde_json::from_str::<AudioState>(event.payload()) else {
+                        return;
+                    };
+                    let active = audio_state.is_playing || audio_state.is_loading || audio_state.is_buffering;
+                    let streaming = active && audio_state.buffered_until < audio_state.duration;
+                    let bandwidth = Arc::clone(&bandwidth);
+                    tauri::async_runtime::spawn(async move {
+                        bandwidth.set_playback_streaming(streaming).await;
+                    });
+                });
+
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("playback-state-changed", move |event| {
+                    let Ok(audio_state) = serde_json::from_str::<AudioState>(event.payload()) else {
+                        return;
+                    };
+                    let state = state.clone();
+                    let playback = if audio_state.is_playing {
+                        PlaybackIndicator::Playing
+                    } else if audio_state.current_track.is_some() && audio_state.current_position > 0.0 {
+                        PlaybackIndicator::Paused
+                    } else {
+                        PlaybackIndicator::Stopped
+                    };
+                    tauri::async_runtime::spawn(async move {
+                        state.tray.set_buffering(audio_state.is_loading || audio_state.is_buffering).await;
+                        state.tray.set_playback(playback).await;
+                    });
+                });
+            }
+
             {
-                use tauri::PhysicalPosition;
-                if let Some(monitor) = window.current_monitor()? {
-                    let screen_size = monitor.size();
-                    if let Ok(window_size) = window.outer_size() {
-                        // Position in top-right corner with some padding
-                        let x = screen_size.width as i32 - window_size.width as i32 - 10;
-                        let y = 50;
+                use tauri::Listener;
+
+                // Announce each new track once it starts playing, ducking the music while speaking
+                let state = app.state::<AppState>().inner().clone();
+                let announced: Arc<std::sync::Mutex<Option<String>>> = Arc::new(std::sync::Mutex::new(None));
+                app.listen("playback-state-changed", move |event| {
+                    let Ok(audio_state) = serde_json::from_str::<AudioState>(event.payload()) else {
+                        return;
+                    };
+                    let Some(track) = audio_state.current_track.filter(|_| audio_state.is_playing) else {
+                        return;
+                    };
+                    {
+                        let Ok(mut announced) = announced.lock() else {
+                            return;
+                        };
+                        if announced.as_deref() == Some(track.id.as_str()) {
+                            return;
+                        }
+                        *announced = Some(track.id.clone());
+                    }
+
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let enabled: bool = state.db.get_preference("announce_tracks").await.ok().flatten().unwrap_or(false);
+                        if !enabled {
+                            return;
+                        }
+                        let _ = state.audio.set_ducked(true).await;
+                        if let Err(e) = tts::speak(&format!("Now playing: {} by {}", track.display_title(), track.display_uploader())).await {
+                            eprintln!("⚠️ Track announcement failed: {}", e);
+                        }
+                        let _ = state.audio.set_ducked(false).await;
+                    });
+                });
+
+                // Write the now playing line for streaming tools whenever the track changes or playback stops
+                let state = app.state::<AppState>().inner().clone();
+                let written: Arc<std::sync::Mutex<Option<String>>> = Arc::new(std::sync::Mutex::new(None));
+                app.listen("playback-state-changed", move |event| {
+                    let Ok(audio_state) = serde_json::from_str::<AudioState>(event.payload()) else {
+                        return;
+                    };
+                    let line = now_playing::line(audio_state.current_track.as_ref());
+                    {
+                        let Ok(mut written) = written.lock() else {
+                            return;
+                        };
+                        if written.as_deref() == Some(line.as_str()) {
+                            return;
+                        }
+                        *written = Some(line.clone());
+                    }
+
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let Ok(output) = get_now_playing(&state).await else {
+                            return;
+                        };
+                        if !output.enabled {
+                            return;
+                        }
+                        if let Err(e) = now_playing::write(&output, &line).await {
+                            eprintln!("⚠️ Failed to write now playing: {}", e);
+                        }
+                    });
+                });
+
+                // Log tracks that play most of the way through into the month's listening log
+                let state = app.state::<AppState>().inner().clone();
+                let logged: Arc<std::sync::Mutex<Option<String>>> = Arc::new(std::sync::Mutex::new(None)); // track logged this play
+                app.listen("playback-state-changed", move |event| {
+                    let Ok(audio_state) = serde_json::from_str::<AudioState>(event.payload()) else {
+                        return;
+                    };
+                    let Some(track) = audio_state.current_track else {
+                        return;
+                    };
+                    {
+                        let Ok(mut logged) = logged.lock() else {
+                            return;
+                        };
+                        // Anything short of the threshold means a new play, which can be logged again.
+                        // A trimmed intro isn't played, so it doesn't count towards the share.
+                        let length = audio_state.duration - audio_state.trim_start;
+                        let played = audio_state.current_position - audio_state.trim_start;
+                        if length <= 0.0 || played / length < LISTENING_LOG_THRESHOLD {
+                            *logged = None;
+                            return;
+                        }
+                        if logged.as_deref() == Some(track.id.as_str()) {
+                            return;
+                        }
+                        *logged = Some(track.id.clone());
+                    }
+
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let enabled: bool = state.db.get_preference("listening_log").await.ok().flatten().unwrap_or(false);
+                        if !enabled {
+                            return;
+                        }
+                        if let Err(e) = add_to_listening_log(&state, track).await {
+                            eprintln!("⚠️ Failed to add to the listening log: {}", e);
+                        }
+                    });
+                });
+
+                // Learn where plays of a track skip to, and offer to skip the intro from then on
+                let handle = app.handle().clone();
+                let state = app.state::<AppState>().inner().clone();
+                let seeked: Arc<std::sync::Mutex<Option<String>>> = Arc::new(std::sync::Mutex::new(None)); // track already seeked this play
+                app.listen("player-event", move |event| {
+                    let Ok(event) = serde_json::from_str::<PlayerEvent>(event.payload()) else {
+                        return;
+                    };
+                    let handle = handle.clone();
+                    let state = state.clone();
+                    let seeked = Arc::clone(&seeked);
+                    tauri::async_runtime::spawn(async move {
+                        handle_intro_skip_event(&handle, &state, event, &seeked).await;
+                    });
+                });
+
+                // Announce finished downloads: single tracks straight away, batches once they're done.
+                // Each one is also matched against the recordings already known.
+                let handle = app.handle().clone();
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("download-completed", move |event| {
+                    let Ok(track) = serde_json::from_str::<YTVideoInfo>(event.payload()) else {
+                        return;
+                    };
+                    tauri::async_runtime::spawn(notify_downloads_finished(handle.clone(), state.clone(), vec![track.id.clone()]));
+                    tauri::async_runtime::spawn(fingerprint_new_downloads(handle.clone(), state.clone(), vec![track.id]));
+                });
+                let handle = app.handle().clone();
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("download-batch-finished", move |event| {
+                    let Ok(video_ids) = serde_json::from_str::<Vec<String>>(event.payload()) else {
+                        return;
+                    };
+                    tauri::async_runtime::spawn(notify_downloads_finished(handle.clone(), state.clone(), video_ids.clone()));
+                    tauri::async_runtime::spawn(fingerprint_new_downloads(handle.clone(), state.clone(), video_ids));
+                });
+
+                // Announce each new track, as far as the notification policy allows
+                let handle = app.handle().clone();
+                let state = app.state::<AppState>().inner().clone();
+                let announced: Arc<std::sync::Mutex<Option<String>>> = Arc::new(std::sync::Mutex::new(None));
+                app.listen("playback-state-changed", move |event| {
+                    let Ok(audio_state) = serde_json::from_str::<AudioState>(event.payload()) else {
+                        return;
+                    };
+                    let Some(track) = audio_state.current_track else {
+                        return;
+                    };
+                    {
+                        let Ok(mut announced) = announced.lock() else {
+                            return;
+                        };
+                        if announced.as_deref() == Some(track.id.as_str()) {
+                            return;
+                        }
+                        *announced = Some(track.id.clone());
+                    }
 
-                        let _ = window.set_position(PhysicalPosition::new(x, y));
+                    let handle = handle.clone();
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let policy = state
+                            .db
+                            .get_preference::<NotificationPolicy>("notification_policy")
+                            .await
+                            .ok()
+                            .flatten()
+                            .unwrap_or_default();
+                        let popup_visible = handle
+                            .get_webview_window("main")
+                            .is_some_and(|window| window.is_visible().unwrap_or(false));
+                        let allowed = tokio::task::spawn_blocking(move || notifications::allows(policy, popup_visible))
+                            .await
+                            .unwrap_or(false);
+                        if allowed {
+                            if let Err(e) = notifications::show_track(&handle, &track) {
+                                eprintln!("⚠️ {}", e);
+                            }
+                        }
+                    });
+                });
+
+                // Fade out the final track ahead of time when the queue ends by quitting
+                let state = app.state::<AppState>().inner().clone();
+                let handle = app.handle().clone();
+                let fading = Arc::new(std::sync::atomic::AtomicBool::new(false));
+                app.listen("playback-state-changed", move |event| {
+                    let Ok(audio_state) = serde_json::from_str::<AudioState>(event.payload()) else {
+                        return;
+                    };
+                    let remaining = audio_state.duration - audio_state.current_position;
+                    if !audio_state.is_playing
+                        || audio_state.duration <= 0.0
+                        || remaining > END_OF_QUEUE_FADE.as_secs_f64()
+                        || fading.load(Ordering::SeqCst)
+                    {
+                        return;
                     }
+
+                    let state = state.clone();
+                    let handle = handle.clone();
+                    let fading = Arc::clone(&fading);
+                    tauri::async_runtime::spawn(async move {
+                        if state.queue.get_end_of_queue_action().await != EndOfQueueAction::FadeOutAndQuit
+                            || state.queue.has_next().await
+                            || state.queue.needs_refill(1).await
+                            || fading.swap(true, Ordering::SeqCst)
+                        {
+                            return;
+                        }
+                        println!("🌙 Fading out the final track before quitting");
+                        let _ = state.audio.fade_out(std::time::Duration::from_secs_f64(remaining.max(0.5))).await;
+                        handle.exit(0);
+                    });
+                });
+
+                // Mirror the queue into the MPRIS track list as tracks change
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("playback-state-changed", move |_| {
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let queue = state.queue.get_queue().await;
+                        let current_index = usize::try_from(state.queue.get_current_index().await).ok();
+                        state.media_keys.update_tracklist(queue, current_index).await;
+                    });
+                });
+
+                // MPRIS applets can jump within the queue and start saved playlists
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("mpris-go-to", move |event| {
+                    let Ok(index) = serde_json::from_str::<usize>(event.payload()) else {
+                        return;
+                    };
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let Some(track) = state.queue.play_track_at(index).await else {
+                            return;
+                        };
+                        if let Err(e) = play_resolved(&state, track, TrackChangeReason::Remote).await {
+                            eprintln!("⚠️ Failed to play the chosen track: {}", e);
+                        }
+                    });
+                });
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("mpris-activate-playlist", move |event| {
+                    let Ok(playlist_id) = serde_json::from_str::<String>(event.payload()) else {
+                        return;
+                    };
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let result = match state.party.ensure_unlocked("Replacing the queue").await {
+                            Ok(()) => start_playlist(&state, &playlist_id, TrackChangeReason::Remote).await,
+                            Err(e) => Err(e),
+                        };
+                        if let Err(e) = result {
+                            eprintln!("⚠️ Failed to start playlist {}: {}", playlist_id, e);
+                        }
+                    });
+                });
+
+                // Hardware volume keys, while taken over from the system, step the app's own volume
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("media-key-volume", move |event| {
+                    let Ok(delta) = serde_json::from_str::<f32>(event.payload()) else {
+                        return;
+                    };
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Ok(volume) = state.audio.adjust_volume(delta).await {
+                            println!("🔊 Volume: {:.0}%", volume * 100.0);
+                            let _ = persist_volume(&state).await;
+                        }
+                    });
+                });
+
+                // Only hold on to the volume keys while something is playing
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("playback-state-changed", move |event| {
+                    let Ok(audio_state) = serde_json::from_str::<AudioState>(event.payload()) else {
+                        return;
+                    };
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        state.media_keys.sync_volume_keys(audio_state.is_playing).await;
+                    });
+                });
+
+                // Finish downloads that were waiting for the playing track's stream to be fetched
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("streamed-audio-finished", move |event| {
+                    let Ok(streamed) = serde_json::from_str::<StreamedAudio>(event.payload()) else {
+                        return;
+                    };
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let path = streamed.path.map(std::path::PathBuf::from);
+                        if let Err(e) = state.downloads.finish_streamed_audio(&streamed.video_id, path).await {
+                            eprintln!("⚠️ Failed to save streamed audio: {}", e);
+                        }
+                    });
+                });
+
+                // Drop stream URLs YouTube has stopped accepting so the next play resolves afresh
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("stream-url-refused", move |event| {
+                    let Ok(video_id) = serde_json::from_str::<String>(event.payload()) else {
+                        return;
+                    };
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        state.ytdlp.invalidate_stream_url(&video_id).await;
+                    });
+                });
+
+                // Switch to the matching profile whenever the output device changes
+                let state = app.state::<AppState>().inner().clone();
+                app.listen("output-device-changed", move |event| {
+                    let Ok(device) = serde_json::from_str::<String>(event.payload()) else {
+                        return;
+                    };
+                    let state = state.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = apply_device_profile(&state, &device).await {
+                            eprintln!("⚠️ Failed to apply audio profile: {}", e);
+                        }
+                    });
+                });
+            }
+
+            if headless {
+                println!("🖥️ Running headless, control playback from the tray menu, media keys or --action=\"<action>\"");
+                // Only media keys and --action launches are left to reach the app
+                if !is_tray_available() {
+                    eprintln!("⚠️ No system tray available, control headless playback with media keys or --action=\"<action>\"");
+                }
+                let state = app.state::<AppState>().inner().clone();
+                setup_headless_controls(app.handle(), state);
+                return Ok(());
+            }
+
+            // Create the main window (it is not created automatically so headless mode can skip it)
+            let window_config = app
+                .config()
+                .app
+                .windows
+                .first()
+                .cloned()
+                .ok_or("Missing main window configuration")?;
+            let window = tauri::WebviewWindowBuilder::from_config(app.handle(), &window_config)?.build()?;
+
+            // Without a tray the popup could never be reopened, so run as a normal window instead
+            let tray_available = is_tray_available();
+            if is_window_flag() || !tray_available {
+                if !tray_available {
+                    println!("🪟 No system tray found, running as a normal window");
                 }
+                let state = app.state::<AppState>().inner().clone();
+                state.tray.set_window_mode(true);
+                tauri::async_runtime::spawn(async move {
+                    let enabled = state.db.get_preference::<bool>("minimize_to_background").await.ok().flatten();
+                    state.tray.set_minimize_to_background(enabled.unwrap_or(false));
+                });
+
+                let _ = window.set_decorations(true);
+                let _ = window.set_skip_taskbar(false);
+                let _ = window.set_always_on_top(false);
+                let _ = window.center();
+                let _ = window.show().and_then(|_| window.set_focus());
+                return Ok(());
             }
 
+            // No click to anchor to yet, so start in the tray corner of the primary monitor
+            let _ = window_placement::place_near_tray(&window, None);
+
             Ok(())
         })
-        .on_window_event(|window, event| match event {
-            WindowEvent::CloseRequested { api, .. } => {
-                // Hide window instead of closing
-                let _ = window.hide();
-                api.prevent_close();
-            }
-            WindowEvent::Focused(false) => {
-                // Auto-hide when clicking outside (loses focus)
-                let _ = window.hide();
+        .on_window_event(|window, event| {
+            let state = window.app_handle().state::<AppState>();
+            let tray = &state.tray;
+            match event {
+                WindowEvent::CloseRequested { api, .. } => {
+                    // In window mode closing quits, unless the user chose to keep playing in the background
+                    if tray.is_window_mode() && !tray.minimizes_to_background() {
+                        window.app_handle().exit(0);
+                        return;
+                    }
+                    // Hide window instead of closing
+                    let _ = window.hide();
+                    api.prevent_close();
+                }
+                WindowEvent::Focused(false) if !tray.is_window_mode() => {
+                    // Auto-hide when clicking outside (loses focus)
+                    let _ = window.hide();
+                }
+                // Every window reports the change, the main one is enough
+                WindowEvent::ThemeChanged(theme) if window.label() == "main" => {
+                    let dark = *theme == tauri::Theme::Dark;
+                    let handle = window.app_handle().clone();
+                    tauri::async_runtime::spawn(async move {
+                        let high_contrast = tokio::task::spawn_blocking(system_theme::detect_high_contrast)
+                            .await
+                            .unwrap_or(false);
+                        use tauri::Emitter;
+                        let _ = handle.emit("system-theme-changed", SystemTheme { dark, high_contrast });
+                    });
+                }
+                _ => {}
             }
-            _ => {}
         })
         .invoke_handler(tauri::generate_handler![
             search_youtube,
             check_ytdlp_installed,
             install_ytdlp,
             get_ytdlp_version,
+            get_ytdlp_versions,
+            set_ytdlp_release,
+            rollback_ytdlp,
+            get_onboarding_status,
+            complete_onboarding_step,
             play_track,
+            warm_track,
+            play_from_clipboard,
+            copy_queue_to_clipboard,
+            copy_current_track_url,
+            get_share_link,
             toggle_play_pause,
             pause_playback,
             stop_playback,
+            unload_playback,
+            execute_player_action,
+            prefetch_metadata,
+            cancel_metadata_prefetch,
             seek_to,
+            replay,
             set_volume,
+            enable_party_mode,
+            disable_party_mode,
+            get_party_mode,
+            get_guest_queue,
+            set_guest_queue,
+            regenerate_guest_token,
+            get_guest_requests,
+            approve_guest_request,
+            reject_guest_request,
+            set_max_volume_gain,
+            set_buffer_target,
+            set_max_volume,
+            set_limiter,
+            get_audio_capabilities,
+            get_prefer_builtin_decoder,
+            set_prefer_builtin_decoder,
+            get_keep_streamed_audio,
+            set_keep_streamed_audio,
+            get_volume_keys,
+            set_volume_keys,
+            get_dominant_colors,
+            export_profile,
+            generate_diagnostics_bundle,
+            import_profile,
+            set_sleep_timer,
+            set_stop_at,
+            cancel_timer,
+            get_timers,
+            get_quiet_hours,
+            set_quiet_hours,
+            get_track_trim,
+            set_track_trim,
+            accept_intro_skip,
+            dismiss_intro_skip,
+            get_blocked_keywords,
+            set_blocked_keywords,
+            get_data_saver_mode,
+            set_data_saver_mode,
+            is_connection_metered,
             set_playback_speed,
+            get_speed_presets,
+            get_track_announcements,
+            set_track_announcements,
+            get_listening_log,
+            set_listening_log,
+            get_now_playing_output,
+            set_now_playing_output,
+            get_notification_policy,
+            set_notification_policy,
+            get_jingle_settings,
+            set_jingle_settings,
+            set_speed_presets,
+            set_audio_effects,
+            list_output_devices,
+            set_output_device,
+            get_audio_profiles,
+            save_audio_profile,
+            delete_audio_profile,
             play_next,
             play_previous,
             get_audio_state,
+            get_player_events,
             add_to_queue,
             get_queue,
+            filter_queue,
             clear_queue,
             toggle_shuffle,
             cycle_repeat_mode,
+            set_repeat_count,
             get_queue_info,
             reorder_queue,
             // Playlist commands
             get_all_playlists,
             create_playlist,
             delete_playlist,
+            duplicate_playlist,
+            merge_playlists,
             get_playlist_tracks,
+            filter_playlist_tracks,
+            get_playlist_summary,
             add_track_to_playlist,
             remove_track_from_playlist,
+            add_tracks_to_playlist,
+            remove_tracks_from_playlist,
+            get_track_playlists,
+            set_track_playlists,
+            update_track_display,
+            set_track_content_type,
+            get_tracks_by_content_type,
             add_to_favorites,
             remove_from_favorites,
             play_playlist,
+            play_track_in_context,
+            get_pinned_playlists,
+            set_playlist_pinned,
+            get_end_of_queue_action,
+            set_end_of_queue_action,
+            play_all_shuffled,
+            play_url_list,
+            start_artist_radio,
+            generate_daily_mixes,
+            generate_weekly_recap,
+            get_weekly_recaps,
+            get_most_skipped,
+            get_saved_session,
+            restore_session,
+            get_sessions,
+            save_session,
+            load_session,
+            delete_session,
             // Download commands
+            get_cookie_source,
+            set_cookie_source,
+            get_ytdlp_options,
+            set_ytdlp_options,
+            import_youtube_library,
+            get_library_sync,
+            set_library_sync,
             download_track,
+            download_tracks,
+            get_download_notifications,
+            set_download_notifications,
+            get_fingerprinting,
+            set_fingerprinting,
+            fingerprint_downloads,
+            get_recording_duplicates,
             get_active_downloads,
             get_downloaded_tracks,
             get_storage_used,
             is_track_downloaded,
             delete_download,
+            delete_downloads,
             cancel_download,
+            set_download_priority,
+            verify_downloads,
+            get_failed_downloads,
+            retry_all_failed,
+            redownload_track,
+            search_in_track,
+            // Bookmark commands
+            add_bookmark,
+            get_bookmarks,
+            delete_bookmark,
+            play_bookmark,
+            // Channel rule commands
+            get_channel_rules,
+            get_channel_rule,
+            save_channel_rule,
+            delete_channel_rule,
+            // Undo commands
+            undo_last_action,
             // Settings commands
             get_downloads_directory,
             set_downloads_directory,
+            get_downloads_watcher_enabled,
+            set_downloads_watcher_enabled,
+            reconcile_downloads,
+            relink_downloads,
+            clean_up_downloads,
             get_audio_quality,
             set_audio_quality,
+            get_data_usage_breakdown,
+            reset_app_data,
+            run_db_maintenance,
+            get_storage_problem,
+            get_system_theme,
+            is_window_mode,
+            get_minimize_to_background,
+            set_minimize_to_background,
             get_app_version,
             // Media key commands
             update_media_metadata,
             update_media_playback_state,
             clear_media_info,
             // Updater commands
-            check_for_updates_manual
+            check_app_update,
+            download_app_update,
+            install_app_update,
+            get_auto_check_updates,
+            set_auto_check_updates,
+            // Telemetry commands
+            get_telemetry_status,
+            set_telemetry_enabled,
+            set_telemetry_endpoint,
+            // Rate limiting commands
+            get_youtube_cooldown
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,11 +1,28 @@
-use souvlaki::{MediaControlEvent, MediaControls, MediaMetadata, MediaPlayback, MediaPosition, PlatformConfig};
+use crate::models::{VolumeKeySettings, YTVideoInfo};
+use souvlaki::{MediaControlEvent, MediaMetadata, MediaPlayback, MediaPosition};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tauri::{AppHandle, Emitter};
 
+// On Linux our own MPRIS server stands in for souvlaki's, adding the TrackList and Playlists interfaces
+#[cfg(target_os = "linux")]
+type MediaControls = crate::mpris::MprisServer;
+#[cfg(not(target_os = "linux"))]
+type MediaControls = souvlaki::MediaControls;
+
+const VOLUME_UP_KEY: &str = "AudioVolumeUp";
+const VOLUME_DOWN_KEY: &str = "AudioVolumeDown";
+
+#[derive(Default)]
+struct VolumeKeys {
+    settings: VolumeKeySettings,
+    registered: bool,
+}
+
 pub struct MediaKeyManager {
     controls: Arc<Mutex<Option<MediaControls>>>,
     app_handle: Arc<Mutex<Option<AppHandle>>>,
+    volume_keys: Arc<Mutex<VolumeKeys>>,
 }
 
 impl MediaKeyManager {
@@ -13,12 +30,32 @@ impl MediaKeyManager {
         Self {
             controls: Arc::new(Mutex::new(None)),
             app_handle: Arc::new(Mutex::new(None)),
+            volume_keys: Arc::new(Mutex::new(VolumeKeys::default())),
         }
     }
 
     pub async fn initialize(&self, app_handle: AppHandle) -> Result<(), String> {
         *self.app_handle.lock().await = Some(app_handle.clone());
 
+        #[cfg(target_os = "linux")]
+        let controls = MediaControls::new(app_handle).map_err(|e| {
+            eprintln!("Failed to create media controls: {}", e);
+            e
+        })?;
+
+        #[cfg(not(target_os = "linux"))]
+        let controls = Self::create_controls(app_handle)?;
+
+        *self.controls.lock().await = Some(controls);
+        println!("🎹 MediaKeyManager: Initialized successfully");
+
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn create_controls(app_handle: AppHandle) -> Result<MediaControls, String> {
+        use souvlaki::PlatformConfig;
+
         // Configure platform settings (hwnd is required in struct but only used on Windows)
         let platform_config = PlatformConfig {
             display_name: "YTAudioBar",
@@ -47,10 +84,7 @@ impl MediaKeyManager {
             return Err(format!("Failed to attach media controls: {:?}", e));
         }
 
-        *self.controls.lock().await = Some(controls);
-        println!("🎹 MediaKeyManager: Initialized successfully");
-
-        Ok(())
+        Ok(controls)
     }
 
     pub async fn update_metadata(&self, title: String, artist: String, duration: f64) {
@@ -83,6 +117,81 @@ impl MediaKeyManager {
         }
     }
 
+    /// Let MPRIS applets browse the queue; other platforms have nowhere to show it
+    pub async fn update_tracklist(&self, queue: Vec<YTVideoInfo>, current_index: Option<usize>) {
+        #[cfg(target_os = "linux")]
+        if let Some(controls) = self.controls.lock().await.as_mut() {
+            if let Err(e) = controls.set_tracklist(queue, current_index) {
+                eprintln!("Failed to set track list: {}", e);
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = (queue, current_index);
+    }
+
+    /// Saved playlists, as (id, name), that MPRIS applets can start
+    pub async fn update_playlists(&self, playlists: Vec<(String, String)>) {
+        #[cfg(target_os = "linux")]
+        if let Some(controls) = self.controls.lock().await.as_mut() {
+            if let Err(e) = controls.set_playlists(playlists) {
+                eprintln!("Failed to set playlists: {}", e);
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = playlists;
+    }
+
+    pub async fn set_volume_keys(&self, settings: VolumeKeySettings, is_playing: bool) {
+        let mut volume_keys = self.volume_keys.lock().await;
+        volume_keys.settings = settings;
+        // Re-register so the handlers pick up the new step
+        self.grab_volume_keys(&mut volume_keys, false).await;
+        self.grab_volume_keys(&mut volume_keys, settings.enabled && is_playing).await;
+    }
+
+    /// The volume keys are only taken from the system while something plays
+    pub async fn sync_volume_keys(&self, is_playing: bool) {
+        let mut volume_keys = self.volume_keys.lock().await;
+        let grab = volume_keys.settings.enabled && is_playing;
+        self.grab_volume_keys(&mut volume_keys, grab).await;
+    }
+
+    // Registering a key as a global shortcut keeps it from reaching the system mixer
+    async fn grab_volume_keys(&self, volume_keys: &mut VolumeKeys, grab: bool) {
+        use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+        if volume_keys.registered == grab {
+            return;
+        }
+        let Some(app_handle) = self.app_handle.lock().await.clone() else {
+            return;
+        };
+        let shortcuts = app_handle.global_shortcut();
+
+        if !grab {
+            for key in [VOLUME_UP_KEY, VOLUME_DOWN_KEY] {
+                // Keys that failed to register have nothing to release
+                let _ = shortcuts.unregister(key);
+            }
+            volume_keys.registered = false;
+            return;
+        }
+
+        let step = volume_keys.settings.step;
+        for (key, delta) in [(VOLUME_UP_KEY, step), (VOLUME_DOWN_KEY, -step)] {
+            let registered = shortcuts.on_shortcut(key, move |app_handle, _shortcut, event| {
+                if event.state == ShortcutState::Pressed {
+                    println!("🎹 Media Key: Volume {:+.2}", delta);
+                    let _ = app_handle.emit("media-key-volume", delta);
+                }
+            });
+            if let Err(e) = registered {
+                eprintln!("⚠️ Failed to take over {}: {}", key, e);
+            }
+        }
+        volume_keys.registered = true;
+    }
+
     pub async fn clear(&self) {
         if let Some(controls) = self.controls.lock().await.as_mut() {
             if let Err(e) = controls.set_playback(MediaPlayback::Stopped) {
@@ -92,7 +201,7 @@ impl MediaKeyManager {
     }
 }
 
-async fn handle_media_event(event: MediaControlEvent, app_handle: AppHandle) {
+pub(crate) async fn handle_media_event(event: MediaControlEvent, app_handle: AppHandle) {
     match event {
         MediaControlEvent::Play => {
             println!("🎹 Media Key: Play");
@@ -123,6 +232,10 @@ async fn handle_media_event(event: MediaControlEvent, app_handle: AppHandle) {
             println!("🎹 Media Key: Seek by {} seconds", offset);
             let _ = app_handle.emit("media-key-seek", offset);
         }
+        MediaControlEvent::Seek(souvlaki::SeekDirection::Backward) => {
+            println!("🎹 Media Key: Replay");
+            let _ = app_handle.emit("media-key-replay", ());
+        }
         MediaControlEvent::SetPosition(position) => {
             let seconds = position.0.as_secs_f64();
             println!("🎹 Media Key: Seek to {} seconds", seconds);
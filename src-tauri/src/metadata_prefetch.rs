@@ -0,0 +1,110 @@
+use crate::artwork;
+use crate::database::DatabaseManager;
+use crate::duration_fixer::DurationFixer;
+use crate::models::{PrefetchPriority, PrefetchedMetadata};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::{JoinHandle, JoinSet};
+
+// Tracks fetched at once; on-screen lists get a few, anything else trickles in one by one
+const VISIBLE_CONCURRENCY: usize = 4;
+const BACKGROUND_CONCURRENCY: usize = 1;
+
+/// Fetches thumbnails for the tracks a list is showing, a few at a time, instead of the webview
+/// requesting every thumbnail at once. Missing durations are left to the duration fixer.
+pub struct MetadataPrefetcher {
+    db: Arc<DatabaseManager>,
+    durations: Arc<DurationFixer>,
+    app_handle: Mutex<Option<AppHandle>>,
+    batches: Mutex<Vec<(PrefetchPriority, JoinHandle<()>)>>,
+}
+
+impl MetadataPrefetcher {
+    pub fn new(db: Arc<DatabaseManager>, durations: Arc<DurationFixer>) -> Self {
+        Self {
+            db,
+            durations,
+            app_handle: Mutex::new(None),
+            batches: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub async fn set_app_handle(&self, handle: AppHandle) {
+        *self.app_handle.lock().await = Some(handle);
+    }
+
+    /// Fetch in list order, emitting `metadata-prefetched` per track. A visible batch means the
+    /// view changed, so it replaces everything still running; a background one only replaces
+    /// other background batches.
+    pub async fn prefetch(self: &Arc<Self>, track_ids: Vec<String>, priority: PrefetchPriority) {
+        let mut batches = self.batches.lock().await;
+        batches.retain(|(batch_priority, handle)| {
+            let replaced = priority == PrefetchPriority::Visible || *batch_priority == PrefetchPriority::Background;
+            if replaced {
+                handle.abort();
+            }
+            !replaced && !handle.is_finished()
+        });
+
+        let concurrency = match priority {
+            PrefetchPriority::Visible => VISIBLE_CONCURRENCY,
+            PrefetchPriority::Background => BACKGROUND_CONCURRENCY,
+        };
+        let prefetcher = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let permits = Arc::new(Semaphore::new(concurrency));
+            // Dropping the set when this batch is aborted aborts the fetches in flight too
+            let mut fetches = JoinSet::new();
+            for video_id in track_ids {
+                let Ok(permit) = Arc::clone(&permits).acquire_owned().await else {
+                    break;
+                };
+                let prefetcher = Arc::clone(&prefetcher);
+                fetches.spawn(async move {
+                    if let Some(metadata) = prefetcher.fetch(video_id).await {
+                        if let Some(handle) = prefetcher.app_handle.lock().await.as_ref() {
+                            let _ = handle.emit("metadata-prefetched", metadata);
+                        }
+                    }
+                    drop(permit);
+                });
+                // Reap finished fetches so the set doesn't grow with the list
+                while fetches.try_join_next().is_some() {}
+            }
+            while fetches.join_next().await.is_some() {}
+        });
+        batches.push((priority, handle));
+    }
+
+    /// Stop every batch, for when the list that asked is no longer shown
+    pub async fn cancel(&self) {
+        for (_, handle) in self.batches.lock().await.drain(..) {
+            handle.abort();
+        }
+    }
+
+    // Only saved tracks are prefetched, search results come with everything already
+    async fn fetch(&self, video_id: String) -> Option<PrefetchedMetadata> {
+        let track = self.db.get_track(&video_id).await.ok().flatten()?;
+
+        let url = track
+            .thumbnail_url
+            .unwrap_or_else(|| format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", video_id));
+        let thumbnail = match artwork::cached_thumbnail(&video_id, &url).await {
+            Ok(bytes) => Some(artwork::data_url(&bytes)),
+            Err(e) => {
+                eprintln!("⚠️ Failed to prefetch thumbnail for {}: {}", video_id, e);
+                None
+            }
+        };
+
+        // An unknown duration is queued with the fixer, which emits `track-duration-fixed` once found
+        let mut duration = None;
+        if track.duration <= 0 {
+            duration = self.durations.duration_or_enqueue(&video_id).await;
+        }
+
+        Some(PrefetchedMetadata { video_id, thumbnail, duration })
+    }
+}
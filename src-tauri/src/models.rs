@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct YTVideoInfo {
@@ -9,6 +10,24 @@ pub struct YTVideoInfo {
     pub thumbnail_url: Option<String>,
     pub audio_url: Option<String>,
     pub description: Option<String>,
+    #[serde(default)]
+    pub content_type: Option<ContentType>, // None until classified from yt-dlp metadata
+    #[serde(default)]
+    pub custom_title: Option<String>, // user override; `title` and `uploader` stay YouTube's,
+    #[serde(default)]
+    pub custom_author: Option<String>, // which channel rules, radio and play history key on
+}
+
+impl YTVideoInfo {
+    /// The title to show, the user's own if they set one
+    pub fn display_title(&self) -> &str {
+        self.custom_title.as_deref().unwrap_or(&self.title)
+    }
+
+    /// The artist to show, the user's own if they set one
+    pub fn display_uploader(&self) -> &str {
+        self.custom_author.as_deref().unwrap_or(&self.uploader)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,10 +35,27 @@ pub struct AudioState {
     pub is_playing: bool,
     pub current_position: f64,
     pub duration: f64,
-    pub volume: f32,
+    pub volume: f32,      // slider position, 0.0 to 1.0
+    pub output_gain: f32, // perceptual gain actually applied to the output
+    pub max_gain: f32,    // gain at full volume
     pub playback_rate: f32,
     pub current_track: Option<YTVideoInfo>,
     pub is_loading: bool,
+    pub is_loaded: bool, // the current track's audio is held, so playing it again starts instantly
+    pub effects: AudioEffects,
+    pub output_device: Option<String>, // name of the device currently playing
+    pub output_unavailable: bool,      // no device could be opened, playback waits for one
+    pub buffer_target: f64,            // seconds fetched ahead of the playhead before playing
+    pub buffered_ahead: f64,           // seconds currently buffered past the playhead
+    pub is_buffering: bool,            // waiting on the download, either to start or after an underrun
+    pub buffered_until: f64,           // position (seconds) the downloaded audio reaches
+    pub network_offline: bool,
+    pub data_saver: bool,              // streaming at low quality to save data
+    pub quiet_hours_active: bool,
+    pub max_volume: f32, // highest slider position ever played, 1.0 for no cap
+    pub limiter: LimiterSettings,
+    pub trim_start: f64,       // where the current track starts playing, past a trimmed intro
+    pub trim_end: Option<f64>, // where it ends early, duration is set to this when trimmed
 }
 
 impl Default for AudioState {
@@ -29,18 +65,160 @@ impl Default for AudioState {
             current_position: 0.0,
             duration: 0.0,
             volume: 1.0,
+            output_gain: 1.0,
+            max_gain: 1.0,
             playback_rate: 1.0,
             current_track: None,
             is_loading: false,
+            is_loaded: false,
+            effects: AudioEffects::default(),
+            output_device: None,
+            output_unavailable: false,
+            buffer_target: 3.0,
+            buffered_ahead: 0.0,
+            is_buffering: false,
+            buffered_until: 0.0,
+            network_offline: false,
+            data_saver: false,
+            quiet_hours_active: false,
+            max_volume: 1.0,
+            limiter: LimiterSettings::default(),
+            trim_start: 0.0,
+            trim_end: None,
         }
     }
 }
 
+// Peak limiter run after the EQ, catching sudden loud passages
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct LimiterSettings {
+    pub enabled: bool,
+    pub ceiling_db: f32, // dBFS that peaks are held under
+}
+
+impl Default for LimiterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ceiling_db: -6.0,
+        }
+    }
+}
+
+// What quiet hours do while they're in effect
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum QuietHoursMode {
+    CapVolume(f32), // highest slider position allowed
+    Block,          // no playback at all
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuietHours {
+    pub enabled: bool,
+    pub start: String, // "HH:MM" local time
+    pub end: String,   // earlier than `start` when the window spans midnight
+    pub mode: QuietHoursMode,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+            mode: QuietHoursMode::CapVolume(0.3),
+        }
+    }
+}
+
+impl QuietHours {
+    /// Start and end times, None if either isn't a valid "HH:MM"
+    pub fn window(&self) -> Option<(chrono::NaiveTime, chrono::NaiveTime)> {
+        let start = chrono::NaiveTime::parse_from_str(&self.start, "%H:%M").ok()?;
+        let end = chrono::NaiveTime::parse_from_str(&self.end, "%H:%M").ok()?;
+        Some((start, end))
+    }
+
+    pub fn is_active_at(&self, now: chrono::NaiveTime) -> bool {
+        let Some((start, end)) = self.window().filter(|_| self.enabled) else {
+            return false;
+        };
+        if start <= end {
+            start <= now && now < end
+        } else {
+            now >= start || now < end
+        }
+    }
+}
+
+// Gains are in dB, 0.0 leaves the band untouched
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct AudioEffects {
+    pub bass_db: f32,
+    pub mid_db: f32,
+    pub treble_db: f32,
+    pub crossfeed: f32, // 0.0 (off) to 1.0
+}
+
+// Settings restored whenever its output device becomes active
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioProfile {
+    pub volume: f32,
+    pub effects: AudioEffects,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaybackError {
+    pub track: YTVideoInfo,
+    pub message: String,
+}
+
+// A stream copy claimed for a download that finished, without a path if it couldn't be kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamedAudio {
+    pub video_id: String,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedTrack {
+    pub track: YTVideoInfo,
+    pub reason: String,
+    pub attempts: u32,
+    pub next_track: Option<YTVideoInfo>,
+}
+
+/// What moved playback to a new track, so history and skip counts can tell an auto-advance
+/// from someone skipping
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrackChangeReason {
+    Auto,     // the previous track ended, failed, or the queue ran out
+    User,     // picked or skipped in the app, tray or a notification
+    MediaKey, // keyboard media keys or the OS media controls
+    Remote,   // player actions from scripts and assistants, MPRIS applets
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackChanged {
+    pub track: YTVideoInfo,
+    pub previous: Option<YTVideoInfo>,
+    pub reason: TrackChangeReason,
+}
+
+/// Sent as `settings-changed`, named by preference key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsChanged {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
 pub enum RepeatMode {
+    #[default]
     Off,
     All,
     One,
+    Count(u32), // repeat the current track this many more times, then go back to the previous mode
 }
 
 impl RepeatMode {
@@ -48,7 +226,7 @@ impl RepeatMode {
         match self {
             RepeatMode::Off => RepeatMode::All,
             RepeatMode::All => RepeatMode::One,
-            RepeatMode::One => RepeatMode::Off,
+            RepeatMode::One | RepeatMode::Count(_) => RepeatMode::Off,
         }
     }
 
@@ -57,17 +235,56 @@ impl RepeatMode {
             RepeatMode::Off => "Off",
             RepeatMode::All => "All",
             RepeatMode::One => "One",
+            RepeatMode::Count(_) => "Count",
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ShuffleScope {
+    Playlist(String),
+    Favorites,
+    Downloads,
+    Library,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum TimerKind {
+    Sleep,  // after a duration
+    StopAt, // at a clock time
+}
+
+// A pending timer that fades playback out and pauses it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTimer {
+    pub id: String,
+    pub kind: TimerKind,
+    pub fires_at: i64, // unix seconds, when the fade reaches silence
+    pub fade_seconds: f64,
+}
+
+// Silent intro and outro cut off a track, in seconds
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct TrackTrim {
+    pub start: f64,
+    pub end: Option<f64>, // None plays to the end
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueueState {
     pub queue: Vec<YTVideoInfo>,
     pub current_index: i32,
     pub shuffle_mode: bool,
     pub repeat_mode: RepeatMode,
+    #[serde(default)]
+    pub repeat_after_count: RepeatMode, // mode a finished Count goes back to
     pub original_queue: Vec<YTVideoInfo>,
+    pub shuffle_scope: Option<ShuffleScope>,
+    pub pending_track_ids: Vec<String>, // shuffled IDs, or pasted links without a shuffle scope, not yet materialized into the queue
+    pub play_history: VecDeque<String>, // most recent first
+    pub radio_station: Option<String>,  // artist whose radio keeps the queue topped up
+    pub source_playlist: Option<String>, // playlist the queue was built from
+    pub started_at: i64,                 // when the queue was built, plays since then belong to it
 }
 
 impl Default for QueueState {
@@ -77,7 +294,14 @@ impl Default for QueueState {
             current_index: -1,
             shuffle_mode: false,
             repeat_mode: RepeatMode::Off,
+            repeat_after_count: RepeatMode::Off,
             original_queue: Vec::new(),
+            shuffle_scope: None,
+            pending_track_ids: Vec::new(),
+            play_history: VecDeque::new(),
+            radio_station: None,
+            source_playlist: None,
+            started_at: chrono::Utc::now().timestamp(),
         }
     }
 }
@@ -119,6 +343,10 @@ pub struct Track {
     pub thumbnail_url: Option<String>,
     pub added_date: i64,
     pub file_path: Option<String>,
+    pub custom_title: Option<String>,  // user override, `title` keeps the YouTube title
+    pub custom_author: Option<String>, // user override, `author` keeps the YouTube uploader
+    pub note: Option<String>,
+    pub content_type: Option<ContentType>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -127,4 +355,667 @@ pub struct Playlist {
     pub name: String,
     pub created_date: i64,
     pub is_system_playlist: bool,
+    #[serde(default)]
+    pub is_generated: bool, // daily mixes, rebuilt from listening history
+    #[serde(default)]
+    pub cover: Option<String>, // data URL of the cached thumbnail mosaic
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: String,
+    pub track_id: String,
+    pub position: f64, // seconds
+    pub note: Option<String>,
+    pub created_date: i64,
+}
+
+/// Defaults applied to every track from one uploader, matched on the YouTube uploader name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelRule {
+    pub uploader: String,
+    pub playback_rate: Option<f32>,  // speed to play at, None keeps the usual speed
+    pub auto_download: bool,         // download tracks when they're played
+    pub playlist_id: Option<String>, // playlist tracks are added to when played or queued
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum SessionRestoreMode {
+    Exact,        // same queue, track and position
+    NextUnplayed, // same playlist, from the first track not heard since the session started
+}
+
+// The queue as it was when the app last played, for picking up where it left off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub queue: Vec<YTVideoInfo>,
+    pub current_index: i32,
+    pub position: f64,
+    pub source_playlist: Option<String>,
+    pub started_at: i64,
+    pub saved_at: i64,
+}
+
+// A named snapshot of the queue, for switching between listening contexts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueSession {
+    pub queue: QueueState, // tracks, index, shuffle and repeat modes
+    pub position: f64,
+    pub saved_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueSessionSummary {
+    pub name: String,
+    pub track_count: usize,
+    pub current_track: Option<YTVideoInfo>,
+    pub shuffle_mode: bool,
+    pub repeat_mode: RepeatMode,
+    pub saved_at: i64,
+}
+
+// One row of the play history, described from the library when the track is in it
+#[derive(Debug, Clone)]
+pub struct PlayRecord {
+    pub track_id: String,
+    pub recording_id: String, // first video id of the same recording, the track's own id when unmatched
+    pub title: Option<String>, // None for plays recorded before titles were kept, of tracks outside the library
+    pub author: Option<String>,
+    pub duration: i64,
+    pub played_at: i64,
+    pub skipped: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecapTrack {
+    pub id: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub plays: i64,
+}
+
+/// One week of listening, Monday to Sunday in local time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyRecap {
+    pub week_start: String, // the Monday, as YYYY-MM-DD
+    pub total_plays: i64,
+    pub total_hours: f64, // tracks played through, skipped plays don't count
+    pub top_tracks: Vec<RecapTrack>,
+    pub new_discoveries: Vec<RecapTrack>, // tracks played for the first time this week
+    pub days_listened: u32,
+    pub longest_streak: u32, // most consecutive days with plays within the week
+    pub streak: u32,         // consecutive days with plays up to the Sunday, earlier weeks included
+    pub generated_at: i64,
+}
+
+// Chromaprint fingerprint of a downloaded file; every upload of one recording shares a group
+#[derive(Debug, Clone)]
+pub struct AudioFingerprint {
+    pub track_id: String,
+    pub group_id: String, // id of the first upload fingerprinted for the recording
+    pub duration: f64,
+    pub fingerprint: Vec<u32>,
+}
+
+/// A download that turned out to be another upload of a recording already in the library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingDuplicate {
+    pub video_id: String,
+    pub title: String,
+    pub original_id: String,
+    pub original_title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FingerprintStatus {
+    pub enabled: bool,
+    pub available: bool, // fpcalc is installed
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackSkipStats {
+    pub track: Track,
+    pub plays: i64,
+    pub skips: i64, // plays left before SKIP_THRESHOLD of the track
+    pub skip_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistSummary {
+    pub playlist_id: String,
+    pub track_count: i64,
+    pub total_duration: i64, // seconds
+    pub downloaded_count: i64,
+    pub downloaded_size: i64, // bytes
+    pub last_played: Option<i64>,
+}
+
+impl From<YTVideoInfo> for Track {
+    fn from(info: YTVideoInfo) -> Self {
+        Self {
+            id: info.id,
+            title: info.title,
+            author: Some(info.uploader),
+            duration: info.duration,
+            thumbnail_url: info.thumbnail_url,
+            added_date: chrono::Utc::now().timestamp(),
+            file_path: None,
+            custom_title: info.custom_title,
+            custom_author: info.custom_author,
+            note: None,
+            content_type: info.content_type,
+        }
+    }
+}
+
+// Custom titles and artists ride along for display, see `display_title`
+impl From<Track> for YTVideoInfo {
+    fn from(track: Track) -> Self {
+        Self {
+            id: track.id,
+            title: track.title,
+            uploader: track.author.unwrap_or_else(|| "Unknown".to_string()),
+            duration: track.duration,
+            thumbnail_url: track.thumbnail_url,
+            audio_url: None,
+            description: None,
+            content_type: track.content_type,
+            custom_title: track.custom_title,
+            custom_author: track.custom_author,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DataScope {
+    SearchCache,
+    Thumbnails,
+    Downloads,
+    Database,
+    Everything,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataUsageBreakdown {
+    pub search_cache: i64,
+    pub thumbnails: i64,
+    pub downloads: i64,
+    pub database: i64,
+}
+
+// Payload of `youtube-cooldown-changed`; requests to YouTube pause while cooling down
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CooldownStatus {
+    pub cooling_down: bool,
+    pub until: Option<i64>, // unix seconds the cooldown ends at
+}
+
+// Payload of `party-mode-changed`; while enabled the volume stays within the range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartyModeStatus {
+    pub enabled: bool,
+    pub min_volume: Option<f32>,
+    pub max_volume: Option<f32>,
+}
+
+// Lets other devices on the network search and add to the queue, see guest_queue.rs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuestQueueSettings {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String, // guests need it in every request, generated when first enabled
+    pub require_approval: bool, // guest tracks wait for the host before joining the queue
+}
+
+impl Default for GuestQueueSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 47800,
+            token: String::new(),
+            require_approval: true,
+        }
+    }
+}
+
+// A track a guest asked for, waiting on the host's approval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuestRequest {
+    pub id: String,
+    pub guest: String, // the guest's IP address
+    pub track: YTVideoInfo,
+    pub requested_at: i64, // unix seconds
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuestQueueStatus {
+    pub settings: GuestQueueSettings,
+    pub running: bool,
+    pub url: Option<String>, // link to share with guests, token included
+}
+
+// "Artist – Title" written on every track change, for OBS and other streaming tools
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NowPlayingOutput {
+    pub enabled: bool,
+    pub file_path: Option<String>,
+    pub pipe_path: Option<String>, // FIFO on Linux and macOS, \\.\pipe\name on Windows
+}
+
+// First-run setup steps, in the order the wizard walks through them
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum OnboardingStep {
+    InstallYtdlp,
+    CheckFfmpeg,
+    ChooseDownloadsDir,
+    CheckPermissions,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnboardingStepStatus {
+    pub step: OnboardingStep,
+    pub done: bool,
+    pub detail: Option<String>, // what was found, e.g. the ffmpeg version or why a check failed
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnboardingStatus {
+    pub steps: Vec<OnboardingStepStatus>,
+    pub current_step: Option<OnboardingStep>, // first step that isn't done, None once set up
+    pub is_complete: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TelemetryEvent {
+    Search,
+    Download,
+    Crash,
+}
+
+// Everything one telemetry upload contains: feature usage counts and nothing else
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryBatch {
+    pub app_version: String,
+    pub os: String,
+    pub since: i64, // unix seconds the counts start at
+    pub until: i64, // set when the batch is sent
+    pub counts: std::collections::BTreeMap<TelemetryEvent, u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryStatus {
+    pub enabled: bool,
+    pub endpoint: Option<String>, // nothing is sent until one is configured
+    pub preview: TelemetryBatch,  // exactly what the next upload would send
+}
+
+// A newer app release found by `check_app_update`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppUpdate {
+    pub version: String,
+    pub current_version: String,
+    pub notes: Option<String>,       // release notes, markdown
+    pub published_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppUpdateProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>, // None when the server doesn't send a length
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum StorageProblemKind {
+    DiskFull,
+    ReadOnly,
+}
+
+// Payload of the `storage-problem` event, downloads stay paused until it clears
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageProblem {
+    pub kind: StorageProblemKind,
+    pub path: String,  // directory that couldn't be written
+    pub cause: String, // human-readable, shown as is
+    pub hint: String,  // what the user can do about it
+}
+
+// Outcome of `run_db_maintenance`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub integrity_ok: bool,
+    pub problems: Vec<String>, // integrity_check messages, empty when healthy
+    pub bytes_reclaimed: i64,
+    pub completed_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceProgress {
+    pub step: String, // "integrity_check", "reindex", "vacuum", then "done"
+    pub progress: f64, // 0.0 to 1.0
+}
+
+// Cookies handed to yt-dlp for requests that need a signed-in account
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CookieSource {
+    Browser(String), // browser name for --cookies-from-browser, e.g. "firefox"
+    File(String),    // path to a Netscape-format cookies.txt
+}
+
+// Where yt-dlp builds come from; nightly and master get extraction fixes before stable
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum YtdlpChannel {
+    #[default]
+    Stable,
+    Nightly,
+    Master,
+}
+
+impl YtdlpChannel {
+    // GitHub repository publishing the channel's releases
+    pub fn repo(self) -> &'static str {
+        match self {
+            YtdlpChannel::Stable => "yt-dlp/yt-dlp",
+            YtdlpChannel::Nightly => "yt-dlp/yt-dlp-nightly-builds",
+            YtdlpChannel::Master => "yt-dlp/yt-dlp-master-builds",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct YtdlpRelease {
+    pub channel: YtdlpChannel,
+    pub version: Option<String>, // pinned release tag, e.g. "2024.08.06"; None follows the latest
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YtdlpVersions {
+    pub release: YtdlpRelease,
+    pub installed: Option<String>,
+    pub previous: Option<String>, // version a rollback would restore
+}
+
+// Advanced yt-dlp options passed to every invocation, to work around extraction breakages
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct YtdlpOptions {
+    pub user_agent: Option<String>,
+    pub player_client: Option<String>, // e.g. "web,mweb" for --extractor-args youtube:player_client=...
+    pub po_token: Option<String>,      // e.g. "web.gvs+TOKEN" for --extractor-args youtube:po_token=...
+    // Extra arguments, as typed, appended to one kind of invocation only
+    #[serde(default)]
+    pub search_args: Option<String>,
+    #[serde(default)]
+    pub stream_args: Option<String>,
+    #[serde(default)]
+    pub download_args: Option<String>,
+}
+
+// What a yt-dlp invocation is for, deciding which extra arguments it gets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum YtdlpOperation {
+    Search,
+    Stream,
+    Download,
+    Lookup, // metadata, playlists and captions; only the shared options
+}
+
+impl YtdlpOptions {
+    pub fn args(&self, operation: YtdlpOperation) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(user_agent) = &self.user_agent {
+            args.push("--user-agent".to_string());
+            args.push(user_agent.clone());
+        }
+
+        let youtube_args: Vec<String> = [("player_client", &self.player_client), ("po_token", &self.po_token)]
+            .into_iter()
+            .filter_map(|(key, value)| value.as_ref().map(|value| format!("{}={}", key, value)))
+            .collect();
+        if !youtube_args.is_empty() {
+            args.push("--extractor-args".to_string());
+            args.push(format!("youtube:{}", youtube_args.join(";")));
+        }
+
+        let extra = match operation {
+            YtdlpOperation::Search => &self.search_args,
+            YtdlpOperation::Stream => &self.stream_args,
+            YtdlpOperation::Download => &self.download_args,
+            YtdlpOperation::Lookup => &None,
+        };
+        if let Some(extra) = extra {
+            // Saved arguments were validated when set
+            args.extend(crate::ytdlp_manager::parse_extra_args(extra).unwrap_or_default());
+        }
+        args
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum YouTubeLibrary {
+    Liked,
+    WatchLater,
+}
+
+impl YouTubeLibrary {
+    pub fn list_id(&self) -> &str {
+        match self {
+            YouTubeLibrary::Liked => "LL",
+            YouTubeLibrary::WatchLater => "WL",
+        }
+    }
+
+    pub fn playlist_name(&self) -> &str {
+        match self {
+            YouTubeLibrary::Liked => "Liked Videos",
+            YouTubeLibrary::WatchLater => "Watch Later",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryImport {
+    pub library: YouTubeLibrary,
+    pub playlist_id: String,
+    pub added: usize,
+    pub total: usize,
+}
+
+// Summary of an imported profile archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileImport {
+    pub app_version: String, // version that exported it
+    pub exported_at: i64,
+    pub downloads_queued: usize, // downloads from the manifest missing on this computer
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ContentType {
+    Music,
+    Speech, // podcasts, lectures, talks
+}
+
+impl ContentType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ContentType::Music => "Music",
+            ContentType::Speech => "Speech",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Music" => Some(ContentType::Music),
+            "Speech" => Some(ContentType::Speech),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedSettings {
+    pub presets: Vec<f32>,
+    pub speech_rate: f32, // default speed for speech content, music always starts at 1.0
+    pub auto_speed: bool, // apply the per-content default when a track starts
+}
+
+impl Default for SpeedSettings {
+    fn default() -> Self {
+        Self {
+            presets: vec![0.75, 1.0, 1.25, 1.5, 1.75, 2.0],
+            speech_rate: 1.0,
+            auto_speed: true,
+        }
+    }
+}
+
+// A short local clip played between tracks when the queue advances, internet-radio style
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JingleSettings {
+    pub enabled: bool,
+    pub file_path: Option<String>,
+    pub every: u32, // tracks between jingles, 1 plays it between every track
+}
+
+impl Default for JingleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file_path: None,
+            every: 1,
+        }
+    }
+}
+
+// Hardware volume keys driving the app's own volume while it plays, instead of the system's
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct VolumeKeySettings {
+    pub enabled: bool,
+    pub step: f32, // slider change per key press, 0.0 to 1.0
+}
+
+impl Default for VolumeKeySettings {
+    fn default() -> Self {
+        Self { enabled: false, step: 0.05 }
+    }
+}
+
+// What auto-advance does once the queue runs out of tracks
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum EndOfQueueAction {
+    #[default]
+    Stop,
+    Radio,                    // queue tracks related to the last one played
+    ReplayQueue,              // start the queue over from the first track
+    FadeOutAndQuit,           // fade out the final track, then exit the app
+    FallbackPlaylist(String), // play this playlist
+}
+
+// A queued track found by filtering, with where it sits in the queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueMatch {
+    pub index: usize,
+    pub track: YTVideoInfo,
+}
+
+// A duration looked up for a track that came without one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackDuration {
+    pub video_id: String,
+    pub duration: i64,
+}
+
+// How urgently listed tracks' metadata is wanted: on screen now, or likely to be soon
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum PrefetchPriority {
+    Visible,
+    Background,
+}
+
+// A listed track's cached thumbnail and, when it had none, its duration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrefetchedMetadata {
+    pub video_id: String,
+    pub thumbnail: Option<String>, // data URL, None when it couldn't be fetched
+    pub duration: Option<i64>,
+}
+
+// Whether to stream at low quality and hold off automatic downloads
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum DataSaverMode {
+    #[default]
+    Auto, // only on metered connections
+    On,
+    Off,
+}
+
+impl DataSaverMode {
+    pub fn is_active(self, metered: bool) -> bool {
+        match self {
+            DataSaverMode::Auto => metered,
+            DataSaverMode::On => true,
+            DataSaverMode::Off => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ListFormat {
+    Text,
+    Markdown,
+}
+
+// Transitions recorded by the audio thread, kept around to diagnose playback problems
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum PlayerEventKind {
+    PlayStarted,
+    Buffered { load_ms: u64 }, // time from the play request until audio was ready
+    Seek { from: f64, to: f64 },
+    Paused,
+    Resumed,
+    Stopped,
+    Stall { position: f64 },  // playback caught up with the download
+    Ended { position: f64 }, // position the sink ran dry at
+    Error { message: String },
+    OutputChanged { device: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerEvent {
+    pub timestamp: i64, // unix milliseconds
+    pub track_id: Option<String>,
+    pub kind: PlayerEventKind,
+}
+
+impl PlayerEvent {
+    pub fn new(track_id: Option<String>, kind: PlayerEventKind) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            track_id,
+            kind,
+        }
+    }
+}
+
+// Payload of `get_system_theme` and the `system-theme-changed` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemTheme {
+    pub dark: bool,
+    pub high_contrast: bool,
+}
+
+// When a notification is shown for the track that just started
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum NotificationPolicy {
+    Always,
+    OnlyWhenHidden, // while the popup isn't showing
+    #[default]
+    Never,          // track changes aren't announced unless the user opts in
+    RespectDnd,     // while the popup isn't showing and the OS isn't in Do Not Disturb or focus mode
+}
+
+// Payload of `intro-skip-suggested`: plays of the track keep skipping to `start`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntroSkipSuggestion {
+    pub video_id: String,
+    pub title: String,
+    pub start: f64,
 }
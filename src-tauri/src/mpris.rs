@@ -0,0 +1,430 @@
+use crate::media_key_manager::handle_media_event;
+use crate::models::YTVideoInfo;
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged;
+use dbus::blocking::Connection;
+use dbus::channel::{MatchingReceiver, Sender};
+use dbus::message::{MatchRule, SignalArgs};
+use dbus::{Message, Path};
+use dbus_crossroads::{Crossroads, IfaceBuilder, IfaceToken, MethodErr};
+use souvlaki::{MediaControlEvent, MediaMetadata, MediaPlayback, MediaPosition, SeekDirection};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.ytaudiobar";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const TRACKLIST_INTERFACE: &str = "org.mpris.MediaPlayer2.TrackList";
+const PLAYLISTS_INTERFACE: &str = "org.mpris.MediaPlayer2.Playlists";
+// Queue entries and playlists are addressed by position, video and playlist IDs aren't valid object paths
+const TRACK_PATH: &str = "/org/ytaudiobar/Track";
+const PLAYLIST_PATH: &str = "/org/ytaudiobar/Playlist";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+// What the D-Bus thread answers property reads from
+struct Snapshot {
+    title: Option<String>,
+    artist: Option<String>,
+    duration: Option<Duration>,
+    status: &'static str,
+    position: Duration,
+    position_at: Instant,
+    queue: Vec<YTVideoInfo>,
+    current_index: Option<usize>,
+    playlists: Vec<(String, String)>, // id, name
+}
+
+impl Snapshot {
+    fn position(&self) -> Duration {
+        if self.status == "Playing" {
+            self.position + self.position_at.elapsed()
+        } else {
+            self.position
+        }
+    }
+
+    fn current_track_id(&self) -> Path<'static> {
+        self.current_index.map(track_path).unwrap_or_else(|| Path::from(NO_TRACK))
+    }
+
+    fn metadata(&self) -> PropMap {
+        let mut metadata = PropMap::new();
+        insert(&mut metadata, "mpris:trackid", self.current_track_id());
+        if let Some(title) = &self.title {
+            insert(&mut metadata, "xesam:title", title.clone());
+        }
+        if let Some(artist) = &self.artist {
+            insert(&mut metadata, "xesam:artist", vec![artist.clone()]);
+        }
+        insert(&mut metadata, "xesam:album", "YouTube".to_string());
+        if let Some(duration) = self.duration {
+            insert(&mut metadata, "mpris:length", duration.as_micros() as i64);
+        }
+        metadata
+    }
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Self {
+            title: None,
+            artist: None,
+            duration: None,
+            status: "Stopped",
+            position: Duration::ZERO,
+            position_at: Instant::now(),
+            queue: Vec::new(),
+            current_index: None,
+            playlists: Vec::new(),
+        }
+    }
+}
+
+// Data of the MPRIS object, shared by all four interfaces
+struct Mpris {
+    snapshot: Arc<Mutex<Snapshot>>,
+    app_handle: AppHandle,
+}
+
+impl Mpris {
+    fn snapshot(&self) -> std::sync::MutexGuard<'_, Snapshot> {
+        self.snapshot.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn send(&self, event: MediaControlEvent) {
+        tauri::async_runtime::spawn(handle_media_event(event, self.app_handle.clone()));
+    }
+}
+
+/// MPRIS on Linux: the Player controls souvlaki provides elsewhere, plus the TrackList and
+/// Playlists interfaces so applets can browse the queue and start saved playlists
+pub struct MprisServer {
+    snapshot: Arc<Mutex<Snapshot>>,
+    signals: mpsc::Sender<Message>,
+}
+
+impl MprisServer {
+    pub fn new(app_handle: AppHandle) -> Result<Self, String> {
+        let snapshot = Arc::new(Mutex::new(Snapshot::default()));
+        let (signals, signal_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        // The connection lives on its own thread, which also sends the signals queued here
+        let thread_snapshot = Arc::clone(&snapshot);
+        std::thread::spawn(move || {
+            let connection = match Connection::new_session()
+                .and_then(|connection| connection.request_name(BUS_NAME, false, true, false).map(|_| connection))
+            {
+                Ok(connection) => connection,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(format!("Failed to register on D-Bus: {}", e)));
+                    return;
+                }
+            };
+            let _ = ready_tx.send(Ok(()));
+            serve(connection, Mpris { snapshot: thread_snapshot, app_handle }, signal_rx);
+        });
+
+        ready_rx
+            .recv()
+            .map_err(|_| "MPRIS thread exited".to_string())??;
+        Ok(Self { snapshot, signals })
+    }
+
+    fn snapshot(&self) -> std::sync::MutexGuard<'_, Snapshot> {
+        self.snapshot.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn set_metadata(&mut self, metadata: MediaMetadata) -> Result<(), String> {
+        let changed = {
+            let mut snapshot = self.snapshot();
+            snapshot.title = metadata.title.map(str::to_string);
+            snapshot.artist = metadata.artist.map(str::to_string);
+            snapshot.duration = metadata.duration;
+            snapshot.metadata()
+        };
+        self.properties_changed(PLAYER_INTERFACE, "Metadata", changed)
+    }
+
+    pub fn set_playback(&mut self, playback: MediaPlayback) -> Result<(), String> {
+        let (status, progress) = match playback {
+            MediaPlayback::Playing { progress } => ("Playing", progress),
+            MediaPlayback::Paused { progress } => ("Paused", progress),
+            MediaPlayback::Stopped => ("Stopped", None),
+        };
+        let changed = {
+            let mut snapshot = self.snapshot();
+            let changed = snapshot.status != status;
+            snapshot.status = status;
+            snapshot.position = progress.map(|MediaPosition(position)| position).unwrap_or_default();
+            snapshot.position_at = Instant::now();
+            changed
+        };
+        if changed {
+            self.properties_changed(PLAYER_INTERFACE, "PlaybackStatus", status.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Replace the track list when the queue or the current track in it changed
+    pub fn set_tracklist(&mut self, queue: Vec<YTVideoInfo>, current_index: Option<usize>) -> Result<(), String> {
+        let current_track = {
+            let mut snapshot = self.snapshot();
+            let unchanged = snapshot.current_index == current_index
+                && snapshot.queue.len() == queue.len()
+                && snapshot.queue.iter().zip(&queue).all(|(a, b)| a.id == b.id);
+            if unchanged {
+                return Ok(());
+            }
+            snapshot.queue = queue;
+            snapshot.current_index = current_index;
+            snapshot.current_track_id()
+        };
+
+        let tracks: Vec<Path<'static>> = (0..self.snapshot().queue.len()).map(track_path).collect();
+        let signal = Message::signal(&Path::from(OBJECT_PATH), &TRACKLIST_INTERFACE.into(), &"TrackListReplaced".into())
+            .append2(tracks, current_track);
+        self.signals.send(signal).map_err(|_| "MPRIS thread exited".to_string())
+    }
+
+    pub fn set_playlists(&mut self, playlists: Vec<(String, String)>) -> Result<(), String> {
+        let count = playlists.len() as u32;
+        let changed = {
+            let mut snapshot = self.snapshot();
+            let changed = snapshot.playlists.len() as u32 != count;
+            snapshot.playlists = playlists;
+            changed
+        };
+        if changed {
+            self.properties_changed(PLAYLISTS_INTERFACE, "PlaylistCount", count)?;
+        }
+        Ok(())
+    }
+
+    fn properties_changed<V: RefArg + 'static>(&self, interface: &str, property: &str, value: V) -> Result<(), String> {
+        let mut changed_properties = PropMap::new();
+        insert(&mut changed_properties, property, value);
+        let signal = PropertiesPropertiesChanged {
+            interface_name: interface.to_string(),
+            changed_properties,
+            invalidated_properties: Vec::new(),
+        };
+        self.signals
+            .send(signal.to_emit_message(&Path::from(OBJECT_PATH)))
+            .map_err(|_| "MPRIS thread exited".to_string())
+    }
+}
+
+fn serve(connection: Connection, mpris: Mpris, signals: mpsc::Receiver<Message>) {
+    let mut crossroads = Crossroads::new();
+    let interfaces = [
+        register_root(&mut crossroads),
+        register_player(&mut crossroads),
+        register_tracklist(&mut crossroads),
+        register_playlists(&mut crossroads),
+    ];
+    crossroads.insert(OBJECT_PATH, &interfaces, mpris);
+
+    connection.start_receive(
+        MatchRule::new_method_call(),
+        Box::new(move |message, connection| {
+            let _ = crossroads.handle_message(message, connection);
+            true
+        }),
+    );
+
+    loop {
+        if let Err(e) = connection.process(Duration::from_millis(100)) {
+            eprintln!("⚠️ MPRIS connection failed: {}", e);
+            return;
+        }
+        loop {
+            match signals.try_recv() {
+                Ok(signal) => {
+                    let _ = connection.send(signal);
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => return,
+            }
+        }
+    }
+}
+
+fn register_root(crossroads: &mut Crossroads) -> IfaceToken<Mpris> {
+    crossroads.register("org.mpris.MediaPlayer2", |b: &mut IfaceBuilder<Mpris>| {
+        b.property("Identity").get(|_, _| Ok("YTAudioBar".to_string()));
+        b.property("CanQuit").get(|_, _| Ok(false));
+        b.property("CanRaise").get(|_, _| Ok(false));
+        b.property("HasTrackList").get(|_, _| Ok(true));
+        b.property("SupportedUriSchemes").get(|_, _| Ok(Vec::<String>::new()));
+        b.property("SupportedMimeTypes").get(|_, _| Ok(Vec::<String>::new()));
+        b.method("Raise", (), (), |_, _, _: ()| Ok(()));
+        b.method("Quit", (), (), |_, _, _: ()| Ok(()));
+    })
+}
+
+fn register_player(crossroads: &mut Crossroads) -> IfaceToken<Mpris> {
+    crossroads.register(PLAYER_INTERFACE, |b: &mut IfaceBuilder<Mpris>| {
+        b.property("PlaybackStatus").get(|_, mpris| Ok(mpris.snapshot().status.to_string()));
+        b.property("Metadata").get(|_, mpris| Ok(mpris.snapshot().metadata()));
+        b.property("Position")
+            .get(|_, mpris| Ok(mpris.snapshot().position().as_micros() as i64))
+            .emits_changed_false();
+        b.property("Rate").get(|_, _| Ok(1.0f64));
+        b.property("MinimumRate").get(|_, _| Ok(1.0f64));
+        b.property("MaximumRate").get(|_, _| Ok(1.0f64));
+        b.property("Volume").get(|_, _| Ok(1.0f64));
+        for capability in ["CanGoNext", "CanGoPrevious", "CanPlay", "CanPause", "CanSeek", "CanControl"] {
+            b.property(capability).get(|_, _| Ok(true));
+        }
+
+        b.method("Play", (), (), |_, mpris, _: ()| {
+            mpris.send(MediaControlEvent::Play);
+            Ok(())
+        });
+        b.method("Pause", (), (), |_, mpris, _: ()| {
+            mpris.send(MediaControlEvent::Pause);
+            Ok(())
+        });
+        b.method("PlayPause", (), (), |_, mpris, _: ()| {
+            mpris.send(MediaControlEvent::Toggle);
+            Ok(())
+        });
+        b.method("Stop", (), (), |_, mpris, _: ()| {
+            mpris.send(MediaControlEvent::Stop);
+            Ok(())
+        });
+        b.method("Next", (), (), |_, mpris, _: ()| {
+            mpris.send(MediaControlEvent::Next);
+            Ok(())
+        });
+        b.method("Previous", (), (), |_, mpris, _: ()| {
+            mpris.send(MediaControlEvent::Previous);
+            Ok(())
+        });
+        b.method("Seek", ("Offset",), (), |_, mpris, (offset,): (i64,)| {
+            let direction = if offset < 0 { SeekDirection::Backward } else { SeekDirection::Forward };
+            mpris.send(MediaControlEvent::SeekBy(direction, Duration::from_micros(offset.unsigned_abs())));
+            Ok(())
+        });
+        b.method("SetPosition", ("TrackId", "Position"), (), |_, mpris, (_, position): (Path<'static>, i64)| {
+            let position = Duration::from_micros(position.max(0) as u64);
+            mpris.send(MediaControlEvent::SetPosition(MediaPosition(position)));
+            Ok(())
+        });
+        b.method("OpenUri", ("Uri",), (), |_, _, _: (String,)| {
+            Err::<(), _>(MethodErr::failed("Opening URIs isn't supported"))
+        });
+    })
+}
+
+fn register_tracklist(crossroads: &mut Crossroads) -> IfaceToken<Mpris> {
+    crossroads.register(TRACKLIST_INTERFACE, |b: &mut IfaceBuilder<Mpris>| {
+        b.property("Tracks")
+            .get(|_, mpris| Ok((0..mpris.snapshot().queue.len()).map(track_path).collect::<Vec<_>>()))
+            .emits_changed_invalidates();
+        b.property("CanEditTracks").get(|_, _| Ok(false));
+        b.signal::<(Vec<Path<'static>>, Path<'static>), _>("TrackListReplaced", ("Tracks", "CurrentTrack"));
+
+        b.method(
+            "GetTracksMetadata",
+            ("TrackIds",),
+            ("Metadata",),
+            |_, mpris, (track_ids,): (Vec<Path<'static>>,)| {
+                let snapshot = mpris.snapshot();
+                let metadata = track_ids
+                    .iter()
+                    .filter_map(|path| {
+                        let index = parse_index(path, TRACK_PATH)?;
+                        Some(track_metadata(path, snapshot.queue.get(index)?))
+                    })
+                    .collect::<Vec<_>>();
+                Ok((metadata,))
+            },
+        );
+        b.method("GoTo", ("TrackId",), (), |_, mpris, (track_id,): (Path<'static>,)| {
+            let index = parse_index(&track_id, TRACK_PATH).ok_or_else(|| MethodErr::invalid_arg(&track_id))?;
+            let _ = mpris.app_handle.emit("mpris-go-to", index);
+            Ok(())
+        });
+        b.method(
+            "AddTrack",
+            ("Uri", "AfterTrack", "SetAsCurrent"),
+            (),
+            |_, _, _: (String, Path<'static>, bool)| Err::<(), _>(MethodErr::failed("The track list is read-only")),
+        );
+        b.method("RemoveTrack", ("TrackId",), (), |_, _, _: (Path<'static>,)| {
+            Err::<(), _>(MethodErr::failed("The track list is read-only"))
+        });
+    })
+}
+
+fn register_playlists(crossroads: &mut Crossroads) -> IfaceToken<Mpris> {
+    crossroads.register(PLAYLISTS_INTERFACE, |b: &mut IfaceBuilder<Mpris>| {
+        b.property("PlaylistCount").get(|_, mpris| Ok(mpris.snapshot().playlists.len() as u32));
+        b.property("Orderings").get(|_, _| Ok(vec!["UserDefined".to_string()]));
+        // Nothing is reported as active; queues are often shuffled or edited after starting a playlist
+        b.property("ActivePlaylist")
+            .get(|_, _| Ok((false, (Path::from("/"), String::new(), String::new()))));
+
+        b.method(
+            "GetPlaylists",
+            ("Index", "MaxCount", "Order", "ReverseOrder"),
+            ("Playlists",),
+            |_, mpris, (index, max_count, _, reverse): (u32, u32, String, bool)| {
+                let snapshot = mpris.snapshot();
+                let mut playlists: Vec<(Path<'static>, String, String)> = snapshot
+                    .playlists
+                    .iter()
+                    .enumerate()
+                    .map(|(position, (_, name))| (playlist_path(position), name.clone(), String::new()))
+                    .collect();
+                if reverse {
+                    playlists.reverse();
+                }
+                let playlists = playlists
+                    .into_iter()
+                    .skip(index as usize)
+                    .take(max_count as usize)
+                    .collect::<Vec<_>>();
+                Ok((playlists,))
+            },
+        );
+        b.method("ActivatePlaylist", ("PlaylistId",), (), |_, mpris, (playlist,): (Path<'static>,)| {
+            let playlist_id = parse_index(&playlist, PLAYLIST_PATH)
+                .and_then(|index| mpris.snapshot().playlists.get(index).map(|(id, _)| id.clone()))
+                .ok_or_else(|| MethodErr::invalid_arg(&playlist))?;
+            let _ = mpris.app_handle.emit("mpris-activate-playlist", playlist_id);
+            Ok(())
+        });
+    })
+}
+
+fn track_metadata(path: &Path<'static>, track: &YTVideoInfo) -> PropMap {
+    let mut metadata = PropMap::new();
+    insert(&mut metadata, "mpris:trackid", path.clone());
+    insert(&mut metadata, "xesam:title", track.display_title().to_string());
+    insert(&mut metadata, "xesam:artist", vec![track.display_uploader().to_string()]);
+    insert(&mut metadata, "mpris:length", track.duration * 1_000_000);
+    if let Some(thumbnail) = &track.thumbnail_url {
+        insert(&mut metadata, "mpris:artUrl", thumbnail.clone());
+    }
+    metadata
+}
+
+fn insert<V: RefArg + 'static>(map: &mut PropMap, key: &str, value: V) {
+    map.insert(key.to_string(), Variant(Box::new(value)));
+}
+
+fn track_path(index: usize) -> Path<'static> {
+    Path::from(format!("{}/{}", TRACK_PATH, index))
+}
+
+fn playlist_path(index: usize) -> Path<'static> {
+    Path::from(format!("{}/{}", PLAYLIST_PATH, index))
+}
+
+fn parse_index(path: &Path, prefix: &str) -> Option<usize> {
+    path.strip_prefix(prefix)?.strip_prefix('/')?.parse().ok()
+}
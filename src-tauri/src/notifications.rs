@@ -0,0 +1,129 @@
+use crate::models::{NotificationPolicy, YTVideoInfo};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_notification::NotificationExt;
+
+/// Whether the policy lets a track change notification through right now.
+/// Blocking, since reading the Do Not Disturb state may query the OS.
+pub fn allows(policy: NotificationPolicy, popup_visible: bool) -> bool {
+    match policy {
+        NotificationPolicy::Always => true,
+        NotificationPolicy::OnlyWhenHidden => !popup_visible,
+        NotificationPolicy::Never => false,
+        NotificationPolicy::RespectDnd => !popup_visible && !is_do_not_disturb(),
+    }
+}
+
+pub fn show_track<R: Runtime>(app: &AppHandle<R>, track: &YTVideoInfo) -> Result<(), String> {
+    app.notification()
+        .builder()
+        .title(track.display_title())
+        .body(track.display_uploader())
+        .show()
+        .map_err(|e| format!("Failed to show notification: {}", e))
+}
+
+/// Show a plain notification, unless the OS is in Do Not Disturb
+pub fn show<R: Runtime>(app: &AppHandle<R>, title: &str, body: &str) -> Result<(), String> {
+    if is_do_not_disturb() {
+        return Ok(());
+    }
+    app.notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+        .map_err(|e| format!("Failed to show notification: {}", e))
+}
+
+/// Show a notification with a "Play now" button that runs `on_play` when clicked. Only Linux
+/// notification servers report the click back, so other platforms use [`show`] instead.
+/// Nothing is shown while the OS is in Do Not Disturb.
+#[cfg(target_os = "linux")]
+pub fn show_with_play_action(title: &str, body: &str, on_play: impl FnOnce() + Send + 'static) -> Result<(), String> {
+    if is_do_not_disturb() {
+        return Ok(());
+    }
+
+    let handle = notify_rust::Notification::new()
+        .appname("YTAudioBar")
+        .summary(title)
+        .body(body)
+        .action("play", "Play now")
+        .show()
+        .map_err(|e| format!("Failed to show notification: {}", e))?;
+    // Waiting blocks until the notification is clicked, dismissed or expires
+    std::thread::spawn(move || {
+        let mut on_play = Some(on_play);
+        handle.wait_for_action(|action| {
+            if action == "play" {
+                if let Some(on_play) = on_play.take() {
+                    on_play();
+                }
+            }
+        });
+    });
+    Ok(())
+}
+
+// Windows doesn't expose the Focus Assist switch itself, but the notification state
+// covers quiet time, presentations and full screen apps, which is when it kicks in
+#[cfg(target_os = "windows")]
+fn is_do_not_disturb() -> bool {
+    use windows::Win32::UI::Shell::{SHQueryUserNotificationState, QUNS_ACCEPTS_NOTIFICATIONS};
+
+    match unsafe { SHQueryUserNotificationState() } {
+        Ok(state) => state != QUNS_ACCEPTS_NOTIFICATIONS,
+        Err(_) => false,
+    }
+}
+
+// Focus modes are recorded as assertions while one is on; the file may be unreadable
+// without Full Disk Access, in which case notifications go through
+#[cfg(target_os = "macos")]
+fn is_do_not_disturb() -> bool {
+    let Some(path) = dirs::home_dir().map(|home| home.join("Library/DoNotDisturb/DB/Assertions.json")) else {
+        return false;
+    };
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    let Ok(assertions) = serde_json::from_str::<serde_json::Value>(&contents) else {
+        return false;
+    };
+    assertions["data"]
+        .as_array()
+        .is_some_and(|data| {
+            data.iter().any(|entry| {
+                entry["storeAssertionRecords"].as_array().is_some_and(|records| !records.is_empty())
+            })
+        })
+}
+
+// KDE and others expose Inhibited on the notification server; GNOME turns banners off instead
+#[cfg(target_os = "linux")]
+fn is_do_not_disturb() -> bool {
+    use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+    use dbus::blocking::Connection;
+    use std::time::Duration;
+
+    if let Ok(connection) = Connection::new_session() {
+        let proxy = connection.with_proxy(
+            "org.freedesktop.Notifications",
+            "/org/freedesktop/Notifications",
+            Duration::from_millis(500),
+        );
+        if let Ok(inhibited) = proxy.get::<bool>("org.freedesktop.Notifications", "Inhibited") {
+            return inhibited;
+        }
+    }
+
+    std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+        .output()
+        .is_ok_and(|output| output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "false")
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn is_do_not_disturb() -> bool {
+    false
+}
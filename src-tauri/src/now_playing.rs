@@ -0,0 +1,57 @@
+use crate::models::{NowPlayingOutput, YTVideoInfo};
+use std::path::Path;
+
+/// The line streaming tools show, empty while nothing is playing
+pub fn line(track: Option<&YTVideoInfo>) -> String {
+    track
+        .map(|track| format!("{} – {}", track.display_uploader(), track.display_title()))
+        .unwrap_or_default()
+}
+
+/// Write the line to the text file and the named pipe, whichever are set
+pub async fn write(output: &NowPlayingOutput, line: &str) -> Result<(), String> {
+    if let Some(path) = output.file_path.as_deref() {
+        write_file(Path::new(path), line).await?;
+    }
+    if let Some(path) = output.pipe_path.as_deref() {
+        write_pipe(path, line).await?;
+    }
+    Ok(())
+}
+
+// Written beside the file and renamed over it, so readers never see it half written
+async fn write_file(path: &Path, line: &str) -> Result<(), String> {
+    let staging = path.with_extension("tmp");
+    tokio::fs::write(&staging, line)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    tokio::fs::rename(&staging, path)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// Nothing is written while no one is reading the FIFO, rather than blocking until they do
+#[cfg(unix)]
+async fn write_pipe(path: &str, line: &str) -> Result<(), String> {
+    use tokio::io::AsyncWriteExt;
+
+    let Ok(mut pipe) = tokio::net::unix::pipe::OpenOptions::new().open_sender(path) else {
+        return Ok(());
+    };
+    pipe.write_all(format!("{}\n", line).as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write to {}: {}", path, e))
+}
+
+// Connects as a client to a pipe like \\.\pipe\nowplaying served by the streaming tool
+#[cfg(windows)]
+async fn write_pipe(path: &str, line: &str) -> Result<(), String> {
+    use tokio::io::AsyncWriteExt;
+
+    let Ok(mut pipe) = tokio::net::windows::named_pipe::ClientOptions::new().open(path) else {
+        return Ok(());
+    };
+    pipe.write_all(format!("{}\n", line).as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write to {}: {}", path, e))
+}
@@ -0,0 +1,91 @@
+use crate::app_data::AppData;
+use crate::audio_capabilities::AudioCapabilities;
+use crate::database::DatabaseManager;
+use crate::models::{OnboardingStatus, OnboardingStep, OnboardingStepStatus, YtdlpRelease};
+use crate::storage_monitor;
+use crate::ytdlp_installer::YTDLPInstaller;
+use std::path::PathBuf;
+
+const STEPS: [OnboardingStep; 4] = [
+    OnboardingStep::InstallYtdlp,
+    OnboardingStep::CheckFfmpeg,
+    OnboardingStep::ChooseDownloadsDir,
+    OnboardingStep::CheckPermissions,
+];
+
+// Steps the user confirmed in the wizard, kept so setup isn't repeated on the next launch
+const COMPLETED_KEY: &str = "onboarding_completed_steps";
+
+/// Where first-run setup stands. Steps backed by a check are re-evaluated every time, so
+/// e.g. a yt-dlp binary deleted after setup sends the wizard back to that step.
+pub async fn get_status(
+    db: &DatabaseManager,
+    capabilities: &AudioCapabilities,
+    downloads_dir: PathBuf,
+) -> Result<OnboardingStatus, String> {
+    let completed = completed_steps(db).await?;
+    let writable_error = check_writable(vec![downloads_dir.clone(), AppData::get_data_dir()]).await.err();
+
+    let steps: Vec<OnboardingStepStatus> = STEPS
+        .iter()
+        .map(|&step| {
+            let confirmed = completed.contains(&step);
+            let (done, detail) = match step {
+                OnboardingStep::InstallYtdlp => (YTDLPInstaller::get_ytdlp_path().exists(), None),
+                // Missing ffmpeg only limits playback, so the user can acknowledge it and move on
+                OnboardingStep::CheckFfmpeg => match &capabilities.ffmpeg_version {
+                    Some(version) => (true, Some(version.clone())),
+                    None => (confirmed, Some("ffmpeg not found, streaming is unavailable".to_string())),
+                },
+                OnboardingStep::ChooseDownloadsDir => (confirmed, Some(downloads_dir.to_string_lossy().to_string())),
+                OnboardingStep::CheckPermissions => (writable_error.is_none(), writable_error.clone()),
+            };
+            OnboardingStepStatus { step, done, detail }
+        })
+        .collect();
+
+    let current_step = steps.iter().find(|s| !s.done).map(|s| s.step);
+    Ok(OnboardingStatus {
+        is_complete: current_step.is_none(),
+        current_step,
+        steps,
+    })
+}
+
+/// Carry out or confirm a step: installs yt-dlp, re-runs the permission check, or records
+/// the user's choice for the steps that need one
+pub async fn complete_step(db: &DatabaseManager, step: OnboardingStep, downloads_dir: PathBuf) -> Result<(), String> {
+    match step {
+        OnboardingStep::InstallYtdlp => {
+            if !YTDLPInstaller::is_installed().await {
+                let release: YtdlpRelease = db.get_preference("ytdlp_release").await.map_err(|e| e.to_string())?.unwrap_or_default();
+                YTDLPInstaller::install(&release).await?;
+            }
+        }
+        OnboardingStep::CheckPermissions => check_writable(vec![downloads_dir, AppData::get_data_dir()]).await?,
+        OnboardingStep::CheckFfmpeg | OnboardingStep::ChooseDownloadsDir => {}
+    }
+
+    let mut completed = completed_steps(db).await?;
+    if !completed.contains(&step) {
+        completed.push(step);
+        db.set_preference(COMPLETED_KEY, &completed).await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+async fn completed_steps(db: &DatabaseManager) -> Result<Vec<OnboardingStep>, String> {
+    let completed = db.get_preference(COMPLETED_KEY).await.map_err(|e| e.to_string())?;
+    Ok(completed.unwrap_or_default())
+}
+
+async fn check_writable(dirs: Vec<PathBuf>) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        for dir in dirs {
+            storage_monitor::probe(&dir).map_err(|e| format!("Can't write to {}: {}", dir.display(), e))?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
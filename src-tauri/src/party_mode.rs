@@ -0,0 +1,133 @@
+use crate::models::PartyModeStatus;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+// Guests can nudge the volume this far either side of where it was when party mode was locked
+const VOLUME_LEEWAY: f32 = 0.15;
+// Wrong PINs in a row before unlocking is refused for a while
+const MAX_PIN_ATTEMPTS: u32 = 5;
+const PIN_LOCKOUT: Duration = Duration::from_secs(60);
+
+/// What's saved while party mode is on, so restarting the app doesn't unlock it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartyLock {
+    salt: String,
+    pin_hash: String,
+    volume: f32, // slider position when locked
+}
+
+impl PartyLock {
+    fn new(pin: &str, volume: f32) -> Self {
+        let salt = uuid::Uuid::new_v4().to_string();
+        let pin_hash = hash_pin(&salt, pin);
+        Self { salt, pin_hash, volume }
+    }
+
+    fn matches(&self, pin: &str) -> bool {
+        hash_pin(&self.salt, pin) == self.pin_hash
+    }
+
+    fn volume_range(&self) -> (f32, f32) {
+        ((self.volume - VOLUME_LEEWAY).max(0.0), (self.volume + VOLUME_LEEWAY).min(1.0))
+    }
+}
+
+#[derive(Default)]
+struct Attempts {
+    failures: u32,
+    locked_out_until: Option<Instant>,
+}
+
+/// Guards stop, clear-queue and delete operations behind a PIN so guests queueing songs
+/// can't end the session
+pub struct PartyMode {
+    lock: Mutex<Option<PartyLock>>,
+    attempts: Mutex<Attempts>,
+}
+
+impl PartyMode {
+    pub fn new() -> Self {
+        Self {
+            lock: Mutex::new(None),
+            attempts: Mutex::new(Attempts::default()),
+        }
+    }
+
+    /// Pick up a lock saved before the last restart
+    pub async fn restore(&self, lock: PartyLock) {
+        *self.lock.lock().await = Some(lock);
+    }
+
+    /// Lock party mode with `pin`, letting the volume move only a little from `volume`
+    pub async fn enable(&self, pin: &str, volume: f32) -> Result<PartyLock, String> {
+        if !(4..=8).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+            return Err("The PIN must be 4 to 8 digits".to_string());
+        }
+
+        let mut lock = self.lock.lock().await;
+        if lock.is_some() {
+            return Err("Party mode is already on".to_string());
+        }
+        let party_lock = PartyLock::new(pin, volume);
+        *lock = Some(party_lock.clone());
+        *self.attempts.lock().await = Attempts::default();
+        Ok(party_lock)
+    }
+
+    /// Unlock with the PIN party mode was enabled with
+    pub async fn disable(&self, pin: &str) -> Result<(), String> {
+        let mut attempts = self.attempts.lock().await;
+        if let Some(until) = attempts.locked_out_until {
+            let remaining = until.saturating_duration_since(Instant::now());
+            if !remaining.is_zero() {
+                return Err(format!("Too many wrong PINs, try again in {} seconds", remaining.as_secs().max(1)));
+            }
+            attempts.locked_out_until = None;
+        }
+
+        let mut lock = self.lock.lock().await;
+        let Some(party_lock) = lock.as_ref() else {
+            return Ok(());
+        };
+        if !party_lock.matches(pin) {
+            attempts.failures += 1;
+            if attempts.failures >= MAX_PIN_ATTEMPTS {
+                attempts.failures = 0;
+                attempts.locked_out_until = Some(Instant::now() + PIN_LOCKOUT);
+            }
+            return Err("Wrong PIN".to_string());
+        }
+
+        *lock = None;
+        *attempts = Attempts::default();
+        Ok(())
+    }
+
+    /// Fail with a message naming `action` while party mode is on
+    pub async fn ensure_unlocked(&self, action: &str) -> Result<(), String> {
+        if self.lock.lock().await.is_some() {
+            return Err(format!("{} is disabled in party mode", action));
+        }
+        Ok(())
+    }
+
+    /// The slider range guests are held to, if party mode is on
+    pub async fn volume_range(&self) -> Option<(f32, f32)> {
+        self.lock.lock().await.as_ref().map(PartyLock::volume_range)
+    }
+
+    pub async fn status(&self) -> PartyModeStatus {
+        let range = self.volume_range().await;
+        PartyModeStatus {
+            enabled: range.is_some(),
+            min_volume: range.map(|(min, _)| min),
+            max_volume: range.map(|(_, max)| max),
+        }
+    }
+}
+
+fn hash_pin(salt: &str, pin: &str) -> String {
+    format!("{:x}", Sha256::digest(format!("{}:{}", salt, pin).as_bytes()))
+}
@@ -0,0 +1,92 @@
+use serde::Deserialize;
+
+/// Launch argument carrying an action, e.g. `--action="volume 50"`
+pub const ACTION_ARG: &str = "--action=";
+
+/// One thing an external trigger (a script, an assistant, a speech tool) asks the player to do.
+/// Sent either as JSON, `{"action": "volume", "percent": 50}`, or as a short phrase, `volume 50`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PlayerAction {
+    Play,
+    Pause,
+    Toggle,
+    Stop,
+    Next,
+    Previous,
+    PlaySearch { query: String },
+    PlayArtist { artist: String },
+    PlayPlaylist { playlist: String },
+    QueueSearch { query: String },
+    QueuePlaylist { playlist: String },
+    Volume { percent: u8 },
+    Seek { seconds: f64 },
+}
+
+/// Read an action from JSON, or from a phrase when the input isn't a JSON object
+pub fn parse(input: &str) -> Result<PlayerAction, String> {
+    let input = input.trim();
+    if input.starts_with('{') {
+        return serde_json::from_str(input).map_err(|e| format!("Invalid action: {}", e));
+    }
+
+    let (verb, rest) = split_word(input);
+    let verb = verb.to_lowercase();
+    let action = match verb.as_str() {
+        "play" | "resume" if rest.is_empty() => PlayerAction::Play,
+        "pause" => PlayerAction::Pause,
+        "toggle" => PlayerAction::Toggle,
+        "stop" => PlayerAction::Stop,
+        "next" | "skip" => PlayerAction::Next,
+        "previous" | "back" => PlayerAction::Previous,
+        "play" | "queue" => {
+            let queue = verb == "queue";
+            let (kind, name) = split_word(rest);
+            match kind.to_lowercase().as_str() {
+                "artist" if !queue && !name.is_empty() => PlayerAction::PlayArtist { artist: name.to_string() },
+                "playlist" if !name.is_empty() => {
+                    let playlist = name.to_string();
+                    if queue {
+                        PlayerAction::QueuePlaylist { playlist }
+                    } else {
+                        PlayerAction::PlayPlaylist { playlist }
+                    }
+                }
+                _ if rest.is_empty() => return Err("Queue what? Try \"queue <search>\"".to_string()),
+                _ if queue => PlayerAction::QueueSearch { query: rest.to_string() },
+                _ => PlayerAction::PlaySearch { query: rest.to_string() },
+            }
+        }
+        "volume" => {
+            let percent = rest.trim_end_matches('%').parse::<u8>().ok().filter(|p| *p <= 100);
+            PlayerAction::Volume { percent: percent.ok_or("Volume takes a percentage from 0 to 100")? }
+        }
+        "seek" => PlayerAction::Seek {
+            seconds: parse_time(rest).ok_or("Seek takes seconds or m:ss")?,
+        },
+        _ => return Err(format!("Unknown action \"{}\"", input)),
+    };
+    Ok(action)
+}
+
+// First word and whatever follows it, both trimmed
+fn split_word(input: &str) -> (&str, &str) {
+    let input = input.trim();
+    match input.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (input, ""),
+    }
+}
+
+// "90" or "1:30" as seconds
+fn parse_time(input: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    for part in input.split(':') {
+        let value: f64 = part.trim().parse().ok()?;
+        if value < 0.0 {
+            return None;
+        }
+        seconds = seconds * 60.0 + value;
+    }
+    Some(seconds)
+}
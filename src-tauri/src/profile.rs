@@ -0,0 +1,135 @@
+use crate::database::DatabaseManager;
+use crate::download_manager::DownloadedTrack;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+// Bumped when the archive layout changes in a way older versions can't read
+const PROFILE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DATABASE_ENTRY: &str = "ytaudiobar.db";
+const DOWNLOADS_ENTRY: &str = "downloads.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProfileManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub exported_at: i64,
+    pub includes_downloads: bool,
+}
+
+/// Bundle the database (playlists, history, settings) and optionally the downloads list into one zip
+pub async fn export(db: &DatabaseManager, downloads: Option<Vec<DownloadedTrack>>, path: &Path) -> Result<(), String> {
+    let snapshot = temp_database_path();
+    db.snapshot_to(&snapshot).await.map_err(|e| format!("Failed to snapshot database: {}", e))?;
+
+    let archive_path = path.to_path_buf();
+    let snapshot_path = snapshot.clone();
+    let result = tokio::task::spawn_blocking(move || write_archive(&snapshot_path, downloads.as_deref(), &archive_path))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r);
+
+    let _ = std::fs::remove_file(&snapshot);
+    result
+}
+
+/// Replace the current profile with an exported one, returning its manifest and downloads list
+pub async fn import(db: &DatabaseManager, path: &Path) -> Result<(ProfileManifest, Vec<DownloadedTrack>), String> {
+    let snapshot = temp_database_path();
+
+    let archive_path = path.to_path_buf();
+    let snapshot_path = snapshot.clone();
+    let read = tokio::task::spawn_blocking(move || read_archive(&archive_path, &snapshot_path))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r);
+
+    let result = match read {
+        Ok((manifest, downloads)) => db
+            .restore_from(&snapshot)
+            .await
+            .map(|_| (manifest, downloads))
+            .map_err(|e| format!("Failed to restore database: {}", e)),
+        Err(e) => Err(e),
+    };
+
+    let _ = std::fs::remove_file(&snapshot);
+    result
+}
+
+fn temp_database_path() -> PathBuf {
+    std::env::temp_dir().join(format!("ytaudiobar-profile-{}.db", uuid::Uuid::new_v4()))
+}
+
+fn write_archive(snapshot: &Path, downloads: Option<&[DownloadedTrack]>, path: &Path) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let manifest = ProfileManifest {
+        format_version: PROFILE_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().timestamp(),
+        includes_downloads: downloads.is_some(),
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    zip.start_file(MANIFEST_ENTRY, options).map_err(|e| e.to_string())?;
+    zip.write_all(&manifest).map_err(|e| e.to_string())?;
+
+    let database = std::fs::read(snapshot).map_err(|e| e.to_string())?;
+    zip.start_file(DATABASE_ENTRY, options).map_err(|e| e.to_string())?;
+    zip.write_all(&database).map_err(|e| e.to_string())?;
+
+    // Only the list of downloads travels, not the audio; the new computer fetches them again
+    if let Some(downloads) = downloads {
+        let json = serde_json::to_vec_pretty(downloads).map_err(|e| e.to_string())?;
+        zip.start_file(DOWNLOADS_ENTRY, options).map_err(|e| e.to_string())?;
+        zip.write_all(&json).map_err(|e| e.to_string())?;
+    }
+
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Checks the manifest before extracting the database to `snapshot`
+fn read_archive(path: &Path, snapshot: &Path) -> Result<(ProfileManifest, Vec<DownloadedTrack>), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut zip = ZipArchive::new(file).map_err(|_| "Not a YTAudioBar profile archive".to_string())?;
+
+    let manifest: ProfileManifest = {
+        let entry = zip.by_name(MANIFEST_ENTRY).map_err(|_| "Not a YTAudioBar profile archive".to_string())?;
+        serde_json::from_reader(entry).map_err(|e| format!("Invalid profile manifest: {}", e))?
+    };
+
+    if manifest.format_version > PROFILE_FORMAT_VERSION || is_newer_version(&manifest.app_version, env!("CARGO_PKG_VERSION")) {
+        return Err(format!(
+            "This profile was exported by YTAudioBar {}. Update to that version or newer to import it.",
+            manifest.app_version
+        ));
+    }
+
+    {
+        let mut entry = zip.by_name(DATABASE_ENTRY).map_err(|_| "Profile archive has no database".to_string())?;
+        let mut database = Vec::new();
+        entry.read_to_end(&mut database).map_err(|e| e.to_string())?;
+        std::fs::write(snapshot, database).map_err(|e| e.to_string())?;
+    }
+
+    let downloads = match zip.by_name(DOWNLOADS_ENTRY) {
+        Ok(entry) => serde_json::from_reader(entry).map_err(|e| format!("Invalid downloads list: {}", e))?,
+        Err(_) => Vec::new(),
+    };
+
+    Ok((manifest, downloads))
+}
+
+// Compares dotted versions numerically, e.g. "1.0.10" is newer than "1.0.9"
+fn is_newer_version(version: &str, current: &str) -> bool {
+    let parse = |v: &str| v.split('.').map(|part| part.parse::<u64>().unwrap_or(0)).collect::<Vec<_>>();
+    parse(version) > parse(current)
+}
@@ -1,19 +1,38 @@
-use crate::models::{QueueState, RepeatMode, YTVideoInfo};
+use crate::models::{EndOfQueueAction, QueueMatch, QueueState, RepeatMode, ShuffleScope, YTVideoInfo};
 use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+// Number of recently played tracks remembered for history-aware shuffling
+const PLAY_HISTORY_LIMIT: usize = 200;
+
+// How much a track's skip rate lowers its shuffle weight, and the weight it never drops below
+const SKIP_PENALTY: f64 = 0.8;
+const MIN_SHUFFLE_WEIGHT: f64 = 0.1;
+
 pub struct QueueManager {
     state: Arc<Mutex<QueueState>>,
+    end_of_queue_action: Arc<Mutex<EndOfQueueAction>>,
 }
 
 impl QueueManager {
     pub fn new() -> Self {
         Self {
             state: Arc::new(Mutex::new(QueueState::default())),
+            end_of_queue_action: Arc::new(Mutex::new(EndOfQueueAction::default())),
         }
     }
 
+    pub async fn set_end_of_queue_action(&self, action: EndOfQueueAction) {
+        *self.end_of_queue_action.lock().await = action;
+    }
+
+    pub async fn get_end_of_queue_action(&self) -> EndOfQueueAction {
+        self.end_of_queue_action.lock().await.clone()
+    }
+
     pub async fn add_to_queue(&self, track: YTVideoInfo) {
         let mut state = self.state.lock().await;
         state.queue.push(track);
@@ -26,6 +45,17 @@ impl QueueManager {
         println!("➕ Added batch to queue. Total tracks: {}", state.queue.len());
     }
 
+    /// Fill in a duration for queued copies of a track that came without one
+    pub async fn set_duration(&self, video_id: &str, duration: i64) {
+        let mut state = self.state.lock().await;
+        let state = &mut *state;
+        for track in state.queue.iter_mut().chain(state.original_queue.iter_mut()) {
+            if track.id == video_id && track.duration <= 0 {
+                track.duration = duration;
+            }
+        }
+    }
+
     pub async fn insert_next(&self, track: YTVideoInfo) {
         let mut state = self.state.lock().await;
         let insert_index = (state.current_index + 1).max(0) as usize;
@@ -61,9 +91,129 @@ impl QueueManager {
         let mut state = self.state.lock().await;
         state.queue.clear();
         state.current_index = -1;
+        state.shuffle_scope = None;
+        state.pending_track_ids.clear();
+        state.radio_station = None;
+        state.source_playlist = None;
+        state.started_at = chrono::Utc::now().timestamp();
         println!("🧹 Queue cleared");
     }
 
+    pub async fn snapshot(&self) -> QueueState {
+        self.state.lock().await.clone()
+    }
+
+    pub async fn restore(&self, snapshot: QueueState) {
+        let mut state = self.state.lock().await;
+        // Keep the play history, it has moved on since the snapshot
+        let play_history = std::mem::take(&mut state.play_history);
+        *state = snapshot;
+        state.play_history = play_history;
+        println!("↩️ Queue restored. Total tracks: {}", state.queue.len());
+    }
+
+    /// Replace the queue with a lazily materialized shuffle of `track_ids`.
+    /// Often skipped tracks tend to come later, recently played ones are pushed to the back.
+    pub async fn start_lazy_shuffle(&self, scope: ShuffleScope, track_ids: Vec<String>, skip_rates: &HashMap<String, f64>) {
+        let mut state = self.state.lock().await;
+
+        // Weighted shuffle: sorting by random^(1/weight) puts heavier tracks first more often
+        let mut rng = rand::thread_rng();
+        let mut keyed: Vec<(f64, String)> = track_ids
+            .into_iter()
+            .map(|id| {
+                let skip_rate = skip_rates.get(&id).copied().unwrap_or(0.0);
+                let weight = (1.0 - skip_rate * SKIP_PENALTY).max(MIN_SHUFFLE_WEIGHT);
+                (rng.gen::<f64>().powf(1.0 / weight), id)
+            })
+            .collect();
+        keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let track_ids: Vec<String> = keyed.into_iter().map(|(_, id)| id).collect();
+
+        let history = &state.play_history;
+        let (mut fresh, recent): (Vec<String>, Vec<String>) =
+            track_ids.into_iter().partition(|id| !history.contains(id));
+        fresh.extend(recent);
+
+        state.queue.clear();
+        state.original_queue.clear();
+        state.current_index = -1;
+        state.shuffle_scope = Some(scope);
+        state.radio_station = None;
+        state.source_playlist = None;
+        state.started_at = chrono::Utc::now().timestamp();
+        // Stored in reverse so chunks can be popped off the end cheaply
+        fresh.reverse();
+        state.pending_track_ids = fresh;
+
+        println!("🔀 Shuffling {} tracks lazily", state.pending_track_ids.len());
+    }
+
+    /// Replace the queue with pasted links, looked up into tracks a few at a time as it plays
+    pub async fn start_url_list(&self, video_ids: Vec<String>) {
+        let mut state = self.state.lock().await;
+        state.queue.clear();
+        state.original_queue.clear();
+        state.current_index = -1;
+        state.shuffle_scope = None;
+        state.radio_station = None;
+        state.source_playlist = None;
+        state.started_at = chrono::Utc::now().timestamp();
+        // Stored in reverse like a lazy shuffle, so chunks can be popped off the end
+        state.pending_track_ids = video_ids.into_iter().rev().collect();
+
+        println!("🔗 Queued {} links", state.pending_track_ids.len());
+    }
+
+    /// Whether the queue is running low and more pending tracks should be materialized
+    pub async fn needs_refill(&self, threshold: usize) -> bool {
+        let state = self.state.lock().await;
+        let remaining = state.queue.len() as i32 - (state.current_index + 1);
+        !state.pending_track_ids.is_empty() && remaining < threshold as i32
+    }
+
+    /// Remember which playlist the queue came from and since when it has been playing
+    pub async fn set_source_playlist(&self, playlist_id: Option<String>, started_at: i64) {
+        let mut state = self.state.lock().await;
+        state.source_playlist = playlist_id;
+        state.started_at = started_at;
+    }
+
+    /// Keep topping up the queue with tracks for `artist` until it is cleared
+    pub async fn start_radio_station(&self, artist: String) {
+        self.state.lock().await.radio_station = Some(artist);
+    }
+
+    /// The radio station's artist, when the queue is running low and should be topped up
+    pub async fn radio_needs_refill(&self, threshold: usize) -> Option<String> {
+        let state = self.state.lock().await;
+        let remaining = state.queue.len() as i32 - (state.current_index + 1);
+        if remaining < threshold as i32 {
+            state.radio_station.clone()
+        } else {
+            None
+        }
+    }
+
+    pub async fn take_pending_chunk(&self, size: usize) -> Vec<String> {
+        let mut state = self.state.lock().await;
+        let split_at = state.pending_track_ids.len().saturating_sub(size);
+        let mut chunk = state.pending_track_ids.split_off(split_at);
+        chunk.reverse();
+        chunk
+    }
+
+    pub async fn get_shuffle_scope(&self) -> Option<ShuffleScope> {
+        self.state.lock().await.shuffle_scope.clone()
+    }
+
+    pub async fn record_played(&self, track_id: &str) {
+        let mut state = self.state.lock().await;
+        state.play_history.retain(|id| id != track_id);
+        state.play_history.push_front(track_id.to_string());
+        state.play_history.truncate(PLAY_HISTORY_LIMIT);
+    }
+
     pub async fn play_track_at(&self, index: usize) -> Option<YTVideoInfo> {
         let mut state = self.state.lock().await;
 
@@ -91,6 +241,20 @@ impl QueueManager {
                     None
                 }
             }
+            RepeatMode::Count(remaining) => {
+                // Repeat current track, going back to the earlier mode once the count is used up
+                state.repeat_mode = if remaining > 1 {
+                    RepeatMode::Count(remaining - 1)
+                } else {
+                    println!("🔁 Repeat count done, back to {}", state.repeat_after_count.as_str());
+                    state.repeat_after_count
+                };
+                if state.current_index >= 0 && (state.current_index as usize) < state.queue.len() {
+                    state.queue.get(state.current_index as usize).cloned()
+                } else {
+                    None
+                }
+            }
             RepeatMode::All => {
                 // Move to next track, loop back to start
                 state.current_index = (state.current_index + 1) % state.queue.len() as i32;
@@ -117,7 +281,7 @@ impl QueueManager {
         }
 
         match state.repeat_mode {
-            RepeatMode::One => {
+            RepeatMode::One | RepeatMode::Count(_) => {
                 // Repeat current track
                 if state.current_index >= 0 && (state.current_index as usize) < state.queue.len() {
                     state.queue.get(state.current_index as usize).cloned()
@@ -154,7 +318,7 @@ impl QueueManager {
         }
 
         match state.repeat_mode {
-            RepeatMode::One | RepeatMode::All => true,
+            RepeatMode::One | RepeatMode::Count(_) | RepeatMode::All => true,
             RepeatMode::Off => (state.current_index + 1) < state.queue.len() as i32,
         }
     }
@@ -226,6 +390,38 @@ impl QueueManager {
         state.repeat_mode
     }
 
+    /// Repeat the current track `count` more times, then go back to the current mode.
+    /// Zero cancels a running count.
+    pub async fn set_repeat_count(&self, count: u32) -> RepeatMode {
+        let mut state = self.state.lock().await;
+        if !matches!(state.repeat_mode, RepeatMode::Count(_)) {
+            state.repeat_after_count = state.repeat_mode;
+        }
+        state.repeat_mode = if count > 0 {
+            RepeatMode::Count(count)
+        } else {
+            state.repeat_after_count
+        };
+
+        println!("🔁 Repeat mode: {:?}", state.repeat_mode);
+        state.repeat_mode
+    }
+
+    /// Queued tracks whose title or uploader contains `query`, ignoring case, with their positions
+    pub async fn filter_queue(&self, query: &str) -> Vec<QueueMatch> {
+        let query = query.trim().to_lowercase();
+        let state = self.state.lock().await;
+        state
+            .queue
+            .iter()
+            .enumerate()
+            .filter(|(_, track)| {
+                track.title.to_lowercase().contains(&query) || track.uploader.to_lowercase().contains(&query)
+            })
+            .map(|(index, track)| QueueMatch { index, track: track.clone() })
+            .collect()
+    }
+
     pub async fn get_queue(&self) -> Vec<YTVideoInfo> {
         let state = self.state.lock().await;
         state.queue.clone()
@@ -236,6 +432,15 @@ impl QueueManager {
         state.current_index
     }
 
+    /// Whether `video_id` is the track the queue is on, not one played from outside it
+    pub async fn is_current(&self, video_id: &str) -> bool {
+        let state = self.state.lock().await;
+        usize::try_from(state.current_index)
+            .ok()
+            .and_then(|index| state.queue.get(index))
+            .is_some_and(|track| track.id == video_id)
+    }
+
     pub async fn get_queue_info(&self) -> String {
         let state = self.state.lock().await;
 
@@ -246,9 +451,10 @@ impl QueueManager {
         let track_info = format!("Track {}/{}", state.current_index + 1, state.queue.len());
         let shuffle_info = if state.shuffle_mode { " • Shuffled" } else { "" };
         let repeat_info = match state.repeat_mode {
-            RepeatMode::Off => "",
-            RepeatMode::All => " • Repeat All",
-            RepeatMode::One => " • Repeat One",
+            RepeatMode::Off => String::new(),
+            RepeatMode::All => " • Repeat All".to_string(),
+            RepeatMode::One => " • Repeat One".to_string(),
+            RepeatMode::Count(remaining) => format!(" • Repeat {}×", remaining),
         };
 
         format!("{}{}{}", track_info, shuffle_info, repeat_info)
@@ -0,0 +1,146 @@
+use crate::models::CooldownStatus;
+use crate::recent_log;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+// Spacing between yt-dlp requests to YouTube, so bursts of searches and downloads don't look like a bot
+const REQUEST_SPACING: Duration = Duration::from_millis(750);
+// A throttled request pauses everything for this long, doubling with each consecutive throttle
+const BACKOFF_BASE: Duration = Duration::from_secs(30);
+const BACKOFF_MAX: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Default)]
+struct Cooldown {
+    until: Option<Instant>,
+    strikes: u32, // consecutive throttled requests
+}
+
+/// Shared by every yt-dlp caller: spaces requests out and backs off exponentially after
+/// YouTube answers with HTTP 429
+pub struct RateLimiter {
+    next_slot: Mutex<Instant>,
+    cooldown: Mutex<Cooldown>,
+    app_handle: Mutex<Option<AppHandle>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            next_slot: Mutex::new(Instant::now()),
+            cooldown: Mutex::new(Cooldown::default()),
+            app_handle: Mutex::new(None),
+        }
+    }
+
+    pub async fn set_app_handle(&self, handle: AppHandle) {
+        *self.app_handle.lock().await = Some(handle);
+    }
+
+    /// Wait for the next request slot, failing right away while cooling down so the user
+    /// isn't left waiting on a search
+    pub async fn acquire(&self) -> Result<(), String> {
+        if let Some(remaining) = self.cooldown_remaining().await {
+            return Err(format!(
+                "YouTube is limiting requests, try again in {} seconds",
+                remaining.as_secs().max(1)
+            ));
+        }
+        self.wait_for_slot().await;
+        Ok(())
+    }
+
+    /// Wait out any cooldown, then for the next request slot; for background work like downloads
+    pub async fn acquire_patiently(&self) {
+        while let Some(remaining) = self.cooldown_remaining().await {
+            tokio::time::sleep(remaining).await;
+        }
+        self.wait_for_slot().await;
+    }
+
+    async fn wait_for_slot(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + REQUEST_SPACING;
+            slot
+        };
+        tokio::time::sleep_until(slot.into()).await;
+    }
+
+    async fn cooldown_remaining(&self) -> Option<Duration> {
+        let until = self.cooldown.lock().await.until?;
+        let remaining = until.saturating_duration_since(Instant::now());
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
+    /// Feed back how a request went: throttling starts or extends the cooldown, anything else
+    /// that succeeded resets the backoff. The error output is kept for diagnostics.
+    pub async fn report(&self, succeeded: bool, error_output: &str) {
+        recent_log::record("yt-dlp", error_output);
+        if is_throttled(error_output) {
+            self.start_cooldown().await;
+        } else if succeeded {
+            self.cooldown.lock().await.strikes = 0;
+        }
+    }
+
+    async fn start_cooldown(&self) {
+        let (until, delay) = {
+            let mut cooldown = self.cooldown.lock().await;
+            // Requests already in flight when the cooldown started don't count as new strikes
+            if cooldown.until.is_some_and(|until| until > Instant::now()) {
+                return;
+            }
+            cooldown.strikes += 1;
+            let delay = BACKOFF_BASE
+                .saturating_mul(2u32.saturating_pow(cooldown.strikes - 1))
+                .min(BACKOFF_MAX);
+            let until = Instant::now() + delay;
+            cooldown.until = Some(until);
+            (until, delay)
+        };
+
+        println!("🧊 YouTube is throttling requests, cooling down for {}s", delay.as_secs());
+        self.emit_status().await;
+
+        // Announce the end of the cooldown; a new one can only start after this one ends
+        let Some(handle) = self.app_handle.lock().await.clone() else {
+            return;
+        };
+        tokio::spawn(async move {
+            tokio::time::sleep_until(until.into()).await;
+            let _ = handle.emit(
+                "youtube-cooldown-changed",
+                CooldownStatus {
+                    cooling_down: false,
+                    until: None,
+                },
+            );
+        });
+    }
+
+    pub async fn status(&self) -> CooldownStatus {
+        let remaining = self.cooldown_remaining().await;
+        CooldownStatus {
+            cooling_down: remaining.is_some(),
+            until: remaining.map(|remaining| chrono::Utc::now().timestamp() + remaining.as_secs() as i64),
+        }
+    }
+
+    async fn emit_status(&self) {
+        let status = self.status().await;
+        if let Some(handle) = self.app_handle.lock().await.as_ref() {
+            let _ = handle.emit("youtube-cooldown-changed", status);
+        }
+    }
+}
+
+/// Whether yt-dlp's error output says YouTube refused the request for sending too many. A 403
+/// usually means an expired URL or a broken extractor, which waiting won't fix
+fn is_throttled(error_output: &str) -> bool {
+    let message = error_output.to_lowercase();
+    ["http error 429", "too many requests"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
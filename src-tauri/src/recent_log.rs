@@ -0,0 +1,47 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+// Enough to cover the last few searches, downloads and streams before a bug report
+const LINE_LIMIT: usize = 500;
+
+static LINES: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+
+/// A line of error output from yt-dlp or the stream decoder, kept for the diagnostics bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    pub source: &'static str,
+    pub line: String,
+    pub logged_at: i64,
+}
+
+/// Keep a tool's error output for the next diagnostics bundle, dropping the oldest lines
+/// once the limit is reached
+pub fn record(source: &'static str, output: &str) {
+    let logged_at = chrono::Utc::now().timestamp();
+    let Ok(mut lines) = LINES.lock() else {
+        return;
+    };
+    for line in output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        if lines.len() == LINE_LIMIT {
+            lines.pop_front();
+        }
+        lines.push_back(LogLine {
+            source,
+            line: line.to_string(),
+            logged_at,
+        });
+    }
+}
+
+/// Every kept line, oldest first
+pub fn recent() -> Vec<LogLine> {
+    LINES
+        .lock()
+        .map(|lines| lines.iter().cloned().collect())
+        .unwrap_or_default()
+}
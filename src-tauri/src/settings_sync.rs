@@ -0,0 +1,97 @@
+use crate::models::SettingsChanged;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+// Every key `settings-changed` can name, announced together when all of them may have changed.
+// Volume and gain aren't among them, they follow `playback-state-changed` instead
+const SETTING_KEYS: &[&str] = &[
+    "announce_tracks",
+    "audio_profiles",
+    "audio_quality",
+    "auto_check_updates",
+    "blocked_keywords",
+    "buffer_target",
+    "cookie_source",
+    "data_saver_mode",
+    "download_notifications",
+    "downloads_directory",
+    "downloads_watcher",
+    "end_of_queue_action",
+    "fingerprinting",
+    "guest_queue",
+    "jingle_settings",
+    "keep_streamed_audio",
+    "library_sync",
+    "limiter",
+    "listening_log",
+    "max_volume",
+    "minimize_to_background",
+    "notification_policy",
+    "now_playing_output",
+    "output_device",
+    "party_mode",
+    "pinned_playlists",
+    "prefer_builtin_decoder",
+    "quiet_hours",
+    "speed_settings",
+    "telemetry_enabled",
+    "telemetry_endpoint",
+    "volume_keys",
+    "ytdlp_options",
+    "ytdlp_release",
+];
+
+/// Remembers a hash of each setting's last saved value and emits `settings-changed` with the keys
+/// that really changed, so the mini-player, the popup and backend listeners stay in sync without
+/// polling. Saving a value that's already set stays quiet; a key not seen yet counts as changed.
+pub struct SettingsSync {
+    hashes: Mutex<HashMap<String, u64>>,
+    app_handle: Mutex<Option<AppHandle>>,
+}
+
+impl SettingsSync {
+    pub fn new() -> Self {
+        Self {
+            hashes: Mutex::new(HashMap::new()),
+            app_handle: Mutex::new(None),
+        }
+    }
+
+    pub async fn set_app_handle(&self, handle: AppHandle) {
+        *self.app_handle.lock().await = Some(handle);
+    }
+
+    /// Note the value a setting was just saved with
+    pub async fn record<T: Serialize>(&self, key: &str, value: &T) {
+        let hash = hash_value(value);
+        let changed = self.hashes.lock().await.insert(key.to_string(), hash) != Some(hash);
+        if changed {
+            self.emit(vec![key.to_string()]).await;
+        }
+    }
+
+    /// Every setting was replaced at once, e.g. by a database reset, including ones last saved in
+    /// an earlier session
+    pub async fn reset(&self) {
+        self.hashes.lock().await.clear();
+        self.emit(SETTING_KEYS.iter().map(|key| key.to_string()).collect()).await;
+    }
+
+    async fn emit(&self, keys: Vec<String>) {
+        println!("⚙️ Settings changed: {}", keys.join(", "));
+        if let Some(handle) = self.app_handle.lock().await.as_ref() {
+            let _ = handle.emit("settings-changed", SettingsChanged { keys });
+        }
+    }
+}
+
+// Hashed as JSON, the form settings are saved in, so any serializable value can be compared
+fn hash_value<T: Serialize>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(value).unwrap_or_default().hash(&mut hasher);
+    hasher.finish()
+}
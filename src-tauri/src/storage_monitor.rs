@@ -0,0 +1,96 @@
+use crate::models::{StorageProblem, StorageProblemKind};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Large enough that a nearly full disk fails the probe, small enough to write every check
+const PROBE_SIZE: usize = 256 * 1024;
+const PROBE_FILE: &str = ".ytaudiobar-write-probe";
+
+/// Tracks whether the downloads folder and database can be written so downloads pause
+/// while the disk is full or read-only
+pub struct StorageMonitor {
+    problem: Mutex<Option<StorageProblem>>,
+}
+
+impl StorageMonitor {
+    pub fn new() -> Self {
+        Self {
+            problem: Mutex::new(None),
+        }
+    }
+
+    pub fn problem(&self) -> Option<StorageProblem> {
+        self.problem.lock().unwrap().clone()
+    }
+
+    /// Record a failed write to `dir`, returning the problem when it's a new storage problem
+    pub fn report(&self, dir: &Path, error: &str) -> Option<StorageProblem> {
+        let problem = StorageProblem::from_error(dir, error)?;
+        let mut current = self.problem.lock().unwrap();
+        if current.is_some() {
+            return None;
+        }
+        *current = Some(problem.clone());
+        Some(problem)
+    }
+
+    /// Probe each directory with a test write, returning whether storage is writable when
+    /// that changed since the last check
+    pub async fn check(&self, dirs: Vec<PathBuf>) -> Option<bool> {
+        let found = tokio::task::spawn_blocking(move || {
+            dirs.iter()
+                .find_map(|dir| probe(dir).err().and_then(|e| StorageProblem::from_error(dir, &e.to_string())))
+        })
+        .await
+        .ok()
+        .flatten();
+
+        let mut current = self.problem.lock().unwrap();
+        let was_writable = current.is_none();
+        let writable = found.is_none();
+        // Keep the first problem's cause while it persists
+        if writable || was_writable {
+            *current = found;
+        }
+        (writable != was_writable).then_some(writable)
+    }
+}
+
+/// Write and remove a test file in `dir`, failing when the directory can't be written
+pub fn probe(dir: &Path) -> std::io::Result<()> {
+    let path = dir.join(PROBE_FILE);
+    let result = std::fs::File::create(&path).and_then(|mut file| {
+        file.write_all(&vec![0u8; PROBE_SIZE])?;
+        file.sync_all()
+    });
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+impl StorageProblem {
+    /// Classify an io, sqlite or yt-dlp error message, None when it isn't a storage problem
+    pub fn from_error(dir: &Path, error: &str) -> Option<Self> {
+        let message = error.to_lowercase();
+        let matches = |patterns: &[&str]| patterns.iter().any(|p| message.contains(p));
+        let path = dir.to_string_lossy().to_string();
+
+        if matches(&["no space left", "disk full", "disk is full", "not enough space", "os error 28", "os error 112"]) {
+            Some(Self {
+                kind: StorageProblemKind::DiskFull,
+                cause: format!("The disk holding {} is full", path),
+                hint: "Free up some space or choose a downloads folder on another drive. Downloads resume on their own once there's room.".to_string(),
+                path,
+            })
+        } else if matches(&["read-only file system", "readonly database", "permission denied", "access is denied", "os error 30"]) {
+            Some(Self {
+                kind: StorageProblemKind::ReadOnly,
+                cause: format!("{} can't be written to", path),
+                hint: "Check that the drive is connected and not mounted read-only, and that you have write permission. Downloads resume on their own once it's writable.".to_string(),
+                path,
+            })
+        } else {
+            None
+        }
+    }
+}
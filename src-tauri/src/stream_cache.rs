@@ -0,0 +1,108 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Bytes read to tell the container apart
+const HEADER_BYTES: usize = 12;
+// Matroska/WebM files start with the EBML magic number
+const EBML_MAGIC: [u8; 4] = [0x1A, 0x45, 0xDF, 0xA3];
+
+struct CacheFile {
+    part_path: PathBuf,
+    file: Option<File>,
+    failed: bool, // a write failed, so the copy has a hole and can't be kept
+}
+
+/// A copy of the compressed audio a stream downloads, written to disk as it's read
+#[derive(Clone)]
+pub struct StreamCache {
+    inner: Arc<Mutex<CacheFile>>,
+}
+
+impl StreamCache {
+    pub fn create(dir: &Path, video_id: &str) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let part_path = dir.join(format!("{}.part", video_id));
+        let file = File::create(&part_path)?;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(CacheFile {
+                part_path,
+                file: Some(file),
+                failed: false,
+            })),
+        })
+    }
+
+    /// Wraps the stream's source so everything read from it is also written to the cache
+    pub fn tee<R: Read>(&self, source: R) -> TeeReader<R> {
+        TeeReader {
+            source,
+            cache: self.clone(),
+        }
+    }
+
+    /// Closes the copy once the stream has ended. A complete copy is renamed after its
+    /// container and returned; anything else is deleted.
+    pub fn finish(&self, complete: bool) -> Option<PathBuf> {
+        let mut cache = self.inner.lock().unwrap();
+        let file = cache.file.take()?;
+        drop(file);
+
+        let extension = (complete && !cache.failed).then(|| container_extension(&cache.part_path)).flatten();
+        let Some(extension) = extension else {
+            let _ = std::fs::remove_file(&cache.part_path);
+            return None;
+        };
+
+        let path = cache.part_path.with_extension(extension);
+        match std::fs::rename(&cache.part_path, &path) {
+            Ok(()) => Some(path),
+            Err(e) => {
+                eprintln!("⚠️ Failed to keep streamed audio: {}", e);
+                let _ = std::fs::remove_file(&cache.part_path);
+                None
+            }
+        }
+    }
+
+    fn write(&self, bytes: &[u8]) {
+        let mut cache = self.inner.lock().unwrap();
+        if cache.failed {
+            return;
+        }
+        let written = cache.file.as_mut().map(|file| file.write_all(bytes));
+        if let Some(Err(e)) = written {
+            eprintln!("⚠️ Stopped copying streamed audio to disk: {}", e);
+            cache.failed = true;
+        }
+    }
+}
+
+/// Reads from a stream's source while copying the bytes into a StreamCache
+pub struct TeeReader<R> {
+    source: R,
+    cache: StreamCache,
+}
+
+impl<R: Read> Read for TeeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.source.read(buf)?;
+        self.cache.write(&buf[..read]);
+        Ok(read)
+    }
+}
+
+// Extension matching the file's container, judged by its first bytes; None for anything the
+// downloads folder wouldn't recognise
+fn container_extension(path: &Path) -> Option<&'static str> {
+    let mut header = [0u8; HEADER_BYTES];
+    File::open(path).ok()?.read_exact(&mut header).ok()?;
+    if header[..4] == EBML_MAGIC {
+        Some("webm")
+    } else if &header[4..8] == b"ftyp" {
+        Some("m4a")
+    } else {
+        None
+    }
+}
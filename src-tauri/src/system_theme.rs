@@ -0,0 +1,123 @@
+use crate::models::SystemTheme;
+
+/// Read the OS dark mode and high contrast settings; anything that can't be read
+/// counts as dark without high contrast, matching the popup's default look
+pub fn detect() -> SystemTheme {
+    SystemTheme {
+        dark: is_dark().unwrap_or(true),
+        high_contrast: is_high_contrast().unwrap_or(false),
+    }
+}
+
+/// Read only the high contrast setting. Dark mode changes arrive as window theme events, but high
+/// contrast has no event to follow.
+pub fn detect_high_contrast() -> bool {
+    is_high_contrast().unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+fn is_dark() -> Option<bool> {
+    use std::ffi::c_void;
+    use windows::core::w;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let mut value = 0u32;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize"),
+            w!("AppsUseLightTheme"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut value as *mut u32 as *mut c_void),
+            Some(&mut size),
+        )
+    };
+    result.is_ok().then_some(value == 0)
+}
+
+#[cfg(target_os = "windows")]
+fn is_high_contrast() -> Option<bool> {
+    use std::ffi::c_void;
+    use windows::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPI_GETHIGHCONTRAST, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+    };
+
+    let mut contrast = HIGHCONTRASTW {
+        cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+        ..Default::default()
+    };
+    unsafe {
+        SystemParametersInfoW(
+            SPI_GETHIGHCONTRAST,
+            contrast.cbSize,
+            Some(&mut contrast as *mut HIGHCONTRASTW as *mut c_void),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    }
+    .ok()?;
+    Some(contrast.dwFlags.contains(HCF_HIGHCONTRASTON))
+}
+
+#[cfg(target_os = "macos")]
+fn is_dark() -> Option<bool> {
+    // The key only exists while dark mode is on
+    Some(defaults(&["read", "-g", "AppleInterfaceStyle"]).is_some_and(|style| style.eq_ignore_ascii_case("dark")))
+}
+
+#[cfg(target_os = "macos")]
+fn is_high_contrast() -> Option<bool> {
+    defaults(&["read", "com.apple.universalaccess", "increaseContrast"]).map(|value| value == "1")
+}
+
+#[cfg(target_os = "macos")]
+fn defaults(args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new("defaults").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// The XDG settings portal covers GNOME, KDE and most other desktops
+#[cfg(target_os = "linux")]
+fn is_dark() -> Option<bool> {
+    // 0 is no preference, 1 prefer dark, 2 prefer light
+    portal_setting("color-scheme").map(|scheme| scheme != 2)
+}
+
+#[cfg(target_os = "linux")]
+fn is_high_contrast() -> Option<bool> {
+    portal_setting("contrast").map(|contrast| contrast == 1)
+}
+
+#[cfg(target_os = "linux")]
+fn portal_setting(key: &str) -> Option<u64> {
+    use dbus::arg::{RefArg, Variant};
+    use dbus::blocking::Connection;
+    use std::time::Duration;
+
+    let connection = Connection::new_session().ok()?;
+    let proxy = connection.with_proxy(
+        "org.freedesktop.portal.Desktop",
+        "/org/freedesktop/portal/desktop",
+        Duration::from_millis(500),
+    );
+    // Read wraps the value in a second variant, which as_u64 looks through
+    let (value,): (Variant<Box<dyn RefArg>>,) = proxy
+        .method_call("org.freedesktop.portal.Settings", "Read", ("org.freedesktop.appearance", key))
+        .ok()?;
+    value.as_u64()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn is_dark() -> Option<bool> {
+    None
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn is_high_contrast() -> Option<bool> {
+    None
+}
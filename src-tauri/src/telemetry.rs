@@ -0,0 +1,153 @@
+use crate::app_data::AppData;
+use crate::models::{TelemetryBatch, TelemetryEvent};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Strictly opt-in usage counts. Nothing is recorded while disabled, and a batch only holds
+/// how often each feature was used, never what was searched, played or downloaded.
+pub struct TelemetryManager {
+    enabled: AtomicBool,
+    endpoint: Mutex<Option<String>>,
+    pending: Mutex<TelemetryBatch>,
+    unsaved: AtomicBool, // counted since the last `flush`
+}
+
+impl TelemetryManager {
+    pub fn new() -> Self {
+        // Counts survive restarts, and crashes, until they are sent
+        let pending = std::fs::read_to_string(pending_path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_else(new_batch);
+
+        Self {
+            enabled: AtomicBool::new(false),
+            endpoint: Mutex::new(None),
+            pending: Mutex::new(pending),
+            unsaved: AtomicBool::new(false),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Turning telemetry off also drops whatever was counted but not sent yet
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+        let mut pending = self.pending.lock().unwrap();
+        if !enabled && !pending.counts.is_empty() {
+            *pending = new_batch();
+            save_pending(&pending);
+        }
+    }
+
+    pub fn get_endpoint(&self) -> Option<String> {
+        self.endpoint.lock().unwrap().clone()
+    }
+
+    pub fn set_endpoint(&self, endpoint: Option<String>) {
+        *self.endpoint.lock().unwrap() = endpoint;
+    }
+
+    /// Count one use of a feature; saved to disk with the next `flush`
+    pub fn record(&self, event: TelemetryEvent, count: u64) {
+        if !self.is_enabled() || count == 0 {
+            return;
+        }
+        *self.pending.lock().unwrap().counts.entry(event).or_insert(0) += count;
+        self.unsaved.store(true, Ordering::SeqCst);
+    }
+
+    /// Count a crash from the panic hook and save it at once, the app is about to abort
+    pub fn record_crash(&self) {
+        if !self.is_enabled() {
+            return;
+        }
+        // The hook runs before unwinding, so a lock held by the panicking thread would deadlock
+        let Ok(mut pending) = self.pending.try_lock() else {
+            return;
+        };
+        *pending.counts.entry(TelemetryEvent::Crash).or_insert(0) += 1;
+        save_pending(&pending);
+    }
+
+    /// Save what was counted since the last flush; blocking, it writes the file
+    pub fn flush(&self) {
+        if self.unsaved.swap(false, Ordering::SeqCst) {
+            save_pending(&self.pending.lock().unwrap());
+        }
+    }
+
+    /// Exactly what the next upload would send
+    pub fn preview(&self) -> TelemetryBatch {
+        self.pending.lock().unwrap().clone()
+    }
+
+    /// Send the pending batch to the configured endpoint, returning whether anything was sent
+    pub async fn upload(&self) -> Result<bool, String> {
+        let Some(endpoint) = self.get_endpoint().filter(|_| self.is_enabled()) else {
+            return Ok(false);
+        };
+        let mut batch = self.preview();
+        if batch.counts.is_empty() {
+            return Ok(false);
+        }
+        batch.until = chrono::Utc::now().timestamp();
+
+        let client = reqwest::Client::builder()
+            .timeout(UPLOAD_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        client
+            .post(&endpoint)
+            .json(&batch)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Failed to send usage metrics: {}", e))?;
+
+        // Keep anything counted while the upload was in flight
+        let mut pending = self.pending.lock().unwrap();
+        for (event, count) in &batch.counts {
+            if let Some(current) = pending.counts.get_mut(event) {
+                *current = current.saturating_sub(*count);
+            }
+        }
+        pending.counts.retain(|_, count| *count > 0);
+        pending.since = batch.until;
+        save_pending(&pending);
+        Ok(true)
+    }
+}
+
+fn new_batch() -> TelemetryBatch {
+    let now = chrono::Utc::now().timestamp();
+    TelemetryBatch {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        since: now,
+        until: now,
+        counts: Default::default(),
+    }
+}
+
+fn pending_path() -> PathBuf {
+    AppData::get_state_dir().join("telemetry.json")
+}
+
+fn save_pending(batch: &TelemetryBatch) {
+    let path = pending_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    if let Ok(json) = serde_json::to_string_pretty(batch) {
+        if let Err(e) = std::fs::write(&path, json) {
+            eprintln!("⚠️ Failed to save usage metrics: {}", e);
+        }
+    }
+}
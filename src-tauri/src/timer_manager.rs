@@ -0,0 +1,53 @@
+use crate::models::{ScheduledTimer, TimerKind};
+use tokio::sync::Mutex;
+
+/// Sleep timers and "stop at" times, both ending playback with a fade
+pub struct TimerManager {
+    timers: Mutex<Vec<ScheduledTimer>>,
+}
+
+impl TimerManager {
+    pub fn new() -> Self {
+        Self {
+            timers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Schedule a timer firing at `fires_at` (unix seconds); only one of each kind is kept
+    pub async fn set(&self, kind: TimerKind, fires_at: i64, fade_seconds: f64) -> ScheduledTimer {
+        let timer = ScheduledTimer {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            fires_at,
+            fade_seconds,
+        };
+
+        let mut timers = self.timers.lock().await;
+        timers.retain(|t| t.kind != kind);
+        timers.push(timer.clone());
+        timers.sort_by_key(|t| t.fires_at);
+        timer
+    }
+
+    pub async fn cancel(&self, id: &str) -> bool {
+        let mut timers = self.timers.lock().await;
+        let before = timers.len();
+        timers.retain(|t| t.id != id);
+        timers.len() != before
+    }
+
+    /// All pending timers, soonest first
+    pub async fn get_all(&self) -> Vec<ScheduledTimer> {
+        self.timers.lock().await.clone()
+    }
+
+    /// Removes and returns timers whose fade should start by `now`
+    pub async fn take_due(&self, now: i64) -> Vec<ScheduledTimer> {
+        let mut timers = self.timers.lock().await;
+        let (due, pending) = timers
+            .drain(..)
+            .partition(|t| now as f64 >= t.fires_at as f64 - t.fade_seconds);
+        *timers = pending;
+        due
+    }
+}
@@ -0,0 +1,225 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::image::Image;
+use tauri::tray::TrayIcon;
+use tokio::sync::Mutex;
+
+// Overlay colors (RGBA)
+const PROGRESS_TRACK: [u8; 4] = [40, 40, 40, 230];
+const PROGRESS_FILL: [u8; 4] = [255, 59, 48, 255];
+const BUFFERING_BADGE: [u8; 4] = [255, 204, 0, 255];
+const GLYPH_BACKGROUND: [u8; 4] = [30, 30, 30, 235];
+const GLYPH_FOREGROUND: [u8; 4] = [255, 255, 255, 255];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PlaybackIndicator {
+    #[default]
+    Stopped,
+    Playing,
+    Paused,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TrayStatus {
+    download_progress: Option<u8>, // whole percent, None when no downloads are active
+    buffering: bool,
+    playback: PlaybackIndicator,
+}
+
+struct BaseIcon {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+/// Owns the tray icon and redraws it with overlays reflecting playback, downloads and buffering
+pub struct TrayManager {
+    tray: Mutex<Option<TrayIcon>>,
+    base_icon: Mutex<Option<BaseIcon>>,
+    status: Mutex<TrayStatus>,
+    window_mode: AtomicBool,            // no usable tray, so the window behaves like a normal app window
+    minimize_to_background: AtomicBool, // in window mode, closing hides the window instead of quitting
+}
+
+impl TrayManager {
+    pub fn new() -> Self {
+        Self {
+            tray: Mutex::new(None),
+            base_icon: Mutex::new(None),
+            status: Mutex::new(TrayStatus::default()),
+            window_mode: AtomicBool::new(false),
+            minimize_to_background: AtomicBool::new(false),
+        }
+    }
+
+    pub fn is_window_mode(&self) -> bool {
+        self.window_mode.load(Ordering::SeqCst)
+    }
+
+    pub fn set_window_mode(&self, enabled: bool) {
+        self.window_mode.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn minimizes_to_background(&self) -> bool {
+        self.minimize_to_background.load(Ordering::SeqCst)
+    }
+
+    pub fn set_minimize_to_background(&self, enabled: bool) {
+        self.minimize_to_background.store(enabled, Ordering::SeqCst);
+    }
+
+    pub async fn set_tray(&self, tray: TrayIcon, icon: &Image<'_>) {
+        *self.base_icon.lock().await = Some(BaseIcon {
+            rgba: icon.rgba().to_vec(),
+            width: icon.width(),
+            height: icon.height(),
+        });
+        *self.tray.lock().await = Some(tray);
+    }
+
+    /// Average progress (0.0 to 1.0) of active downloads, or None when idle
+    pub async fn set_download_progress(&self, progress: Option<f64>) {
+        let progress = progress.map(|p| (p.clamp(0.0, 1.0) * 100.0) as u8);
+        self.update(|status| status.download_progress = progress).await;
+    }
+
+    pub async fn set_buffering(&self, buffering: bool) {
+        self.update(|status| status.buffering = buffering).await;
+    }
+
+    pub async fn set_playback(&self, playback: PlaybackIndicator) {
+        self.update(|status| status.playback = playback).await;
+    }
+
+    async fn update(&self, apply: impl FnOnce(&mut TrayStatus)) {
+        let status = {
+            let mut status = self.status.lock().await;
+            let previous = status.clone();
+            apply(&mut status);
+            // Skip redraws when nothing visible changed
+            if *status == previous {
+                return;
+            }
+            status.clone()
+        };
+
+        self.render(&status).await;
+    }
+
+    async fn render(&self, status: &TrayStatus) {
+        let base_icon = self.base_icon.lock().await;
+        let Some(base) = base_icon.as_ref() else {
+            return;
+        };
+
+        let mut rgba = base.rgba.clone();
+        let (width, height) = (base.width, base.height);
+
+        if let Some(percent) = status.download_progress {
+            // Progress bar along the bottom edge
+            let bar_top = height - (height / 6).max(2);
+            fill_rect(&mut rgba, width, 0, bar_top, width, height, PROGRESS_TRACK);
+            let filled = width * percent as u32 / 100;
+            fill_rect(&mut rgba, width, 0, bar_top, filled, height, PROGRESS_FILL);
+        }
+
+        if status.playback != PlaybackIndicator::Stopped {
+            // Play/pause glyph on a dark disc in the bottom-left corner
+            let radius = (width / 4).max(3);
+            let (cx, cy) = (radius, height - radius);
+            fill_circle(&mut rgba, width, cx, cy, radius, GLYPH_BACKGROUND);
+
+            let glyph = radius / 2;
+            match status.playback {
+                PlaybackIndicator::Playing => {
+                    fill_triangle(&mut rgba, width, cx - glyph / 2, cy - glyph, cx + glyph, cy, GLYPH_FOREGROUND);
+                }
+                PlaybackIndicator::Paused => {
+                    let bar = (glyph / 2).max(1);
+                    fill_rect(&mut rgba, width, cx - glyph, cy - glyph, cx - glyph + bar, cy + glyph, GLYPH_FOREGROUND);
+                    fill_rect(&mut rgba, width, cx + glyph - bar, cy - glyph, cx + glyph, cy + glyph, GLYPH_FOREGROUND);
+                }
+                PlaybackIndicator::Stopped => {}
+            }
+        }
+
+        if status.buffering {
+            // Dot badge in the top-right corner
+            let radius = (width / 5).max(2);
+            fill_circle(&mut rgba, width, width - radius, radius, radius, BUFFERING_BADGE);
+        }
+
+        if let Some(tray) = self.tray.lock().await.as_ref() {
+            if let Err(e) = tray.set_icon(Some(Image::new_owned(rgba, width, height))) {
+                eprintln!("⚠️ Failed to update tray icon: {}", e);
+            }
+        }
+    }
+}
+
+/// Whether the desktop can show the tray icon. On Linux the icon is a StatusNotifierItem,
+/// which never appears unless something owns the StatusNotifierWatcher name.
+pub fn is_tray_available() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        let output = std::process::Command::new("dbus-send")
+            .args([
+                "--session",
+                "--print-reply",
+                "--dest=org.freedesktop.DBus",
+                "/org/freedesktop/DBus",
+                "org.freedesktop.DBus.NameHasOwner",
+                "string:org.kde.StatusNotifierWatcher",
+            ])
+            .output();
+        match output {
+            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).contains("boolean true"),
+            // Without dbus-send there's no telling, so assume the tray works as before
+            _ => true,
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        true
+    }
+}
+
+fn fill_rect(rgba: &mut [u8], width: u32, x0: u32, y0: u32, x1: u32, y1: u32, color: [u8; 4]) {
+    for y in y0..y1 {
+        for x in x0..x1.min(width) {
+            let index = ((y * width + x) * 4) as usize;
+            if let Some(pixel) = rgba.get_mut(index..index + 4) {
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+fn fill_circle(rgba: &mut [u8], width: u32, cx: u32, cy: u32, radius: u32, color: [u8; 4]) {
+    let r2 = (radius * radius) as i64;
+    for y in cy.saturating_sub(radius)..cy + radius {
+        for x in cx.saturating_sub(radius)..(cx + radius).min(width) {
+            let dx = x as i64 - cx as i64;
+            let dy = y as i64 - cy as i64;
+            if dx * dx + dy * dy <= r2 {
+                let index = ((y * width + x) * 4) as usize;
+                if let Some(pixel) = rgba.get_mut(index..index + 4) {
+                    pixel.copy_from_slice(&color);
+                }
+            }
+        }
+    }
+}
+
+// Right-pointing triangle with its left edge at `left` spanning `top` to the mirrored bottom,
+// and its tip at (`tip_x`, `mid_y`)
+fn fill_triangle(rgba: &mut [u8], width: u32, left: u32, top: u32, tip_x: u32, mid_y: u32, color: [u8; 4]) {
+    let half_height = mid_y.saturating_sub(top).max(1) as f64;
+    let length = tip_x.saturating_sub(left) as f64;
+    for y in top..=mid_y + (mid_y - top) {
+        // Width of the triangle shrinks linearly towards the tip
+        let distance = (y as f64 - mid_y as f64).abs() / half_height;
+        let row_end = left + (length * (1.0 - distance)) as u32;
+        fill_rect(rgba, width, left, y, row_end, y + 1, color);
+    }
+}
@@ -0,0 +1,53 @@
+use tokio::process::Command;
+
+// Environment variable the Windows engine reads the text from
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const TEXT_VAR: &str = "YTAB_TTS";
+
+/// Speak text with the OS text-to-speech engine, returning once it has finished
+pub async fn speak(text: &str) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let candidates: Vec<(&str, Vec<String>)> = {
+        // The text is read from TEXT_VAR rather than spliced into the script, so no quoting in a
+        // title can break out of the string
+        let script = format!(
+            "Add-Type -AssemblyName System.Speech; (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak($env:{})",
+            TEXT_VAR
+        );
+        vec![(
+            "powershell",
+            vec!["-NoProfile".to_string(), "-Command".to_string(), script],
+        )]
+    };
+
+    #[cfg(target_os = "macos")]
+    let candidates: Vec<(&str, Vec<String>)> =
+        vec![("say", vec!["--".to_string(), text.to_string()])];
+
+    // speech-dispatcher first, then espeak. `--` keeps a title starting with '-' from being read
+    // as an option
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let candidates: Vec<(&str, Vec<String>)> = vec![
+        (
+            "spd-say",
+            vec!["--wait".to_string(), "--".to_string(), text.to_string()],
+        ),
+        ("espeak-ng", vec!["--".to_string(), text.to_string()]),
+        ("espeak", vec!["--".to_string(), text.to_string()]),
+    ];
+
+    for (program, args) in candidates {
+        if let Ok(status) = Command::new(program)
+            .args(&args)
+            .env(TEXT_VAR, text)
+            .status()
+            .await
+        {
+            if status.success() {
+                return Ok(());
+            }
+        }
+    }
+
+    Err("No text-to-speech engine available".to_string())
+}
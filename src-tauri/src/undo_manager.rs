@@ -0,0 +1,117 @@
+use crate::models::{Playlist, QueueState};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+// How long a destructive action can be undone
+const UNDO_WINDOW: Duration = Duration::from_secs(30);
+
+// (track_id, added_date) pairs captured before memberships are removed
+pub type MembershipSnapshot = Vec<(String, i64)>;
+
+// A download's files moved aside: (video_id, [(staged path, original path)])
+pub type StagedDownload = (String, Vec<(PathBuf, PathBuf)>);
+
+pub enum UndoAction {
+    DeletePlaylist {
+        playlist: Playlist,
+        memberships: MembershipSnapshot,
+    },
+    RemoveTracks {
+        playlist_id: String,
+        memberships: MembershipSnapshot,
+    },
+    ClearQueue {
+        snapshot: QueueState,
+    },
+    DeleteDownloads {
+        downloads: Vec<StagedDownload>,
+    },
+}
+
+impl UndoAction {
+    pub fn describe(&self) -> String {
+        match self {
+            UndoAction::DeletePlaylist { playlist, .. } => format!("Restored playlist \"{}\"", playlist.name),
+            UndoAction::RemoveTracks { memberships, .. } => format!("Restored {} track(s)", memberships.len()),
+            UndoAction::ClearQueue { snapshot } => format!("Restored queue of {} track(s)", snapshot.queue.len()),
+            UndoAction::DeleteDownloads { downloads } if downloads.len() == 1 => "Restored download".to_string(),
+            UndoAction::DeleteDownloads { downloads } => format!("Restored {} downloads", downloads.len()),
+        }
+    }
+
+    // Permanently apply the action once it can no longer be undone
+    fn finalize(self) {
+        if let UndoAction::DeleteDownloads { downloads } = self {
+            for (video_id, staged_files) in downloads {
+                for (staged, _) in staged_files {
+                    if let Err(e) = std::fs::remove_file(&staged) {
+                        eprintln!("⚠️ Failed to purge staged file {}: {}", staged.display(), e);
+                    }
+                }
+                println!("🗑️ Download {} permanently deleted", video_id);
+            }
+        }
+    }
+}
+
+struct PendingUndo {
+    id: u64,
+    action: UndoAction,
+    created: Instant,
+}
+
+pub struct UndoManager {
+    pending: Arc<Mutex<Option<PendingUndo>>>,
+    next_id: Arc<Mutex<u64>>,
+}
+
+impl UndoManager {
+    pub fn new() -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(None)),
+            next_id: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Record a destructive action, finalizing whatever was pending before it
+    pub async fn push(&self, action: UndoAction) {
+        let id = {
+            let mut next_id = self.next_id.lock().await;
+            *next_id += 1;
+            *next_id
+        };
+
+        let previous = self.pending.lock().await.replace(PendingUndo {
+            id,
+            action,
+            created: Instant::now(),
+        });
+        if let Some(previous) = previous {
+            previous.action.finalize();
+        }
+
+        // Expire the entry once the undo window has passed
+        let pending = Arc::clone(&self.pending);
+        tokio::spawn(async move {
+            tokio::time::sleep(UNDO_WINDOW).await;
+            let mut pending = pending.lock().await;
+            if pending.as_ref().map(|p| p.id) == Some(id) {
+                if let Some(expired) = pending.take() {
+                    expired.action.finalize();
+                }
+            }
+        });
+    }
+
+    /// Take the pending action if it is still within the undo window
+    pub async fn take(&self) -> Option<UndoAction> {
+        let pending = self.pending.lock().await.take()?;
+        if pending.created.elapsed() > UNDO_WINDOW {
+            pending.action.finalize();
+            return None;
+        }
+        Some(pending.action)
+    }
+}
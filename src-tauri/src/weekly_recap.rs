@@ -0,0 +1,120 @@
+use crate::database::DatabaseManager;
+use crate::models::{PlayRecord, RecapTrack, WeeklyRecap};
+use chrono::{Datelike, Local, NaiveDate, NaiveTime, TimeZone};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+const TOP_TRACKS: usize = 5;
+const DISCOVERIES_SHOWN: usize = 5;
+// How far back plays are read when a streak runs into the week
+const STREAK_LOOKBACK_DAYS: i64 = 365;
+
+/// Monday of the most recent week that has fully ended
+pub fn last_full_week() -> NaiveDate {
+    let today = Local::now().date_naive();
+    today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64 + 7)
+}
+
+/// Summarise the week starting on `week_start` (a Monday) from the play history
+pub async fn generate_weekly_recap(db: &DatabaseManager, week_start: NaiveDate) -> Result<WeeklyRecap, String> {
+    let week_end = week_start + chrono::Duration::days(7);
+    let since = local_timestamp(week_start);
+    let until = local_timestamp(week_end);
+
+    let plays = db.get_plays_between(since, until).await.map_err(|e| e.to_string())?;
+    let first_played = db.get_first_played_recording_ids(since, until).await.map_err(|e| e.to_string())?;
+    let play_times = db
+        .get_play_times(local_timestamp(week_end - chrono::Duration::days(STREAK_LOOKBACK_DAYS)), until)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let total_seconds: i64 = plays.iter().filter(|p| !p.skipped).map(|p| p.duration.max(0)).sum();
+    let ranked = rank_tracks(&plays);
+    let new_discoveries = ranked
+        .iter()
+        .filter(|t| first_played.contains(&t.id))
+        .take(DISCOVERIES_SHOWN)
+        .cloned()
+        .collect();
+
+    let days: BTreeSet<NaiveDate> = play_times.iter().filter_map(|t| local_date(*t)).collect();
+    let week_days: Vec<NaiveDate> = days.range(week_start..week_end).copied().collect();
+
+    Ok(WeeklyRecap {
+        week_start: week_start.format("%Y-%m-%d").to_string(),
+        total_plays: plays.len() as i64,
+        total_hours: total_seconds as f64 / 3600.0,
+        top_tracks: ranked.into_iter().take(TOP_TRACKS).collect(),
+        new_discoveries,
+        days_listened: week_days.len() as u32,
+        longest_streak: longest_run(&week_days),
+        streak: streak_ending(&days, week_end - chrono::Duration::days(1)),
+        generated_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+// Recordings played through at least once, most played first, every upload of one counted
+// together. Plays that can't be named are left out.
+fn rank_tracks(plays: &[PlayRecord]) -> Vec<RecapTrack> {
+    let mut counts: HashMap<&str, RecapTrack> = HashMap::new();
+    let mut last_played: HashMap<&str, i64> = HashMap::new();
+    let heard: HashSet<&str> = plays.iter().filter(|p| !p.skipped).map(|p| p.recording_id.as_str()).collect();
+
+    for play in plays.iter().filter(|p| heard.contains(p.recording_id.as_str())) {
+        let Some(title) = &play.title else {
+            continue;
+        };
+        let entry = counts.entry(&play.recording_id).or_insert_with(|| RecapTrack {
+            id: play.recording_id.clone(),
+            title: title.clone(),
+            author: play.author.clone(),
+            plays: 0,
+        });
+        entry.plays += 1;
+        last_played.insert(&play.recording_id, play.played_at);
+    }
+
+    let mut tracks: Vec<RecapTrack> = counts.into_values().collect();
+    tracks.sort_by_key(|t| (std::cmp::Reverse(t.plays), std::cmp::Reverse(last_played[t.id.as_str()])));
+    tracks
+}
+
+// Longest run of consecutive dates in a sorted list
+fn longest_run(days: &[NaiveDate]) -> u32 {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in days {
+        run = match previous {
+            Some(previous) if previous.succ_opt() == Some(*day) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*day);
+    }
+    longest
+}
+
+// Consecutive days with plays counting back from `last_day`, zero when nothing played that day
+fn streak_ending(days: &BTreeSet<NaiveDate>, last_day: NaiveDate) -> u32 {
+    let mut streak = 0;
+    let mut day = Some(last_day);
+    while let Some(current) = day.filter(|d| days.contains(d)) {
+        streak += 1;
+        day = current.pred_opt();
+    }
+    streak
+}
+
+// Local midnight at the start of `date`; on a DST change the earlier of two midnights
+fn local_timestamp(date: NaiveDate) -> i64 {
+    let midnight = date.and_time(NaiveTime::MIN);
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|d| d.timestamp())
+        .unwrap_or_else(|| midnight.and_utc().timestamp())
+}
+
+fn local_date(timestamp: i64) -> Option<NaiveDate> {
+    Local.timestamp_opt(timestamp, 0).single().map(|d| d.date_naive())
+}
@@ -0,0 +1,92 @@
+use tauri::{Monitor, PhysicalPosition, PhysicalSize, Rect, Runtime, WebviewWindow};
+
+// Gap between the popup and the tray icon or screen edge, in logical pixels
+const MARGIN: f64 = 8.0;
+
+/// Move the popup next to the tray icon, or into the tray corner of the screen when
+/// the platform doesn't report where the icon is (Linux never does)
+pub fn place_near_tray<R: Runtime>(window: &WebviewWindow<R>, tray_rect: Option<Rect>) -> tauri::Result<()> {
+    let window_scale = window.scale_factor()?;
+    let tray_rect = tray_rect.map(|rect| {
+        let position: PhysicalPosition<i32> = rect.position.to_physical(window_scale);
+        let size: PhysicalSize<u32> = rect.size.to_physical(window_scale);
+        (position, size)
+    });
+
+    let monitor = match tray_rect {
+        Some((position, size)) => window.monitor_from_point(
+            position.x as f64 + size.width as f64 / 2.0,
+            position.y as f64 + size.height as f64 / 2.0,
+        )?,
+        None => None,
+    };
+    let Some(monitor) = monitor.or(window.primary_monitor()?).or(window.current_monitor()?) else {
+        return Ok(());
+    };
+
+    // The outer size is in the window's current scale; rescale it for the monitor it lands on
+    let scale = monitor.scale_factor();
+    let outer_size = window.outer_size()?;
+    let width = (outer_size.width as f64 / window_scale * scale).round() as i32;
+    let height = (outer_size.height as f64 / window_scale * scale).round() as i32;
+    let margin = (MARGIN * scale).round() as i32;
+
+    let position = match tray_rect {
+        Some((tray_position, tray_size)) => anchored(&monitor, tray_position, tray_size, width, height, margin),
+        None => corner(&monitor, width, height, margin),
+    };
+    window.set_position(position)
+}
+
+// Centre the popup on the icon, opening away from whichever screen edge the tray sits on
+fn anchored(
+    monitor: &Monitor,
+    tray_position: PhysicalPosition<i32>,
+    tray_size: PhysicalSize<u32>,
+    width: i32,
+    height: i32,
+    margin: i32,
+) -> PhysicalPosition<i32> {
+    let work_area = monitor.work_area();
+    let monitor_middle = monitor.position().y + monitor.size().height as i32 / 2;
+    let tray_middle = tray_position.y + tray_size.height as i32 / 2;
+
+    let x = tray_position.x + tray_size.width as i32 / 2 - width / 2;
+    let y = if tray_middle > monitor_middle {
+        tray_position.y - height - margin
+    } else {
+        tray_position.y + tray_size.height as i32 + margin
+    };
+
+    clamp_to(work_area.position, work_area.size, x, y, width, height, margin)
+}
+
+// The work area already excludes the taskbar, panels and menu bar, so only the corner differs per OS
+fn corner(monitor: &Monitor, width: i32, height: i32, margin: i32) -> PhysicalPosition<i32> {
+    let work_area = monitor.work_area();
+    let x = work_area.position.x + work_area.size.width as i32 - width - margin;
+
+    #[cfg(target_os = "windows")]
+    let y = work_area.position.y + work_area.size.height as i32 - height - margin;
+    #[cfg(not(target_os = "windows"))]
+    let y = work_area.position.y + margin;
+
+    clamp_to(work_area.position, work_area.size, x, y, width, height, margin)
+}
+
+fn clamp_to(
+    origin: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    margin: i32,
+) -> PhysicalPosition<i32> {
+    let max_x = (origin.x + size.width as i32 - width - margin).max(origin.x);
+    let max_y = (origin.y + size.height as i32 - height - margin).max(origin.y);
+    PhysicalPosition::new(
+        x.clamp((origin.x + margin).min(max_x), max_x),
+        y.clamp((origin.y + margin).min(max_y), max_y),
+    )
+}
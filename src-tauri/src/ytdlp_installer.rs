@@ -1,35 +1,89 @@
-use std::path::PathBuf;
+use crate::app_data::AppData;
+use crate::models::YtdlpRelease;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::{Notify, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(target_os = "windows")]
+const ASSET_NAME: &str = "yt-dlp.exe";
+
+#[cfg(not(target_os = "windows"))]
+const ASSET_NAME: &str = "yt-dlp";
+
+// Shared by everything running yt-dlp and taken alone to swap the binary, so an update waits for
+// running lookups to finish and anything started meanwhile waits for the update. Downloads hold it
+// for a whole run, so they're stopped when a swap is requested and resume from their partial file
+// afterwards. Streams and their URL lookups don't take it, a track shouldn't wait on a download;
+// the swap never leaves the path empty for them.
+static BINARY_LOCK: RwLock<()> = RwLock::const_new(());
+
+// Set while a swap waits for the lock, wakes long-running holders so they let go
+static SWAP_PENDING: AtomicBool = AtomicBool::new(false);
+static SWAP_REQUESTED: Notify = Notify::const_new();
 
 pub struct YTDLPInstaller;
 
 impl YTDLPInstaller {
     pub fn get_ytdlp_dir() -> PathBuf {
-        let mut path = dirs::data_local_dir()
-            .unwrap_or_else(|| PathBuf::from("."));
-        path.push("ytaudiobar");
-        path.push("bin");
-        path
+        AppData::get_data_dir().join("bin")
     }
 
     pub fn get_ytdlp_path() -> PathBuf {
-        let mut path = Self::get_ytdlp_dir();
+        Self::get_ytdlp_dir().join(ASSET_NAME)
+    }
 
-        #[cfg(target_os = "windows")]
-        path.push("yt-dlp.exe");
+    // The binary replaced by the last install, kept so a misbehaving build can be rolled back
+    fn get_previous_path() -> PathBuf {
+        Self::get_ytdlp_dir().join(format!("{}.previous", ASSET_NAME))
+    }
 
-        #[cfg(not(target_os = "windows"))]
-        path.push("yt-dlp");
+    /// Hold while yt-dlp runs so the binary isn't swapped out from under it
+    pub async fn hold_binary() -> RwLockReadGuard<'static, ()> {
+        BINARY_LOCK.read().await
+    }
 
-        path
+    /// Resolves once a swap is waiting for the binary, for holders that would otherwise keep it
+    /// for minutes
+    pub async fn swap_requested() {
+        let notified = SWAP_REQUESTED.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        if SWAP_PENDING.load(Ordering::SeqCst) {
+            return;
+        }
+        notified.await;
+    }
+
+    /// Wait for running yt-dlp work to drain, holding off new work until the guard is dropped
+    pub async fn lock_binary() -> RwLockWriteGuard<'static, ()> {
+        if let Ok(guard) = BINARY_LOCK.try_write() {
+            return guard;
+        }
+        println!("⏳ Waiting for running yt-dlp work before swapping the binary...");
+        SWAP_PENDING.store(true, Ordering::SeqCst);
+        SWAP_REQUESTED.notify_waiters();
+        let guard = BINARY_LOCK.write().await;
+        SWAP_PENDING.store(false, Ordering::SeqCst);
+        guard
     }
 
     pub async fn is_installed() -> bool {
         Self::get_ytdlp_path().exists()
     }
 
-    pub async fn install() -> Result<(), String> {
+    // Download URL of the release's binary for this platform
+    fn download_url(release: &YtdlpRelease) -> String {
+        let repo = release.channel.repo();
+        match &release.version {
+            Some(version) => format!("https://github.com/{}/releases/download/{}/{}", repo, version, ASSET_NAME),
+            None => format!("https://github.com/{}/releases/latest/download/{}", repo, ASSET_NAME),
+        }
+    }
+
+    /// Install the release, keeping the binary it replaces for `rollback`
+    pub async fn install(release: &YtdlpRelease) -> Result<(), String> {
         let ytdlp_dir = Self::get_ytdlp_dir();
         let ytdlp_path = Self::get_ytdlp_path();
 
@@ -38,17 +92,11 @@ impl YTDLPInstaller {
             .await
             .map_err(|e| format!("Failed to create directory: {}", e))?;
 
-        // Download URL based on platform
-        #[cfg(target_os = "windows")]
-        let download_url = "https://github.com/yt-dlp/yt-dlp/releases/latest/download/yt-dlp.exe";
-
-        #[cfg(target_os = "linux")]
-        let download_url = "https://github.com/yt-dlp/yt-dlp/releases/latest/download/yt-dlp";
-
+        let download_url = Self::download_url(release);
         println!("Downloading yt-dlp from: {}", download_url);
 
         // Download the binary
-        let response = reqwest::get(download_url)
+        let response = reqwest::get(&download_url)
             .await
             .map_err(|e| format!("Failed to download yt-dlp: {}", e))?;
 
@@ -61,40 +109,97 @@ impl YTDLPInstaller {
             .await
             .map_err(|e| format!("Failed to read download: {}", e))?;
 
-        // Write to file
-        let mut file = fs::File::create(&ytdlp_path)
+        // Write next to the current binary first, so a failed download leaves it untouched
+        let download_path = ytdlp_dir.join(format!("{}.download", ASSET_NAME));
+        let mut file = fs::File::create(&download_path)
             .await
             .map_err(|e| format!("Failed to create file: {}", e))?;
 
         file.write_all(&bytes)
             .await
             .map_err(|e| format!("Failed to write file: {}", e))?;
+        drop(file);
 
         // Make executable on Linux
         #[cfg(not(target_os = "windows"))]
         {
             use std::os::unix::fs::PermissionsExt;
-            let mut perms = std::fs::metadata(&ytdlp_path)
+            let mut perms = std::fs::metadata(&download_path)
                 .map_err(|e| format!("Failed to get file metadata: {}", e))?
                 .permissions();
             perms.set_mode(0o755);
-            std::fs::set_permissions(&ytdlp_path, perms)
+            std::fs::set_permissions(&download_path, perms)
                 .map_err(|e| format!("Failed to set permissions: {}", e))?;
         }
 
+        let _swap = Self::lock_binary().await;
+        if ytdlp_path.exists() {
+            fs::copy(&ytdlp_path, Self::get_previous_path())
+                .await
+                .map_err(|e| format!("Failed to keep the previous yt-dlp: {}", e))?;
+        }
+        Self::replace_binary(&download_path)
+            .await
+            .map_err(|e| format!("Failed to install yt-dlp: {}", e))?;
+
         println!("yt-dlp installed successfully at: {}", ytdlp_path.display());
 
         Ok(())
     }
 
-    pub async fn get_version() -> Result<String, String> {
+    /// Swap back to the binary replaced by the last install; rolling back again undoes it
+    pub async fn rollback() -> Result<(), String> {
+        let ytdlp_path = Self::get_ytdlp_path();
+        let previous_path = Self::get_previous_path();
+        if !previous_path.exists() {
+            return Err("There's no previous yt-dlp version to roll back to".to_string());
+        }
+
+        let staged_path = Self::get_ytdlp_dir().join(format!("{}.rollback", ASSET_NAME));
+        let _swap = Self::lock_binary().await;
+        let swapped = async {
+            fs::copy(&previous_path, &staged_path).await?;
+            fs::copy(&ytdlp_path, &previous_path).await?;
+            Self::replace_binary(&staged_path).await
+        };
+        swapped.await.map_err(|e| format!("Failed to roll back yt-dlp: {}", e))?;
+
+        println!("yt-dlp rolled back to the previous version");
+        Ok(())
+    }
+
+    // Moves `new_path` over the installed binary in one step, so anything starting yt-dlp meanwhile
+    // finds one or the other. Windows won't replace a binary a stream is still running, but lets it
+    // be moved aside first.
+    async fn replace_binary(new_path: &Path) -> std::io::Result<()> {
         let ytdlp_path = Self::get_ytdlp_path();
+        if fs::rename(new_path, &ytdlp_path).await.is_ok() {
+            return Ok(());
+        }
+
+        let aside_path = Self::get_ytdlp_dir().join(format!("{}.old", ASSET_NAME));
+        let _ = fs::remove_file(&aside_path).await;
+        fs::rename(&ytdlp_path, &aside_path).await?;
+        fs::rename(new_path, &ytdlp_path).await?;
+        // Fails while the old binary is still running, it's retried on the next swap
+        let _ = fs::remove_file(&aside_path).await;
+        Ok(())
+    }
+
+    pub async fn get_version() -> Result<String, String> {
+        Self::version_of(&Self::get_ytdlp_path()).await
+    }
+
+    pub async fn get_previous_version() -> Result<String, String> {
+        Self::version_of(&Self::get_previous_path()).await
+    }
 
+    async fn version_of(ytdlp_path: &Path) -> Result<String, String> {
         if !ytdlp_path.exists() {
             return Err("yt-dlp not installed".to_string());
         }
 
-        let output = tokio::process::Command::new(&ytdlp_path)
+        let output = tokio::process::Command::new(ytdlp_path)
             .arg("--version")
             .output()
             .await
@@ -1,15 +1,207 @@
-use crate::models::YTVideoInfo;
+use crate::content_classifier;
+use crate::models::{CookieSource, YTVideoInfo, YouTubeLibrary, YtdlpOperation, YtdlpOptions};
+use crate::rate_limiter::RateLimiter;
 use crate::ytdlp_installer::YTDLPInstaller;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::process::Stdio;
+use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
+use tokio::sync::Mutex;
 
-pub struct YTDLPManager;
+// Entries fetched from a channel's uploads or a YouTube mix when building a radio station
+const ARTIST_TRACK_LIMIT: usize = 20;
+const MIX_TRACK_LIMIT: usize = 25;
+// Resolved stream URLs are dropped this long before YouTube says they expire
+const STREAM_URL_EXPIRY_MARGIN: i64 = 10 * 60;
+
+// A direct audio URL and the unix time it stops being usable
+struct CachedStreamUrl {
+    url: String,
+    expires_at: i64,
+}
+
+pub struct YTDLPManager {
+    cookies: Mutex<Option<CookieSource>>,
+    blocked_keywords: Mutex<Vec<String>>, // lowercase; search results matching any are dropped
+    limiter: Arc<RateLimiter>,
+    options: Mutex<YtdlpOptions>,
+    stream_urls: Mutex<HashMap<(String, String), CachedStreamUrl>>, // by video ID and format
+}
 
 impl YTDLPManager {
-    pub fn new() -> Self {
-        Self
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            cookies: Mutex::new(None),
+            blocked_keywords: Mutex::new(Vec::new()),
+            limiter,
+            options: Mutex::new(YtdlpOptions::default()),
+            stream_urls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn set_blocked_keywords(&self, keywords: Vec<String>) {
+        *self.blocked_keywords.lock().await = keywords
+            .iter()
+            .map(|k| k.trim().to_lowercase())
+            .filter(|k| !k.is_empty())
+            .collect();
+    }
+
+    pub async fn get_blocked_keywords(&self) -> Vec<String> {
+        self.blocked_keywords.lock().await.clone()
+    }
+
+    // Matches against the title, uploader and description
+    async fn is_blocked(&self, video: &YTVideoInfo) -> bool {
+        let keywords = self.blocked_keywords.lock().await;
+        if keywords.is_empty() {
+            return false;
+        }
+        let text = format!(
+            "{} {} {}",
+            video.title,
+            video.uploader,
+            video.description.as_deref().unwrap_or("")
+        )
+        .to_lowercase();
+        keywords.iter().any(|k| text.contains(k.as_str()))
+    }
+
+    pub async fn set_cookies(&self, cookies: Option<CookieSource>) {
+        *self.cookies.lock().await = cookies;
+    }
+
+    pub async fn get_cookies(&self) -> Option<CookieSource> {
+        self.cookies.lock().await.clone()
+    }
+
+    pub async fn set_options(&self, options: YtdlpOptions) {
+        *self.options.lock().await = options;
+        // URLs resolved with the old client settings may not play under the new ones
+        self.stream_urls.lock().await.clear();
+    }
+
+    pub async fn get_options(&self) -> YtdlpOptions {
+        self.options.lock().await.clone()
+    }
+
+    async fn option_args(&self, operation: YtdlpOperation) -> Vec<String> {
+        self.options.lock().await.args(operation)
+    }
+
+    async fn cookie_args(&self) -> Vec<String> {
+        match self.cookies.lock().await.as_ref() {
+            Some(CookieSource::Browser(browser)) => vec!["--cookies-from-browser".to_string(), browser.clone()],
+            Some(CookieSource::File(path)) => vec!["--cookies".to_string(), path.clone()],
+            None => Vec::new(),
+        }
+    }
+
+    /// List the videos in the signed-in user's Liked or Watch Later playlist
+    pub async fn fetch_library(&self, library: YouTubeLibrary) -> Result<Vec<YTVideoInfo>, String> {
+        let cookie_args = self.cookie_args().await;
+        if cookie_args.is_empty() {
+            return Err("Configure cookies to import your YouTube library".to_string());
+        }
+
+        let ytdlp_path = Self::get_ytdlp_path();
+        let url = format!("https://www.youtube.com/playlist?list={}", library.list_id());
+
+        let option_args = self.option_args(YtdlpOperation::Lookup).await;
+        self.limiter.acquire().await?;
+        let _binary = YTDLPInstaller::hold_binary().await;
+        let output = Command::new(&ytdlp_path)
+            .args(["--flat-playlist", "--dump-json", "--no-warnings", "--ignore-errors"])
+            .args(&cookie_args)
+            .arg(&url)
+            .args(&option_args)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+        self.limiter.report(output.status.success(), &String::from_utf8_lossy(&output.stderr)).await;
+
+        if !output.status.success() && output.stdout.is_empty() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Failed to fetch {}: {}", library.playlist_name(), stderr.trim()));
+        }
+
+        let videos = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter_map(|json| Self::parse_flat_entry(&json))
+            .collect();
+
+        Ok(videos)
+    }
+
+    /// An artist's tracks, from a channel URL or @handle (its uploads) or an uploader name (top search results)
+    pub async fn fetch_artist_tracks(&self, uploader_or_channel: &str) -> Result<Vec<YTVideoInfo>, String> {
+        let artist = uploader_or_channel.trim();
+        let channel_url = if artist.starts_with('@') {
+            Some(format!("https://www.youtube.com/{}/videos", artist))
+        } else if artist.contains("youtube.com/") {
+            Some(format!("{}/videos", artist.trim_end_matches('/').trim_end_matches("/videos")))
+        } else {
+            None
+        };
+
+        if let Some(url) = channel_url {
+            return self.fetch_flat_list(&url, ARTIST_TRACK_LIMIT).await;
+        }
+
+        // Prefer results actually uploaded by the artist, falling back to anything the search found
+        let results = self.search(artist.to_string(), true).await?;
+        let needle = artist.to_lowercase();
+        let own: Vec<YTVideoInfo> = results
+            .iter()
+            .filter(|t| t.uploader.to_lowercase().contains(&needle))
+            .cloned()
+            .collect();
+        Ok(if own.is_empty() { results } else { own })
+    }
+
+    /// The tracks of YouTube's automatic mix for a video, mostly by related artists
+    pub async fn fetch_mix(&self, video_id: &str) -> Result<Vec<YTVideoInfo>, String> {
+        let url = format!("https://www.youtube.com/watch?v={}&list=RD{}", video_id, video_id);
+        let tracks = self.fetch_flat_list(&url, MIX_TRACK_LIMIT).await?;
+        Ok(tracks.into_iter().filter(|t| t.id != video_id).collect())
+    }
+
+    // First `limit` entries of a playlist or channel, without blocked ones
+    async fn fetch_flat_list(&self, url: &str, limit: usize) -> Result<Vec<YTVideoInfo>, String> {
+        let ytdlp_path = Self::get_ytdlp_path();
+
+        let option_args = self.option_args(YtdlpOperation::Lookup).await;
+        self.limiter.acquire().await?;
+        let _binary = YTDLPInstaller::hold_binary().await;
+        let output = Command::new(&ytdlp_path)
+            .args(["--flat-playlist", "--dump-json", "--no-warnings", "--ignore-errors"])
+            .args(["--playlist-end", &limit.to_string()])
+            .arg(url)
+            .args(&option_args)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+        self.limiter.report(output.status.success(), &String::from_utf8_lossy(&output.stderr)).await;
+
+        if !output.status.success() && output.stdout.is_empty() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Failed to fetch {}: {}", url, stderr.trim()));
+        }
+
+        let mut videos = Vec::new();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let Some(video) = serde_json::from_str::<Value>(line).ok().and_then(|json| Self::parse_flat_entry(&json)) else {
+                continue;
+            };
+            if !self.is_blocked(&video).await {
+                videos.push(video);
+            }
+        }
+
+        Ok(videos)
     }
 
     pub async fn search(&self, query: String, music_mode: bool) -> Result<Vec<YTVideoInfo>, String> {
@@ -21,6 +213,9 @@ impl YTDLPManager {
 
         let ytdlp_path = Self::get_ytdlp_path();
 
+        let option_args = self.option_args(YtdlpOperation::Search).await;
+        self.limiter.acquire().await?;
+        let _binary = YTDLPInstaller::hold_binary().await;
         let mut child = Command::new(&ytdlp_path)
             .args(&[
                 "--dump-json",
@@ -28,8 +223,9 @@ impl YTDLPManager {
                 "--ignore-errors",
                 &search_query,
             ])
+            .args(&option_args)
             .stdout(Stdio::piped())
-            .stderr(Stdio::null())
+            .stderr(Stdio::piped())
             .spawn()
             .map_err(|e| format!("Failed to spawn yt-dlp: {}. Make sure yt-dlp is installed.", e))?;
 
@@ -38,6 +234,15 @@ impl YTDLPManager {
             .take()
             .ok_or("Failed to capture stdout")?;
 
+        // Read stderr alongside stdout so a full pipe can't stall yt-dlp; it tells whether YouTube throttled us
+        let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+        let error_handle = tokio::spawn(async move {
+            use tokio::io::AsyncReadExt;
+            let mut errors = String::new();
+            let _ = BufReader::new(stderr).read_to_string(&mut errors).await;
+            errors
+        });
+
         let reader = BufReader::new(stdout);
         let mut lines = reader.lines();
         let mut results = Vec::new();
@@ -45,12 +250,16 @@ impl YTDLPManager {
         while let Ok(Some(line)) = lines.next_line().await {
             if let Ok(json) = serde_json::from_str::<Value>(&line) {
                 if let Some(video) = Self::parse_video_info(&json) {
-                    results.push(video);
+                    if !self.is_blocked(&video).await {
+                        results.push(video);
+                    }
                 }
             }
         }
 
-        child.wait().await.map_err(|e| format!("yt-dlp process error: {}", e))?;
+        let status = child.wait().await.map_err(|e| format!("yt-dlp process error: {}", e))?;
+        let errors = error_handle.await.unwrap_or_default();
+        self.limiter.report(status.success(), &errors).await;
 
         Ok(results)
     }
@@ -59,6 +268,8 @@ impl YTDLPManager {
         let ytdlp_path = Self::get_ytdlp_path();
         let url = format!("https://www.youtube.com/watch?v={}", video_id);
 
+        let option_args = self.option_args(YtdlpOperation::Stream).await;
+        self.limiter.acquire().await?;
         let output = Command::new(&ytdlp_path)
             .args(&[
                 "--dump-json",
@@ -66,9 +277,11 @@ impl YTDLPManager {
                 "--no-warnings",
                 &url,
             ])
+            .args(&option_args)
             .output()
             .await
             .map_err(|e| format!("Failed to get audio URL: {}", e))?;
+        self.limiter.report(output.status.success(), &String::from_utf8_lossy(&output.stderr)).await;
 
         if !output.status.success() {
             return Err("Failed to extract audio URL from YouTube".to_string());
@@ -90,7 +303,119 @@ impl YTDLPManager {
         Ok((audio_url, ext))
     }
 
+    /// Direct audio URL for streaming `video_id` in `format`, reused until shortly before it expires
+    pub async fn get_stream_url(&self, video_id: &str, format: &str) -> Result<String, String> {
+        let key = (video_id.to_string(), format.to_string());
+        let now = chrono::Utc::now().timestamp();
+        if let Some(cached) = self.stream_urls.lock().await.get(&key) {
+            if cached.expires_at > now {
+                return Ok(cached.url.clone());
+            }
+        }
+
+        let ytdlp_path = Self::get_ytdlp_path();
+        let url = format!("https://www.youtube.com/watch?v={}", video_id);
+
+        let option_args = self.option_args(YtdlpOperation::Stream).await;
+        self.limiter.acquire().await?;
+        let output = Command::new(&ytdlp_path)
+            .args(["--get-url", "-f", format, "--no-warnings", &url])
+            .args(&option_args)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to resolve stream URL: {}", e))?;
+        self.limiter.report(output.status.success(), &String::from_utf8_lossy(&output.stderr)).await;
+
+        if !output.status.success() {
+            return Err("Failed to extract audio URL from YouTube".to_string());
+        }
+
+        let stream_url = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .ok_or_else(|| "No audio URL found in response".to_string())?;
+
+        // Without an expiry there's no telling how long the URL lasts, so it isn't kept
+        if let Some(expires_at) = stream_url_expiry(&stream_url) {
+            let mut stream_urls = self.stream_urls.lock().await;
+            stream_urls.retain(|_, cached| cached.expires_at > now);
+            stream_urls.insert(key, CachedStreamUrl {
+                url: stream_url.clone(),
+                expires_at: expires_at - STREAM_URL_EXPIRY_MARGIN,
+            });
+        }
+
+        Ok(stream_url)
+    }
+
+    /// Forget resolved stream URLs for a video, e.g. after YouTube refused one
+    pub async fn invalidate_stream_url(&self, video_id: &str) {
+        self.stream_urls.lock().await.retain(|(id, _), _| id != video_id);
+    }
+
+    /// Just a video's length in seconds, without the rest of its metadata
+    pub async fn get_duration(&self, video_id: &str) -> Result<i64, String> {
+        let ytdlp_path = Self::get_ytdlp_path();
+        let url = format!("https://www.youtube.com/watch?v={}", video_id);
+
+        let option_args = self.option_args(YtdlpOperation::Lookup).await;
+        self.limiter.acquire().await?;
+        let _binary = YTDLPInstaller::hold_binary().await;
+        let output = Command::new(&ytdlp_path)
+            .args(["--print", "duration", "--skip-download", "--no-warnings", "--no-playlist", &url])
+            .args(&option_args)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+        self.limiter.report(output.status.success(), &String::from_utf8_lossy(&output.stderr)).await;
+
+        if !output.status.success() {
+            return Err(format!("Failed to fetch duration for {}", video_id));
+        }
+
+        // Printed as a float, or "NA" for live streams
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse::<f64>()
+            .map(|seconds| seconds.round() as i64)
+            .map_err(|_| format!("No duration for {}", video_id))
+    }
+
+    pub async fn get_video_info(&self, video_id: &str) -> Result<YTVideoInfo, String> {
+        let ytdlp_path = Self::get_ytdlp_path();
+        let url = format!("https://www.youtube.com/watch?v={}", video_id);
+
+        let option_args = self.option_args(YtdlpOperation::Lookup).await;
+        self.limiter.acquire().await?;
+        let _binary = YTDLPInstaller::hold_binary().await;
+        let output = Command::new(&ytdlp_path)
+            .args(["--dump-json", "--no-warnings", "--no-playlist", &url])
+            .args(&option_args)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+        self.limiter.report(output.status.success(), &String::from_utf8_lossy(&output.stderr)).await;
+
+        if !output.status.success() {
+            return Err(format!("Failed to fetch video info for {}", video_id));
+        }
+
+        let json: Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Failed to parse yt-dlp output: {}", e))?;
+
+        Self::parse_video_info(&json).ok_or_else(|| "Incomplete video info from yt-dlp".to_string())
+    }
+
     fn parse_video_info(json: &Value) -> Option<YTVideoInfo> {
+        let duration = json.get("duration").and_then(|v| v.as_i64()).unwrap_or(0);
+        let categories: Vec<&str> = json
+            .get("categories")
+            .and_then(|v| v.as_array())
+            .map(|categories| categories.iter().filter_map(|c| c.as_str()).collect())
+            .unwrap_or_default();
+
         Some(YTVideoInfo {
             id: json.get("id")?.as_str()?.to_string(),
             title: json.get("title")?.as_str()?.to_string(),
@@ -99,7 +424,7 @@ impl YTDLPManager {
                 .and_then(|v| v.as_str())
                 .unwrap_or("Unknown")
                 .to_string(),
-            duration: json.get("duration").and_then(|v| v.as_i64()).unwrap_or(0),
+            duration,
             thumbnail_url: json
                 .get("thumbnail")
                 .and_then(|v| v.as_str())
@@ -109,9 +434,35 @@ impl YTDLPManager {
                 .get("description")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
+            content_type: content_classifier::classify_metadata(&categories, duration),
+            custom_title: None,
+            custom_author: None,
         })
     }
 
+    // Flat playlist entries carry a thumbnail list and channel name instead of the full metadata
+    fn parse_flat_entry(json: &Value) -> Option<YTVideoInfo> {
+        let mut video = Self::parse_video_info(json)?;
+
+        if video.thumbnail_url.is_none() {
+            video.thumbnail_url = json
+                .get("thumbnails")
+                .and_then(|v| v.as_array())
+                .and_then(|thumbnails| thumbnails.last())
+                .and_then(|t| t.get("url"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+        }
+
+        if video.uploader == "Unknown" {
+            if let Some(channel) = json.get("channel").and_then(|v| v.as_str()) {
+                video.uploader = channel.to_string();
+            }
+        }
+
+        Some(video)
+    }
+
     fn get_ytdlp_path() -> String {
         // Use the installer's path
         let installed_path = YTDLPInstaller::get_ytdlp_path();
@@ -121,6 +472,7 @@ impl YTDLPManager {
     pub async fn check_ytdlp_exists(&self) -> bool {
         let ytdlp_path = Self::get_ytdlp_path();
 
+        let _binary = YTDLPInstaller::hold_binary().await;
         Command::new(&ytdlp_path)
             .arg("--version")
             .output()
@@ -132,6 +484,7 @@ impl YTDLPManager {
     pub async fn update_ytdlp(&self) -> Result<(), String> {
         let ytdlp_path = Self::get_ytdlp_path();
 
+        let _swap = YTDLPInstaller::lock_binary().await;
         let output = Command::new(&ytdlp_path)
             .arg("-U")
             .output()
@@ -145,3 +498,176 @@ impl YTDLPManager {
         Ok(())
     }
 }
+
+/// Extract the video ID from a YouTube watch, short, shorts or music URL
+pub fn extract_video_id(text: &str) -> Option<String> {
+    let text = text.trim();
+    if !text.contains("youtube.com") && !text.contains("youtu.be") {
+        return None;
+    }
+
+    let candidate = if let Some((_, rest)) = text.split_once("youtu.be/") {
+        rest
+    } else if let Some((_, rest)) = text.split_once("/shorts/") {
+        rest
+    } else if let Some((_, rest)) = text.split_once("/embed/") {
+        rest
+    } else {
+        let (_, query) = text.split_once('?')?;
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("v="))?
+    };
+
+    let id: String = candidate
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+
+    (id.len() == 11).then_some(id)
+}
+
+// Flags that could run programs, load other configs, or move output where the app won't find it
+const DENIED_ARGS: [&str; 30] = [
+    "--exec",
+    "--exec-before-download",
+    "--netrc-cmd",
+    "--config-location",
+    "--config-locations",
+    "--plugin-dirs",
+    "--use-postprocessor",
+    "--postprocessor-args",
+    "--ppa",
+    "--downloader",
+    "--external-downloader",
+    "--downloader-args",
+    "--external-downloader-args",
+    "--ffmpeg-location",
+    "--output",
+    "-o",
+    "--paths",
+    "-P",
+    "--batch-file",
+    "-a",
+    "--load-info-json",
+    "--print-to-file",
+    "--cookies",
+    "--cookies-from-browser",
+    "--cache-dir",
+    "--update",
+    "--update-to",
+    "-U",
+    "--alias",
+    "--",
+];
+
+/// Split extra arguments typed by the user the way a shell would, refusing denied flags
+pub fn parse_extra_args(text: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+
+    for c in text.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err("Unclosed quote in extra arguments".to_string());
+    }
+    if in_arg {
+        args.push(current);
+    }
+
+    for arg in &args {
+        let denied = DENIED_ARGS.iter().any(|&flag| {
+            if flag.starts_with("--") {
+                // "--flag", "--flag=value", and abbreviations yt-dlp would expand to the flag
+                let name = arg.split('=').next().unwrap_or(arg);
+                name.starts_with("--") && flag.starts_with(name)
+            } else {
+                // "-o", "-ovalue" and grouped short options like "-xo"; what follows a letter may be
+                // its value rather than more options, so any denied letter in the group refuses it
+                let letter = flag.trim_start_matches('-');
+                match arg.strip_prefix('-') {
+                    Some(group) if !group.starts_with('-') => group.contains(letter),
+                    _ => false,
+                }
+            }
+        });
+        if denied {
+            return Err(format!("{} isn't allowed in extra arguments", arg));
+        }
+    }
+    Ok(args)
+}
+
+// Unix time a googlevideo URL stops working, from its `expire` query parameter
+fn stream_url_expiry(url: &str) -> Option<i64> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("expire="))
+        .and_then(|value| value.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_extra_args;
+
+    #[test]
+    fn splits_like_a_shell() {
+        assert_eq!(
+            parse_extra_args(r#"--sleep-requests 1 --user-agent "Mozilla 5.0" -x"#).unwrap(),
+            vec!["--sleep-requests", "1", "--user-agent", "Mozilla 5.0", "-x"]
+        );
+        assert_eq!(parse_extra_args("  ").unwrap(), Vec::<String>::new());
+        assert!(parse_extra_args("--user-agent 'Mozilla").is_err());
+    }
+
+    #[test]
+    fn refuses_denied_long_flags() {
+        assert!(parse_extra_args("--exec rm").is_err());
+        assert!(parse_extra_args("--output=/tmp/x").is_err());
+        assert!(parse_extra_args("--outp /tmp/x").is_err());
+        assert!(parse_extra_args("-- -o").is_err());
+    }
+
+    #[test]
+    fn refuses_denied_short_flags() {
+        assert!(parse_extra_args("-o /tmp/x").is_err());
+        assert!(parse_extra_args("-o/tmp/x").is_err());
+        assert!(parse_extra_args("-U").is_err());
+    }
+
+    #[test]
+    fn refuses_denied_flags_grouped_with_others() {
+        assert!(parse_extra_args("-xo/tmp/evil").is_err());
+        assert!(parse_extra_args("-qa list.txt").is_err());
+        assert!(parse_extra_args("-xP /tmp").is_err());
+        assert!(parse_extra_args("-xU").is_err());
+    }
+
+    #[test]
+    fn allows_values_and_other_flags() {
+        assert!(parse_extra_args("-x -q --no-playlist").is_ok());
+        assert!(parse_extra_args("--format bestaudio").is_ok());
+        assert!(parse_extra_args("--sleep-requests 1 -N 4").is_ok());
+    }
+}
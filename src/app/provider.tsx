@@ -1,9 +1,26 @@
-import { ReactNode, Suspense } from 'react'
+import { ReactNode, Suspense, useEffect } from 'react'
 import AppErrorPage from '@/features/errors/app-error'
 import { ErrorBoundary } from 'react-error-boundary'
 import { TooltipProvider } from '@/components/ui/tooltip'
+import { getSystemTheme, listenToSystemThemeChanged, type SystemTheme } from '@/lib/tauri'
+
+// Dark is the default look, so only light and high contrast need a class on the root
+function applySystemTheme(theme: SystemTheme) {
+    const root = document.documentElement
+    root.classList.toggle('dark', theme.dark)
+    root.classList.toggle('light', !theme.dark)
+    root.classList.toggle('high-contrast', theme.high_contrast)
+}
 
 export default function AppProvider({ children }: { children: ReactNode }) {
+    useEffect(() => {
+        getSystemTheme().then(applySystemTheme).catch(() => {})
+        const unlisten = listenToSystemThemeChanged(applySystemTheme)
+        return () => {
+            unlisten.then((fn) => fn())
+        }
+    }, [])
+
     return (
         <Suspense fallback={<>Loading...</>}>
             <ErrorBoundary FallbackComponent={AppErrorPage}>
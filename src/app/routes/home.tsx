@@ -2,6 +2,7 @@ import { useState, useEffect } from 'react'
 import { AppHeader } from '@/components/app-header'
 import { MiniPlayer } from '@/features/player/mini-player'
 import { ExpandedPlayer } from '@/features/player/expanded-player'
+import { IntroSkipPrompt } from '@/features/player/intro-skip-prompt'
 import { SearchTab } from '@/features/search/search-tab'
 import { QueueTab } from '@/features/queue/queue-tab'
 import { PlaylistsTab } from '@/features/playlists/playlists-tab'
@@ -17,6 +18,7 @@ import {
     playNext as playNextTrack,
     playPrevious as playPreviousTrack,
     seekTo,
+    replay,
     updateMediaMetadata,
     updateMediaPlaybackState,
     clearMediaInfo,
@@ -27,6 +29,8 @@ import {
     listenToMediaKeyPause,
     listenToMediaKeySeek,
     listenToMediaKeySeekTo,
+    listenToMediaKeyReplay,
+    listenToTrackDurationFixed,
     type AudioState,
     type YTVideoInfo
 } from '@/lib/tauri'
@@ -68,6 +72,16 @@ export function HomePage() {
         initYtdlp()
     }, [])
 
+    // Flat searches often come back without durations, they're looked up in the background
+    useEffect(() => {
+        const unlisten = listenToTrackDurationFixed(({ video_id, duration }) => {
+            setSearchResults((results) => results.map((t) => t.id === video_id ? { ...t, duration } : t))
+        })
+        return () => {
+            unlisten.then(fn => fn())
+        }
+    }, [])
+
     // Listen to playback state changes
     useEffect(() => {
         const unlisten = listenToPlaybackState((state) => {
@@ -169,6 +183,12 @@ export function HomePage() {
             })
         )
 
+        unlisteners.push(
+            listenToMediaKeyReplay(() => {
+                replay().catch(console.error)
+            })
+        )
+
         return () => {
             Promise.all(unlisteners).then((fns) => fns.forEach((fn) => fn()))
         }
@@ -235,7 +255,7 @@ export function HomePage() {
                         <MiniPlayer
                             track={currentTrack}
                             isPlaying={isPlaying}
-                            isLoading={audioState?.is_loading || false}
+                            isLoading={audioState?.is_loading || audioState?.is_buffering || false}
                             onExpand={() => setIsExpanded(true)}
                         />
                     ) : audioState && (
@@ -244,6 +264,7 @@ export function HomePage() {
                             onCollapse={() => setIsExpanded(false)}
                         />
                     )}
+                    <IntroSkipPrompt />
                 </>
             )}
 
@@ -0,0 +1,94 @@
+import { useState } from 'react'
+import { X } from 'lucide-react'
+import { updateTrackDisplay, type YTVideoInfo } from '@/lib/tauri'
+
+interface TrackDetailsModalProps {
+    track: YTVideoInfo
+    note: string | null
+    onSaved: (title: string | null, artist: string | null, note: string | null) => void
+    onClose: () => void
+}
+
+// Blank fields clear the override, so the YouTube title and channel show again
+export function TrackDetailsModal({ track, note, onSaved, onClose }: TrackDetailsModalProps) {
+    const [title, setTitle] = useState(track.custom_title ?? '')
+    const [artist, setArtist] = useState(track.custom_author ?? '')
+    const [trackNote, setTrackNote] = useState(note ?? '')
+    const [error, setError] = useState<string | null>(null)
+
+    const handleSave = async () => {
+        const clean = (value: string) => value.trim() || null
+        try {
+            await updateTrackDisplay(track, clean(title), clean(artist), clean(trackNote))
+            onSaved(clean(title), clean(artist), clean(trackNote))
+            onClose()
+        } catch (error: any) {
+            setError(String(error))
+            console.error('Failed to save track details:', error)
+        }
+    }
+
+    const inputClass = 'w-full px-3 py-2 bg-secondary border-none rounded-lg text-[13px] text-foreground placeholder:text-muted-foreground focus:outline-none focus:ring-2 focus:ring-[var(--macos-blue)] mb-3'
+
+    return (
+        <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50" onClick={onClose}>
+            <div className="bg-card rounded-xl w-[300px] flex flex-col" onClick={(e) => e.stopPropagation()}>
+                {/* Header */}
+                <div className="flex items-center justify-between px-4 py-3 border-b border-macos-separator">
+                    <div className="flex-1 min-w-0 pr-2">
+                        <h3 className="text-[13px] font-semibold text-foreground truncate">
+                            {track.title}
+                        </h3>
+                        <p className="text-[11px] text-muted-foreground truncate">Edit details</p>
+                    </div>
+                    <button
+                        onClick={onClose}
+                        className="w-8 h-8 flex items-center justify-center rounded-full hover-macos-button transition-colors flex-shrink-0"
+                    >
+                        <X className="w-4 h-4 text-muted-foreground" />
+                    </button>
+                </div>
+
+                <div className="p-4">
+                    <input
+                        type="text"
+                        value={title}
+                        onChange={(e) => setTitle(e.target.value)}
+                        placeholder={track.title}
+                        className={inputClass}
+                        autoFocus
+                    />
+                    <input
+                        type="text"
+                        value={artist}
+                        onChange={(e) => setArtist(e.target.value)}
+                        placeholder={track.uploader}
+                        className={inputClass}
+                    />
+                    <textarea
+                        value={trackNote}
+                        onChange={(e) => setTrackNote(e.target.value)}
+                        placeholder="Note"
+                        rows={3}
+                        className={`${inputClass} resize-none`}
+                    />
+                    {error && <div className="text-[11px] text-macos-red mb-3">{error}</div>}
+                    <div className="flex gap-2">
+                        <button
+                            onClick={onClose}
+                            className="flex-1 px-4 py-2 rounded-lg text-[13px] font-medium text-[var(--macos-blue)] hover-macos-button transition-colors"
+                        >
+                            Cancel
+                        </button>
+                        <button
+                            onClick={handleSave}
+                            className="flex-1 px-4 py-2 rounded-lg text-[13px] font-medium bg-[var(--macos-blue)] text-white hover:opacity-90 transition-opacity"
+                        >
+                            Save
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </div>
+    )
+}
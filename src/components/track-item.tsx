@@ -1,9 +1,13 @@
-import { useState, useEffect } from 'react'
-import { Play, Pause, Heart, Trash, Loader2, Music, Download } from 'lucide-react'
-import { playTrack, togglePlayPause, downloadTrack, isTrackDownloaded, getActiveDownloads, type YTVideoInfo, type Track, formatDuration } from '@/lib/tauri'
+import { useState, useEffect, useRef } from 'react'
+import { Play, Pause, Heart, Trash, Loader2, Music, Download, Pencil } from 'lucide-react'
+import { playTrack, playTrackInContext, warmTrack, togglePlayPause, downloadTrack, isTrackDownloaded, getActiveDownloads, type YTVideoInfo, type Track, formatDuration, displayTitle, displayUploader } from '@/lib/tauri'
 import { PlaylistSelectionModal } from '@/features/playlists/playlist-selection-modal'
+import { TrackDetailsModal } from '@/components/track-details-modal'
 import { usePlayerStore } from '@/stores/player-store'
 
+// How long the pointer rests on a track before the start of it is buffered
+const WARM_DELAY_MS = 400
+
 interface TrackItemProps {
     track: YTVideoInfo | Track
     context: 'search' | 'queue' | 'playlist'
@@ -11,6 +15,8 @@ interface TrackItemProps {
     isPlaying?: boolean
     queueIndex?: number
     isFavorite?: boolean
+    thumbnail?: string | null // prefetched copy; null holds the placeholder until it arrives
+    playlistId?: string // playing queues the rest of this playlist around the track
     onRemove?: () => void
     onToggleFavorite?: () => void
 }
@@ -21,22 +27,35 @@ export function TrackItem({
     isCurrentTrack,
     queueIndex,
     isFavorite,
+    thumbnail,
+    playlistId,
     onRemove,
     onToggleFavorite
 }: TrackItemProps) {
     const [showPlaylistModal, setShowPlaylistModal] = useState(false)
-    const [isDownloaded, setIsDownloaded] = useState(false)
+    const [showDetailsModal, setShowDetailsModal] = useState(false)
+    // Names and note just edited here, shown until the list reloads with them
+    const [edited, setEdited] = useState<{ custom_title: string | null, custom_author: string | null, note: string | null } | null>(null)
+    // Saved tracks carry their download path, so the badge shows before the status check
+    const [isDownloaded, setIsDownloaded] = useState('file_path' in track && !!track.file_path)
     const [isDownloading, setIsDownloading] = useState(false)
     const [downloadProgress, setDownloadProgress] = useState<number>(0)
     const [isCheckingDownload, setIsCheckingDownload] = useState(true)
+    const warmTimeout = useRef<ReturnType<typeof setTimeout> | null>(null)
 
     // Convert Track to YTVideoInfo format
-    const videoInfo: YTVideoInfo = 'uploader' in track ? track : {
+    const baseInfo: YTVideoInfo = 'uploader' in track ? track : {
         ...track,
         uploader: track.author || 'Unknown',
         audio_url: null,
         description: null
     }
+    const videoInfo: YTVideoInfo = edited
+        ? { ...baseInfo, custom_title: edited.custom_title, custom_author: edited.custom_author }
+        : baseInfo
+    const note = edited ? edited.note : 'note' in track ? track.note : null
+
+    const thumbnailSrc = thumbnail === undefined ? videoInfo.thumbnail_url : thumbnail
 
     // Use Zustand store for player state
     const { loadingTrackId, currentTrack, isPlaying: globalIsPlaying } = usePlayerStore()
@@ -130,9 +149,11 @@ export function TrackItem({
             // Otherwise, play the new track
             if (currentTrack?.id === videoInfo.id) {
                 await togglePlayPause()
+            } else if (playlistId) {
+                // Queue the playlist around the track so next and previous follow it
+                await playTrackInContext(videoInfo.id, playlistId)
             } else {
                 // Play track directly WITHOUT adding to queue
-                // Queue is only populated via "Play All" on playlists
                 await playTrack(videoInfo)
             }
         } catch (error) {
@@ -140,6 +161,21 @@ export function TrackItem({
         }
     }
 
+    // Buffer the start of a track the user lingers on, so clicking it plays almost at once
+    const handleWarm = () => {
+        if (isDownloaded || currentTrack?.id === videoInfo.id || warmTimeout.current) return
+        warmTimeout.current = setTimeout(() => {
+            warmTrack(videoInfo.id).catch((error) => console.error('Failed to warm track:', error))
+        }, WARM_DELAY_MS)
+    }
+
+    const cancelWarm = () => {
+        if (warmTimeout.current) clearTimeout(warmTimeout.current)
+        warmTimeout.current = null
+    }
+
+    useEffect(() => cancelWarm, [])
+
     // Removed: handleAddToQueue - tracks are no longer manually added to queue
     // Queue is only populated by "Play All" playlist action
 
@@ -176,6 +212,10 @@ export function TrackItem({
                         : ''
                 }`}
                 onClick={handlePlay}
+                onMouseEnter={handleWarm}
+                onMouseLeave={cancelWarm}
+                onFocus={handleWarm}
+                onBlur={cancelWarm}
             >
                 {/* Leading Element - Queue Number */}
                 {context === 'queue' && queueIndex !== undefined && (
@@ -186,10 +226,10 @@ export function TrackItem({
 
                 {/* Thumbnail - 48x48px with 4px radius */}
                 <div className="w-12 h-12 rounded flex-shrink-0 bg-secondary overflow-hidden">
-                    {videoInfo.thumbnail_url ? (
+                    {thumbnailSrc ? (
                         <img
-                            src={videoInfo.thumbnail_url}
-                            alt={videoInfo.title}
+                            src={thumbnailSrc}
+                            alt={displayTitle(videoInfo)}
                             className="w-full h-full object-cover"
                         />
                     ) : (
@@ -208,10 +248,10 @@ export function TrackItem({
                                 : 'text-foreground'
                         }`}
                     >
-                        {videoInfo.title}
+                        {displayTitle(videoInfo)}
                     </div>
                     <div className="flex items-center gap-1.5 text-[12px] text-muted-foreground">
-                        <span className="truncate">{videoInfo.uploader}</span>
+                        <span className="truncate">{displayUploader(videoInfo)}</span>
                         {videoInfo.duration && (
                             <>
                                 <span>•</span>
@@ -289,6 +329,20 @@ export function TrackItem({
                         </button>
                     )}
 
+                    {/* Edit Button - rename the track or add a note */}
+                    {context !== 'queue' && (
+                        <button
+                            onClick={(e) => {
+                                e.stopPropagation()
+                                setShowDetailsModal(true)
+                            }}
+                            className="w-6 h-6 flex items-center justify-center hover-macos-button rounded"
+                            title="Edit details"
+                        >
+                            <Pencil className="w-4 h-4 text-foreground" />
+                        </button>
+                    )}
+
                     {/* Favorite Toggle - All contexts except playlist */}
                     {context !== 'playlist' && (
                         <button
@@ -320,6 +374,16 @@ export function TrackItem({
                 </div>
             </div>
 
+            {/* Track Details Modal */}
+            {showDetailsModal && (
+                <TrackDetailsModal
+                    track={videoInfo}
+                    note={note}
+                    onSaved={(custom_title, custom_author, note) => setEdited({ custom_title, custom_author, note })}
+                    onClose={() => setShowDetailsModal(false)}
+                />
+            )}
+
             {/* Playlist Selection Modal */}
             {showPlaylistModal && (
                 <PlaylistSelectionModal
@@ -1,16 +1,30 @@
 import { useState, useEffect } from 'react'
-import { Download, X, CheckSquare, Square } from 'lucide-react'
+import { Download, X, CheckSquare, Square, Zap } from 'lucide-react'
 import {
     getActiveDownloads,
     getDownloadedTracks,
     getStorageUsed,
     deleteDownload,
+    deleteDownloads,
     cancelDownload,
+    setDownloadPriority,
+    getFailedDownloads,
+    retryAllFailed,
+    redownloadTrack,
     listenToDownloadsUpdate,
+    listenToStorageChanged,
+    getStorageProblem,
+    listenToStorageProblem,
+    listenToStorageProblemResolved,
     getAllPlaylists,
     getPlaylistTracks,
+    getRecordingDuplicates,
+    listenToRecordingDuplicateFound,
+    type RecordingDuplicate,
     type DownloadProgress,
-    type DownloadedTrack
+    type DownloadedTrack,
+    type FailedDownload,
+    type StorageProblem
 } from '@/lib/tauri'
 import { TrackItem } from '@/components/track-item'
 import { TabHeader } from '@/components/tab-header'
@@ -18,21 +32,26 @@ import { TabHeader } from '@/components/tab-header'
 export function DownloadsTab() {
     const [activeDownloads, setActiveDownloads] = useState<DownloadProgress[]>([])
     const [downloadedTracks, setDownloadedTracks] = useState<DownloadedTrack[]>([])
+    const [failedDownloads, setFailedDownloads] = useState<FailedDownload[]>([])
     const [storageUsed, setStorageUsed] = useState<number>(0)
+    const [storageProblem, setStorageProblem] = useState<StorageProblem | null>(null)
     const [isSelectionMode, setIsSelectionMode] = useState(false)
     const [selectedTracks, setSelectedTracks] = useState<Set<string>>(new Set())
     const [favoriteTrackIds, setFavoriteTrackIds] = useState<Set<string>>(new Set())
+    const [duplicates, setDuplicates] = useState<RecordingDuplicate[]>([])
     const [isLoading, setIsLoading] = useState(true)
 
     const loadDownloads = async () => {
         try {
-            const [active, downloaded, storage] = await Promise.all([
+            const [active, downloaded, failed, storage] = await Promise.all([
                 getActiveDownloads(),
                 getDownloadedTracks(),
+                getFailedDownloads(),
                 getStorageUsed()
             ])
             setActiveDownloads(active)
             setDownloadedTracks(downloaded)
+            setFailedDownloads(failed)
             setStorageUsed(storage)
 
             // Load favorites
@@ -60,6 +79,17 @@ export function DownloadsTab() {
             loadDownloads()
         })
 
+        const unlistenStorage = listenToStorageChanged(setStorageUsed)
+
+        // Re-uploads of a recording already downloaded, found by fingerprinting
+        const loadDuplicates = () => getRecordingDuplicates().then(setDuplicates).catch(console.error)
+        loadDuplicates()
+        const unlistenDuplicates = listenToRecordingDuplicateFound(() => loadDuplicates())
+
+        getStorageProblem().then(setStorageProblem).catch(console.error)
+        const unlistenProblem = listenToStorageProblem(setStorageProblem)
+        const unlistenResolved = listenToStorageProblemResolved(() => setStorageProblem(null))
+
         // Listen for favorites updates from playlist modal
         const handleFavoritesUpdate = () => loadDownloads()
         window.addEventListener('favorites-updated', handleFavoritesUpdate)
@@ -67,6 +97,10 @@ export function DownloadsTab() {
         return () => {
             clearInterval(interval)
             unlisten.then(fn => fn())
+            unlistenStorage.then(fn => fn())
+            unlistenDuplicates.then(fn => fn())
+            unlistenProblem.then(fn => fn())
+            unlistenResolved.then(fn => fn())
             window.removeEventListener('favorites-updated', handleFavoritesUpdate)
         }
     }, [])
@@ -80,9 +114,37 @@ export function DownloadsTab() {
         }
     }
 
+    const handleTogglePriority = async (download: DownloadProgress) => {
+        try {
+            await setDownloadPriority(download.video_id, download.priority === 'High' ? 'Normal' : 'High')
+            await loadDownloads()
+        } catch (error) {
+            console.error('Failed to change download priority:', error)
+        }
+    }
+
+    const handleRetryAllFailed = async () => {
+        try {
+            await retryAllFailed()
+            await loadDownloads()
+        } catch (error) {
+            console.error('Failed to retry downloads:', error)
+        }
+    }
+
+    const handleRedownload = async (videoId: string) => {
+        try {
+            await redownloadTrack(videoId)
+            await loadDownloads()
+        } catch (error) {
+            console.error('Failed to redownload track:', error)
+        }
+    }
+
     const handleDeleteDownload = async (videoId: string) => {
         try {
             await deleteDownload(videoId)
+            setDuplicates(duplicates.filter((d) => d.video_id !== videoId))
             await loadDownloads()
         } catch (error) {
             console.error('Failed to delete download:', error)
@@ -100,10 +162,9 @@ export function DownloadsTab() {
     }
 
     const handleDeleteSelected = async () => {
+        if (!confirm(`Delete ${selectedTracks.size} downloaded track(s)?`)) return
         try {
-            await Promise.all(
-                Array.from(selectedTracks).map(id => deleteDownload(id))
-            )
+            await deleteDownloads(Array.from(selectedTracks))
             setSelectedTracks(new Set())
             setIsSelectionMode(false)
             await loadDownloads()
@@ -126,7 +187,7 @@ export function DownloadsTab() {
             .reduce((sum, t) => sum + t.file_size, 0)
     }
 
-    const hasDownloads = activeDownloads.length > 0 || downloadedTracks.length > 0
+    const hasDownloads = activeDownloads.length > 0 || downloadedTracks.length > 0 || failedDownloads.length > 0
 
     return (
         <div className="flex flex-col h-full bg-background">
@@ -178,6 +239,15 @@ export function DownloadsTab() {
                 }
             />
 
+            {storageProblem && (
+                <div className="px-3 py-2 border-b border-macos-separator flex-shrink-0">
+                    <div className="text-[13px] font-semibold text-macos-red">
+                        Downloads paused: {storageProblem.cause}
+                    </div>
+                    <div className="text-[11px] text-muted-foreground">{storageProblem.hint}</div>
+                </div>
+            )}
+
             {/* Downloads Content */}
             <div className="flex-1 overflow-y-auto">
                 {isLoading ? null : !hasDownloads ? (
@@ -206,12 +276,73 @@ export function DownloadsTab() {
                                             key={download.video_id}
                                             download={download}
                                             onCancel={handleCancelDownload}
+                                            onTogglePriority={handleTogglePriority}
                                         />
                                     ))}
                                 </div>
                             </>
                         )}
 
+                        {/* Failed Downloads Section */}
+                        {failedDownloads.length > 0 && (
+                            <>
+                                <div className="px-3 py-2 mt-4 flex items-center justify-between">
+                                    <h3 className="text-[13px] font-semibold text-foreground">
+                                        Failed ({failedDownloads.length})
+                                    </h3>
+                                    <button
+                                        onClick={handleRetryAllFailed}
+                                        className="text-[13px] text-[var(--macos-blue)] hover:opacity-80"
+                                    >
+                                        Retry All
+                                    </button>
+                                </div>
+                                <div className="space-y-1">
+                                    {failedDownloads.map((failed) => (
+                                        <div key={failed.track.id} className="px-3 py-2">
+                                            <div className="text-[13px] font-semibold text-foreground truncate">
+                                                {failed.track.title}
+                                            </div>
+                                            <div className="text-[11px] text-macos-red truncate">
+                                                {failed.kind === 'Network' ? 'Network error, retrying when back online' : failed.error}
+                                            </div>
+                                        </div>
+                                    ))}
+                                </div>
+                            </>
+                        )}
+
+                        {/* Same Recording Section */}
+                        {duplicates.length > 0 && (
+                            <>
+                                <div className="px-3 py-2 mt-4">
+                                    <h3 className="text-[13px] font-semibold text-foreground">
+                                        Same Recording ({duplicates.length})
+                                    </h3>
+                                </div>
+                                <div className="space-y-1">
+                                    {duplicates.map((duplicate) => (
+                                        <div key={duplicate.video_id} className="flex items-center gap-2 px-3 py-2">
+                                            <div className="flex-1 min-w-0">
+                                                <div className="text-[13px] font-semibold text-foreground truncate">
+                                                    {duplicate.title}
+                                                </div>
+                                                <div className="text-[11px] text-muted-foreground truncate">
+                                                    Another upload of {duplicate.original_title}
+                                                </div>
+                                            </div>
+                                            <button
+                                                onClick={() => handleDeleteDownload(duplicate.video_id)}
+                                                className="text-[13px] text-macos-red hover:opacity-80 flex-shrink-0"
+                                            >
+                                                Delete
+                                            </button>
+                                        </div>
+                                    ))}
+                                </div>
+                            </>
+                        )}
+
                         {/* Downloaded Tracks Section */}
                         {downloadedTracks.length > 0 && (
                             <>
@@ -249,6 +380,19 @@ export function DownloadsTab() {
                                                             : undefined
                                                     }
                                                 />
+                                                {track.is_truncated && (
+                                                    <div className="flex items-center gap-2 px-3 pb-2 text-[11px]">
+                                                        <span className="text-macos-red truncate">
+                                                            Incomplete: the file is shorter than the track
+                                                        </span>
+                                                        <button
+                                                            onClick={() => handleRedownload(track.video_info.id)}
+                                                            className="text-[var(--macos-blue)] hover:opacity-80 flex-shrink-0"
+                                                        >
+                                                            Redownload
+                                                        </button>
+                                                    </div>
+                                                )}
                                             </div>
                                         </div>
                                     ))}
@@ -265,9 +409,10 @@ export function DownloadsTab() {
 interface ActiveDownloadRowProps {
     download: DownloadProgress
     onCancel: (videoId: string) => void
+    onTogglePriority: (download: DownloadProgress) => void
 }
 
-function ActiveDownloadRow({ download, onCancel }: ActiveDownloadRowProps) {
+function ActiveDownloadRow({ download, onCancel, onTogglePriority }: ActiveDownloadRowProps) {
     const percentage = Math.round(download.progress * 100)
 
     return (
@@ -323,14 +468,24 @@ function ActiveDownloadRow({ download, onCancel }: ActiveDownloadRowProps) {
                             <span>ETA: {download.eta}</span>
                         </>
                     )}
+                    {download.throttled && (
+                        <>
+                            <span>•</span>
+                            <span>Slowed for playback</span>
+                        </>
+                    )}
                 </div>
-                {download.error && (
-                    <div className="text-[11px] text-macos-red mt-1">
-                        Error: {download.error}
-                    </div>
-                )}
             </div>
 
+            {/* Priority Button - high priority keeps full speed while a track streams */}
+            <button
+                onClick={() => onTogglePriority(download)}
+                className="w-6 h-6 flex items-center justify-center hover-macos-button rounded"
+                title={download.priority === 'High' ? 'Let playback go first' : 'Download at full speed'}
+            >
+                <Zap className={`w-4 h-4 ${download.priority === 'High' ? 'text-[var(--macos-blue)] fill-[var(--macos-blue)]' : 'text-muted-foreground'}`} />
+            </button>
+
             {/* Cancel Button */}
             <button
                 onClick={() => onCancel(download.video_id)}
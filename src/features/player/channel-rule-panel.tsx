@@ -0,0 +1,84 @@
+import { useState, useEffect } from 'react'
+import { getChannelRule, saveChannelRule, getAllPlaylists, type ChannelRule, type Playlist } from '@/lib/tauri'
+
+const SPEED_OPTIONS = [0.75, 1.0, 1.25, 1.5, 1.75, 2.0]
+
+interface ChannelRulePanelProps {
+    uploader: string
+}
+
+// Defaults for every track from this uploader, saved as soon as they're changed
+export function ChannelRulePanel({ uploader }: ChannelRulePanelProps) {
+    const [rule, setRule] = useState<ChannelRule>({ uploader, playback_rate: null, auto_download: false, playlist_id: null })
+    const [playlists, setPlaylists] = useState<Playlist[]>([])
+    const [error, setError] = useState<string | null>(null)
+
+    useEffect(() => {
+        setError(null)
+        getChannelRule(uploader)
+            .then((saved) => setRule(saved ?? { uploader, playback_rate: null, auto_download: false, playlist_id: null }))
+            .catch((error) => console.error('Failed to load channel rule:', error))
+    }, [uploader])
+
+    useEffect(() => {
+        getAllPlaylists()
+            .then((all) => setPlaylists(all.filter((p) => !p.is_generated)))
+            .catch((error) => console.error('Failed to load playlists:', error))
+    }, [])
+
+    const handleChange = async (changed: ChannelRule) => {
+        setRule(changed)
+        setError(null)
+        try {
+            await saveChannelRule(changed)
+        } catch (error: any) {
+            setError(String(error))
+            console.error('Failed to save channel rule:', error)
+        }
+    }
+
+    return (
+        <div className="mt-2 flex flex-col gap-2 text-[12px] text-foreground">
+            <p className="text-[11px] text-muted-foreground truncate">For every track from {uploader}</p>
+            <label className="flex items-center justify-between gap-2">
+                Speed
+                <select
+                    value={rule.playback_rate ?? ''}
+                    onChange={(e) => handleChange({ ...rule, playback_rate: e.target.value ? Number(e.target.value) : null })}
+                    className="px-2 py-1 bg-secondary rounded-md text-[12px] text-foreground border-none outline-none"
+                >
+                    <option value="">Usual speed</option>
+                    {SPEED_OPTIONS.map((speed) => (
+                        <option key={speed} value={speed}>
+                            {speed.toFixed(2)}x
+                        </option>
+                    ))}
+                </select>
+            </label>
+            <label className="flex items-center justify-between gap-2">
+                Add to playlist
+                <select
+                    value={rule.playlist_id ?? ''}
+                    onChange={(e) => handleChange({ ...rule, playlist_id: e.target.value || null })}
+                    className="max-w-[180px] px-2 py-1 bg-secondary rounded-md text-[12px] text-foreground border-none outline-none"
+                >
+                    <option value="">None</option>
+                    {playlists.map((playlist) => (
+                        <option key={playlist.id} value={playlist.id}>
+                            {playlist.name}
+                        </option>
+                    ))}
+                </select>
+            </label>
+            <label className="flex items-center gap-2">
+                <input
+                    type="checkbox"
+                    checked={rule.auto_download}
+                    onChange={(e) => handleChange({ ...rule, auto_download: e.target.checked })}
+                />
+                Download when played
+            </label>
+            {error && <p className="text-[11px] text-macos-red">{error}</p>}
+        </div>
+    )
+}
@@ -1,7 +1,8 @@
 import { useState, useEffect } from 'react'
-import { Play, Pause, SkipBack, SkipForward, ChevronDown, MinusCircle, PlusCircle, Loader2 } from 'lucide-react'
-import { togglePlayPause, playPrevious, playNext, seekTo, setPlaybackSpeed, formatTime, type AudioState } from '@/lib/tauri'
+import { Play, Pause, SkipBack, SkipForward, ChevronDown, MinusCircle, PlusCircle, Loader2, Scissors, UserCog } from 'lucide-react'
+import { togglePlayPause, playPrevious, playNext, seekTo, setPlaybackSpeed, setTrackTrim, getDominantColors, formatTime, displayTitle, displayUploader, type AudioState } from '@/lib/tauri'
 import { ScrollingText } from '@/components/scrolling-text'
+import { ChannelRulePanel } from '@/features/player/channel-rule-panel'
 
 interface ExpandedPlayerProps {
     audioState: AudioState
@@ -11,12 +12,34 @@ interface ExpandedPlayerProps {
 export function ExpandedPlayer({ audioState, onCollapse }: ExpandedPlayerProps) {
     const [position, setPosition] = useState(audioState.current_position)
     const [playbackRate, setPlaybackRate] = useState(audioState.playback_rate)
+    const [accentColor, setAccentColor] = useState<string | null>(null)
+    const [showTrim, setShowTrim] = useState(false)
+    const [trimError, setTrimError] = useState<string | null>(null)
+    const [showChannelRule, setShowChannelRule] = useState(false)
 
     useEffect(() => {
         setPosition(audioState.current_position)
         setPlaybackRate(audioState.playback_rate)
     }, [audioState])
 
+    // Tint the controls with the current track's thumbnail colors
+    const trackId = audioState.current_track?.id
+    useEffect(() => {
+        setAccentColor(null)
+        if (!trackId) return
+
+        let cancelled = false
+        getDominantColors(trackId)
+            .then((colors) => {
+                if (!cancelled) setAccentColor(colors[0] ?? null)
+            })
+            .catch((error) => console.error('Failed to get track colors:', error))
+
+        return () => {
+            cancelled = true
+        }
+    }, [trackId])
+
     const handleTogglePlayPause = async () => {
         try {
             await togglePlayPause()
@@ -61,7 +84,24 @@ export function ExpandedPlayer({ audioState, onCollapse }: ExpandedPlayerProps)
         }
     }
 
+    // Trims are set from the playhead: the intro ends here, or the outro starts here
+    const handleTrim = async (start: number, end: number | null) => {
+        const track = audioState.current_track
+        if (!track) return
+
+        setTrimError(null)
+        try {
+            await setTrackTrim(track.id, start, end)
+        } catch (error: any) {
+            setTrimError(String(error))
+            console.error('Failed to trim track:', error)
+        }
+    }
+
     const progress = audioState.duration > 0 ? (position / audioState.duration) * 100 : 0
+    const buffered = audioState.duration > 0 ? Math.min((audioState.buffered_until / audioState.duration) * 100, 100) : 0
+    const isWaiting = audioState.is_loading || audioState.is_buffering
+    const accent = accentColor ?? 'var(--macos-blue)'
 
     return (
         <div className="border-t border-macos-separator bg-card flex-shrink-0 px-4 py-4">
@@ -73,14 +113,37 @@ export function ExpandedPlayer({ audioState, onCollapse }: ExpandedPlayerProps)
                         <div className="flex-1 min-w-0">
                             <div className="mb-0.5">
                                 <ScrollingText
-                                    text={audioState.current_track.title}
+                                    text={displayTitle(audioState.current_track)}
                                     className="text-[15px] font-semibold text-foreground"
                                     speed={50}
                                 />
                             </div>
-                            <p className="text-[13px] text-muted-foreground truncate">
-                                {audioState.current_track.uploader}
-                            </p>
+                            <div className="flex items-center gap-1 min-w-0">
+                                <p className="text-[13px] text-muted-foreground truncate">
+                                    {displayUploader(audioState.current_track)}
+                                </p>
+                                <button
+                                    onClick={() => setShowChannelRule(!showChannelRule)}
+                                    className={`w-5 h-5 flex items-center justify-center hover-macos-button rounded flex-shrink-0 ${
+                                        showChannelRule ? 'text-[var(--macos-blue)]' : 'text-muted-foreground'
+                                    }`}
+                                    aria-label="Channel rule"
+                                    title="Defaults for this channel"
+                                >
+                                    <UserCog className="w-3.5 h-3.5" />
+                                </button>
+                            </div>
+                            {showChannelRule && <ChannelRulePanel uploader={audioState.current_track.uploader} />}
+                            {audioState.network_offline && (
+                                <p className="text-[11px] text-macos-red mt-0.5">
+                                    Offline, waiting for the network to return
+                                </p>
+                            )}
+                            {audioState.output_unavailable && (
+                                <p className="text-[11px] text-macos-red mt-0.5">
+                                    Audio device busy, waiting for it to free up
+                                </p>
+                            )}
                         </div>
                         <button
                             onClick={onCollapse}
@@ -106,11 +169,12 @@ export function ExpandedPlayer({ audioState, onCollapse }: ExpandedPlayerProps)
                     {/* Play/Pause */}
                     <button
                         onClick={handleTogglePlayPause}
-                        className="w-12 h-12 flex items-center justify-center rounded-full bg-[var(--macos-blue)] hover:opacity-90 transition-opacity"
-                        aria-label={audioState.is_loading ? 'Loading...' : audioState.is_playing ? 'Pause' : 'Play'}
+                        className="w-12 h-12 flex items-center justify-center rounded-full hover:opacity-90 transition-opacity"
+                        style={{ backgroundColor: accent }}
+                        aria-label={isWaiting ? 'Loading...' : audioState.is_playing ? 'Pause' : 'Play'}
                         disabled={audioState.is_loading}
                     >
-                        {audioState.is_loading ? (
+                        {isWaiting ? (
                             <Loader2 className="w-6 h-6 text-white animate-spin" />
                         ) : audioState.is_playing ? (
                             <Pause className="w-6 h-6 text-white fill-white" />
@@ -133,7 +197,7 @@ export function ExpandedPlayer({ audioState, onCollapse }: ExpandedPlayerProps)
                 <div className="mb-1">
                     <input
                         type="range"
-                        min="0"
+                        min={audioState.trim_start}
                         max={audioState.duration || 100}
                         value={position}
                         onChange={handleSeek}
@@ -147,7 +211,7 @@ export function ExpandedPlayer({ audioState, onCollapse }: ExpandedPlayerProps)
                                  [&::-webkit-slider-thumb]:shadow-md
                                  disabled:opacity-50 disabled:cursor-not-allowed"
                         style={{
-                            background: `linear-gradient(to right, var(--macos-blue) ${progress}%, rgba(255,255,255,0.1) ${progress}%)`
+                            background: `linear-gradient(to right, ${accent} ${progress}%, rgba(255,255,255,0.25) ${progress}%, rgba(255,255,255,0.25) ${buffered}%, rgba(255,255,255,0.1) ${buffered}%)`
                         }}
                     />
                 </div>
@@ -179,8 +243,53 @@ export function ExpandedPlayer({ audioState, onCollapse }: ExpandedPlayerProps)
                         </button>
                     </div>
 
-                    <span>{formatTime(audioState.duration)}</span>
+                    <div className="flex items-center gap-1">
+                        <button
+                            onClick={() => setShowTrim(!showTrim)}
+                            disabled={!audioState.current_track}
+                            className={`w-6 h-6 flex items-center justify-center hover-macos-button rounded disabled:opacity-30 ${
+                                showTrim || audioState.trim_start > 0 || audioState.trim_end !== null
+                                    ? 'text-[var(--macos-blue)]'
+                                    : 'text-muted-foreground'
+                            }`}
+                            aria-label="Trim intro and outro"
+                            title="Trim intro and outro"
+                        >
+                            <Scissors className="w-3.5 h-3.5" />
+                        </button>
+                        <span>{formatTime(audioState.duration)}</span>
+                    </div>
                 </div>
+
+                {/* Trim the silent intro and outro of this track */}
+                {showTrim && (
+                    <div className="mt-2">
+                        <div className="flex items-center gap-2 text-[12px]">
+                            <button
+                                onClick={() => handleTrim(position, audioState.trim_end)}
+                                className="px-2 py-1 rounded-md hover-macos-button text-[var(--macos-blue)]"
+                            >
+                                Start Here
+                            </button>
+                            <button
+                                onClick={() => handleTrim(audioState.trim_start, position)}
+                                className="px-2 py-1 rounded-md hover-macos-button text-[var(--macos-blue)]"
+                            >
+                                End Here
+                            </button>
+                            <button
+                                onClick={() => handleTrim(0, null)}
+                                disabled={audioState.trim_start === 0 && audioState.trim_end === null}
+                                className="px-2 py-1 rounded-md hover-macos-button text-muted-foreground disabled:opacity-30"
+                            >
+                                Clear
+                            </button>
+                        </div>
+                        {trimError && (
+                            <p className="text-[11px] text-macos-red mt-1">{trimError}</p>
+                        )}
+                    </div>
+                )}
             </div>
         </div>
     )
@@ -0,0 +1,50 @@
+import { useState, useEffect } from 'react'
+import { FastForward } from 'lucide-react'
+import { acceptIntroSkip, dismissIntroSkip, listenToIntroSkipSuggested, formatTime, type IntroSkipSuggestion } from '@/lib/tauri'
+
+// Offered when plays of the current track keep skipping to the same spot
+export function IntroSkipPrompt() {
+    const [suggestion, setSuggestion] = useState<IntroSkipSuggestion | null>(null)
+
+    useEffect(() => {
+        const unlisten = listenToIntroSkipSuggested(setSuggestion)
+        return () => {
+            unlisten.then((fn) => fn())
+        }
+    }, [])
+
+    if (!suggestion) return null
+
+    const handleAnswer = async (accept: boolean) => {
+        try {
+            await (accept
+                ? acceptIntroSkip(suggestion.video_id, suggestion.start)
+                : dismissIntroSkip(suggestion.video_id))
+        } catch (error) {
+            console.error('Failed to answer intro skip:', error)
+        } finally {
+            setSuggestion(null)
+        }
+    }
+
+    return (
+        <div className="flex items-center gap-2 px-4 py-2 border-t border-macos-separator bg-card flex-shrink-0">
+            <FastForward className="w-4 h-4 text-muted-foreground flex-shrink-0" />
+            <p className="flex-1 min-w-0 text-[12px] text-foreground truncate">
+                Always start this track at {formatTime(suggestion.start)}?
+            </p>
+            <button
+                onClick={() => handleAnswer(true)}
+                className="px-3 py-1.5 text-[13px] rounded-md hover-macos-button text-[var(--macos-blue)]"
+            >
+                Skip Intro
+            </button>
+            <button
+                onClick={() => handleAnswer(false)}
+                className="px-3 py-1.5 text-[13px] rounded-md hover-macos-button text-muted-foreground"
+            >
+                No Thanks
+            </button>
+        </div>
+    )
+}
@@ -1,5 +1,5 @@
 import { Volume2, VolumeX, SkipBack, SkipForward, Play, Pause, ChevronDown, Loader2 } from 'lucide-react'
-import { togglePlayPause, playPrevious, playNext, type YTVideoInfo } from '@/lib/tauri'
+import { togglePlayPause, playPrevious, playNext, displayTitle, displayUploader, type YTVideoInfo } from '@/lib/tauri'
 import { ScrollingText } from '@/components/scrolling-text'
 
 interface MiniPlayerProps {
@@ -49,7 +49,7 @@ export function MiniPlayer({ track, isPlaying, isLoading, onExpand }: MiniPlayer
             {/* Track Info - Single line with separator */}
             <div className="flex-1 min-w-0 text-[13px] text-foreground">
                 <ScrollingText
-                    text={`${track.title} • ${track.uploader}`}
+                    text={`${displayTitle(track)} • ${displayUploader(track)}`}
                     className="font-medium"
                     speed={50}
                 />
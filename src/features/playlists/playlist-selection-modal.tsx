@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react'
 import { X, Plus, Check, Heart, Music } from 'lucide-react'
-import { getAllPlaylists, getPlaylistTracks, addTrackToPlaylist, createPlaylist, type Playlist, type YTVideoInfo } from '@/lib/tauri'
+import { getAllPlaylists, getPlaylistTracks, addTrackToPlaylist, createPlaylist, FAVORITES_ID, type Playlist, type YTVideoInfo } from '@/lib/tauri'
 
 interface PlaylistWithData extends Playlist {
     trackCount: number
@@ -19,7 +19,8 @@ export function PlaylistSelectionModal({ track, onClose }: PlaylistSelectionModa
 
     const loadPlaylists = async () => {
         try {
-            const allPlaylists = await getAllPlaylists()
+            // Daily mixes are rebuilt every day, tracks added to them wouldn't stay
+            const allPlaylists = (await getAllPlaylists()).filter(p => !p.is_generated)
 
             // Load track data for each playlist
             const playlistsWithData = await Promise.all(
@@ -99,11 +100,11 @@ export function PlaylistSelectionModal({ track, onClose }: PlaylistSelectionModa
                             }`}
                         >
                             <div className={`w-8 h-8 rounded-lg flex items-center justify-center flex-shrink-0 ${
-                                playlist.is_system_playlist
+                                playlist.id === FAVORITES_ID
                                     ? 'bg-macos-red/10 text-macos-red'
                                     : 'bg-[var(--macos-blue)]/10 text-[var(--macos-blue)]'
                             }`}>
-                                {playlist.is_system_playlist ? (
+                                {playlist.id === FAVORITES_ID ? (
                                     <Heart className="w-4 h-4" />
                                 ) : (
                                     <Music className="w-4 h-4" />
@@ -1,17 +1,28 @@
 import { useState, useEffect } from 'react'
-import { Plus, Heart, Music, ArrowLeft, Play, ChevronRight } from 'lucide-react'
-import { getAllPlaylists, getPlaylistTracks, createPlaylist, removeTrackFromPlaylist, playPlaylist, type Playlist, type Track } from '@/lib/tauri'
+import { Plus, Heart, Music, ArrowLeft, Play, ChevronRight, Pin, PinOff, CalendarDays, Search } from 'lucide-react'
+import { getAllPlaylists, getPlaylistTracks, filterPlaylistTracks, createPlaylist, removeTrackFromPlaylist, playPlaylist, getPinnedPlaylists, setPlaylistPinned, listenToPlaylistCoversUpdated, listenToDailyMixesUpdated, listenToMetadataPrefetched, listenToTrackDurationFixed, prefetchMetadata, cancelMetadataPrefetch, FAVORITES_ID, type Playlist, type Track } from '@/lib/tauri'
 import { TrackItem } from '@/components/track-item'
 import { TabHeader } from '@/components/tab-header'
+import { WeeklyRecap } from '@/features/recap/weekly-recap'
+
+// Pinned playlists show up in the Windows taskbar jump list
+const SUPPORTS_JUMP_LIST = navigator.userAgent.includes('Windows')
 
 export function PlaylistsTab() {
     const [playlists, setPlaylists] = useState<Playlist[]>([])
     const [selectedPlaylist, setSelectedPlaylist] = useState<Playlist | null>(null)
     const [playlistTracks, setPlaylistTracks] = useState<Track[]>([])
+    // Tracks matching the filter, null when there's no filter
+    const [trackFilter, setTrackFilter] = useState('')
+    const [filteredTracks, setFilteredTracks] = useState<Track[] | null>(null)
+    // Thumbnails fetched by the backend a few at a time, null where fetching failed
+    const [thumbnails, setThumbnails] = useState<Record<string, string | null>>({})
     const [showCreateModal, setShowCreateModal] = useState(false)
     const [newPlaylistName, setNewPlaylistName] = useState('')
     const [isLoading, setIsLoading] = useState(true)
     const [isLoadingTracks, setIsLoadingTracks] = useState(false)
+    const [pinnedIds, setPinnedIds] = useState<string[]>([])
+    const [showRecap, setShowRecap] = useState(false)
 
     const loadPlaylists = async () => {
         try {
@@ -34,14 +45,63 @@ export function PlaylistsTab() {
 
     useEffect(() => {
         loadPlaylists()
+        if (SUPPORTS_JUMP_LIST) {
+            getPinnedPlaylists().then(setPinnedIds).catch(() => setPinnedIds([]))
+        }
+
+        // Covers are built in the background the first time a playlist is listed
+        const unlisten = listenToPlaylistCoversUpdated(() => {
+            loadPlaylists()
+        })
+        const unlistenMixes = listenToDailyMixesUpdated(() => {
+            loadPlaylists()
+        })
+        const unlistenPrefetch = listenToMetadataPrefetched(({ video_id, thumbnail, duration }) => {
+            setThumbnails((current) => ({ ...current, [video_id]: thumbnail }))
+            if (duration !== null) {
+                setPlaylistTracks((tracks) => tracks.map((t) => t.id === video_id ? { ...t, duration } : t))
+            }
+        })
+
+        const unlistenDurations = listenToTrackDurationFixed(({ video_id, duration }) => {
+            setPlaylistTracks((tracks) => tracks.map((t) => t.id === video_id ? { ...t, duration } : t))
+            setFilteredTracks((tracks) => tracks && tracks.map((t) => t.id === video_id ? { ...t, duration } : t))
+        })
+
+        return () => {
+            unlisten.then(fn => fn())
+            unlistenMixes.then(fn => fn())
+            unlistenPrefetch.then(fn => fn())
+            unlistenDurations.then(fn => fn())
+            cancelMetadataPrefetch().catch(() => {})
+        }
     }, [])
 
+    // Big playlists are filtered in the database instead of here
+    useEffect(() => {
+        if (!selectedPlaylist || !trackFilter.trim()) {
+            setFilteredTracks(null)
+            return
+        }
+        const timeout = setTimeout(() => {
+            filterPlaylistTracks(selectedPlaylist.id, trackFilter)
+                .then(setFilteredTracks)
+                .catch((error) => console.error('Failed to filter playlist:', error))
+        }, 200)
+        return () => clearTimeout(timeout)
+    }, [selectedPlaylist, trackFilter, playlistTracks.length])
+
     const handleSelectPlaylist = async (playlist: Playlist) => {
         setSelectedPlaylist(playlist)
+        setTrackFilter('')
         setIsLoadingTracks(true)
         try {
             const tracks = await getPlaylistTracks(playlist.id)
             setPlaylistTracks(tracks)
+            setThumbnails({})
+            prefetchMetadata(tracks.map((t) => t.id), 'Visible').catch((error) => {
+                console.error('Failed to prefetch track metadata:', error)
+            })
         } catch (error) {
             console.error('Failed to load playlist tracks:', error)
         } finally {
@@ -52,6 +112,7 @@ export function PlaylistsTab() {
     const handleBackToPlaylists = () => {
         setSelectedPlaylist(null)
         setPlaylistTracks([])
+        cancelMetadataPrefetch().catch(() => {})
         loadPlaylists()
     }
 
@@ -80,6 +141,18 @@ export function PlaylistsTab() {
         }
     }
 
+    const handleTogglePinned = async () => {
+        if (!selectedPlaylist) return
+
+        const pinned = !pinnedIds.includes(selectedPlaylist.id)
+        try {
+            await setPlaylistPinned(selectedPlaylist.id, pinned)
+            setPinnedIds(await getPinnedPlaylists())
+        } catch (error) {
+            console.error('Failed to pin playlist:', error)
+        }
+    }
+
     const handlePlayPlaylist = async () => {
         if (!selectedPlaylist) return
 
@@ -90,6 +163,10 @@ export function PlaylistsTab() {
         }
     }
 
+    if (showRecap) {
+        return <WeeklyRecap onBack={() => setShowRecap(false)} />
+    }
+
     // List View
     if (!selectedPlaylist) {
         return (
@@ -97,13 +174,22 @@ export function PlaylistsTab() {
                 <TabHeader
                     title="Playlists"
                     actions={
-                        <button
-                            onClick={() => setShowCreateModal(true)}
-                            className="w-8 h-8 flex items-center justify-center rounded-full hover-macos-button"
-                            title="Create Playlist"
-                        >
-                            <Plus className="w-5 h-5 text-[var(--macos-blue)]" />
-                        </button>
+                        <>
+                            <button
+                                onClick={() => setShowRecap(true)}
+                                className="w-8 h-8 flex items-center justify-center rounded-full hover-macos-button"
+                                title="Your Week"
+                            >
+                                <CalendarDays className="w-4 h-4 text-[var(--macos-blue)]" />
+                            </button>
+                            <button
+                                onClick={() => setShowCreateModal(true)}
+                                className="w-8 h-8 flex items-center justify-center rounded-full hover-macos-button"
+                                title="Create Playlist"
+                            >
+                                <Plus className="w-5 h-5 text-[var(--macos-blue)]" />
+                            </button>
+                        </>
                     }
                 />
 
@@ -126,12 +212,14 @@ export function PlaylistsTab() {
                                 onClick={() => handleSelectPlaylist(playlist)}
                                 className="w-full flex items-center gap-3 px-4 py-3 hover-macos-button transition-colors"
                             >
-                                <div className={`w-10 h-10 rounded-lg flex items-center justify-center flex-shrink-0 ${
-                                    playlist.is_system_playlist
+                                <div className={`w-10 h-10 rounded-lg flex items-center justify-center flex-shrink-0 overflow-hidden ${
+                                    playlist.id === FAVORITES_ID
                                         ? 'bg-[var(--macos-red)]/10'
                                         : 'bg-[var(--macos-blue)]/10'
                                 }`}>
-                                    {playlist.is_system_playlist ? (
+                                    {playlist.cover && playlist.id !== FAVORITES_ID ? (
+                                        <img src={playlist.cover} alt="" className="w-full h-full object-cover" />
+                                    ) : playlist.id === FAVORITES_ID ? (
                                         <Heart className="w-5 h-5 text-macos-red fill-[var(--macos-red)]" />
                                     ) : (
                                         <Music className="w-5 h-5 text-[var(--macos-blue)]" />
@@ -210,6 +298,19 @@ export function PlaylistsTab() {
                             {playlistTracks.length} track{playlistTracks.length === 1 ? '' : 's'}
                         </div>
                     </div>
+                    {SUPPORTS_JUMP_LIST && (
+                        <button
+                            onClick={handleTogglePinned}
+                            className="w-8 h-8 flex items-center justify-center rounded-full hover-macos-button"
+                            title={pinnedIds.includes(selectedPlaylist.id) ? 'Unpin from Jump List' : 'Pin to Jump List'}
+                        >
+                            {pinnedIds.includes(selectedPlaylist.id) ? (
+                                <PinOff className="w-4 h-4 text-foreground" />
+                            ) : (
+                                <Pin className="w-4 h-4 text-foreground" />
+                            )}
+                        </button>
+                    )}
                     {playlistTracks.length > 0 && (
                         <button
                             onClick={handlePlayPlaylist}
@@ -222,6 +323,19 @@ export function PlaylistsTab() {
                 </div>
             </div>
 
+            {playlistTracks.length > 0 && (
+                <div className="px-4 py-2 border-b border-macos-separator flex items-center gap-2">
+                    <Search className="w-4 h-4 text-muted-foreground" />
+                    <input
+                        type="text"
+                        value={trackFilter}
+                        onChange={(e) => setTrackFilter(e.target.value)}
+                        placeholder="Filter this playlist"
+                        className="flex-1 bg-transparent text-[13px] text-foreground placeholder:text-muted-foreground border-none outline-none"
+                    />
+                </div>
+            )}
+
             {/* Tracks */}
             <div className="flex-1 overflow-y-auto">
                 {isLoadingTracks ? null : playlistTracks.length === 0 ? (
@@ -236,11 +350,16 @@ export function PlaylistsTab() {
                     </div>
                 ) : (
                     <div className="py-2">
-                        {playlistTracks.map((track) => (
+                        {filteredTracks?.length === 0 && (
+                            <p className="px-4 py-2 text-[13px] text-muted-foreground">No tracks match "{trackFilter}"</p>
+                        )}
+                        {(filteredTracks ?? playlistTracks).map((track) => (
                             <TrackItem
                                 key={track.id}
                                 track={track}
                                 context="playlist"
+                                playlistId={selectedPlaylist?.id}
+                                thumbnail={track.id in thumbnails ? (thumbnails[track.id] ?? undefined) : null}
                                 onRemove={() => handleRemoveTrack(track.id)}
                             />
                         ))}
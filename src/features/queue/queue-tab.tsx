@@ -1,9 +1,12 @@
-import { useState, useEffect } from 'react'
-import { Shuffle, Repeat, Repeat1, ListMusic, GripVertical } from 'lucide-react'
-import { getQueue, getQueueInfo, toggleShuffle, cycleRepeatMode, reorderQueue, type YTVideoInfo, type RepeatMode } from '@/lib/tauri'
+import { useState, useEffect, useRef } from 'react'
+import { Shuffle, Repeat, Repeat1, ListMusic, GripVertical, Layers, X, Search } from 'lucide-react'
+import { getQueue, filterQueue, getQueueInfo, toggleShuffle, cycleRepeatMode, setRepeatCount, reorderQueue, getSavedSession, restoreSession, getSessions, saveSession, loadSession, deleteSession, playUrlList, getGuestRequests, approveGuestRequest, rejectGuestRequest, listenToGuestRequests, type GuestRequest, type QueueMatch, type YTVideoInfo, type RepeatMode, type SavedSession, type SessionRestoreMode, type QueueSessionSummary } from '@/lib/tauri'
 import { TrackItem } from '@/components/track-item'
 import { TabHeader } from '@/components/tab-header'
 
+const repeatLabel = (mode: RepeatMode) =>
+    typeof mode === 'string' ? `Repeat ${mode}` : `Repeat ${mode.Count} more ${mode.Count === 1 ? 'time' : 'times'}`
+
 export function QueueTab() {
     const [queue, setQueue] = useState<YTVideoInfo[]>([])
     const [queueInfo, setQueueInfo] = useState('')
@@ -11,6 +14,21 @@ export function QueueTab() {
     const [repeatMode, setRepeatMode] = useState<RepeatMode>('Off')
     const [draggedIndex, setDraggedIndex] = useState<number | null>(null)
     const [isLoading, setIsLoading] = useState(true)
+    const [savedSession, setSavedSession] = useState<SavedSession | null>(null)
+    const [showUrlList, setShowUrlList] = useState(false)
+    const [urlList, setUrlList] = useState('')
+    const [urlListError, setUrlListError] = useState<string | null>(null)
+    const [guestRequests, setGuestRequests] = useState<GuestRequest[]>([])
+    const [showRepeatCount, setShowRepeatCount] = useState(false)
+    const [repeatCount, setRepeatCountInput] = useState(3)
+    const [showSessions, setShowSessions] = useState(false)
+    const [sessions, setSessions] = useState<QueueSessionSummary[]>([])
+    const [sessionName, setSessionName] = useState('')
+    const [sessionError, setSessionError] = useState<string | null>(null)
+    const [queueFilter, setQueueFilter] = useState('')
+    const [filteredQueue, setFilteredQueue] = useState<QueueMatch[] | null>(null)
+    // The refresh interval only sees the filter through this
+    const filterRef = useRef('')
 
     const loadQueue = async () => {
         try {
@@ -20,6 +38,8 @@ export function QueueTab() {
             ])
             setQueue(queueData)
             setQueueInfo(info)
+            const filter = filterRef.current.trim()
+            setFilteredQueue(filter ? await filterQueue(filter) : null)
         } catch (error) {
             console.error('Failed to load queue:', error)
         } finally {
@@ -29,12 +49,25 @@ export function QueueTab() {
 
     useEffect(() => {
         loadQueue()
+        getSavedSession().then(setSavedSession).catch(() => setSavedSession(null))
+
+        getGuestRequests().then(setGuestRequests).catch(() => setGuestRequests([]))
+        const unlistenGuestRequests = listenToGuestRequests(setGuestRequests)
 
         // Set up interval to refresh queue
         const interval = setInterval(loadQueue, 2000)
-        return () => clearInterval(interval)
+        return () => {
+            clearInterval(interval)
+            unlistenGuestRequests.then(fn => fn())
+        }
     }, [])
 
+    useEffect(() => {
+        filterRef.current = queueFilter
+        const timeout = setTimeout(loadQueue, 200)
+        return () => clearTimeout(timeout)
+    }, [queueFilter])
+
     const handleToggleShuffle = async () => {
         try {
             const enabled = await toggleShuffle()
@@ -54,6 +87,96 @@ export function QueueTab() {
         }
     }
 
+    const handleRestoreSession = async (mode: SessionRestoreMode) => {
+        try {
+            await restoreSession(mode)
+            setSavedSession(null)
+            await loadQueue()
+        } catch (error) {
+            console.error('Failed to restore session:', error)
+        }
+    }
+
+    const handleSetRepeatCount = async (count: number) => {
+        try {
+            setRepeatMode(await setRepeatCount(count))
+            setShowRepeatCount(false)
+            await loadQueue()
+        } catch (error) {
+            console.error('Failed to set repeat count:', error)
+        }
+    }
+
+    const handleToggleSessions = async () => {
+        setSessionError(null)
+        if (!showSessions) {
+            setSessions(await getSessions().catch(() => []))
+        }
+        setShowSessions(!showSessions)
+    }
+
+    const handleSaveSession = async () => {
+        setSessionError(null)
+        try {
+            await saveSession(sessionName)
+            setSessionName('')
+            setSessions(await getSessions())
+        } catch (error: any) {
+            setSessionError(String(error))
+            console.error('Failed to save session:', error)
+        }
+    }
+
+    const handleLoadSession = async (name: string) => {
+        setSessionError(null)
+        try {
+            await loadSession(name)
+            const session = sessions.find((s) => s.name === name)
+            if (session) {
+                setShuffleMode(session.shuffle_mode)
+                setRepeatMode(session.repeat_mode)
+            }
+            setShowSessions(false)
+            await loadQueue()
+        } catch (error: any) {
+            setSessionError(String(error))
+            console.error('Failed to load session:', error)
+        }
+    }
+
+    const handleDeleteSession = async (name: string) => {
+        setSessionError(null)
+        try {
+            await deleteSession(name)
+            setSessions(await getSessions())
+        } catch (error: any) {
+            setSessionError(String(error))
+            console.error('Failed to delete session:', error)
+        }
+    }
+
+    const handlePlayUrlList = async () => {
+        setUrlListError(null)
+        try {
+            await playUrlList(urlList)
+            setUrlList('')
+            setShowUrlList(false)
+            await loadQueue()
+        } catch (error: any) {
+            setUrlListError(String(error))
+            console.error('Failed to play URL list:', error)
+        }
+    }
+
+    const handleGuestRequest = async (id: string, approve: boolean) => {
+        try {
+            await (approve ? approveGuestRequest(id) : rejectGuestRequest(id))
+            await loadQueue()
+        } catch (error) {
+            console.error('Failed to answer guest request:', error)
+        }
+    }
+
     // Drag and drop handlers
     const handleDragStart = (index: number) => {
         setDraggedIndex(index)
@@ -95,6 +218,17 @@ export function QueueTab() {
                 subtitle={queue.length > 0 ? queueInfo : undefined}
                 actions={
                     <>
+                        <button
+                            onClick={handleToggleSessions}
+                            className={`w-8 h-8 flex items-center justify-center rounded-full hover-macos-button transition-colors ${
+                                showSessions
+                                    ? 'text-[var(--macos-blue)]'
+                                    : 'text-muted-foreground'
+                            }`}
+                            title="Sessions"
+                        >
+                            <Layers className="w-5 h-5" />
+                        </button>
                         <button
                             onClick={handleToggleShuffle}
                             className={`w-8 h-8 flex items-center justify-center rounded-full hover-macos-button transition-colors ${
@@ -108,14 +242,18 @@ export function QueueTab() {
                         </button>
                         <button
                             onClick={handleCycleRepeat}
+                            onContextMenu={(e) => {
+                                e.preventDefault()
+                                setShowRepeatCount(!showRepeatCount)
+                            }}
                             className={`w-8 h-8 flex items-center justify-center rounded-full hover-macos-button transition-colors ${
                                 repeatMode !== 'Off'
                                     ? 'text-[var(--macos-blue)]'
                                     : 'text-muted-foreground'
                             }`}
-                            title={`Repeat ${repeatMode}`}
+                            title={`${repeatLabel(repeatMode)} (right-click to repeat a number of times)`}
                         >
-                            {repeatMode === 'One' ? (
+                            {repeatMode === 'One' || typeof repeatMode !== 'string' ? (
                                 <Repeat1 className="w-5 h-5" />
                             ) : (
                                 <Repeat className="w-5 h-5" />
@@ -125,6 +263,122 @@ export function QueueTab() {
                 }
             />
 
+            {/* Repeat the current track a number of times, for practicing along */}
+            {showRepeatCount && (
+                <div className="px-4 py-2 border-b border-muted-foreground/20 flex items-center gap-2 text-[13px] text-foreground">
+                    Repeat this track
+                    <input
+                        type="number"
+                        min={1}
+                        max={99}
+                        value={repeatCount}
+                        onChange={(e) => setRepeatCountInput(Math.min(99, Math.max(1, Number(e.target.value) || 1)))}
+                        className="w-[56px] px-2 py-1 bg-secondary rounded-md text-[13px] text-foreground border-none outline-none"
+                    />
+                    more times
+                    <button
+                        onClick={() => handleSetRepeatCount(repeatCount)}
+                        className="ml-auto px-3 py-1.5 text-[13px] rounded-md hover-macos-button text-[var(--macos-blue)]"
+                    >
+                        Repeat
+                    </button>
+                    {typeof repeatMode !== 'string' && (
+                        <button
+                            onClick={() => handleSetRepeatCount(0)}
+                            className="px-3 py-1.5 text-[13px] rounded-md hover-macos-button text-muted-foreground"
+                        >
+                            Cancel
+                        </button>
+                    )}
+                </div>
+            )}
+
+            {/* Named queue sessions to switch between */}
+            {showSessions && (
+                <div className="px-4 py-2 border-b border-muted-foreground/20">
+                    <div className="flex items-center gap-2">
+                        <input
+                            type="text"
+                            value={sessionName}
+                            onChange={(e) => setSessionName(e.target.value)}
+                            onKeyDown={(e) => e.key === 'Enter' && handleSaveSession()}
+                            placeholder="Save this queue as..."
+                            className="flex-1 px-3 py-1.5 bg-secondary rounded-lg text-[13px] text-foreground border-none outline-none"
+                        />
+                        <button
+                            onClick={handleSaveSession}
+                            disabled={!sessionName.trim() || queue.length === 0}
+                            className="px-3 py-1.5 text-[13px] rounded-md hover-macos-button text-[var(--macos-blue)]"
+                        >
+                            Save
+                        </button>
+                    </div>
+                    {sessionError && (
+                        <p className="text-[11px] text-macos-red mt-1">{sessionError}</p>
+                    )}
+                    {sessions.map((session) => (
+                        <div key={session.name} className="flex items-center gap-2 py-1">
+                            <button
+                                onClick={() => handleLoadSession(session.name)}
+                                className="flex-1 min-w-0 text-left"
+                            >
+                                <p className="text-[13px] text-foreground truncate">{session.name}</p>
+                                <p className="text-[11px] text-muted-foreground truncate">
+                                    {session.track_count} tracks{session.current_track ? ` · ${session.current_track.title}` : ''}
+                                </p>
+                            </button>
+                            <button
+                                onClick={() => handleDeleteSession(session.name)}
+                                className="w-6 h-6 flex items-center justify-center rounded-full hover-macos-button text-muted-foreground"
+                                title="Delete Session"
+                            >
+                                <X className="w-4 h-4" />
+                            </button>
+                        </div>
+                    ))}
+                </div>
+            )}
+
+            {/* Tracks guests asked for, waiting on approval */}
+            {guestRequests.length > 0 && (
+                <div className="px-4 py-2 border-b border-muted-foreground/20">
+                    <p className="text-[11px] text-muted-foreground mb-1">Guest Requests</p>
+                    {guestRequests.map((request) => (
+                        <div key={request.id} className="flex items-center gap-2 py-1">
+                            <div className="flex-1 min-w-0">
+                                <p className="text-[13px] text-foreground truncate">{request.track.title}</p>
+                                <p className="text-[11px] text-muted-foreground truncate">{request.guest}</p>
+                            </div>
+                            <button
+                                onClick={() => handleGuestRequest(request.id, true)}
+                                className="px-3 py-1.5 text-[13px] rounded-md hover-macos-button text-[var(--macos-blue)]"
+                            >
+                                Add
+                            </button>
+                            <button
+                                onClick={() => handleGuestRequest(request.id, false)}
+                                className="px-3 py-1.5 text-[13px] rounded-md hover-macos-button text-muted-foreground"
+                            >
+                                Decline
+                            </button>
+                        </div>
+                    ))}
+                </div>
+            )}
+
+            {queue.length > 0 && (
+                <div className="px-4 py-2 border-b border-muted-foreground/20 flex items-center gap-2">
+                    <Search className="w-4 h-4 text-muted-foreground" />
+                    <input
+                        type="text"
+                        value={queueFilter}
+                        onChange={(e) => setQueueFilter(e.target.value)}
+                        placeholder="Filter the queue"
+                        className="flex-1 bg-transparent text-[13px] text-foreground placeholder:text-muted-foreground border-none outline-none"
+                    />
+                </div>
+            )}
+
             {/* Queue Content */}
             <div className="flex-1 overflow-y-auto">
                 {isLoading ? null : queue.length === 0 ? (
@@ -136,6 +390,67 @@ export function QueueTab() {
                         <p className="text-[13px] text-muted-foreground max-w-[250px]">
                             Use "Play All" on a playlist to add tracks to your queue
                         </p>
+                        {savedSession && savedSession.queue.length > 0 && (
+                            <div className="flex gap-2 mt-4">
+                                <button
+                                    onClick={() => handleRestoreSession('Exact')}
+                                    className="px-3 py-1.5 text-[13px] rounded-md hover-macos-button text-[var(--macos-blue)]"
+                                >
+                                    Resume Last Session
+                                </button>
+                                {savedSession.source_playlist && (
+                                    <button
+                                        onClick={() => handleRestoreSession('NextUnplayed')}
+                                        className="px-3 py-1.5 text-[13px] rounded-md hover-macos-button text-[var(--macos-blue)]"
+                                    >
+                                        Next Unplayed
+                                    </button>
+                                )}
+                            </div>
+                        )}
+                        {showUrlList ? (
+                            <div className="w-full mt-4">
+                                <textarea
+                                    value={urlList}
+                                    onChange={(e) => setUrlList(e.target.value)}
+                                    placeholder="One YouTube link per line"
+                                    rows={5}
+                                    className="w-full px-3 py-2 bg-secondary rounded-lg text-[13px] text-foreground border-none outline-none focus:ring-2 focus:ring-[var(--macos-blue)] transition-all resize-none"
+                                />
+                                {urlListError && (
+                                    <p className="text-[11px] text-macos-red mt-1">{urlListError}</p>
+                                )}
+                                <button
+                                    onClick={handlePlayUrlList}
+                                    disabled={!urlList.trim()}
+                                    className="mt-2 px-3 py-1.5 text-[13px] rounded-md hover-macos-button text-[var(--macos-blue)]"
+                                >
+                                    Play Links
+                                </button>
+                            </div>
+                        ) : (
+                            <button
+                                onClick={() => setShowUrlList(true)}
+                                className="mt-2 px-3 py-1.5 text-[13px] rounded-md hover-macos-button text-[var(--macos-blue)]"
+                            >
+                                Paste Links
+                            </button>
+                        )}
+                    </div>
+                ) : filteredQueue ? (
+                    // Matches keep their place in the full queue; reordering needs the whole list
+                    <div className="py-2">
+                        {filteredQueue.length === 0 && (
+                            <p className="px-4 py-2 text-[13px] text-muted-foreground">No tracks match "{queueFilter}"</p>
+                        )}
+                        {filteredQueue.map((match) => (
+                            <TrackItem
+                                key={`${match.track.id}-${match.index}`}
+                                track={match.track}
+                                context="queue"
+                                queueIndex={match.index}
+                            />
+                        ))}
                     </div>
                 ) : (
                     <div className="py-2">
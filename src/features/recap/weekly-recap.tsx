@@ -0,0 +1,165 @@
+import { useState, useEffect } from 'react'
+import { ArrowLeft, CalendarDays, RefreshCw } from 'lucide-react'
+import { getWeeklyRecaps, generateWeeklyRecap, listenToWeeklyRecapReady, type WeeklyRecap as Recap, type RecapTrack } from '@/lib/tauri'
+
+interface WeeklyRecapProps {
+    onBack: () => void
+}
+
+// "Mar 3 – Mar 9" for the week starting on the given Monday
+const formatWeek = (weekStart: string) => {
+    const start = new Date(`${weekStart}T00:00:00`)
+    const end = new Date(start)
+    end.setDate(start.getDate() + 6)
+    const format = (date: Date) => date.toLocaleDateString(undefined, { month: 'short', day: 'numeric' })
+    return `${format(start)} – ${format(end)}`
+}
+
+export function WeeklyRecap({ onBack }: WeeklyRecapProps) {
+    const [recaps, setRecaps] = useState<Recap[]>([])
+    const [selected, setSelected] = useState(0)
+    const [isLoading, setIsLoading] = useState(true)
+    const [isGenerating, setIsGenerating] = useState(false)
+
+    const loadRecaps = async () => {
+        try {
+            setRecaps(await getWeeklyRecaps())
+            setSelected(0)
+        } catch (error) {
+            console.error('Failed to load weekly recaps:', error)
+        } finally {
+            setIsLoading(false)
+        }
+    }
+
+    useEffect(() => {
+        loadRecaps()
+        const unlisten = listenToWeeklyRecapReady(() => {
+            loadRecaps()
+        })
+        return () => {
+            unlisten.then(fn => fn())
+        }
+    }, [])
+
+    const handleGenerate = async () => {
+        setIsGenerating(true)
+        try {
+            await generateWeeklyRecap()
+            await loadRecaps()
+        } catch (error) {
+            console.error('Failed to generate weekly recap:', error)
+        } finally {
+            setIsGenerating(false)
+        }
+    }
+
+    const recap = recaps[selected]
+
+    return (
+        <div className="flex flex-col h-full bg-background">
+            {/* Header */}
+            <div className="px-4 py-3 border-b border-macos-separator bg-card">
+                <div className="flex items-center gap-3">
+                    <button
+                        onClick={onBack}
+                        className="w-8 h-8 flex items-center justify-center rounded-full hover-macos-button"
+                        aria-label="Back to playlists"
+                    >
+                        <ArrowLeft className="w-5 h-5 text-foreground" />
+                    </button>
+                    <div className="flex-1 min-w-0">
+                        <h2 className="text-[17px] font-semibold text-foreground truncate">Your Week</h2>
+                        {recaps.length > 0 && (
+                            <select
+                                value={selected}
+                                onChange={(e) => setSelected(Number(e.target.value))}
+                                className="bg-transparent text-[12px] text-muted-foreground border-none outline-none p-0"
+                            >
+                                {recaps.map((r, index) => (
+                                    <option key={r.week_start} value={index}>
+                                        {formatWeek(r.week_start)}
+                                    </option>
+                                ))}
+                            </select>
+                        )}
+                    </div>
+                    <button
+                        onClick={handleGenerate}
+                        disabled={isGenerating}
+                        className="w-8 h-8 flex items-center justify-center rounded-full hover-macos-button disabled:opacity-50"
+                        title="Rebuild last week's recap"
+                    >
+                        <RefreshCw className={`w-4 h-4 text-foreground ${isGenerating ? 'animate-spin' : ''}`} />
+                    </button>
+                </div>
+            </div>
+
+            <div className="flex-1 overflow-y-auto">
+                {isLoading ? null : !recap ? (
+                    <div className="flex flex-col items-center justify-center h-full text-center px-6">
+                        <CalendarDays className="w-12 h-12 text-muted-foreground mb-4 opacity-60" />
+                        <h3 className="text-[15px] font-semibold text-foreground mb-2">
+                            No recap yet
+                        </h3>
+                        <p className="text-[13px] text-muted-foreground max-w-[250px]">
+                            A summary of your listening arrives every Monday
+                        </p>
+                    </div>
+                ) : (
+                    <div className="px-4 py-3">
+                        <div className="grid grid-cols-2 gap-2 mb-4">
+                            <Stat label="Hours listened" value={recap.total_hours.toFixed(1)} />
+                            <Stat label="Plays" value={String(recap.total_plays)} />
+                            <Stat label="Days listened" value={`${recap.days_listened} of 7`} />
+                            <Stat
+                                label="Streak"
+                                value={`${recap.streak} day${recap.streak === 1 ? '' : 's'}`}
+                                detail={`Best this week: ${recap.longest_streak}`}
+                            />
+                        </div>
+
+                        <TrackList title="Top Tracks" tracks={recap.top_tracks} empty="Nothing played through this week" />
+                        <TrackList title="New Discoveries" tracks={recap.new_discoveries} empty="No new tracks this week" />
+                    </div>
+                )}
+            </div>
+        </div>
+    )
+}
+
+function Stat({ label, value, detail }: { label: string, value: string, detail?: string }) {
+    return (
+        <div className="px-3 py-2 bg-secondary rounded-lg">
+            <div className="text-[11px] text-muted-foreground">{label}</div>
+            <div className="text-[17px] font-semibold text-foreground tabular-nums">{value}</div>
+            {detail && <div className="text-[11px] text-muted-foreground">{detail}</div>}
+        </div>
+    )
+}
+
+function TrackList({ title, tracks, empty }: { title: string, tracks: RecapTrack[], empty: string }) {
+    return (
+        <div className="mb-4">
+            <h3 className="text-[13px] font-medium text-foreground mb-2">{title}</h3>
+            {tracks.length === 0 ? (
+                <p className="text-[12px] text-muted-foreground">{empty}</p>
+            ) : (
+                tracks.map((track, index) => (
+                    <div key={track.id} className="flex items-center gap-3 py-1.5">
+                        <span className="w-4 text-[12px] text-muted-foreground tabular-nums text-right">{index + 1}</span>
+                        <div className="flex-1 min-w-0">
+                            <div className="text-[13px] text-foreground truncate">{track.title}</div>
+                            {track.author && (
+                                <div className="text-[11px] text-muted-foreground truncate">{track.author}</div>
+                            )}
+                        </div>
+                        <span className="text-[11px] text-muted-foreground tabular-nums">
+                            {track.plays} play{track.plays === 1 ? '' : 's'}
+                        </span>
+                    </div>
+                ))
+            )}
+        </div>
+    )
+}
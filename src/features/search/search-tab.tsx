@@ -1,6 +1,13 @@
 import { useState, useEffect } from 'react'
 import { Search, Music } from 'lucide-react'
-import { type YTVideoInfo, getAllPlaylists, getPlaylistTracks } from '@/lib/tauri'
+import {
+    type CooldownStatus,
+    type YTVideoInfo,
+    getAllPlaylists,
+    getPlaylistTracks,
+    getYoutubeCooldown,
+    listenToYoutubeCooldown
+} from '@/lib/tauri'
 import { TrackItem } from '@/components/track-item'
 
 interface SearchTabProps {
@@ -12,6 +19,16 @@ interface SearchTabProps {
 
 export function SearchTab({ query, isMusicMode, results, isSearching }: SearchTabProps) {
     const [favoriteTrackIds, setFavoriteTrackIds] = useState<Set<string>>(new Set())
+    const [cooldown, setCooldown] = useState<CooldownStatus | null>(null)
+
+    // Searches fail fast while YouTube is throttling us, so say why
+    useEffect(() => {
+        getYoutubeCooldown().then(setCooldown).catch(console.error)
+        const unlisten = listenToYoutubeCooldown(setCooldown)
+        return () => {
+            unlisten.then(fn => fn())
+        }
+    }, [])
 
     // Load favorite tracks
     useEffect(() => {
@@ -37,6 +54,18 @@ export function SearchTab({ query, isMusicMode, results, isSearching }: SearchTa
 
     return (
         <div className="flex flex-col h-full overflow-y-auto bg-background">
+            {cooldown?.cooling_down && (
+                <div className="px-3 py-2 border-b border-macos-separator flex-shrink-0">
+                    <div className="text-[13px] font-semibold text-macos-orange">
+                        Cooling down: YouTube is limiting requests
+                    </div>
+                    <div className="text-[11px] text-muted-foreground">
+                        {cooldown.until
+                            ? `Searches resume at ${new Date(cooldown.until * 1000).toLocaleTimeString()}. Downloads wait and continue on their own.`
+                            : 'Searches resume shortly. Downloads wait and continue on their own.'}
+                    </div>
+                </div>
+            )}
             {!query ? (
                 <div className="flex flex-col items-center justify-center h-full text-center px-6">
                     {isMusicMode ? (
@@ -1,15 +1,92 @@
-import { useState, useEffect } from 'react'
-import { Folder, Github, AlertCircle } from 'lucide-react'
+import { useState, useEffect, useRef } from 'react'
+import { Folder, Github, AlertCircle, Upload, Download, FileArchive } from 'lucide-react'
 import { open } from '@tauri-apps/plugin-shell'
-import { open as openDialog } from '@tauri-apps/plugin-dialog'
+import { open as openDialog, save as saveDialog } from '@tauri-apps/plugin-dialog'
+import { relaunch } from '@tauri-apps/plugin-process'
 import {
     getDownloadsDirectory,
     setDownloadsDirectory,
     getAudioQuality,
     setAudioQuality as saveAudioQuality,
-    getAppVersion
+    getAppVersion,
+    exportProfile,
+    importProfile,
+    generateDiagnosticsBundle,
+    getTelemetryStatus,
+    setTelemetryEnabled,
+    type TelemetryStatus,
+    getYtdlpOptions,
+    setYtdlpOptions,
+    type YtdlpOptions,
+    getYtdlpVersions,
+    getJingleSettings,
+    setJingleSettings,
+    type JingleSettings,
+    getVolumeKeys,
+    setVolumeKeys,
+    type VolumeKeySettings,
+    getNowPlayingOutput,
+    setNowPlayingOutput,
+    type NowPlayingOutput,
+    getNotificationPolicy,
+    getDownloadNotifications,
+    setDownloadNotifications,
+    setNotificationPolicy,
+    type NotificationPolicy,
+    getFingerprinting,
+    setFingerprinting,
+    fingerprintDownloads,
+    type FingerprintStatus,
+    getListeningLog,
+    setListeningLog,
+    getAudioCapabilities,
+    getPreferBuiltinDecoder,
+    setPreferBuiltinDecoder,
+    getKeepStreamedAudio,
+    setKeepStreamedAudio,
+    getChannelRules,
+    deleteChannelRule,
+    type ChannelRule,
+    getPartyMode,
+    enablePartyMode,
+    disablePartyMode,
+    listenToPartyMode,
+    listenToSettingsChanged,
+    type PartyModeStatus,
+    getGuestQueue,
+    setGuestQueue,
+    regenerateGuestToken,
+    type GuestQueueSettings,
+    type GuestQueueStatus,
+    setYtdlpRelease,
+    rollbackYtdlp,
+    type YtdlpChannel,
+    type YtdlpVersions,
+    isWindowMode,
+    getMinimizeToBackground,
+    setMinimizeToBackground,
+    checkAppUpdate,
+    downloadAppUpdate,
+    installAppUpdate,
+    getAutoCheckUpdates,
+    setAutoCheckUpdates,
+    listenToAppUpdateProgress,
+    type AppUpdate
 } from '@/lib/tauri'
 
+const YTDLP_CHANNEL_OPTIONS: { value: YtdlpChannel, label: string }[] = [
+    { value: 'Stable', label: 'Stable' },
+    { value: 'Nightly', label: 'Nightly' },
+    { value: 'Master', label: 'Master' },
+]
+
+const NOTIFICATION_POLICY_OPTIONS: { value: NotificationPolicy, label: string }[] = [
+    { value: 'RespectDnd', label: 'When hidden, unless Do Not Disturb is on' },
+    { value: 'OnlyWhenHidden', label: 'When the popup is hidden' },
+    { value: 'Always', label: 'Always' },
+    { value: 'Never', label: 'Never' },
+]
+
 const AUDIO_QUALITY_OPTIONS = [
     { value: 'best', label: 'Best Available' },
     { value: '320', label: '320 kbps' },
@@ -18,25 +95,119 @@ const AUDIO_QUALITY_OPTIONS = [
     { value: '128', label: '128 kbps' },
 ]
 
+// A `settings-changed` this soon after the tab saved the same key is the echo of that save
+const OWN_SAVE_WINDOW_MS = 2000
+
 export function SettingsTab() {
     const [downloadLocation, setDownloadLocation] = useState('')
     const [audioQuality, setAudioQuality] = useState('best')
     const [appVersion, setAppVersion] = useState('1.0.0')
     const [isLoading, setIsLoading] = useState(true)
     const [isMigrating, setIsMigrating] = useState(false)
+    const [includeDownloads, setIncludeDownloads] = useState(true)
+    const [isTransferring, setIsTransferring] = useState(false)
+    const [windowMode, setWindowMode] = useState(false)
+    const [minimizeToBackground, setMinimizeToBackgroundState] = useState(false)
+    const [autoCheckUpdates, setAutoCheckUpdatesState] = useState(true)
+    const [availableUpdate, setAvailableUpdate] = useState<AppUpdate | null>(null)
+    const [updateStatus, setUpdateStatus] = useState<string | null>(null)
+    const [isUpdating, setIsUpdating] = useState(false)
+    const [telemetry, setTelemetry] = useState<TelemetryStatus | null>(null)
+    const [showTelemetryPreview, setShowTelemetryPreview] = useState(false)
+    const [ytdlpOptions, setYtdlpOptionsState] = useState<YtdlpOptions>({
+        user_agent: null,
+        player_client: null,
+        po_token: null,
+        search_args: null,
+        stream_args: null,
+        download_args: null
+    })
+    const [ytdlpOptionsStatus, setYtdlpOptionsStatus] = useState<string | null>(null)
+    const [jingles, setJingles] = useState<JingleSettings>({ enabled: false, file_path: null, every: 1 })
+    const [jingleError, setJingleError] = useState<string | null>(null)
+    const [volumeKeys, setVolumeKeysState] = useState<VolumeKeySettings>({ enabled: false, step: 0.05 })
+    const [volumeKeysError, setVolumeKeysError] = useState<string | null>(null)
+    const [nowPlaying, setNowPlaying] = useState<NowPlayingOutput>({ enabled: false, file_path: null, pipe_path: null })
+    const [nowPlayingError, setNowPlayingError] = useState<string | null>(null)
+    const [downloadNotifications, setDownloadNotificationsState] = useState(true)
+    const [fingerprinting, setFingerprintingState] = useState<FingerprintStatus>({ enabled: false, available: false })
+    const [fingerprintStatus, setFingerprintStatus] = useState<string | null>(null)
+    const [isFingerprinting, setIsFingerprinting] = useState(false)
+    const [notificationPolicy, setNotificationPolicyState] = useState<NotificationPolicy>('Never')
+    const [listeningLog, setListeningLogState] = useState(false)
+    const [builtinDecoder, setBuiltinDecoder] = useState(false)
+    const [ffmpegAvailable, setFfmpegAvailable] = useState(true)
+    const [keepStreamedAudio, setKeepStreamedAudioState] = useState(false)
+    const [channelRules, setChannelRules] = useState<ChannelRule[]>([])
+    const [partyMode, setPartyMode] = useState<PartyModeStatus | null>(null)
+    const [partyPin, setPartyPin] = useState('')
+    const [partyError, setPartyError] = useState<string | null>(null)
+    const [guestQueue, setGuestQueueState] = useState<GuestQueueStatus | null>(null)
+    const [guestQueueError, setGuestQueueError] = useState<string | null>(null)
+    const [ytdlpVersions, setYtdlpVersions] = useState<YtdlpVersions | null>(null)
+    const [ytdlpChannel, setYtdlpChannel] = useState<YtdlpChannel>('Stable')
+    const [ytdlpPin, setYtdlpPin] = useState('')
+    const [ytdlpStatus, setYtdlpStatus] = useState<string | null>(null)
+    const [isInstallingYtdlp, setIsInstallingYtdlp] = useState(false)
+    // When this tab last saved each setting, so the echo of its own save doesn't reload the field
+    const ownSaves = useRef(new Map<string, number>())
+    // yt-dlp options are edited in text fields and only saved on demand
+    const ytdlpOptionsEdited = useRef(false)
+
+    const markSaving = (key: string) => ownSaves.current.set(key, Date.now())
 
     // Load settings from backend
     useEffect(() => {
         const loadSettings = async () => {
             try {
-                const [location, quality, version] = await Promise.all([
+                const [location, quality, version, windowed, minimize, autoCheck, telemetryStatus, options, versions, jingleSettings, volumeKeySettings, nowPlayingOutput, notifications, downloadNotify, fingerprints, logListens, preferBuiltin, keepStreamed, capabilities, rules, party, guests] = await Promise.all([
                     getDownloadsDirectory(),
                     getAudioQuality(),
-                    getAppVersion()
+                    getAppVersion(),
+                    isWindowMode(),
+                    getMinimizeToBackground(),
+                    getAutoCheckUpdates(),
+                    getTelemetryStatus(),
+                    getYtdlpOptions(),
+                    getYtdlpVersions(),
+                    getJingleSettings(),
+                    getVolumeKeys(),
+                    getNowPlayingOutput(),
+                    getNotificationPolicy(),
+                    getDownloadNotifications(),
+                    getFingerprinting(),
+                    getListeningLog(),
+                    getPreferBuiltinDecoder(),
+                    getKeepStreamedAudio(),
+                    getAudioCapabilities(),
+                    getChannelRules(),
+                    getPartyMode(),
+                    getGuestQueue()
                 ])
                 setDownloadLocation(location)
                 setAudioQuality(quality)
                 setAppVersion(version)
+                setWindowMode(windowed)
+                setMinimizeToBackgroundState(minimize)
+                setAutoCheckUpdatesState(autoCheck)
+                setTelemetry(telemetryStatus)
+                setYtdlpOptionsState(options)
+                setYtdlpVersions(versions)
+                setJingles(jingleSettings)
+                setVolumeKeysState(volumeKeySettings)
+                setNowPlaying(nowPlayingOutput)
+                setNotificationPolicyState(notifications)
+                setDownloadNotificationsState(downloadNotify)
+                setFingerprintingState(fingerprints)
+                setListeningLogState(logListens)
+                setBuiltinDecoder(preferBuiltin)
+                setKeepStreamedAudioState(keepStreamed)
+                setFfmpegAvailable(capabilities.ffmpeg_version !== null)
+                setChannelRules(rules)
+                setPartyMode(party)
+                setGuestQueueState(guests)
+                setYtdlpChannel(versions.release.channel)
+                setYtdlpPin(versions.release.version ?? '')
             } catch (error) {
                 console.error('Failed to load settings:', error)
             } finally {
@@ -44,6 +215,55 @@ export function SettingsTab() {
             }
         }
         loadSettings()
+
+        // Refreshes the fields showing each setting `settings-changed` can name
+        const reloaders: Record<string, () => Promise<void>> = {
+            downloads_directory: async () => setDownloadLocation(await getDownloadsDirectory()),
+            audio_quality: async () => setAudioQuality(await getAudioQuality()),
+            minimize_to_background: async () => setMinimizeToBackgroundState(await getMinimizeToBackground()),
+            auto_check_updates: async () => setAutoCheckUpdatesState(await getAutoCheckUpdates()),
+            telemetry_enabled: async () => setTelemetry(await getTelemetryStatus()),
+            telemetry_endpoint: async () => setTelemetry(await getTelemetryStatus()),
+            ytdlp_options: async () => {
+                if (!ytdlpOptionsEdited.current) setYtdlpOptionsState(await getYtdlpOptions())
+            },
+            ytdlp_release: async () => {
+                const versions = await getYtdlpVersions()
+                setYtdlpVersions(versions)
+                setYtdlpChannel(versions.release.channel)
+                setYtdlpPin(versions.release.version ?? '')
+            },
+            jingle_settings: async () => setJingles(await getJingleSettings()),
+            volume_keys: async () => setVolumeKeysState(await getVolumeKeys()),
+            now_playing_output: async () => setNowPlaying(await getNowPlayingOutput()),
+            notification_policy: async () => setNotificationPolicyState(await getNotificationPolicy()),
+            download_notifications: async () => setDownloadNotificationsState(await getDownloadNotifications()),
+            fingerprinting: async () => setFingerprintingState(await getFingerprinting()),
+            listening_log: async () => setListeningLogState(await getListeningLog()),
+            prefer_builtin_decoder: async () => setBuiltinDecoder(await getPreferBuiltinDecoder()),
+            keep_streamed_audio: async () => setKeepStreamedAudioState(await getKeepStreamedAudio()),
+            party_mode: async () => setPartyMode(await getPartyMode()),
+            guest_queue: async () => setGuestQueueState(await getGuestQueue())
+        }
+
+        // Another window, or the backend itself, saved a setting
+        const unlistenSettings = listenToSettingsChanged(({ keys }) => {
+            const now = Date.now()
+            for (const key of keys) {
+                const savedHere = now - (ownSaves.current.get(key) ?? 0) < OWN_SAVE_WINDOW_MS
+                if (savedHere) continue
+                reloaders[key]?.().catch((error) => console.error(`Failed to reload ${key}:`, error))
+            }
+        })
+        const unlistenProgress = listenToAppUpdateProgress(({ downloaded, total }) => {
+            setUpdateStatus(total ? `Downloading... ${Math.round((downloaded / total) * 100)}%` : 'Downloading...')
+        })
+        const unlistenPartyMode = listenToPartyMode(setPartyMode)
+        return () => {
+            unlistenProgress.then(fn => fn())
+            unlistenPartyMode.then(fn => fn())
+            unlistenSettings.then(fn => fn())
+        }
     }, [])
 
     const handleChangeDownloadLocation = async () => {
@@ -57,6 +277,7 @@ export function SettingsTab() {
             if (selected && typeof selected === 'string') {
                 setIsMigrating(true)
                 try {
+                    markSaving('downloads_directory')
                     await setDownloadsDirectory(selected)
                     setDownloadLocation(selected)
                 } catch (error: any) {
@@ -76,12 +297,369 @@ export function SettingsTab() {
         const quality = e.target.value
         setAudioQuality(quality)
         try {
+            markSaving('audio_quality')
             await saveAudioQuality(quality)
         } catch (error) {
             console.error('Failed to save audio quality:', error)
         }
     }
 
+    const handleMinimizeToBackgroundChange = async (enabled: boolean) => {
+        setMinimizeToBackgroundState(enabled)
+        try {
+            markSaving('minimize_to_background')
+            await setMinimizeToBackground(enabled)
+        } catch (error) {
+            console.error('Failed to save minimize to background:', error)
+        }
+    }
+
+    const handleAutoCheckUpdatesChange = async (enabled: boolean) => {
+        setAutoCheckUpdatesState(enabled)
+        try {
+            markSaving('auto_check_updates')
+            await setAutoCheckUpdates(enabled)
+        } catch (error) {
+            console.error('Failed to save automatic update checks:', error)
+        }
+    }
+
+    const handleTelemetryChange = async (enabled: boolean) => {
+        try {
+            markSaving('telemetry_enabled')
+            await setTelemetryEnabled(enabled)
+            setTelemetry(await getTelemetryStatus())
+        } catch (error) {
+            console.error('Failed to save usage metrics setting:', error)
+        }
+    }
+
+    const handleYtdlpOptionChange = (field: keyof YtdlpOptions, value: string) => {
+        ytdlpOptionsEdited.current = true
+        setYtdlpOptionsState({ ...ytdlpOptions, [field]: value || null })
+        setYtdlpOptionsStatus(null)
+    }
+
+    const handleSaveYtdlpOptions = async () => {
+        try {
+            markSaving('ytdlp_options')
+            await setYtdlpOptions(ytdlpOptions)
+            ytdlpOptionsEdited.current = false
+            setYtdlpOptionsStatus('Saved. New searches, streams and downloads use these options.')
+        } catch (error: any) {
+            setYtdlpOptionsStatus(String(error))
+            console.error('Failed to save yt-dlp options:', error)
+        }
+    }
+
+    const handleJinglesChange = async (settings: JingleSettings) => {
+        setJingles(settings)
+        setJingleError(null)
+        try {
+            markSaving('jingle_settings')
+            await setJingleSettings(settings)
+        } catch (error: any) {
+            setJingleError(String(error))
+            console.error('Failed to save jingle settings:', error)
+        }
+    }
+
+    const handleVolumeKeysChange = async (settings: VolumeKeySettings) => {
+        setVolumeKeysState(settings)
+        setVolumeKeysError(null)
+        try {
+            markSaving('volume_keys')
+            await setVolumeKeys(settings)
+        } catch (error) {
+            setVolumeKeysError(String(error))
+            console.error('Failed to save volume key settings:', error)
+        }
+    }
+
+    const handleChooseJingle = async () => {
+        try {
+            const selected = await openDialog({
+                multiple: false,
+                title: 'Select Jingle',
+                filters: [{ name: 'Audio', extensions: ['mp3', 'm4a', 'ogg', 'opus', 'wav', 'flac'] }]
+            })
+            if (selected && typeof selected === 'string') {
+                await handleJinglesChange({ ...jingles, file_path: selected, enabled: true })
+            }
+        } catch (error) {
+            console.error('Failed to open file picker:', error)
+        }
+    }
+
+    const handleNowPlayingChange = async (output: NowPlayingOutput) => {
+        setNowPlaying(output)
+        setNowPlayingError(null)
+        try {
+            markSaving('now_playing_output')
+            await setNowPlayingOutput(output)
+        } catch (error: any) {
+            setNowPlayingError(String(error))
+            console.error('Failed to save now playing output:', error)
+        }
+    }
+
+    const handleChooseNowPlayingFile = async () => {
+        try {
+            const path = await saveDialog({
+                title: 'Now Playing Text File',
+                defaultPath: 'now-playing.txt',
+                filters: [{ name: 'Text', extensions: ['txt'] }]
+            })
+            if (path) {
+                await handleNowPlayingChange({ ...nowPlaying, file_path: path, enabled: true })
+            }
+        } catch (error) {
+            console.error('Failed to open file picker:', error)
+        }
+    }
+
+    const handleDownloadNotificationsChange = async (enabled: boolean) => {
+        setDownloadNotificationsState(enabled)
+        try {
+            markSaving('download_notifications')
+            await setDownloadNotifications(enabled)
+        } catch (error) {
+            console.error('Failed to save download notifications:', error)
+        }
+    }
+
+    const handleFingerprintingChange = async (enabled: boolean) => {
+        setFingerprintStatus(null)
+        try {
+            markSaving('fingerprinting')
+            await setFingerprinting(enabled)
+            setFingerprintingState({ ...fingerprinting, enabled })
+        } catch (error: any) {
+            setFingerprintStatus(String(error))
+            console.error('Failed to save fingerprinting:', error)
+        }
+    }
+
+    const handleScanDownloads = async () => {
+        setIsFingerprinting(true)
+        setFingerprintStatus('Scanning downloads...')
+        try {
+            const found = await fingerprintDownloads()
+            setFingerprintStatus(found.length === 0
+                ? 'No re-uploads found'
+                : `Found ${found.length} re-upload${found.length === 1 ? '' : 's'}, listed in Downloads`)
+        } catch (error: any) {
+            setFingerprintStatus(String(error))
+            console.error('Failed to fingerprint downloads:', error)
+        } finally {
+            setIsFingerprinting(false)
+        }
+    }
+
+    const handleNotificationPolicyChange = async (policy: NotificationPolicy) => {
+        setNotificationPolicyState(policy)
+        try {
+            markSaving('notification_policy')
+            await setNotificationPolicy(policy)
+        } catch (error) {
+            console.error('Failed to save notification policy:', error)
+        }
+    }
+
+    const handleListeningLogChange = async (enabled: boolean) => {
+        setListeningLogState(enabled)
+        try {
+            markSaving('listening_log')
+            await setListeningLog(enabled)
+        } catch (error) {
+            console.error('Failed to save listening log setting:', error)
+        }
+    }
+
+    const handleBuiltinDecoderChange = async (enabled: boolean) => {
+        setBuiltinDecoder(enabled)
+        try {
+            markSaving('prefer_builtin_decoder')
+            await setPreferBuiltinDecoder(enabled)
+        } catch (error) {
+            console.error('Failed to save decoder setting:', error)
+        }
+    }
+
+    const handleKeepStreamedAudioChange = async (enabled: boolean) => {
+        setKeepStreamedAudioState(enabled)
+        try {
+            markSaving('keep_streamed_audio')
+            await setKeepStreamedAudio(enabled)
+        } catch (error) {
+            console.error('Failed to save streamed audio setting:', error)
+        }
+    }
+
+    const handleDeleteChannelRule = async (uploader: string) => {
+        try {
+            await deleteChannelRule(uploader)
+            setChannelRules(channelRules.filter((rule) => rule.uploader !== uploader))
+        } catch (error) {
+            console.error('Failed to delete channel rule:', error)
+        }
+    }
+
+    const handleTogglePartyMode = async () => {
+        setPartyError(null)
+        try {
+            markSaving('party_mode')
+            setPartyMode(partyMode?.enabled ? await disablePartyMode(partyPin) : await enablePartyMode(partyPin))
+            setPartyPin('')
+        } catch (error: any) {
+            setPartyError(String(error))
+            console.error('Failed to toggle party mode:', error)
+        }
+    }
+
+    const handleGuestQueueChange = async (settings: GuestQueueSettings) => {
+        setGuestQueueError(null)
+        try {
+            markSaving('guest_queue')
+            setGuestQueueState(await setGuestQueue(settings))
+        } catch (error: any) {
+            setGuestQueueError(String(error))
+            console.error('Failed to save guest queue settings:', error)
+        }
+    }
+
+    const handleRegenerateGuestToken = async () => {
+        setGuestQueueError(null)
+        try {
+            markSaving('guest_queue')
+            setGuestQueueState(await regenerateGuestToken())
+        } catch (error: any) {
+            setGuestQueueError(String(error))
+            console.error('Failed to regenerate guest link:', error)
+        }
+    }
+
+    const handleInstallYtdlp = async () => {
+        setIsInstallingYtdlp(true)
+        setYtdlpStatus('Installing...')
+        try {
+            markSaving('ytdlp_release')
+            setYtdlpVersions(await setYtdlpRelease({ channel: ytdlpChannel, version: ytdlpPin.trim() || null }))
+            setYtdlpStatus(null)
+        } catch (error: any) {
+            setYtdlpStatus(String(error))
+            console.error('Failed to install yt-dlp:', error)
+        } finally {
+            setIsInstallingYtdlp(false)
+        }
+    }
+
+    const handleRollbackYtdlp = async () => {
+        setIsInstallingYtdlp(true)
+        try {
+            markSaving('ytdlp_release')
+            setYtdlpVersions(await rollbackYtdlp())
+            setYtdlpStatus(null)
+        } catch (error: any) {
+            setYtdlpStatus(String(error))
+            console.error('Failed to roll back yt-dlp:', error)
+        } finally {
+            setIsInstallingYtdlp(false)
+        }
+    }
+
+    const handleCheckForUpdates = async () => {
+        setIsUpdating(true)
+        setUpdateStatus('Checking...')
+        try {
+            const update = await checkAppUpdate()
+            setAvailableUpdate(update)
+            setUpdateStatus(update ? null : 'YTAudioBar is up to date')
+        } catch (error: any) {
+            setUpdateStatus(null)
+            alert(error || 'Failed to check for updates')
+            console.error('Failed to check for updates:', error)
+        } finally {
+            setIsUpdating(false)
+        }
+    }
+
+    const handleInstallUpdate = async () => {
+        setIsUpdating(true)
+        try {
+            await downloadAppUpdate()
+            setUpdateStatus('Installing...')
+            await installAppUpdate()
+            await relaunch()
+        } catch (error: any) {
+            setUpdateStatus(null)
+            alert(error || 'Failed to install the update')
+            console.error('Failed to install update:', error)
+        } finally {
+            setIsUpdating(false)
+        }
+    }
+
+    const handleExportProfile = async () => {
+        const path = await saveDialog({
+            title: 'Export Profile',
+            defaultPath: 'ytaudiobar-profile.zip',
+            filters: [{ name: 'YTAudioBar Profile', extensions: ['zip'] }]
+        })
+        if (!path) return
+
+        setIsTransferring(true)
+        try {
+            await exportProfile(path, includeDownloads)
+        } catch (error: any) {
+            alert(error || 'Failed to export profile')
+            console.error('Failed to export profile:', error)
+        } finally {
+            setIsTransferring(false)
+        }
+    }
+
+    const handleImportProfile = async () => {
+        const path = await openDialog({
+            multiple: false,
+            title: 'Import Profile',
+            filters: [{ name: 'YTAudioBar Profile', extensions: ['zip'] }]
+        })
+        if (!path || typeof path !== 'string') return
+        if (!confirm('Importing replaces your playlists, history and settings. Continue?')) return
+
+        setIsTransferring(true)
+        try {
+            const result = await importProfile(path, includeDownloads)
+            const queued = result.downloads_queued > 0
+                ? ` ${result.downloads_queued} download${result.downloads_queued === 1 ? '' : 's'} will be fetched again.`
+                : ''
+            alert(`Profile imported.${queued} YTAudioBar will restart to apply it.`)
+            await relaunch()
+        } catch (error: any) {
+            alert(error || 'Failed to import profile')
+            console.error('Failed to import profile:', error)
+        } finally {
+            setIsTransferring(false)
+        }
+    }
+
+    const handleSaveDiagnostics = async () => {
+        const path = await saveDialog({
+            title: 'Save Diagnostics',
+            defaultPath: 'ytaudiobar-diagnostics.zip',
+            filters: [{ name: 'Zip Archive', extensions: ['zip'] }]
+        })
+        if (!path) return
+
+        try {
+            await generateDiagnosticsBundle(path)
+        } catch (error: any) {
+            alert(error || 'Failed to save diagnostics')
+            console.error('Failed to save diagnostics:', error)
+        }
+    }
+
     const handleOpenGitHub = () => {
         open('https://github.com/ilyassan/ytaudiobar')
     }
@@ -150,6 +728,491 @@ export function SettingsTab() {
                             Higher quality means larger file sizes
                         </p>
                     </div>
+
+                    <label className="flex items-center gap-2 text-[13px] text-foreground">
+                        <input
+                            type="checkbox"
+                            checked={downloadNotifications}
+                            onChange={(e) => handleDownloadNotificationsChange(e.target.checked)}
+                        />
+                        Notify when downloads finish
+                    </label>
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        Playlist downloads send one notification once every track is done, with a Play now button on Linux
+                    </p>
+
+                    <label className="flex items-center gap-2 mt-4 text-[13px] text-foreground">
+                        <input
+                            type="checkbox"
+                            checked={keepStreamedAudio}
+                            onChange={(e) => handleKeepStreamedAudioChange(e.target.checked)}
+                        />
+                        Save downloads from what's already streamed
+                    </label>
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        Downloading the playing track keeps the audio fetched for playback instead of fetching it again
+                    </p>
+
+                    <div className="flex items-center gap-2 mt-4">
+                        <label className="flex-1 flex items-center gap-2 text-[13px] text-foreground">
+                            <input
+                                type="checkbox"
+                                checked={fingerprinting.enabled}
+                                disabled={!fingerprinting.available}
+                                onChange={(e) => handleFingerprintingChange(e.target.checked)}
+                            />
+                            Recognise re-uploads of the same recording
+                        </label>
+                        {fingerprinting.enabled && (
+                            <button
+                                onClick={handleScanDownloads}
+                                disabled={isFingerprinting}
+                                className="px-3 py-1.5 bg-secondary hover-macos-button rounded-lg text-[13px] text-foreground font-medium transition-colors disabled:opacity-50"
+                            >
+                                Scan Downloads
+                            </button>
+                        )}
+                    </div>
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        {fingerprintStatus ?? (fingerprinting.available
+                            ? 'Downloads are fingerprinted so copies count as one track for play counts and shuffles'
+                            : 'Needs fpcalc from Chromaprint on your PATH')}
+                    </p>
+                </section>
+
+                {/* Divider */}
+                <div className="h-[1px] bg-muted-foreground/20 mb-8" />
+
+                {/* Window Section, only shown when there's no tray to reopen the app from */}
+                {windowMode && (
+                    <>
+                        <section className="mb-8">
+                            <h2 className="text-[20px] font-semibold text-foreground mb-4">Window</h2>
+
+                            <label className="flex items-center gap-2 text-[13px] text-foreground">
+                                <input
+                                    type="checkbox"
+                                    checked={minimizeToBackground}
+                                    onChange={(e) => handleMinimizeToBackgroundChange(e.target.checked)}
+                                />
+                                Minimize to background
+                            </label>
+                            <p className="text-[11px] text-muted-foreground mt-1">
+                                Closing the window keeps music playing. Launch YTAudioBar again to bring it back.
+                            </p>
+                        </section>
+
+                        {/* Divider */}
+                        <div className="h-[1px] bg-muted-foreground/20 mb-8" />
+                    </>
+                )}
+
+                {/* Playback Section */}
+                <section className="mb-8">
+                    <h2 className="text-[20px] font-semibold text-foreground mb-4">Playback</h2>
+
+                    <label className="flex items-center gap-2 text-[13px] text-foreground">
+                        <input
+                            type="checkbox"
+                            checked={jingles.enabled}
+                            disabled={!jingles.file_path}
+                            onChange={(e) => handleJinglesChange({ ...jingles, enabled: e.target.checked })}
+                        />
+                        Play a jingle every
+                        <input
+                            type="number"
+                            min={1}
+                            value={jingles.every}
+                            onChange={(e) => handleJinglesChange({ ...jingles, every: Math.max(1, Number(e.target.value) || 1) })}
+                            className="w-[56px] px-2 py-1 bg-secondary rounded-md text-[13px] text-foreground border-none outline-none"
+                        />
+                        {jingles.every === 1 ? 'track' : 'tracks'}
+                    </label>
+                    <div className="flex items-center gap-2 mt-2">
+                        <div className="flex-1 px-3 py-2 bg-secondary rounded-lg text-[13px] text-foreground truncate">
+                            {jingles.file_path ?? 'No file chosen'}
+                        </div>
+                        <button
+                            onClick={handleChooseJingle}
+                            className="px-4 py-2 bg-secondary hover-macos-button rounded-lg text-[13px] text-foreground font-medium transition-colors flex items-center gap-2"
+                        >
+                            <Folder className="w-4 h-4" />
+                            Choose
+                        </button>
+                    </div>
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        {jingleError ?? 'A short chime or station ident played as the queue moves to the next track'}
+                    </p>
+
+                    <label className="flex items-center gap-2 mt-4 text-[13px] text-foreground">
+                        <input
+                            type="checkbox"
+                            checked={nowPlaying.enabled}
+                            disabled={!nowPlaying.file_path && !nowPlaying.pipe_path}
+                            onChange={(e) => handleNowPlayingChange({ ...nowPlaying, enabled: e.target.checked })}
+                        />
+                        Write the current track for streaming tools
+                    </label>
+                    <div className="flex items-center gap-2 mt-2">
+                        <div className="flex-1 px-3 py-2 bg-secondary rounded-lg text-[13px] text-foreground truncate">
+                            {nowPlaying.file_path ?? 'No text file chosen'}
+                        </div>
+                        <button
+                            onClick={handleChooseNowPlayingFile}
+                            className="px-4 py-2 bg-secondary hover-macos-button rounded-lg text-[13px] text-foreground font-medium transition-colors flex items-center gap-2"
+                        >
+                            <Folder className="w-4 h-4" />
+                            Choose
+                        </button>
+                    </div>
+                    <input
+                        type="text"
+                        value={nowPlaying.pipe_path ?? ''}
+                        onChange={(e) => setNowPlaying({ ...nowPlaying, pipe_path: e.target.value || null })}
+                        onBlur={() => handleNowPlayingChange(nowPlaying)}
+                        placeholder="Named pipe (optional)"
+                        className="w-full mt-2 px-3 py-2 bg-secondary rounded-lg text-[13px] text-foreground border-none outline-none"
+                    />
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        {nowPlayingError ?? '"Artist – Title" is rewritten on every track change, OBS can show the file as a text source'}
+                    </p>
+
+                    <label className="flex items-center gap-2 mt-4 text-[13px] text-foreground">
+                        <input
+                            type="checkbox"
+                            checked={builtinDecoder || !ffmpegAvailable}
+                            disabled={!ffmpegAvailable}
+                            onChange={(e) => handleBuiltinDecoderChange(e.target.checked)}
+                        />
+                        Use the built-in decoder
+                    </label>
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        {ffmpegAvailable
+                            ? 'Decodes AAC, MP3, FLAC and Vorbis without ffmpeg, which still handles anything else'
+                            : "ffmpeg isn't installed, so audio is decoded by the app itself. Streams come as AAC, and some downloads may not play."}
+                    </p>
+
+                    <label className="flex items-center gap-2 mt-4 text-[13px] text-foreground">
+                        <input
+                            type="checkbox"
+                            checked={volumeKeys.enabled}
+                            onChange={(e) => handleVolumeKeysChange({ ...volumeKeys, enabled: e.target.checked })}
+                        />
+                        Volume keys change the app's volume, by
+                        <input
+                            type="number"
+                            min={1}
+                            max={25}
+                            value={Math.round(volumeKeys.step * 100)}
+                            onChange={(e) => handleVolumeKeysChange({ ...volumeKeys, step: Math.min(25, Math.max(1, Number(e.target.value) || 1)) / 100 })}
+                            className="w-[56px] px-2 py-1 bg-secondary rounded-md text-[13px] text-foreground border-none outline-none"
+                        />
+                        %
+                    </label>
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        {volumeKeysError ?? 'Only while something is playing, the keys control the system volume again once playback stops'}
+                    </p>
+
+                    <label className="block text-[13px] font-medium text-foreground mt-4 mb-2">
+                        Track change notifications
+                    </label>
+                    <select
+                        value={notificationPolicy}
+                        onChange={(e) => handleNotificationPolicyChange(e.target.value as NotificationPolicy)}
+                        className="w-full px-3 py-2 bg-secondary rounded-lg text-[13px] text-foreground border-none outline-none focus:ring-2 focus:ring-[var(--macos-blue)] transition-all"
+                    >
+                        {NOTIFICATION_POLICY_OPTIONS.map((option) => (
+                            <option key={option.value} value={option.value}>
+                                {option.label}
+                            </option>
+                        ))}
+                    </select>
+
+                    <label className="flex items-center gap-2 mt-4 text-[13px] text-foreground">
+                        <input
+                            type="checkbox"
+                            checked={listeningLog}
+                            onChange={(e) => handleListeningLogChange(e.target.checked)}
+                        />
+                        Keep a monthly listening log
+                    </label>
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        Tracks that play past 80% are added to a playlist for the month, like "Listening Log – March 2026"
+                    </p>
+
+                    <label className="block text-[13px] font-medium text-foreground mt-4 mb-2">
+                        Channel rules
+                    </label>
+                    {channelRules.map((rule) => (
+                        <div key={rule.uploader} className="flex items-center gap-2 px-3 py-2 mb-1 bg-secondary rounded-lg text-[13px]">
+                            <div className="flex-1 min-w-0">
+                                <p className="text-foreground truncate">{rule.uploader}</p>
+                                <p className="text-[11px] text-muted-foreground truncate">
+                                    {[
+                                        rule.playback_rate !== null && `${rule.playback_rate.toFixed(2)}x`,
+                                        rule.auto_download && 'download on play',
+                                        rule.playlist_id !== null && 'add to a playlist',
+                                    ].filter(Boolean).join(', ')}
+                                </p>
+                            </div>
+                            <button
+                                onClick={() => handleDeleteChannelRule(rule.uploader)}
+                                className="px-2 py-1 rounded-md hover-macos-button text-[var(--macos-blue)]"
+                            >
+                                Remove
+                            </button>
+                        </div>
+                    ))}
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        {channelRules.length === 0
+                            ? 'Set a speed, auto-download or playlist for a channel from the channel button in the expanded player'
+                            : 'Applied whenever a track from the channel is played, the playlist also when it is queued'}
+                    </p>
+
+                    <div className="flex items-center gap-2 mt-4">
+                        <input
+                            type="password"
+                            inputMode="numeric"
+                            value={partyPin}
+                            onChange={(e) => setPartyPin(e.target.value.replace(/\D/g, '').slice(0, 8))}
+                            placeholder="PIN"
+                            className="w-[96px] px-3 py-2 bg-secondary rounded-lg text-[13px] text-foreground border-none outline-none"
+                        />
+                        <button
+                            onClick={handleTogglePartyMode}
+                            disabled={partyPin.length < 4}
+                            className="px-4 py-2 bg-secondary hover-macos-button rounded-lg text-[13px] text-foreground font-medium transition-colors disabled:opacity-50"
+                        >
+                            {partyMode?.enabled ? 'Unlock Party Mode' : 'Start Party Mode'}
+                        </button>
+                    </div>
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        {partyError ?? (partyMode?.enabled
+                            ? `Guests can queue songs; stopping, clearing and deleting need the PIN. Volume stays between ${Math.round((partyMode.min_volume ?? 0) * 100)}% and ${Math.round((partyMode.max_volume ?? 1) * 100)}%.`
+                            : 'Lock stop, clear-queue and delete controls behind a 4 to 8 digit PIN while guests pick songs')}
+                    </p>
+                </section>
+
+                {/* Divider */}
+                <div className="h-[1px] bg-muted-foreground/20 mb-8" />
+
+                {/* Guest Queue Section */}
+                {guestQueue && (
+                    <>
+                        <section className="mb-8">
+                            <h2 className="text-[20px] font-semibold text-foreground mb-4">Guest Queue</h2>
+
+                            <label className="flex items-center gap-2 text-[13px] text-foreground">
+                                <input
+                                    type="checkbox"
+                                    checked={guestQueue.settings.enabled}
+                                    onChange={(e) => handleGuestQueueChange({ ...guestQueue.settings, enabled: e.target.checked })}
+                                />
+                                Let devices on my network add songs on port
+                                <input
+                                    type="number"
+                                    min={1024}
+                                    max={65535}
+                                    value={guestQueue.settings.port}
+                                    onChange={(e) => setGuestQueueState({ ...guestQueue, settings: { ...guestQueue.settings, port: Number(e.target.value) || 0 } })}
+                                    onBlur={() => handleGuestQueueChange(guestQueue.settings)}
+                                    className="w-[72px] px-2 py-1 bg-secondary rounded-md text-[13px] text-foreground border-none outline-none"
+                                />
+                            </label>
+                            <label className="flex items-center gap-2 mt-2 text-[13px] text-foreground">
+                                <input
+                                    type="checkbox"
+                                    checked={guestQueue.settings.require_approval}
+                                    onChange={(e) => handleGuestQueueChange({ ...guestQueue.settings, require_approval: e.target.checked })}
+                                />
+                                Approve guest songs before they're queued
+                            </label>
+                            {guestQueue.url && (
+                                <div className="flex items-center gap-2 mt-2">
+                                    <div className="flex-1 px-3 py-2 bg-secondary rounded-lg text-[13px] text-foreground truncate select-all">
+                                        {guestQueue.url}
+                                    </div>
+                                    <button
+                                        onClick={handleRegenerateGuestToken}
+                                        className="px-4 py-2 bg-secondary hover-macos-button rounded-lg text-[13px] text-foreground font-medium transition-colors"
+                                    >
+                                        New Link
+                                    </button>
+                                </div>
+                            )}
+                            <p className="text-[11px] text-muted-foreground mt-1">
+                                {guestQueueError ?? 'Guests open the link to search and add songs, they can\'t control playback'}
+                            </p>
+                        </section>
+
+                        {/* Divider */}
+                        <div className="h-[1px] bg-muted-foreground/20 mb-8" />
+                    </>
+                )}
+
+                {/* Profile Section */}
+                <section className="mb-8">
+                    <h2 className="text-[20px] font-semibold text-foreground mb-4">Profile</h2>
+
+                    <label className="flex items-center gap-2 mb-3 text-[13px] text-foreground">
+                        <input
+                            type="checkbox"
+                            checked={includeDownloads}
+                            onChange={(e) => setIncludeDownloads(e.target.checked)}
+                        />
+                        Include the list of downloaded tracks
+                    </label>
+
+                    <div className="flex items-center gap-2">
+                        <button
+                            onClick={handleExportProfile}
+                            disabled={isTransferring}
+                            className={`flex-1 px-4 py-2 bg-secondary hover-macos-button rounded-lg text-[13px] text-foreground font-medium transition-colors flex items-center justify-center gap-2 ${
+                                isTransferring ? 'opacity-50 cursor-not-allowed' : ''
+                            }`}
+                        >
+                            <Upload className="w-4 h-4" />
+                            Export
+                        </button>
+                        <button
+                            onClick={handleImportProfile}
+                            disabled={isTransferring}
+                            className={`flex-1 px-4 py-2 bg-secondary hover-macos-button rounded-lg text-[13px] text-foreground font-medium transition-colors flex items-center justify-center gap-2 ${
+                                isTransferring ? 'opacity-50 cursor-not-allowed' : ''
+                            }`}
+                        >
+                            <Download className="w-4 h-4" />
+                            Import
+                        </button>
+                    </div>
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        Move your playlists, history and settings to another computer
+                    </p>
+                </section>
+
+                {/* Divider */}
+                <div className="h-[1px] bg-muted-foreground/20 mb-8" />
+
+                {/* Privacy Section */}
+                <section className="mb-8">
+                    <h2 className="text-[20px] font-semibold text-foreground mb-4">Privacy</h2>
+
+                    <label className="flex items-center gap-2 text-[13px] text-foreground">
+                        <input
+                            type="checkbox"
+                            checked={telemetry?.enabled ?? false}
+                            onChange={(e) => handleTelemetryChange(e.target.checked)}
+                        />
+                        Share anonymous usage counts
+                    </label>
+                    <p className="text-[11px] text-muted-foreground mt-1">
+                        Only how often searches, downloads and crashes happen. Never what you search, play or download.
+                    </p>
+                    {telemetry?.enabled && (
+                        <>
+                            <button
+                                onClick={() => setShowTelemetryPreview(!showTelemetryPreview)}
+                                className="mt-2 text-[13px] text-[var(--macos-blue)] hover:opacity-80"
+                            >
+                                {showTelemetryPreview ? 'Hide' : 'Preview'} what would be sent
+                            </button>
+                            {showTelemetryPreview && (
+                                <pre className="mt-2 px-3 py-2 bg-secondary rounded-lg text-[11px] text-foreground overflow-x-auto">
+                                    {JSON.stringify(telemetry.preview, null, 2)}
+                                </pre>
+                            )}
+                        </>
+                    )}
+                </section>
+
+                {/* Divider */}
+                <div className="h-[1px] bg-muted-foreground/20 mb-8" />
+
+                {/* Advanced Section */}
+                <section className="mb-8">
+                    <h2 className="text-[20px] font-semibold text-foreground mb-4">Advanced</h2>
+
+                    {/* yt-dlp Release */}
+                    <div className="mb-4">
+                        <label className="block text-[13px] font-medium text-foreground mb-2">
+                            yt-dlp Channel
+                        </label>
+                        <div className="flex items-center gap-2">
+                            <select
+                                value={ytdlpChannel}
+                                onChange={(e) => setYtdlpChannel(e.target.value as YtdlpChannel)}
+                                className="flex-1 px-3 py-2 bg-secondary rounded-lg text-[13px] text-foreground border-none outline-none focus:ring-2 focus:ring-[var(--macos-blue)] transition-all"
+                            >
+                                {YTDLP_CHANNEL_OPTIONS.map((option) => (
+                                    <option key={option.value} value={option.value}>
+                                        {option.label}
+                                    </option>
+                                ))}
+                            </select>
+                            <input
+                                type="text"
+                                value={ytdlpPin}
+                                placeholder="Latest"
+                                onChange={(e) => setYtdlpPin(e.target.value)}
+                                className="w-[130px] px-3 py-2 bg-secondary rounded-lg text-[13px] text-foreground border-none outline-none focus:ring-2 focus:ring-[var(--macos-blue)] transition-all"
+                            />
+                            <button
+                                onClick={handleInstallYtdlp}
+                                disabled={isInstallingYtdlp}
+                                className={`px-4 py-2 bg-secondary hover-macos-button rounded-lg text-[13px] text-foreground font-medium transition-colors ${
+                                    isInstallingYtdlp ? 'opacity-50 cursor-not-allowed' : ''
+                                }`}
+                            >
+                                Install
+                            </button>
+                        </div>
+                        <p className="text-[11px] text-muted-foreground mt-1">
+                            {ytdlpStatus ?? `Installed: ${ytdlpVersions?.installed ?? 'none'}. Nightly gets fixes first; pin a version to stay on it.`}
+                        </p>
+                        {ytdlpVersions?.previous && (
+                            <button
+                                onClick={handleRollbackYtdlp}
+                                disabled={isInstallingYtdlp}
+                                className="mt-1 text-[13px] text-[var(--macos-blue)] hover:opacity-80"
+                            >
+                                Roll back to {ytdlpVersions.previous}
+                            </button>
+                        )}
+                    </div>
+
+                    <p className="text-[11px] text-muted-foreground mb-3">
+                        yt-dlp options for working around YouTube extraction problems. Leave blank to use yt-dlp's defaults. Flags that run programs or change where files go aren't allowed.
+                    </p>
+
+                    {([
+                        ['user_agent', 'User Agent', 'Mozilla/5.0 ...'],
+                        ['player_client', 'Player Clients', 'web,mweb'],
+                        ['po_token', 'PO Token', 'web.gvs+TOKEN'],
+                        ['search_args', 'Extra Search Arguments', '--geo-bypass'],
+                        ['stream_args', 'Extra Streaming Arguments', '--http-chunk-size 10M'],
+                        ['download_args', 'Extra Download Arguments', '--embed-metadata'],
+                    ] as const).map(([field, label, placeholder]) => (
+                        <div key={field} className="mb-3">
+                            <label className="block text-[13px] font-medium text-foreground mb-2">{label}</label>
+                            <input
+                                type="text"
+                                value={ytdlpOptions[field] ?? ''}
+                                placeholder={placeholder}
+                                onChange={(e) => handleYtdlpOptionChange(field, e.target.value)}
+                                className="w-full px-3 py-2 bg-secondary rounded-lg text-[13px] text-foreground border-none outline-none focus:ring-2 focus:ring-[var(--macos-blue)] transition-all"
+                            />
+                        </div>
+                    ))}
+
+                    <button
+                        onClick={handleSaveYtdlpOptions}
+                        className="px-4 py-2 bg-secondary hover-macos-button rounded-lg text-[13px] text-foreground font-medium transition-colors"
+                    >
+                        Save
+                    </button>
+                    {ytdlpOptionsStatus && (
+                        <p className="text-[11px] text-muted-foreground mt-1">{ytdlpOptionsStatus}</p>
+                    )}
                 </section>
 
                 {/* Divider */}
@@ -169,6 +1232,53 @@ export function SettingsTab() {
                         </div>
                     </div>
 
+                    {/* Updates */}
+                    <div className="mb-4">
+                        <label className="flex items-center gap-2 mb-3 text-[13px] text-foreground">
+                            <input
+                                type="checkbox"
+                                checked={autoCheckUpdates}
+                                onChange={(e) => handleAutoCheckUpdatesChange(e.target.checked)}
+                            />
+                            Check for updates automatically
+                        </label>
+
+                        {availableUpdate ? (
+                            <div className="px-3 py-2 bg-secondary rounded-lg">
+                                <div className="text-[13px] font-medium text-foreground">
+                                    Version {availableUpdate.version} is available
+                                </div>
+                                {availableUpdate.notes && (
+                                    <p className="text-[11px] text-muted-foreground mt-1 whitespace-pre-line max-h-32 overflow-y-auto">
+                                        {availableUpdate.notes}
+                                    </p>
+                                )}
+                                <button
+                                    onClick={handleInstallUpdate}
+                                    disabled={isUpdating}
+                                    className={`mt-2 text-[13px] text-[var(--macos-blue)] hover:opacity-80 ${
+                                        isUpdating ? 'opacity-50 cursor-not-allowed' : ''
+                                    }`}
+                                >
+                                    {isUpdating ? updateStatus ?? 'Updating...' : 'Update and Restart'}
+                                </button>
+                            </div>
+                        ) : (
+                            <button
+                                onClick={handleCheckForUpdates}
+                                disabled={isUpdating}
+                                className={`w-full px-4 py-2 bg-secondary hover-macos-button rounded-lg text-[13px] text-foreground font-medium transition-colors ${
+                                    isUpdating ? 'opacity-50 cursor-not-allowed' : ''
+                                }`}
+                            >
+                                Check for Updates
+                            </button>
+                        )}
+                        {!availableUpdate && updateStatus && (
+                            <p className="text-[11px] text-muted-foreground mt-1">{updateStatus}</p>
+                        )}
+                    </div>
+
                     {/* Links */}
                     <div className="space-y-2">
                         <button
@@ -185,6 +1295,13 @@ export function SettingsTab() {
                             <AlertCircle className="w-5 h-5" />
                             <span>Report an Issue</span>
                         </button>
+                        <button
+                            onClick={handleSaveDiagnostics}
+                            className="w-full flex items-center gap-3 px-3 py-2 hover-macos-button rounded-lg text-[13px] text-foreground transition-colors"
+                        >
+                            <FileArchive className="w-5 h-5" />
+                            <span>Save Diagnostics for a Bug Report</span>
+                        </button>
                     </div>
                 </section>
             </div>
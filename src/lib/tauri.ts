@@ -10,6 +10,9 @@ export interface YTVideoInfo {
     thumbnail_url: string | null
     audio_url: string | null
     description: string | null
+    content_type?: ContentType | null
+    custom_title?: string | null // the user's override, title and uploader stay YouTube's
+    custom_author?: string | null
 }
 
 export interface Track {
@@ -20,6 +23,28 @@ export interface Track {
     thumbnail_url: string | null
     added_date: number
     file_path: string | null
+    custom_title: string | null
+    custom_author: string | null
+    note: string | null
+    content_type: ContentType | null
+}
+
+export interface QueueMatch {
+    index: number // position in the full queue
+    track: YTVideoInfo
+}
+
+export interface TrackDuration {
+    video_id: string
+    duration: number
+}
+
+export type PrefetchPriority = 'Visible' | 'Background'
+
+export interface PrefetchedMetadata {
+    video_id: string
+    thumbnail: string | null // data URL, null when it couldn't be fetched
+    duration: number | null // only set when the track had no duration
 }
 
 export interface Playlist {
@@ -27,18 +52,291 @@ export interface Playlist {
     name: string
     created_date: number
     is_system_playlist: boolean
+    is_generated: boolean
+    cover: string | null
+}
+
+// Id of the built-in favorites playlist; listening logs are system playlists too, but not favorites
+export const FAVORITES_ID = 'favorites'
+
+export interface RecapTrack {
+    id: string
+    title: string
+    author: string | null
+    plays: number
+}
+
+export interface WeeklyRecap {
+    week_start: string
+    total_plays: number
+    total_hours: number
+    top_tracks: RecapTrack[]
+    new_discoveries: RecapTrack[]
+    days_listened: number
+    longest_streak: number
+    streak: number
+    generated_at: number
+}
+
+export interface TrackSkipStats {
+    track: Track
+    plays: number
+    skips: number
+    skip_rate: number
+}
+
+export type SessionRestoreMode = 'Exact' | 'NextUnplayed'
+
+export interface SavedSession {
+    queue: YTVideoInfo[]
+    current_index: number
+    position: number
+    source_playlist: string | null
+    started_at: number
+    saved_at: number
+}
+
+export interface QueueSessionSummary {
+    name: string
+    track_count: number
+    current_track: YTVideoInfo | null
+    shuffle_mode: boolean
+    repeat_mode: RepeatMode
+    saved_at: number
+}
+
+export interface ChannelRule {
+    uploader: string
+    playback_rate: number | null
+    auto_download: boolean
+    playlist_id: string | null
+}
+
+export interface Bookmark {
+    id: string
+    track_id: string
+    position: number
+    note: string | null
+    created_date: number
+}
+
+export interface PlaylistSummary {
+    playlist_id: string
+    track_count: number
+    total_duration: number
+    downloaded_count: number
+    downloaded_size: number
+    last_played: number | null
 }
 
 export interface AudioState {
     is_playing: boolean
     is_loading: boolean
+    is_loaded: boolean // stopped tracks stay loaded, unloaded ones clear current_track
     current_position: number
     duration: number
+    volume: number
+    output_gain: number
+    max_gain: number
     playback_rate: number
     current_track: YTVideoInfo | null
+    effects: AudioEffects
+    output_device: string | null
+    output_unavailable: boolean // no device could be opened, playback waits for one
+    buffer_target: number
+    buffered_ahead: number
+    is_buffering: boolean
+    buffered_until: number
+    network_offline: boolean
+    data_saver: boolean
+    quiet_hours_active: boolean
+    max_volume: number
+    limiter: LimiterSettings
+    trim_start: number // where the current track starts playing
+    trim_end: number | null // where it ends early, duration already matches it
+}
+
+export interface IntroSkipSuggestion {
+    video_id: string
+    title: string
+    start: number
+}
+
+export interface TrackTrim {
+    start: number
+    end: number | null
+}
+
+export interface LimiterSettings {
+    enabled: boolean
+    ceiling_db: number // dBFS
+}
+
+export interface AudioEffects {
+    bass_db: number
+    mid_db: number
+    treble_db: number
+    crossfeed: number
+}
+
+export type ContentType = 'Music' | 'Speech'
+
+export interface SpeedSettings {
+    presets: number[]
+    speech_rate: number
+    auto_speed: boolean
+}
+
+export interface JingleSettings {
+    enabled: boolean
+    file_path: string | null
+    every: number // tracks between jingles, 1 plays it between every track
+}
+
+export interface VolumeKeySettings {
+    enabled: boolean
+    step: number // slider change per key press, 0.0 to 1.0
+}
+
+export type NotificationPolicy = 'Always' | 'OnlyWhenHidden' | 'Never' | 'RespectDnd'
+
+export interface NowPlayingOutput {
+    enabled: boolean
+    file_path: string | null
+    pipe_path: string | null // FIFO, or \\.\pipe\name on Windows
+}
+
+export interface PartyModeStatus {
+    enabled: boolean
+    min_volume: number | null // volume guests are held to while enabled
+    max_volume: number | null
+}
+
+export interface GuestQueueSettings {
+    enabled: boolean
+    port: number
+    token: string // generated when first enabled
+    require_approval: boolean
+}
+
+export interface GuestQueueStatus {
+    settings: GuestQueueSettings
+    running: boolean
+    url: string | null // link to share with guests
 }
 
-export type RepeatMode = 'Off' | 'All' | 'One'
+export interface GuestRequest {
+    id: string
+    guest: string // IP address
+    track: YTVideoInfo
+    requested_at: number // unix seconds
+}
+
+export interface AudioProfile {
+    volume: number
+    effects: AudioEffects
+}
+
+// Count repeats the current track that many more times, then goes back to the previous mode
+export type RepeatMode = 'Off' | 'All' | 'One' | { Count: number }
+
+export type ShuffleScope = { Playlist: string } | 'Favorites' | 'Downloads' | 'Library'
+
+export type EndOfQueueAction =
+    | 'Stop'
+    | 'Radio'
+    | 'ReplayQueue'
+    | 'FadeOutAndQuit'
+    | { FallbackPlaylist: string }
+
+export interface ProfileImport {
+    app_version: string
+    exported_at: number
+    downloads_queued: number
+}
+
+export type TimerKind = 'Sleep' | 'StopAt'
+
+export interface ScheduledTimer {
+    id: string
+    kind: TimerKind
+    fires_at: number // unix seconds
+    fade_seconds: number
+}
+
+export type QuietHoursMode = { CapVolume: number } | 'Block'
+
+export interface QuietHours {
+    enabled: boolean
+    start: string // "HH:MM"
+    end: string
+    mode: QuietHoursMode
+}
+
+export type DataSaverMode = 'Auto' | 'On' | 'Off'
+
+export type DecodeStrategy = 'Ffmpeg' | 'Symphonia' | 'Unsupported'
+
+export interface FormatSupport {
+    extension: string
+    codec: string
+    strategy: DecodeStrategy
+    builtin: boolean
+}
+
+export interface AudioCapabilities {
+    ffmpeg_version: string | null
+    ffprobe_available: boolean
+    streaming_available: boolean
+    symphonia_codecs: string[]
+    formats: FormatSupport[]
+}
+
+export type ListFormat = 'Text' | 'Markdown'
+
+export interface PlaybackError {
+    track: YTVideoInfo
+    message: string
+}
+
+export type PlayerEventKind =
+    | 'PlayStarted'
+    | { Buffered: { load_ms: number } }
+    | { Seek: { from: number; to: number } }
+    | { Stall: { position: number } }
+    | 'Paused'
+    | 'Resumed'
+    | 'Stopped'
+    | { Ended: { position: number } }
+    | { Error: { message: string } }
+    | { OutputChanged: { device: string } }
+
+export interface PlayerEvent {
+    timestamp: number
+    track_id: string | null
+    kind: PlayerEventKind
+}
+
+export type TrackChangeReason = 'auto' | 'user' | 'media-key' | 'remote'
+
+export interface TrackChanged {
+    track: YTVideoInfo
+    previous: YTVideoInfo | null
+    reason: TrackChangeReason
+}
+
+export interface SettingsChanged {
+    keys: string[]
+}
+
+export interface SkippedTrack {
+    track: YTVideoInfo
+    reason: string
+    attempts: number
+    next_track: YTVideoInfo | null
+}
+
+export type DownloadPriority = 'Normal' | 'High'
 
 export interface DownloadProgress {
     video_id: string
@@ -47,7 +345,175 @@ export interface DownloadProgress {
     eta: string
     file_size: string
     is_completed: boolean
-    error: string | null
+    priority: DownloadPriority
+    throttled: boolean // rate-limited while playback streams
+}
+
+export type DownloadErrorKind = 'Network' | 'Unavailable' | 'Storage' | 'Other'
+
+export interface FailedDownload {
+    track: YTVideoInfo
+    quality: string | null
+    error: string
+    kind: DownloadErrorKind
+    failed_at: number
+}
+
+export type DataScope = 'SearchCache' | 'Thumbnails' | 'Downloads' | 'Database' | 'Everything'
+
+export interface DataUsageBreakdown {
+    search_cache: number
+    thumbnails: number
+    downloads: number
+    database: number
+}
+
+export type OnboardingStep = 'InstallYtdlp' | 'CheckFfmpeg' | 'ChooseDownloadsDir' | 'CheckPermissions'
+
+export interface OnboardingStepStatus {
+    step: OnboardingStep
+    done: boolean
+    detail: string | null
+}
+
+export interface OnboardingStatus {
+    steps: OnboardingStepStatus[]
+    current_step: OnboardingStep | null
+    is_complete: boolean
+}
+
+export type TelemetryEvent = 'Search' | 'Download' | 'Crash'
+
+export interface TelemetryBatch {
+    app_version: string
+    os: string
+    since: number
+    until: number
+    counts: Partial<Record<TelemetryEvent, number>>
+}
+
+export interface TelemetryStatus {
+    enabled: boolean
+    endpoint: string | null
+    preview: TelemetryBatch
+}
+
+export interface AppUpdate {
+    version: string
+    current_version: string
+    notes: string | null
+    published_at: number | null
+}
+
+export interface AppUpdateProgress {
+    downloaded: number
+    total: number | null
+}
+
+export type StorageProblemKind = 'DiskFull' | 'ReadOnly'
+
+export interface StorageProblem {
+    kind: StorageProblemKind
+    path: string
+    cause: string
+    hint: string
+}
+
+export interface SystemTheme {
+    dark: boolean
+    high_contrast: boolean
+}
+
+export interface CooldownStatus {
+    cooling_down: boolean
+    until: number | null // unix seconds
+}
+
+export interface MaintenanceReport {
+    integrity_ok: boolean
+    problems: string[]
+    bytes_reclaimed: number
+    completed_at: number
+}
+
+export interface MaintenanceProgress {
+    step: 'integrity_check' | 'reindex' | 'vacuum' | 'done'
+    progress: number
+}
+
+export interface DownloadsChange {
+    added: string[]
+    removed: string[]
+}
+
+export interface RelinkReport {
+    relinked: string[]
+    unmatched: number
+}
+
+export interface BatchProgress {
+    total: number
+    completed: number
+    failed: number
+    progress: number
+}
+
+export interface CleanupReport {
+    metadata_removed: number
+    partial_removed: number
+    bytes_reclaimed: number
+}
+
+export interface CaptionLine {
+    start: number
+    end: number
+    text: string
+}
+
+export type CookieSource = { Browser: string } | { File: string }
+
+export type YtdlpChannel = 'Stable' | 'Nightly' | 'Master'
+
+export interface YtdlpRelease {
+    channel: YtdlpChannel
+    version: string | null // pinned release tag, e.g. "2024.08.06"; null follows the latest
+}
+
+export interface YtdlpVersions {
+    release: YtdlpRelease
+    installed: string | null
+    previous: string | null // version a rollback would restore
+}
+
+export interface YtdlpOptions {
+    user_agent: string | null
+    player_client: string | null // e.g. "web,mweb"
+    po_token: string | null // e.g. "web.gvs+TOKEN"
+    // Extra arguments appended to one kind of invocation, e.g. "--geo-bypass --sleep-requests 1"
+    search_args: string | null
+    stream_args: string | null
+    download_args: string | null
+}
+
+export type YouTubeLibrary = 'Liked' | 'WatchLater'
+
+export interface LibraryImport {
+    library: YouTubeLibrary
+    playlist_id: string
+    added: number
+    total: number
+}
+
+export interface RecordingDuplicate {
+    video_id: string
+    title: string
+    original_id: string
+    original_title: string
+}
+
+export interface FingerprintStatus {
+    enabled: boolean
+    available: boolean
 }
 
 export interface DownloadedTrack {
@@ -55,6 +521,8 @@ export interface DownloadedTrack {
     file_path: string
     file_size: number
     download_date: number
+    actual_duration: number | null
+    is_truncated: boolean
 }
 
 // ===== TAURI COMMANDS =====
@@ -67,48 +535,217 @@ export const searchYoutube = (query: string, musicMode: boolean) =>
 export const checkYtdlpInstalled = () => invoke<boolean>('check_ytdlp_installed')
 export const installYtdlp = () => invoke<void>('install_ytdlp')
 export const getYtdlpVersion = () => invoke<string>('get_ytdlp_version')
+export const getYtdlpVersions = () => invoke<YtdlpVersions>('get_ytdlp_versions')
+export const setYtdlpRelease = (release: YtdlpRelease) => invoke<YtdlpVersions>('set_ytdlp_release', { release })
+export const rollbackYtdlp = () => invoke<YtdlpVersions>('rollback_ytdlp')
+export const getOnboardingStatus = () =>
+    invoke<OnboardingStatus>('get_onboarding_status')
+export const completeOnboardingStep = (step: OnboardingStep) =>
+    invoke<OnboardingStatus>('complete_onboarding_step', { step })
 
 // Playback
 export const playTrack = (track: YTVideoInfo) => invoke<void>('play_track', { track })
+export const warmTrack = (videoId: string) => invoke<void>('warm_track', { videoId })
+export const playFromClipboard = () => invoke<YTVideoInfo>('play_from_clipboard')
+export const executePlayerAction = (action: string) => invoke<void>('execute_player_action', { action })
+export const playUrlList = (text: string) => invoke<YTVideoInfo>('play_url_list', { text })
+export const copyQueueToClipboard = (format: ListFormat, playlistId: string | null = null) =>
+    invoke<string>('copy_queue_to_clipboard', { format, playlistId })
+export const copyCurrentTrackUrl = () => invoke<string>('copy_current_track_url')
+export const getShareLink = (withTimestamp: boolean) => invoke<string>('get_share_link', { withTimestamp })
 export const togglePlayPause = () => invoke<void>('toggle_play_pause')
 export const pausePlayback = () => invoke<void>('pause_playback')
 export const stopPlayback = () => invoke<void>('stop_playback')
+export const unloadPlayback = () => invoke<void>('unload_playback')
 export const seekTo = (position: number) => invoke<void>('seek_to', { position })
+export const replay = (seconds?: number) => invoke<void>('replay', { seconds })
 export const setVolume = (volume: number) => invoke<void>('set_volume', { volume })
+export const setMaxVolumeGain = (maxGain: number) => invoke<void>('set_max_volume_gain', { maxGain })
+export const setMaxVolume = (limit: number) => invoke<void>('set_max_volume', { limit })
+export const setLimiter = (limiter: LimiterSettings) => invoke<void>('set_limiter', { limiter })
+export const setBufferTarget = (seconds: number) => invoke<void>('set_buffer_target', { seconds })
+export const generateDiagnosticsBundle = (path: string) =>
+    invoke<void>('generate_diagnostics_bundle', { path })
+export const exportProfile = (path: string, includeDownloads: boolean) =>
+    invoke<void>('export_profile', { path, includeDownloads })
+export const importProfile = (path: string, restoreDownloads: boolean) =>
+    invoke<ProfileImport>('import_profile', { path, restoreDownloads })
+export const getDominantColors = (videoId: string) => invoke<string[]>('get_dominant_colors', { videoId })
+export const getAudioCapabilities = () => invoke<AudioCapabilities>('get_audio_capabilities')
+export const getPreferBuiltinDecoder = () => invoke<boolean>('get_prefer_builtin_decoder')
+export const setPreferBuiltinDecoder = (enabled: boolean) => invoke<void>('set_prefer_builtin_decoder', { enabled })
+export const getKeepStreamedAudio = () => invoke<boolean>('get_keep_streamed_audio')
+export const setKeepStreamedAudio = (enabled: boolean) => invoke<void>('set_keep_streamed_audio', { enabled })
+export const setSleepTimer = (minutes: number) => invoke<ScheduledTimer>('set_sleep_timer', { minutes })
+export const setStopAt = (time: string) => invoke<ScheduledTimer>('set_stop_at', { time })
+export const cancelTimer = (id: string) => invoke<void>('cancel_timer', { id })
+export const getTimers = () => invoke<ScheduledTimer[]>('get_timers')
+export const getQuietHours = () => invoke<QuietHours>('get_quiet_hours')
+export const setQuietHours = (quietHours: QuietHours) => invoke<void>('set_quiet_hours', { quietHours })
+export const getBlockedKeywords = () => invoke<string[]>('get_blocked_keywords')
+export const setBlockedKeywords = (keywords: string[]) => invoke<void>('set_blocked_keywords', { keywords })
+export const getDataSaverMode = () => invoke<DataSaverMode>('get_data_saver_mode')
+export const setDataSaverMode = (mode: DataSaverMode) => invoke<void>('set_data_saver_mode', { mode })
+export const isConnectionMetered = () => invoke<boolean>('is_connection_metered')
 export const setPlaybackSpeed = (rate: number) => invoke<void>('set_playback_speed', { rate })
+export const getTrackAnnouncements = () => invoke<boolean>('get_track_announcements')
+export const setTrackAnnouncements = (enabled: boolean) => invoke<void>('set_track_announcements', { enabled })
+export const getListeningLog = () => invoke<boolean>('get_listening_log')
+export const setListeningLog = (enabled: boolean) => invoke<void>('set_listening_log', { enabled })
+export const getJingleSettings = () => invoke<JingleSettings>('get_jingle_settings')
+export const setJingleSettings = (settings: JingleSettings) => invoke<void>('set_jingle_settings', { settings })
+export const getVolumeKeys = () => invoke<VolumeKeySettings>('get_volume_keys')
+export const setVolumeKeys = (settings: VolumeKeySettings) => invoke<void>('set_volume_keys', { settings })
+export const getNowPlayingOutput = () => invoke<NowPlayingOutput>('get_now_playing_output')
+export const setNowPlayingOutput = (output: NowPlayingOutput) => invoke<void>('set_now_playing_output', { output })
+export const getDownloadNotifications = () => invoke<boolean>('get_download_notifications')
+export const setDownloadNotifications = (enabled: boolean) => invoke<void>('set_download_notifications', { enabled })
+export const getFingerprinting = () => invoke<FingerprintStatus>('get_fingerprinting')
+export const setFingerprinting = (enabled: boolean) => invoke<void>('set_fingerprinting', { enabled })
+export const fingerprintDownloads = () => invoke<RecordingDuplicate[]>('fingerprint_downloads')
+export const getRecordingDuplicates = () => invoke<RecordingDuplicate[]>('get_recording_duplicates')
+export const getNotificationPolicy = () => invoke<NotificationPolicy>('get_notification_policy')
+export const setNotificationPolicy = (policy: NotificationPolicy) => invoke<void>('set_notification_policy', { policy })
+export const getPartyMode = () => invoke<PartyModeStatus>('get_party_mode')
+export const enablePartyMode = (pin: string) => invoke<PartyModeStatus>('enable_party_mode', { pin })
+export const disablePartyMode = (pin: string) => invoke<PartyModeStatus>('disable_party_mode', { pin })
+export const getGuestQueue = () => invoke<GuestQueueStatus>('get_guest_queue')
+export const setGuestQueue = (settings: GuestQueueSettings) => invoke<GuestQueueStatus>('set_guest_queue', { settings })
+export const regenerateGuestToken = () => invoke<GuestQueueStatus>('regenerate_guest_token')
+export const getGuestRequests = () => invoke<GuestRequest[]>('get_guest_requests')
+export const approveGuestRequest = (id: string) => invoke<void>('approve_guest_request', { id })
+export const rejectGuestRequest = (id: string) => invoke<void>('reject_guest_request', { id })
+export const getSpeedPresets = () => invoke<SpeedSettings>('get_speed_presets')
+export const setSpeedPresets = (settings: SpeedSettings) => invoke<void>('set_speed_presets', { settings })
+export const setAudioEffects = (effects: AudioEffects) => invoke<void>('set_audio_effects', { effects })
+export const listOutputDevices = () => invoke<string[]>('list_output_devices')
+export const setOutputDevice = (device: string | null) => invoke<void>('set_output_device', { device })
+export const getAudioProfiles = () => invoke<Record<string, AudioProfile>>('get_audio_profiles')
+export const saveAudioProfile = (device: string, profile: AudioProfile) =>
+    invoke<void>('save_audio_profile', { device, profile })
+export const deleteAudioProfile = (device: string) => invoke<void>('delete_audio_profile', { device })
 export const playNext = () => invoke<YTVideoInfo | null>('play_next')
 export const playPrevious = () => invoke<YTVideoInfo | null>('play_previous')
 export const getAudioState = () => invoke<AudioState>('get_audio_state')
+export const getPlayerEvents = () => invoke<PlayerEvent[]>('get_player_events')
 
 // Queue
 export const addToQueue = (track: YTVideoInfo) => invoke<void>('add_to_queue', { track })
 export const getQueue = () => invoke<YTVideoInfo[]>('get_queue')
+export const filterQueue = (query: string) => invoke<QueueMatch[]>('filter_queue', { query })
 export const clearQueue = () => invoke<void>('clear_queue')
 export const toggleShuffle = () => invoke<boolean>('toggle_shuffle')
+export const getTrackTrim = (videoId: string) => invoke<TrackTrim | null>('get_track_trim', { videoId })
+export const setTrackTrim = (videoId: string, start: number, end: number | null) =>
+    invoke<void>('set_track_trim', { videoId, start, end })
+export const acceptIntroSkip = (videoId: string, start: number) =>
+    invoke<void>('accept_intro_skip', { videoId, start })
+export const dismissIntroSkip = (videoId: string) => invoke<void>('dismiss_intro_skip', { videoId })
 export const cycleRepeatMode = () => invoke<RepeatMode>('cycle_repeat_mode')
+export const setRepeatCount = (count: number) => invoke<RepeatMode>('set_repeat_count', { count })
 export const getQueueInfo = () => invoke<string>('get_queue_info')
 export const reorderQueue = (newQueue: YTVideoInfo[]) => invoke<void>('reorder_queue', { newQueue })
+export const getEndOfQueueAction = () => invoke<EndOfQueueAction>('get_end_of_queue_action')
+export const setEndOfQueueAction = (action: EndOfQueueAction) =>
+    invoke<void>('set_end_of_queue_action', { action })
 
 // Playlists
 export const getAllPlaylists = () => invoke<Playlist[]>('get_all_playlists')
 export const createPlaylist = (name: string) => invoke<string>('create_playlist', { name })
 export const deletePlaylist = (id: string) => invoke<void>('delete_playlist', { id })
+export const duplicatePlaylist = (id: string, newName: string) =>
+    invoke<string>('duplicate_playlist', { id, newName })
+export const mergePlaylists = (sourceIds: string[], targetId: string, dedupe: boolean) =>
+    invoke<void>('merge_playlists', { sourceIds, targetId, dedupe })
 export const getPlaylistTracks = (playlistId: string) =>
     invoke<Track[]>('get_playlist_tracks', { playlistId })
+export const filterPlaylistTracks = (playlistId: string, query: string) =>
+    invoke<Track[]>('filter_playlist_tracks', { playlistId, query })
+export const getPlaylistSummary = (playlistId: string) =>
+    invoke<PlaylistSummary>('get_playlist_summary', { playlistId })
+export const prefetchMetadata = (trackIds: string[], priority: PrefetchPriority) =>
+    invoke<void>('prefetch_metadata', { trackIds, priority })
+export const cancelMetadataPrefetch = () => invoke<void>('cancel_metadata_prefetch')
 export const addTrackToPlaylist = (track: YTVideoInfo, playlistId: string) =>
     invoke<void>('add_track_to_playlist', { track, playlistId })
 export const removeTrackFromPlaylist = (trackId: string, playlistId: string) =>
     invoke<void>('remove_track_from_playlist', { trackId, playlistId })
+export const addTracksToPlaylist = (trackIds: string[], playlistId: string) =>
+    invoke<void>('add_tracks_to_playlist', { trackIds, playlistId })
+export const removeTracksFromPlaylist = (trackIds: string[], playlistId: string) =>
+    invoke<void>('remove_tracks_from_playlist', { trackIds, playlistId })
+export const getTrackPlaylists = (trackId: string) =>
+    invoke<string[]>('get_track_playlists', { trackId })
+export const setTrackPlaylists = (track: YTVideoInfo, playlistIds: string[]) =>
+    invoke<void>('set_track_playlists', { track, playlistIds })
+// Saves the track first if it isn't yet; null clears an override
+export const updateTrackDisplay = (
+    track: YTVideoInfo,
+    title: string | null,
+    artist: string | null,
+    note: string | null
+) => invoke<void>('update_track_display', { track, title, artist, note })
+export const setTrackContentType = (trackId: string, contentType: ContentType | null) =>
+    invoke<void>('set_track_content_type', { trackId, contentType })
+export const getTracksByContentType = (contentType: ContentType) =>
+    invoke<Track[]>('get_tracks_by_content_type', { contentType })
 export const addToFavorites = (track: YTVideoInfo) =>
     invoke<void>('add_to_favorites', { track })
 export const removeFromFavorites = (trackId: string) =>
     invoke<void>('remove_from_favorites', { trackId })
 export const playPlaylist = (playlistId: string) =>
     invoke<void>('play_playlist', { playlistId })
+export const playTrackInContext = (trackId: string, playlistId: string) =>
+    invoke<void>('play_track_in_context', { trackId, playlistId })
+export const getPinnedPlaylists = () =>
+    invoke<string[]>('get_pinned_playlists')
+export const setPlaylistPinned = (playlistId: string, pinned: boolean) =>
+    invoke<void>('set_playlist_pinned', { playlistId, pinned })
+export const playAllShuffled = (scope: ShuffleScope) =>
+    invoke<void>('play_all_shuffled', { scope })
+export const getMostSkipped = (limit?: number) =>
+    invoke<TrackSkipStats[]>('get_most_skipped', { limit: limit ?? null })
+export const generateWeeklyRecap = () => invoke<WeeklyRecap>('generate_weekly_recap')
+export const getWeeklyRecaps = () => invoke<WeeklyRecap[]>('get_weekly_recaps')
+export const generateDailyMixes = () =>
+    invoke<string[]>('generate_daily_mixes')
+export const startArtistRadio = (uploaderOrChannel: string) =>
+    invoke<YTVideoInfo>('start_artist_radio', { uploaderOrChannel })
+export const getSavedSession = () =>
+    invoke<SavedSession | null>('get_saved_session')
+export const restoreSession = (mode: SessionRestoreMode) =>
+    invoke<YTVideoInfo | null>('restore_session', { mode })
+export const getSessions = () =>
+    invoke<QueueSessionSummary[]>('get_sessions')
+export const saveSession = (name: string) =>
+    invoke<void>('save_session', { name })
+export const loadSession = (name: string) =>
+    invoke<YTVideoInfo | null>('load_session', { name })
+export const deleteSession = (name: string) =>
+    invoke<void>('delete_session', { name })
+
+// YouTube library import
+export const getCookieSource = () =>
+    invoke<CookieSource | null>('get_cookie_source')
+export const setCookieSource = (source: CookieSource | null) =>
+    invoke<void>('set_cookie_source', { source })
+export const getYtdlpOptions = () =>
+    invoke<YtdlpOptions>('get_ytdlp_options')
+export const setYtdlpOptions = (options: YtdlpOptions) =>
+    invoke<void>('set_ytdlp_options', { options })
+export const importYoutubeLibrary = (library: YouTubeLibrary) =>
+    invoke<LibraryImport>('import_youtube_library', { library })
+export const getLibrarySync = () =>
+    invoke<YouTubeLibrary[]>('get_library_sync')
+export const setLibrarySync = (libraries: YouTubeLibrary[]) =>
+    invoke<void>('set_library_sync', { libraries })
 
 // Downloads
-export const downloadTrack = (track: YTVideoInfo) =>
-    invoke<void>('download_track', { track })
+// quality overrides the default audio quality for this download only
+export const downloadTrack = (track: YTVideoInfo, quality?: string) =>
+    invoke<void>('download_track', { track, quality: quality ?? null })
+export const downloadTracks = (tracks: YTVideoInfo[], quality?: string) =>
+    invoke<number>('download_tracks', { tracks, quality: quality ?? null })
 export const getActiveDownloads = () =>
     invoke<DownloadProgress[]>('get_active_downloads')
 export const getDownloadedTracks = () =>
@@ -119,20 +756,100 @@ export const isTrackDownloaded = (videoId: string) =>
     invoke<boolean>('is_track_downloaded', { videoId })
 export const deleteDownload = (videoId: string) =>
     invoke<void>('delete_download', { videoId })
+export const deleteDownloads = (videoIds: string[]) =>
+    invoke<number>('delete_downloads', { videoIds })
 export const cancelDownload = (videoId: string) =>
     invoke<void>('cancel_download', { videoId })
+export const setDownloadPriority = (videoId: string, priority: DownloadPriority) =>
+    invoke<void>('set_download_priority', { videoId, priority })
+export const getFailedDownloads = () =>
+    invoke<FailedDownload[]>('get_failed_downloads')
+export const retryAllFailed = () =>
+    invoke<number>('retry_all_failed')
+export const verifyDownloads = () =>
+    invoke<string[]>('verify_downloads')
+export const redownloadTrack = (videoId: string) =>
+    invoke<void>('redownload_track', { videoId })
+export const searchInTrack = (videoId: string, text: string) =>
+    invoke<CaptionLine[]>('search_in_track', { videoId, text })
+
+// Bookmarks
+export const addBookmark = (videoId: string, position: number, note: string | null) =>
+    invoke<Bookmark>('add_bookmark', { videoId, position, note })
+export const getBookmarks = (videoId: string) =>
+    invoke<Bookmark[]>('get_bookmarks', { videoId })
+export const deleteBookmark = (id: string) =>
+    invoke<void>('delete_bookmark', { id })
+export const playBookmark = (id: string) =>
+    invoke<void>('play_bookmark', { id })
+
+// Channel rules
+export const getChannelRules = () => invoke<ChannelRule[]>('get_channel_rules')
+export const getChannelRule = (uploader: string) =>
+    invoke<ChannelRule | null>('get_channel_rule', { uploader })
+export const saveChannelRule = (rule: ChannelRule) =>
+    invoke<void>('save_channel_rule', { rule })
+export const deleteChannelRule = (uploader: string) =>
+    invoke<void>('delete_channel_rule', { uploader })
+
+// Undo
+export const undoLastAction = () => invoke<string | null>('undo_last_action')
 
 // Settings
 export const getDownloadsDirectory = () =>
     invoke<string>('get_downloads_directory')
 export const setDownloadsDirectory = (path: string) =>
     invoke<void>('set_downloads_directory', { path })
+export const getDownloadsWatcherEnabled = () =>
+    invoke<boolean>('get_downloads_watcher_enabled')
+export const setDownloadsWatcherEnabled = (enabled: boolean) =>
+    invoke<void>('set_downloads_watcher_enabled', { enabled })
+export const reconcileDownloads = () =>
+    invoke<DownloadsChange>('reconcile_downloads')
+export const relinkDownloads = (folder: string) =>
+    invoke<RelinkReport>('relink_downloads', { folder })
+export const cleanUpDownloads = () =>
+    invoke<CleanupReport>('clean_up_downloads')
 export const getAudioQuality = () =>
     invoke<string>('get_audio_quality')
 export const setAudioQuality = (quality: string) =>
     invoke<void>('set_audio_quality', { quality })
+export const getDataUsageBreakdown = () =>
+    invoke<DataUsageBreakdown>('get_data_usage_breakdown')
+export const resetAppData = (scope: DataScope) =>
+    invoke<void>('reset_app_data', { scope })
+export const isWindowMode = () =>
+    invoke<boolean>('is_window_mode')
+export const getMinimizeToBackground = () =>
+    invoke<boolean>('get_minimize_to_background')
+export const setMinimizeToBackground = (enabled: boolean) =>
+    invoke<void>('set_minimize_to_background', { enabled })
+export const getYoutubeCooldown = () =>
+    invoke<CooldownStatus>('get_youtube_cooldown')
+export const getSystemTheme = () =>
+    invoke<SystemTheme>('get_system_theme')
+export const getStorageProblem = () =>
+    invoke<StorageProblem | null>('get_storage_problem')
+export const runDbMaintenance = () =>
+    invoke<MaintenanceReport>('run_db_maintenance')
+export const getTelemetryStatus = () =>
+    invoke<TelemetryStatus>('get_telemetry_status')
+export const setTelemetryEnabled = (enabled: boolean) =>
+    invoke<void>('set_telemetry_enabled', { enabled })
+export const setTelemetryEndpoint = (endpoint: string) =>
+    invoke<void>('set_telemetry_endpoint', { endpoint })
 export const getAppVersion = () =>
     invoke<string>('get_app_version')
+export const checkAppUpdate = () =>
+    invoke<AppUpdate | null>('check_app_update')
+export const downloadAppUpdate = () =>
+    invoke<void>('download_app_update')
+export const installAppUpdate = () =>
+    invoke<void>('install_app_update')
+export const getAutoCheckUpdates = () =>
+    invoke<boolean>('get_auto_check_updates')
+export const setAutoCheckUpdates = (enabled: boolean) =>
+    invoke<void>('set_auto_check_updates', { enabled })
 
 // Media Keys
 export const updateMediaMetadata = (title: string, artist: string, duration: number) =>
@@ -151,12 +868,134 @@ export const listenToPlaybackState = (
     })
 }
 
-export const listenToDownloadsUpdate = (callback: () => void) => {
-    return listen('downloads-updated', () => {
-        callback()
+export const listenToIntroSkipSuggested = (callback: (suggestion: IntroSkipSuggestion) => void) => {
+    return listen<IntroSkipSuggestion>('intro-skip-suggested', (event) => callback(event.payload))
+}
+
+export const listenToPlaybackError = (callback: (error: PlaybackError) => void) => {
+    return listen<PlaybackError>('playback-error', (event) => callback(event.payload))
+}
+
+export const listenToPlayerEvent = (callback: (event: PlayerEvent) => void) => {
+    return listen<PlayerEvent>('player-event', (event) => callback(event.payload))
+}
+
+export const listenToPlaylistCoversUpdated = (callback: (playlistIds: string[]) => void) => {
+    return listen<string[]>('playlist-covers-updated', (event) => callback(event.payload))
+}
+
+export const listenToMetadataPrefetched = (callback: (metadata: PrefetchedMetadata) => void) => {
+    return listen<PrefetchedMetadata>('metadata-prefetched', (event) => callback(event.payload))
+}
+
+export const listenToTrackDurationFixed = (callback: (fixed: TrackDuration) => void) => {
+    return listen<TrackDuration>('track-duration-fixed', (event) => callback(event.payload))
+}
+
+export const listenToDownloadsCleaned = (callback: (report: CleanupReport) => void) => {
+    return listen<CleanupReport>('downloads-cleaned', (event) => callback(event.payload))
+}
+
+export const listenToAppUpdateProgress = (callback: (progress: AppUpdateProgress) => void) => {
+    return listen<AppUpdateProgress>('app-update-progress', (event) => callback(event.payload))
+}
+
+export const listenToAppUpdateAvailable = (callback: (update: AppUpdate) => void) => {
+    return listen<AppUpdate>('app-update-available', (event) => callback(event.payload))
+}
+
+export const listenToSystemThemeChanged = (callback: (theme: SystemTheme) => void) => {
+    return listen<SystemTheme>('system-theme-changed', (event) => callback(event.payload))
+}
+
+export const listenToStorageProblem = (callback: (problem: StorageProblem) => void) => {
+    return listen<StorageProblem>('storage-problem', (event) => callback(event.payload))
+}
+
+export const listenToStorageProblemResolved = (callback: () => void) => {
+    return listen('storage-problem-resolved', () => callback())
+}
+
+export const listenToYoutubeCooldown = (callback: (status: CooldownStatus) => void) => {
+    return listen<CooldownStatus>('youtube-cooldown-changed', (event) => callback(event.payload))
+}
+
+export const listenToPartyMode = (callback: (status: PartyModeStatus) => void) => {
+    return listen<PartyModeStatus>('party-mode-changed', (event) => callback(event.payload))
+}
+
+export const listenToGuestRequests = (callback: (requests: GuestRequest[]) => void) => {
+    return listen<GuestRequest[]>('guest-requests-changed', (event) => callback(event.payload))
+}
+
+export const listenToDbMaintenanceProgress = (callback: (progress: MaintenanceProgress) => void) => {
+    return listen<MaintenanceProgress>('db-maintenance-progress', (event) => callback(event.payload))
+}
+
+export const listenToDailyMixesUpdated = (callback: (playlistIds: string[]) => void) => {
+    return listen<string[]>('daily-mixes-updated', (event) => callback(event.payload))
+}
+
+export const listenToWeeklyRecapReady = (callback: (recap: WeeklyRecap) => void) => {
+    return listen<WeeklyRecap>('weekly-recap-ready', (event) => callback(event.payload))
+}
+
+export const listenToTimerFired = (callback: (timer: ScheduledTimer) => void) => {
+    return listen<ScheduledTimer>('timer-fired', (event) => callback(event.payload))
+}
+
+export const listenToNetworkStatus = (callback: (online: boolean) => void) => {
+    return listen<boolean>('network-status-changed', (event) => callback(event.payload))
+}
+
+export const listenToMeteredStatus = (callback: (metered: boolean) => void) => {
+    return listen<boolean>('metered-status-changed', (event) => callback(event.payload))
+}
+
+export const listenToOutputDeviceChanged = (callback: (device: string) => void) => {
+    return listen<string>('output-device-changed', (event) => callback(event.payload))
+}
+
+export const listenToTrackChanged = (callback: (change: TrackChanged) => void) => {
+    return listen<TrackChanged>('track-changed', (event) => callback(event.payload))
+}
+
+export const listenToSettingsChanged = (callback: (change: SettingsChanged) => void) => {
+    return listen<SettingsChanged>('settings-changed', (event) => callback(event.payload))
+}
+
+export const listenToTrackSkipped = (callback: (skipped: SkippedTrack) => void) => {
+    return listen<SkippedTrack>('track-skipped', (event) => callback(event.payload))
+}
+
+export const listenToLibrarySynced = (callback: (result: LibraryImport) => void) => {
+    return listen<LibraryImport>('library-synced', (event) => callback(event.payload))
+}
+
+// Payload is set when the directory watcher detected external changes
+export const listenToRecordingDuplicateFound = (callback: (duplicate: RecordingDuplicate) => void) => {
+    return listen<RecordingDuplicate>('recording-duplicate-found', (event) => callback(event.payload))
+}
+
+export const listenToDownloadsUpdate = (callback: (change: DownloadsChange | null) => void) => {
+    return listen<DownloadsChange | null>('downloads-updated', (event) => {
+        callback(event.payload)
     })
 }
 
+export const listenToBatchProgress = (callback: (progress: BatchProgress) => void) => {
+    return listen<BatchProgress>('download-batch-progress', (event) => callback(event.payload))
+}
+
+export const listenToStorageChanged = (callback: (bytes: number) => void) => {
+    return listen<number>('storage-changed', (event) => callback(event.payload))
+}
+
+// A finished download came out shorter than the track; offer redownloadTrack
+export const listenToDownloadTruncated = (callback: (track: YTVideoInfo) => void) => {
+    return listen<YTVideoInfo>('download-truncated', (event) => callback(event.payload))
+}
+
 export const listenToMediaKeyPlay = (callback: () => void) => {
     return listen('media-key-play', () => callback())
 }
@@ -185,11 +1024,19 @@ export const listenToMediaKeySeekTo = (callback: (position: number) => void) =>
     return listen<number>('media-key-seek-to', (event) => callback(event.payload))
 }
 
+export const listenToMediaKeyReplay = (callback: () => void) => {
+    return listen('media-key-replay', () => callback())
+}
+
 export const listenToMediaKeyStop = (callback: () => void) => {
     return listen('media-key-stop', () => callback())
 }
 
 // ===== UTILITIES =====
+export const displayTitle = (track: YTVideoInfo): string => track.custom_title || track.title
+
+export const displayUploader = (track: YTVideoInfo): string => track.custom_author || track.uploader
+
 export const formatDuration = (seconds: number | null): string => {
     if (!seconds || seconds <= 0) return '0:00'
     const mins = Math.floor(seconds / 60)